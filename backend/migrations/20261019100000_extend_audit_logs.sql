@@ -0,0 +1,12 @@
+-- Link audit entries to the entity they describe so patient history can be rebuilt
+ALTER TABLE audit_logs
+  ADD COLUMN entity_type TEXT,
+  ADD COLUMN entity_id INTEGER,
+  ADD COLUMN patient_id INTEGER,
+  ADD COLUMN details JSONB;
+
+CREATE INDEX idx_audit_logs_patient_created ON audit_logs (patient_id, created_at);
+
+-- Down
+-- DROP INDEX idx_audit_logs_patient_created;
+-- ALTER TABLE audit_logs DROP COLUMN details, DROP COLUMN patient_id, DROP COLUMN entity_id, DROP COLUMN entity_type;
@@ -0,0 +1,19 @@
+-- no-transaction
+CREATE TABLE households (
+  id SERIAL PRIMARY KEY,
+  name TEXT NOT NULL,
+  head_patient_id INTEGER REFERENCES public.patients(id) ON DELETE SET NULL,
+  village TEXT,
+  address JSONB,
+  created_at TIMESTAMP DEFAULT now(),
+  updated_at TIMESTAMP DEFAULT now()
+);
+
+ALTER TABLE patients ADD COLUMN household_id INTEGER REFERENCES public.households(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_households_village ON households (village);
+CREATE INDEX idx_patients_household ON patients (household_id);
+
+-- Down
+-- ALTER TABLE patients DROP COLUMN household_id;
+-- DROP TABLE households;
@@ -0,0 +1,16 @@
+-- no-transaction
+-- Each relationship is stored in both directions: "related_patient_id is the <relationship_type> of patient_id"
+CREATE TABLE patient_relationships (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  related_patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  relationship_type TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT now(),
+  UNIQUE (patient_id, related_patient_id, relationship_type),
+  CHECK (patient_id <> related_patient_id)
+);
+
+CREATE INDEX idx_patient_relationships_related ON patient_relationships (related_patient_id);
+
+-- Down
+-- DROP TABLE patient_relationships;
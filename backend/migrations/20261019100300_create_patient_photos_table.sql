@@ -0,0 +1,17 @@
+-- no-transaction
+-- Current photo per patient; the binary content lives in blob storage under storage_key
+CREATE TABLE patient_photos (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL UNIQUE REFERENCES public.patients(id) ON DELETE CASCADE,
+  storage_key TEXT NOT NULL,
+  thumbnail_key TEXT NOT NULL,
+  content_type TEXT NOT NULL,
+  size_bytes BIGINT NOT NULL,
+  width INTEGER NOT NULL,
+  height INTEGER NOT NULL,
+  original_filename TEXT,
+  created_at TIMESTAMP DEFAULT now()
+);
+
+-- Down
+-- DROP TABLE patient_photos;
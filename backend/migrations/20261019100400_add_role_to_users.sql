@@ -0,0 +1,6 @@
+-- no-transaction
+-- Staff role carried in access tokens: admin, clinician or technician
+ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'clinician';
+
+-- Down
+-- ALTER TABLE users DROP COLUMN role;
@@ -0,0 +1,41 @@
+-- no-transaction
+-- Attachment content is stored once per SHA-256 digest, however many records reference it
+CREATE TABLE attachment_blobs (
+  sha256 TEXT PRIMARY KEY,
+  storage_key TEXT NOT NULL,
+  content_type TEXT NOT NULL,
+  size_bytes BIGINT NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE TABLE record_attachments (
+  id SERIAL PRIMARY KEY,
+  record_id INTEGER NOT NULL REFERENCES public.medical_records(id) ON DELETE CASCADE,
+  sha256 TEXT NOT NULL REFERENCES attachment_blobs(sha256),
+  filename TEXT NOT NULL,
+  content_type TEXT NOT NULL,
+  size_bytes BIGINT NOT NULL,
+  uploaded_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT now(),
+  UNIQUE (record_id, sha256)
+);
+
+CREATE INDEX idx_record_attachments_sha256 ON record_attachments (sha256);
+
+-- In-progress chunked uploads; received bytes are staged on local disk until complete
+CREATE TABLE attachment_uploads (
+  id UUID PRIMARY KEY,
+  record_id INTEGER NOT NULL REFERENCES public.medical_records(id) ON DELETE CASCADE,
+  filename TEXT NOT NULL,
+  total_bytes BIGINT NOT NULL,
+  received_bytes BIGINT NOT NULL DEFAULT 0,
+  uploaded_by INTEGER NOT NULL REFERENCES public.users(id) ON DELETE CASCADE,
+  created_at TIMESTAMP NOT NULL DEFAULT now(),
+  updated_at TIMESTAMP NOT NULL DEFAULT now(),
+  expires_at TIMESTAMP NOT NULL
+);
+
+-- Down
+-- DROP TABLE attachment_uploads;
+-- DROP TABLE record_attachments;
+-- DROP TABLE attachment_blobs;
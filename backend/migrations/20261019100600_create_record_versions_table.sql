@@ -0,0 +1,22 @@
+-- no-transaction
+-- Every state a medical record has been in: a full snapshot plus the field-level diff from the previous version
+CREATE TABLE record_versions (
+  id SERIAL PRIMARY KEY,
+  record_id INTEGER NOT NULL REFERENCES public.medical_records(id) ON DELETE CASCADE,
+  version INTEGER NOT NULL,
+  kind TEXT NOT NULL,
+  reason TEXT,
+  changes JSONB NOT NULL DEFAULT '{}',
+  snapshot JSONB NOT NULL,
+  changed_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT now(),
+  UNIQUE (record_id, version)
+);
+
+-- Existing records start their history at version 1
+INSERT INTO record_versions (record_id, version, kind, snapshot, created_at)
+SELECT r.id, 1, 'created', to_jsonb(r), COALESCE(r.created_at, now())
+FROM medical_records r;
+
+-- Down
+-- DROP TABLE record_versions;
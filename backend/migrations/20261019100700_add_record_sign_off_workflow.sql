@@ -0,0 +1,35 @@
+-- no-transaction
+-- Records move draft -> submitted -> reviewed -> signed; rejection returns them to draft
+ALTER TABLE medical_records
+  ADD COLUMN submitted_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  ADD COLUMN submitted_at TIMESTAMP,
+  ADD COLUMN reviewed_at TIMESTAMP,
+  ADD COLUMN signed_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  ADD COLUMN signed_at TIMESTAMP;
+
+-- Map the free-text statuses used so far onto the workflow. Nobody signed the records marked
+-- finished so far, so they wait on a signer as reviewed records.
+UPDATE medical_records SET status = CASE LOWER(TRIM(status))
+    WHEN 'signed' THEN 'reviewed'
+    WHEN 'completed' THEN 'reviewed'
+    WHEN 'final' THEN 'reviewed'
+    WHEN 'reviewed' THEN 'reviewed'
+    WHEN 'submitted' THEN 'submitted'
+    WHEN 'pending' THEN 'submitted'
+    WHEN 'pending review' THEN 'submitted'
+    ELSE 'draft'
+  END;
+UPDATE medical_records SET submitted_at = COALESCE(updated_at, created_at) WHERE status IN ('submitted', 'reviewed');
+
+ALTER TABLE medical_records
+  ALTER COLUMN status SET DEFAULT 'draft',
+  ADD CONSTRAINT medical_records_status_check CHECK (status IN ('draft', 'submitted', 'reviewed', 'signed'));
+
+-- Reviewer worklists only ever look at records waiting on someone
+CREATE INDEX idx_medical_records_awaiting_sign_off ON medical_records (status, submitted_at)
+  WHERE status IN ('submitted', 'reviewed');
+
+-- Down
+-- DROP INDEX idx_medical_records_awaiting_sign_off;
+-- ALTER TABLE medical_records DROP CONSTRAINT medical_records_status_check;
+-- ALTER TABLE medical_records DROP COLUMN submitted_by, DROP COLUMN submitted_at, DROP COLUMN reviewed_at, DROP COLUMN signed_by, DROP COLUMN signed_at;
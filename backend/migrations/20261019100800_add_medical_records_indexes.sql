@@ -0,0 +1,12 @@
+-- no-transaction
+-- Access paths of GET /records and GET /patients/:id/records: newest first, keyed by (date, id)
+CREATE INDEX idx_medical_records_patient_date ON medical_records (patient_id, date DESC, id DESC);
+CREATE INDEX idx_medical_records_date ON medical_records (date DESC, id DESC);
+CREATE INDEX idx_medical_records_type_date ON medical_records (record_type, date DESC, id DESC);
+CREATE INDEX idx_medical_records_status_date ON medical_records (status, date DESC, id DESC);
+
+-- Down
+-- DROP INDEX idx_medical_records_status_date;
+-- DROP INDEX idx_medical_records_type_date;
+-- DROP INDEX idx_medical_records_date;
+-- DROP INDEX idx_medical_records_patient_date;
@@ -0,0 +1,77 @@
+-- no-transaction
+-- One measurement session (a set of observations taken together) per row
+CREATE TABLE vital_sets (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  record_id INTEGER REFERENCES public.medical_records(id) ON DELETE SET NULL,
+  observed_at TIMESTAMP NOT NULL,
+  recorded_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_vital_sets_patient_observed ON vital_sets (patient_id, observed_at DESC);
+
+-- Values are stored in the kind's canonical UCUM unit
+CREATE TABLE vital_observations (
+  id SERIAL PRIMARY KEY,
+  set_id INTEGER NOT NULL REFERENCES vital_sets(id) ON DELETE CASCADE,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  kind TEXT NOT NULL,
+  value DOUBLE PRECISION NOT NULL,
+  unit TEXT NOT NULL,
+  observed_at TIMESTAMP NOT NULL,
+  derived BOOLEAN NOT NULL DEFAULT false,
+  reference_low DOUBLE PRECISION,
+  reference_high DOUBLE PRECISION,
+  flag TEXT,
+  UNIQUE (set_id, kind)
+);
+
+CREATE INDEX idx_vital_observations_series ON vital_observations (patient_id, kind, observed_at DESC);
+
+-- Normal and critical limits by kind, age band and optionally sex; the most specific row applies
+CREATE TABLE vital_reference_ranges (
+  id SERIAL PRIMARY KEY,
+  kind TEXT NOT NULL,
+  sex TEXT,
+  min_age_months INTEGER NOT NULL DEFAULT 0,
+  max_age_months INTEGER,
+  low DOUBLE PRECISION,
+  high DOUBLE PRECISION,
+  critical_low DOUBLE PRECISION,
+  critical_high DOUBLE PRECISION
+);
+
+-- Age bands: infant <1y, toddler 1-2y, preschool 3-5y, school 6-11y, adolescent 12-17y, adult 18y+
+INSERT INTO vital_reference_ranges (kind, min_age_months, max_age_months, low, high, critical_low, critical_high) VALUES
+  ('heart_rate', 0, 12, 100, 160, 80, 200),
+  ('heart_rate', 12, 36, 90, 150, 70, 180),
+  ('heart_rate', 36, 72, 80, 140, 60, 170),
+  ('heart_rate', 72, 144, 70, 120, 50, 150),
+  ('heart_rate', 144, 216, 60, 100, 45, 140),
+  ('heart_rate', 216, NULL, 60, 100, 40, 130),
+  ('respiratory_rate', 0, 12, 30, 60, 20, 70),
+  ('respiratory_rate', 12, 36, 24, 40, 15, 60),
+  ('respiratory_rate', 36, 72, 22, 34, 15, 50),
+  ('respiratory_rate', 72, 144, 18, 30, 12, 40),
+  ('respiratory_rate', 144, 216, 12, 20, 10, 30),
+  ('respiratory_rate', 216, NULL, 12, 20, 8, 25),
+  ('systolic_bp', 0, 12, 70, 100, 60, 130),
+  ('systolic_bp', 12, 72, 80, 110, 70, 140),
+  ('systolic_bp', 72, 144, 85, 120, 75, 150),
+  ('systolic_bp', 144, 216, 95, 130, 85, 170),
+  ('systolic_bp', 216, NULL, 90, 139, 80, 180),
+  ('diastolic_bp', 0, 12, 35, 65, NULL, 90),
+  ('diastolic_bp', 12, 72, 45, 70, NULL, 95),
+  ('diastolic_bp', 72, 144, 50, 80, NULL, 100),
+  ('diastolic_bp', 144, 216, 55, 85, NULL, 110),
+  ('diastolic_bp', 216, NULL, 60, 89, 40, 120),
+  ('temperature', 0, NULL, 36.1, 37.9, 35.0, 40.0),
+  ('spo2', 0, NULL, 95, NULL, 90, NULL),
+  ('blood_glucose', 0, NULL, 3.9, 7.8, 3.0, 20.0),
+  ('bmi', 216, NULL, 18.5, 24.9, 16.0, 40.0);
+
+-- Down
+-- DROP TABLE vital_reference_ranges;
+-- DROP TABLE vital_observations;
+-- DROP TABLE vital_sets;
@@ -0,0 +1,51 @@
+-- no-transaction
+-- NEWS2 inputs that are not measurements, and the score computed for the set
+ALTER TABLE vital_sets
+  ADD COLUMN supplemental_oxygen BOOLEAN,
+  ADD COLUMN consciousness TEXT,
+  ADD COLUMN news2_score INTEGER,
+  ADD COLUMN news2_risk TEXT,
+  ADD COLUMN news2_missing TEXT[];
+
+CREATE INDEX idx_vital_sets_news2 ON vital_sets (patient_id, observed_at DESC) WHERE news2_score IS NOT NULL;
+
+-- Why a patient is flagged critical, and whether staff or the early warning score set it
+ALTER TABLE patients
+  ADD COLUMN critical_reason TEXT,
+  ADD COLUMN critical_flag_source TEXT;
+
+UPDATE patients SET critical_flag_source = 'manual' WHERE critical_flag;
+
+CREATE TABLE patient_care_team (
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  user_id INTEGER NOT NULL REFERENCES public.users(id) ON DELETE CASCADE,
+  role TEXT,
+  assigned_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  assigned_at TIMESTAMP NOT NULL DEFAULT now(),
+  PRIMARY KEY (patient_id, user_id)
+);
+
+CREATE INDEX idx_patient_care_team_user ON patient_care_team (user_id);
+
+-- One row per recipient; recipient_id is NULL when the patient has no care team
+CREATE TABLE clinical_alerts (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  vital_set_id INTEGER REFERENCES vital_sets(id) ON DELETE CASCADE,
+  recipient_id INTEGER REFERENCES public.users(id) ON DELETE CASCADE,
+  kind TEXT NOT NULL,
+  severity TEXT NOT NULL,
+  message TEXT NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT now(),
+  acknowledged_at TIMESTAMP,
+  acknowledged_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_clinical_alerts_open ON clinical_alerts (recipient_id, created_at DESC) WHERE acknowledged_at IS NULL;
+
+-- Down
+-- DROP TABLE clinical_alerts;
+-- DROP TABLE patient_care_team;
+-- ALTER TABLE patients DROP COLUMN critical_flag_source, DROP COLUMN critical_reason;
+-- ALTER TABLE vital_sets DROP COLUMN news2_missing, DROP COLUMN news2_risk, DROP COLUMN news2_score,
+--   DROP COLUMN consciousness, DROP COLUMN supplemental_oxygen;
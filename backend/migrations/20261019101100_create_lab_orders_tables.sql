@@ -0,0 +1,139 @@
+-- no-transaction
+-- Orderable tests and the analytes each one reports
+CREATE TABLE lab_tests (
+  code TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  specimen_type TEXT NOT NULL
+);
+
+CREATE TABLE lab_analytes (
+  test_code TEXT NOT NULL REFERENCES lab_tests(code) ON DELETE CASCADE,
+  code TEXT NOT NULL,
+  name TEXT NOT NULL,
+  -- numeric analytes are flagged against lab_reference_ranges, text ones against normal_values
+  result_type TEXT NOT NULL DEFAULT 'numeric' CHECK (result_type IN ('numeric', 'text')),
+  unit TEXT,
+  normal_values TEXT[],
+  position INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (test_code, code)
+);
+
+-- Ranges are keyed by analyte code so an analyte shared between tests shares its ranges
+CREATE TABLE lab_reference_ranges (
+  id SERIAL PRIMARY KEY,
+  analyte_code TEXT NOT NULL,
+  sex TEXT,
+  min_age_months INTEGER NOT NULL DEFAULT 0,
+  max_age_months INTEGER,
+  low DOUBLE PRECISION,
+  high DOUBLE PRECISION,
+  critical_low DOUBLE PRECISION,
+  critical_high DOUBLE PRECISION
+);
+
+CREATE INDEX idx_lab_reference_ranges_analyte ON lab_reference_ranges (analyte_code);
+
+CREATE TABLE lab_orders (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  record_id INTEGER REFERENCES public.medical_records(id) ON DELETE SET NULL,
+  facility TEXT NOT NULL,
+  priority TEXT NOT NULL DEFAULT 'routine',
+  specimen_type TEXT NOT NULL,
+  clinical_notes TEXT,
+  status TEXT NOT NULL DEFAULT 'ordered'
+    CHECK (status IN ('ordered', 'collected', 'resulted', 'reviewed', 'cancelled')),
+  ordered_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  ordered_at TIMESTAMP NOT NULL DEFAULT now(),
+  collected_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  collected_at TIMESTAMP,
+  resulted_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  resulted_at TIMESTAMP,
+  reviewed_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  reviewed_at TIMESTAMP,
+  review_note TEXT,
+  cancelled_reason TEXT,
+  updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_lab_orders_patient ON lab_orders (patient_id, ordered_at DESC);
+CREATE INDEX idx_lab_orders_outstanding ON lab_orders (facility, ordered_at)
+  WHERE status IN ('ordered', 'collected', 'resulted');
+
+CREATE TABLE lab_order_tests (
+  order_id INTEGER NOT NULL REFERENCES lab_orders(id) ON DELETE CASCADE,
+  test_code TEXT NOT NULL REFERENCES lab_tests(code),
+  PRIMARY KEY (order_id, test_code)
+);
+
+CREATE TABLE lab_results (
+  id SERIAL PRIMARY KEY,
+  order_id INTEGER NOT NULL REFERENCES lab_orders(id) ON DELETE CASCADE,
+  test_code TEXT NOT NULL,
+  analyte_code TEXT NOT NULL,
+  analyte_name TEXT NOT NULL,
+  value_numeric DOUBLE PRECISION,
+  value_text TEXT,
+  unit TEXT,
+  reference_low DOUBLE PRECISION,
+  reference_high DOUBLE PRECISION,
+  flag TEXT,
+  resulted_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  resulted_at TIMESTAMP NOT NULL DEFAULT now(),
+  UNIQUE (order_id, test_code, analyte_code),
+  FOREIGN KEY (order_id, test_code) REFERENCES lab_order_tests(order_id, test_code) ON DELETE CASCADE
+);
+
+INSERT INTO lab_tests (code, name, specimen_type) VALUES
+  ('cbc', 'Complete blood count', 'blood'),
+  ('hb', 'Haemoglobin', 'blood'),
+  ('fbs', 'Fasting blood sugar', 'blood'),
+  ('rbs', 'Random blood sugar', 'blood'),
+  ('hba1c', 'Glycated haemoglobin (HbA1c)', 'blood'),
+  ('rft', 'Renal function tests', 'blood'),
+  ('malaria_rdt', 'Malaria rapid diagnostic test', 'blood'),
+  ('hiv_rapid', 'HIV rapid test', 'blood'),
+  ('urine_dipstick', 'Urine dipstick', 'urine'),
+  ('sputum_afb', 'Sputum smear for AFB', 'sputum');
+
+INSERT INTO lab_analytes (test_code, code, name, result_type, unit, normal_values, position) VALUES
+  ('cbc', 'hemoglobin', 'Haemoglobin', 'numeric', 'g/dL', NULL, 1),
+  ('cbc', 'wbc', 'White cell count', 'numeric', '10*9/L', NULL, 2),
+  ('cbc', 'platelets', 'Platelet count', 'numeric', '10*9/L', NULL, 3),
+  ('hb', 'hemoglobin', 'Haemoglobin', 'numeric', 'g/dL', NULL, 1),
+  ('fbs', 'glucose_fasting', 'Glucose (fasting)', 'numeric', 'mmol/L', NULL, 1),
+  ('rbs', 'glucose_random', 'Glucose (random)', 'numeric', 'mmol/L', NULL, 1),
+  ('hba1c', 'hba1c', 'HbA1c', 'numeric', '%', NULL, 1),
+  ('rft', 'creatinine', 'Creatinine', 'numeric', 'umol/L', NULL, 1),
+  ('rft', 'urea', 'Urea', 'numeric', 'mmol/L', NULL, 2),
+  ('malaria_rdt', 'malaria_antigen', 'Malaria antigen', 'text', NULL, '{negative}', 1),
+  ('hiv_rapid', 'hiv_antibody', 'HIV-1/2 antibody', 'text', NULL, '{non-reactive,negative}', 1),
+  ('urine_dipstick', 'urine_protein', 'Protein', 'text', NULL, '{negative,nil,trace}', 1),
+  ('urine_dipstick', 'urine_glucose', 'Glucose', 'text', NULL, '{negative,nil}', 2),
+  ('sputum_afb', 'afb', 'Acid-fast bacilli', 'text', NULL, '{negative,not seen}', 1);
+
+-- Age bands in months as for vital_reference_ranges; haemoglobin and creatinine differ by sex in adults
+INSERT INTO lab_reference_ranges (analyte_code, sex, min_age_months, max_age_months, low, high, critical_low, critical_high) VALUES
+  ('hemoglobin', NULL, 6, 60, 11.0, 14.0, 7.0, 20.0),
+  ('hemoglobin', NULL, 60, 144, 11.5, 15.5, 7.0, 20.0),
+  ('hemoglobin', NULL, 144, 216, 12.0, 16.0, 7.0, 20.0),
+  ('hemoglobin', 'female', 216, NULL, 12.0, 15.5, 7.0, 20.0),
+  ('hemoglobin', 'male', 216, NULL, 13.0, 17.0, 7.0, 20.0),
+  ('wbc', NULL, 0, 216, 5.0, 15.0, 2.0, 30.0),
+  ('wbc', NULL, 216, NULL, 4.0, 11.0, 2.0, 30.0),
+  ('platelets', NULL, 0, NULL, 150, 450, 50, 1000),
+  ('glucose_fasting', NULL, 0, NULL, 3.9, 5.5, 2.8, 25.0),
+  ('glucose_random', NULL, 0, NULL, 3.9, 7.8, 2.8, 25.0),
+  ('hba1c', NULL, 0, NULL, 4.0, 5.6, NULL, 14.0),
+  ('creatinine', NULL, 0, 216, 20, 80, NULL, 300),
+  ('creatinine', 'female', 216, NULL, 44, 80, NULL, 500),
+  ('creatinine', 'male', 216, NULL, 62, 106, NULL, 500),
+  ('urea', NULL, 0, NULL, 2.5, 7.8, NULL, 30.0);
+
+-- Down
+-- DROP TABLE lab_results;
+-- DROP TABLE lab_order_tests;
+-- DROP TABLE lab_orders;
+-- DROP TABLE lab_reference_ranges;
+-- DROP TABLE lab_analytes;
+-- DROP TABLE lab_tests;
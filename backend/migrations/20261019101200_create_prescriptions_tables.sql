@@ -0,0 +1,120 @@
+-- no-transaction
+-- Local drug/allergen-class table used for allergy checks when prescribing
+CREATE TABLE allergen_classes (
+  code TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  -- other spellings patients' allergy lists use for the class, in lowercase
+  aliases TEXT[] NOT NULL DEFAULT '{}',
+  -- classes with known cross-sensitivity: a warning that can be overridden, not a hard stop
+  cross_reactive_with TEXT[] NOT NULL DEFAULT '{}'
+);
+
+CREATE TABLE drugs (
+  name TEXT PRIMARY KEY,
+  -- brand and alternative names, in lowercase
+  synonyms TEXT[] NOT NULL DEFAULT '{}',
+  allergen_class TEXT REFERENCES allergen_classes(code),
+  default_route TEXT
+);
+
+CREATE INDEX idx_drugs_synonyms ON drugs USING GIN (synonyms);
+
+CREATE TABLE prescriptions (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  record_id INTEGER REFERENCES public.medical_records(id) ON DELETE SET NULL,
+  -- NULL when the drug is not in the local table
+  drug TEXT REFERENCES drugs(name),
+  drug_name TEXT NOT NULL,
+  dose TEXT NOT NULL,
+  route TEXT NOT NULL,
+  frequency TEXT NOT NULL,
+  -- NULL for long-term medication
+  duration_days INTEGER CHECK (duration_days > 0),
+  start_date DATE NOT NULL,
+  end_date DATE,
+  instructions TEXT,
+  status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'stopped')),
+  prescribed_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  prescribed_at TIMESTAMP NOT NULL DEFAULT now(),
+  -- overridable warnings the prescriber accepted, with their reason
+  overridden_warnings JSONB,
+  override_reason TEXT,
+  stopped_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  stopped_at TIMESTAMP,
+  stop_reason TEXT
+);
+
+CREATE INDEX idx_prescriptions_patient ON prescriptions (patient_id, start_date DESC);
+
+INSERT INTO allergen_classes (code, name, aliases, cross_reactive_with) VALUES
+  ('penicillin', 'Penicillins', '{penicillins,pcn}', '{cephalosporin,carbapenem}'),
+  ('cephalosporin', 'Cephalosporins', '{cephalosporins}', '{penicillin,carbapenem}'),
+  ('carbapenem', 'Carbapenems', '{carbapenems}', '{penicillin,cephalosporin}'),
+  ('sulfonamide', 'Sulfonamide antibiotics', '{sulfa,sulpha,sulfonamides,sulpha drugs,sulfa drugs}', '{}'),
+  ('nsaid', 'NSAIDs', '{nsaids,anti-inflammatories,painkillers (nsaid)}', '{}'),
+  ('macrolide', 'Macrolides', '{macrolides}', '{}'),
+  ('fluoroquinolone', 'Fluoroquinolones', '{fluoroquinolones,quinolones}', '{}'),
+  ('tetracycline', 'Tetracyclines', '{tetracyclines}', '{}'),
+  ('aminoglycoside', 'Aminoglycosides', '{aminoglycosides}', '{}'),
+  ('opioid', 'Opioids', '{opioids,opiates}', '{}'),
+  ('ace_inhibitor', 'ACE inhibitors', '{ace inhibitors,ace-i,acei}', '{}'),
+  ('statin', 'Statins', '{statins}', '{}'),
+  ('aromatic_anticonvulsant', 'Aromatic anticonvulsants', '{anticonvulsants}', '{}');
+
+INSERT INTO drugs (name, synonyms, allergen_class, default_route) VALUES
+  ('amoxicillin', '{amoxycillin,mox,novamox}', 'penicillin', 'oral'),
+  ('amoxicillin-clavulanate', '{co-amoxiclav,augmentin,amoxiclav}', 'penicillin', 'oral'),
+  ('ampicillin', '{}', 'penicillin', 'iv'),
+  ('benzathine penicillin', '{benzathine benzylpenicillin,penidure}', 'penicillin', 'im'),
+  ('phenoxymethylpenicillin', '{penicillin v}', 'penicillin', 'oral'),
+  ('cloxacillin', '{}', 'penicillin', 'oral'),
+  ('cefalexin', '{cephalexin}', 'cephalosporin', 'oral'),
+  ('cefixime', '{taxim-o}', 'cephalosporin', 'oral'),
+  ('ceftriaxone', '{monocef}', 'cephalosporin', 'iv'),
+  ('cefuroxime', '{}', 'cephalosporin', 'oral'),
+  ('meropenem', '{}', 'carbapenem', 'iv'),
+  ('cotrimoxazole', '{co-trimoxazole,trimethoprim-sulfamethoxazole,septran,bactrim}', 'sulfonamide', 'oral'),
+  ('sulfadiazine', '{}', 'sulfonamide', 'oral'),
+  ('ibuprofen', '{brufen}', 'nsaid', 'oral'),
+  ('diclofenac', '{voveran}', 'nsaid', 'oral'),
+  ('aspirin', '{acetylsalicylic acid,ecosprin,disprin}', 'nsaid', 'oral'),
+  ('naproxen', '{}', 'nsaid', 'oral'),
+  ('azithromycin', '{azithral,azee}', 'macrolide', 'oral'),
+  ('erythromycin', '{}', 'macrolide', 'oral'),
+  ('clarithromycin', '{}', 'macrolide', 'oral'),
+  ('ciprofloxacin', '{ciplox}', 'fluoroquinolone', 'oral'),
+  ('levofloxacin', '{}', 'fluoroquinolone', 'oral'),
+  ('ofloxacin', '{}', 'fluoroquinolone', 'oral'),
+  ('doxycycline', '{}', 'tetracycline', 'oral'),
+  ('gentamicin', '{}', 'aminoglycoside', 'iv'),
+  ('amikacin', '{}', 'aminoglycoside', 'iv'),
+  ('morphine', '{}', 'opioid', 'oral'),
+  ('tramadol', '{}', 'opioid', 'oral'),
+  ('codeine', '{}', 'opioid', 'oral'),
+  ('enalapril', '{}', 'ace_inhibitor', 'oral'),
+  ('ramipril', '{}', 'ace_inhibitor', 'oral'),
+  ('lisinopril', '{}', 'ace_inhibitor', 'oral'),
+  ('atorvastatin', '{}', 'statin', 'oral'),
+  ('carbamazepine', '{}', 'aromatic_anticonvulsant', 'oral'),
+  ('phenytoin', '{}', 'aromatic_anticonvulsant', 'oral'),
+  ('phenobarbital', '{phenobarbitone}', 'aromatic_anticonvulsant', 'oral'),
+  ('paracetamol', '{acetaminophen,crocin,dolo,calpol}', NULL, 'oral'),
+  ('metformin', '{glycomet}', NULL, 'oral'),
+  ('glibenclamide', '{glyburide}', NULL, 'oral'),
+  ('insulin', '{}', NULL, 'sc'),
+  ('amlodipine', '{}', NULL, 'oral'),
+  ('salbutamol', '{albuterol,asthalin}', NULL, 'inhaled'),
+  ('oral rehydration salts', '{ors}', NULL, 'oral'),
+  ('zinc sulfate', '{zinc}', NULL, 'oral'),
+  ('iron and folic acid', '{ifa,ferrous sulfate with folic acid}', NULL, 'oral'),
+  ('albendazole', '{}', NULL, 'oral'),
+  ('artemether-lumefantrine', '{coartem}', NULL, 'oral'),
+  ('chloroquine', '{}', NULL, 'oral'),
+  ('oxytocin', '{}', NULL, 'im'),
+  ('metronidazole', '{flagyl}', NULL, 'oral');
+
+-- Down
+-- DROP TABLE prescriptions;
+-- DROP TABLE drugs;
+-- DROP TABLE allergen_classes;
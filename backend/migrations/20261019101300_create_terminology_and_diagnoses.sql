@@ -0,0 +1,82 @@
+-- no-transaction
+-- Code systems loaded from the files in TERMINOLOGY_DIR, one row per file
+CREATE TABLE terminology_systems (
+  code TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  version TEXT,
+  source_file TEXT NOT NULL,
+  -- SHA-256 of the file last loaded; unchanged files are skipped on startup
+  checksum TEXT NOT NULL,
+  code_count INTEGER NOT NULL DEFAULT 0,
+  loaded_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE TABLE terminology_codes (
+  system TEXT NOT NULL REFERENCES terminology_systems(code) ON DELETE CASCADE,
+  code TEXT NOT NULL,
+  display TEXT NOT NULL,
+  -- other names clinicians use for the concept, in lowercase
+  synonyms TEXT[] NOT NULL DEFAULT '{}',
+  -- lowercase code, display and synonyms, matched by search
+  search_text TEXT NOT NULL,
+  -- codes dropped from a newer file stay for the diagnoses that use them, but are not offered
+  active BOOLEAN NOT NULL DEFAULT TRUE,
+  PRIMARY KEY (system, code)
+);
+
+CREATE INDEX idx_terminology_codes_synonyms ON terminology_codes USING GIN (synonyms);
+
+CREATE TABLE diagnoses (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  record_id INTEGER REFERENCES public.medical_records(id) ON DELETE SET NULL,
+  system TEXT NOT NULL,
+  code TEXT NOT NULL,
+  -- the code's display text when it was recorded
+  display TEXT NOT NULL,
+  onset_date DATE,
+  resolved_date DATE,
+  notes TEXT,
+  -- 'clinician', or 'condition_mapping' for diagnoses coded from free-text conditions
+  source TEXT NOT NULL DEFAULT 'clinician' CHECK (source IN ('clinician', 'condition_mapping')),
+  recorded_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT now(),
+  updated_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  updated_at TIMESTAMP NOT NULL DEFAULT now(),
+  FOREIGN KEY (system, code) REFERENCES terminology_codes(system, code),
+  CHECK (resolved_date IS NULL OR onset_date IS NULL OR resolved_date >= onset_date)
+);
+
+CREATE INDEX idx_diagnoses_patient ON diagnoses (patient_id);
+-- a condition is only open once per patient
+CREATE UNIQUE INDEX idx_diagnoses_open ON diagnoses (patient_id, system, code) WHERE resolved_date IS NULL;
+
+-- Free-text entries of patients.active_conditions and what they were coded as
+CREATE TABLE condition_mappings (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  source_text TEXT NOT NULL,
+  -- lowercase with single spaces
+  normalized_text TEXT NOT NULL,
+  status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'mapped', 'unmappable')),
+  -- how the code was found: 'exact', 'learned' (an earlier review of the same text),
+  -- 'suggested' (best search hit, needs review), 'reviewed' or 'none'
+  match_kind TEXT NOT NULL,
+  system TEXT,
+  code TEXT,
+  diagnosis_id INTEGER REFERENCES diagnoses(id) ON DELETE SET NULL,
+  reviewed_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  reviewed_at TIMESTAMP,
+  review_note TEXT,
+  created_at TIMESTAMP NOT NULL DEFAULT now(),
+  FOREIGN KEY (system, code) REFERENCES terminology_codes(system, code),
+  UNIQUE (patient_id, normalized_text)
+);
+
+CREATE INDEX idx_condition_mappings_queue ON condition_mappings (status, normalized_text);
+
+-- Down
+-- DROP TABLE condition_mappings;
+-- DROP TABLE diagnoses;
+-- DROP TABLE terminology_codes;
+-- DROP TABLE terminology_systems;
@@ -0,0 +1,115 @@
+-- no-transaction
+-- Vaccine catalog and configurable immunization schedules
+CREATE TABLE vaccines (
+  code TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  default_route TEXT,
+  default_site TEXT
+);
+
+CREATE TABLE immunization_schedules (
+  code TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  -- the schedule used when a request does not name one
+  is_default BOOLEAN NOT NULL DEFAULT FALSE,
+  updated_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX idx_immunization_schedules_default ON immunization_schedules (is_default) WHERE is_default;
+
+-- Ages are in days from date_of_birth. A dose is due at due_age_days (or min_interval_days
+-- after the previous dose of the same vaccine, whichever is later), overdue once
+-- overdue_age_days - due_age_days more have passed, and missed after max_age_days.
+CREATE TABLE schedule_doses (
+  schedule_code TEXT NOT NULL REFERENCES immunization_schedules(code) ON DELETE CASCADE,
+  vaccine_code TEXT NOT NULL REFERENCES vaccines(code),
+  dose_number INTEGER NOT NULL CHECK (dose_number > 0),
+  -- the name the programme uses, e.g. 'OPV-0' or 'DPT booster 1'
+  label TEXT NOT NULL,
+  min_age_days INTEGER NOT NULL CHECK (min_age_days >= 0),
+  due_age_days INTEGER NOT NULL,
+  overdue_age_days INTEGER NOT NULL,
+  max_age_days INTEGER,
+  min_interval_days INTEGER CHECK (min_interval_days > 0),
+  -- NULL for everyone
+  sex TEXT,
+  PRIMARY KEY (schedule_code, vaccine_code, dose_number),
+  CHECK (min_age_days <= due_age_days AND due_age_days <= overdue_age_days),
+  CHECK (max_age_days IS NULL OR max_age_days >= overdue_age_days)
+);
+
+CREATE TABLE immunizations (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  record_id INTEGER REFERENCES public.medical_records(id) ON DELETE SET NULL,
+  vaccine_code TEXT NOT NULL REFERENCES vaccines(code),
+  dose_number INTEGER NOT NULL CHECK (dose_number > 0),
+  administered_on DATE NOT NULL,
+  lot_number TEXT,
+  site TEXT,
+  route TEXT,
+  -- the staff member who gave the dose, or the name of an outreach worker without an account
+  administered_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  administered_by_name TEXT,
+  -- copied from a vaccination card rather than given by us
+  historical BOOLEAN NOT NULL DEFAULT FALSE,
+  notes TEXT,
+  recorded_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  recorded_at TIMESTAMP NOT NULL DEFAULT now(),
+  UNIQUE (patient_id, vaccine_code, dose_number)
+);
+
+CREATE INDEX idx_immunizations_patient ON immunizations (patient_id, administered_on);
+
+INSERT INTO vaccines (code, name, default_route, default_site) VALUES
+  ('bcg', 'BCG', 'intradermal', 'left_upper_arm'),
+  ('opv', 'Oral polio vaccine', 'oral', 'oral'),
+  ('hepb', 'Hepatitis B', 'im', 'left_thigh'),
+  ('penta', 'Pentavalent (DTwP-HepB-Hib)', 'im', 'left_thigh'),
+  ('rota', 'Rotavirus', 'oral', 'oral'),
+  ('fipv', 'Fractional inactivated polio vaccine', 'intradermal', 'right_upper_arm'),
+  ('pcv', 'Pneumococcal conjugate', 'im', 'right_thigh'),
+  ('mr', 'Measles-rubella', 'sc', 'right_upper_arm'),
+  ('je', 'Japanese encephalitis', 'sc', 'left_upper_arm'),
+  ('dpt', 'DPT', 'im', 'left_thigh'),
+  ('td', 'Tetanus-diphtheria (Td)', 'im', 'left_upper_arm'),
+  ('hpv', 'Human papillomavirus', 'im', 'left_upper_arm');
+
+INSERT INTO immunization_schedules (code, name, is_default) VALUES
+  ('national', 'National childhood schedule', TRUE);
+
+INSERT INTO schedule_doses
+  (schedule_code, vaccine_code, dose_number, label, min_age_days, due_age_days, overdue_age_days, max_age_days, min_interval_days, sex)
+VALUES
+  ('national', 'bcg', 1, 'BCG', 0, 0, 28, 365, NULL, NULL),
+  ('national', 'hepb', 1, 'Hepatitis B birth dose', 0, 0, 1, 1, NULL, NULL),
+  ('national', 'opv', 1, 'OPV-0', 0, 0, 15, 15, NULL, NULL),
+  ('national', 'opv', 2, 'OPV-1', 42, 42, 70, 1825, 28, NULL),
+  ('national', 'opv', 3, 'OPV-2', 70, 70, 98, 1825, 28, NULL),
+  ('national', 'opv', 4, 'OPV-3', 98, 98, 126, 1825, 28, NULL),
+  ('national', 'opv', 5, 'OPV booster', 487, 487, 730, 1825, 180, NULL),
+  ('national', 'penta', 1, 'Pentavalent-1', 42, 42, 70, 365, NULL, NULL),
+  ('national', 'penta', 2, 'Pentavalent-2', 70, 70, 98, 365, 28, NULL),
+  ('national', 'penta', 3, 'Pentavalent-3', 98, 98, 126, 365, 28, NULL),
+  ('national', 'rota', 1, 'Rotavirus-1', 42, 42, 70, 365, NULL, NULL),
+  ('national', 'rota', 2, 'Rotavirus-2', 70, 70, 98, 365, 28, NULL),
+  ('national', 'rota', 3, 'Rotavirus-3', 98, 98, 126, 365, 28, NULL),
+  ('national', 'fipv', 1, 'fIPV-1', 42, 42, 70, 365, NULL, NULL),
+  ('national', 'fipv', 2, 'fIPV-2', 98, 98, 126, 365, 56, NULL),
+  ('national', 'pcv', 1, 'PCV-1', 42, 42, 70, 365, NULL, NULL),
+  ('national', 'pcv', 2, 'PCV-2', 98, 98, 126, 365, 56, NULL),
+  ('national', 'pcv', 3, 'PCV booster', 270, 270, 365, 730, 180, NULL),
+  ('national', 'mr', 1, 'MR-1', 270, 270, 365, 1825, NULL, NULL),
+  ('national', 'mr', 2, 'MR-2', 487, 487, 730, 1825, 28, NULL),
+  ('national', 'dpt', 1, 'DPT booster 1', 487, 487, 730, 2555, NULL, NULL),
+  ('national', 'dpt', 2, 'DPT booster 2', 1825, 1825, 2190, 2555, 180, NULL),
+  ('national', 'td', 1, 'Td (10 years)', 3650, 3650, 4015, 5839, NULL, NULL),
+  ('national', 'td', 2, 'Td (16 years)', 5840, 5840, 6205, 6935, 365, NULL),
+  ('national', 'hpv', 1, 'HPV', 3285, 3285, 3650, 5474, NULL, 'female');
+
+-- Down
+-- DROP TABLE immunizations;
+-- DROP TABLE schedule_doses;
+-- DROP TABLE immunization_schedules;
+-- DROP TABLE vaccines;
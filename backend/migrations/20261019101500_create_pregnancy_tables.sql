@@ -0,0 +1,108 @@
+-- no-transaction
+-- Pregnancy episodes, antenatal (ANC) contacts and delivery outcomes
+
+-- The recommended contact schedule, by completed week of gestation, with the checks due
+-- at each contact.
+CREATE TABLE anc_schedule (
+  contact_number INTEGER PRIMARY KEY CHECK (contact_number > 0),
+  gestational_week INTEGER NOT NULL UNIQUE CHECK (gestational_week BETWEEN 4 AND 42),
+  label TEXT NOT NULL,
+  checks TEXT[] NOT NULL DEFAULT '{}'
+);
+
+CREATE TABLE pregnancies (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  -- first day of the last menstrual period, when known
+  lmp DATE,
+  edd DATE NOT NULL,
+  -- how the EDD was arrived at: lmp, ultrasound or clinical
+  edd_method TEXT NOT NULL,
+  -- pregnancies including this one, and previous births at 28 weeks or more
+  gravida INTEGER NOT NULL CHECK (gravida >= 1),
+  para INTEGER NOT NULL CHECK (para >= 0 AND para < gravida),
+  booked_on DATE NOT NULL,
+  -- active, delivered or ended
+  status TEXT NOT NULL DEFAULT 'active',
+  risk_factors TEXT[] NOT NULL DEFAULT '{}',
+  notes TEXT,
+  outcome TEXT,
+  outcome_date DATE,
+  delivery_mode TEXT,
+  delivery_place TEXT,
+  maternal_death BOOLEAN,
+  outcome_notes TEXT,
+  outcome_recorded_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  registered_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  registered_at TIMESTAMP NOT NULL DEFAULT now(),
+  updated_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  updated_at TIMESTAMP NOT NULL DEFAULT now(),
+  CHECK ((status = 'active') = (outcome IS NULL)),
+  CHECK ((outcome IS NULL) = (outcome_date IS NULL))
+);
+
+-- one ongoing pregnancy per woman
+CREATE UNIQUE INDEX idx_pregnancies_active ON pregnancies (patient_id) WHERE status = 'active';
+CREATE INDEX idx_pregnancies_patient ON pregnancies (patient_id, edd);
+
+-- Contacts that took place. contact_number is the scheduled contact the visit counts as,
+-- or NULL for an extra visit.
+CREATE TABLE anc_contacts (
+  id SERIAL PRIMARY KEY,
+  pregnancy_id INTEGER NOT NULL REFERENCES pregnancies(id) ON DELETE CASCADE,
+  contact_number INTEGER REFERENCES anc_schedule(contact_number),
+  contact_date DATE NOT NULL,
+  gestational_age_days INTEGER NOT NULL,
+  record_id INTEGER REFERENCES public.medical_records(id) ON DELETE SET NULL,
+  weight_kg DOUBLE PRECISION,
+  systolic_bp INTEGER,
+  diastolic_bp INTEGER,
+  haemoglobin DOUBLE PRECISION,
+  fundal_height_cm DOUBLE PRECISION,
+  fetal_heart_rate INTEGER,
+  urine_protein TEXT,
+  urine_glucose TEXT,
+  fetal_presentation TEXT,
+  checks_done TEXT[] NOT NULL DEFAULT '{}',
+  notes TEXT,
+  recorded_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  recorded_at TIMESTAMP NOT NULL DEFAULT now(),
+  UNIQUE (pregnancy_id, contact_number)
+);
+
+CREATE INDEX idx_anc_contacts_pregnancy ON anc_contacts (pregnancy_id, contact_date);
+
+CREATE TABLE pregnancy_births (
+  id SERIAL PRIMARY KEY,
+  pregnancy_id INTEGER NOT NULL REFERENCES pregnancies(id) ON DELETE CASCADE,
+  birth_order INTEGER NOT NULL CHECK (birth_order > 0),
+  -- live_birth or stillbirth
+  outcome TEXT NOT NULL,
+  sex TEXT,
+  birth_weight_grams INTEGER CHECK (birth_weight_grams > 0),
+  -- the baby's own patient record, when one was registered
+  newborn_patient_id INTEGER REFERENCES public.patients(id) ON DELETE SET NULL,
+  UNIQUE (pregnancy_id, birth_order)
+);
+
+INSERT INTO anc_schedule (contact_number, gestational_week, label, checks) VALUES
+  (1, 12, 'First contact',
+   '{weight,blood_pressure,haemoglobin,blood_group,urine_protein,urine_glucose,hiv,syphilis,hepatitis_b,ultrasound,td,iron_folic_acid}'),
+  (2, 20, 'Second contact',
+   '{weight,blood_pressure,urine_protein,fundal_height,fetal_heart_rate,td,iron_folic_acid,calcium,deworming}'),
+  (3, 26, 'Third contact',
+   '{weight,blood_pressure,haemoglobin,urine_protein,fundal_height,fetal_heart_rate,glucose_tolerance,iron_folic_acid,calcium}'),
+  (4, 30, 'Fourth contact',
+   '{weight,blood_pressure,urine_protein,fundal_height,fetal_heart_rate,iron_folic_acid,calcium,birth_preparedness}'),
+  (5, 34, 'Fifth contact',
+   '{weight,blood_pressure,urine_protein,fundal_height,fetal_heart_rate,iron_folic_acid,calcium,birth_preparedness}'),
+  (6, 36, 'Sixth contact',
+   '{weight,blood_pressure,haemoglobin,urine_protein,fundal_height,fetal_heart_rate,fetal_presentation,birth_preparedness}'),
+  (7, 38, 'Seventh contact', '{blood_pressure,urine_protein,fundal_height,fetal_heart_rate,fetal_presentation}'),
+  (8, 40, 'Eighth contact', '{blood_pressure,urine_protein,fundal_height,fetal_heart_rate,fetal_presentation}');
+
+-- Down
+-- DROP TABLE pregnancy_births;
+-- DROP TABLE anc_contacts;
+-- DROP TABLE pregnancies;
+-- DROP TABLE anc_schedule;
@@ -0,0 +1,34 @@
+-- no-transaction
+-- Child growth monitoring: anthropometry with WHO z-scores and nutrition classification
+CREATE TABLE growth_measurements (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  record_id INTEGER REFERENCES public.medical_records(id) ON DELETE SET NULL,
+  measured_on DATE NOT NULL,
+  age_days INTEGER NOT NULL CHECK (age_days >= 0),
+  weight_kg DOUBLE PRECISION,
+  -- recumbent length when measured_lying, otherwise standing height
+  height_cm DOUBLE PRECISION,
+  measured_lying BOOLEAN NOT NULL,
+  muac_cm DOUBLE PRECISION,
+  -- bilateral pitting oedema
+  oedema BOOLEAN NOT NULL DEFAULT FALSE,
+  -- computed from the WHO reference tables when the measurement is recorded
+  weight_for_age_z DOUBLE PRECISION,
+  height_for_age_z DOUBLE PRECISION,
+  weight_for_height_z DOUBLE PRECISION,
+  -- normal, mam or sam
+  acute_malnutrition TEXT,
+  -- normal, moderate or severe
+  stunting TEXT,
+  underweight TEXT,
+  notes TEXT,
+  recorded_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  recorded_at TIMESTAMP NOT NULL DEFAULT now(),
+  CHECK (weight_kg IS NOT NULL OR height_cm IS NOT NULL OR muac_cm IS NOT NULL OR oedema)
+);
+
+CREATE INDEX idx_growth_measurements_patient ON growth_measurements (patient_id, measured_on DESC);
+
+-- Down
+-- DROP TABLE growth_measurements;
@@ -0,0 +1,57 @@
+-- no-transaction
+-- Weekly sessions a provider works at a facility, split into bookable slots
+CREATE TABLE provider_availability (
+  id SERIAL PRIMARY KEY,
+  provider_id INTEGER NOT NULL REFERENCES public.users(id) ON DELETE CASCADE,
+  facility TEXT NOT NULL,
+  -- ISO weekday: 1 = Monday .. 7 = Sunday
+  weekday SMALLINT NOT NULL CHECK (weekday BETWEEN 1 AND 7),
+  start_time TIME NOT NULL,
+  end_time TIME NOT NULL,
+  slot_minutes INTEGER NOT NULL DEFAULT 15 CHECK (slot_minutes BETWEEN 5 AND 240),
+  valid_from DATE,
+  valid_until DATE,
+  created_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT now(),
+  CHECK (end_time > start_time),
+  CHECK (valid_until IS NULL OR valid_from IS NULL OR valid_until >= valid_from)
+);
+
+CREATE INDEX idx_provider_availability_provider ON provider_availability (provider_id, weekday);
+
+CREATE TABLE appointments (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  -- NULL for follow-ups booked without a provider, including those carried over from next_visit
+  provider_id INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  facility TEXT,
+  appointment_type TEXT NOT NULL DEFAULT 'consultation',
+  starts_at TIMESTAMP NOT NULL,
+  ends_at TIMESTAMP NOT NULL,
+  status TEXT NOT NULL DEFAULT 'booked'
+    CHECK (status IN ('booked', 'checked_in', 'seen', 'no_show', 'cancelled')),
+  reason TEXT,
+  notes TEXT,
+  booked_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  booked_at TIMESTAMP NOT NULL DEFAULT now(),
+  checked_in_at TIMESTAMP,
+  seen_at TIMESTAMP,
+  cancelled_reason TEXT,
+  reschedule_count INTEGER NOT NULL DEFAULT 0,
+  updated_at TIMESTAMP NOT NULL DEFAULT now(),
+  CHECK (ends_at > starts_at)
+);
+
+CREATE INDEX idx_appointments_provider ON appointments (provider_id, starts_at);
+CREATE INDEX idx_appointments_patient ON appointments (patient_id, starts_at);
+CREATE INDEX idx_appointments_facility ON appointments (facility, starts_at);
+
+-- Carry existing follow-up dates over as unassigned follow-up appointments
+INSERT INTO appointments (patient_id, appointment_type, starts_at, ends_at, reason)
+SELECT id, 'follow_up', next_visit + TIME '09:00', next_visit + TIME '09:15', 'Follow-up visit'
+FROM patients
+WHERE next_visit IS NOT NULL;
+
+-- Down
+-- DROP TABLE appointments;
+-- DROP TABLE provider_availability;
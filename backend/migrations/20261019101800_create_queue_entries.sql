@@ -0,0 +1,32 @@
+-- no-transaction
+-- A patient's pass through a clinic on one day: arrival, triage, consultation start and end
+CREATE TABLE queue_entries (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  appointment_id INTEGER REFERENCES public.appointments(id) ON DELETE SET NULL,
+  facility TEXT NOT NULL,
+  status TEXT NOT NULL DEFAULT 'waiting'
+    CHECK (status IN ('waiting', 'triaged', 'in_consultation', 'completed', 'left')),
+  -- set at triage: emergency, urgent or routine
+  priority TEXT,
+  arrived_at TIMESTAMP NOT NULL DEFAULT now(),
+  triaged_at TIMESTAMP,
+  triaged_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  triage_notes TEXT,
+  consultation_started_at TIMESTAMP,
+  consultation_ended_at TIMESTAMP,
+  provider_id INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  left_at TIMESTAMP,
+  left_reason TEXT,
+  registered_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_queue_entries_facility ON queue_entries (facility, arrived_at);
+CREATE INDEX idx_queue_entries_patient ON queue_entries (patient_id, arrived_at DESC);
+-- a patient is in at most one queue at a time
+CREATE UNIQUE INDEX idx_queue_entries_open ON queue_entries (patient_id)
+  WHERE status IN ('waiting', 'triaged', 'in_consultation');
+
+-- Down
+-- DROP TABLE queue_entries;
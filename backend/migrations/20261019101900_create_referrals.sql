@@ -0,0 +1,48 @@
+-- no-transaction
+-- Referrals of a patient from one facility to another, and the notes exchanged on them
+CREATE TABLE referrals (
+  id SERIAL PRIMARY KEY,
+  patient_id INTEGER NOT NULL REFERENCES public.patients(id) ON DELETE CASCADE,
+  from_facility TEXT NOT NULL,
+  to_facility TEXT NOT NULL,
+  reason TEXT NOT NULL,
+  urgency TEXT NOT NULL DEFAULT 'routine',
+  clinical_summary TEXT,
+  -- the record the summary was written in, if any
+  record_id INTEGER REFERENCES public.medical_records(id) ON DELETE SET NULL,
+  status TEXT NOT NULL DEFAULT 'sent'
+    CHECK (status IN ('sent', 'received', 'accepted', 'completed', 'declined', 'cancelled')),
+  referred_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  sent_at TIMESTAMP NOT NULL DEFAULT now(),
+  received_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  received_at TIMESTAMP,
+  accepted_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  accepted_at TIMESTAMP,
+  completed_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  completed_at TIMESTAMP,
+  -- what the receiving facility did, for the referrer
+  outcome TEXT,
+  closed_reason TEXT,
+  updated_at TIMESTAMP NOT NULL DEFAULT now(),
+  CHECK (from_facility <> to_facility)
+);
+
+CREATE INDEX idx_referrals_to ON referrals (to_facility, sent_at DESC);
+CREATE INDEX idx_referrals_from ON referrals (from_facility, sent_at DESC);
+CREATE INDEX idx_referrals_patient ON referrals (patient_id, sent_at DESC);
+
+CREATE TABLE referral_notes (
+  id SERIAL PRIMARY KEY,
+  referral_id INTEGER NOT NULL REFERENCES referrals(id) ON DELETE CASCADE,
+  -- which end wrote it: 'referrer' or 'receiver'
+  side TEXT NOT NULL CHECK (side IN ('referrer', 'receiver')),
+  note TEXT NOT NULL,
+  author_id INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_referral_notes_referral ON referral_notes (referral_id, created_at);
+
+-- Down
+-- DROP TABLE referral_notes;
+-- DROP TABLE referrals;
@@ -0,0 +1,141 @@
+-- no-transaction
+-- Facilities and departments, staff assignments, and facility references in place of the
+-- free-text facility names on orders, sessions, appointments, queues and referrals
+CREATE TABLE facilities (
+  id SERIAL PRIMARY KEY,
+  -- short unique code, e.g. PHC-RAMPUR
+  code TEXT NOT NULL UNIQUE,
+  name TEXT NOT NULL,
+  facility_type TEXT NOT NULL DEFAULT 'other'
+    CHECK (facility_type IN ('sub_centre', 'phc', 'chc', 'sub_district_hospital', 'district_hospital', 'clinic', 'other')),
+  district TEXT,
+  latitude DOUBLE PRECISION CHECK (latitude BETWEEN -90 AND 90),
+  longitude DOUBLE PRECISION CHECK (longitude BETWEEN -180 AND 180),
+  active BOOLEAN NOT NULL DEFAULT true,
+  created_at TIMESTAMP NOT NULL DEFAULT now(),
+  updated_at TIMESTAMP NOT NULL DEFAULT now(),
+  CHECK ((latitude IS NULL) = (longitude IS NULL))
+);
+
+CREATE UNIQUE INDEX idx_facilities_name ON facilities (LOWER(name));
+CREATE INDEX idx_facilities_district ON facilities (LOWER(district));
+
+CREATE TABLE departments (
+  id SERIAL PRIMARY KEY,
+  code TEXT NOT NULL UNIQUE,
+  name TEXT NOT NULL,
+  active BOOLEAN NOT NULL DEFAULT true
+);
+
+CREATE UNIQUE INDEX idx_departments_name ON departments (LOWER(name));
+
+INSERT INTO departments (code, name) VALUES
+  ('general-medicine', 'General Medicine'),
+  ('pediatrics', 'Pediatrics'),
+  ('obstetrics', 'Obstetrics and Gynaecology'),
+  ('surgery', 'Surgery'),
+  ('laboratory', 'Laboratory'),
+  ('pharmacy', 'Pharmacy'),
+  ('general-ward', 'General Ward'),
+  ('it', 'IT Department');
+
+ALTER TABLE users ADD COLUMN department_id INTEGER REFERENCES departments(id) ON DELETE SET NULL;
+
+-- Where each member of staff works; at most one primary facility each
+CREATE TABLE user_facilities (
+  user_id INTEGER NOT NULL REFERENCES public.users(id) ON DELETE CASCADE,
+  facility_id INTEGER NOT NULL REFERENCES facilities(id) ON DELETE CASCADE,
+  is_primary BOOLEAN NOT NULL DEFAULT false,
+  assigned_by INTEGER REFERENCES public.users(id) ON DELETE SET NULL,
+  assigned_at TIMESTAMP NOT NULL DEFAULT now(),
+  PRIMARY KEY (user_id, facility_id)
+);
+
+CREATE UNIQUE INDEX idx_user_facilities_primary ON user_facilities (user_id) WHERE is_primary;
+CREATE INDEX idx_user_facilities_facility ON user_facilities (facility_id);
+
+-- Every facility named so far becomes a facility row; names that differ only in case or
+-- punctuation share a code and so a facility
+CREATE FUNCTION pg_temp.facility_code(name TEXT) RETURNS TEXT AS $$
+  SELECT upper(btrim(regexp_replace(name, '[^A-Za-z0-9]+', '-', 'g'), '-'))
+$$ LANGUAGE sql IMMUTABLE;
+
+INSERT INTO facilities (code, name)
+SELECT DISTINCT ON (pg_temp.facility_code(name)) pg_temp.facility_code(name), name
+FROM (
+    SELECT btrim(facility) AS name FROM lab_orders
+    UNION ALL SELECT btrim(facility) FROM provider_availability
+    UNION ALL SELECT btrim(facility) FROM appointments WHERE facility IS NOT NULL
+    UNION ALL SELECT btrim(facility) FROM queue_entries
+    UNION ALL SELECT btrim(from_facility) FROM referrals
+    UNION ALL SELECT btrim(to_facility) FROM referrals
+) named
+WHERE pg_temp.facility_code(name) <> ''
+ORDER BY pg_temp.facility_code(name), name;
+
+ALTER TABLE medical_records ADD COLUMN facility_id INTEGER REFERENCES facilities(id);
+CREATE INDEX idx_medical_records_facility_date ON medical_records (facility_id, date DESC, id DESC);
+
+ALTER TABLE lab_orders ADD COLUMN facility_id INTEGER REFERENCES facilities(id);
+UPDATE lab_orders o SET facility_id = f.id FROM facilities f WHERE f.code = pg_temp.facility_code(o.facility);
+ALTER TABLE lab_orders ALTER COLUMN facility_id SET NOT NULL;
+DROP INDEX idx_lab_orders_outstanding;
+ALTER TABLE lab_orders DROP COLUMN facility;
+CREATE INDEX idx_lab_orders_outstanding ON lab_orders (facility_id, ordered_at)
+  WHERE status IN ('ordered', 'collected', 'resulted');
+
+ALTER TABLE provider_availability ADD COLUMN facility_id INTEGER REFERENCES facilities(id);
+UPDATE provider_availability a SET facility_id = f.id FROM facilities f WHERE f.code = pg_temp.facility_code(a.facility);
+ALTER TABLE provider_availability ALTER COLUMN facility_id SET NOT NULL;
+ALTER TABLE provider_availability DROP COLUMN facility;
+
+ALTER TABLE appointments ADD COLUMN facility_id INTEGER REFERENCES facilities(id);
+UPDATE appointments a SET facility_id = f.id FROM facilities f WHERE f.code = pg_temp.facility_code(a.facility);
+DROP INDEX idx_appointments_facility;
+ALTER TABLE appointments DROP COLUMN facility;
+CREATE INDEX idx_appointments_facility ON appointments (facility_id, starts_at);
+
+ALTER TABLE queue_entries ADD COLUMN facility_id INTEGER REFERENCES facilities(id);
+UPDATE queue_entries q SET facility_id = f.id FROM facilities f WHERE f.code = pg_temp.facility_code(q.facility);
+ALTER TABLE queue_entries ALTER COLUMN facility_id SET NOT NULL;
+DROP INDEX idx_queue_entries_facility;
+ALTER TABLE queue_entries DROP COLUMN facility;
+CREATE INDEX idx_queue_entries_facility ON queue_entries (facility_id, arrived_at);
+
+ALTER TABLE referrals
+  ADD COLUMN from_facility_id INTEGER REFERENCES facilities(id),
+  ADD COLUMN to_facility_id INTEGER REFERENCES facilities(id);
+UPDATE referrals r SET from_facility_id = f.id FROM facilities f WHERE f.code = pg_temp.facility_code(r.from_facility);
+UPDATE referrals r SET to_facility_id = f.id FROM facilities f WHERE f.code = pg_temp.facility_code(r.to_facility);
+ALTER TABLE referrals
+  ALTER COLUMN from_facility_id SET NOT NULL,
+  ALTER COLUMN to_facility_id SET NOT NULL,
+  DROP COLUMN from_facility,
+  DROP COLUMN to_facility,
+  ADD CHECK (from_facility_id <> to_facility_id);
+CREATE INDEX idx_referrals_to ON referrals (to_facility_id, sent_at DESC);
+CREATE INDEX idx_referrals_from ON referrals (from_facility_id, sent_at DESC);
+
+-- Down
+-- Facility names are restored from the facilities table before it is dropped.
+-- ALTER TABLE referrals ADD COLUMN from_facility TEXT, ADD COLUMN to_facility TEXT;
+-- UPDATE referrals r SET from_facility = f.name FROM facilities f WHERE f.id = r.from_facility_id;
+-- UPDATE referrals r SET to_facility = f.name FROM facilities f WHERE f.id = r.to_facility_id;
+-- ALTER TABLE referrals DROP COLUMN from_facility_id, DROP COLUMN to_facility_id;
+-- ALTER TABLE queue_entries ADD COLUMN facility TEXT;
+-- UPDATE queue_entries q SET facility = f.name FROM facilities f WHERE f.id = q.facility_id;
+-- ALTER TABLE queue_entries DROP COLUMN facility_id;
+-- ALTER TABLE appointments ADD COLUMN facility TEXT;
+-- UPDATE appointments a SET facility = f.name FROM facilities f WHERE f.id = a.facility_id;
+-- ALTER TABLE appointments DROP COLUMN facility_id;
+-- ALTER TABLE provider_availability ADD COLUMN facility TEXT;
+-- UPDATE provider_availability a SET facility = f.name FROM facilities f WHERE f.id = a.facility_id;
+-- ALTER TABLE provider_availability DROP COLUMN facility_id;
+-- ALTER TABLE lab_orders ADD COLUMN facility TEXT;
+-- UPDATE lab_orders o SET facility = f.name FROM facilities f WHERE f.id = o.facility_id;
+-- ALTER TABLE lab_orders DROP COLUMN facility_id;
+-- ALTER TABLE medical_records DROP COLUMN facility_id;
+-- DROP TABLE user_facilities;
+-- ALTER TABLE users DROP COLUMN department_id;
+-- DROP TABLE departments;
+-- DROP TABLE facilities;
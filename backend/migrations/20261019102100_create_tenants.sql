@@ -0,0 +1,115 @@
+-- no-transaction
+-- Partner organisations sharing one deployment. Users, patients, records and consents belong
+-- to a tenant; handlers that serve them filter on it and run as tenant_scoped, which row-level
+-- security confines to the tenant in the app.tenant_id setting.
+CREATE TABLE tenants (
+  id SERIAL PRIMARY KEY,
+  code TEXT NOT NULL UNIQUE,
+  name TEXT NOT NULL,
+  -- users of a suspended tenant cannot sign in
+  active BOOLEAN NOT NULL DEFAULT true,
+  created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX idx_tenants_name ON tenants (LOWER(name));
+
+-- Everything recorded so far belongs to the first tenant
+INSERT INTO tenants (code, name) VALUES ('default', 'Default organisation');
+
+ALTER TABLE users ADD COLUMN tenant_id INTEGER NOT NULL DEFAULT 1 REFERENCES tenants(id);
+ALTER TABLE users ALTER COLUMN tenant_id DROP DEFAULT;
+CREATE INDEX idx_users_tenant ON users (tenant_id);
+
+ALTER TABLE patients ADD COLUMN tenant_id INTEGER NOT NULL DEFAULT 1 REFERENCES tenants(id);
+ALTER TABLE patients ALTER COLUMN tenant_id DROP DEFAULT;
+ALTER TABLE patients ADD CONSTRAINT patients_id_tenant_key UNIQUE (id, tenant_id);
+CREATE INDEX idx_patients_tenant ON patients (tenant_id);
+
+-- Records and consents always sit in their patient's tenant; left out on insert, the
+-- patient's tenant is filled in
+CREATE FUNCTION patient_tenant() RETURNS trigger AS $$
+BEGIN
+  IF NEW.tenant_id IS NULL THEN
+    SELECT tenant_id INTO NEW.tenant_id FROM patients WHERE id = NEW.patient_id;
+  END IF;
+  RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE medical_records ADD COLUMN tenant_id INTEGER;
+UPDATE medical_records r SET tenant_id = p.tenant_id FROM patients p WHERE p.id = r.patient_id;
+ALTER TABLE medical_records
+  ALTER COLUMN tenant_id SET NOT NULL,
+  ADD CONSTRAINT medical_records_patient_tenant_fkey
+    FOREIGN KEY (patient_id, tenant_id) REFERENCES patients (id, tenant_id);
+CREATE INDEX idx_medical_records_tenant_date ON medical_records (tenant_id, date DESC, id DESC);
+CREATE TRIGGER medical_records_tenant BEFORE INSERT ON medical_records
+  FOR EACH ROW EXECUTE FUNCTION patient_tenant();
+
+ALTER TABLE consents ADD COLUMN tenant_id INTEGER;
+UPDATE consents c SET tenant_id = p.tenant_id FROM patients p WHERE p.id = c.patient_id;
+ALTER TABLE consents
+  ALTER COLUMN tenant_id SET NOT NULL,
+  ADD CONSTRAINT consents_patient_tenant_fkey
+    FOREIGN KEY (patient_id, tenant_id) REFERENCES patients (id, tenant_id);
+CREATE INDEX idx_consents_tenant ON consents (tenant_id);
+CREATE TRIGGER consents_tenant BEFORE INSERT ON consents
+  FOR EACH ROW EXECUTE FUNCTION patient_tenant();
+
+-- The role tenant-scoped requests switch to. It is not the table owner, so row-level
+-- security applies to it.
+DO $$
+BEGIN
+  IF NOT EXISTS (SELECT 1 FROM pg_roles WHERE rolname = 'tenant_scoped') THEN
+    CREATE ROLE tenant_scoped NOLOGIN;
+  END IF;
+END
+$$;
+GRANT tenant_scoped TO CURRENT_USER;
+GRANT USAGE ON SCHEMA public TO tenant_scoped;
+GRANT SELECT, INSERT, UPDATE, DELETE ON ALL TABLES IN SCHEMA public TO tenant_scoped;
+GRANT USAGE, SELECT, UPDATE ON ALL SEQUENCES IN SCHEMA public TO tenant_scoped;
+ALTER DEFAULT PRIVILEGES IN SCHEMA public GRANT SELECT, INSERT, UPDATE, DELETE ON TABLES TO tenant_scoped;
+ALTER DEFAULT PRIVILEGES IN SCHEMA public GRANT USAGE, SELECT, UPDATE ON SEQUENCES TO tenant_scoped;
+
+-- current_setting without a default fails when app.tenant_id is unset, so a request that
+-- forgot to pick a tenant sees an error rather than every tenant
+ALTER TABLE users ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON users
+  USING (tenant_id = current_setting('app.tenant_id')::int)
+  WITH CHECK (tenant_id = current_setting('app.tenant_id')::int);
+
+ALTER TABLE patients ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON patients
+  USING (tenant_id = current_setting('app.tenant_id')::int)
+  WITH CHECK (tenant_id = current_setting('app.tenant_id')::int);
+
+ALTER TABLE medical_records ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON medical_records
+  USING (tenant_id = current_setting('app.tenant_id')::int)
+  WITH CHECK (tenant_id = current_setting('app.tenant_id')::int);
+
+ALTER TABLE consents ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON consents
+  USING (tenant_id = current_setting('app.tenant_id')::int)
+  WITH CHECK (tenant_id = current_setting('app.tenant_id')::int);
+
+-- Down
+-- DROP POLICY tenant_isolation ON consents;
+-- ALTER TABLE consents DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON medical_records;
+-- ALTER TABLE medical_records DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON patients;
+-- ALTER TABLE patients DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON users;
+-- ALTER TABLE users DISABLE ROW LEVEL SECURITY;
+-- DROP TRIGGER consents_tenant ON consents;
+-- ALTER TABLE consents DROP COLUMN tenant_id;
+-- DROP TRIGGER medical_records_tenant ON medical_records;
+-- ALTER TABLE medical_records DROP COLUMN tenant_id;
+-- DROP FUNCTION patient_tenant();
+-- ALTER TABLE patients DROP CONSTRAINT patients_id_tenant_key;
+-- ALTER TABLE patients DROP COLUMN tenant_id;
+-- ALTER TABLE users DROP COLUMN tenant_id;
+-- DROP TABLE tenants;
+-- The tenant_scoped role is shared by the cluster and left in place.
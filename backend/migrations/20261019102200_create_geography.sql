@@ -0,0 +1,61 @@
+-- no-transaction
+-- Administrative geography (state -> district -> block -> village), and patients linked to
+-- their village by id instead of by free-text name
+CREATE TABLE geo_areas (
+  id SERIAL PRIMARY KEY,
+  level TEXT NOT NULL CHECK (level IN ('state', 'district', 'block', 'village')),
+  -- unique code, by default the parent's code and the name, e.g. UP-SITAPUR-MISRIKH-RAMPUR
+  code TEXT NOT NULL UNIQUE,
+  name TEXT NOT NULL,
+  -- the area one level up; states have none
+  parent_id INTEGER REFERENCES geo_areas(id),
+  latitude DOUBLE PRECISION CHECK (latitude BETWEEN -90 AND 90),
+  longitude DOUBLE PRECISION CHECK (longitude BETWEEN -180 AND 180),
+  -- GeoJSON Polygon or MultiPolygon geometry
+  boundary JSONB,
+  created_at TIMESTAMP NOT NULL DEFAULT now(),
+  updated_at TIMESTAMP NOT NULL DEFAULT now(),
+  CHECK ((level = 'state') = (parent_id IS NULL)),
+  CHECK ((latitude IS NULL) = (longitude IS NULL))
+);
+
+CREATE UNIQUE INDEX idx_geo_areas_sibling_name ON geo_areas (COALESCE(parent_id, 0), LOWER(name));
+CREATE INDEX idx_geo_areas_parent ON geo_areas (parent_id);
+CREATE INDEX idx_geo_areas_level_name ON geo_areas (level, LOWER(name));
+
+-- patients.village stays, as the linked village's name, for the readers that display it
+ALTER TABLE patients ADD COLUMN village_id INTEGER REFERENCES geo_areas(id);
+CREATE INDEX idx_patients_village ON patients (village_id);
+
+-- Villages typed so far are not placed anywhere yet: they become villages of an Unassigned
+-- block for admins to move under the right block. Spellings that differ only in case,
+-- spacing or punctuation share a code and so a village.
+CREATE FUNCTION pg_temp.area_code(name TEXT) RETURNS TEXT AS $$
+  SELECT upper(btrim(regexp_replace(name, '[^A-Za-z0-9]+', '-', 'g'), '-'))
+$$ LANGUAGE sql IMMUTABLE;
+
+INSERT INTO geo_areas (level, code, name) VALUES ('state', 'UNASSIGNED', 'Unassigned');
+INSERT INTO geo_areas (level, code, name, parent_id)
+SELECT 'district', 'UNASSIGNED-D', 'Unassigned', id FROM geo_areas WHERE code = 'UNASSIGNED';
+INSERT INTO geo_areas (level, code, name, parent_id)
+SELECT 'block', 'UNASSIGNED-B', 'Unassigned', id FROM geo_areas WHERE code = 'UNASSIGNED-D';
+
+INSERT INTO geo_areas (level, code, name, parent_id)
+SELECT 'village', 'UNASSIGNED-B-' || code, name, (SELECT id FROM geo_areas WHERE code = 'UNASSIGNED-B')
+FROM (
+    -- the most common spelling names the village
+    SELECT pg_temp.area_code(village) AS code,
+           mode() WITHIN GROUP (ORDER BY regexp_replace(btrim(village), '\s+', ' ', 'g')) AS name
+    FROM patients
+    WHERE pg_temp.area_code(village) <> ''
+    GROUP BY 1
+) typed;
+
+UPDATE patients p SET village_id = v.id, village = v.name
+FROM geo_areas v
+WHERE v.code = 'UNASSIGNED-B-' || pg_temp.area_code(p.village);
+UPDATE patients SET village = NULL WHERE village_id IS NULL;
+
+-- Down
+-- ALTER TABLE patients DROP COLUMN village_id;
+-- DROP TABLE geo_areas;
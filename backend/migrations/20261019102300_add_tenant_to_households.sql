@@ -0,0 +1,58 @@
+-- no-transaction
+-- Households and family links belong to a tenant like the patients in them
+ALTER TABLE households ADD COLUMN tenant_id INTEGER REFERENCES tenants(id);
+UPDATE households h SET tenant_id = COALESCE(
+  (SELECT tenant_id FROM patients WHERE id = h.head_patient_id),
+  (SELECT tenant_id FROM patients WHERE household_id = h.id ORDER BY id LIMIT 1),
+  1
+);
+-- members and heads registered with another tenant leave the household
+UPDATE patients p SET household_id = NULL
+FROM households h
+WHERE h.id = p.household_id AND h.tenant_id <> p.tenant_id;
+UPDATE households h SET head_patient_id = NULL
+FROM patients p
+WHERE p.id = h.head_patient_id AND p.tenant_id <> h.tenant_id;
+
+ALTER TABLE households
+  ALTER COLUMN tenant_id SET NOT NULL,
+  ADD CONSTRAINT households_id_tenant_key UNIQUE (id, tenant_id),
+  DROP CONSTRAINT households_head_patient_id_fkey,
+  ADD CONSTRAINT households_head_patient_tenant_fkey
+    FOREIGN KEY (head_patient_id, tenant_id) REFERENCES patients (id, tenant_id) ON DELETE SET NULL (head_patient_id);
+CREATE INDEX idx_households_tenant ON households (tenant_id);
+
+ALTER TABLE patients
+  DROP CONSTRAINT patients_household_id_fkey,
+  ADD CONSTRAINT patients_household_tenant_fkey
+    FOREIGN KEY (household_id, tenant_id) REFERENCES households (id, tenant_id) ON DELETE SET NULL (household_id);
+
+-- relatives are always patients of the same tenant
+DELETE FROM patient_relationships r
+USING patients a, patients b
+WHERE a.id = r.patient_id AND b.id = r.related_patient_id AND a.tenant_id <> b.tenant_id;
+
+ALTER TABLE households ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON households
+  USING (tenant_id = current_setting('app.tenant_id')::int)
+  WITH CHECK (tenant_id = current_setting('app.tenant_id')::int);
+
+-- patients is itself confined, so a link is visible when its patient is, and can only be
+-- made when both patients are
+ALTER TABLE patient_relationships ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON patient_relationships
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id))
+  WITH CHECK (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id)
+              AND EXISTS (SELECT 1 FROM patients p WHERE p.id = related_patient_id));
+
+-- Down
+-- DROP POLICY tenant_isolation ON patient_relationships;
+-- ALTER TABLE patient_relationships DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON households;
+-- ALTER TABLE households DISABLE ROW LEVEL SECURITY;
+-- ALTER TABLE patients DROP CONSTRAINT patients_household_tenant_fkey,
+--   ADD CONSTRAINT patients_household_id_fkey FOREIGN KEY (household_id) REFERENCES households(id) ON DELETE SET NULL;
+-- ALTER TABLE households DROP CONSTRAINT households_head_patient_tenant_fkey,
+--   ADD CONSTRAINT households_head_patient_id_fkey FOREIGN KEY (head_patient_id) REFERENCES patients(id) ON DELETE SET NULL;
+-- ALTER TABLE households DROP CONSTRAINT households_id_tenant_key;
+-- ALTER TABLE households DROP COLUMN tenant_id;
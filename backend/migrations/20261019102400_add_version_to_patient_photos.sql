@@ -0,0 +1,6 @@
+-- no-transaction
+-- Signed photo URLs name the version they were issued for, so replacing a photo retires them
+ALTER TABLE patient_photos ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- Down
+-- ALTER TABLE patient_photos DROP COLUMN version;
@@ -0,0 +1,12 @@
+-- no-transaction
+-- Blood pressure norms separate by sex in adolescence; upper limits are the 95th percentile at
+-- the 50th height percentile for age 17 (Fourth Report on High Blood Pressure in Children and
+-- Adolescents), critical limits as for the unisex band
+INSERT INTO vital_reference_ranges (kind, sex, min_age_months, max_age_months, low, high, critical_low, critical_high) VALUES
+  ('systolic_bp', 'male', 144, 216, 95, 136, 85, 170),
+  ('systolic_bp', 'female', 144, 216, 95, 128, 85, 170),
+  ('diastolic_bp', 'male', 144, 216, 55, 87, NULL, 110),
+  ('diastolic_bp', 'female', 144, 216, 55, 84, NULL, 110);
+
+-- Down
+-- DELETE FROM vital_reference_ranges WHERE sex IS NOT NULL;
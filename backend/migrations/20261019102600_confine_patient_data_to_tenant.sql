@@ -0,0 +1,170 @@
+-- no-transaction
+-- Everything recorded about a patient is confined to the patient's tenant. patients (and
+-- medical_records) are confined themselves, so a row is visible when the row it hangs off is,
+-- and can only be written for a patient of the current tenant.
+
+ALTER TABLE appointments ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON appointments
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE clinical_alerts ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON clinical_alerts
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE condition_mappings ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON condition_mappings
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE diagnoses ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON diagnoses
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE growth_measurements ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON growth_measurements
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE immunizations ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON immunizations
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE lab_orders ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON lab_orders
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE patient_care_team ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON patient_care_team
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE patient_photos ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON patient_photos
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE pregnancies ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON pregnancies
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE prescriptions ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON prescriptions
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE queue_entries ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON queue_entries
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE referrals ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON referrals
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE vital_sets ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON vital_sets
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+ALTER TABLE vital_observations ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON vital_observations
+  USING (EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id));
+
+-- through their parent rows
+ALTER TABLE anc_contacts ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON anc_contacts
+  USING (EXISTS (SELECT 1 FROM pregnancies pr WHERE pr.id = pregnancy_id));
+
+ALTER TABLE pregnancy_births ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON pregnancy_births
+  USING (EXISTS (SELECT 1 FROM pregnancies pr WHERE pr.id = pregnancy_id))
+  WITH CHECK (EXISTS (SELECT 1 FROM pregnancies pr WHERE pr.id = pregnancy_id)
+              AND (newborn_patient_id IS NULL OR EXISTS (SELECT 1 FROM patients p WHERE p.id = newborn_patient_id)));
+
+ALTER TABLE lab_order_tests ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON lab_order_tests
+  USING (EXISTS (SELECT 1 FROM lab_orders o WHERE o.id = order_id));
+
+ALTER TABLE lab_results ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON lab_results
+  USING (EXISTS (SELECT 1 FROM lab_orders o WHERE o.id = order_id));
+
+ALTER TABLE referral_notes ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON referral_notes
+  USING (EXISTS (SELECT 1 FROM referrals r WHERE r.id = referral_id));
+
+ALTER TABLE attachment_uploads ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON attachment_uploads
+  USING (EXISTS (SELECT 1 FROM medical_records r WHERE r.id = record_id));
+
+ALTER TABLE medical_reports ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON medical_reports
+  USING (EXISTS (SELECT 1 FROM medical_records r WHERE r.id = record_id));
+
+ALTER TABLE record_attachments ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON record_attachments
+  USING (EXISTS (SELECT 1 FROM medical_records r WHERE r.id = record_id));
+
+ALTER TABLE record_versions ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON record_versions
+  USING (EXISTS (SELECT 1 FROM medical_records r WHERE r.id = record_id));
+
+-- audit entries belong to the tenant of the patient they concern, or else of the user who
+-- made them
+ALTER TABLE audit_logs ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON audit_logs
+  USING (CASE WHEN patient_id IS NOT NULL
+              THEN EXISTS (SELECT 1 FROM patients p WHERE p.id = patient_id)
+              ELSE EXISTS (SELECT 1 FROM users u WHERE u.id = user_id) END);
+
+-- Stored content is shared by every record holding the same file, whichever tenant it is in,
+-- so whether it is still referenced is answered across tenants
+CREATE FUNCTION attachment_blob_in_use(blob TEXT) RETURNS BOOLEAN AS $$
+  SELECT EXISTS (SELECT 1 FROM record_attachments WHERE sha256 = blob)
+$$ LANGUAGE sql STABLE SECURITY DEFINER SET search_path = public;
+
+-- Down
+-- DROP FUNCTION attachment_blob_in_use(TEXT);
+-- DROP POLICY tenant_isolation ON audit_logs;
+-- ALTER TABLE audit_logs DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON record_versions;
+-- ALTER TABLE record_versions DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON record_attachments;
+-- ALTER TABLE record_attachments DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON medical_reports;
+-- ALTER TABLE medical_reports DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON attachment_uploads;
+-- ALTER TABLE attachment_uploads DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON referral_notes;
+-- ALTER TABLE referral_notes DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON lab_results;
+-- ALTER TABLE lab_results DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON lab_order_tests;
+-- ALTER TABLE lab_order_tests DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON pregnancy_births;
+-- ALTER TABLE pregnancy_births DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON anc_contacts;
+-- ALTER TABLE anc_contacts DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON vital_observations;
+-- ALTER TABLE vital_observations DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON vital_sets;
+-- ALTER TABLE vital_sets DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON referrals;
+-- ALTER TABLE referrals DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON queue_entries;
+-- ALTER TABLE queue_entries DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON prescriptions;
+-- ALTER TABLE prescriptions DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON pregnancies;
+-- ALTER TABLE pregnancies DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON patient_photos;
+-- ALTER TABLE patient_photos DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON patient_care_team;
+-- ALTER TABLE patient_care_team DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON lab_orders;
+-- ALTER TABLE lab_orders DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON immunizations;
+-- ALTER TABLE immunizations DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON growth_measurements;
+-- ALTER TABLE growth_measurements DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON diagnoses;
+-- ALTER TABLE diagnoses DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON condition_mappings;
+-- ALTER TABLE condition_mappings DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON clinical_alerts;
+-- ALTER TABLE clinical_alerts DISABLE ROW LEVEL SECURITY;
+-- DROP POLICY tenant_isolation ON appointments;
+-- ALTER TABLE appointments DISABLE ROW LEVEL SECURITY;
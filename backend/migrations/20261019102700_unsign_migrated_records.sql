@@ -0,0 +1,12 @@
+-- no-transaction
+-- 20261019100700 first marked the records finished before the workflow as signed, with no
+-- signer. Records signed through the workflow were always reviewed first, so the ones with
+-- neither a signer nor a review time are those: they go back to waiting on a signer.
+UPDATE medical_records
+SET status = 'reviewed',
+    signed_at = NULL,
+    submitted_at = COALESCE(submitted_at, updated_at, created_at)
+WHERE status = 'signed' AND signed_by IS NULL AND reviewed_at IS NULL;
+
+-- Down
+-- Nothing to undo: a signature without a signer is not restored.
@@ -1,3 +1,4 @@
 // ...existing code...
 
 pub mod db;
+pub mod state;
@@ -0,0 +1,146 @@
+use axum::extract::FromRef;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::services::attachments::StagingArea;
+use crate::services::auth::TokenKeys;
+use crate::services::growth::GrowthReferences;
+use crate::services::media::UrlSigner;
+use crate::services::storage::{BlobStore, FilesystemStore, S3Store};
+use crate::services::terminology::TerminologyFiles;
+
+/// Shared state handed to every router. Handlers extract only the parts they need.
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: PgPool,
+    pub storage: Arc<dyn BlobStore>,
+    pub url_signer: UrlSigner,
+    pub tokens: TokenKeys,
+    pub staging: StagingArea,
+    pub limits: UploadLimits,
+    pub terminology: TerminologyFiles,
+    pub growth: Arc<GrowthReferences>,
+}
+
+#[derive(Clone, Copy)]
+pub struct UploadLimits {
+    pub max_photo_bytes: usize,
+    pub max_attachment_bytes: u64,
+    /// Largest single chunk of a resumable attachment upload.
+    pub max_chunk_bytes: usize,
+}
+
+fn env_or(name: &str, default: &str) -> String {
+    std::env::var(name).unwrap_or_else(|_| default.to_string())
+}
+
+/// A secret from the environment, or a random one that only lives as long as the process.
+fn secret_or_random(name: &str, consequence: &str) -> Vec<u8> {
+    match std::env::var(name) {
+        Ok(secret) if !secret.is_empty() => secret.into_bytes(),
+        _ => {
+            tracing::warn!("{} is not set; {}", name, consequence);
+            format!("{}{}", uuid::Uuid::new_v4(), uuid::Uuid::new_v4()).into_bytes()
+        }
+    }
+}
+
+impl AppState {
+    pub fn from_env(pool: PgPool) -> Self {
+        let storage: Arc<dyn BlobStore> = match env_or("STORAGE_BACKEND", "filesystem").as_str() {
+            "s3" => {
+                let endpoint = std::env::var("S3_ENDPOINT").expect("S3_ENDPOINT must be set for the s3 storage backend");
+                Arc::new(S3Store::new(
+                    url::Url::parse(&endpoint).expect("S3_ENDPOINT must be a valid URL"),
+                    std::env::var("S3_BUCKET").expect("S3_BUCKET must be set for the s3 storage backend"),
+                    env_or("S3_REGION", "us-east-1"),
+                    std::env::var("S3_ACCESS_KEY").expect("S3_ACCESS_KEY must be set for the s3 storage backend"),
+                    std::env::var("S3_SECRET_KEY").expect("S3_SECRET_KEY must be set for the s3 storage backend"),
+                ))
+            }
+            "filesystem" => Arc::new(FilesystemStore::new(env_or("STORAGE_FS_ROOT", "./storage"))),
+            other => panic!("Unknown STORAGE_BACKEND '{}', expected 'filesystem' or 's3'", other),
+        };
+
+        let secret = secret_or_random("MEDIA_URL_SECRET", "signed media URLs will stop working on restart");
+        let jwt_secret = secret_or_random("JWT_SECRET", "access tokens will stop working on restart");
+        let jwt_ttl_seconds: i64 = env_or("JWT_TTL_SECONDS", "28800")
+            .parse()
+            .expect("JWT_TTL_SECONDS must be a number of seconds");
+        let ttl_seconds: i64 = env_or("MEDIA_URL_TTL_SECONDS", "900")
+            .parse()
+            .expect("MEDIA_URL_TTL_SECONDS must be a number of seconds");
+        let max_photo_bytes: usize = env_or("PHOTO_MAX_BYTES", "5242880")
+            .parse()
+            .expect("PHOTO_MAX_BYTES must be a number of bytes");
+        let max_attachment_bytes: u64 = env_or("ATTACHMENT_MAX_BYTES", "268435456")
+            .parse()
+            .expect("ATTACHMENT_MAX_BYTES must be a number of bytes");
+        let max_chunk_bytes: usize = env_or("ATTACHMENT_CHUNK_MAX_BYTES", "8388608")
+            .parse()
+            .expect("ATTACHMENT_CHUNK_MAX_BYTES must be a number of bytes");
+        let growth = GrowthReferences::load(env_or("GROWTH_TABLES_DIR", "./growth")).unwrap_or_else(|e| {
+            tracing::error!("Failed to load growth reference tables, z-scores will be missing: {}", e);
+            GrowthReferences::default()
+        });
+
+        AppState {
+            pool,
+            storage,
+            url_signer: UrlSigner::new(secret, chrono::Duration::seconds(ttl_seconds)),
+            tokens: TokenKeys::new(&jwt_secret, chrono::Duration::seconds(jwt_ttl_seconds)),
+            staging: StagingArea::new(env_or("ATTACHMENT_STAGING_DIR", "./storage/.staging")),
+            limits: UploadLimits { max_photo_bytes, max_attachment_bytes, max_chunk_bytes },
+            terminology: TerminologyFiles::new(env_or("TERMINOLOGY_DIR", "./terminology")),
+            growth: Arc::new(growth),
+        }
+    }
+}
+
+impl FromRef<AppState> for PgPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn BlobStore> {
+    fn from_ref(state: &AppState) -> Self {
+        state.storage.clone()
+    }
+}
+
+impl FromRef<AppState> for UrlSigner {
+    fn from_ref(state: &AppState) -> Self {
+        state.url_signer.clone()
+    }
+}
+
+impl FromRef<AppState> for UploadLimits {
+    fn from_ref(state: &AppState) -> Self {
+        state.limits
+    }
+}
+
+impl FromRef<AppState> for TokenKeys {
+    fn from_ref(state: &AppState) -> Self {
+        state.tokens.clone()
+    }
+}
+
+impl FromRef<AppState> for StagingArea {
+    fn from_ref(state: &AppState) -> Self {
+        state.staging.clone()
+    }
+}
+
+impl FromRef<AppState> for TerminologyFiles {
+    fn from_ref(state: &AppState) -> Self {
+        state.terminology.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<GrowthReferences> {
+    fn from_ref(state: &AppState) -> Self {
+        state.growth.clone()
+    }
+}
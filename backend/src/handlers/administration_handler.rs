@@ -1,54 +1,127 @@
-use axum::{Json, extract::State};
+use axum::{Json, extract::{Path, Query, State}};
 use axum::http::StatusCode;
 use serde::Deserialize;
 use bcrypt::{hash, DEFAULT_COST};
 use sqlx::Error as SqlxError;
+use crate::models::error::ServiceError;
+use crate::models::tenant::Tenant;
+use crate::services::access::{check_admin_role, check_platform_admin_role};
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::tenancy;
+use crate::services::validation::{require_optional_text, require_text, ValidationErrors};
+/// Fields the users table stores; the add-user form's profile fields (names,
+/// phone, licence, ...) have no column yet and are ignored on deserialize.
 #[derive(Deserialize)]
 pub struct NewUser {
-    pub first_name: String,
-    pub last_name: String,
     pub email: String,
-    pub phone: Option<String>,
     pub role: String,
+    /// Name or code of a department in the catalogue; may be left empty.
     pub department: String,
-    pub specialization: Option<String>,
-    pub license_number: Option<String>,
-    pub username: String,
+    /// Made the new user's primary facility.
+    pub facility_id: Option<i32>,
     pub password: String,
-    pub status: String,
-    pub permissions: Option<Vec<String>>,
+    /// Defaults to the creator's tenant; only platform admins may name another.
+    pub tenant_id: Option<i32>,
 }
+/// Add a member of staff to the creator's tenant. Admins only.
 pub async fn create_user(
+    user: AuthUser,
     State(pool): State<PgPool>,
     Json(payload): Json<NewUser>,
 ) -> Result<StatusCode, (StatusCode, String)> {
     use std::borrow::Cow;
+    let forbidden = |message: &str| (StatusCode::FORBIDDEN, message.to_string());
+    if !user.role.is_admin() {
+        return Err(forbidden("Only admins can add users"));
+    }
+    let role = StaffRole::parse(&payload.role).ok_or_else(|| {
+        (StatusCode::UNPROCESSABLE_ENTITY, format!("role must be one of: {}", StaffRole::allowed()))
+    })?;
+    let platform_admin = user.role == StaffRole::PlatformAdmin;
+    if role == StaffRole::PlatformAdmin && !platform_admin {
+        return Err(forbidden("Only platform admins can add platform admins"));
+    }
+    let tenant_id = payload.tenant_id.unwrap_or(user.tenant_id);
+    if tenant_id != user.tenant_id && !platform_admin {
+        return Err(forbidden("Only platform admins can add users to another tenant"));
+    }
     // Hash password
     let password_hash = hash(&payload.password, DEFAULT_COST)
         .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Password hash failed".to_string()))?;
 
+    let db_error = |e: SqlxError| (StatusCode::INTERNAL_SERVER_ERROR, format!("DB error: {}", e));
+    let mut tx = tenancy::begin(&pool, tenant_id).await.map_err(db_error)?;
+    let tenant_active: Option<bool> = sqlx::query_scalar("SELECT active FROM tenants WHERE id = $1")
+        .bind(tenant_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    if tenant_active != Some(true) {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "tenant_id is not an active tenant".to_string()));
+    }
+    let department = payload.department.trim();
+    let department_id: Option<i32> = if department.is_empty() {
+        None
+    } else {
+        let found = sqlx::query_scalar(
+            "SELECT id FROM departments WHERE active AND (LOWER(name) = LOWER($1) OR code = LOWER($1))",
+        )
+        .bind(department)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        Some(found.ok_or_else(|| {
+            (StatusCode::UNPROCESSABLE_ENTITY, format!("department '{}' is not in the catalogue", department))
+        })?)
+    };
+    if let Some(facility_id) = payload.facility_id {
+        let active: Option<bool> = sqlx::query_scalar("SELECT active FROM facilities WHERE id = $1")
+            .bind(facility_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        if active != Some(true) {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, "facility_id is not an active facility".to_string()));
+        }
+    }
+
     // Use sqlx::query for runtime queries
-    let res = sqlx::query(
-        r#"INSERT INTO users (email, password_hash, created_at)
-            VALUES ($1, $2, now())"#
+    let res = sqlx::query_scalar::<_, i32>(
+        r#"INSERT INTO users (email, password_hash, role, department_id, tenant_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, now()) RETURNING id"#
     )
     .bind(&payload.email)
     .bind(&password_hash)
-    .execute(&pool)
+    .bind(role.as_str())
+    .bind(department_id)
+    .bind(tenant_id)
+    .fetch_one(&mut *tx)
     .await;
 
-    match res {
-        Ok(_) => Ok(StatusCode::CREATED),
+    let user_id = match res {
+        Ok(id) => id,
         Err(SqlxError::Database(db_err)) if db_err.code() == Some(Cow::Borrowed("23505")) => {
             // Unique violation
-            Err((StatusCode::CONFLICT, "Email already exists".to_string()))
+            return Err((StatusCode::CONFLICT, "Email already exists".to_string()));
         }
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, format!("DB error: {}", e)))
+        Err(e) => return Err(db_error(e)),
+    };
+    if let Some(facility_id) = payload.facility_id {
+        sqlx::query("INSERT INTO user_facilities (user_id, facility_id, is_primary) VALUES ($1, $2, true)")
+            .bind(user_id)
+            .bind(facility_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
     }
+    tx.commit().await.map_err(db_error)?;
+    Ok(StatusCode::CREATED)
 }
 use serde::Serialize;
 use sqlx::PgPool;
-use crate::models::user::User;
+use crate::models::user::{StaffRole, User};
+use crate::services::validation::TextEnum;
 use crate::models::role::Role;
 
 
@@ -65,22 +138,167 @@ pub struct AdministrationResponse {
     pub roles: Vec<Role>,
 }
 
-pub async fn get_administration(State(pool): State<PgPool>) -> Json<AdministrationResponse> {
+#[derive(Deserialize)]
+pub struct AdministrationQuery {
+    /// Platform admins may look at another tenant's users; defaults to their own.
+    pub tenant_id: Option<i32>,
+}
+
+/// Users of the caller's tenant and the role catalogue. Admins only.
+pub async fn get_administration(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<AdministrationQuery>,
+) -> Result<Json<AdministrationResponse>, ServiceError> {
+    check_admin_role(&user)?;
+    let tenant_id = params.tenant_id.unwrap_or(user.tenant_id);
+    if tenant_id != user.tenant_id {
+        check_platform_admin_role(&user)?;
+    }
+    let mut tx = tenancy::begin(&pool, tenant_id).await.map_err(db_error)?;
     // Users
-    let users = sqlx::query_as!(User, "SELECT * FROM users")
-        .fetch_all(&pool).await.unwrap_or_default();
+    let users = sqlx::query_as!(User, "SELECT * FROM users WHERE tenant_id = $1", tenant_id)
+        .fetch_all(&mut *tx).await.map_err(db_error)?;
     let total_users = users.len() as i64;
     // Roles
     let roles = sqlx::query_as!(Role, "SELECT * FROM roles")
-        .fetch_all(&pool).await.unwrap_or_default();
+        .fetch_all(&mut *tx).await.map_err(db_error)?;
     let roles_count = roles.len() as i64;
 
-    Json(AdministrationResponse {
+    Ok(Json(AdministrationResponse {
         stats: AdministrationStats {
             total_users,
             roles: roles_count,
         },
         users,
         roles,
+    }))
+}
+
+fn db_error(e: SqlxError) -> ServiceError {
+    eprintln!("[ADMINISTRATION ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+#[derive(Deserialize)]
+pub struct CreateTenantRequest {
+    /// Short unique code, e.g. `ngo-north`.
+    pub code: String,
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateTenantRequest {
+    pub name: Option<String>,
+    /// Suspending a tenant stops its users signing in; its data is kept.
+    pub active: Option<bool>,
+}
+
+/// Every tenant for platform admins; a tenant admin sees their own.
+pub async fn list_tenants(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<Tenant>>, ServiceError> {
+    check_admin_role(&user)?;
+    let tenants = sqlx::query_as::<_, Tenant>(
+        "SELECT * FROM tenants WHERE $1 OR id = $2 ORDER BY name",
+    )
+    .bind(user.role == StaffRole::PlatformAdmin)
+    .bind(user.tenant_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+    Ok(Json(tenants))
+}
+
+/// Register a partner organisation. Its first admin is then added with `POST /api/users`
+/// and a `tenant_id`.
+pub async fn create_tenant(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<CreateTenantRequest>,
+) -> Result<(StatusCode, Json<Tenant>), ServiceError> {
+    check_platform_admin_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    require_text(&mut errors, "code", &mut payload.code);
+    require_text(&mut errors, "name", &mut payload.name);
+    errors.into_result()?;
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    let tenant = sqlx::query_as::<_, Tenant>("INSERT INTO tenants (code, name) VALUES ($1, $2) RETURNING *")
+        .bind(payload.code.to_lowercase())
+        .bind(&payload.name)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match &e {
+            SqlxError::Database(db) if db.is_unique_violation() => {
+                ServiceError::Conflict("A tenant with that code or name already exists".to_string())
+            }
+            _ => db_error(e),
+        })?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "tenant.created",
+        entity_type: "tenant",
+        entity_id: tenant.id,
+        patient_id: None,
+        details: serde_json::json!({ "code": tenant.code, "name": tenant.name }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(tenant)))
+}
+
+/// Rename, suspend or reinstate a tenant.
+pub async fn update_tenant(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<UpdateTenantRequest>,
+) -> Result<Json<Tenant>, ServiceError> {
+    check_platform_admin_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    require_optional_text(&mut errors, "name", &mut payload.name);
+    errors.into_result()?;
+    if id == user.tenant_id && payload.active == Some(false) {
+        return Err(ServiceError::Conflict("You cannot suspend your own tenant".to_string()));
+    }
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    let before = sqlx::query_as::<_, Tenant>("SELECT * FROM tenants WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?
+        .ok_or(ServiceError::NotFound)?;
+    let tenant = sqlx::query_as::<_, Tenant>(
+        "UPDATE tenants SET name = COALESCE($2, name), active = COALESCE($3, active) WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(&payload.name)
+    .bind(payload.active)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        SqlxError::Database(db) if db.is_unique_violation() => {
+            ServiceError::Conflict("A tenant with that name already exists".to_string())
+        }
+        _ => db_error(e),
+    })?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "tenant.updated",
+        entity_type: "tenant",
+        entity_id: id,
+        patient_id: None,
+        details: serde_json::json!({
+            "before": { "name": before.name, "active": before.active },
+            "after": { "name": tenant.name, "active": tenant.active },
+        }),
     })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(tenant))
 }
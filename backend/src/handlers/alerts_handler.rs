@@ -0,0 +1,233 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::models::alert::{CareTeamMember, ClinicalAlert};
+use crate::models::error::ServiceError;
+use crate::models::user::StaffRole;
+use crate::services::access::check_clinical_role;
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::tenancy;
+use crate::services::validation::{field_error, trim_optional};
+
+const MAX_ALERTS: i64 = 200;
+
+#[derive(Deserialize)]
+pub struct AssignCareTeamRequest {
+    pub user_id: i32,
+    pub role: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AlertQuery {
+    /// `open` (the default), `acknowledged` or `all`.
+    pub status: Option<String>,
+    pub patient_id: Option<i32>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[ALERTS ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+async fn ensure_patient(tx: &mut Transaction<'_, Postgres>, tenant_id: i32, id: i32) -> Result<(), ServiceError> {
+    let exists: Option<i32> = sqlx::query_scalar("SELECT id FROM patients WHERE id = $1 AND tenant_id = $2")
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    exists.map(|_| ()).ok_or(ServiceError::NotFound)
+}
+
+async fn fetch_care_team(tx: &mut Transaction<'_, Postgres>, patient_id: i32) -> Result<Vec<CareTeamMember>, ServiceError> {
+    sqlx::query_as::<_, CareTeamMember>(
+        "SELECT t.patient_id, t.user_id, u.email, u.role AS staff_role, t.role, t.assigned_by, t.assigned_at
+         FROM patient_care_team t
+         JOIN users u ON u.id = t.user_id
+         WHERE t.patient_id = $1
+         ORDER BY t.assigned_at, t.user_id",
+    )
+    .bind(patient_id)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(db_error)
+}
+
+pub async fn list_care_team(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<CareTeamMember>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    ensure_patient(&mut tx, user.tenant_id, patient_id).await?;
+    let team = fetch_care_team(&mut tx, patient_id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(team))
+}
+
+/// Add a member of staff to the patient's care team, or change their role on it.
+pub async fn assign_care_team_member(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<AssignCareTeamRequest>,
+) -> Result<Json<Vec<CareTeamMember>>, ServiceError> {
+    check_clinical_role(&user)?;
+    trim_optional(&mut payload.role);
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    ensure_patient(&mut tx, user.tenant_id, patient_id).await?;
+    let staff: Option<i32> = sqlx::query_scalar("SELECT id FROM users WHERE id = $1 AND tenant_id = $2")
+        .bind(payload.user_id)
+        .bind(user.tenant_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    if staff.is_none() {
+        return Err(field_error("user_id", "does not refer to an existing user"));
+    }
+
+    sqlx::query(
+        "INSERT INTO patient_care_team (patient_id, user_id, role, assigned_by)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (patient_id, user_id) DO UPDATE SET role = EXCLUDED.role",
+    )
+    .bind(patient_id)
+    .bind(payload.user_id)
+    .bind(&payload.role)
+    .bind(user.id)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "patient.care_team_assigned",
+        entity_type: "patient",
+        entity_id: patient_id,
+        patient_id: Some(patient_id),
+        details: serde_json::json!({ "user_id": payload.user_id, "role": payload.role }),
+    })
+    .await
+    .map_err(db_error)?;
+    let team = fetch_care_team(&mut tx, patient_id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(team))
+}
+
+pub async fn remove_care_team_member(
+    user: AuthUser,
+    Path((patient_id, member_id)): Path<(i32, i32)>,
+    State(pool): State<PgPool>,
+) -> Result<StatusCode, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    ensure_patient(&mut tx, user.tenant_id, patient_id).await?;
+    let removed = sqlx::query("DELETE FROM patient_care_team WHERE patient_id = $1 AND user_id = $2")
+        .bind(patient_id)
+        .bind(member_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    if removed.rows_affected() == 0 {
+        return Err(ServiceError::NotFound);
+    }
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "patient.care_team_removed",
+        entity_type: "patient",
+        entity_id: patient_id,
+        patient_id: Some(patient_id),
+        details: serde_json::json!({ "user_id": member_id }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Alerts for the caller: those addressed to them plus unaddressed ones for patients without
+/// a care team. Admins see every alert of their tenant, platform admins those of every tenant.
+pub async fn list_alerts(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<AlertQuery>,
+) -> Result<Json<Vec<ClinicalAlert>>, ServiceError> {
+    let acknowledged = match params.status.as_deref().map(str::trim) {
+        None | Some("") | Some("open") => Some(false),
+        Some("acknowledged") => Some(true),
+        Some("all") => None,
+        Some(_) => return Err(field_error("status", "must be one of: open, acknowledged, all")),
+    };
+    let platform_admin = user.role == StaffRole::PlatformAdmin;
+    let mut tx = if platform_admin {
+        pool.begin().await
+    } else {
+        tenancy::begin(&pool, user.tenant_id).await
+    }
+    .map_err(db_error)?;
+    let alerts = sqlx::query_as::<_, ClinicalAlert>(
+        "SELECT * FROM clinical_alerts a
+         WHERE ($1 OR recipient_id = $2 OR (recipient_id IS NULL AND $3))
+           AND ($4::boolean IS NULL OR (acknowledged_at IS NOT NULL) = $4)
+           AND ($5::int IS NULL OR patient_id = $5)
+           AND ($7 OR EXISTS (SELECT 1 FROM patients p WHERE p.id = a.patient_id AND p.tenant_id = $8))
+         ORDER BY created_at DESC, id DESC
+         LIMIT $6",
+    )
+    .bind(user.role.is_admin())
+    .bind(user.id)
+    .bind(check_clinical_role(&user).is_ok())
+    .bind(acknowledged)
+    .bind(params.patient_id)
+    .bind(MAX_ALERTS)
+    .bind(platform_admin)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(alerts))
+}
+
+pub async fn acknowledge_alert(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<ClinicalAlert>, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let alert = sqlx::query_as::<_, ClinicalAlert>(
+        "SELECT a.* FROM clinical_alerts a
+         JOIN patients p ON p.id = a.patient_id AND p.tenant_id = $2
+         WHERE a.id = $1",
+    )
+    .bind(id)
+    .bind(user.tenant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    if alert.recipient_id.is_some_and(|recipient| recipient != user.id) && !user.role.is_admin() {
+        return Err(ServiceError::Forbidden);
+    }
+    if alert.acknowledged_at.is_some() {
+        return Err(ServiceError::Conflict("Alert has already been acknowledged".to_string()));
+    }
+    let alert = sqlx::query_as::<_, ClinicalAlert>(
+        "UPDATE clinical_alerts SET acknowledged_at = now(), acknowledged_by = $2
+         WHERE id = $1 AND acknowledged_at IS NULL RETURNING *",
+    )
+    .bind(id)
+    .bind(user.id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::Conflict("Alert has already been acknowledged".to_string()))?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(alert))
+}
@@ -1,25 +1,114 @@
-use axum::{Json, extract::State};
-use serde::Serialize;
+use axum::{Json, extract::{Query, State}, http::header, response::IntoResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use sqlx::PgPool;
-use chrono::{Utc, Duration};
+use chrono::{NaiveDate, Utc, Duration};
+
+use crate::models::error::ServiceError;
+use crate::models::geography::GeoArea;
+use crate::services::analytics::{ComparisonRange, PeriodChange, ReportRange};
+use crate::services::auth::AuthUser;
+use crate::services::facilities::facility_scope;
+use crate::services::geography::{feature, feature_collection, GEOJSON_CONTENT_TYPE};
+use crate::services::queue::average_wait_minutes;
+use crate::services::tenancy;
+
+/// The average wait covers consultations started in this many days.
+const WAIT_TIME_DAYS: i64 = 30;
+
+/// Patients of the caller's tenant (`$2`) count towards a facility once they have a record,
+/// appointment or queue visit there.
+const PATIENT_SEEN_AT: &str = "p.tenant_id = $2 AND ($1::int[] IS NULL
+     OR EXISTS (SELECT 1 FROM medical_records r WHERE r.patient_id = p.id AND r.facility_id = ANY($1))
+     OR EXISTS (SELECT 1 FROM appointments a WHERE a.patient_id = p.id AND a.facility_id = ANY($1))
+     OR EXISTS (SELECT 1 FROM queue_entries q WHERE q.patient_id = p.id AND q.facility_id = ANY($1)))";
+
+/// Records of the caller's tenant, optionally at the facilities in `$1`.
+const RECORD_SCOPE: &str = "tenant_id = $2 AND ($1::int[] IS NULL OR facility_id = ANY($1))";
+
+/// Patients and the buckets they visited in, from records dated and queue arrivals between
+/// `$3` and `$4`, at the facilities in `$1` if any.
+fn visits_by_bucket(range: &ReportRange) -> String {
+    format!(
+        "SELECT patient_id, {record_bucket} AS bucket FROM medical_records
+         WHERE date BETWEEN $3 AND $4 AND ($1::int[] IS NULL OR facility_id = ANY($1))
+         UNION
+         SELECT patient_id, {queue_bucket} FROM queue_entries
+         WHERE arrived_at >= $3 AND arrived_at < $4::date + 1 AND ($1::int[] IS NULL OR facility_id = ANY($1))",
+        record_bucket = range.bucket_of("date"),
+        queue_bucket = range.bucket_of("arrived_at"),
+    )
+}
+
+/// Whether patient `p` had a record or queue visit between the dates in `from` and `to`,
+/// at the facilities in `$1` if any.
+fn active_between(from: &str, to: &str) -> String {
+    format!(
+        "(EXISTS (SELECT 1 FROM medical_records r WHERE r.patient_id = p.id AND r.date BETWEEN {from} AND {to}
+                 AND ($1::int[] IS NULL OR r.facility_id = ANY($1)))
+          OR EXISTS (SELECT 1 FROM queue_entries q WHERE q.patient_id = p.id
+                 AND q.arrived_at >= {from} AND q.arrived_at < {to}::date + 1
+                 AND ($1::int[] IS NULL OR q.facility_id = ANY($1))))"
+    )
+}
+
+#[derive(Deserialize)]
+pub struct AnalyticsQuery {
+    pub facility_id: Option<i32>,
+    /// Only the caller's facilities.
+    pub my_facilities: Option<bool>,
+    /// The disease trend covers `from` to `to`, split into `day`, `week` or `month` buckets.
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub granularity: Option<String>,
+    /// What village growth compares the range with: `previous_period` (default) or
+    /// `previous_year`.
+    pub compare: Option<String>,
+}
 
 #[derive(Serialize)]
 pub struct StatsResponse {
     pub total_patients: i64,
     pub total_records: i64,
     pub consultations_mtd: i64,
+    /// Consultation records dated from `from` to `to`.
+    pub consultations_in_range: i64,
     pub completed: i64,
     pub pending: i64,
     pub lab_results: i64,
-    pub avg_wait_time: Option<String>, // Placeholder
+    /// Arrival to consultation over the last 30 days, e.g. "18 min".
+    pub avg_wait_time: Option<String>,
     pub data_completeness: Option<String>, // Placeholder
 }
 
+#[derive(Deserialize)]
+pub struct VillageMapQuery {
+    pub facility_id: Option<i32>,
+    pub my_facilities: Option<bool>,
+    /// Only villages under this state, district or block.
+    pub within: Option<i32>,
+}
+
 #[derive(Serialize)]
 pub struct VillageDistribution {
+    pub village_id: i32,
     pub name: String,
     pub patients: i64,
-    pub growth: String, // Placeholder
+    /// Change in new registrations, e.g. "+25%".
+    pub growth: String,
+    /// Patients registered in the range, against the comparison range.
+    pub new_registrations: PeriodChange,
+    /// Patients with a record or queue visit in the range, against the comparison range.
+    pub active_patients: PeriodChange,
+    /// One point per bucket of the range, for a sparkline.
+    pub trend: Vec<VillageTrend>,
+}
+
+#[derive(Serialize)]
+pub struct VillageTrend {
+    pub period: NaiveDate,
+    pub new_registrations: i64,
+    pub active_patients: i64,
 }
 
 #[derive(Serialize)]
@@ -28,109 +117,281 @@ pub struct ConditionDistribution {
     pub percentage: f32,
 }
 
+/// Records dated in one bucket of the range.
 #[derive(Serialize)]
 pub struct DiseaseTrend {
-    pub month: String,
+    /// First day of the bucket.
+    pub period: NaiveDate,
+    pub label: String,
     pub value: i64,
 }
 
 #[derive(Serialize)]
 pub struct AnalyticsResponse {
+    pub range: ReportRange,
+    pub comparison: ComparisonRange,
     pub stats: StatsResponse,
     pub villages: Vec<VillageDistribution>,
     pub conditions: Vec<ConditionDistribution>,
     pub disease_trend: Vec<DiseaseTrend>,
 }
 
-pub async fn get_analytics(State(pool): State<PgPool>) -> Json<AnalyticsResponse> {
+/// Dashboard figures for the caller's tenant, across every facility or limited with
+/// `facility_id` / `my_facilities`. The trend has one bucket per period of the range, empty
+/// ones included, oldest first.
+pub async fn get_analytics(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<AnalyticsQuery>,
+) -> Result<Json<AnalyticsResponse>, ServiceError> {
+    let today = Utc::now().date_naive();
+    let range = ReportRange::resolve(params.from, params.to, params.granularity.as_deref(), today)?;
+    let comparison = ComparisonRange::resolve(&range, params.compare.as_deref())?;
+    let facility_ids = facility_scope(&pool, user.id, params.facility_id, params.my_facilities)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    let tenant_id = user.tenant_id;
+    let mut tx = tenancy::begin(&pool, tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+
     // Total patients
-    let total_patients: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM patients")
-        .fetch_one(&pool).await.unwrap_or((0,));
+    let total_patients: (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM patients p WHERE {PATIENT_SEEN_AT}"))
+        .bind(&facility_ids)
+        .bind(tenant_id)
+        .fetch_one(&mut *tx).await.map_err(|_| ServiceError::InternalServerError)?;
     // Total records
-    let total_records: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM medical_records")
-        .fetch_one(&pool).await.unwrap_or((0,));
-    // Consultations MTD
-    let mtd_prefix = Utc::now().format("%Y-%m").to_string();
-    let consultations_mtd: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM medical_records WHERE date LIKE $1 || '%'"
-    )
-    .bind(&mtd_prefix)
-    .fetch_one(&pool).await.unwrap_or((0,));
+    let total_records: (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM medical_records WHERE {RECORD_SCOPE}"))
+        .bind(&facility_ids)
+        .bind(tenant_id)
+        .fetch_one(&mut *tx).await.map_err(|_| ServiceError::InternalServerError)?;
+    // Consultations this month, and in the range
+    let (consultations_mtd, consultations_in_range): (i64, i64) = sqlx::query_as(&format!(
+        "SELECT COUNT(*) FILTER (WHERE date >= date_trunc('month', $3::date) AND date <= $3),
+                COUNT(*) FILTER (WHERE date BETWEEN $4 AND $5)
+         FROM medical_records WHERE LOWER(record_type) = 'consultation' AND {RECORD_SCOPE}"
+    ))
+    .bind(&facility_ids)
+    .bind(tenant_id)
+    .bind(today)
+    .bind(range.from)
+    .bind(range.to)
+    .fetch_one(&mut *tx).await.map_err(|_| ServiceError::InternalServerError)?;
     // Completed, pending, lab_results
-    let completed: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM medical_records WHERE LOWER(status) = 'completed'"
-    ).fetch_one(&pool).await.unwrap_or((0,));
-    let pending: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM medical_records WHERE LOWER(status) = 'pending'"
-    ).fetch_one(&pool).await.unwrap_or((0,));
-    let lab_results: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM medical_records WHERE LOWER(record_type) = 'lab'"
-    ).fetch_one(&pool).await.unwrap_or((0,));
-
-    // Village distribution
-    let villages = sqlx::query!(
-        "SELECT village as name, COUNT(*) as patients FROM patients WHERE village IS NOT NULL GROUP BY village"
-    )
-    .fetch_all(&pool).await.unwrap_or_default()
-    .into_iter()
-    .map(|row| VillageDistribution {
-        name: row.name.unwrap_or_default(),
-        patients: row.patients.unwrap_or(0),
-        growth: "+0%".to_string(),
-    }).collect();
+    let completed: (i64,) = sqlx::query_as(&format!(
+        "SELECT COUNT(*) FROM medical_records WHERE status = 'signed' AND {RECORD_SCOPE}"
+    )).bind(&facility_ids).bind(tenant_id).fetch_one(&mut *tx).await.map_err(|_| ServiceError::InternalServerError)?;
+    let pending: (i64,) = sqlx::query_as(&format!(
+        "SELECT COUNT(*) FROM medical_records WHERE status <> 'signed' AND {RECORD_SCOPE}"
+    )).bind(&facility_ids).bind(tenant_id).fetch_one(&mut *tx).await.map_err(|_| ServiceError::InternalServerError)?;
+    let lab_results: (i64,) = sqlx::query_as(&format!(
+        "SELECT COUNT(*) FROM medical_records WHERE LOWER(record_type) = 'lab' AND {RECORD_SCOPE}"
+    )).bind(&facility_ids).bind(tenant_id).fetch_one(&mut *tx).await.map_err(|_| ServiceError::InternalServerError)?;
 
-    // Condition distribution
-    let condition_rows = sqlx::query!(
-        "SELECT unnest(active_conditions) as condition FROM patients WHERE active_conditions IS NOT NULL"
-    )
-    .fetch_all(&pool).await.unwrap_or_default();
-    let mut condition_map = std::collections::HashMap::new();
-    for row in &condition_rows {
-        if let Some(cond) = &row.condition {
-            *condition_map.entry(cond.clone()).or_insert(0) += 1;
-        }
-    }
-    let total_conditions: i64 = condition_map.values().sum();
-    let conditions = condition_map.into_iter().map(|(condition, count)| ConditionDistribution {
+    // Condition distribution: open coded diagnoses grouped by code, plus free-text conditions
+    // that have not been through the condition mapping review yet
+    let condition_rows: Vec<(String, i64)> = sqlx::query_as(&format!(
+        "SELECT MIN(label), COUNT(DISTINCT patient_id) FROM (
+             SELECT patient_id, system || ':' || code AS key, display AS label
+             FROM diagnoses WHERE resolved_date IS NULL
+             UNION ALL
+             SELECT p.id, 'text:' || lower(regexp_replace(btrim(t.condition), '\\s+', ' ', 'g')), btrim(t.condition)
+             FROM patients p, unnest(p.active_conditions) AS t(condition)
+             WHERE btrim(t.condition) <> ''
+               AND NOT EXISTS (
+                   SELECT 1 FROM condition_mappings m
+                   WHERE m.patient_id = p.id AND m.status <> 'pending'
+                     AND m.normalized_text = lower(regexp_replace(btrim(t.condition), '\\s+', ' ', 'g')))
+         ) c
+         WHERE EXISTS (SELECT 1 FROM patients p WHERE p.id = c.patient_id AND {PATIENT_SEEN_AT})
+         GROUP BY key"
+    ))
+    .bind(&facility_ids)
+    .bind(tenant_id)
+    .fetch_all(&mut *tx).await.map_err(|_| ServiceError::InternalServerError)?;
+    let total_conditions: i64 = condition_rows.iter().map(|(_, count)| count).sum();
+    let conditions = condition_rows.into_iter().map(|(condition, count)| ConditionDistribution {
         condition,
         percentage: if total_conditions > 0 { (count as f32 / total_conditions as f32) * 100.0 } else { 0.0 },
     }).collect();
 
-    // Disease trend (last 7 months, by record date)
-    let mut trend_map: std::collections::HashMap<String, i64> = std::collections::HashMap::new();
-    let now = Utc::now();
-    for i in 0..7 {
-        let month = now - Duration::days(30 * i);
-        let key = month.format("%b").to_string();
-        trend_map.insert(key.clone(), 0);
-    }
-    let trend_rows = sqlx::query!("SELECT date FROM medical_records")
-        .fetch_all(&pool).await.unwrap_or_default();
-    for row in &trend_rows {
-        let date = row.date;
-        let key = date.format("%b").to_string();
-        if let Some(v) = trend_map.get_mut(&key) {
-            *v += 1;
-        }
-    }
-    let mut disease_trend: Vec<DiseaseTrend> = trend_map.into_iter().map(|(month, value)| DiseaseTrend { month, value }).collect();
-    disease_trend.sort_by_key(|d| d.month.clone());
+    // Disease trend: records per bucket of the range, by record date
+    let trend_rows: Vec<(NaiveDate, i64)> = sqlx::query_as(&format!(
+        "WITH buckets AS ({buckets}),
+         counts AS (
+             SELECT {bucket} AS bucket, COUNT(*) AS value FROM medical_records
+             WHERE date BETWEEN $3 AND $4 AND {RECORD_SCOPE}
+             GROUP BY 1
+         )
+         SELECT b.bucket, COALESCE(c.value, 0) FROM buckets b LEFT JOIN counts c USING (bucket)
+         ORDER BY b.bucket",
+        buckets = range.buckets("$3", "$4"),
+        bucket = range.bucket_of("date"),
+    ))
+    .bind(&facility_ids)
+    .bind(tenant_id)
+    .bind(range.from)
+    .bind(range.to)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|_| ServiceError::InternalServerError)?;
+    let disease_trend: Vec<DiseaseTrend> = trend_rows
+        .into_iter()
+        .map(|(period, value)| DiseaseTrend { period, label: range.granularity.label(period), value })
+        .collect();
+
+    // Village distribution, with registrations and active patients in the range against the
+    // comparison range
+    let village_rows: Vec<(i32, String, i64, i64, i64, i64, i64)> = sqlx::query_as(&format!(
+        "SELECT v.id, v.name, COUNT(*),
+                COUNT(*) FILTER (WHERE p.created_at >= $3 AND p.created_at < $4::date + 1),
+                COUNT(*) FILTER (WHERE p.created_at >= $5 AND p.created_at < $6::date + 1),
+                COUNT(*) FILTER (WHERE {active}),
+                COUNT(*) FILTER (WHERE {active_before})
+         FROM patients p JOIN geo_areas v ON v.id = p.village_id
+         WHERE {PATIENT_SEEN_AT} GROUP BY v.id, v.name ORDER BY COUNT(*) DESC, v.name",
+        active = active_between("$3", "$4"),
+        active_before = active_between("$5", "$6"),
+    ))
+    .bind(&facility_ids)
+    .bind(tenant_id)
+    .bind(range.from)
+    .bind(range.to)
+    .bind(comparison.from)
+    .bind(comparison.to)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|_| ServiceError::InternalServerError)?;
+    // (village, bucket) -> (new registrations, active patients)
+    let village_trend_rows: Vec<(i32, NaiveDate, i64, i64)> = sqlx::query_as(&format!(
+        "SELECT p.village_id, b.bucket,
+                COUNT(*) FILTER (WHERE b.registered),
+                COUNT(*) FILTER (WHERE NOT b.registered)
+         FROM (
+             SELECT id AS patient_id, {registered_bucket} AS bucket, true AS registered FROM patients
+             WHERE created_at >= $3 AND created_at < $4::date + 1
+             UNION ALL
+             SELECT patient_id, bucket, false FROM ({visits}) v
+         ) b
+         JOIN patients p ON p.id = b.patient_id
+         WHERE p.village_id IS NOT NULL AND {PATIENT_SEEN_AT}
+         GROUP BY p.village_id, b.bucket",
+        registered_bucket = range.bucket_of("created_at"),
+        visits = visits_by_bucket(&range),
+    ))
+    .bind(&facility_ids)
+    .bind(tenant_id)
+    .bind(range.from)
+    .bind(range.to)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|_| ServiceError::InternalServerError)?;
+    let village_trend: std::collections::HashMap<(i32, NaiveDate), (i64, i64)> = village_trend_rows
+        .into_iter()
+        .map(|(village_id, bucket, registered, active)| ((village_id, bucket), (registered, active)))
+        .collect();
+    let villages = village_rows
+        .into_iter()
+        .map(|(village_id, name, patients, new_now, new_before, active_now, active_before)| {
+            let new_registrations = PeriodChange::new(new_now, new_before);
+            let trend = disease_trend
+                .iter()
+                .map(|bucket| {
+                    let (new_registrations, active_patients) =
+                        village_trend.get(&(village_id, bucket.period)).copied().unwrap_or_default();
+                    VillageTrend { period: bucket.period, new_registrations, active_patients }
+                })
+                .collect();
+            VillageDistribution {
+                village_id,
+                name,
+                patients,
+                growth: new_registrations.summary(),
+                new_registrations,
+                active_patients: PeriodChange::new(active_now, active_before),
+                trend,
+            }
+        })
+        .collect();
+
+    // Wait time from the clinic queue
+    let wait_since = today - Duration::days(WAIT_TIME_DAYS);
+    let avg_wait_time = average_wait_minutes(&mut *tx, facility_ids.as_deref(), tenant_id, wait_since)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?
+        .map(|minutes| format!("{:.0} min", minutes));
 
     let stats = StatsResponse {
         total_patients: total_patients.0,
         total_records: total_records.0,
-        consultations_mtd: consultations_mtd.0,
+        consultations_mtd,
+        consultations_in_range,
         completed: completed.0,
         pending: pending.0,
         lab_results: lab_results.0,
-        avg_wait_time: None, // Placeholder
+        avg_wait_time,
         data_completeness: None, // Placeholder
     };
 
-    Json(AnalyticsResponse {
+    Ok(Json(AnalyticsResponse {
+        range,
+        comparison,
         stats,
         villages,
         conditions,
         disease_trend,
-    })
+    }))
+}
+
+/// Villages as a GeoJSON feature collection with the caller's patients counted per village,
+/// for map views. Villages without patients are included with zero counts.
+pub async fn get_village_map(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<VillageMapQuery>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let facility_ids = facility_scope(&pool, user.id, params.facility_id, params.my_facilities)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    let villages: Vec<GeoArea> = sqlx::query_as(
+        "WITH RECURSIVE within AS (
+             SELECT id FROM geo_areas WHERE id = $1
+             UNION ALL
+             SELECT g.id FROM geo_areas g JOIN within w ON g.parent_id = w.id
+         )
+         SELECT * FROM geo_areas
+         WHERE level = 'village' AND ($1::int IS NULL OR id IN (SELECT id FROM within))
+         ORDER BY name, id",
+    )
+    .bind(params.within)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|_| ServiceError::InternalServerError)?;
+    let counts: Vec<(i32, i64, i64, i64)> = sqlx::query_as(&format!(
+        "SELECT p.village_id, COUNT(*),
+                COUNT(*) FILTER (WHERE p.status = 'active'),
+                COUNT(*) FILTER (WHERE p.critical_flag)
+         FROM patients p WHERE p.village_id IS NOT NULL AND {PATIENT_SEEN_AT}
+         GROUP BY p.village_id"
+    ))
+    .bind(&facility_ids)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|_| ServiceError::InternalServerError)?;
+    tx.commit().await.map_err(|_| ServiceError::InternalServerError)?;
+
+    let counts: std::collections::HashMap<i32, (i64, i64, i64)> =
+        counts.into_iter().map(|(id, patients, active, critical)| (id, (patients, active, critical))).collect();
+    let features = villages
+        .iter()
+        .map(|village| {
+            let (patients, active, critical) = counts.get(&village.id).copied().unwrap_or_default();
+            feature(village, json!({ "patients": patients, "active_patients": active, "critical_patients": critical }))
+        })
+        .collect();
+    Ok(([(header::CONTENT_TYPE, GEOJSON_CONTENT_TYPE)], Json(feature_collection(features))))
 }
@@ -0,0 +1,854 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use crate::models::appointment::{
+    Appointment, AppointmentAction, AppointmentStatus, AppointmentType, ProviderAvailability, Slot,
+};
+use crate::models::error::ServiceError;
+use crate::models::user::StaffRole;
+use crate::services::access::check_clinical_role;
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::facilities::{facility_scope, optional_facility, require_facility};
+use crate::services::scheduling::{free_slots, session_for, sessions_on, sync_next_visit, DEFAULT_APPOINTMENT_MINUTES};
+use crate::services::tenancy;
+use crate::services::validation::{
+    field_error, normalize_optional_enum, trim_optional, TextEnum, ValidationErrors,
+};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+/// Bookings may start this far in the past, so a walk-in can be booked into the current slot.
+const BOOKING_GRACE_MINUTES: i64 = 15;
+
+#[derive(Deserialize)]
+pub struct BookAppointmentRequest {
+    pub patient_id: i32,
+    /// A clinician or admin; leave out for an unassigned follow-up.
+    pub provider_id: Option<i32>,
+    /// Defaults to the facility of the provider's session, then the booker's primary facility.
+    pub facility_id: Option<i32>,
+    /// Defaults to `consultation`.
+    pub appointment_type: Option<String>,
+    pub starts_at: NaiveDateTime,
+    /// Defaults to one slot of the provider's session.
+    pub ends_at: Option<NaiveDateTime>,
+    pub reason: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RescheduleRequest {
+    pub starts_at: NaiveDateTime,
+    /// Defaults to the appointment's current length.
+    pub ends_at: Option<NaiveDateTime>,
+    /// Move the appointment to another provider; keeps the current one when left out.
+    pub provider_id: Option<i32>,
+    pub facility_id: Option<i32>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct CancelAppointmentRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AppointmentQuery {
+    pub patient_id: Option<i32>,
+    pub provider_id: Option<i32>,
+    pub facility_id: Option<i32>,
+    /// Only appointments at the caller's facilities.
+    pub my_facilities: Option<bool>,
+    pub status: Option<String>,
+    pub appointment_type: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct AvailabilityRequest {
+    /// Defaults to the provider's primary facility.
+    pub facility_id: Option<i32>,
+    /// ISO weekday, 1 = Monday .. 7 = Sunday.
+    pub weekday: i16,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub slot_minutes: Option<i32>,
+    pub valid_from: Option<NaiveDate>,
+    pub valid_until: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct ScheduleQuery {
+    /// Defaults to today.
+    pub date: Option<NaiveDate>,
+    pub facility_id: Option<i32>,
+    pub my_facilities: Option<bool>,
+    pub provider_id: Option<i32>,
+}
+
+#[derive(Serialize, FromRow)]
+pub struct AppointmentSummary {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub appointment: Appointment,
+    pub patient_name: String,
+    pub provider_email: Option<String>,
+    pub facility_name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ProviderSchedule {
+    pub provider_id: i32,
+    pub provider_email: String,
+    pub sessions: Vec<ProviderAvailability>,
+    pub appointments: Vec<AppointmentSummary>,
+    pub free_slots: Vec<Slot>,
+}
+
+#[derive(Serialize)]
+pub struct DailySchedule {
+    pub date: NaiveDate,
+    /// The facilities the schedule was limited to, if any.
+    pub facility_ids: Option<Vec<i32>>,
+    pub providers: Vec<ProviderSchedule>,
+    /// Appointments booked without a provider.
+    pub unassigned: Vec<AppointmentSummary>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[APPOINTMENT ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+const SUMMARY_SELECT: &str = "SELECT a.*, p.first_name || ' ' || p.last_name AS patient_name, u.email AS provider_email,
+            f.name AS facility_name
+     FROM appointments a
+     JOIN patients p ON p.id = a.patient_id
+     LEFT JOIN users u ON u.id = a.provider_id
+     LEFT JOIN facilities f ON f.id = a.facility_id";
+
+async fn lock_appointment(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: i32,
+    id: i32,
+) -> Result<Appointment, ServiceError> {
+    sqlx::query_as::<_, Appointment>(
+        "SELECT a.* FROM appointments a JOIN patients p ON p.id = a.patient_id AND p.tenant_id = $2
+         WHERE a.id = $1 FOR UPDATE OF a",
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)
+}
+
+async fn load_summary(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: i32,
+    id: i32,
+) -> Result<AppointmentSummary, ServiceError> {
+    sqlx::query_as::<_, AppointmentSummary>(&format!("{SUMMARY_SELECT} WHERE a.id = $1 AND p.tenant_id = $2"))
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?
+        .ok_or(ServiceError::NotFound)
+}
+
+fn transition_error(action: AppointmentAction, appointment: &Appointment) -> ServiceError {
+    ServiceError::Conflict(format!(
+        "An appointment that is {} cannot be {}",
+        appointment.status.replace('_', " "),
+        action.past_tense()
+    ))
+}
+
+fn current_status(appointment: &Appointment) -> Result<AppointmentStatus, ServiceError> {
+    AppointmentStatus::parse(&appointment.status).ok_or(ServiceError::InternalServerError)
+}
+
+/// Lock a provider of the tenant so concurrent bookings for them are checked one at a time,
+/// and make sure they can see patients.
+async fn lock_provider(tx: &mut Transaction<'_, Postgres>, tenant_id: i32, provider_id: i32) -> Result<(), ServiceError> {
+    let role: Option<String> = sqlx::query_scalar("SELECT role FROM users WHERE id = $1 AND tenant_id = $2 FOR UPDATE")
+        .bind(provider_id)
+        .bind(tenant_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    match role.as_deref().and_then(StaffRole::parse) {
+        Some(StaffRole::Admin | StaffRole::PlatformAdmin | StaffRole::Clinician) => Ok(()),
+        Some(StaffRole::Technician) => Err(field_error("provider_id", "must be a clinician or admin")),
+        None => Err(field_error("provider_id", "does not refer to an existing user")),
+    }
+}
+
+/// A slot being booked or moved to.
+struct SlotRequest {
+    tenant_id: i32,
+    patient_id: i32,
+    provider_id: Option<i32>,
+    facility_id: Option<i32>,
+    starts_at: NaiveDateTime,
+    ends_at: Option<NaiveDateTime>,
+    /// Used when neither `ends_at` nor a session gives the length.
+    default_minutes: i64,
+    /// The appointment being rescheduled, which does not conflict with itself.
+    appointment_id: Option<i32>,
+}
+
+/// Check a slot against the provider's availability and existing appointments, returning the
+/// facility and end time to store.
+///
+/// Providers without any availability templates can be booked at any time. Conflicts are
+/// overlapping booked, checked-in or seen appointments for the same provider or patient.
+async fn check_slot(
+    tx: &mut Transaction<'_, Postgres>,
+    slot: SlotRequest,
+) -> Result<(Option<i32>, NaiveDateTime), ServiceError> {
+    let mut facility_id = slot.facility_id;
+    let mut ends_at = slot.ends_at;
+    if let Some(provider_id) = slot.provider_id {
+        lock_provider(tx, slot.tenant_id, provider_id).await?;
+        let templates = sqlx::query_as::<_, ProviderAvailability>(
+            "SELECT * FROM provider_availability WHERE provider_id = $1",
+        )
+        .bind(provider_id)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(db_error)?;
+        if !templates.is_empty() {
+            let end_for_check = ends_at.unwrap_or(slot.starts_at + Duration::minutes(1));
+            let session = session_for(&templates, slot.starts_at, end_for_check, facility_id)
+                .ok_or_else(|| field_error("starts_at", "is outside the provider's availability"))?;
+            facility_id.get_or_insert(session.facility_id);
+            let session_end = slot.starts_at.date().and_time(session.end_time);
+            let slot_end = slot.starts_at + Duration::minutes(i64::from(session.slot_minutes));
+            ends_at.get_or_insert(slot_end.min(session_end));
+        }
+    }
+    let ends_at = ends_at.unwrap_or(slot.starts_at + Duration::minutes(slot.default_minutes));
+    if ends_at <= slot.starts_at {
+        return Err(field_error("ends_at", "must be after starts_at"));
+    }
+
+    let conflict = sqlx::query_as::<_, Appointment>(
+        "SELECT * FROM appointments
+         WHERE status = ANY($1) AND ($2::int IS NULL OR id <> $2)
+           AND (provider_id = $3 OR patient_id = $4)
+           AND starts_at < $6 AND $5 < ends_at
+         ORDER BY starts_at LIMIT 1",
+    )
+    .bind(AppointmentStatus::ACTIVE)
+    .bind(slot.appointment_id)
+    .bind(slot.provider_id)
+    .bind(slot.patient_id)
+    .bind(slot.starts_at)
+    .bind(ends_at)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    if let Some(conflict) = conflict {
+        let whose = if conflict.patient_id == slot.patient_id { "The patient" } else { "The provider" };
+        return Err(ServiceError::Conflict(format!(
+            "{} already has appointment #{} from {} to {}",
+            whose,
+            conflict.id,
+            conflict.starts_at.format("%Y-%m-%d %H:%M"),
+            conflict.ends_at.format("%H:%M")
+        )));
+    }
+    Ok((facility_id, ends_at))
+}
+
+pub async fn list_appointments(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(mut params): Query<AppointmentQuery>,
+) -> Result<Json<Vec<AppointmentSummary>>, ServiceError> {
+    let mut errors = ValidationErrors::default();
+    normalize_optional_enum::<AppointmentStatus>(&mut errors, "status", &mut params.status);
+    normalize_optional_enum::<AppointmentType>(&mut errors, "appointment_type", &mut params.appointment_type);
+    errors.into_result()?;
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let facilities = facility_scope(&pool, user.id, params.facility_id, params.my_facilities)
+        .await
+        .map_err(db_error)?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let appointments = sqlx::query_as::<_, AppointmentSummary>(&format!(
+        "{SUMMARY_SELECT}
+         WHERE p.tenant_id = $9
+           AND ($1::int IS NULL OR a.patient_id = $1)
+           AND ($2::int IS NULL OR a.provider_id = $2)
+           AND ($3::int[] IS NULL OR a.facility_id = ANY($3))
+           AND ($4::text IS NULL OR a.status = $4)
+           AND ($5::text IS NULL OR a.appointment_type = $5)
+           AND ($6::date IS NULL OR a.starts_at >= $6::date)
+           AND ($7::date IS NULL OR a.starts_at < $7::date + 1)
+         ORDER BY a.starts_at, a.id
+         LIMIT $8"
+    ))
+    .bind(params.patient_id)
+    .bind(params.provider_id)
+    .bind(&facilities)
+    .bind(&params.status)
+    .bind(&params.appointment_type)
+    .bind(params.from)
+    .bind(params.to)
+    .bind(limit)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(appointments))
+}
+
+/// A patient's appointments, latest first.
+pub async fn list_patient_appointments(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<AppointmentSummary>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let appointments = sqlx::query_as::<_, AppointmentSummary>(&format!(
+        "{SUMMARY_SELECT} WHERE a.patient_id = $1 AND p.tenant_id = $2 ORDER BY a.starts_at DESC, a.id DESC"
+    ))
+    .bind(patient_id)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(appointments))
+}
+
+pub async fn get_appointment(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<AppointmentSummary>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let summary = load_summary(&mut tx, user.tenant_id, id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(summary))
+}
+
+/// Book a patient into a slot. The slot must fall in one of the provider's sessions when they
+/// have any, and must not overlap another appointment of the provider or the patient.
+pub async fn book_appointment(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<BookAppointmentRequest>,
+) -> Result<(StatusCode, Json<AppointmentSummary>), ServiceError> {
+    let mut errors = ValidationErrors::default();
+    trim_optional(&mut payload.reason);
+    trim_optional(&mut payload.notes);
+    normalize_optional_enum::<AppointmentType>(&mut errors, "appointment_type", &mut payload.appointment_type);
+    if payload.starts_at < Utc::now().naive_utc() - Duration::minutes(BOOKING_GRACE_MINUTES) {
+        errors.add("starts_at", "cannot be in the past");
+    }
+    errors.into_result()?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let status: Option<String> =
+        sqlx::query_scalar("SELECT status FROM patients WHERE id = $1 AND tenant_id = $2 FOR UPDATE")
+            .bind(payload.patient_id)
+            .bind(user.tenant_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(db_error)?;
+    match status.as_deref() {
+        None => return Err(field_error("patient_id", "does not refer to an existing patient")),
+        Some("deceased") => return Err(field_error("patient_id", "refers to a deceased patient")),
+        Some(_) => {}
+    }
+    if let Some(requested) = payload.facility_id {
+        optional_facility(&mut *tx, "facility_id", Some(requested), user.id).await?;
+    }
+    let (facility_id, ends_at) = check_slot(&mut tx, SlotRequest {
+        tenant_id: user.tenant_id,
+        patient_id: payload.patient_id,
+        provider_id: payload.provider_id,
+        facility_id: payload.facility_id,
+        starts_at: payload.starts_at,
+        ends_at: payload.ends_at,
+        default_minutes: DEFAULT_APPOINTMENT_MINUTES,
+        appointment_id: None,
+    })
+    .await?;
+    let facility_id = match facility_id {
+        Some(id) => Some(id),
+        None => optional_facility(&mut *tx, "facility_id", None, user.id).await?.map(|f| f.id),
+    };
+    let appointment_type = payload
+        .appointment_type
+        .unwrap_or_else(|| AppointmentType::Consultation.as_str().to_string());
+    let appointment = sqlx::query_as::<_, Appointment>(
+        "INSERT INTO appointments (patient_id, provider_id, facility_id, appointment_type, starts_at, ends_at,
+             reason, notes, booked_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+         RETURNING *",
+    )
+    .bind(payload.patient_id)
+    .bind(payload.provider_id)
+    .bind(facility_id)
+    .bind(&appointment_type)
+    .bind(payload.starts_at)
+    .bind(ends_at)
+    .bind(&payload.reason)
+    .bind(&payload.notes)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    sync_next_visit(&mut *tx, appointment.patient_id).await.map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "appointment.booked",
+        entity_type: "appointment",
+        entity_id: appointment.id,
+        patient_id: Some(appointment.patient_id),
+        details: serde_json::json!({
+            "provider_id": appointment.provider_id,
+            "facility_id": appointment.facility_id,
+            "appointment_type": appointment.appointment_type,
+            "starts_at": appointment.starts_at,
+            "ends_at": appointment.ends_at,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    let summary = load_summary(&mut tx, user.tenant_id, appointment.id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(summary)))
+}
+
+/// Move a booked appointment to a new time, and optionally to another provider or facility.
+pub async fn reschedule_appointment(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(payload): Json<RescheduleRequest>,
+) -> Result<Json<AppointmentSummary>, ServiceError> {
+    if payload.starts_at < Utc::now().naive_utc() - Duration::minutes(BOOKING_GRACE_MINUTES) {
+        return Err(field_error("starts_at", "cannot be in the past"));
+    }
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let before = lock_appointment(&mut tx, user.tenant_id, id).await?;
+    AppointmentAction::Reschedule
+        .apply(current_status(&before)?)
+        .ok_or_else(|| transition_error(AppointmentAction::Reschedule, &before))?;
+    let provider_id = payload.provider_id.or(before.provider_id);
+    if let Some(requested) = payload.facility_id {
+        optional_facility(&mut *tx, "facility_id", Some(requested), user.id).await?;
+    }
+    // a new provider or facility picks its own session; otherwise keep where it was
+    let facility_id = payload
+        .facility_id
+        .or_else(|| (provider_id == before.provider_id).then_some(before.facility_id).flatten());
+    let (facility_id, ends_at) = check_slot(&mut tx, SlotRequest {
+        tenant_id: user.tenant_id,
+        patient_id: before.patient_id,
+        provider_id,
+        facility_id,
+        starts_at: payload.starts_at,
+        ends_at: payload.ends_at,
+        default_minutes: (before.ends_at - before.starts_at).num_minutes(),
+        appointment_id: Some(id),
+    })
+    .await?;
+    // without a session to place it, the appointment stays at its facility
+    let facility_id = facility_id.or(before.facility_id);
+    let appointment = sqlx::query_as::<_, Appointment>(
+        "UPDATE appointments SET provider_id = $2, facility_id = $3, starts_at = $4, ends_at = $5,
+             reschedule_count = reschedule_count + 1, updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(provider_id)
+    .bind(facility_id)
+    .bind(payload.starts_at)
+    .bind(ends_at)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    sync_next_visit(&mut *tx, appointment.patient_id).await.map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "appointment.rescheduled",
+        entity_type: "appointment",
+        entity_id: id,
+        patient_id: Some(appointment.patient_id),
+        details: serde_json::json!({
+            "from": { "starts_at": before.starts_at, "provider_id": before.provider_id, "facility_id": before.facility_id },
+            "to": { "starts_at": appointment.starts_at, "provider_id": appointment.provider_id, "facility_id": appointment.facility_id },
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    let summary = load_summary(&mut tx, user.tenant_id, id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(summary))
+}
+
+/// Move an appointment along its workflow and re-derive the patient's next visit.
+async fn apply_action(
+    pool: &PgPool,
+    user: &AuthUser,
+    id: i32,
+    action: AppointmentAction,
+    reason: Option<String>,
+) -> Result<AppointmentSummary, ServiceError> {
+    let mut tx = tenancy::begin(pool, user.tenant_id).await.map_err(db_error)?;
+    let before = lock_appointment(&mut tx, user.tenant_id, id).await?;
+    let to = action
+        .apply(current_status(&before)?)
+        .ok_or_else(|| transition_error(action, &before))?;
+    let now = Utc::now().naive_utc();
+    match action {
+        AppointmentAction::CheckIn if before.starts_at.date() != now.date() => {
+            return Err(ServiceError::Conflict("Patients can only be checked in on the day of the appointment".to_string()));
+        }
+        AppointmentAction::MarkNoShow if before.starts_at > now => {
+            return Err(ServiceError::Conflict("An appointment cannot be marked as a no-show before it starts".to_string()));
+        }
+        _ => {}
+    }
+    let appointment = sqlx::query_as::<_, Appointment>(
+        "UPDATE appointments SET status = $2,
+             checked_in_at = CASE WHEN $2 = 'checked_in' THEN $3 ELSE checked_in_at END,
+             seen_at = CASE WHEN $2 = 'seen' THEN $3 ELSE seen_at END,
+             cancelled_reason = COALESCE($4, cancelled_reason),
+             updated_at = $3
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(to.as_str())
+    .bind(now)
+    .bind(&reason)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    sync_next_visit(&mut *tx, appointment.patient_id).await.map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: &format!("appointment.{}", to.as_str()),
+        entity_type: "appointment",
+        entity_id: id,
+        patient_id: Some(appointment.patient_id),
+        details: serde_json::json!({ "from": before.status, "to": appointment.status, "reason": reason }),
+    })
+    .await
+    .map_err(db_error)?;
+    let summary = load_summary(&mut tx, user.tenant_id, id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(summary)
+}
+
+pub async fn check_in_appointment(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<AppointmentSummary>, ServiceError> {
+    Ok(Json(apply_action(&pool, &user, id, AppointmentAction::CheckIn, None).await?))
+}
+
+/// Marking a patient as seen is for clinicians and admins.
+pub async fn mark_appointment_seen(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<AppointmentSummary>, ServiceError> {
+    check_clinical_role(&user)?;
+    Ok(Json(apply_action(&pool, &user, id, AppointmentAction::See, None).await?))
+}
+
+pub async fn mark_appointment_no_show(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<AppointmentSummary>, ServiceError> {
+    Ok(Json(apply_action(&pool, &user, id, AppointmentAction::MarkNoShow, None).await?))
+}
+
+/// Cancel a booked or checked-in appointment; a `reason` is required.
+pub async fn cancel_appointment(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<CancelAppointmentRequest>>,
+) -> Result<Json<AppointmentSummary>, ServiceError> {
+    let mut payload = payload.map(|Json(p)| p).unwrap_or_default();
+    trim_optional(&mut payload.reason);
+    let Some(reason) = payload.reason else {
+        return Err(field_error("reason", "is required when cancelling an appointment"));
+    };
+    Ok(Json(apply_action(&pool, &user, id, AppointmentAction::Cancel, Some(reason)).await?))
+}
+
+pub async fn list_availability(
+    user: AuthUser,
+    Path(provider_id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<ProviderAvailability>>, ServiceError> {
+    let templates = sqlx::query_as::<_, ProviderAvailability>(
+        "SELECT a.* FROM provider_availability a JOIN users u ON u.id = a.provider_id AND u.tenant_id = $2
+         WHERE a.provider_id = $1 ORDER BY a.weekday, a.start_time, a.id",
+    )
+    .bind(provider_id)
+    .bind(user.tenant_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+    Ok(Json(templates))
+}
+
+/// Admins manage everyone's availability; clinicians their own.
+fn check_availability_owner(user: &AuthUser, provider_id: i32) -> Result<(), ServiceError> {
+    match user.role {
+        StaffRole::Admin | StaffRole::PlatformAdmin => Ok(()),
+        StaffRole::Clinician if user.id == provider_id => Ok(()),
+        _ => Err(ServiceError::Forbidden),
+    }
+}
+
+/// Add a weekly session. Sessions of one provider on the same weekday cannot overlap.
+pub async fn add_availability(
+    user: AuthUser,
+    Path(provider_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(payload): Json<AvailabilityRequest>,
+) -> Result<(StatusCode, Json<ProviderAvailability>), ServiceError> {
+    check_availability_owner(&user, provider_id)?;
+    let mut errors = ValidationErrors::default();
+    if !(1..=7).contains(&payload.weekday) {
+        errors.add("weekday", "must be between 1 (Monday) and 7 (Sunday)");
+    }
+    if payload.end_time <= payload.start_time {
+        errors.add("end_time", "must be after start_time");
+    }
+    let slot_minutes = payload.slot_minutes.unwrap_or(DEFAULT_APPOINTMENT_MINUTES as i32);
+    if !(5..=240).contains(&slot_minutes) {
+        errors.add("slot_minutes", "must be between 5 and 240");
+    }
+    if let (Some(from), Some(until)) = (payload.valid_from, payload.valid_until) {
+        if until < from {
+            errors.add("valid_until", "cannot be before valid_from");
+        }
+    }
+    errors.into_result()?;
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    lock_provider(&mut tx, user.tenant_id, provider_id).await?;
+    let facility = require_facility(&mut *tx, "facility_id", payload.facility_id, provider_id).await?;
+    let overlapping: Option<i32> = sqlx::query_scalar(
+        "SELECT id FROM provider_availability
+         WHERE provider_id = $1 AND weekday = $2 AND start_time < $4 AND $3 < end_time
+           AND (valid_until IS NULL OR $5::date IS NULL OR valid_until >= $5)
+           AND (valid_from IS NULL OR $6::date IS NULL OR valid_from <= $6)
+         LIMIT 1",
+    )
+    .bind(provider_id)
+    .bind(payload.weekday)
+    .bind(payload.start_time)
+    .bind(payload.end_time)
+    .bind(payload.valid_from)
+    .bind(payload.valid_until)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    if let Some(existing) = overlapping {
+        return Err(ServiceError::Conflict(format!("Overlaps the provider's session #{}", existing)));
+    }
+    let template = sqlx::query_as::<_, ProviderAvailability>(
+        "INSERT INTO provider_availability (provider_id, facility_id, weekday, start_time, end_time, slot_minutes,
+             valid_from, valid_until, created_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+         RETURNING *",
+    )
+    .bind(provider_id)
+    .bind(facility.id)
+    .bind(payload.weekday)
+    .bind(payload.start_time)
+    .bind(payload.end_time)
+    .bind(slot_minutes)
+    .bind(payload.valid_from)
+    .bind(payload.valid_until)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "availability.created",
+        entity_type: "provider_availability",
+        entity_id: template.id,
+        patient_id: None,
+        details: serde_json::to_value(&template).unwrap_or_default(),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(template)))
+}
+
+/// Remove a session. Appointments already booked in it are kept.
+pub async fn delete_availability(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<StatusCode, ServiceError> {
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    let template = sqlx::query_as::<_, ProviderAvailability>(
+        "SELECT a.* FROM provider_availability a JOIN users u ON u.id = a.provider_id AND u.tenant_id = $2
+         WHERE a.id = $1 FOR UPDATE OF a",
+    )
+    .bind(id)
+    .bind(user.tenant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    check_availability_owner(&user, template.provider_id)?;
+    sqlx::query("DELETE FROM provider_availability WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "availability.deleted",
+        entity_type: "provider_availability",
+        entity_id: id,
+        patient_id: None,
+        details: serde_json::to_value(&template).unwrap_or_default(),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Open slots in a provider's sessions on a day (today by default).
+pub async fn list_provider_slots(
+    user: AuthUser,
+    Path(provider_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Query(params): Query<ScheduleQuery>,
+) -> Result<Json<Vec<Slot>>, ServiceError> {
+    let date = params.date.unwrap_or_else(|| Utc::now().date_naive());
+    let facilities = facility_scope(&pool, user.id, params.facility_id, params.my_facilities)
+        .await
+        .map_err(db_error)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let schedule = build_schedule(&mut tx, user.tenant_id, date, facilities, Some(provider_id)).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(schedule.providers.into_iter().flat_map(|p| p.free_slots).collect()))
+}
+
+/// Everyone's day at a glance: each provider's sessions, appointments and open slots.
+pub async fn get_daily_schedule(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<ScheduleQuery>,
+) -> Result<Json<DailySchedule>, ServiceError> {
+    let date = params.date.unwrap_or_else(|| Utc::now().date_naive());
+    let facilities = facility_scope(&pool, user.id, params.facility_id, params.my_facilities)
+        .await
+        .map_err(db_error)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let schedule = build_schedule(&mut tx, user.tenant_id, date, facilities, params.provider_id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(schedule))
+}
+
+async fn build_schedule(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: i32,
+    date: NaiveDate,
+    facility_ids: Option<Vec<i32>>,
+    provider_id: Option<i32>,
+) -> Result<DailySchedule, ServiceError> {
+    let templates = sqlx::query_as::<_, ProviderAvailability>(
+        "SELECT a.* FROM provider_availability a JOIN users u ON u.id = a.provider_id AND u.tenant_id = $3
+         WHERE ($1::int IS NULL OR a.provider_id = $1) AND ($2::int[] IS NULL OR a.facility_id = ANY($2))
+         ORDER BY a.provider_id, a.start_time",
+    )
+    .bind(provider_id)
+    .bind(&facility_ids)
+    .bind(tenant_id)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    let appointments = sqlx::query_as::<_, AppointmentSummary>(&format!(
+        "{SUMMARY_SELECT}
+         WHERE a.starts_at >= $1::date AND a.starts_at < $1::date + 1
+           AND ($2::int IS NULL OR a.provider_id = $2) AND p.tenant_id = $3
+         ORDER BY a.starts_at, a.id"
+    ))
+    .bind(date)
+    .bind(provider_id)
+    .bind(tenant_id)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    let emails: Vec<(i32, String)> = sqlx::query_as("SELECT id, email FROM users WHERE tenant_id = $1 ORDER BY email")
+        .bind(tenant_id)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(db_error)?;
+
+    let at_facility = |a: &AppointmentSummary| {
+        facility_ids.as_ref().is_none_or(|ids| a.appointment.facility_id.is_some_and(|f| ids.contains(&f)))
+    };
+    // a provider is on the schedule if they work that day or have appointments on it
+    let now = Utc::now().naive_utc();
+    let mut providers: Vec<ProviderSchedule> = emails
+        .into_iter()
+        .filter(|(id, _)| provider_id.is_none_or(|p| p == *id))
+        .filter_map(|(id, email)| {
+            let own: Vec<ProviderAvailability> = templates.iter().filter(|t| t.provider_id == id).cloned().collect();
+            let sessions: Vec<ProviderAvailability> = sessions_on(&own, date).into_iter().cloned().collect();
+            let booked: Vec<&AppointmentSummary> =
+                appointments.iter().filter(|a| a.appointment.provider_id == Some(id)).collect();
+            if sessions.is_empty() && !booked.iter().any(|a| at_facility(a)) {
+                return None;
+            }
+            // every active appointment of the provider blocks the slot, whichever facility it is at
+            let blocking: Vec<Appointment> = booked
+                .iter()
+                .filter(|a| AppointmentStatus::ACTIVE.contains(&a.appointment.status.as_str()))
+                .map(|a| a.appointment.clone())
+                .collect();
+            Some(ProviderSchedule {
+                provider_id: id,
+                provider_email: email,
+                free_slots: free_slots(&sessions, &blocking, date, Some(now)),
+                sessions,
+                appointments: Vec::new(),
+            })
+        })
+        .collect();
+    let mut unassigned = Vec::new();
+    for appointment in appointments.into_iter().filter(at_facility) {
+        match providers.iter_mut().find(|p| Some(p.provider_id) == appointment.appointment.provider_id) {
+            Some(provider) => provider.appointments.push(appointment),
+            None => unassigned.push(appointment),
+        }
+    }
+    Ok(DailySchedule { date, facility_ids, providers, unassigned })
+}
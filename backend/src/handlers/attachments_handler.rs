@@ -0,0 +1,575 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::config::state::UploadLimits;
+use crate::handlers::records_handler::{authorize_record, ensure_editable, lock_record};
+use crate::models::attachment::{AttachmentUpload, RecordAttachment};
+use crate::models::error::ServiceError;
+use crate::models::record::MedicalRecord;
+use crate::services::access::RecordAction;
+use crate::services::attachments::{
+    blob_key, content_disposition, parse_content_range, parse_range, sniff_attachment_type, ByteRange,
+    StagingArea, SNIFF_BYTES,
+};
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::storage::{BlobStore, StorageError};
+use crate::services::tenancy;
+use crate::services::validation::{require_text, ValidationErrors};
+use crate::services::versions::{record_version, VersionEntry, VersionKind};
+
+const MAX_FILENAME_CHARS: usize = 255;
+const UNSUPPORTED_TYPE: &str = "Attachments must be PDF, JPEG, PNG, WebP, TIFF or DICOM files";
+
+#[derive(Deserialize)]
+pub struct CreateUploadRequest {
+    pub filename: String,
+    pub size_bytes: i64,
+}
+
+impl CreateUploadRequest {
+    fn validate(&mut self, max_bytes: u64) -> Result<(), ServiceError> {
+        let mut errors = ValidationErrors::default();
+        // some browsers send the client-side path; keep only the file name
+        self.filename = self.filename.rsplit(['/', '\\']).next().unwrap_or_default().to_string();
+        require_text(&mut errors, "filename", &mut self.filename);
+        if self.filename.chars().count() > MAX_FILENAME_CHARS {
+            errors.add("filename", format!("must be at most {} characters", MAX_FILENAME_CHARS));
+        }
+        if self.size_bytes <= 0 {
+            errors.add("size_bytes", "must be greater than zero");
+        }
+        errors.into_result()?;
+        if self.size_bytes as u64 > max_bytes {
+            return Err(ServiceError::PayloadTooLarge(format!(
+                "Attachments may be at most {} bytes",
+                max_bytes
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+pub struct UploadSessionResponse {
+    #[serde(flatten)]
+    pub upload: AttachmentUpload,
+    pub max_chunk_bytes: usize,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[RECORD ATTACHMENT ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+fn io_error(e: std::io::Error) -> ServiceError {
+    eprintln!("[RECORD ATTACHMENT ERROR] Staging error: {}", e);
+    ServiceError::InternalServerError
+}
+
+fn storage_error(e: StorageError) -> ServiceError {
+    eprintln!("[RECORD ATTACHMENT ERROR] Storage error: {}", e);
+    ServiceError::InternalServerError
+}
+
+/// Download path of an attachment; this is what the record's `attachments` column lists.
+fn content_path(record_id: i32, attachment_id: i32) -> String {
+    format!("/records/{}/attachments/{}/content", record_id, attachment_id)
+}
+
+/// Drop abandoned uploads along with their staged bytes.
+async fn purge_expired_uploads(pool: &PgPool, staging: &StagingArea) -> Result<(), ServiceError> {
+    let expired: Vec<Uuid> = sqlx::query_scalar("DELETE FROM attachment_uploads WHERE expires_at < now() RETURNING id")
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+    for upload_id in expired {
+        staging.remove(upload_id).await;
+    }
+    Ok(())
+}
+
+/// Start a resumable upload. The client then PUTs the file in chunks with `Content-Range`.
+pub async fn create_attachment_upload(
+    user: AuthUser,
+    Path(record_id): Path<i32>,
+    State(pool): State<PgPool>,
+    State(staging): State<StagingArea>,
+    State(limits): State<UploadLimits>,
+    Json(mut payload): Json<CreateUploadRequest>,
+) -> Result<(StatusCode, Json<UploadSessionResponse>), ServiceError> {
+    payload.validate(limits.max_attachment_bytes)?;
+    purge_expired_uploads(&pool, &staging).await?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    ensure_editable(&authorize_record(&mut *tx, record_id, &user, RecordAction::Write).await?)?;
+    let upload = sqlx::query_as::<_, AttachmentUpload>(
+        r#"INSERT INTO attachment_uploads (id, record_id, filename, total_bytes, uploaded_by, expires_at)
+           VALUES ($1, $2, $3, $4, $5, now() + interval '24 hours')
+           RETURNING *"#
+    )
+    .bind(Uuid::new_v4())
+    .bind(record_id)
+    .bind(&payload.filename)
+    .bind(payload.size_bytes)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(UploadSessionResponse { upload, max_chunk_bytes: limits.max_chunk_bytes })))
+}
+
+/// Progress of an upload, so an interrupted client knows where to resume.
+pub async fn get_attachment_upload(
+    user: AuthUser,
+    Path((record_id, upload_id)): Path<(i32, Uuid)>,
+    State(pool): State<PgPool>,
+    State(limits): State<UploadLimits>,
+) -> Result<Json<UploadSessionResponse>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    authorize_record(&mut *tx, record_id, &user, RecordAction::Write).await?;
+    let upload = sqlx::query_as::<_, AttachmentUpload>(
+        "SELECT * FROM attachment_uploads WHERE id = $1 AND record_id = $2 AND uploaded_by = $3 AND expires_at >= now()"
+    )
+    .bind(upload_id)
+    .bind(record_id)
+    .bind(user.id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(UploadSessionResponse { upload, max_chunk_bytes: limits.max_chunk_bytes }))
+}
+
+/// Receive one chunk. Chunks must arrive in order; the one that completes the file turns the
+/// upload into an attachment (201, or 200 when the record already has identical content).
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_attachment_chunk(
+    user: AuthUser,
+    Path((record_id, upload_id)): Path<(i32, Uuid)>,
+    State(pool): State<PgPool>,
+    State(storage): State<Arc<dyn BlobStore>>,
+    State(staging): State<StagingArea>,
+    State(limits): State<UploadLimits>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, ServiceError> {
+    let (start, end, total) = headers
+        .get(header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_content_range)
+        .ok_or_else(|| ServiceError::BadRequest("Content-Range must be 'bytes <start>-<end>/<total>'".to_string()))?;
+    let chunk = to_bytes(body, limits.max_chunk_bytes).await.map_err(|_| {
+        ServiceError::PayloadTooLarge(format!("Chunks may be at most {} bytes", limits.max_chunk_bytes))
+    })?;
+    if chunk.len() as u64 != end - start + 1 {
+        return Err(ServiceError::BadRequest("Chunk length does not match Content-Range".to_string()));
+    }
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    ensure_editable(&authorize_record(&mut *tx, record_id, &user, RecordAction::Write).await?)?;
+    let mut upload = sqlx::query_as::<_, AttachmentUpload>(
+        r#"SELECT * FROM attachment_uploads
+           WHERE id = $1 AND record_id = $2 AND uploaded_by = $3 AND expires_at >= now()
+           FOR UPDATE"#
+    )
+    .bind(upload_id)
+    .bind(record_id)
+    .bind(user.id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    if total != upload.total_bytes as u64 {
+        return Err(ServiceError::BadRequest(format!("Upload is {} bytes, not {}", upload.total_bytes, total)));
+    }
+    if start != upload.received_bytes as u64 {
+        return Err(ServiceError::Conflict(format!(
+            "Expected the chunk starting at byte {}",
+            upload.received_bytes
+        )));
+    }
+    // reject unsupported files on the first chunk rather than after the whole transfer
+    if start == 0 && (chunk.len() >= SNIFF_BYTES || end + 1 == total) && sniff_attachment_type(&chunk).is_none() {
+        return Err(ServiceError::UnsupportedMediaType(UNSUPPORTED_TYPE.to_string()));
+    }
+    staging.write_at(upload_id, start, &chunk).await.map_err(io_error)?;
+
+    if end + 1 < total {
+        upload = sqlx::query_as::<_, AttachmentUpload>(
+            "UPDATE attachment_uploads SET received_bytes = $1, updated_at = now() WHERE id = $2 RETURNING *"
+        )
+        .bind((end + 1) as i64)
+        .bind(upload_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+        let response = UploadSessionResponse { upload, max_chunk_bytes: limits.max_chunk_bytes };
+        return Ok(Json(response).into_response());
+    }
+
+    let (status, attachment) = complete_upload(&mut tx, storage.as_ref(), &staging, &upload, &user).await?;
+    tx.commit().await.map_err(db_error)?;
+    staging.remove(upload_id).await;
+    Ok((status, Json(attachment)).into_response())
+}
+
+/// Hash the staged file, store its content unless an identical blob exists, and attach it.
+async fn complete_upload(
+    tx: &mut Transaction<'_, Postgres>,
+    storage: &dyn BlobStore,
+    staging: &StagingArea,
+    upload: &AttachmentUpload,
+    user: &AuthUser,
+) -> Result<(StatusCode, RecordAttachment), ServiceError> {
+    let (sha256, head) = staging.digest(upload.id).await.map_err(io_error)?;
+    let content_type = sniff_attachment_type(&head)
+        .ok_or_else(|| ServiceError::UnsupportedMediaType(UNSUPPORTED_TYPE.to_string()))?;
+    // the record may have been signed while the upload was in flight
+    let record = lock_record(tx, user.tenant_id, upload.record_id).await?;
+    ensure_editable(&record)?;
+
+    // Locking the blob row keeps a concurrent delete from removing content we are about to
+    // reference, and the blob lock keeps a delete that already committed from removing content
+    // we store again.
+    lock_blob(&mut **tx, &sha256).await?;
+    let stored: Option<String> = sqlx::query_scalar("SELECT storage_key FROM attachment_blobs WHERE sha256 = $1 FOR UPDATE")
+        .bind(&sha256)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    if stored.is_none() {
+        let key = blob_key(&sha256);
+        storage
+            .put_file(&key, &staging.path_for(upload.id), content_type)
+            .await
+            .map_err(storage_error)?;
+        sqlx::query(
+            r#"INSERT INTO attachment_blobs (sha256, storage_key, content_type, size_bytes)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT (sha256) DO NOTHING"#
+        )
+        .bind(&sha256)
+        .bind(&key)
+        .bind(content_type)
+        .bind(upload.total_bytes)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    }
+
+    sqlx::query("DELETE FROM attachment_uploads WHERE id = $1")
+        .bind(upload.id)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    let inserted = sqlx::query_as::<_, RecordAttachment>(
+        r#"INSERT INTO record_attachments (record_id, sha256, filename, content_type, size_bytes, uploaded_by)
+           VALUES ($1, $2, $3, $4, $5, $6)
+           ON CONFLICT (record_id, sha256) DO NOTHING
+           RETURNING *"#
+    )
+    .bind(record.id)
+    .bind(&sha256)
+    .bind(&upload.filename)
+    .bind(content_type)
+    .bind(upload.total_bytes)
+    .bind(user.id)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_error)?;
+
+    let Some(attachment) = inserted else {
+        let existing = sqlx::query_as::<_, RecordAttachment>(
+            "SELECT * FROM record_attachments WHERE record_id = $1 AND sha256 = $2"
+        )
+        .bind(record.id)
+        .bind(&sha256)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(db_error)?;
+        return Ok((StatusCode::OK, existing));
+    };
+
+    let updated = sqlx::query_as::<_, MedicalRecord>(
+        "UPDATE medical_records SET attachments = array_append(COALESCE(attachments, '{}'), $1), updated_at = now() WHERE id = $2 RETURNING *"
+    )
+    .bind(content_path(record.id, attachment.id))
+    .bind(record.id)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    record_version(&mut **tx, VersionEntry {
+        previous: Some(&record),
+        record: &updated,
+        kind: VersionKind::Edited,
+        reason: Some(&format!("Attached {}", attachment.filename)),
+        changed_by: Some(user.id),
+    })
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut **tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "record.attachment_added",
+        entity_type: "medical_record",
+        entity_id: record.id,
+        patient_id: Some(record.patient_id),
+        details: serde_json::json!({
+            "attachment_id": attachment.id,
+            "filename": attachment.filename,
+            "sha256": attachment.sha256,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    Ok((StatusCode::CREATED, attachment))
+}
+
+pub async fn cancel_attachment_upload(
+    user: AuthUser,
+    Path((record_id, upload_id)): Path<(i32, Uuid)>,
+    State(pool): State<PgPool>,
+    State(staging): State<StagingArea>,
+) -> Result<StatusCode, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    authorize_record(&mut *tx, record_id, &user, RecordAction::Write).await?;
+    sqlx::query_scalar::<_, Uuid>(
+        "DELETE FROM attachment_uploads WHERE id = $1 AND record_id = $2 AND uploaded_by = $3 RETURNING id"
+    )
+    .bind(upload_id)
+    .bind(record_id)
+    .bind(user.id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    tx.commit().await.map_err(db_error)?;
+    staging.remove(upload_id).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_record_attachments(
+    user: AuthUser,
+    Path(record_id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<RecordAttachment>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    authorize_record(&mut *tx, record_id, &user, RecordAction::Read).await?;
+    let attachments = sqlx::query_as::<_, RecordAttachment>(
+        "SELECT * FROM record_attachments WHERE record_id = $1 ORDER BY created_at, id"
+    )
+    .bind(record_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(attachments))
+}
+
+async fn fetch_attachment(
+    tx: &mut Transaction<'_, Postgres>,
+    record_id: i32,
+    attachment_id: i32,
+) -> Result<RecordAttachment, ServiceError> {
+    sqlx::query_as::<_, RecordAttachment>("SELECT * FROM record_attachments WHERE id = $1 AND record_id = $2")
+        .bind(attachment_id)
+        .bind(record_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?
+        .ok_or(ServiceError::NotFound)
+}
+
+pub async fn get_record_attachment(
+    user: AuthUser,
+    Path((record_id, attachment_id)): Path<(i32, i32)>,
+    State(pool): State<PgPool>,
+) -> Result<Json<RecordAttachment>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    authorize_record(&mut *tx, record_id, &user, RecordAction::Read).await?;
+    let attachment = fetch_attachment(&mut tx, record_id, attachment_id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(attachment))
+}
+
+/// Serve attachment content, honouring a single `Range` so large scans can be fetched in parts.
+pub async fn download_record_attachment(
+    user: AuthUser,
+    Path((record_id, attachment_id)): Path<(i32, i32)>,
+    State(pool): State<PgPool>,
+    State(storage): State<Arc<dyn BlobStore>>,
+    headers: HeaderMap,
+) -> Result<Response, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    authorize_record(&mut *tx, record_id, &user, RecordAction::Read).await?;
+    let attachment = fetch_attachment(&mut tx, record_id, attachment_id).await?;
+    let storage_key: String = sqlx::query_scalar("SELECT storage_key FROM attachment_blobs WHERE sha256 = $1")
+        .bind(&attachment.sha256)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    let size = attachment.size_bytes as u64;
+    let etag = format!("\"{}\"", attachment.sha256);
+    // a stale If-Range means the client's partial copy is of something else: send it all
+    let range_header = match headers.get(header::IF_RANGE).and_then(|v| v.to_str().ok()) {
+        Some(validator) if validator != etag => None,
+        _ => headers.get(header::RANGE).and_then(|v| v.to_str().ok()),
+    };
+    let response = Response::builder()
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, &etag)
+        .header(header::CACHE_CONTROL, "private")
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff");
+    let response = match parse_range(range_header, size) {
+        ByteRange::Full => {
+            let content = storage.stream(&storage_key, None).await.map_err(storage_error)?;
+            response
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, &attachment.content_type)
+                .header(header::CONTENT_LENGTH, size)
+                .header(header::CONTENT_DISPOSITION, content_disposition(&attachment.filename))
+                .body(Body::from_stream(content))
+        }
+        ByteRange::Partial { start, end } => {
+            let content = storage.stream(&storage_key, Some((start, end))).await.map_err(storage_error)?;
+            response
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_TYPE, &attachment.content_type)
+                .header(header::CONTENT_LENGTH, end - start + 1)
+                .header(header::CONTENT_DISPOSITION, content_disposition(&attachment.filename))
+                .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, size))
+                .body(Body::from_stream(content))
+        }
+        ByteRange::Unsatisfiable => response
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", size))
+            .body(Body::empty()),
+    };
+    response.map_err(|_| ServiceError::InternalServerError)
+}
+
+/// Detach a file from a record. The stored content is removed once no record references it.
+pub async fn delete_record_attachment(
+    user: AuthUser,
+    Path((record_id, attachment_id)): Path<(i32, i32)>,
+    State(pool): State<PgPool>,
+    State(storage): State<Arc<dyn BlobStore>>,
+) -> Result<StatusCode, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    authorize_record(&mut *tx, record_id, &user, RecordAction::Write).await?;
+    let record = lock_record(&mut tx, user.tenant_id, record_id).await?;
+    ensure_editable(&record)?;
+    let attachment = sqlx::query_as::<_, RecordAttachment>(
+        "DELETE FROM record_attachments WHERE id = $1 AND record_id = $2 RETURNING *"
+    )
+    .bind(attachment_id)
+    .bind(record_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+
+    let storage_key: String = sqlx::query_scalar("SELECT storage_key FROM attachment_blobs WHERE sha256 = $1 FOR UPDATE")
+        .bind(&attachment.sha256)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    let still_used: bool = sqlx::query_scalar("SELECT attachment_blob_in_use($1)")
+        .bind(&attachment.sha256)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    if !still_used {
+        sqlx::query("DELETE FROM attachment_blobs WHERE sha256 = $1")
+            .bind(&attachment.sha256)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+    }
+
+    let updated = sqlx::query_as::<_, MedicalRecord>(
+        "UPDATE medical_records SET attachments = array_remove(attachments, $1), updated_at = now() WHERE id = $2 RETURNING *"
+    )
+    .bind(content_path(record_id, attachment_id))
+    .bind(record_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    record_version(&mut *tx, VersionEntry {
+        previous: Some(&record),
+        record: &updated,
+        kind: VersionKind::Edited,
+        reason: Some(&format!("Removed attachment {}", attachment.filename)),
+        changed_by: Some(user.id),
+    })
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "record.attachment_removed",
+        entity_type: "medical_record",
+        entity_id: record_id,
+        patient_id: Some(record.patient_id),
+        details: serde_json::json!({
+            "attachment_id": attachment.id,
+            "filename": attachment.filename,
+            "sha256": attachment.sha256,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    // The content goes only once the detach is committed. Failing to remove it leaves an
+    // orphaned object behind, not a broken attachment, so the detach still stands.
+    if !still_used {
+        let _ = remove_unused_blob(&pool, storage.as_ref(), &attachment.sha256, &storage_key).await;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Serialises storing and removing the content of one blob: held by an upload from the
+/// moment it looks the blob up until it commits.
+async fn lock_blob<'e, E: sqlx::PgExecutor<'e>>(executor: E, sha256: &str) -> Result<(), ServiceError> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+        .bind(sha256)
+        .execute(executor)
+        .await
+        .map_err(db_error)?;
+    Ok(())
+}
+
+/// Remove the stored content of a blob whose row is gone, unless an upload of the same
+/// content has stored it again since.
+async fn remove_unused_blob(
+    pool: &PgPool,
+    storage: &dyn BlobStore,
+    sha256: &str,
+    storage_key: &str,
+) -> Result<(), ServiceError> {
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    lock_blob(&mut *tx, sha256).await?;
+    let stored_again: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM attachment_blobs WHERE sha256 = $1)")
+        .bind(sha256)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    if !stored_again {
+        storage.delete(storage_key).await.map_err(storage_error)?;
+    }
+    tx.commit().await.map_err(db_error)
+}
@@ -5,6 +5,7 @@ use bcrypt::verify;
 
 use crate::models::error::ServiceError;
 use crate::models::user::User;
+use crate::services::auth::TokenKeys;
 
 #[derive(Deserialize)]
 pub struct LoginRequest {
@@ -12,13 +13,16 @@ pub struct LoginRequest {
     pub password_hash: String,
 }
 
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[AUTH ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
 pub async fn login(
     State(pool): State<PgPool>,
+    State(tokens): State<TokenKeys>,
     Json(payload): Json<LoginRequest>,
 ) -> Result<impl IntoResponse, ServiceError> {
-    // Debug: print incoming payload
-    println!("[LOGIN DEBUG] Incoming payload: email='{}', password_hash='{}'", payload.email, payload.password_hash);
-
     // Query the users table for the email
     let user = sqlx::query_as!(
         User,
@@ -27,10 +31,7 @@ pub async fn login(
     )
     .fetch_optional(&pool)
     .await
-    .map_err(|e| {
-        println!("[LOGIN DEBUG] DB error: {:?}", e);
-        ServiceError::Unauthorized
-    })?;
+    .map_err(db_error)?;
 
     if let Some(user_record) = user {
         // Detect if the stored hash is a bcrypt hash (starts with $2)
@@ -40,16 +41,26 @@ pub async fn login(
             payload.password_hash == user_record.password_hash
         };
 
-        if matches {
-            println!("[LOGIN DEBUG] User found and authenticated for email='{}'!", payload.email);
+        // users of a suspended tenant are turned away like a wrong password
+        let tenant_active: bool = sqlx::query_scalar("SELECT active FROM tenants WHERE id = $1")
+            .bind(user_record.tenant_id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(db_error)?
+            .unwrap_or(false);
+        if matches && tenant_active {
+            let (token, expires_at) = tokens
+                .issue(&user_record)
+                .map_err(|_| ServiceError::InternalServerError)?;
             let response = serde_json::json!({
-                "token": "example.jwt.token",
-                "user": payload.email
+                "token": token,
+                "user": payload.email,
+                "role": user_record.role,
+                "expires_at": expires_at
             });
             return Ok((StatusCode::OK, Json(response)));
         }
     }
 
-    println!("[LOGIN DEBUG] Authentication failed for email='{}'", payload.email);
     Err(ServiceError::Unauthorized)
 }
\ No newline at end of file
@@ -1,19 +1,24 @@
 use axum::{extract::{State, Path}, Json};
 use sqlx::PgPool;
 use crate::models::consent::Consent;
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::tenancy;
 use axum::http::StatusCode;
 
-pub async fn list_consents(State(pool): State<PgPool>) -> Result<Json<Vec<Consent>>, StatusCode> {
-    let consents = sqlx::query_as!(Consent, "SELECT * FROM consents")
-        .fetch_all(&pool)
+pub async fn list_consents(user: AuthUser, State(pool): State<PgPool>) -> Result<Json<Vec<Consent>>, StatusCode> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let consents = sqlx::query_as!(Consent, "SELECT * FROM consents WHERE tenant_id = $1", user.tenant_id)
+        .fetch_all(&mut *tx)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     Ok(Json(consents))
 }
 
-pub async fn get_consent(State(pool): State<PgPool>, Path(id): Path<i32>) -> Result<Json<Consent>, StatusCode> {
-    let consent = sqlx::query_as!(Consent, "SELECT * FROM consents WHERE id = $1", id)
-        .fetch_one(&pool)
+pub async fn get_consent(user: AuthUser, State(pool): State<PgPool>, Path(id): Path<i32>) -> Result<Json<Consent>, StatusCode> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let consent = sqlx::query_as!(Consent, "SELECT * FROM consents WHERE id = $1 AND tenant_id = $2", id, user.tenant_id)
+        .fetch_one(&mut *tx)
         .await
         .map_err(|_| StatusCode::NOT_FOUND)?;
     Ok(Json(consent))
@@ -24,10 +29,30 @@ pub struct UpdateConsent {
     pub granted: bool,
 }
 
-pub async fn update_consent(State(pool): State<PgPool>, Path(id): Path<i32>, Json(payload): Json<UpdateConsent>) -> Result<StatusCode, StatusCode> {
-    sqlx::query!("UPDATE consents SET granted = $1 WHERE id = $2", payload.granted, id)
-        .execute(&pool)
+pub async fn update_consent(user: AuthUser, State(pool): State<PgPool>, Path(id): Path<i32>, Json(payload): Json<UpdateConsent>) -> Result<StatusCode, StatusCode> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let consent = sqlx::query_as!(Consent, "SELECT * FROM consents WHERE id = $1 AND tenant_id = $2 FOR UPDATE", id, user.tenant_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    sqlx::query!("UPDATE consents SET granted = $1 WHERE id = $2 AND tenant_id = $3", payload.granted, id, user.tenant_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if consent.granted != payload.granted {
+        audit::record(&mut *tx, AuditEntry {
+            user_id: Some(user.id),
+            action: if payload.granted { "consent.granted" } else { "consent.revoked" },
+            entity_type: "consent",
+            entity_id: consent.id,
+            patient_id: Some(consent.patient_id),
+            details: serde_json::json!({ "consent_type": consent.consent_type }),
+        })
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     Ok(StatusCode::NO_CONTENT)
 }
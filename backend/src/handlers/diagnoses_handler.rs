@@ -0,0 +1,679 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::handlers::records_handler::authorize_record;
+use crate::models::error::ServiceError;
+use crate::models::terminology::{
+    ConditionMapping, ConditionMappingStatus, Diagnosis, DiagnosisStatus, MatchKind, TerminologyCode,
+};
+use crate::services::access::{check_admin_role, check_clinical_role, RecordAction};
+use crate::services::audit::{self, diff_fields, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::tenancy;
+use crate::services::terminology::match_condition;
+use crate::services::validation::{
+    check_not_future, field_error, normalize_optional_enum, normalize_term, require_text, trim_optional, TextEnum,
+    ValidationErrors,
+};
+
+/// Code system used when a request does not name one.
+const DEFAULT_SYSTEM: &str = "icd10";
+const DEFAULT_QUEUE_LIMIT: i64 = 100;
+const MAX_QUEUE_LIMIT: i64 = 500;
+
+#[derive(Deserialize)]
+pub struct CreateDiagnosisRequest {
+    /// Defaults to `icd10`.
+    pub system: Option<String>,
+    pub code: String,
+    pub onset_date: Option<NaiveDate>,
+    pub resolved_date: Option<NaiveDate>,
+    pub notes: Option<String>,
+    pub record_id: Option<i32>,
+}
+
+/// Replaces the mutable fields of a diagnosis; the code itself cannot change.
+#[derive(Deserialize)]
+pub struct UpdateDiagnosisRequest {
+    pub onset_date: Option<NaiveDate>,
+    pub resolved_date: Option<NaiveDate>,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DiagnosisQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct RunMappingRequest {
+    /// Defaults to `icd10`.
+    pub system: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MappingQueueQuery {
+    /// Defaults to `pending`.
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct AcceptMappingRequest {
+    /// Defaults to the suggested code's system.
+    pub system: Option<String>,
+    /// Defaults to the suggested code.
+    pub code: Option<String>,
+    pub onset_date: Option<NaiveDate>,
+    pub note: Option<String>,
+    /// Also resolve the other pending entries with the same text. Defaults to `true`.
+    pub apply_to_matching: Option<bool>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct RejectMappingRequest {
+    pub note: Option<String>,
+    /// Also resolve the other pending entries with the same text. Defaults to `true`.
+    pub apply_to_matching: Option<bool>,
+}
+
+#[derive(Serialize, Default)]
+pub struct MappingRunSummary {
+    pub patients_scanned: usize,
+    pub conditions_scanned: usize,
+    /// Entries mapped by an earlier run or a reviewer.
+    pub already_processed: usize,
+    pub auto_mapped: usize,
+    pub queued: usize,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct ConditionMappingItem {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub mapping: ConditionMapping,
+    pub patient_name: String,
+    /// Display of the mapped or suggested code.
+    pub code_display: Option<String>,
+    /// Pending entries with the same text, this one included.
+    pub matching_pending: i64,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[DIAGNOSES ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+async fn ensure_patient(tx: &mut Transaction<'_, Postgres>, tenant_id: i32, id: i32) -> Result<(), ServiceError> {
+    let exists: Option<i32> = sqlx::query_scalar("SELECT id FROM patients WHERE id = $1 AND tenant_id = $2")
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    exists.map(|_| ()).ok_or(ServiceError::NotFound)
+}
+
+fn system_or_default(system: Option<&str>) -> String {
+    system.map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty()).unwrap_or_else(|| DEFAULT_SYSTEM.to_string())
+}
+
+/// The active code `code` in `system`, matched case-insensitively, or a `422` on `field`.
+async fn active_code(pool: &PgPool, field: &str, system: &str, code: &str) -> Result<TerminologyCode, ServiceError> {
+    sqlx::query_as::<_, TerminologyCode>(
+        "SELECT system, code, display, synonyms, active FROM terminology_codes
+         WHERE system = $1 AND upper(code) = upper($2) AND active",
+    )
+    .bind(system)
+    .bind(code.trim())
+    .fetch_optional(pool)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| field_error(field, format!("is not an active {} code", system)))
+}
+
+fn check_dates(errors: &mut ValidationErrors, onset_date: Option<NaiveDate>, resolved_date: Option<NaiveDate>) {
+    if let Some(onset) = onset_date {
+        check_not_future(errors, "onset_date", onset);
+    }
+    if let Some(resolved) = resolved_date {
+        check_not_future(errors, "resolved_date", resolved);
+        if onset_date.is_some_and(|onset| resolved < onset) {
+            errors.add("resolved_date", "cannot be before onset_date");
+        }
+    }
+}
+
+fn open_conflict(e: sqlx::Error, code: &str) -> ServiceError {
+    match &e {
+        sqlx::Error::Database(db) if db.constraint() == Some("idx_diagnoses_open") => {
+            ServiceError::Conflict(format!("Patient already has an open diagnosis of {}", code))
+        }
+        _ => db_error(e),
+    }
+}
+
+/// The patient's open diagnosis of `code`, recording one if there is none.
+async fn open_diagnosis(
+    tx: &mut Transaction<'_, Postgres>,
+    user: &AuthUser,
+    patient_id: i32,
+    code: &TerminologyCode,
+    onset_date: Option<NaiveDate>,
+    source_text: &str,
+) -> Result<i32, ServiceError> {
+    let existing: Option<i32> = sqlx::query_scalar(
+        "SELECT id FROM diagnoses WHERE patient_id = $1 AND system = $2 AND code = $3 AND resolved_date IS NULL",
+    )
+    .bind(patient_id)
+    .bind(&code.system)
+    .bind(&code.code)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO diagnoses (patient_id, system, code, display, onset_date, source, recorded_by, updated_by)
+         VALUES ($1, $2, $3, $4, $5, 'condition_mapping', $6, $6)
+         RETURNING id",
+    )
+    .bind(patient_id)
+    .bind(&code.system)
+    .bind(&code.code)
+    .bind(&code.display)
+    .bind(onset_date)
+    .bind(user.id)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut **tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "diagnosis.recorded",
+        entity_type: "diagnosis",
+        entity_id: id,
+        patient_id: Some(patient_id),
+        details: serde_json::json!({
+            "system": code.system,
+            "code": code.code,
+            "source": "condition_mapping",
+            "condition": source_text,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    Ok(id)
+}
+
+/// The patient's coded diagnoses, open ones first.
+pub async fn list_diagnoses(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Query(mut params): Query<DiagnosisQuery>,
+) -> Result<Json<Vec<Diagnosis>>, ServiceError> {
+    let mut errors = ValidationErrors::default();
+    normalize_optional_enum::<DiagnosisStatus>(&mut errors, "status", &mut params.status);
+    errors.into_result()?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    ensure_patient(&mut tx, user.tenant_id, patient_id).await?;
+    let diagnoses = sqlx::query_as::<_, Diagnosis>(
+        "SELECT * FROM diagnoses
+         WHERE patient_id = $1
+           AND ($2::text IS NULL OR ($2 = 'active') = (resolved_date IS NULL))
+         ORDER BY resolved_date IS NOT NULL, resolved_date DESC, onset_date DESC NULLS LAST, id DESC",
+    )
+    .bind(patient_id)
+    .bind(&params.status)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(diagnoses))
+}
+
+pub async fn create_diagnosis(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<CreateDiagnosisRequest>,
+) -> Result<(StatusCode, Json<Diagnosis>), ServiceError> {
+    check_clinical_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    require_text(&mut errors, "code", &mut payload.code);
+    trim_optional(&mut payload.notes);
+    check_dates(&mut errors, payload.onset_date, payload.resolved_date);
+    errors.into_result()?;
+    let system = system_or_default(payload.system.as_deref());
+    let code = active_code(&pool, "code", &system, &payload.code).await?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    ensure_patient(&mut tx, user.tenant_id, patient_id).await?;
+    if let Some(record_id) = payload.record_id {
+        let record = authorize_record(&mut *tx, record_id, &user, RecordAction::Read).await?;
+        if record.patient_id != patient_id {
+            return Err(field_error("record_id", "belongs to a different patient"));
+        }
+    }
+
+    let diagnosis = sqlx::query_as::<_, Diagnosis>(
+        "INSERT INTO diagnoses (patient_id, record_id, system, code, display, onset_date, resolved_date, notes,
+             recorded_by, updated_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+         RETURNING *",
+    )
+    .bind(patient_id)
+    .bind(payload.record_id)
+    .bind(&code.system)
+    .bind(&code.code)
+    .bind(&code.display)
+    .bind(payload.onset_date)
+    .bind(payload.resolved_date)
+    .bind(&payload.notes)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| open_conflict(e, &code.code))?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "diagnosis.recorded",
+        entity_type: "diagnosis",
+        entity_id: diagnosis.id,
+        patient_id: Some(patient_id),
+        details: serde_json::json!({
+            "system": diagnosis.system,
+            "code": diagnosis.code,
+            "source": diagnosis.source,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(diagnosis)))
+}
+
+/// Change onset, resolution or notes. Clearing `resolved_date` reopens the diagnosis.
+pub async fn update_diagnosis(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<UpdateDiagnosisRequest>,
+) -> Result<Json<Diagnosis>, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    trim_optional(&mut payload.notes);
+    check_dates(&mut errors, payload.onset_date, payload.resolved_date);
+    errors.into_result()?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let current = sqlx::query_as::<_, Diagnosis>(
+        "SELECT d.* FROM diagnoses d JOIN patients p ON p.id = d.patient_id AND p.tenant_id = $2
+         WHERE d.id = $1 FOR UPDATE OF d",
+    )
+    .bind(id)
+    .bind(user.tenant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    let diagnosis = sqlx::query_as::<_, Diagnosis>(
+        "UPDATE diagnoses SET onset_date = $2, resolved_date = $3, notes = $4, updated_by = $5, updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(payload.onset_date)
+    .bind(payload.resolved_date)
+    .bind(&payload.notes)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| open_conflict(e, &current.code))?;
+    let changes = diff_fields(
+        &serde_json::to_value(&current).unwrap_or_default(),
+        &serde_json::to_value(&diagnosis).unwrap_or_default(),
+        &["updated_by", "updated_at"],
+    );
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "diagnosis.updated",
+        entity_type: "diagnosis",
+        entity_id: id,
+        patient_id: Some(diagnosis.patient_id),
+        details: serde_json::json!({ "code": diagnosis.code, "changes": changes }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(diagnosis))
+}
+
+/// Remove a diagnosis recorded in error. Resolved conditions should be resolved, not deleted.
+pub async fn delete_diagnosis(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<StatusCode, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let diagnosis = sqlx::query_as::<_, Diagnosis>(
+        "DELETE FROM diagnoses d USING patients p
+         WHERE d.id = $1 AND p.id = d.patient_id AND p.tenant_id = $2 RETURNING d.*",
+    )
+    .bind(id)
+    .bind(user.tenant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    // the free-text condition goes back to the review queue
+    sqlx::query(
+        "UPDATE condition_mappings
+         SET status = 'pending', match_kind = 'suggested', reviewed_by = NULL, reviewed_at = NULL
+         WHERE diagnosis_id IS NULL AND patient_id = $1 AND status = 'mapped' AND system = $2 AND code = $3",
+    )
+    .bind(diagnosis.patient_id)
+    .bind(&diagnosis.system)
+    .bind(&diagnosis.code)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "diagnosis.deleted",
+        entity_type: "diagnosis",
+        entity_id: id,
+        patient_id: Some(diagnosis.patient_id),
+        details: serde_json::json!({ "system": diagnosis.system, "code": diagnosis.code }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Map every free-text entry of the tenant's `patients.active_conditions` not seen before. Confident matches
+/// are coded straight away; the rest go to the review queue with the best suggestion.
+/// Safe to run again: entries already in `condition_mappings` are skipped.
+pub async fn run_condition_mapping(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    payload: Option<Json<RunMappingRequest>>,
+) -> Result<Json<MappingRunSummary>, ServiceError> {
+    check_admin_role(&user)?;
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let system = system_or_default(payload.system.as_deref());
+    let known: Option<String> = sqlx::query_scalar("SELECT code FROM terminology_systems WHERE code = $1")
+        .bind(&system)
+        .fetch_optional(&pool)
+        .await
+        .map_err(db_error)?;
+    if known.is_none() {
+        return Err(field_error("system", format!("'{}' has not been loaded", system)));
+    }
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let patients: Vec<(i32, Vec<String>)> = sqlx::query_as(
+        "SELECT id, active_conditions FROM patients
+         WHERE cardinality(active_conditions) > 0 AND tenant_id = $1
+         ORDER BY id",
+    )
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    let processed: HashSet<(i32, String)> = sqlx::query_as::<_, (i32, String)>(
+        "SELECT m.patient_id, m.normalized_text FROM condition_mappings m
+         JOIN patients p ON p.id = m.patient_id AND p.tenant_id = $1",
+    )
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .into_iter()
+    .collect();
+
+    let mut summary = MappingRunSummary { patients_scanned: patients.len(), ..Default::default() };
+    let mut matches: HashMap<String, (MatchKind, Option<TerminologyCode>)> = HashMap::new();
+    for (patient_id, conditions) in patients {
+        let mut seen = HashSet::new();
+        for condition in conditions {
+            let text = normalize_term(&condition);
+            if text.is_empty() || !seen.insert(text.clone()) {
+                continue;
+            }
+            summary.conditions_scanned += 1;
+            if processed.contains(&(patient_id, text.clone())) {
+                summary.already_processed += 1;
+                continue;
+            }
+            if !matches.contains_key(&text) {
+                let found = match_condition(&pool, &system, &text).await.map_err(db_error)?;
+                matches.insert(text.clone(), found);
+            }
+            let (kind, code) = &matches[&text];
+            let (status, diagnosis_id) = match code {
+                Some(code) if kind.is_confident() => {
+                    summary.auto_mapped += 1;
+                    let id = open_diagnosis(&mut tx, &user, patient_id, code, None, condition.trim()).await?;
+                    (ConditionMappingStatus::Mapped, Some(id))
+                }
+                _ => {
+                    summary.queued += 1;
+                    (ConditionMappingStatus::Pending, None)
+                }
+            };
+            sqlx::query(
+                "INSERT INTO condition_mappings
+                     (patient_id, source_text, normalized_text, status, match_kind, system, code, diagnosis_id)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            )
+            .bind(patient_id)
+            .bind(condition.trim())
+            .bind(&text)
+            .bind(status.as_str())
+            .bind(kind.as_str())
+            .bind(code.as_ref().map(|c| c.system.as_str()))
+            .bind(code.as_ref().map(|c| c.code.as_str()))
+            .bind(diagnosis_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+    }
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(summary))
+}
+
+/// The review queue: free-text conditions by status, most common texts first so one review
+/// can resolve many patients.
+pub async fn list_condition_mappings(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(mut params): Query<MappingQueueQuery>,
+) -> Result<Json<Vec<ConditionMappingItem>>, ServiceError> {
+    let mut errors = ValidationErrors::default();
+    normalize_optional_enum::<ConditionMappingStatus>(&mut errors, "status", &mut params.status);
+    errors.into_result()?;
+    let status = params.status.unwrap_or_else(|| ConditionMappingStatus::Pending.as_str().to_string());
+    let limit = params.limit.unwrap_or(DEFAULT_QUEUE_LIMIT).clamp(1, MAX_QUEUE_LIMIT);
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let items = sqlx::query_as::<_, ConditionMappingItem>(
+        "SELECT m.*, p.first_name || ' ' || p.last_name AS patient_name, c.display AS code_display,
+             COUNT(*) FILTER (WHERE m.status = 'pending') OVER (PARTITION BY m.normalized_text) AS matching_pending
+         FROM condition_mappings m
+         JOIN patients p ON p.id = m.patient_id AND p.tenant_id = $3
+         LEFT JOIN terminology_codes c ON c.system = m.system AND c.code = m.code
+         WHERE m.status = $1
+         ORDER BY matching_pending DESC, m.normalized_text, m.id
+         LIMIT $2",
+    )
+    .bind(&status)
+    .bind(limit)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(items))
+}
+
+/// Lock a pending mapping of the tenant and, when asked, the tenant's other pending entries
+/// with the same text.
+async fn lock_pending(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: i32,
+    id: i32,
+    apply_to_matching: bool,
+) -> Result<Vec<ConditionMapping>, ServiceError> {
+    let mapping = sqlx::query_as::<_, ConditionMapping>(
+        "SELECT m.* FROM condition_mappings m JOIN patients p ON p.id = m.patient_id AND p.tenant_id = $2
+         WHERE m.id = $1 FOR UPDATE OF m",
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    if mapping.status != ConditionMappingStatus::Pending.as_str() {
+        return Err(ServiceError::Conflict(format!("Condition has already been reviewed ({})", mapping.status)));
+    }
+    let mut targets = vec![mapping];
+    if apply_to_matching {
+        let matching = sqlx::query_as::<_, ConditionMapping>(
+            "SELECT m.* FROM condition_mappings m JOIN patients p ON p.id = m.patient_id AND p.tenant_id = $3
+             WHERE m.normalized_text = $1 AND m.status = 'pending' AND m.id <> $2
+             ORDER BY m.id
+             FOR UPDATE OF m",
+        )
+        .bind(&targets[0].normalized_text)
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(db_error)?;
+        targets.extend(matching);
+    }
+    Ok(targets)
+}
+
+/// Confirm the suggested code, or pick another, and code the condition for the patient.
+/// Future runs map the same text to this code without review.
+pub async fn accept_condition_mapping(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<AcceptMappingRequest>,
+) -> Result<Json<Vec<ConditionMapping>>, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    trim_optional(&mut payload.code);
+    trim_optional(&mut payload.note);
+    if let Some(onset) = payload.onset_date {
+        check_not_future(&mut errors, "onset_date", onset);
+    }
+    errors.into_result()?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let targets = lock_pending(&mut tx, user.tenant_id, id, payload.apply_to_matching.unwrap_or(true)).await?;
+    let (system, code) = match (&payload.code, &targets[0].code) {
+        (Some(code), _) => (system_or_default(payload.system.as_deref().or(targets[0].system.as_deref())), code.clone()),
+        (None, Some(suggested)) => (targets[0].system.clone().unwrap_or_default(), suggested.clone()),
+        (None, None) => return Err(field_error("code", "is required; this condition has no suggested code")),
+    };
+    let code = active_code(&pool, "code", &system, &code).await?;
+
+    let mut accepted = Vec::with_capacity(targets.len());
+    for target in &targets {
+        let onset_date = if target.id == id { payload.onset_date } else { None };
+        let diagnosis_id =
+            open_diagnosis(&mut tx, &user, target.patient_id, &code, onset_date, &target.source_text).await?;
+        let mapping = sqlx::query_as::<_, ConditionMapping>(
+            "UPDATE condition_mappings
+             SET status = 'mapped', match_kind = $7, system = $2, code = $3, diagnosis_id = $4,
+                 reviewed_by = $5, reviewed_at = now(), review_note = $6
+             WHERE id = $1 RETURNING *",
+        )
+        .bind(target.id)
+        .bind(&code.system)
+        .bind(&code.code)
+        .bind(diagnosis_id)
+        .bind(user.id)
+        .bind(&payload.note)
+        .bind(MatchKind::Reviewed.as_str())
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        audit::record(&mut *tx, AuditEntry {
+            user_id: Some(user.id),
+            action: "condition_mapping.accepted",
+            entity_type: "condition_mapping",
+            entity_id: mapping.id,
+            patient_id: Some(mapping.patient_id),
+            details: serde_json::json!({
+                "condition": mapping.source_text,
+                "system": code.system,
+                "code": code.code,
+                "suggested": target.code,
+            }),
+        })
+        .await
+        .map_err(db_error)?;
+        accepted.push(mapping);
+    }
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(accepted))
+}
+
+/// Mark a condition as not codable (a symptom, a typo, "none"); a `note` is required.
+pub async fn reject_condition_mapping(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<RejectMappingRequest>>,
+) -> Result<Json<Vec<ConditionMapping>>, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut payload = payload.map(|Json(p)| p).unwrap_or_default();
+    trim_optional(&mut payload.note);
+    let Some(note) = payload.note else {
+        return Err(field_error("note", "is required when marking a condition unmappable"));
+    };
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let targets = lock_pending(&mut tx, user.tenant_id, id, payload.apply_to_matching.unwrap_or(true)).await?;
+    let mut rejected = Vec::with_capacity(targets.len());
+    for target in &targets {
+        let mapping = sqlx::query_as::<_, ConditionMapping>(
+            "UPDATE condition_mappings
+             SET status = 'unmappable', reviewed_by = $2, reviewed_at = now(), review_note = $3
+             WHERE id = $1 RETURNING *",
+        )
+        .bind(target.id)
+        .bind(user.id)
+        .bind(&note)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        audit::record(&mut *tx, AuditEntry {
+            user_id: Some(user.id),
+            action: "condition_mapping.rejected",
+            entity_type: "condition_mapping",
+            entity_id: mapping.id,
+            patient_id: Some(mapping.patient_id),
+            details: serde_json::json!({ "condition": mapping.source_text, "note": note }),
+        })
+        .await
+        .map_err(db_error)?;
+        rejected.push(mapping);
+    }
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(rejected))
+}
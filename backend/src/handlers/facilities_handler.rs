@@ -0,0 +1,451 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::models::error::ServiceError;
+use crate::models::facility::{Department, Facility, FacilityAssignment, FacilityStaff, FacilityType};
+use crate::services::access::check_platform_admin_role;
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::validation::{
+    code_from, field_error, normalize_optional_enum, require_optional_text, require_text, trim_optional, TextEnum,
+    ValidationErrors,
+};
+
+#[derive(Deserialize)]
+pub struct CreateFacilityRequest {
+    /// Derived from the name when left out, e.g. `PHC-RAMPUR`.
+    pub code: Option<String>,
+    pub name: String,
+    /// Defaults to `other`.
+    pub facility_type: Option<String>,
+    pub district: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateFacilityRequest {
+    pub code: Option<String>,
+    pub name: Option<String>,
+    pub facility_type: Option<String>,
+    pub district: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Closed facilities stay on old records but cannot be picked for new ones.
+    pub active: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct FacilityQuery {
+    pub district: Option<String>,
+    pub facility_type: Option<String>,
+    /// Defaults to active facilities only; `false` lists the closed ones.
+    pub active: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateDepartmentRequest {
+    /// Derived from the name when left out, e.g. `general-medicine`.
+    pub code: Option<String>,
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct AssignmentRequest {
+    pub department_id: Option<i32>,
+    /// Every facility the user works at; replaces the current assignments.
+    #[serde(default)]
+    pub facility_ids: Vec<i32>,
+    /// One of `facility_ids`; defaults to the first.
+    pub primary_facility_id: Option<i32>,
+}
+
+#[derive(Serialize)]
+pub struct UserAssignment {
+    pub user_id: i32,
+    pub department_id: Option<i32>,
+    pub department: Option<String>,
+    /// Primary facility first.
+    pub facilities: Vec<FacilityAssignment>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[FACILITY ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+fn unique_error(what: &str) -> impl FnOnce(sqlx::Error) -> ServiceError + '_ {
+    move |e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            ServiceError::Conflict(format!("A {} with that code or name already exists", what))
+        }
+        _ => db_error(e),
+    }
+}
+
+fn check_coordinates(errors: &mut ValidationErrors, latitude: Option<f64>, longitude: Option<f64>) {
+    if latitude.is_some() != longitude.is_some() {
+        errors.add("longitude", "latitude and longitude go together");
+    }
+    if latitude.is_some_and(|lat| !(-90.0..=90.0).contains(&lat)) {
+        errors.add("latitude", "must be between -90 and 90");
+    }
+    if longitude.is_some_and(|lon| !(-180.0..=180.0).contains(&lon)) {
+        errors.add("longitude", "must be between -180 and 180");
+    }
+}
+
+async fn load_facility(pool: &PgPool, id: i32) -> Result<Facility, ServiceError> {
+    sqlx::query_as::<_, Facility>("SELECT * FROM facilities WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(db_error)?
+        .ok_or(ServiceError::NotFound)
+}
+
+pub async fn list_facilities(
+    _user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(mut params): Query<FacilityQuery>,
+) -> Result<Json<Vec<Facility>>, ServiceError> {
+    let mut errors = ValidationErrors::default();
+    trim_optional(&mut params.district);
+    normalize_optional_enum::<FacilityType>(&mut errors, "facility_type", &mut params.facility_type);
+    errors.into_result()?;
+    let facilities = sqlx::query_as::<_, Facility>(
+        "SELECT * FROM facilities
+         WHERE active = $1
+           AND ($2::text IS NULL OR LOWER(district) = LOWER($2))
+           AND ($3::text IS NULL OR facility_type = $3)
+         ORDER BY name",
+    )
+    .bind(params.active.unwrap_or(true))
+    .bind(&params.district)
+    .bind(&params.facility_type)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+    Ok(Json(facilities))
+}
+
+pub async fn get_facility(
+    _user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Facility>, ServiceError> {
+    Ok(Json(load_facility(&pool, id).await?))
+}
+
+/// Register a facility. Facilities are shared by every tenant, so managing them is for
+/// platform admins.
+pub async fn create_facility(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<CreateFacilityRequest>,
+) -> Result<(StatusCode, Json<Facility>), ServiceError> {
+    check_platform_admin_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    require_text(&mut errors, "name", &mut payload.name);
+    require_optional_text(&mut errors, "code", &mut payload.code);
+    trim_optional(&mut payload.district);
+    normalize_optional_enum::<FacilityType>(&mut errors, "facility_type", &mut payload.facility_type);
+    check_coordinates(&mut errors, payload.latitude, payload.longitude);
+    let code = payload.code.as_deref().map(code_from).unwrap_or_else(|| code_from(&payload.name)).to_uppercase();
+    if code.is_empty() && !payload.name.is_empty() {
+        errors.add("code", "must contain letters or digits");
+    }
+    errors.into_result()?;
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    let facility = sqlx::query_as::<_, Facility>(
+        "INSERT INTO facilities (code, name, facility_type, district, latitude, longitude)
+         VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+    )
+    .bind(&code)
+    .bind(&payload.name)
+    .bind(payload.facility_type.as_deref().unwrap_or(FacilityType::Other.as_str()))
+    .bind(&payload.district)
+    .bind(payload.latitude)
+    .bind(payload.longitude)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(unique_error("facility"))?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "facility.created",
+        entity_type: "facility",
+        entity_id: facility.id,
+        patient_id: None,
+        details: serde_json::to_value(&facility).unwrap_or_default(),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(facility)))
+}
+
+pub async fn update_facility(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<UpdateFacilityRequest>,
+) -> Result<Json<Facility>, ServiceError> {
+    check_platform_admin_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    require_optional_text(&mut errors, "name", &mut payload.name);
+    require_optional_text(&mut errors, "code", &mut payload.code);
+    trim_optional(&mut payload.district);
+    normalize_optional_enum::<FacilityType>(&mut errors, "facility_type", &mut payload.facility_type);
+    check_coordinates(&mut errors, payload.latitude, payload.longitude);
+    let code = payload.code.as_deref().map(|code| code_from(code).to_uppercase());
+    if code.as_deref() == Some("") {
+        errors.add("code", "must contain letters or digits");
+    }
+    errors.into_result()?;
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    let before = sqlx::query_as::<_, Facility>("SELECT * FROM facilities WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?
+        .ok_or(ServiceError::NotFound)?;
+    let moved = payload.latitude.is_some();
+    let facility = sqlx::query_as::<_, Facility>(
+        "UPDATE facilities SET
+             code = COALESCE($2, code),
+             name = COALESCE($3, name),
+             facility_type = COALESCE($4, facility_type),
+             district = COALESCE($5, district),
+             latitude = CASE WHEN $6 THEN $7 ELSE latitude END,
+             longitude = CASE WHEN $6 THEN $8 ELSE longitude END,
+             active = COALESCE($9, active),
+             updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(&code)
+    .bind(&payload.name)
+    .bind(&payload.facility_type)
+    .bind(&payload.district)
+    .bind(moved)
+    .bind(payload.latitude)
+    .bind(payload.longitude)
+    .bind(payload.active)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(unique_error("facility"))?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "facility.updated",
+        entity_type: "facility",
+        entity_id: id,
+        patient_id: None,
+        details: serde_json::json!({
+            "before": serde_json::to_value(&before).unwrap_or_default(),
+            "after": serde_json::to_value(&facility).unwrap_or_default(),
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(facility))
+}
+
+/// Everyone of the caller's tenant assigned to a facility, primary staff first.
+pub async fn list_facility_staff(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<FacilityStaff>>, ServiceError> {
+    load_facility(&pool, id).await?;
+    let staff = sqlx::query_as::<_, FacilityStaff>(
+        "SELECT u.id AS user_id, u.email, u.role, u.department_id, d.name AS department, uf.is_primary
+         FROM user_facilities uf
+         JOIN users u ON u.id = uf.user_id
+         LEFT JOIN departments d ON d.id = u.department_id
+         WHERE uf.facility_id = $1 AND u.tenant_id = $2
+         ORDER BY uf.is_primary DESC, u.email",
+    )
+    .bind(id)
+    .bind(user.tenant_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+    Ok(Json(staff))
+}
+
+pub async fn list_departments(
+    _user: AuthUser,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<Department>>, ServiceError> {
+    let departments = sqlx::query_as::<_, Department>("SELECT * FROM departments WHERE active ORDER BY name")
+        .fetch_all(&pool)
+        .await
+        .map_err(db_error)?;
+    Ok(Json(departments))
+}
+
+pub async fn create_department(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<CreateDepartmentRequest>,
+) -> Result<(StatusCode, Json<Department>), ServiceError> {
+    check_platform_admin_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    require_text(&mut errors, "name", &mut payload.name);
+    require_optional_text(&mut errors, "code", &mut payload.code);
+    let code = payload.code.as_deref().map(code_from).unwrap_or_else(|| code_from(&payload.name)).to_lowercase();
+    if code.is_empty() && !payload.name.is_empty() {
+        errors.add("code", "must contain letters or digits");
+    }
+    errors.into_result()?;
+
+    let department = sqlx::query_as::<_, Department>(
+        "INSERT INTO departments (code, name) VALUES ($1, $2) RETURNING *",
+    )
+    .bind(&code)
+    .bind(&payload.name)
+    .fetch_one(&pool)
+    .await
+    .map_err(unique_error("department"))?;
+    Ok((StatusCode::CREATED, Json(department)))
+}
+
+/// `user_id`'s assignment; users of other tenants than `tenant_id` are not found.
+async fn load_assignment(
+    conn: &mut sqlx::PgConnection,
+    user_id: i32,
+    tenant_id: i32,
+) -> Result<UserAssignment, ServiceError> {
+    let department: Option<(Option<i32>, Option<String>)> = sqlx::query_as(
+        "SELECT u.department_id, d.name FROM users u LEFT JOIN departments d ON d.id = u.department_id WHERE u.id = $1 AND u.tenant_id = $2",
+    )
+    .bind(user_id)
+    .bind(tenant_id)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(db_error)?;
+    let Some((department_id, department)) = department else {
+        return Err(ServiceError::NotFound);
+    };
+    let facilities = sqlx::query_as::<_, FacilityAssignment>(
+        "SELECT uf.facility_id, f.name AS facility_name, uf.is_primary, uf.assigned_by, uf.assigned_at
+         FROM user_facilities uf JOIN facilities f ON f.id = uf.facility_id
+         WHERE uf.user_id = $1
+         ORDER BY uf.is_primary DESC, f.name",
+    )
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(db_error)?;
+    Ok(UserAssignment { user_id, department_id, department, facilities })
+}
+
+/// Where a member of staff works and in which department.
+pub async fn get_user_assignment(
+    user: AuthUser,
+    Path(user_id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<UserAssignment>, ServiceError> {
+    let mut conn = pool.acquire().await.map_err(db_error)?;
+    Ok(Json(load_assignment(&mut conn, user_id, user.tenant_id).await?))
+}
+
+/// Replace a user's department and facility assignments. Platform admins only.
+pub async fn set_user_assignment(
+    user: AuthUser,
+    Path(user_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<AssignmentRequest>,
+) -> Result<Json<UserAssignment>, ServiceError> {
+    check_platform_admin_role(&user)?;
+    let primary = payload.primary_facility_id.or(payload.facility_ids.first().copied());
+    payload.facility_ids.sort_unstable();
+    payload.facility_ids.dedup();
+    if let Some(primary) = primary {
+        if !payload.facility_ids.contains(&primary) {
+            return Err(field_error("primary_facility_id", "must be one of facility_ids"));
+        }
+    }
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    let before = load_assignment(&mut tx, user_id, user.tenant_id).await?;
+    if let Some(department_id) = payload.department_id {
+        let active: Option<bool> = sqlx::query_scalar("SELECT active FROM departments WHERE id = $1")
+            .bind(department_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        if active != Some(true) {
+            return Err(field_error("department_id", "is not an active department"));
+        }
+    }
+    let known: Vec<i32> = sqlx::query_scalar("SELECT id FROM facilities WHERE id = ANY($1) AND active")
+        .bind(&payload.facility_ids)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    let unknown: Vec<String> = payload
+        .facility_ids
+        .iter()
+        .filter(|id| !known.contains(id))
+        .map(|id| id.to_string())
+        .collect();
+    if !unknown.is_empty() {
+        return Err(field_error("facility_ids", format!("are not active facilities: {}", unknown.join(", "))));
+    }
+
+    sqlx::query("UPDATE users SET department_id = $2 WHERE id = $1")
+        .bind(user_id)
+        .bind(payload.department_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    sqlx::query("DELETE FROM user_facilities WHERE user_id = $1 AND NOT (facility_id = ANY($2))")
+        .bind(user_id)
+        .bind(&payload.facility_ids)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    // clear the old primary first so the one-primary index never sees two
+    sqlx::query("UPDATE user_facilities SET is_primary = false WHERE user_id = $1 AND is_primary")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    sqlx::query(
+        "INSERT INTO user_facilities (user_id, facility_id, is_primary, assigned_by)
+         SELECT $1, f, f = $3, $4 FROM UNNEST($2::int[]) AS f
+         ON CONFLICT (user_id, facility_id) DO UPDATE SET is_primary = EXCLUDED.is_primary",
+    )
+    .bind(user_id)
+    .bind(&payload.facility_ids)
+    .bind(primary)
+    .bind(user.id)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    let after = load_assignment(&mut tx, user_id, user.tenant_id).await?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "user.assignment_changed",
+        entity_type: "user",
+        entity_id: user_id,
+        patient_id: None,
+        details: serde_json::json!({
+            "before": { "department_id": before.department_id, "facility_ids": before.facilities.iter().map(|f| f.facility_id).collect::<Vec<_>>() },
+            "after": { "department_id": after.department_id, "facility_ids": after.facilities.iter().map(|f| f.facility_id).collect::<Vec<_>>(), "primary_facility_id": primary },
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(after))
+}
@@ -0,0 +1,336 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+use crate::models::error::ServiceError;
+use crate::models::geography::{GeoArea, GeoAreaRef, GeoLevel};
+use crate::services::access::check_platform_admin_role;
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::geography::{check_boundary, feature, feature_collection, GEOJSON_CONTENT_TYPE};
+use crate::services::validation::{
+    code_from, field_error, like_literal, normalize_enum, normalize_optional_enum, require_optional_text,
+    require_text, trim_optional, TextEnum, ValidationErrors,
+};
+
+/// The areas under `$1` (itself included), or every area when `$1` is null.
+const WITHIN: &str = "WITH RECURSIVE within AS (
+        SELECT id FROM geo_areas WHERE id = $1
+        UNION ALL
+        SELECT g.id FROM geo_areas g JOIN within w ON g.parent_id = w.id
+    )";
+
+#[derive(Deserialize)]
+pub struct CreateAreaRequest {
+    /// `state`, `district`, `block` or `village`.
+    pub level: String,
+    pub name: String,
+    /// Defaults to the parent's code followed by the name.
+    pub code: Option<String>,
+    /// An area one level up; required for everything but states.
+    pub parent_id: Option<i32>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub boundary: Option<Value>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateAreaRequest {
+    pub name: Option<String>,
+    pub code: Option<String>,
+    /// Move the area, e.g. a village out of the Unassigned block; the new parent must be on
+    /// the same level as the old one.
+    pub parent_id: Option<i32>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub boundary: Option<Value>,
+}
+
+#[derive(Deserialize)]
+pub struct AreaQuery {
+    pub level: Option<String>,
+    pub parent_id: Option<i32>,
+    /// Anywhere below this area, at any depth.
+    pub within: Option<i32>,
+    /// Case-insensitive substring of the name.
+    pub q: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct GeoAreaDetail {
+    #[serde(flatten)]
+    pub area: GeoArea,
+    /// From the state down to the parent.
+    pub ancestors: Vec<GeoAreaRef>,
+    pub children: Vec<GeoAreaRef>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[GEOGRAPHY ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+fn conflict(e: sqlx::Error) -> ServiceError {
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => ServiceError::Conflict(
+            "An area with that code, or with that name under the same parent, already exists".to_string(),
+        ),
+        _ => db_error(e),
+    }
+}
+
+fn check_coordinates(errors: &mut ValidationErrors, latitude: Option<f64>, longitude: Option<f64>) {
+    if latitude.is_some() != longitude.is_some() {
+        errors.add("longitude", "latitude and longitude go together");
+    }
+    if latitude.is_some_and(|lat| !(-90.0..=90.0).contains(&lat)) {
+        errors.add("latitude", "must be between -90 and 90");
+    }
+    if longitude.is_some_and(|lon| !(-180.0..=180.0).contains(&lon)) {
+        errors.add("longitude", "must be between -180 and 180");
+    }
+}
+
+async fn load_area<'e, E: sqlx::PgExecutor<'e>>(executor: E, id: i32) -> Result<Option<GeoArea>, ServiceError> {
+    sqlx::query_as::<_, GeoArea>("SELECT * FROM geo_areas WHERE id = $1")
+        .bind(id)
+        .fetch_optional(executor)
+        .await
+        .map_err(db_error)
+}
+
+async fn query_areas(pool: &PgPool, mut params: AreaQuery) -> Result<Vec<GeoArea>, ServiceError> {
+    let mut errors = ValidationErrors::default();
+    normalize_optional_enum::<GeoLevel>(&mut errors, "level", &mut params.level);
+    trim_optional(&mut params.q);
+    errors.into_result()?;
+    sqlx::query_as::<_, GeoArea>(&format!(
+        "{WITHIN}
+         SELECT * FROM geo_areas g
+         WHERE ($1::int IS NULL OR g.id IN (SELECT id FROM within))
+           AND ($2::text IS NULL OR g.level = $2)
+           AND ($3::int IS NULL OR g.parent_id = $3)
+           AND ($4::text IS NULL OR g.name ILIKE '%' || $4 || '%')
+         ORDER BY g.name, g.id"
+    ))
+    .bind(params.within)
+    .bind(&params.level)
+    .bind(params.parent_id)
+    .bind(params.q.as_deref().map(like_literal))
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)
+}
+
+pub async fn list_areas(
+    _user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<AreaQuery>,
+) -> Result<Json<Vec<GeoArea>>, ServiceError> {
+    Ok(Json(query_areas(&pool, params).await?))
+}
+
+/// The same areas as a GeoJSON feature collection for map views. Areas without a boundary
+/// are drawn as their point, or have no geometry.
+pub async fn get_areas_geojson(
+    _user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<AreaQuery>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let areas = query_areas(&pool, params).await?;
+    let features = areas.iter().map(|area| feature(area, json!({}))).collect();
+    Ok(([(header::CONTENT_TYPE, GEOJSON_CONTENT_TYPE)], Json(feature_collection(features))))
+}
+
+pub async fn get_area(
+    _user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<GeoAreaDetail>, ServiceError> {
+    let area = load_area(&pool, id).await?.ok_or(ServiceError::NotFound)?;
+    let ancestors = sqlx::query_as::<_, GeoAreaRef>(
+        "WITH RECURSIVE up AS (
+             SELECT parent_id, 1 AS depth FROM geo_areas WHERE id = $1
+             UNION ALL
+             SELECT g.parent_id, u.depth + 1 FROM up u JOIN geo_areas g ON g.id = u.parent_id
+         )
+         SELECT g.id, g.level, g.code, g.name FROM up u JOIN geo_areas g ON g.id = u.parent_id
+         ORDER BY u.depth DESC",
+    )
+    .bind(id)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+    let children = sqlx::query_as::<_, GeoAreaRef>(
+        "SELECT id, level, code, name FROM geo_areas WHERE parent_id = $1 ORDER BY name, id",
+    )
+    .bind(id)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+    Ok(Json(GeoAreaDetail { area, ancestors, children }))
+}
+
+/// Add a state, district, block or village. The geography is shared by every tenant, so
+/// this is for platform admins.
+pub async fn create_area(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<CreateAreaRequest>,
+) -> Result<(StatusCode, Json<GeoArea>), ServiceError> {
+    check_platform_admin_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    normalize_enum::<GeoLevel>(&mut errors, "level", &mut payload.level);
+    require_text(&mut errors, "name", &mut payload.name);
+    require_optional_text(&mut errors, "code", &mut payload.code);
+    check_coordinates(&mut errors, payload.latitude, payload.longitude);
+    check_boundary(&mut errors, &payload.boundary);
+    errors.into_result()?;
+    payload.name = payload.name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let level = GeoLevel::parse(&payload.level).unwrap_or(GeoLevel::Village);
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    let parent = match (level.parent(), payload.parent_id) {
+        (None, None) => None,
+        (None, Some(_)) => return Err(field_error("parent_id", "must be left out for a state")),
+        (Some(expected), parent_id) => {
+            let parent = match parent_id {
+                Some(parent_id) => load_area(&mut *tx, parent_id).await?,
+                None => None,
+            };
+            match parent {
+                Some(parent) if parent.level == expected.as_str() => Some(parent),
+                _ => {
+                    return Err(field_error("parent_id", format!("must be a {} for a {}", expected.as_str(), level.as_str())))
+                }
+            }
+        }
+    };
+    let own_code = code_from(payload.code.as_deref().unwrap_or(&payload.name)).to_uppercase();
+    if own_code.is_empty() {
+        return Err(field_error("code", "must contain letters or digits"));
+    }
+    let code = match (&payload.code, &parent) {
+        (None, Some(parent)) => format!("{}-{}", parent.code, own_code),
+        _ => own_code,
+    };
+
+    let area = sqlx::query_as::<_, GeoArea>(
+        "INSERT INTO geo_areas (level, code, name, parent_id, latitude, longitude, boundary)
+         VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+    )
+    .bind(level.as_str())
+    .bind(&code)
+    .bind(&payload.name)
+    .bind(parent.as_ref().map(|p| p.id))
+    .bind(payload.latitude)
+    .bind(payload.longitude)
+    .bind(&payload.boundary)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(conflict)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "geo_area.created",
+        entity_type: "geo_area",
+        entity_id: area.id,
+        patient_id: None,
+        details: json!({ "level": area.level, "code": area.code, "name": area.name, "parent_id": area.parent_id }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(area)))
+}
+
+/// Rename, recode, move or redraw an area. Patients of a renamed village show the new name.
+pub async fn update_area(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<UpdateAreaRequest>,
+) -> Result<Json<GeoArea>, ServiceError> {
+    check_platform_admin_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    require_optional_text(&mut errors, "name", &mut payload.name);
+    require_optional_text(&mut errors, "code", &mut payload.code);
+    check_coordinates(&mut errors, payload.latitude, payload.longitude);
+    check_boundary(&mut errors, &payload.boundary);
+    let code = payload.code.as_deref().map(|code| code_from(code).to_uppercase());
+    if code.as_deref() == Some("") {
+        errors.add("code", "must contain letters or digits");
+    }
+    errors.into_result()?;
+    let name = payload.name.as_deref().map(|name| name.split_whitespace().collect::<Vec<_>>().join(" "));
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    let before = sqlx::query_as::<_, GeoArea>("SELECT * FROM geo_areas WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?
+        .ok_or(ServiceError::NotFound)?;
+    if let Some(parent_id) = payload.parent_id {
+        let expected = GeoLevel::parse(&before.level).and_then(GeoLevel::parent);
+        let parent = load_area(&mut *tx, parent_id).await?;
+        match (expected, parent) {
+            (Some(expected), Some(parent)) if parent.level == expected.as_str() => {}
+            (None, _) => return Err(field_error("parent_id", "states have no parent")),
+            (Some(expected), _) => {
+                return Err(field_error("parent_id", format!("must be a {} for a {}", expected.as_str(), before.level)))
+            }
+        }
+    }
+    let moved = payload.latitude.is_some();
+    let area = sqlx::query_as::<_, GeoArea>(
+        "UPDATE geo_areas SET
+             name = COALESCE($2, name),
+             code = COALESCE($3, code),
+             parent_id = COALESCE($4, parent_id),
+             latitude = CASE WHEN $5 THEN $6 ELSE latitude END,
+             longitude = CASE WHEN $5 THEN $7 ELSE longitude END,
+             boundary = COALESCE($8, boundary),
+             updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(&name)
+    .bind(&code)
+    .bind(payload.parent_id)
+    .bind(moved)
+    .bind(payload.latitude)
+    .bind(payload.longitude)
+    .bind(&payload.boundary)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(conflict)?;
+    if area.name != before.name && area.level == GeoLevel::Village.as_str() {
+        sqlx::query("UPDATE patients SET village = $2 WHERE village_id = $1")
+            .bind(id)
+            .bind(&area.name)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+    }
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "geo_area.updated",
+        entity_type: "geo_area",
+        entity_id: id,
+        patient_id: None,
+        details: json!({
+            "before": { "code": before.code, "name": before.name, "parent_id": before.parent_id },
+            "after": { "code": area.code, "name": area.name, "parent_id": area.parent_id },
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(area))
+}
@@ -0,0 +1,371 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::handlers::records_handler::authorize_record;
+use crate::models::error::ServiceError;
+use crate::models::growth::{
+    AcuteMalnutrition, GrowthDeficit, GrowthIndicator, GrowthMeasurement, GrowthPoint, ReferencePoint,
+};
+use crate::services::access::{check_clinical_role, RecordAction};
+use crate::services::alerts::{notify_care_team, AlertEntry};
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::growth::{
+    age_based_measure, classify_acute, classify_deficit, implausible, GrowthReferences, GrowthSex, DAYS_PER_MONTH,
+    LYING_UNTIL_DAYS, MAX_AGE_DAYS,
+};
+use crate::services::tenancy;
+use crate::services::validation::{
+    check_not_future, field_error, normalize_enum, trim_optional, TextEnum, ValidationErrors,
+};
+
+#[derive(Deserialize)]
+pub struct RecordGrowthRequest {
+    /// Defaults to today.
+    pub measured_on: Option<NaiveDate>,
+    pub weight_kg: Option<f64>,
+    pub height_cm: Option<f64>,
+    /// Defaults to lying (length) under two years and standing (height) after.
+    pub measured_lying: Option<bool>,
+    pub muac_cm: Option<f64>,
+    #[serde(default)]
+    pub oedema: bool,
+    pub notes: Option<String>,
+    pub record_id: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct GrowthChartQuery {
+    /// wfa, lhfa, wfl or wfh. Defaults to wfa.
+    pub indicator: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct GrowthChart {
+    pub patient_id: i32,
+    pub indicator: String,
+    pub sex: Option<String>,
+    /// `months` for age-based charts, `cm` for weight-for-length/height.
+    pub x_unit: &'static str,
+    /// `kg` or `cm`.
+    pub y_unit: &'static str,
+    pub points: Vec<GrowthPoint>,
+    /// Empty when the child's sex has no reference table.
+    pub reference: Vec<ReferencePoint>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[GROWTH ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+async fn load_child(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: i32,
+    patient_id: i32,
+) -> Result<(NaiveDate, String), ServiceError> {
+    sqlx::query_as("SELECT date_of_birth, gender FROM patients WHERE id = $1 AND tenant_id = $2")
+        .bind(patient_id)
+        .bind(tenant_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?
+        .ok_or(ServiceError::NotFound)
+}
+
+pub async fn list_growth_measurements(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<GrowthMeasurement>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    load_child(&mut tx, user.tenant_id, patient_id).await?;
+    let measurements = sqlx::query_as::<_, GrowthMeasurement>(
+        "SELECT * FROM growth_measurements WHERE patient_id = $1 ORDER BY measured_on, id",
+    )
+    .bind(patient_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(measurements))
+}
+
+/// Record a measurement with its z-scores and nutrition classification. A child newly
+/// classified as acutely malnourished or stunted raises an alert for the care team.
+pub async fn record_growth_measurement(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    State(growth): State<Arc<GrowthReferences>>,
+    Json(mut payload): Json<RecordGrowthRequest>,
+) -> Result<(StatusCode, Json<GrowthMeasurement>), ServiceError> {
+    let measured_on = payload.measured_on.unwrap_or_else(|| Utc::now().date_naive());
+    let mut errors = ValidationErrors::default();
+    check_not_future(&mut errors, "measured_on", measured_on);
+    trim_optional(&mut payload.notes);
+    let mut check_range = |field: &str, value: Option<f64>, low: f64, high: f64| {
+        if value.is_some_and(|v| !(low..=high).contains(&v)) {
+            errors.add(field, format!("must be between {} and {}", low, high));
+        }
+    };
+    check_range("weight_kg", payload.weight_kg, 0.5, 40.0);
+    check_range("height_cm", payload.height_cm, 38.0, 130.0);
+    check_range("muac_cm", payload.muac_cm, 6.0, 25.0);
+    if payload.weight_kg.is_none() && payload.height_cm.is_none() && payload.muac_cm.is_none() && !payload.oedema {
+        errors.add("weight_kg", "or height_cm, muac_cm or oedema is required");
+    }
+    errors.into_result()?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let (date_of_birth, gender) = load_child(&mut tx, user.tenant_id, patient_id).await?;
+    if measured_on < date_of_birth {
+        return Err(field_error("measured_on", "cannot be before the patient's date of birth"));
+    }
+    let age_days = (measured_on - date_of_birth).num_days() as i32;
+    if age_days >= MAX_AGE_DAYS {
+        return Err(ServiceError::BadRequest("Growth monitoring covers children under five".to_string()));
+    }
+    if let Some(record_id) = payload.record_id {
+        let record = authorize_record(&mut *tx, record_id, &user, RecordAction::Write).await?;
+        if record.patient_id != patient_id {
+            return Err(field_error("record_id", "belongs to a different patient"));
+        }
+    }
+    let measured_lying = payload.measured_lying.unwrap_or(age_days < LYING_UNTIL_DAYS);
+
+    let sex = GrowthSex::from_gender(&gender);
+    let weight_for_age_z = sex.zip(payload.weight_kg).and_then(|(sex, weight)| growth.weight_for_age(sex, age_days, weight));
+    let height_for_age_z = sex
+        .zip(payload.height_cm)
+        .and_then(|(sex, height)| growth.height_for_age(sex, age_days, height, measured_lying));
+    let weight_for_height_z = match (sex, payload.height_cm, payload.weight_kg) {
+        (Some(sex), Some(height), Some(weight)) => {
+            growth.weight_for_height(sex, age_days, height, measured_lying, weight)
+        }
+        _ => None,
+    };
+    let mut errors = ValidationErrors::default();
+    for (field, indicator, z) in [
+        ("weight_kg", "wfa", weight_for_age_z),
+        ("height_cm", "lhfa", height_for_age_z),
+        ("weight_kg", "wfh", weight_for_height_z),
+    ] {
+        if let Some(z) = z.filter(|z| implausible(indicator, *z)) {
+            errors.add(field, format!("gives an implausible {} z-score of {:.1}; check the measurement", indicator, z));
+        }
+    }
+    errors.into_result()?;
+    let acute = classify_acute(age_days, weight_for_height_z, payload.muac_cm, payload.oedema);
+    let stunting = classify_deficit(height_for_age_z);
+    let underweight = classify_deficit(weight_for_age_z);
+
+    let measurement = sqlx::query_as::<_, GrowthMeasurement>(
+        "INSERT INTO growth_measurements (patient_id, record_id, measured_on, age_days, weight_kg, height_cm,
+             measured_lying, muac_cm, oedema, weight_for_age_z, height_for_age_z, weight_for_height_z,
+             acute_malnutrition, stunting, underweight, notes, recorded_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+         RETURNING *",
+    )
+    .bind(patient_id)
+    .bind(payload.record_id)
+    .bind(measured_on)
+    .bind(age_days)
+    .bind(payload.weight_kg)
+    .bind(payload.height_cm)
+    .bind(measured_lying)
+    .bind(payload.muac_cm)
+    .bind(payload.oedema)
+    .bind(weight_for_age_z)
+    .bind(height_for_age_z)
+    .bind(weight_for_height_z)
+    .bind(acute.map(|a| a.as_str()))
+    .bind(stunting.map(|s| s.as_str()))
+    .bind(underweight.map(|u| u.as_str()))
+    .bind(&payload.notes)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    // compare with the last assessment before this one
+    let (previous_acute, previous_stunting): (Option<String>, Option<String>) = sqlx::query_as(
+        "SELECT
+             (SELECT acute_malnutrition FROM growth_measurements
+              WHERE patient_id = $1 AND id <> $2 AND measured_on <= $3 AND acute_malnutrition IS NOT NULL
+              ORDER BY measured_on DESC, id DESC LIMIT 1),
+             (SELECT stunting FROM growth_measurements
+              WHERE patient_id = $1 AND id <> $2 AND measured_on <= $3 AND stunting IS NOT NULL
+              ORDER BY measured_on DESC, id DESC LIMIT 1)",
+    )
+    .bind(patient_id)
+    .bind(measurement.id)
+    .bind(measured_on)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    let previous_acute = previous_acute.as_deref().and_then(AcuteMalnutrition::parse).unwrap_or(AcuteMalnutrition::Normal);
+    let previous_stunting = previous_stunting.as_deref().and_then(GrowthDeficit::parse).unwrap_or(GrowthDeficit::Normal);
+    let mut findings = Vec::new();
+    match acute {
+        Some(AcuteMalnutrition::Sam) if previous_acute < AcuteMalnutrition::Sam => {
+            findings.push("severe acute malnutrition (SAM)")
+        }
+        Some(AcuteMalnutrition::Mam) if previous_acute < AcuteMalnutrition::Mam => {
+            findings.push("moderate acute malnutrition (MAM)")
+        }
+        _ => {}
+    }
+    match stunting {
+        Some(GrowthDeficit::Severe) if previous_stunting < GrowthDeficit::Severe => findings.push("severe stunting"),
+        Some(GrowthDeficit::Moderate) if previous_stunting < GrowthDeficit::Moderate => findings.push("stunting"),
+        _ => {}
+    }
+    if !findings.is_empty() {
+        let message = format!("Growth check on {}: {}", measured_on, findings.join(", "));
+        notify_care_team(&mut tx, AlertEntry {
+            patient_id,
+            vital_set_id: None,
+            kind: "malnutrition",
+            severity: if acute == Some(AcuteMalnutrition::Sam) { "high" } else { "medium" },
+            message: &message,
+        })
+        .await
+        .map_err(db_error)?;
+    }
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "growth_measurement.recorded",
+        entity_type: "growth_measurement",
+        entity_id: measurement.id,
+        patient_id: Some(patient_id),
+        details: serde_json::json!({
+            "measured_on": measurement.measured_on,
+            "acute_malnutrition": measurement.acute_malnutrition,
+            "stunting": measurement.stunting,
+            "underweight": measurement.underweight,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(measurement)))
+}
+
+/// Remove a measurement recorded in error.
+pub async fn delete_growth_measurement(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<StatusCode, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let measurement = sqlx::query_as::<_, GrowthMeasurement>(
+        "DELETE FROM growth_measurements g USING patients p
+         WHERE g.id = $1 AND p.id = g.patient_id AND p.tenant_id = $2 RETURNING g.*",
+    )
+    .bind(id)
+    .bind(user.tenant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "growth_measurement.deleted",
+        entity_type: "growth_measurement",
+        entity_id: id,
+        patient_id: Some(measurement.patient_id),
+        details: serde_json::json!({
+            "measured_on": measurement.measured_on,
+            "weight_kg": measurement.weight_kg,
+            "height_cm": measurement.height_cm,
+            "muac_cm": measurement.muac_cm,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A child's measurements for one indicator, with the WHO reference curves to plot them on.
+pub async fn get_growth_chart(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    State(growth): State<Arc<GrowthReferences>>,
+    Query(params): Query<GrowthChartQuery>,
+) -> Result<Json<GrowthChart>, ServiceError> {
+    let mut indicator = params.indicator.unwrap_or_else(|| GrowthIndicator::WeightForAge.as_str().to_string());
+    let mut errors = ValidationErrors::default();
+    normalize_enum::<GrowthIndicator>(&mut errors, "indicator", &mut indicator);
+    errors.into_result()?;
+    let indicator = GrowthIndicator::parse(&indicator).ok_or(ServiceError::InternalServerError)?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let (_, gender) = load_child(&mut tx, user.tenant_id, patient_id).await?;
+    let sex = GrowthSex::from_gender(&gender);
+    let measurements = sqlx::query_as::<_, GrowthMeasurement>(
+        "SELECT * FROM growth_measurements WHERE patient_id = $1 ORDER BY measured_on, id",
+    )
+    .bind(patient_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    let months = |age_days: i32| (f64::from(age_days) / DAYS_PER_MONTH * 100.0).round() / 100.0;
+    let points = measurements
+        .iter()
+        .filter_map(|m| {
+            let (x, y, z) = match indicator {
+                GrowthIndicator::WeightForAge => (months(m.age_days), m.weight_kg?, m.weight_for_age_z),
+                GrowthIndicator::HeightForAge => {
+                    let (_, height) = age_based_measure(m.age_days, m.height_cm?, m.measured_lying);
+                    (months(m.age_days), height, m.height_for_age_z)
+                }
+                GrowthIndicator::WeightForLength | GrowthIndicator::WeightForHeight => {
+                    let under_two = m.age_days < LYING_UNTIL_DAYS;
+                    if under_two != (indicator == GrowthIndicator::WeightForLength) {
+                        return None;
+                    }
+                    let (_, height) = age_based_measure(m.age_days, m.height_cm?, m.measured_lying);
+                    (height, m.weight_kg?, m.weight_for_height_z)
+                }
+            };
+            Some(GrowthPoint { measurement_id: m.id, measured_on: m.measured_on, x, y, z })
+        })
+        .collect();
+    let reference = match (sex, indicator) {
+        (None, _) => Vec::new(),
+        (Some(sex), GrowthIndicator::WeightForAge) => growth.reference_curve("wfa", sex),
+        (Some(sex), GrowthIndicator::HeightForAge) => {
+            let mut curve = growth.reference_curve("lfa", sex);
+            curve.extend(growth.reference_curve("hfa", sex));
+            curve
+        }
+        (Some(sex), GrowthIndicator::WeightForLength) => growth.reference_curve("wfl", sex),
+        (Some(sex), GrowthIndicator::WeightForHeight) => growth.reference_curve("wfh", sex),
+    };
+    Ok(Json(GrowthChart {
+        patient_id,
+        indicator: indicator.as_str().to_string(),
+        sex: sex.map(|s| if s == GrowthSex::Boys { "male" } else { "female" }.to_string()),
+        x_unit: match indicator {
+            GrowthIndicator::WeightForAge | GrowthIndicator::HeightForAge => "months",
+            _ => "cm",
+        },
+        y_unit: if indicator == GrowthIndicator::HeightForAge { "cm" } else { "kg" },
+        points,
+        reference,
+    }))
+}
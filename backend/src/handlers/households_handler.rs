@@ -0,0 +1,522 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, PgPool};
+
+use crate::models::error::ServiceError;
+use crate::models::household::{Household, PatientRelationship, RelationshipType};
+use crate::models::patient::Patient;
+use crate::services::auth::AuthUser;
+use crate::services::tenancy;
+use crate::services::validation::{
+    check_address, field_error, require_optional_text, require_text, trim_optional, TextEnum,
+    ValidationErrors,
+};
+
+#[derive(Deserialize)]
+pub struct CreateHouseholdRequest {
+    pub name: String,
+    pub head_patient_id: Option<i32>,
+    pub village: Option<String>,
+    pub address: Option<Value>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateHouseholdRequest {
+    pub name: Option<String>,
+    pub head_patient_id: Option<i32>,
+    pub village: Option<String>,
+    pub address: Option<Value>,
+}
+
+#[derive(Deserialize)]
+pub struct HouseholdQuery {
+    pub village: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AddMemberRequest {
+    pub patient_id: i32,
+}
+
+#[derive(Deserialize)]
+pub struct CreateRelationshipRequest {
+    /// The relative; the new row reads "related_patient_id is the <relationship_type> of the patient".
+    pub related_patient_id: i32,
+    pub relationship_type: String,
+}
+
+#[derive(Serialize)]
+pub struct HouseholdDetail {
+    pub household: Household,
+    pub members: Vec<Patient>,
+}
+
+/// Per-household workload summary for outreach teams.
+#[derive(Serialize, FromRow)]
+pub struct HouseholdOutreach {
+    pub household_id: i32,
+    pub name: String,
+    pub village: Option<String>,
+    pub head_patient_id: Option<i32>,
+    pub member_count: i64,
+    pub under_five_count: i64,
+    pub critical_count: i64,
+    pub overdue_visit_count: i64,
+    pub next_visit: Option<NaiveDate>,
+    pub last_contact: Option<NaiveDate>,
+}
+
+impl CreateHouseholdRequest {
+    fn validate(&mut self) -> Result<(), ServiceError> {
+        let mut errors = ValidationErrors::default();
+        require_text(&mut errors, "name", &mut self.name);
+        trim_optional(&mut self.village);
+        check_address(&mut errors, &mut self.address);
+        errors.into_result()
+    }
+}
+
+impl UpdateHouseholdRequest {
+    fn validate(&mut self) -> Result<(), ServiceError> {
+        let mut errors = ValidationErrors::default();
+        require_optional_text(&mut errors, "name", &mut self.name);
+        trim_optional(&mut self.village);
+        check_address(&mut errors, &mut self.address);
+        errors.into_result()
+    }
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[HOUSEHOLDS ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+/// Move a patient into a household; the patient becomes a member if they were not one already.
+async fn assign_member(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    tenant_id: i32,
+    household_id: i32,
+    patient_id: i32,
+    field: &str,
+) -> Result<(), ServiceError> {
+    let updated = sqlx::query(
+        "UPDATE patients SET household_id = $1, updated_at = now() WHERE id = $2 AND tenant_id = $3"
+    )
+    .bind(household_id)
+    .bind(patient_id)
+    .bind(tenant_id)
+    .execute(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    if updated.rows_affected() == 0 {
+        return Err(field_error(field, "does not refer to an existing patient"));
+    }
+    // A head who moves out no longer heads their old household.
+    sqlx::query("UPDATE households SET head_patient_id = NULL WHERE head_patient_id = $1 AND id <> $2")
+        .bind(patient_id)
+        .bind(household_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    Ok(())
+}
+
+pub async fn list_households(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<HouseholdQuery>,
+) -> Result<Json<Vec<Household>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let households = sqlx::query_as::<_, Household>(
+        r#"SELECT * FROM households
+           WHERE tenant_id = $2 AND ($1::text IS NULL OR LOWER(village) = LOWER($1))
+           ORDER BY village NULLS LAST, name"#
+    )
+    .bind(params.village.as_deref().map(str::trim))
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(households))
+}
+
+pub async fn get_household(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<HouseholdDetail>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let household = load_household(&mut tx, user.tenant_id, id).await?;
+    let members = fetch_members(&mut tx, user.tenant_id, id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(HouseholdDetail { household, members }))
+}
+
+/// A household of the caller's tenant, or `404`.
+async fn load_household(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    tenant_id: i32,
+    id: i32,
+) -> Result<Household, ServiceError> {
+    sqlx::query_as::<_, Household>("SELECT * FROM households WHERE id = $1 AND tenant_id = $2")
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?
+        .ok_or(ServiceError::NotFound)
+}
+
+pub async fn create_household(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<CreateHouseholdRequest>,
+) -> Result<(StatusCode, Json<Household>), ServiceError> {
+    payload.validate()?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let household = sqlx::query_as::<_, Household>(
+        r#"INSERT INTO households (name, village, address, created_at, updated_at, tenant_id)
+           VALUES ($1, $2, $3, now(), now(), $4)
+           RETURNING *"#
+    )
+    .bind(&payload.name)
+    .bind(&payload.village)
+    .bind(&payload.address)
+    .bind(user.tenant_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    let household = match payload.head_patient_id {
+        Some(head_id) => set_head(&mut tx, user.tenant_id, household.id, head_id).await?,
+        None => household,
+    };
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(household)))
+}
+
+pub async fn update_household(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<UpdateHouseholdRequest>,
+) -> Result<Json<Household>, ServiceError> {
+    payload.validate()?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let household = sqlx::query_as::<_, Household>(
+        r#"UPDATE households SET
+               name = COALESCE($1, name),
+               village = COALESCE($2, village),
+               address = COALESCE($3, address),
+               updated_at = now()
+           WHERE id = $4 AND tenant_id = $5
+           RETURNING *"#
+    )
+    .bind(&payload.name)
+    .bind(&payload.village)
+    .bind(&payload.address)
+    .bind(id)
+    .bind(user.tenant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+
+    let household = match payload.head_patient_id {
+        Some(head_id) => set_head(&mut tx, user.tenant_id, household.id, head_id).await?,
+        None => household,
+    };
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(household))
+}
+
+async fn set_head(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    tenant_id: i32,
+    household_id: i32,
+    head_id: i32,
+) -> Result<Household, ServiceError> {
+    assign_member(tx, tenant_id, household_id, head_id, "head_patient_id").await?;
+    sqlx::query_as::<_, Household>(
+        "UPDATE households SET head_patient_id = $1, updated_at = now() WHERE id = $2 RETURNING *"
+    )
+    .bind(head_id)
+    .bind(household_id)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(db_error)
+}
+
+pub async fn delete_household(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<StatusCode, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let result = sqlx::query("DELETE FROM households WHERE id = $1 AND tenant_id = $2")
+        .bind(id)
+        .bind(user.tenant_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    if result.rows_affected() > 0 {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ServiceError::NotFound)
+    }
+}
+
+async fn fetch_members(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    tenant_id: i32,
+    household_id: i32,
+) -> Result<Vec<Patient>, ServiceError> {
+    sqlx::query_as::<_, Patient>(
+        "SELECT * FROM patients WHERE household_id = $1 AND tenant_id = $2 ORDER BY date_of_birth, id"
+    )
+    .bind(household_id)
+    .bind(tenant_id)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(db_error)
+}
+
+pub async fn list_household_members(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<Patient>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    load_household(&mut tx, user.tenant_id, id).await?;
+    let members = fetch_members(&mut tx, user.tenant_id, id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(members))
+}
+
+/// Add a patient of the caller's tenant to the household; patients of other tenants are
+/// reported as unknown.
+pub async fn add_household_member(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(payload): Json<AddMemberRequest>,
+) -> Result<StatusCode, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let exists: Option<i32> = sqlx::query_scalar("SELECT id FROM households WHERE id = $1 AND tenant_id = $2 FOR UPDATE")
+        .bind(id)
+        .bind(user.tenant_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    if exists.is_none() {
+        return Err(ServiceError::NotFound);
+    }
+    assign_member(&mut tx, user.tenant_id, id, payload.patient_id, "patient_id").await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn remove_household_member(
+    user: AuthUser,
+    Path((id, patient_id)): Path<(i32, i32)>,
+    State(pool): State<PgPool>,
+) -> Result<StatusCode, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let result = sqlx::query(
+        "UPDATE patients SET household_id = NULL, updated_at = now()
+         WHERE id = $1 AND household_id = $2 AND tenant_id = $3"
+    )
+    .bind(patient_id)
+    .bind(id)
+    .bind(user.tenant_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    if result.rows_affected() == 0 {
+        return Err(ServiceError::NotFound);
+    }
+    sqlx::query("UPDATE households SET head_patient_id = NULL, updated_at = now() WHERE id = $1 AND head_patient_id = $2")
+        .bind(id)
+        .bind(patient_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Households ranked by how urgently they need a visit.
+pub async fn get_household_outreach(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<HouseholdQuery>,
+) -> Result<Json<Vec<HouseholdOutreach>>, ServiceError> {
+    let under_five_cutoff = Utc::now()
+        .date_naive()
+        .checked_sub_months(chrono::Months::new(60))
+        .unwrap_or(NaiveDate::MIN);
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let rows = sqlx::query_as::<_, HouseholdOutreach>(
+        r#"
+        SELECT h.id AS household_id, h.name, h.village, h.head_patient_id,
+               COUNT(p.id) AS member_count,
+               COUNT(p.id) FILTER (WHERE p.date_of_birth > $2) AS under_five_count,
+               COUNT(p.id) FILTER (WHERE p.critical_flag) AS critical_count,
+               COUNT(p.id) FILTER (WHERE p.next_visit < CURRENT_DATE) AS overdue_visit_count,
+               MIN(p.next_visit) FILTER (WHERE p.next_visit >= CURRENT_DATE) AS next_visit,
+               MAX(last_record.date) AS last_contact
+        FROM households h
+        LEFT JOIN patients p ON p.household_id = h.id
+        LEFT JOIN LATERAL (
+            SELECT MAX(r.date) AS date FROM medical_records r WHERE r.patient_id = p.id
+        ) last_record ON true
+        WHERE h.tenant_id = $3 AND ($1::text IS NULL OR LOWER(h.village) = LOWER($1))
+        GROUP BY h.id
+        ORDER BY critical_count DESC, overdue_visit_count DESC, last_contact ASC NULLS FIRST, h.id
+        "#,
+    )
+    .bind(params.village.as_deref().map(str::trim))
+    .bind(under_five_cutoff)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(rows))
+}
+
+/// Whether `patient_id` is a patient of the caller's tenant.
+async fn patient_in_tenant(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    tenant_id: i32,
+    patient_id: i32,
+) -> Result<Option<String>, ServiceError> {
+    sqlx::query_scalar("SELECT gender FROM patients WHERE id = $1 AND tenant_id = $2")
+        .bind(patient_id)
+        .bind(tenant_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)
+}
+
+pub async fn list_relationships(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<PatientRelationship>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    patient_in_tenant(&mut tx, user.tenant_id, id).await?.ok_or(ServiceError::NotFound)?;
+    let relationships = sqlx::query_as::<_, PatientRelationship>(
+        "SELECT * FROM patient_relationships WHERE patient_id = $1 ORDER BY relationship_type, id"
+    )
+    .bind(id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(relationships))
+}
+
+/// Link two patients of the caller's tenant; the reverse relationship is stored alongside.
+pub async fn create_relationship(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(payload): Json<CreateRelationshipRequest>,
+) -> Result<(StatusCode, Json<PatientRelationship>), ServiceError> {
+    let relationship_type = RelationshipType::parse(&payload.relationship_type).ok_or_else(|| {
+        field_error("relationship_type", format!("must be one of: {}", RelationshipType::allowed()))
+    })?;
+    if payload.related_patient_id == id {
+        return Err(field_error("related_patient_id", "must be a different patient"));
+    }
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let patient_gender = patient_in_tenant(&mut tx, user.tenant_id, id).await?.ok_or(ServiceError::NotFound)?;
+    let related_exists = patient_in_tenant(&mut tx, user.tenant_id, payload.related_patient_id).await?;
+    let mut errors = ValidationErrors::default();
+    if related_exists.is_none() {
+        errors.add("related_patient_id", "does not refer to an existing patient");
+    }
+    // When the relative is a child, the patient is the parent and their gender decides mother/father.
+    let inverse = relationship_type.inverse(&patient_gender.to_lowercase());
+    if inverse.is_none() {
+        errors.add(
+            "relationship_type",
+            "cannot infer mother or father for this patient's gender; record the link from the child's side",
+        );
+    }
+    errors.into_result()?;
+
+    let relationship = sqlx::query_as::<_, PatientRelationship>(
+        r#"INSERT INTO patient_relationships (patient_id, related_patient_id, relationship_type, created_at)
+           VALUES ($1, $2, $3, now())
+           ON CONFLICT DO NOTHING
+           RETURNING *"#
+    )
+    .bind(id)
+    .bind(payload.related_patient_id)
+    .bind(relationship_type.as_str())
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::Conflict("Relationship already exists".to_string()))?;
+
+    if let Some(inverse) = inverse {
+        sqlx::query(
+            r#"INSERT INTO patient_relationships (patient_id, related_patient_id, relationship_type, created_at)
+               VALUES ($1, $2, $3, now())
+               ON CONFLICT DO NOTHING"#
+        )
+        .bind(payload.related_patient_id)
+        .bind(id)
+        .bind(inverse.as_str())
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    }
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(relationship)))
+}
+
+pub async fn delete_relationship(
+    user: AuthUser,
+    Path((id, relationship_id)): Path<(i32, i32)>,
+    State(pool): State<PgPool>,
+) -> Result<StatusCode, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    patient_in_tenant(&mut tx, user.tenant_id, id).await?.ok_or(ServiceError::NotFound)?;
+    let relationship = sqlx::query_as::<_, PatientRelationship>(
+        "DELETE FROM patient_relationships WHERE id = $1 AND patient_id = $2 RETURNING *"
+    )
+    .bind(relationship_id)
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+
+    let inverse_types: Vec<&str> = RelationshipType::parse(&relationship.relationship_type)
+        .map(|t| t.inverse_types().iter().map(|i| i.as_str()).collect())
+        .unwrap_or_default();
+    sqlx::query(
+        r#"DELETE FROM patient_relationships
+           WHERE patient_id = $1 AND related_patient_id = $2 AND relationship_type = ANY($3)"#
+    )
+    .bind(relationship.related_patient_id)
+    .bind(relationship.patient_id)
+    .bind(inverse_types)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,773 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use crate::handlers::records_handler::authorize_record;
+use crate::models::error::ServiceError;
+use crate::models::immunization::{
+    DoseState, DoseStatus, Immunization, ImmunizationSchedule, ScheduleDose, VaccinationSite, Vaccine,
+};
+use crate::models::patient::Gender;
+use crate::models::prescription::Route;
+use crate::services::access::{check_clinical_role, check_platform_admin_role, RecordAction};
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::immunization::{counts_for_coverage, evaluate_schedule};
+use crate::services::tenancy;
+use crate::services::validation::{
+    check_not_future, field_error, normalize_optional_enum, require_text, trim_optional, ValidationErrors,
+};
+use crate::services::vitals::age_in_months;
+
+const DEFAULT_COVERAGE_AGE_MONTHS: u32 = 60;
+const DEFAULT_DEFAULTER_LIMIT: usize = 200;
+const MAX_DEFAULTER_LIMIT: usize = 1000;
+
+#[derive(Deserialize)]
+pub struct RecordImmunizationRequest {
+    /// Vaccine code or name.
+    pub vaccine: String,
+    /// Defaults to the dose after the last one recorded for the vaccine.
+    pub dose_number: Option<i32>,
+    /// Defaults to today.
+    pub administered_on: Option<NaiveDate>,
+    /// Required unless `historical`.
+    pub lot_number: Option<String>,
+    /// Defaults to the vaccine's usual site.
+    pub site: Option<String>,
+    /// Defaults to the vaccine's usual route.
+    pub route: Option<String>,
+    /// Defaults to the current user unless `administered_by_name` is given or the dose is historical.
+    pub administered_by: Option<i32>,
+    pub administered_by_name: Option<String>,
+    #[serde(default)]
+    pub historical: bool,
+    pub notes: Option<String>,
+    pub record_id: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct ScheduleRequest {
+    pub name: String,
+    pub is_default: Option<bool>,
+    pub doses: Vec<ScheduleDose>,
+}
+
+#[derive(Deserialize)]
+pub struct VaccineRequest {
+    pub name: String,
+    pub default_route: Option<String>,
+    pub default_site: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ScheduleQuery {
+    /// Defaults to the default schedule.
+    pub schedule: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CoverageQuery {
+    pub schedule: Option<String>,
+    pub village: Option<String>,
+    /// Children younger than this are counted. Defaults to 60.
+    pub max_age_months: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct DefaulterQuery {
+    pub schedule: Option<String>,
+    pub village: Option<String>,
+    /// Also list children whose doses are due but not yet overdue.
+    #[serde(default)]
+    pub include_due: bool,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct ScheduleDetail {
+    #[serde(flatten)]
+    pub schedule: ImmunizationSchedule,
+    pub doses: Vec<ScheduleDose>,
+}
+
+#[derive(Serialize, Default)]
+pub struct DoseCounts {
+    pub given: usize,
+    pub due: usize,
+    pub overdue: usize,
+    pub upcoming: usize,
+    pub missed: usize,
+}
+
+#[derive(Serialize)]
+pub struct ImmunizationCard {
+    pub patient_id: i32,
+    pub patient_name: String,
+    pub date_of_birth: NaiveDate,
+    pub schedule: String,
+    pub schedule_name: String,
+    pub counts: DoseCounts,
+    pub doses: Vec<DoseState>,
+    /// Every dose recorded, including vaccines outside the schedule.
+    pub immunizations: Vec<Immunization>,
+}
+
+#[derive(Serialize)]
+pub struct DoseCoverage {
+    pub vaccine_code: String,
+    pub dose_number: i32,
+    pub label: String,
+    /// Children who should have had the dose by now.
+    pub eligible: usize,
+    pub given: usize,
+    pub coverage_percent: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct VillageCoverage {
+    pub village: Option<String>,
+    pub children: usize,
+    /// Children with at least one overdue dose.
+    pub defaulters: usize,
+    pub doses: Vec<DoseCoverage>,
+}
+
+#[derive(Serialize)]
+pub struct Defaulter {
+    pub patient_id: i32,
+    pub patient_name: String,
+    pub village: Option<String>,
+    pub household_id: Option<i32>,
+    pub phone_number: String,
+    pub date_of_birth: NaiveDate,
+    pub age_months: i32,
+    pub oldest_overdue_date: Option<NaiveDate>,
+    pub overdue: Vec<DoseState>,
+    pub due: Vec<DoseState>,
+}
+
+#[derive(FromRow)]
+struct ChildRow {
+    id: i32,
+    patient_name: String,
+    village: Option<String>,
+    household_id: Option<i32>,
+    phone_number: String,
+    date_of_birth: NaiveDate,
+    gender: String,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[IMMUNIZATIONS ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+/// The named schedule, or the default one, with its doses.
+async fn load_schedule(pool: &PgPool, code: Option<&str>) -> Result<ScheduleDetail, ServiceError> {
+    let code = code.map(|c| c.trim().to_lowercase()).filter(|c| !c.is_empty());
+    let schedule = sqlx::query_as::<_, ImmunizationSchedule>(
+        "SELECT * FROM immunization_schedules WHERE ($1::text IS NULL AND is_default) OR code = $1",
+    )
+    .bind(&code)
+    .fetch_optional(pool)
+    .await
+    .map_err(db_error)?;
+    let Some(schedule) = schedule else {
+        return Err(match code {
+            Some(_) => field_error("schedule", "is not a known schedule"),
+            None => field_error("schedule", "is required; no default schedule is configured"),
+        });
+    };
+    let doses = sqlx::query_as::<_, ScheduleDose>(
+        "SELECT vaccine_code, dose_number, label, min_age_days, due_age_days, overdue_age_days, max_age_days,
+             min_interval_days, sex
+         FROM schedule_doses WHERE schedule_code = $1
+         ORDER BY due_age_days, vaccine_code, dose_number",
+    )
+    .bind(&schedule.code)
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?;
+    Ok(ScheduleDetail { schedule, doses })
+}
+
+/// Active patients of the tenant born after `born_after` with their recorded doses.
+async fn load_children(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: i32,
+    village: Option<&str>,
+    born_after: Option<NaiveDate>,
+) -> Result<Vec<(ChildRow, Vec<Immunization>)>, ServiceError> {
+    let children = sqlx::query_as::<_, ChildRow>(
+        "SELECT id, first_name || ' ' || last_name AS patient_name, village, household_id, phone_number,
+             date_of_birth, gender
+         FROM patients
+         WHERE status = 'active' AND tenant_id = $3
+           AND ($1::text IS NULL OR LOWER(village) = LOWER($1))
+           AND ($2::date IS NULL OR date_of_birth > $2)
+         ORDER BY village, last_name, first_name, id",
+    )
+    .bind(village.map(str::trim).filter(|v| !v.is_empty()))
+    .bind(born_after)
+    .bind(tenant_id)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    let ids: Vec<i32> = children.iter().map(|c| c.id).collect();
+    let immunizations = sqlx::query_as::<_, Immunization>("SELECT * FROM immunizations WHERE patient_id = ANY($1)")
+        .bind(&ids)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    let mut by_patient: HashMap<i32, Vec<Immunization>> = HashMap::new();
+    for immunization in immunizations {
+        by_patient.entry(immunization.patient_id).or_default().push(immunization);
+    }
+    Ok(children
+        .into_iter()
+        .map(|child| {
+            let given = by_patient.remove(&child.id).unwrap_or_default();
+            (child, given)
+        })
+        .collect())
+}
+
+pub async fn list_vaccines(_user: AuthUser, State(pool): State<PgPool>) -> Result<Json<Vec<Vaccine>>, ServiceError> {
+    let vaccines = sqlx::query_as::<_, Vaccine>("SELECT * FROM vaccines ORDER BY name")
+        .fetch_all(&pool)
+        .await
+        .map_err(db_error)?;
+    Ok(Json(vaccines))
+}
+
+/// Add a vaccine to the catalog or rename it. The catalog is shared by every tenant, so this
+/// is for platform admins.
+pub async fn put_vaccine(
+    user: AuthUser,
+    Path(code): Path<String>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<VaccineRequest>,
+) -> Result<Json<Vaccine>, ServiceError> {
+    check_platform_admin_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    require_text(&mut errors, "name", &mut payload.name);
+    normalize_optional_enum::<Route>(&mut errors, "default_route", &mut payload.default_route);
+    normalize_optional_enum::<VaccinationSite>(&mut errors, "default_site", &mut payload.default_site);
+    errors.into_result()?;
+    let vaccine = sqlx::query_as::<_, Vaccine>(
+        "INSERT INTO vaccines (code, name, default_route, default_site) VALUES ($1, $2, $3, $4)
+         ON CONFLICT (code) DO UPDATE SET name = $2, default_route = $3, default_site = $4
+         RETURNING *",
+    )
+    .bind(code.trim().to_lowercase())
+    .bind(&payload.name)
+    .bind(&payload.default_route)
+    .bind(&payload.default_site)
+    .fetch_one(&pool)
+    .await
+    .map_err(db_error)?;
+    Ok(Json(vaccine))
+}
+
+pub async fn list_schedules(
+    _user: AuthUser,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<ScheduleDetail>>, ServiceError> {
+    let codes: Vec<String> =
+        sqlx::query_scalar("SELECT code FROM immunization_schedules ORDER BY is_default DESC, code")
+            .fetch_all(&pool)
+            .await
+            .map_err(db_error)?;
+    let mut schedules = Vec::with_capacity(codes.len());
+    for code in codes {
+        schedules.push(load_schedule(&pool, Some(&code)).await?);
+    }
+    Ok(Json(schedules))
+}
+
+pub async fn get_schedule(
+    _user: AuthUser,
+    Path(code): Path<String>,
+    State(pool): State<PgPool>,
+) -> Result<Json<ScheduleDetail>, ServiceError> {
+    match load_schedule(&pool, Some(&code)).await {
+        Err(ServiceError::Validation(_)) => Err(ServiceError::NotFound),
+        result => result.map(Json),
+    }
+}
+
+/// Create a schedule or replace all of its doses. Platform admins only: every tenant uses the
+/// same schedules.
+pub async fn put_schedule(
+    user: AuthUser,
+    Path(code): Path<String>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<ScheduleRequest>,
+) -> Result<Json<ScheduleDetail>, ServiceError> {
+    check_platform_admin_role(&user)?;
+    let code = code.trim().to_lowercase();
+    let vaccines: HashSet<String> = sqlx::query_scalar("SELECT code FROM vaccines")
+        .fetch_all(&pool)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .collect();
+
+    let mut errors = ValidationErrors::default();
+    require_text(&mut errors, "name", &mut payload.name);
+    if payload.doses.is_empty() {
+        errors.add("doses", "must not be empty");
+    }
+    let mut seen = HashSet::new();
+    for (index, dose) in payload.doses.iter_mut().enumerate() {
+        let field = |name: &str| format!("doses[{}].{}", index, name);
+        dose.vaccine_code = dose.vaccine_code.trim().to_lowercase();
+        if !vaccines.contains(&dose.vaccine_code) {
+            errors.add(field("vaccine_code"), "is not in the vaccine catalog");
+        }
+        if dose.dose_number < 1 {
+            errors.add(field("dose_number"), "must be at least 1");
+        } else if !seen.insert((dose.vaccine_code.clone(), dose.dose_number)) {
+            errors.add(field("dose_number"), "is listed twice for this vaccine");
+        }
+        require_text(&mut errors, &field("label"), &mut dose.label);
+        if dose.min_age_days < 0 {
+            errors.add(field("min_age_days"), "must not be negative");
+        }
+        if dose.due_age_days < dose.min_age_days {
+            errors.add(field("due_age_days"), "must not be less than min_age_days");
+        }
+        if dose.overdue_age_days < dose.due_age_days {
+            errors.add(field("overdue_age_days"), "must not be less than due_age_days");
+        }
+        if dose.max_age_days.is_some_and(|max| max < dose.overdue_age_days) {
+            errors.add(field("max_age_days"), "must not be less than overdue_age_days");
+        }
+        if dose.min_interval_days.is_some_and(|days| days < 1) {
+            errors.add(field("min_interval_days"), "must be at least 1");
+        }
+        normalize_optional_enum::<Gender>(&mut errors, &field("sex"), &mut dose.sex);
+    }
+    errors.into_result()?;
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    if payload.is_default == Some(true) {
+        sqlx::query("UPDATE immunization_schedules SET is_default = FALSE WHERE is_default AND code <> $1")
+            .bind(&code)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+    }
+    sqlx::query(
+        "INSERT INTO immunization_schedules (code, name, is_default, updated_by) VALUES ($1, $2, COALESCE($3, FALSE), $4)
+         ON CONFLICT (code) DO UPDATE SET name = $2, is_default = COALESCE($3, immunization_schedules.is_default),
+             updated_by = $4, updated_at = now()",
+    )
+    .bind(&code)
+    .bind(&payload.name)
+    .bind(payload.is_default)
+    .bind(user.id)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    sqlx::query("DELETE FROM schedule_doses WHERE schedule_code = $1")
+        .bind(&code)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    for dose in &payload.doses {
+        sqlx::query(
+            "INSERT INTO schedule_doses (schedule_code, vaccine_code, dose_number, label, min_age_days, due_age_days,
+                 overdue_age_days, max_age_days, min_interval_days, sex)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(&code)
+        .bind(&dose.vaccine_code)
+        .bind(dose.dose_number)
+        .bind(&dose.label)
+        .bind(dose.min_age_days)
+        .bind(dose.due_age_days)
+        .bind(dose.overdue_age_days)
+        .bind(dose.max_age_days)
+        .bind(dose.min_interval_days)
+        .bind(&dose.sex)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    }
+    tx.commit().await.map_err(db_error)?;
+    load_schedule(&pool, Some(&code)).await.map(Json)
+}
+
+pub async fn list_immunizations(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<Immunization>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let exists: Option<i32> = sqlx::query_scalar("SELECT id FROM patients WHERE id = $1 AND tenant_id = $2")
+        .bind(patient_id)
+        .bind(user.tenant_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    exists.ok_or(ServiceError::NotFound)?;
+    let immunizations = sqlx::query_as::<_, Immunization>(
+        "SELECT * FROM immunizations WHERE patient_id = $1 ORDER BY administered_on, vaccine_code, dose_number",
+    )
+    .bind(patient_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(immunizations))
+}
+
+pub async fn record_immunization(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<RecordImmunizationRequest>,
+) -> Result<(StatusCode, Json<Immunization>), ServiceError> {
+    check_clinical_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    require_text(&mut errors, "vaccine", &mut payload.vaccine);
+    trim_optional(&mut payload.lot_number);
+    trim_optional(&mut payload.administered_by_name);
+    trim_optional(&mut payload.notes);
+    normalize_optional_enum::<VaccinationSite>(&mut errors, "site", &mut payload.site);
+    normalize_optional_enum::<Route>(&mut errors, "route", &mut payload.route);
+    if payload.lot_number.is_none() && !payload.historical {
+        errors.add("lot_number", "is required unless the dose is historical");
+    }
+    if payload.dose_number.is_some_and(|dose| dose < 1) {
+        errors.add("dose_number", "must be at least 1");
+    }
+    let administered_on = payload.administered_on.unwrap_or_else(|| Utc::now().date_naive());
+    check_not_future(&mut errors, "administered_on", administered_on);
+    errors.into_result()?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let date_of_birth: NaiveDate =
+        sqlx::query_scalar("SELECT date_of_birth FROM patients WHERE id = $1 AND tenant_id = $2")
+            .bind(patient_id)
+            .bind(user.tenant_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(db_error)?
+            .ok_or(ServiceError::NotFound)?;
+    if administered_on < date_of_birth {
+        return Err(field_error("administered_on", "cannot be before the patient's date of birth"));
+    }
+    let vaccine = sqlx::query_as::<_, Vaccine>("SELECT * FROM vaccines WHERE code = lower($1) OR lower(name) = lower($1)")
+        .bind(&payload.vaccine)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| field_error("vaccine", "is not in the vaccine catalog"))?;
+    if let Some(record_id) = payload.record_id {
+        let record = authorize_record(&mut *tx, record_id, &user, RecordAction::Read).await?;
+        if record.patient_id != patient_id {
+            return Err(field_error("record_id", "belongs to a different patient"));
+        }
+    }
+    let administered_by = match (payload.administered_by, &payload.administered_by_name) {
+        (Some(id), _) => Some(id),
+        (None, None) if !payload.historical => Some(user.id),
+        _ => None,
+    };
+
+    // the same vaccine twice in a day is a double entry, whatever dose number it was given
+    let same_day: Option<i32> = sqlx::query_scalar(
+        "SELECT dose_number FROM immunizations WHERE patient_id = $1 AND vaccine_code = $2 AND administered_on = $3",
+    )
+    .bind(patient_id)
+    .bind(&vaccine.code)
+    .bind(administered_on)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    if let Some(dose) = same_day {
+        return Err(ServiceError::Conflict(format!(
+            "Dose {} of {} is already recorded on {}",
+            dose, vaccine.name, administered_on
+        )));
+    }
+    let dose_number = match payload.dose_number {
+        Some(dose) => dose,
+        None => {
+            let last: Option<i32> = sqlx::query_scalar(
+                "SELECT MAX(dose_number) FROM immunizations WHERE patient_id = $1 AND vaccine_code = $2",
+            )
+            .bind(patient_id)
+            .bind(&vaccine.code)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(db_error)?;
+            last.unwrap_or(0) + 1
+        }
+    };
+    let immunization = sqlx::query_as::<_, Immunization>(
+        "INSERT INTO immunizations (patient_id, record_id, vaccine_code, dose_number, administered_on, lot_number,
+             site, route, administered_by, administered_by_name, historical, notes, recorded_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+         RETURNING *",
+    )
+    .bind(patient_id)
+    .bind(payload.record_id)
+    .bind(&vaccine.code)
+    .bind(dose_number)
+    .bind(administered_on)
+    .bind(&payload.lot_number)
+    .bind(payload.site.as_ref().or(vaccine.default_site.as_ref()))
+    .bind(payload.route.as_ref().or(vaccine.default_route.as_ref()))
+    .bind(administered_by)
+    .bind(&payload.administered_by_name)
+    .bind(payload.historical)
+    .bind(&payload.notes)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => ServiceError::Conflict(format!(
+            "Dose {} of {} is already recorded for this patient",
+            dose_number, vaccine.name
+        )),
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            field_error("administered_by", "does not refer to an existing user")
+        }
+        _ => db_error(e),
+    })?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "immunization.recorded",
+        entity_type: "immunization",
+        entity_id: immunization.id,
+        patient_id: Some(patient_id),
+        details: serde_json::json!({
+            "vaccine": immunization.vaccine_code,
+            "dose_number": immunization.dose_number,
+            "lot_number": immunization.lot_number,
+            "historical": immunization.historical,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(immunization)))
+}
+
+/// Remove a dose recorded in error.
+pub async fn delete_immunization(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<StatusCode, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let immunization = sqlx::query_as::<_, Immunization>(
+        "DELETE FROM immunizations i USING patients p
+         WHERE i.id = $1 AND p.id = i.patient_id AND p.tenant_id = $2 RETURNING i.*",
+    )
+    .bind(id)
+    .bind(user.tenant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "immunization.deleted",
+        entity_type: "immunization",
+        entity_id: id,
+        patient_id: Some(immunization.patient_id),
+        details: serde_json::json!({
+            "vaccine": immunization.vaccine_code,
+            "dose_number": immunization.dose_number,
+            "administered_on": immunization.administered_on,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The patient's vaccination card: each scheduled dose with its status and dates.
+pub async fn get_immunization_card(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Query(params): Query<ScheduleQuery>,
+) -> Result<Json<ImmunizationCard>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let patient: (String, NaiveDate, String) = sqlx::query_as(
+        "SELECT first_name || ' ' || last_name, date_of_birth, gender FROM patients WHERE id = $1 AND tenant_id = $2",
+    )
+    .bind(patient_id)
+    .bind(user.tenant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    let schedule = load_schedule(&pool, params.schedule.as_deref()).await?;
+    let immunizations = sqlx::query_as::<_, Immunization>(
+        "SELECT * FROM immunizations WHERE patient_id = $1 ORDER BY administered_on, vaccine_code, dose_number",
+    )
+    .bind(patient_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    let (patient_name, date_of_birth, gender) = patient;
+    let today = Utc::now().date_naive();
+    let doses = evaluate_schedule(date_of_birth, &gender, today, &schedule.doses, &immunizations);
+    let mut counts = DoseCounts::default();
+    for dose in &doses {
+        match dose.status {
+            DoseStatus::Given => counts.given += 1,
+            DoseStatus::Due => counts.due += 1,
+            DoseStatus::Overdue => counts.overdue += 1,
+            DoseStatus::Upcoming => counts.upcoming += 1,
+            DoseStatus::Missed => counts.missed += 1,
+        }
+    }
+    Ok(Json(ImmunizationCard {
+        patient_id,
+        patient_name,
+        date_of_birth,
+        schedule: schedule.schedule.code,
+        schedule_name: schedule.schedule.name,
+        counts,
+        doses,
+        immunizations,
+    }))
+}
+
+/// Per-village coverage of each scheduled dose among the tenant's young children: of those
+/// who should have had the dose by now, how many have.
+pub async fn get_immunization_coverage(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<CoverageQuery>,
+) -> Result<Json<Vec<VillageCoverage>>, ServiceError> {
+    let schedule = load_schedule(&pool, params.schedule.as_deref()).await?;
+    let today = Utc::now().date_naive();
+    let max_age_months = params.max_age_months.unwrap_or(DEFAULT_COVERAGE_AGE_MONTHS);
+    let born_after = today.checked_sub_months(chrono::Months::new(max_age_months)).unwrap_or(NaiveDate::MIN);
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let children = load_children(&mut tx, user.tenant_id, params.village.as_deref(), Some(born_after)).await?;
+    tx.commit().await.map_err(db_error)?;
+
+    // village -> (children, defaulters, (vaccine, dose) -> (eligible, given))
+    type DoseTally = HashMap<(String, i32), (usize, usize)>;
+    let mut villages: BTreeMap<Option<String>, (usize, usize, DoseTally)> = BTreeMap::new();
+    for (child, given) in &children {
+        let states = evaluate_schedule(child.date_of_birth, &child.gender, today, &schedule.doses, given);
+        let entry = villages.entry(child.village.clone()).or_default();
+        entry.0 += 1;
+        if states.iter().any(|s| s.status == DoseStatus::Overdue) {
+            entry.1 += 1;
+        }
+        for state in states.iter().filter(|s| counts_for_coverage(s)) {
+            let tally = entry.2.entry((state.vaccine_code.clone(), state.dose_number)).or_default();
+            tally.0 += 1;
+            if state.status == DoseStatus::Given {
+                tally.1 += 1;
+            }
+        }
+    }
+
+    let coverage = villages
+        .into_iter()
+        .map(|(village, (children, defaulters, tally))| VillageCoverage {
+            village,
+            children,
+            defaulters,
+            doses: schedule
+                .doses
+                .iter()
+                .map(|dose| {
+                    let (eligible, given) =
+                        tally.get(&(dose.vaccine_code.clone(), dose.dose_number)).copied().unwrap_or_default();
+                    DoseCoverage {
+                        vaccine_code: dose.vaccine_code.clone(),
+                        dose_number: dose.dose_number,
+                        label: dose.label.clone(),
+                        eligible,
+                        given,
+                        coverage_percent: (eligible > 0)
+                            .then(|| (given as f64 * 1000.0 / eligible as f64).round() / 10.0),
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+    Ok(Json(coverage))
+}
+
+/// Children with overdue doses, by village and most overdue first, for outreach follow-up.
+pub async fn list_immunization_defaulters(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<DefaulterQuery>,
+) -> Result<Json<Vec<Defaulter>>, ServiceError> {
+    let schedule = load_schedule(&pool, params.schedule.as_deref()).await?;
+    let today = Utc::now().date_naive();
+    // nobody older than the last age any dose can be given at can default on it
+    let oldest_eligible = schedule
+        .doses
+        .iter()
+        .map(|d| d.max_age_days)
+        .collect::<Option<Vec<_>>>()
+        .and_then(|ages| ages.into_iter().max())
+        .map(|days| today - Duration::days(i64::from(days)));
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let children = load_children(&mut tx, user.tenant_id, params.village.as_deref(), oldest_eligible).await?;
+    tx.commit().await.map_err(db_error)?;
+
+    let mut defaulters: Vec<Defaulter> = children
+        .into_iter()
+        .filter_map(|(child, given)| {
+            let states = evaluate_schedule(child.date_of_birth, &child.gender, today, &schedule.doses, &given);
+            let (overdue, due): (Vec<DoseState>, Vec<DoseState>) = states
+                .into_iter()
+                .filter(|s| s.status == DoseStatus::Overdue || (params.include_due && s.status == DoseStatus::Due))
+                .partition(|s| s.status == DoseStatus::Overdue);
+            if overdue.is_empty() && due.is_empty() {
+                return None;
+            }
+            Some(Defaulter {
+                patient_id: child.id,
+                patient_name: child.patient_name,
+                village: child.village,
+                household_id: child.household_id,
+                phone_number: child.phone_number,
+                date_of_birth: child.date_of_birth,
+                age_months: age_in_months(child.date_of_birth, today),
+                oldest_overdue_date: overdue.iter().map(|s| s.overdue_date).min(),
+                overdue,
+                due,
+            })
+        })
+        .collect();
+    defaulters.sort_by(|a, b| {
+        (&a.village, a.oldest_overdue_date.is_none(), a.oldest_overdue_date, a.patient_id).cmp(&(
+            &b.village,
+            b.oldest_overdue_date.is_none(),
+            b.oldest_overdue_date,
+            b.patient_id,
+        ))
+    });
+    defaulters.truncate(params.limit.unwrap_or(DEFAULT_DEFAULTER_LIMIT).clamp(1, MAX_DEFAULTER_LIMIT));
+    Ok(Json(defaulters))
+}
@@ -0,0 +1,753 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use crate::handlers::records_handler::authorize_record;
+use crate::models::error::ServiceError;
+use crate::models::lab::{
+    LabAnalyte, LabFlag, LabOrder, LabOrderAction, LabOrderStatus, LabPriority, LabResult, LabTest, SpecimenType,
+};
+use crate::models::patient::Gender;
+use crate::services::access::{check_clinical_role, RecordAction};
+use crate::services::alerts::{notify_care_team, AlertEntry};
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::facilities::{facility_scope, require_facility};
+use crate::services::labs::{applicable_ranges, flag_numeric, flag_text};
+use crate::services::tenancy;
+use crate::services::validation::{
+    field_error, normalize_optional_enum, trim_optional, TextEnum, ValidationErrors,
+};
+use crate::services::vitals::age_in_months;
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+
+/// Orders still waiting on someone: the lab to collect or report, or a clinician to review.
+const OUTSTANDING_STATUSES: &[&str] = &["ordered", "collected", "resulted"];
+
+#[derive(Deserialize)]
+pub struct CreateLabOrderRequest {
+    pub patient_id: i32,
+    /// Test codes from `GET /lab/tests`.
+    pub tests: Vec<String>,
+    /// Defaults to the orderer's primary facility.
+    pub facility_id: Option<i32>,
+    pub priority: Option<String>,
+    /// Defaults to the specimen the tests need.
+    pub specimen_type: Option<String>,
+    pub clinical_notes: Option<String>,
+    pub record_id: Option<i32>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct CollectRequest {
+    /// When the specimen was taken; defaults to now.
+    pub collected_at: Option<NaiveDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct ResultInput {
+    /// Needed only when the analyte belongs to more than one test on the order.
+    pub test_code: Option<String>,
+    pub analyte: String,
+    /// A number for numeric analytes, text (e.g. "negative") for the others.
+    pub value: Value,
+    /// Must match the analyte's unit when given.
+    pub unit: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ReportResultsRequest {
+    pub results: Vec<ResultInput>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct ReviewRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct CancelRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LabOrderQuery {
+    pub patient_id: Option<i32>,
+    pub status: Option<String>,
+    pub facility_id: Option<i32>,
+    /// Only orders at the caller's facilities.
+    pub my_facilities: Option<bool>,
+    pub priority: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct OutstandingQuery {
+    pub facility_id: Option<i32>,
+    pub my_facilities: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct LabTestEntry {
+    #[serde(flatten)]
+    pub test: LabTest,
+    pub analytes: Vec<LabAnalyte>,
+}
+
+#[derive(Serialize)]
+pub struct LabOrderDetail {
+    #[serde(flatten)]
+    pub order: LabOrder,
+    pub tests: Vec<LabTest>,
+    pub results: Vec<LabResult>,
+}
+
+#[derive(Serialize, FromRow)]
+pub struct LabOrderSummary {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub order: LabOrder,
+    pub patient_name: String,
+    pub facility_name: String,
+    pub tests: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct FacilityOutstanding {
+    pub facility_id: i32,
+    pub facility_name: String,
+    pub ordered: usize,
+    pub collected: usize,
+    pub resulted: usize,
+    /// Most urgent first, then oldest first.
+    pub orders: Vec<LabOrderSummary>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[LAB ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+async fn lock_order(tx: &mut Transaction<'_, Postgres>, tenant_id: i32, id: i32) -> Result<LabOrder, ServiceError> {
+    sqlx::query_as::<_, LabOrder>(
+        "SELECT o.* FROM lab_orders o JOIN patients p ON p.id = o.patient_id AND p.tenant_id = $2
+         WHERE o.id = $1 FOR UPDATE OF o",
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)
+}
+
+async fn order_detail(tx: &mut Transaction<'_, Postgres>, order: LabOrder) -> Result<LabOrderDetail, ServiceError> {
+    let tests = sqlx::query_as::<_, LabTest>(
+        "SELECT t.* FROM lab_order_tests o JOIN lab_tests t ON t.code = o.test_code
+         WHERE o.order_id = $1 ORDER BY t.code",
+    )
+    .bind(order.id)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    let results = sqlx::query_as::<_, LabResult>(
+        "SELECT r.* FROM lab_results r
+         LEFT JOIN lab_analytes a ON a.test_code = r.test_code AND a.code = r.analyte_code
+         WHERE r.order_id = $1 ORDER BY r.test_code, a.position, r.id",
+    )
+    .bind(order.id)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    Ok(LabOrderDetail { order, tests, results })
+}
+
+fn transition_error(action: LabOrderAction, order: &LabOrder) -> ServiceError {
+    ServiceError::Conflict(format!("An order that is {} cannot be {}", order.status, action.past_tense()))
+}
+
+fn current_status(order: &LabOrder) -> Result<LabOrderStatus, ServiceError> {
+    LabOrderStatus::parse(&order.status).ok_or(ServiceError::InternalServerError)
+}
+
+/// The test catalog with each test's analytes.
+pub async fn list_lab_tests(
+    _user: AuthUser,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<LabTestEntry>>, ServiceError> {
+    let tests = sqlx::query_as::<_, LabTest>("SELECT * FROM lab_tests ORDER BY name")
+        .fetch_all(&pool)
+        .await
+        .map_err(db_error)?;
+    let analytes = sqlx::query_as::<_, LabAnalyte>("SELECT * FROM lab_analytes ORDER BY test_code, position, code")
+        .fetch_all(&pool)
+        .await
+        .map_err(db_error)?;
+    let entries = tests
+        .into_iter()
+        .map(|test| {
+            let analytes = analytes.iter().filter(|a| a.test_code == test.code).cloned().collect();
+            LabTestEntry { test, analytes }
+        })
+        .collect();
+    Ok(Json(entries))
+}
+
+/// Order one or more tests on a single specimen. Ordering is for clinicians and admins.
+pub async fn create_lab_order(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<CreateLabOrderRequest>,
+) -> Result<(StatusCode, Json<LabOrderDetail>), ServiceError> {
+    check_clinical_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    trim_optional(&mut payload.clinical_notes);
+    normalize_optional_enum::<LabPriority>(&mut errors, "priority", &mut payload.priority);
+    normalize_optional_enum::<SpecimenType>(&mut errors, "specimen_type", &mut payload.specimen_type);
+    let mut codes: Vec<String> = payload.tests.iter().map(|code| code.trim().to_lowercase()).collect();
+    codes.sort();
+    codes.dedup();
+    if codes.is_empty() {
+        errors.add("tests", "must name at least one test");
+    }
+    errors.into_result()?;
+
+    let tests = sqlx::query_as::<_, LabTest>("SELECT * FROM lab_tests WHERE code = ANY($1) ORDER BY code")
+        .bind(&codes)
+        .fetch_all(&pool)
+        .await
+        .map_err(db_error)?;
+    let unknown: Vec<&str> = codes
+        .iter()
+        .filter(|code| !tests.iter().any(|t| &t.code == *code))
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        return Err(field_error("tests", format!("unknown test codes: {}", unknown.join(", "))));
+    }
+    let specimen_type = match payload.specimen_type.take() {
+        Some(specimen) => specimen,
+        None => {
+            let first = &tests[0].specimen_type;
+            if tests.iter().any(|t| &t.specimen_type != first) {
+                return Err(field_error("tests", "need different specimens; order them separately"));
+            }
+            first.clone()
+        }
+    };
+
+    let facility = require_facility(&pool, "facility_id", payload.facility_id, user.id).await?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let exists: Option<i32> = sqlx::query_scalar("SELECT id FROM patients WHERE id = $1 AND tenant_id = $2")
+        .bind(payload.patient_id)
+        .bind(user.tenant_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    if exists.is_none() {
+        return Err(field_error("patient_id", "does not refer to an existing patient"));
+    }
+    if let Some(record_id) = payload.record_id {
+        let record = authorize_record(&mut *tx, record_id, &user, RecordAction::Read).await?;
+        if record.patient_id != payload.patient_id {
+            return Err(field_error("record_id", "belongs to a different patient"));
+        }
+    }
+    let order = sqlx::query_as::<_, LabOrder>(
+        "INSERT INTO lab_orders (patient_id, record_id, facility_id, priority, specimen_type, clinical_notes, ordered_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+    )
+    .bind(payload.patient_id)
+    .bind(payload.record_id)
+    .bind(facility.id)
+    .bind(payload.priority.as_deref().unwrap_or(LabPriority::Routine.as_str()))
+    .bind(&specimen_type)
+    .bind(&payload.clinical_notes)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    sqlx::query("INSERT INTO lab_order_tests (order_id, test_code) SELECT $1, UNNEST($2::text[])")
+        .bind(order.id)
+        .bind(&codes)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "lab_order.created",
+        entity_type: "lab_order",
+        entity_id: order.id,
+        patient_id: Some(order.patient_id),
+        details: serde_json::json!({ "tests": codes, "priority": order.priority, "facility_id": order.facility_id }),
+    })
+    .await
+    .map_err(db_error)?;
+    let detail = order_detail(&mut tx, order).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(detail)))
+}
+
+const SUMMARY_SELECT: &str = "SELECT o.*, p.first_name || ' ' || p.last_name AS patient_name,
+            f.name AS facility_name,
+            ARRAY(SELECT test_code FROM lab_order_tests t WHERE t.order_id = o.id ORDER BY test_code) AS tests
+     FROM lab_orders o
+     JOIN patients p ON p.id = o.patient_id
+     JOIN facilities f ON f.id = o.facility_id";
+
+pub async fn list_lab_orders(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(mut params): Query<LabOrderQuery>,
+) -> Result<Json<Vec<LabOrderSummary>>, ServiceError> {
+    let mut errors = ValidationErrors::default();
+    normalize_optional_enum::<LabOrderStatus>(&mut errors, "status", &mut params.status);
+    normalize_optional_enum::<LabPriority>(&mut errors, "priority", &mut params.priority);
+    errors.into_result()?;
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let facilities = facility_scope(&pool, user.id, params.facility_id, params.my_facilities)
+        .await
+        .map_err(db_error)?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let orders = sqlx::query_as::<_, LabOrderSummary>(&format!(
+        "{SUMMARY_SELECT}
+         WHERE ($1::int IS NULL OR o.patient_id = $1)
+           AND ($2::text IS NULL OR o.status = $2)
+           AND ($3::int[] IS NULL OR o.facility_id = ANY($3))
+           AND ($4::text IS NULL OR o.priority = $4)
+           AND ($5::date IS NULL OR o.ordered_at >= $5::date)
+           AND ($6::date IS NULL OR o.ordered_at < $6::date + 1)
+           AND p.tenant_id = $8
+         ORDER BY o.ordered_at DESC, o.id DESC
+         LIMIT $7"
+    ))
+    .bind(params.patient_id)
+    .bind(&params.status)
+    .bind(&facilities)
+    .bind(&params.priority)
+    .bind(params.from)
+    .bind(params.to)
+    .bind(limit)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(orders))
+}
+
+/// Orders not yet reviewed or cancelled, grouped by facility.
+pub async fn list_outstanding_lab_orders(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<OutstandingQuery>,
+) -> Result<Json<Vec<FacilityOutstanding>>, ServiceError> {
+    let facilities = facility_scope(&pool, user.id, params.facility_id, params.my_facilities)
+        .await
+        .map_err(db_error)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let orders = sqlx::query_as::<_, LabOrderSummary>(&format!(
+        "{SUMMARY_SELECT}
+         WHERE o.status = ANY($1) AND ($2::int[] IS NULL OR o.facility_id = ANY($2)) AND p.tenant_id = $3
+         ORDER BY f.name, o.facility_id,
+                  CASE o.priority WHEN 'stat' THEN 0 WHEN 'urgent' THEN 1 ELSE 2 END,
+                  o.ordered_at, o.id"
+    ))
+    .bind(OUTSTANDING_STATUSES)
+    .bind(&facilities)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    let mut grouped: Vec<FacilityOutstanding> = Vec::new();
+    for summary in orders {
+        if grouped.last().is_none_or(|f| f.facility_id != summary.order.facility_id) {
+            grouped.push(FacilityOutstanding {
+                facility_id: summary.order.facility_id,
+                facility_name: summary.facility_name.clone(),
+                ordered: 0,
+                collected: 0,
+                resulted: 0,
+                orders: Vec::new(),
+            });
+        }
+        let Some(current) = grouped.last_mut() else { continue };
+        match LabOrderStatus::parse(&summary.order.status) {
+            Some(LabOrderStatus::Ordered) => current.ordered += 1,
+            Some(LabOrderStatus::Collected) => current.collected += 1,
+            Some(LabOrderStatus::Resulted) => current.resulted += 1,
+            _ => {}
+        }
+        current.orders.push(summary);
+    }
+    Ok(Json(grouped))
+}
+
+pub async fn get_lab_order(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<LabOrderDetail>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let order = sqlx::query_as::<_, LabOrder>(
+        "SELECT o.* FROM lab_orders o JOIN patients p ON p.id = o.patient_id AND p.tenant_id = $2
+         WHERE o.id = $1",
+    )
+    .bind(id)
+    .bind(user.tenant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    let detail = order_detail(&mut tx, order).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(detail))
+}
+
+pub async fn collect_lab_order(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<CollectRequest>>,
+) -> Result<Json<LabOrderDetail>, ServiceError> {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let now = Utc::now().naive_utc();
+    let collected_at = payload.collected_at.unwrap_or(now);
+    if collected_at > now + chrono::Duration::minutes(5) {
+        return Err(field_error("collected_at", "cannot be in the future"));
+    }
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let order = lock_order(&mut tx, user.tenant_id, id).await?;
+    let to = LabOrderAction::Collect
+        .apply(current_status(&order)?)
+        .ok_or_else(|| transition_error(LabOrderAction::Collect, &order))?;
+    if collected_at < order.ordered_at - chrono::Duration::minutes(5) {
+        return Err(field_error("collected_at", "cannot be before the order was placed"));
+    }
+    let order = sqlx::query_as::<_, LabOrder>(
+        "UPDATE lab_orders SET status = $2, collected_by = $3, collected_at = $4, updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(to.as_str())
+    .bind(user.id)
+    .bind(collected_at)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "lab_order.collected",
+        entity_type: "lab_order",
+        entity_id: id,
+        patient_id: Some(order.patient_id),
+        details: serde_json::json!({ "collected_at": collected_at }),
+    })
+    .await
+    .map_err(db_error)?;
+    let detail = order_detail(&mut tx, order).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(detail))
+}
+
+/// A submitted result resolved against the order's tests and checked against its analyte.
+struct CheckedResult<'a> {
+    analyte: &'a LabAnalyte,
+    numeric: Option<f64>,
+    text: Option<String>,
+}
+
+fn check_results<'a>(
+    inputs: &[ResultInput],
+    analytes: &'a [LabAnalyte],
+) -> Result<Vec<CheckedResult<'a>>, ServiceError> {
+    let mut errors = ValidationErrors::default();
+    let mut checked: Vec<CheckedResult<'a>> = Vec::new();
+    if inputs.is_empty() {
+        errors.add("results", "must contain at least one result");
+    }
+    for (i, input) in inputs.iter().enumerate() {
+        let field = format!("results[{}]", i);
+        let code = input.analyte.trim().to_lowercase();
+        let test_code = input.test_code.as_deref().map(|t| t.trim().to_lowercase());
+        let matches: Vec<&LabAnalyte> = analytes
+            .iter()
+            .filter(|a| a.code == code && test_code.as_ref().is_none_or(|t| &a.test_code == t))
+            .collect();
+        let analyte = match matches.as_slice() {
+            [analyte] => *analyte,
+            [] => {
+                errors.add(format!("{}.analyte", field), "is not reported by any test on this order");
+                continue;
+            }
+            _ => {
+                errors.add(format!("{}.test_code", field), "is required; the analyte is on more than one test");
+                continue;
+            }
+        };
+        if checked.iter().any(|c| std::ptr::eq(c.analyte, analyte)) {
+            errors.add(format!("{}.analyte", field), "is given more than once");
+            continue;
+        }
+        if let (Some(unit), Some(expected)) = (input.unit.as_deref(), analyte.unit.as_deref()) {
+            if !unit.trim().eq_ignore_ascii_case(expected) {
+                errors.add(format!("{}.unit", field), format!("must be {}", expected));
+                continue;
+            }
+        }
+        let (numeric, text) = match (analyte.result_type.as_str(), &input.value) {
+            ("numeric", Value::Number(n)) => (n.as_f64(), None),
+            ("numeric", Value::String(s)) => match s.trim().parse::<f64>() {
+                Ok(n) if n.is_finite() => (Some(n), None),
+                _ => {
+                    errors.add(format!("{}.value", field), "must be a number");
+                    continue;
+                }
+            },
+            ("numeric", _) => {
+                errors.add(format!("{}.value", field), "must be a number");
+                continue;
+            }
+            (_, Value::String(s)) if !s.trim().is_empty() => (None, Some(s.trim().to_string())),
+            (_, Value::Number(n)) => (None, Some(n.to_string())),
+            _ => {
+                errors.add(format!("{}.value", field), "must be a non-empty text value");
+                continue;
+            }
+        };
+        checked.push(CheckedResult { analyte, numeric, text });
+    }
+    errors.into_result()?;
+    Ok(checked)
+}
+
+/// Report results for a collected order. Each value is flagged against the patient's age-
+/// and sex-specific range; critical values alert the patient's care team.
+pub async fn report_lab_results(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(payload): Json<ReportResultsRequest>,
+) -> Result<Json<LabOrderDetail>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let order = lock_order(&mut tx, user.tenant_id, id).await?;
+    let to = LabOrderAction::Result
+        .apply(current_status(&order)?)
+        .ok_or_else(|| transition_error(LabOrderAction::Result, &order))?;
+
+    let analytes = sqlx::query_as::<_, LabAnalyte>(
+        "SELECT a.* FROM lab_analytes a JOIN lab_order_tests t ON t.test_code = a.test_code
+         WHERE t.order_id = $1 ORDER BY a.test_code, a.position",
+    )
+    .bind(id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    let checked = check_results(&payload.results, &analytes)?;
+
+    let (date_of_birth, gender) = sqlx::query_as::<_, (NaiveDate, String)>(
+        "SELECT date_of_birth, gender FROM patients WHERE id = $1",
+    )
+    .bind(order.patient_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    let sex = Gender::parse(&gender).map(|g| g.as_str()).unwrap_or("unknown");
+    let sampled_on = order.collected_at.unwrap_or(order.ordered_at).date();
+    let codes: Vec<String> = checked.iter().map(|c| c.analyte.code.clone()).collect();
+    let ranges = applicable_ranges(&mut *tx, &codes, sex, age_in_months(date_of_birth, sampled_on))
+        .await
+        .map_err(db_error)?;
+
+    let mut critical = Vec::new();
+    for result in &checked {
+        let range = ranges.get(&result.analyte.code);
+        let flag = match (result.numeric, result.text.as_deref()) {
+            (Some(value), _) => range.map(|r| flag_numeric(r, value)),
+            (None, Some(text)) => flag_text(result.analyte, text),
+            (None, None) => None,
+        };
+        if flag.is_some_and(LabFlag::is_critical) {
+            critical.push(format!(
+                "{} {}{} ({})",
+                result.analyte.name,
+                result.numeric.map(|v| v.to_string()).unwrap_or_default(),
+                result.analyte.unit.as_deref().map(|u| format!(" {}", u)).unwrap_or_default(),
+                flag.map(|f| f.as_str()).unwrap_or_default(),
+            ));
+        }
+        sqlx::query(
+            "INSERT INTO lab_results (order_id, test_code, analyte_code, analyte_name, value_numeric, value_text,
+                 unit, reference_low, reference_high, flag, resulted_by)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+             ON CONFLICT (order_id, test_code, analyte_code) DO UPDATE SET
+                 value_numeric = EXCLUDED.value_numeric, value_text = EXCLUDED.value_text, unit = EXCLUDED.unit,
+                 reference_low = EXCLUDED.reference_low, reference_high = EXCLUDED.reference_high,
+                 flag = EXCLUDED.flag, resulted_by = EXCLUDED.resulted_by, resulted_at = now()",
+        )
+        .bind(id)
+        .bind(&result.analyte.test_code)
+        .bind(&result.analyte.code)
+        .bind(&result.analyte.name)
+        .bind(result.numeric)
+        .bind(&result.text)
+        .bind(&result.analyte.unit)
+        .bind(range.and_then(|r| r.low))
+        .bind(range.and_then(|r| r.high))
+        .bind(flag.map(|f| f.as_str()))
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    }
+
+    let order = sqlx::query_as::<_, LabOrder>(
+        "UPDATE lab_orders SET status = $2, resulted_by = $3, resulted_at = now(), updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(to.as_str())
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "lab_order.resulted",
+        entity_type: "lab_order",
+        entity_id: id,
+        patient_id: Some(order.patient_id),
+        details: serde_json::json!({ "analytes": codes, "critical": critical }),
+    })
+    .await
+    .map_err(db_error)?;
+    if !critical.is_empty() {
+        let message = format!("Critical lab result on order #{}: {}", id, critical.join(", "));
+        notify_care_team(&mut tx, AlertEntry {
+            patient_id: order.patient_id,
+            vital_set_id: None,
+            kind: "critical_result",
+            severity: "critical",
+            message: &message,
+        })
+        .await
+        .map_err(db_error)?;
+    }
+    let detail = order_detail(&mut tx, order).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(detail))
+}
+
+/// Sign off a fully resulted order. Reviewing is for clinicians and admins.
+pub async fn review_lab_order(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<ReviewRequest>>,
+) -> Result<Json<LabOrderDetail>, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut payload = payload.map(|Json(p)| p).unwrap_or_default();
+    trim_optional(&mut payload.note);
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let order = lock_order(&mut tx, user.tenant_id, id).await?;
+    let to = LabOrderAction::Review
+        .apply(current_status(&order)?)
+        .ok_or_else(|| transition_error(LabOrderAction::Review, &order))?;
+    let missing: Vec<String> = sqlx::query_scalar(
+        "SELECT a.test_code || '.' || a.code FROM lab_analytes a
+         JOIN lab_order_tests t ON t.test_code = a.test_code AND t.order_id = $1
+         LEFT JOIN lab_results r ON r.order_id = $1 AND r.test_code = a.test_code AND r.analyte_code = a.code
+         WHERE r.id IS NULL ORDER BY a.test_code, a.position",
+    )
+    .bind(id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    if !missing.is_empty() {
+        return Err(ServiceError::Conflict(format!("Results are still missing for: {}", missing.join(", "))));
+    }
+
+    let order = sqlx::query_as::<_, LabOrder>(
+        "UPDATE lab_orders SET status = $2, reviewed_by = $3, reviewed_at = now(), review_note = $4, updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(to.as_str())
+    .bind(user.id)
+    .bind(&payload.note)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "lab_order.reviewed",
+        entity_type: "lab_order",
+        entity_id: id,
+        patient_id: Some(order.patient_id),
+        details: serde_json::json!({ "note": payload.note }),
+    })
+    .await
+    .map_err(db_error)?;
+    let detail = order_detail(&mut tx, order).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(detail))
+}
+
+/// Withdraw an order before results are in; a `reason` is required.
+pub async fn cancel_lab_order(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<CancelRequest>>,
+) -> Result<Json<LabOrderDetail>, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut payload = payload.map(|Json(p)| p).unwrap_or_default();
+    trim_optional(&mut payload.reason);
+    let Some(reason) = payload.reason else {
+        return Err(field_error("reason", "is required when cancelling an order"));
+    };
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let order = lock_order(&mut tx, user.tenant_id, id).await?;
+    let to = LabOrderAction::Cancel
+        .apply(current_status(&order)?)
+        .ok_or_else(|| transition_error(LabOrderAction::Cancel, &order))?;
+    let order = sqlx::query_as::<_, LabOrder>(
+        "UPDATE lab_orders SET status = $2, cancelled_reason = $3, updated_at = now() WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(to.as_str())
+    .bind(&reason)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "lab_order.cancelled",
+        entity_type: "lab_order",
+        entity_id: id,
+        patient_id: Some(order.patient_id),
+        details: serde_json::json!({ "reason": reason }),
+    })
+    .await
+    .map_err(db_error)?;
+    let detail = order_detail(&mut tx, order).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(detail))
+}
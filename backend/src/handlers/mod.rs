@@ -4,3 +4,22 @@ pub mod auth_handler;
 pub mod analytics_handler;
 pub mod administration_handler;
 pub mod consents_handler;
+pub mod timeline_handler;
+pub mod households_handler;
+pub mod photos_handler;
+pub mod attachments_handler;
+pub mod record_workflow_handler;
+pub mod vitals_handler;
+pub mod alerts_handler;
+pub mod labs_handler;
+pub mod prescriptions_handler;
+pub mod terminology_handler;
+pub mod diagnoses_handler;
+pub mod immunizations_handler;
+pub mod pregnancies_handler;
+pub mod growth_handler;
+pub mod appointments_handler;
+pub mod queue_handler;
+pub mod referrals_handler;
+pub mod facilities_handler;
+pub mod geography_handler;
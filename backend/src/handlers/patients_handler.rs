@@ -1,63 +1,215 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
 use sqlx::PgPool;
-use serde_json;
 
-use crate::models::patient::Patient;
+use crate::models::patient::{BloodType, EmergencyContact, Gender, Patient, PatientStatus};
 use crate::models::error::ServiceError;
 use crate::models::consent::Consent;
 use crate::models::record::MedicalRecord;
-use axum::extract::Query;
+use crate::models::terminology::Diagnosis;
+use crate::handlers::vitals_handler::VitalSetResponse;
+use crate::models::household::{FamilyMember, Household};
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::facilities::facility_scope;
+use crate::services::geography::resolve_village;
+use crate::services::scheduling::book_follow_up;
+use crate::services::tenancy;
+use crate::services::validation::{
+    check_address, check_date_of_birth, check_email, check_not_past, check_phone, check_schema, field_error, normalize_enum,
+    normalize_optional_enum, require_optional_text, require_text, trim_optional, ValidationErrors,
+};
 use serde::Serialize;
+use sqlx::types::Json as SqlJson;
+use std::collections::BTreeMap;
+
+/// How many records of each type the profile summary includes.
+const RECENT_RECORDS_PER_TYPE: i64 = 5;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenTask {
+    pub kind: String,
+    pub record_id: Option<i32>,
+    pub summary: String,
+    pub due_date: Option<NaiveDate>,
+}
+
 #[derive(Serialize)]
 pub struct PatientProfile {
     pub patient: Patient,
-    pub consents: Vec<Consent>,
-    pub medical_records: Vec<MedicalRecord>,
+    pub age: i32,
+    pub active_consents: Vec<Consent>,
+    pub recent_records: BTreeMap<String, Vec<MedicalRecord>>,
+    /// The most recent vital set with its observations.
+    pub latest_vitals: Option<VitalSetResponse>,
+    pub allergies: Vec<String>,
+    /// As typed on the patient.
+    pub active_conditions: Vec<String>,
+    /// The typed conditions no reviewed mapping has coded yet.
+    pub uncoded_conditions: Vec<String>,
+    /// Open coded diagnoses.
+    pub active_diagnoses: Vec<Diagnosis>,
+    pub upcoming_visit: Option<NaiveDate>,
+    pub open_tasks: Vec<OpenTask>,
+    pub household: Option<Household>,
+    pub family: Vec<FamilyMember>,
+}
+
+#[derive(sqlx::FromRow)]
+struct PatientProfileRow {
+    #[sqlx(flatten)]
+    patient: Patient,
+    active_consents: SqlJson<Vec<Consent>>,
+    recent_records: SqlJson<BTreeMap<String, Vec<MedicalRecord>>>,
+    latest_vitals: Option<SqlJson<VitalSetResponse>>,
+    active_diagnoses: SqlJson<Vec<Diagnosis>>,
+    uncoded_conditions: SqlJson<Vec<String>>,
+    open_tasks: SqlJson<Vec<OpenTask>>,
+    household: Option<SqlJson<Household>>,
+    family: SqlJson<Vec<FamilyMember>>,
 }
-/// Get a detailed patient profile (patient, consents, records)
+
+/// Get the clinical summary for a patient in a single round-trip.
 pub async fn get_patient_profile(
+    user: AuthUser,
     Path(id): Path<i32>,
     State(pool): State<PgPool>,
 ) -> Result<Json<PatientProfile>, ServiceError> {
-    // Get patient
-    let patient = sqlx::query_as!(Patient,
-        r#"SELECT * FROM patients WHERE id = $1"#,
-        id
-    )
-    .fetch_optional(&pool)
-    .await
-    .map_err(|_| ServiceError::Unauthorized)?;
-    let patient = match patient {
-        Some(p) => p,
-        None => return Err(ServiceError::NotFound),
-    };
-
-    // Get consents
-    let consents = sqlx::query_as!(Consent,
-        r#"SELECT * FROM consents WHERE patient_id = $1"#,
-        id
-    )
-    .fetch_all(&pool)
-    .await
-    .unwrap_or_default();
-
-    // Get medical records
-    let medical_records = sqlx::query_as!(MedicalRecord,
-        r#"SELECT * FROM medical_records WHERE patient_id = $1"#,
-        id
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    let row = sqlx::query_as::<_, PatientProfileRow>(
+        r#"
+        WITH RECURSIVE p AS (
+            SELECT * FROM patients WHERE id = $1 AND tenant_id = $3
+        ),
+        latest_consents AS (
+            SELECT DISTINCT ON (consent_type) *
+            FROM consents
+            WHERE patient_id = $1
+            ORDER BY consent_type, created_at DESC NULLS LAST, id DESC
+        ),
+        ranked_records AS (
+            SELECT r.*, row_number() OVER (PARTITION BY r.record_type ORDER BY r.date DESC, r.id DESC) AS rn
+            FROM medical_records r
+            WHERE r.patient_id = $1
+        ),
+        open_tasks AS (
+            SELECT 'record_pending' AS kind, r.id AS record_id, r.title AS summary, r.date AS due_date
+            FROM medical_records r
+            WHERE r.patient_id = $1 AND r.status <> 'signed'
+            UNION ALL
+            SELECT 'visit_overdue', NULL, 'Follow-up visit is overdue', p.next_visit
+            FROM p
+            WHERE p.next_visit < CURRENT_DATE
+        ),
+        -- direct relatives, plus grandparents and grandchildren reached through parent/child links
+        lineage AS (
+            SELECT rel.related_patient_id AS relative_id,
+                   rel.relationship_type AS relationship,
+                   CASE rel.relationship_type WHEN 'mother' THEN 1 WHEN 'father' THEN 1 WHEN 'child' THEN -1 ELSE 0 END AS generation,
+                   ARRAY[$1, rel.related_patient_id] AS path
+            FROM patient_relationships rel
+            WHERE rel.patient_id = $1
+            UNION ALL
+            SELECT rel.related_patient_id,
+                   CASE WHEN l.generation > 0 THEN 'grandparent' ELSE 'grandchild' END,
+                   l.generation * 2,
+                   l.path || rel.related_patient_id
+            FROM lineage l
+            JOIN patient_relationships rel ON rel.patient_id = l.relative_id
+            WHERE ((l.generation = 1 AND rel.relationship_type IN ('mother', 'father'))
+                OR (l.generation = -1 AND rel.relationship_type = 'child'))
+              AND NOT rel.related_patient_id = ANY(l.path)
+        )
+        SELECT p.*,
+            COALESCE(
+                (SELECT json_agg(c ORDER BY c.consent_type) FROM latest_consents c WHERE c.granted),
+                '[]'
+            ) AS active_consents,
+            COALESCE(
+                (SELECT json_object_agg(g.record_type, g.records)
+                 FROM (
+                    SELECT record_type, jsonb_agg(to_jsonb(rr) - 'rn' ORDER BY rr.date DESC, rr.id DESC) AS records
+                    FROM ranked_records rr
+                    WHERE rr.rn <= $2
+                    GROUP BY record_type
+                 ) g),
+                '{}'
+            ) AS recent_records,
+            (SELECT to_jsonb(s) || jsonb_build_object('observations', COALESCE(
+                        (SELECT jsonb_agg(o ORDER BY o.kind) FROM vital_observations o WHERE o.set_id = s.id),
+                        '[]'))
+             FROM vital_sets s
+             WHERE s.patient_id = $1
+             ORDER BY s.observed_at DESC, s.id DESC
+             LIMIT 1
+            ) AS latest_vitals,
+            COALESCE(
+                (SELECT json_agg(d ORDER BY d.onset_date DESC NULLS LAST, d.display)
+                 FROM diagnoses d
+                 WHERE d.patient_id = $1 AND d.resolved_date IS NULL),
+                '[]'
+            ) AS active_diagnoses,
+            COALESCE(
+                (SELECT json_agg(btrim(t.condition) ORDER BY t.n)
+                 FROM unnest(p.active_conditions) WITH ORDINALITY AS t(condition, n)
+                 WHERE btrim(t.condition) <> ''
+                   AND NOT EXISTS (
+                       SELECT 1 FROM condition_mappings m
+                       WHERE m.patient_id = p.id AND m.status = 'mapped'
+                         AND m.normalized_text = lower(regexp_replace(btrim(t.condition), '\s+', ' ', 'g')))),
+                '[]'
+            ) AS uncoded_conditions,
+            COALESCE(
+                (SELECT json_agg(t ORDER BY t.due_date NULLS LAST) FROM open_tasks t),
+                '[]'
+            ) AS open_tasks,
+            (SELECT to_json(h) FROM households h WHERE h.id = p.household_id) AS household,
+            COALESCE(
+                (SELECT json_agg(f ORDER BY f.generation DESC, f.date_of_birth)
+                 FROM (
+                    SELECT DISTINCT ON (l.relative_id, l.relationship)
+                           rp.id AS patient_id, rp.first_name, rp.last_name, rp.gender, rp.date_of_birth,
+                           l.relationship, l.generation
+                    FROM lineage l
+                    JOIN patients rp ON rp.id = l.relative_id AND rp.tenant_id = $3
+                 ) f),
+                '[]'
+            ) AS family
+        FROM p
+        "#,
     )
-    .fetch_all(&pool)
+    .bind(id)
+    .bind(RECENT_RECORDS_PER_TYPE)
+    .bind(user.tenant_id)
+    .fetch_optional(&mut *tx)
     .await
-    .unwrap_or_default();
+    .map_err(|e| {
+        eprintln!("[PATIENT PROFILE ERROR] DB error: {:?}", e);
+        ServiceError::InternalServerError
+    })?
+    .ok_or(ServiceError::NotFound)?;
 
+    let patient = row.patient;
+    let today = chrono::Utc::now().date_naive();
     Ok(Json(PatientProfile {
+        age: patient.age_on(today),
+        active_consents: row.active_consents.0,
+        recent_records: row.recent_records.0,
+        latest_vitals: row.latest_vitals.map(|v| v.0),
+        allergies: patient.known_allergies.clone().unwrap_or_default(),
+        active_conditions: patient.active_conditions.clone().unwrap_or_default(),
+        uncoded_conditions: row.uncoded_conditions.0,
+        active_diagnoses: row.active_diagnoses.0,
+        upcoming_visit: patient.next_visit.filter(|d| *d >= today),
+        open_tasks: row.open_tasks.0,
+        household: row.household.map(|h| h.0),
+        family: row.family.0,
         patient,
-        consents,
-        medical_records,
     }))
 }
 
@@ -77,6 +229,8 @@ pub struct CreatePatientRequest {
     pub email: Option<String>,
     pub address: Option<Value>,
     pub village: Option<String>,
+    /// Wins over `village`, which is otherwise matched to a village by name.
+    pub village_id: Option<i32>,
     pub emergency_contact: Option<Value>,
     #[serde(default)]
     pub active_conditions: Option<Vec<String>>,
@@ -85,8 +239,10 @@ pub struct CreatePatientRequest {
     pub additional_notes: Option<String>,
     pub status: Option<String>,
     pub critical_flag: Option<bool>,
+    pub critical_reason: Option<String>,
     pub profile_picture_url: Option<String>,
     pub next_visit: Option<NaiveDate>,
+    pub household_id: Option<i32>,
 }
 
 #[derive(Deserialize)]
@@ -101,37 +257,152 @@ pub struct UpdatePatientRequest {
     pub email: Option<String>,
     pub address: Option<Value>,
     pub village: Option<String>,
+    /// Wins over `village`, which is otherwise matched to a village by name.
+    pub village_id: Option<i32>,
     pub emergency_contact: Option<Value>,
     pub active_conditions: Option<Vec<String>>,
     pub known_allergies: Option<Vec<String>>,
     pub additional_notes: Option<String>,
     pub status: Option<String>,
     pub critical_flag: Option<bool>,
+    pub critical_reason: Option<String>,
     pub profile_picture_url: Option<String>,
     pub next_visit: Option<NaiveDate>,
+    pub household_id: Option<i32>,
+}
+
+impl CreatePatientRequest {
+    /// Check the payload and rewrite it into the canonical stored form.
+    fn validate(&mut self) -> Result<(), ServiceError> {
+        let mut errors = ValidationErrors::default();
+        require_text(&mut errors, "first_name", &mut self.first_name);
+        require_text(&mut errors, "last_name", &mut self.last_name);
+        trim_optional(&mut self.middle_name);
+        trim_optional(&mut self.village);
+        trim_optional(&mut self.additional_notes);
+        trim_optional(&mut self.critical_reason);
+        trim_optional(&mut self.blood_type);
+        trim_optional(&mut self.email);
+        check_date_of_birth(&mut errors, "date_of_birth", self.date_of_birth);
+        normalize_enum::<Gender>(&mut errors, "gender", &mut self.gender);
+        normalize_optional_enum::<BloodType>(&mut errors, "blood_type", &mut self.blood_type);
+        normalize_optional_enum::<PatientStatus>(&mut errors, "status", &mut self.status);
+        check_phone(&mut errors, "phone_number", &mut self.phone_number);
+        check_email(&mut errors, "email", &mut self.email);
+        check_address(&mut errors, &mut self.address);
+        check_emergency_contact(&mut errors, &mut self.emergency_contact);
+        if let Some(next_visit) = self.next_visit {
+            check_not_past(&mut errors, "next_visit", next_visit);
+        }
+        errors.into_result()
+    }
+}
+
+impl UpdatePatientRequest {
+    /// Same rules as creation, applied only to the fields being changed.
+    fn validate(&mut self) -> Result<(), ServiceError> {
+        let mut errors = ValidationErrors::default();
+        require_optional_text(&mut errors, "first_name", &mut self.first_name);
+        require_optional_text(&mut errors, "last_name", &mut self.last_name);
+        trim_optional(&mut self.middle_name);
+        trim_optional(&mut self.village);
+        trim_optional(&mut self.additional_notes);
+        trim_optional(&mut self.critical_reason);
+        trim_optional(&mut self.blood_type);
+        trim_optional(&mut self.email);
+        if let Some(date_of_birth) = self.date_of_birth {
+            check_date_of_birth(&mut errors, "date_of_birth", date_of_birth);
+        }
+        normalize_optional_enum::<Gender>(&mut errors, "gender", &mut self.gender);
+        normalize_optional_enum::<BloodType>(&mut errors, "blood_type", &mut self.blood_type);
+        normalize_optional_enum::<PatientStatus>(&mut errors, "status", &mut self.status);
+        if let Some(phone_number) = self.phone_number.as_mut() {
+            check_phone(&mut errors, "phone_number", phone_number);
+        }
+        check_email(&mut errors, "email", &mut self.email);
+        check_address(&mut errors, &mut self.address);
+        check_emergency_contact(&mut errors, &mut self.emergency_contact);
+        if let Some(next_visit) = self.next_visit {
+            check_not_past(&mut errors, "next_visit", next_visit);
+        }
+        errors.into_result()
+    }
+}
+
+async fn check_household_exists(pool: &PgPool, tenant_id: i32, household_id: Option<i32>) -> Result<(), ServiceError> {
+    let Some(household_id) = household_id else {
+        return Ok(());
+    };
+    let exists: Option<i32> = sqlx::query_scalar("SELECT id FROM households WHERE id = $1 AND tenant_id = $2")
+        .bind(household_id)
+        .bind(tenant_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    match exists {
+        Some(_) => Ok(()),
+        None => Err(field_error("household_id", "does not refer to an existing household")),
+    }
+}
+
+fn check_emergency_contact(errors: &mut ValidationErrors, value: &mut Option<Value>) {
+    if let Some(mut contact) = check_schema::<EmergencyContact>(errors, "emergency_contact", value) {
+        require_text(errors, "emergency_contact.name", &mut contact.name);
+        check_phone(errors, "emergency_contact.phone", &mut contact.phone);
+        trim_optional(&mut contact.relationship);
+        *value = serde_json::to_value(&contact).ok();
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PatientListQuery {
+    /// Patients with a record, appointment or queue visit at this facility.
+    pub facility_id: Option<i32>,
+    /// Patients seen at any of the caller's facilities.
+    pub my_facilities: Option<bool>,
 }
 
 pub async fn list_patients(
+    user: AuthUser,
     State(pool): State<PgPool>,
+    Query(params): Query<PatientListQuery>,
 ) -> Result<Json<Vec<Patient>>, ServiceError> {
+    let facility_ids = facility_scope(&pool, user.id, params.facility_id, params.my_facilities)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
     let patients = sqlx::query_as!(Patient,
-        r#"SELECT * FROM patients"#
+        r#"SELECT * FROM patients p
+           WHERE p.tenant_id = $2
+             AND ($1::int[] IS NULL
+              OR EXISTS (SELECT 1 FROM medical_records r WHERE r.patient_id = p.id AND r.facility_id = ANY($1))
+              OR EXISTS (SELECT 1 FROM appointments a WHERE a.patient_id = p.id AND a.facility_id = ANY($1))
+              OR EXISTS (SELECT 1 FROM queue_entries q WHERE q.patient_id = p.id AND q.facility_id = ANY($1)))"#,
+        facility_ids.as_deref(),
+        user.tenant_id
     )
-    .fetch_all(&pool)
+    .fetch_all(&mut *tx)
     .await
     .map_err(|_| ServiceError::Unauthorized)?;
     Ok(Json(patients))
 }
 
 pub async fn get_patient(
+    user: AuthUser,
     Path(id): Path<i32>,
     State(pool): State<PgPool>,
 ) -> Result<Json<Patient>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
     let patient = sqlx::query_as!(Patient,
-        r#"SELECT * FROM patients WHERE id = $1"#,
-        id
+        r#"SELECT * FROM patients WHERE id = $1 AND tenant_id = $2"#,
+        id,
+        user.tenant_id
     )
-    .fetch_optional(&pool)
+    .fetch_optional(&mut *tx)
     .await
     .map_err(|_| ServiceError::Unauthorized)?;
     if let Some(patient) = patient {
@@ -142,22 +413,31 @@ pub async fn get_patient(
 }
 
 pub async fn create_patient(
+    user: AuthUser,
     State(pool): State<PgPool>,
-    Json(payload): Json<CreatePatientRequest>,
+    Json(mut payload): Json<CreatePatientRequest>,
 ) -> Result<(StatusCode, Json<Patient>), ServiceError> {
+    payload.validate()?;
+    check_household_exists(&pool, user.tenant_id, payload.household_id).await?;
+    let village = resolve_village(&pool, payload.village_id, payload.village.as_deref()).await?;
     let now = chrono::Utc::now().naive_utc();
     let status = payload.status.unwrap_or_else(|| "active".to_string());
-    let active_conditions_ref = payload.active_conditions.as_ref().map(Vec::as_slice);
-    let known_allergies_ref = payload.known_allergies.as_ref().map(Vec::as_slice);
+    let critical_flag_source = payload.critical_flag.unwrap_or(false).then_some("manual");
+    let active_conditions_ref = payload.active_conditions.as_deref();
+    let known_allergies_ref = payload.known_allergies.as_deref();
     let address = payload.address;
     let emergency_contact = payload.emergency_contact;
 
-    let rec = sqlx::query_as!(Patient,
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    let mut rec = sqlx::query_as!(Patient,
         r#"
         INSERT INTO patients (
-            first_name, middle_name, last_name, date_of_birth, gender, blood_type, phone_number, email, address, village, emergency_contact, active_conditions, known_allergies, additional_notes, status, critical_flag, profile_picture_url, next_visit, created_at, updated_at
+            first_name, middle_name, last_name, date_of_birth, gender, blood_type, phone_number, email, address, village, emergency_contact, active_conditions, known_allergies, additional_notes, status, critical_flag, profile_picture_url, created_at, updated_at, household_id,
+            critical_reason, critical_flag_source, tenant_id, village_id
         ) VALUES (
-            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24
         ) RETURNING *
         "#,
         payload.first_name,
@@ -169,7 +449,7 @@ pub async fn create_patient(
         payload.phone_number,
         payload.email,
         address,
-        payload.village,
+        village.as_ref().map(|v| v.name.clone()),
         emergency_contact,
         active_conditions_ref,
         known_allergies_ref,
@@ -177,24 +457,51 @@ pub async fn create_patient(
         status,
         payload.critical_flag,
         payload.profile_picture_url,
-        payload.next_visit,
         Some(now),
-        Some(now)
+        Some(now),
+        payload.household_id,
+        payload.critical_reason.filter(|_| critical_flag_source.is_some()),
+        critical_flag_source,
+        user.tenant_id,
+        village.as_ref().map(|v| v.id)
     )
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|_| ServiceError::Unauthorized)?;
+    if let Some(next_visit) = payload.next_visit {
+        rec.next_visit = book_follow_up(&mut tx, rec.id, next_visit)
+            .await
+            .map_err(|_| ServiceError::InternalServerError)?;
+    }
+    tx.commit().await.map_err(|_| ServiceError::InternalServerError)?;
     Ok((StatusCode::CREATED, Json(rec)))
 }
 
 pub async fn update_patient(
+    user: AuthUser,
     Path(id): Path<i32>,
     State(pool): State<PgPool>,
-    Json(payload): Json<UpdatePatientRequest>,
+    Json(mut payload): Json<UpdatePatientRequest>,
 ) -> Result<Json<Patient>, ServiceError> {
+    payload.validate()?;
+    check_household_exists(&pool, user.tenant_id, payload.household_id).await?;
+    let village = resolve_village(&pool, payload.village_id, payload.village.as_deref()).await?;
     let now = chrono::Utc::now().naive_utc();
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    let before = sqlx::query_as!(Patient,
+        r#"SELECT * FROM patients WHERE id = $1 AND tenant_id = $2 FOR UPDATE"#,
+        id,
+        user.tenant_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|_| ServiceError::InternalServerError)?
+    .ok_or(ServiceError::NotFound)?;
+
     // Build dynamic SQL for only provided fields (for brevity, here is a simple version)
-    let patient = sqlx::query_as!(Patient,
+    let mut patient = sqlx::query_as!(Patient,
         r#"
         UPDATE patients SET
             first_name = COALESCE($1, first_name),
@@ -213,10 +520,17 @@ pub async fn update_patient(
             additional_notes = COALESCE($14, additional_notes),
             status = COALESCE($15, status),
             critical_flag = COALESCE($16, critical_flag),
+            -- a flag set or cleared by staff takes ownership from the early warning score
+            critical_reason = CASE WHEN $16::boolean = false THEN NULL
+                                   WHEN COALESCE($16, critical_flag, false) THEN COALESCE($21, critical_reason)
+                                   ELSE critical_reason END,
+            critical_flag_source = CASE WHEN $16::boolean IS NULL THEN critical_flag_source
+                                        WHEN $16 THEN 'manual' ELSE NULL END,
             profile_picture_url = COALESCE($17, profile_picture_url),
-            next_visit = COALESCE($18, next_visit),
-            updated_at = $19
-        WHERE id = $20
+            household_id = COALESCE($20, household_id),
+            village_id = COALESCE($23, village_id),
+            updated_at = $18
+        WHERE id = $19 AND tenant_id = $22
         RETURNING *
         "#,
         payload.first_name,
@@ -228,7 +542,7 @@ pub async fn update_patient(
         payload.phone_number,
         payload.email,
         payload.address,
-        payload.village,
+        village.as_ref().map(|v| v.name.clone()),
         payload.emergency_contact,
         payload.active_conditions.as_deref(),
         payload.known_allergies.as_deref(),
@@ -236,41 +550,82 @@ pub async fn update_patient(
         payload.status,
         payload.critical_flag,
         payload.profile_picture_url,
-        payload.next_visit,
         Some(now),
-        id
+        id,
+        payload.household_id,
+        payload.critical_reason,
+        user.tenant_id,
+        village.as_ref().map(|v| v.id)
     )
-    .fetch_optional(&pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|_| ServiceError::Unauthorized)?;
-    if let Some(patient) = patient {
-        Ok(Json(patient))
-    } else {
-        Err(ServiceError::NotFound)
+
+    if let Some(next_visit) = payload.next_visit {
+        patient.next_visit = book_follow_up(&mut tx, id, next_visit)
+            .await
+            .map_err(|_| ServiceError::InternalServerError)?;
     }
+    record_patient_changes(&mut tx, user.id, &before, &patient)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    tx.commit().await.map_err(|_| ServiceError::InternalServerError)?;
+    Ok(Json(patient))
 }
 
-use axum::extract::Request;
+/// Write profile edits and status changes to the audit trail.
+async fn record_patient_changes(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: i32,
+    before: &Patient,
+    after: &Patient,
+) -> Result<(), sqlx::Error> {
+    let before_json = serde_json::to_value(before).unwrap_or_default();
+    let after_json = serde_json::to_value(after).unwrap_or_default();
+    let mut changes = audit::diff_fields(&before_json, &after_json, &["updated_at", "created_at"]);
+
+    if let Some(status_change) = changes.remove("status") {
+        audit::record(&mut **tx, AuditEntry {
+            user_id: Some(user_id),
+            action: "patient.status_changed",
+            entity_type: "patient",
+            entity_id: after.id,
+            patient_id: Some(after.id),
+            details: status_change,
+        }).await?;
+    }
+    if !changes.is_empty() {
+        audit::record(&mut **tx, AuditEntry {
+            user_id: Some(user_id),
+            action: "patient.updated",
+            entity_type: "patient",
+            entity_id: after.id,
+            patient_id: Some(after.id),
+            details: serde_json::json!({ "changes": changes }),
+        }).await?;
+    }
+    Ok(())
+}
 
 pub async fn delete_patient(
+    user: AuthUser,
     Path(id): Path<i32>,
     State(pool): State<PgPool>,
-    req: Request,
 ) -> Result<StatusCode, ServiceError> {
-    // Debug: print all request headers
-    println!("[DELETE PATIENT DEBUG] Incoming request headers:");
-    for (name, value) in req.headers().iter() {
-        println!("  {}: {:?}", name, value);
-    }
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
     let result = sqlx::query!(
-        "DELETE FROM patients WHERE id = $1",
-        id
+        "DELETE FROM patients WHERE id = $1 AND tenant_id = $2",
+        id,
+        user.tenant_id
     )
-    .execute(&pool)
+    .execute(&mut *tx)
     .await;
     match result {
         Ok(res) => {
             if res.rows_affected() > 0 {
+                tx.commit().await.map_err(|_| ServiceError::InternalServerError)?;
                 Ok(StatusCode::NO_CONTENT)
             } else {
                 Err(ServiceError::NotFound)
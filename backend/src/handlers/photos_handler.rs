@@ -0,0 +1,329 @@
+use axum::{
+    body::Body,
+    extract::{Multipart, Path, Query, State},
+    http::{header, StatusCode},
+    response::Response,
+    Json,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::config::state::UploadLimits;
+use crate::models::error::ServiceError;
+use crate::models::photo::PatientPhoto;
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::media::{process_photo, sniff_image_format, SignedUrl, UrlSigner};
+use crate::services::storage::{BlobStore, StorageError};
+use crate::services::tenancy;
+
+/// Multipart field that carries the image.
+const PHOTO_FIELD: &str = "photo";
+const PHOTO_CONTENT_TYPE: &str = "image/jpeg";
+
+#[derive(Serialize)]
+pub struct PhotoResponse {
+    #[serde(flatten)]
+    pub photo: PatientPhoto,
+    pub url: SignedUrl,
+    pub thumbnail_url: SignedUrl,
+}
+
+#[derive(Deserialize)]
+pub struct PhotoQuery {
+    pub variant: Option<String>,
+    /// Photo version the URL was signed for.
+    pub v: i32,
+    pub expires: i64,
+    pub signature: String,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[PATIENT PHOTO ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+fn storage_error(e: StorageError) -> ServiceError {
+    match e {
+        StorageError::NotFound => ServiceError::NotFound,
+        other => {
+            eprintln!("[PATIENT PHOTO ERROR] Storage error: {}", other);
+            ServiceError::InternalServerError
+        }
+    }
+}
+
+/// Stable API path of a patient's photo; clients need a signed copy of it to fetch the image.
+fn photo_path(patient_id: i32) -> String {
+    format!("/patients/{}/photo", patient_id)
+}
+
+/// The path that gets signed: it names the photo version, so URLs issued before a
+/// replacement stop working.
+fn signed_path(patient_id: i32, version: i32, thumbnail: bool) -> String {
+    if thumbnail {
+        format!("/patients/{}/photo?v={}&variant=thumbnail", patient_id, version)
+    } else {
+        format!("/patients/{}/photo?v={}", patient_id, version)
+    }
+}
+
+fn signed_response(signer: &UrlSigner, photo: PatientPhoto) -> PhotoResponse {
+    PhotoResponse {
+        url: signer.sign(&signed_path(photo.patient_id, photo.version, false)),
+        thumbnail_url: signer.sign(&signed_path(photo.patient_id, photo.version, true)),
+        photo,
+    }
+}
+
+/// `404` unless the patient belongs to the caller's tenant.
+async fn check_patient(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    tenant_id: i32,
+    patient_id: i32,
+) -> Result<(), ServiceError> {
+    let exists: Option<i32> = sqlx::query_scalar("SELECT id FROM patients WHERE id = $1 AND tenant_id = $2")
+        .bind(patient_id)
+        .bind(tenant_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    exists.map(|_| ()).ok_or(ServiceError::NotFound)
+}
+
+/// Read the photo field, refusing to buffer more than `max_bytes`.
+async fn read_photo_field(
+    multipart: &mut Multipart,
+    max_bytes: usize,
+) -> Result<(Vec<u8>, Option<String>), ServiceError> {
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ServiceError::BadRequest(e.body_text()))?
+    {
+        if field.name() != Some(PHOTO_FIELD) {
+            continue;
+        }
+        let filename = field.file_name().map(str::to_string);
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field
+            .chunk()
+            .await
+            .map_err(|e| ServiceError::BadRequest(e.body_text()))?
+        {
+            if bytes.len() + chunk.len() > max_bytes {
+                return Err(ServiceError::PayloadTooLarge(format!(
+                    "Photo exceeds the {} byte limit",
+                    max_bytes
+                )));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        return Ok((bytes, filename));
+    }
+    Err(ServiceError::BadRequest(format!("Missing multipart field '{}'", PHOTO_FIELD)))
+}
+
+/// Upload or replace a patient's photo. Accepts JPEG, PNG or WebP; the stored copy is a
+/// metadata-free JPEG plus a thumbnail.
+pub async fn upload_patient_photo(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    State(storage): State<Arc<dyn BlobStore>>,
+    State(signer): State<UrlSigner>,
+    State(limits): State<UploadLimits>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<PhotoResponse>), ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    check_patient(&mut tx, user.tenant_id, id).await?;
+    tx.commit().await.map_err(db_error)?;
+
+    let (bytes, filename) = read_photo_field(&mut multipart, limits.max_photo_bytes).await?;
+    let format = sniff_image_format(&bytes).ok_or_else(|| {
+        ServiceError::UnsupportedMediaType("Photo must be a JPEG, PNG or WebP image".to_string())
+    })?;
+    let processed = tokio::task::spawn_blocking(move || process_photo(&bytes, format))
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?
+        .map_err(|e| ServiceError::UnsupportedMediaType(format!("Photo could not be decoded: {}", e)))?;
+
+    let object_id = uuid::Uuid::new_v4();
+    let storage_key = format!("patients/{}/photo-{}.jpg", id, object_id);
+    let thumbnail_key = format!("patients/{}/photo-{}-thumb.jpg", id, object_id);
+    let size_bytes = processed.photo.len() as i64;
+    storage
+        .put(&storage_key, processed.photo, PHOTO_CONTENT_TYPE)
+        .await
+        .map_err(storage_error)?;
+    storage
+        .put(&thumbnail_key, processed.thumbnail, PHOTO_CONTENT_TYPE)
+        .await
+        .map_err(storage_error)?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    check_patient(&mut tx, user.tenant_id, id).await?;
+    let previous = sqlx::query_as::<_, PatientPhoto>("SELECT * FROM patient_photos WHERE patient_id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    let photo = sqlx::query_as::<_, PatientPhoto>(
+        r#"INSERT INTO patient_photos (patient_id, storage_key, thumbnail_key, content_type, size_bytes, width, height, original_filename, created_at)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, now())
+           ON CONFLICT (patient_id) DO UPDATE SET
+               storage_key = EXCLUDED.storage_key,
+               thumbnail_key = EXCLUDED.thumbnail_key,
+               content_type = EXCLUDED.content_type,
+               size_bytes = EXCLUDED.size_bytes,
+               width = EXCLUDED.width,
+               height = EXCLUDED.height,
+               original_filename = EXCLUDED.original_filename,
+               created_at = EXCLUDED.created_at,
+               version = patient_photos.version + 1
+           RETURNING *"#
+    )
+    .bind(id)
+    .bind(&storage_key)
+    .bind(&thumbnail_key)
+    .bind(PHOTO_CONTENT_TYPE)
+    .bind(size_bytes)
+    .bind(processed.width as i32)
+    .bind(processed.height as i32)
+    .bind(filename)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    sqlx::query("UPDATE patients SET profile_picture_url = $1, updated_at = now() WHERE id = $2")
+        .bind(photo_path(id))
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "patient.updated",
+        entity_type: "patient",
+        entity_id: id,
+        patient_id: Some(id),
+        details: serde_json::json!({
+            "changes": { "photo": { "from": previous.as_ref().map(|p| p.created_at), "to": photo.created_at } }
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    if let Some(previous) = previous {
+        remove_blobs(storage.as_ref(), &previous).await;
+    }
+    Ok((StatusCode::CREATED, Json(signed_response(&signer, photo))))
+}
+
+/// Old blobs are cleaned up best-effort; a failure only leaves an orphaned object behind.
+async fn remove_blobs(storage: &dyn BlobStore, photo: &PatientPhoto) {
+    for key in [&photo.storage_key, &photo.thumbnail_key] {
+        if let Err(e) = storage.delete(key).await {
+            eprintln!("[PATIENT PHOTO ERROR] Failed to delete {}: {}", key, e);
+        }
+    }
+}
+
+async fn fetch_photo<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    patient_id: i32,
+) -> Result<PatientPhoto, ServiceError> {
+    sqlx::query_as::<_, PatientPhoto>("SELECT * FROM patient_photos WHERE patient_id = $1")
+        .bind(patient_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(db_error)?
+        .ok_or(ServiceError::NotFound)
+}
+
+/// Fresh signed URLs for a patient's photo and thumbnail.
+pub async fn get_patient_photo_url(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    State(signer): State<UrlSigner>,
+) -> Result<Json<PhotoResponse>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    check_patient(&mut tx, user.tenant_id, id).await?;
+    let photo = fetch_photo(&mut *tx, id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(signed_response(&signer, photo)))
+}
+
+/// Serve the image behind a signed URL. The signature stands in for sign-in, since image
+/// tags cannot send a token; it only covers the photo version it was issued for.
+pub async fn get_patient_photo(
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    State(storage): State<Arc<dyn BlobStore>>,
+    State(signer): State<UrlSigner>,
+    Query(params): Query<PhotoQuery>,
+) -> Result<Response, ServiceError> {
+    let thumbnail = match params.variant.as_deref() {
+        None => false,
+        Some("thumbnail") => true,
+        Some(other) => return Err(ServiceError::BadRequest(format!("Unknown photo variant: {}", other))),
+    };
+    if !signer.verify(&signed_path(id, params.v, thumbnail), params.expires, &params.signature) {
+        return Err(ServiceError::Forbidden);
+    }
+
+    let photo = fetch_photo(&pool, id).await?;
+    if photo.version != params.v {
+        return Err(ServiceError::NotFound);
+    }
+    let key = if thumbnail { &photo.thumbnail_key } else { &photo.storage_key };
+    let bytes = storage.get(key).await.map_err(storage_error)?;
+    let max_age = (params.expires - chrono::Utc::now().timestamp()).max(0);
+    Response::builder()
+        .header(header::CONTENT_TYPE, photo.content_type)
+        .header(header::CACHE_CONTROL, format!("private, max-age={}", max_age))
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .body(Body::from(bytes))
+        .map_err(|_| ServiceError::InternalServerError)
+}
+
+pub async fn delete_patient_photo(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    State(storage): State<Arc<dyn BlobStore>>,
+) -> Result<StatusCode, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    check_patient(&mut tx, user.tenant_id, id).await?;
+    let photo = sqlx::query_as::<_, PatientPhoto>("DELETE FROM patient_photos WHERE patient_id = $1 RETURNING *")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?
+        .ok_or(ServiceError::NotFound)?;
+    sqlx::query("UPDATE patients SET profile_picture_url = NULL, updated_at = now() WHERE id = $1 AND profile_picture_url = $2")
+        .bind(id)
+        .bind(photo_path(id))
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "patient.updated",
+        entity_type: "patient",
+        entity_id: id,
+        patient_id: Some(id),
+        details: serde_json::json!({
+            "changes": { "photo": { "from": photo.created_at, "to": Option::<NaiveDateTime>::None } }
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    remove_blobs(storage.as_ref(), &photo).await;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,947 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use crate::handlers::records_handler::authorize_record;
+use crate::models::error::ServiceError;
+use crate::models::immunization::Immunization;
+use crate::models::patient::Gender;
+use crate::models::pregnancy::{
+    AncCheck, AncContact, AncContactState, AncContactStatus, AncScheduleContact, BirthOutcome, DeliveryMode,
+    DeliveryPlace, Dipstick, EddMethod, FetalPresentation, GestationalAge, Pregnancy, PregnancyBirth,
+    PregnancyOutcome, PregnancyStatus, RiskFactor,
+};
+use crate::services::access::{check_clinical_role, RecordAction};
+use crate::services::alerts::{notify_care_team, AlertEntry};
+use crate::services::antenatal::{
+    baseline_risks, contact_number_for_visit, contact_risks, edd_from_lmp, evaluate_contacts, gestation_start,
+    gestational_age, gestational_age_days,
+};
+use crate::services::audit::{self, diff_fields, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::tenancy;
+use crate::services::validation::{
+    check_not_future, field_error, normalize_enum, normalize_optional_enum, trim_optional, TextEnum,
+    ValidationErrors,
+};
+
+const DEFAULT_DUE_LIMIT: usize = 200;
+const MAX_DUE_LIMIT: usize = 1000;
+/// Longest plausible gestation at booking or at a contact.
+const MAX_GESTATION_WEEKS: i32 = 44;
+
+#[derive(Deserialize)]
+pub struct PregnancyRequest {
+    /// First day of the last menstrual period.
+    pub lmp: Option<NaiveDate>,
+    /// Calculated from `lmp` when omitted.
+    pub edd: Option<NaiveDate>,
+    /// Required when `edd` is given: lmp, ultrasound or clinical.
+    pub edd_method: Option<String>,
+    pub gravida: i32,
+    pub para: i32,
+    /// Defaults to today. Ignored on update.
+    pub booked_on: Option<NaiveDate>,
+    #[serde(default)]
+    pub risk_factors: Vec<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RecordAncContactRequest {
+    /// Defaults to today.
+    pub contact_date: Option<NaiveDate>,
+    /// The scheduled contact the visit counts as. Worked out from the date when omitted.
+    pub contact_number: Option<i32>,
+    pub record_id: Option<i32>,
+    pub weight_kg: Option<f64>,
+    pub systolic_bp: Option<i32>,
+    pub diastolic_bp: Option<i32>,
+    pub haemoglobin: Option<f64>,
+    pub fundal_height_cm: Option<f64>,
+    pub fetal_heart_rate: Option<i32>,
+    pub urine_protein: Option<String>,
+    pub urine_glucose: Option<String>,
+    pub fetal_presentation: Option<String>,
+    /// Checks done that have no measurement, e.g. `iron_folic_acid` or `hiv`.
+    #[serde(default)]
+    pub checks_done: Vec<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BirthRequest {
+    pub outcome: String,
+    pub sex: Option<String>,
+    pub birth_weight_grams: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct RecordOutcomeRequest {
+    pub outcome: String,
+    /// Defaults to today.
+    pub outcome_date: Option<NaiveDate>,
+    pub delivery_mode: Option<String>,
+    pub delivery_place: Option<String>,
+    #[serde(default)]
+    pub maternal_death: bool,
+    /// One entry per baby; required for a live birth or stillbirth.
+    #[serde(default)]
+    pub births: Vec<BirthRequest>,
+    /// Register each live-born baby as a patient in the mother's household.
+    #[serde(default)]
+    pub register_newborns: bool,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AncDueQuery {
+    pub village: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct PregnancyDetail {
+    #[serde(flatten)]
+    pub pregnancy: Pregnancy,
+    pub high_risk: bool,
+    /// Today's gestational age, or at the outcome once the pregnancy has ended.
+    pub gestational_age: GestationalAge,
+    pub contacts: Vec<AncContactState>,
+    /// Every visit, including extra ones outside the schedule.
+    pub visits: Vec<AncContact>,
+    /// Td doses given since the start of the pregnancy.
+    pub td_doses: Vec<Immunization>,
+    pub births: Vec<PregnancyBirth>,
+}
+
+#[derive(Serialize)]
+pub struct AncDueEntry {
+    pub pregnancy_id: i32,
+    pub patient_id: i32,
+    pub patient_name: String,
+    pub village: Option<String>,
+    pub household_id: Option<i32>,
+    pub phone_number: String,
+    pub edd: NaiveDate,
+    pub gestational_age: GestationalAge,
+    pub high_risk: bool,
+    pub risk_factors: Vec<String>,
+    pub last_contact_date: Option<NaiveDate>,
+    pub oldest_overdue_date: Option<NaiveDate>,
+    /// Past the EDD with no outcome recorded.
+    pub past_edd: bool,
+    pub missed_contacts: usize,
+    pub overdue: Vec<AncContactState>,
+    pub due: Vec<AncContactState>,
+}
+
+#[derive(FromRow)]
+struct MotherRow {
+    #[sqlx(flatten)]
+    pregnancy: Pregnancy,
+    patient_name: String,
+    village: Option<String>,
+    household_id: Option<i32>,
+    phone_number: String,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[PREGNANCIES ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+async fn load_anc_schedule<'e>(executor: impl sqlx::PgExecutor<'e>) -> Result<Vec<AncScheduleContact>, ServiceError> {
+    sqlx::query_as::<_, AncScheduleContact>("SELECT * FROM anc_schedule ORDER BY gestational_week")
+        .fetch_all(executor)
+        .await
+        .map_err(db_error)
+}
+
+fn pregnancy_status(pregnancy: &Pregnancy) -> Option<PregnancyStatus> {
+    PregnancyStatus::parse(&pregnancy.status)
+}
+
+/// Check dating, parity and risk factors, filling in the EDD from the LMP when it is missing.
+fn validate_pregnancy(payload: &mut PregnancyRequest, booked_on: NaiveDate) -> Result<NaiveDate, ServiceError> {
+    let mut errors = ValidationErrors::default();
+    trim_optional(&mut payload.notes);
+    normalize_optional_enum::<EddMethod>(&mut errors, "edd_method", &mut payload.edd_method);
+    if let Some(lmp) = payload.lmp {
+        check_not_future(&mut errors, "lmp", lmp);
+    }
+    let edd = match (payload.lmp, payload.edd) {
+        (_, Some(edd)) => {
+            if payload.edd_method.is_none() {
+                errors.add("edd_method", "is required when edd is given");
+            } else if payload.edd_method.as_deref() == Some(EddMethod::Lmp.as_str()) && payload.lmp.is_none() {
+                errors.add("lmp", "is required when the EDD is dated from the LMP");
+            }
+            Some(edd)
+        }
+        (Some(lmp), None) => {
+            payload.edd_method = Some(EddMethod::Lmp.as_str().to_string());
+            Some(edd_from_lmp(lmp))
+        }
+        (None, None) => {
+            errors.add("lmp", "or edd is required");
+            None
+        }
+    };
+    if let Some(edd) = edd {
+        let weeks = gestational_age_days(edd, booked_on).div_euclid(7);
+        if !(0..=MAX_GESTATION_WEEKS).contains(&weeks) {
+            errors.add(
+                if payload.edd.is_some() { "edd" } else { "lmp" },
+                format!("gives a gestational age of {} weeks at booking", weeks),
+            );
+        }
+    }
+    if payload.gravida < 1 {
+        errors.add("gravida", "must be at least 1, counting this pregnancy");
+    }
+    if payload.para < 0 {
+        errors.add("para", "must not be negative");
+    } else if payload.para >= payload.gravida {
+        errors.add("para", "must be less than gravida");
+    }
+    let mut seen = HashSet::new();
+    for (index, factor) in payload.risk_factors.iter_mut().enumerate() {
+        normalize_enum::<RiskFactor>(&mut errors, &format!("risk_factors[{}]", index), factor);
+    }
+    payload.risk_factors.retain(|factor| seen.insert(factor.clone()));
+    errors.into_result()?;
+    Ok(edd.unwrap_or(booked_on))
+}
+
+async fn pregnancy_detail(
+    tx: &mut Transaction<'_, Postgres>,
+    pregnancy: Pregnancy,
+) -> Result<PregnancyDetail, ServiceError> {
+    let schedule = load_anc_schedule(&mut **tx).await?;
+    let visits = sqlx::query_as::<_, AncContact>(
+        "SELECT * FROM anc_contacts WHERE pregnancy_id = $1 ORDER BY contact_date, id",
+    )
+    .bind(pregnancy.id)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    let td_doses = sqlx::query_as::<_, Immunization>(
+        "SELECT * FROM immunizations
+         WHERE patient_id = $1 AND vaccine_code = 'td' AND administered_on >= $2
+           AND ($3::date IS NULL OR administered_on <= $3)
+         ORDER BY administered_on",
+    )
+    .bind(pregnancy.patient_id)
+    .bind(gestation_start(pregnancy.edd))
+    .bind(pregnancy.outcome_date)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    let births = sqlx::query_as::<_, PregnancyBirth>(
+        "SELECT * FROM pregnancy_births WHERE pregnancy_id = $1 ORDER BY birth_order",
+    )
+    .bind(pregnancy.id)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(db_error)?;
+
+    let today = Utc::now().date_naive();
+    let contacts =
+        evaluate_contacts(pregnancy.edd, pregnancy.booked_on, pregnancy.outcome_date, today, &schedule, &visits);
+    Ok(PregnancyDetail {
+        high_risk: !pregnancy.risk_factors.is_empty(),
+        gestational_age: gestational_age(pregnancy.edd, pregnancy.outcome_date.unwrap_or(today)),
+        contacts,
+        visits,
+        td_doses,
+        births,
+        pregnancy,
+    })
+}
+
+async fn lock_pregnancy(tx: &mut Transaction<'_, Postgres>, tenant_id: i32, id: i32) -> Result<Pregnancy, ServiceError> {
+    sqlx::query_as::<_, Pregnancy>(
+        "SELECT pr.* FROM pregnancies pr JOIN patients p ON p.id = pr.patient_id AND p.tenant_id = $2
+         WHERE pr.id = $1 FOR UPDATE OF pr",
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)
+}
+
+fn ensure_active(pregnancy: &Pregnancy) -> Result<(), ServiceError> {
+    match pregnancy_status(pregnancy) {
+        Some(PregnancyStatus::Active) => Ok(()),
+        _ => Err(ServiceError::Conflict(format!(
+            "Pregnancy #{} has already ended ({})",
+            pregnancy.id,
+            pregnancy.outcome.as_deref().unwrap_or(&pregnancy.status)
+        ))),
+    }
+}
+
+pub async fn get_anc_schedule(
+    _user: AuthUser,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<AncScheduleContact>>, ServiceError> {
+    load_anc_schedule(&pool).await.map(Json)
+}
+
+pub async fn list_pregnancies(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<Pregnancy>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let exists: Option<i32> = sqlx::query_scalar("SELECT id FROM patients WHERE id = $1 AND tenant_id = $2")
+        .bind(patient_id)
+        .bind(user.tenant_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    exists.ok_or(ServiceError::NotFound)?;
+    let pregnancies = sqlx::query_as::<_, Pregnancy>(
+        "SELECT * FROM pregnancies WHERE patient_id = $1 ORDER BY edd DESC",
+    )
+    .bind(patient_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(pregnancies))
+}
+
+/// Register a pregnancy. Age and parity risk factors are added to those given.
+pub async fn create_pregnancy(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<PregnancyRequest>,
+) -> Result<(StatusCode, Json<PregnancyDetail>), ServiceError> {
+    check_clinical_role(&user)?;
+    let booked_on = payload.booked_on.unwrap_or_else(|| Utc::now().date_naive());
+    let mut errors = ValidationErrors::default();
+    check_not_future(&mut errors, "booked_on", booked_on);
+    errors.into_result()?;
+    let edd = validate_pregnancy(&mut payload, booked_on)?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let (date_of_birth, gender): (NaiveDate, String) =
+        sqlx::query_as("SELECT date_of_birth, gender FROM patients WHERE id = $1 AND tenant_id = $2")
+            .bind(patient_id)
+            .bind(user.tenant_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(db_error)?
+            .ok_or(ServiceError::NotFound)?;
+    if Gender::parse(&gender) != Some(Gender::Female) {
+        return Err(ServiceError::BadRequest("Pregnancies can only be registered for female patients".to_string()));
+    }
+    let mut risk_factors = payload.risk_factors.clone();
+    for risk in baseline_risks(date_of_birth, booked_on, payload.para) {
+        if !risk_factors.iter().any(|r| r == risk.as_str()) {
+            risk_factors.push(risk.as_str().to_string());
+        }
+    }
+
+    let pregnancy = sqlx::query_as::<_, Pregnancy>(
+        "INSERT INTO pregnancies (patient_id, lmp, edd, edd_method, gravida, para, booked_on, risk_factors, notes,
+             registered_by, updated_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10)
+         RETURNING *",
+    )
+    .bind(patient_id)
+    .bind(payload.lmp)
+    .bind(edd)
+    .bind(&payload.edd_method)
+    .bind(payload.gravida)
+    .bind(payload.para)
+    .bind(booked_on)
+    .bind(&risk_factors)
+    .bind(&payload.notes)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            ServiceError::Conflict("The patient already has an ongoing pregnancy".to_string())
+        }
+        _ => db_error(e),
+    })?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "pregnancy.registered",
+        entity_type: "pregnancy",
+        entity_id: pregnancy.id,
+        patient_id: Some(patient_id),
+        details: serde_json::json!({
+            "edd": pregnancy.edd,
+            "edd_method": pregnancy.edd_method,
+            "gravida": pregnancy.gravida,
+            "para": pregnancy.para,
+            "risk_factors": pregnancy.risk_factors,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    let detail = pregnancy_detail(&mut tx, pregnancy).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(detail)))
+}
+
+/// The pregnancy with its ANC schedule, visits and outcome.
+pub async fn get_pregnancy(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<PregnancyDetail>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let pregnancy = sqlx::query_as::<_, Pregnancy>(
+        "SELECT pr.* FROM pregnancies pr JOIN patients p ON p.id = pr.patient_id AND p.tenant_id = $2
+         WHERE pr.id = $1",
+    )
+    .bind(id)
+    .bind(user.tenant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    let detail = pregnancy_detail(&mut tx, pregnancy).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(detail))
+}
+
+/// Replace the dating, parity, risk factors and notes, e.g. after a dating scan. Contacts
+/// already held keep the contact they were counted as.
+pub async fn update_pregnancy(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<PregnancyRequest>,
+) -> Result<Json<PregnancyDetail>, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let current = lock_pregnancy(&mut tx, user.tenant_id, id).await?;
+    let edd = validate_pregnancy(&mut payload, current.booked_on)?;
+    let pregnancy = sqlx::query_as::<_, Pregnancy>(
+        "UPDATE pregnancies SET lmp = $2, edd = $3, edd_method = $4, gravida = $5, para = $6, risk_factors = $7,
+             notes = $8, updated_by = $9, updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(payload.lmp)
+    .bind(edd)
+    .bind(&payload.edd_method)
+    .bind(payload.gravida)
+    .bind(payload.para)
+    .bind(&payload.risk_factors)
+    .bind(&payload.notes)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    let changes = diff_fields(
+        &serde_json::to_value(&current).unwrap_or_default(),
+        &serde_json::to_value(&pregnancy).unwrap_or_default(),
+        &["updated_by", "updated_at"],
+    );
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "pregnancy.updated",
+        entity_type: "pregnancy",
+        entity_id: id,
+        patient_id: Some(pregnancy.patient_id),
+        details: serde_json::json!({ "changes": changes }),
+    })
+    .await
+    .map_err(db_error)?;
+    let detail = pregnancy_detail(&mut tx, pregnancy).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(detail))
+}
+
+/// Record an ANC visit. It counts as the scheduled contact whose window it falls in, and any
+/// risk factors its measurements show are added to the pregnancy and sent to the care team.
+pub async fn record_anc_contact(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<RecordAncContactRequest>,
+) -> Result<(StatusCode, Json<PregnancyDetail>), ServiceError> {
+    check_clinical_role(&user)?;
+    let contact_date = payload.contact_date.unwrap_or_else(|| Utc::now().date_naive());
+    let mut errors = ValidationErrors::default();
+    check_not_future(&mut errors, "contact_date", contact_date);
+    trim_optional(&mut payload.notes);
+    normalize_optional_enum::<Dipstick>(&mut errors, "urine_protein", &mut payload.urine_protein);
+    normalize_optional_enum::<Dipstick>(&mut errors, "urine_glucose", &mut payload.urine_glucose);
+    normalize_optional_enum::<FetalPresentation>(&mut errors, "fetal_presentation", &mut payload.fetal_presentation);
+    let mut check_range = |field: &str, value: Option<f64>, low: f64, high: f64| {
+        if value.is_some_and(|v| !(low..=high).contains(&v)) {
+            errors.add(field, format!("must be between {} and {}", low, high));
+        }
+    };
+    check_range("weight_kg", payload.weight_kg, 25.0, 200.0);
+    check_range("systolic_bp", payload.systolic_bp.map(f64::from), 60.0, 260.0);
+    check_range("diastolic_bp", payload.diastolic_bp.map(f64::from), 30.0, 160.0);
+    check_range("haemoglobin", payload.haemoglobin, 2.0, 20.0);
+    check_range("fundal_height_cm", payload.fundal_height_cm, 5.0, 50.0);
+    check_range("fetal_heart_rate", payload.fetal_heart_rate.map(f64::from), 50.0, 240.0);
+    match (payload.systolic_bp, payload.diastolic_bp) {
+        (Some(systolic), Some(diastolic)) if systolic <= diastolic => {
+            errors.add("diastolic_bp", "must be lower than systolic_bp")
+        }
+        (Some(_), None) => errors.add("diastolic_bp", "is required with systolic_bp"),
+        (None, Some(_)) => errors.add("systolic_bp", "is required with diastolic_bp"),
+        _ => {}
+    }
+    for (index, check) in payload.checks_done.iter_mut().enumerate() {
+        normalize_enum::<AncCheck>(&mut errors, &format!("checks_done[{}]", index), check);
+    }
+    errors.into_result()?;
+
+    let implied = [
+        (payload.weight_kg.is_some(), AncCheck::Weight),
+        (payload.systolic_bp.is_some(), AncCheck::BloodPressure),
+        (payload.haemoglobin.is_some(), AncCheck::Haemoglobin),
+        (payload.fundal_height_cm.is_some(), AncCheck::FundalHeight),
+        (payload.fetal_heart_rate.is_some(), AncCheck::FetalHeartRate),
+        (payload.urine_protein.is_some(), AncCheck::UrineProtein),
+        (payload.urine_glucose.is_some(), AncCheck::UrineGlucose),
+        (payload.fetal_presentation.is_some(), AncCheck::FetalPresentation),
+    ];
+    let mut checks_done: Vec<String> = Vec::new();
+    for check in payload
+        .checks_done
+        .iter()
+        .map(String::as_str)
+        .chain(implied.iter().filter(|(done, _)| *done).map(|(_, check)| check.as_str()))
+    {
+        if !checks_done.iter().any(|c| c == check) {
+            checks_done.push(check.to_string());
+        }
+    }
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let schedule = load_anc_schedule(&mut *tx).await?;
+    let pregnancy = lock_pregnancy(&mut tx, user.tenant_id, id).await?;
+    ensure_active(&pregnancy)?;
+    let age_days = gestational_age_days(pregnancy.edd, contact_date);
+    if !(0..=MAX_GESTATION_WEEKS * 7).contains(&age_days) {
+        return Err(field_error("contact_date", "is outside this pregnancy"));
+    }
+    if let Some(record_id) = payload.record_id {
+        let record = authorize_record(&mut *tx, record_id, &user, RecordAction::Read).await?;
+        if record.patient_id != pregnancy.patient_id {
+            return Err(field_error("record_id", "belongs to a different patient"));
+        }
+    }
+    let held: Vec<i32> = sqlx::query_scalar(
+        "SELECT contact_number FROM anc_contacts WHERE pregnancy_id = $1 AND contact_number IS NOT NULL",
+    )
+    .bind(id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    let contact_number = match payload.contact_number {
+        Some(number) if !schedule.iter().any(|c| c.contact_number == number) => {
+            return Err(field_error("contact_number", "is not a scheduled contact"));
+        }
+        Some(number) if held.contains(&number) => {
+            return Err(ServiceError::Conflict(format!("Contact {} is already recorded for this pregnancy", number)));
+        }
+        Some(number) => Some(number),
+        None => contact_number_for_visit(pregnancy.edd, &schedule, &held, contact_date),
+    };
+    // a Td dose recorded for the same day counts as the Td check
+    let td_given: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM immunizations WHERE patient_id = $1 AND vaccine_code = 'td' AND administered_on = $2)",
+    )
+    .bind(pregnancy.patient_id)
+    .bind(contact_date)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    if td_given && !checks_done.iter().any(|c| c == AncCheck::Td.as_str()) {
+        checks_done.push(AncCheck::Td.as_str().to_string());
+    }
+
+    let contact = sqlx::query_as::<_, AncContact>(
+        "INSERT INTO anc_contacts (pregnancy_id, contact_number, contact_date, gestational_age_days, record_id,
+             weight_kg, systolic_bp, diastolic_bp, haemoglobin, fundal_height_cm, fetal_heart_rate, urine_protein,
+             urine_glucose, fetal_presentation, checks_done, notes, recorded_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+         RETURNING *",
+    )
+    .bind(id)
+    .bind(contact_number)
+    .bind(contact_date)
+    .bind(age_days)
+    .bind(payload.record_id)
+    .bind(payload.weight_kg)
+    .bind(payload.systolic_bp)
+    .bind(payload.diastolic_bp)
+    .bind(payload.haemoglobin)
+    .bind(payload.fundal_height_cm)
+    .bind(payload.fetal_heart_rate)
+    .bind(&payload.urine_protein)
+    .bind(&payload.urine_glucose)
+    .bind(&payload.fetal_presentation)
+    .bind(&checks_done)
+    .bind(&payload.notes)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            ServiceError::Conflict("That contact is already recorded for this pregnancy".to_string())
+        }
+        _ => db_error(e),
+    })?;
+
+    let (risks, urgent) = contact_risks(&contact);
+    let new_risks: Vec<&str> = risks
+        .iter()
+        .map(|r| r.as_str())
+        .filter(|r| !pregnancy.risk_factors.iter().any(|existing| existing == r))
+        .collect();
+    let mut pregnancy = pregnancy;
+    if !new_risks.is_empty() {
+        pregnancy = sqlx::query_as::<_, Pregnancy>(
+            "UPDATE pregnancies SET risk_factors = risk_factors || $2, updated_by = $3, updated_at = now()
+             WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(&new_risks)
+        .bind(user.id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    }
+    if urgent || !new_risks.is_empty() {
+        let flagged: Vec<&str> = risks.iter().map(|r| r.as_str()).collect();
+        let message = format!(
+            "ANC contact on {} at {} weeks: {}",
+            contact_date,
+            age_days / 7,
+            flagged.join(", ").replace('_', " ")
+        );
+        notify_care_team(&mut tx, AlertEntry {
+            patient_id: pregnancy.patient_id,
+            vital_set_id: None,
+            kind: "anc_risk",
+            severity: if urgent { "high" } else { "medium" },
+            message: &message,
+        })
+        .await
+        .map_err(db_error)?;
+    }
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "anc_contact.recorded",
+        entity_type: "anc_contact",
+        entity_id: contact.id,
+        patient_id: Some(pregnancy.patient_id),
+        details: serde_json::json!({
+            "pregnancy_id": id,
+            "contact_number": contact.contact_number,
+            "contact_date": contact.contact_date,
+            "new_risk_factors": new_risks,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    let detail = pregnancy_detail(&mut tx, pregnancy).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(detail)))
+}
+
+/// Remove a contact recorded in error. Risk factors it added stay until edited.
+pub async fn delete_anc_contact(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<StatusCode, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let contact = sqlx::query_as::<_, AncContact>(
+        "DELETE FROM anc_contacts c USING pregnancies pr, patients p
+         WHERE c.id = $1 AND pr.id = c.pregnancy_id AND p.id = pr.patient_id AND p.tenant_id = $2
+         RETURNING c.*",
+    )
+    .bind(id)
+    .bind(user.tenant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    let patient_id: i32 = sqlx::query_scalar("SELECT patient_id FROM pregnancies WHERE id = $1")
+        .bind(contact.pregnancy_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "anc_contact.deleted",
+        entity_type: "anc_contact",
+        entity_id: id,
+        patient_id: Some(patient_id),
+        details: serde_json::json!({
+            "pregnancy_id": contact.pregnancy_id,
+            "contact_number": contact.contact_number,
+            "contact_date": contact.contact_date,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Close the pregnancy with its outcome and, for a birth, each baby.
+pub async fn record_pregnancy_outcome(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<RecordOutcomeRequest>,
+) -> Result<Json<PregnancyDetail>, ServiceError> {
+    check_clinical_role(&user)?;
+    let outcome_date = payload.outcome_date.unwrap_or_else(|| Utc::now().date_naive());
+    let mut errors = ValidationErrors::default();
+    check_not_future(&mut errors, "outcome_date", outcome_date);
+    trim_optional(&mut payload.notes);
+    normalize_enum::<PregnancyOutcome>(&mut errors, "outcome", &mut payload.outcome);
+    normalize_optional_enum::<DeliveryMode>(&mut errors, "delivery_mode", &mut payload.delivery_mode);
+    normalize_optional_enum::<DeliveryPlace>(&mut errors, "delivery_place", &mut payload.delivery_place);
+    for (index, birth) in payload.births.iter_mut().enumerate() {
+        let field = |name: &str| format!("births[{}].{}", index, name);
+        normalize_enum::<BirthOutcome>(&mut errors, &field("outcome"), &mut birth.outcome);
+        normalize_optional_enum::<Gender>(&mut errors, &field("sex"), &mut birth.sex);
+        if birth.birth_weight_grams.is_some_and(|grams| !(300..=7000).contains(&grams)) {
+            errors.add(field("birth_weight_grams"), "must be between 300 and 7000");
+        }
+    }
+    if let Some(outcome) = PregnancyOutcome::parse(&payload.outcome) {
+        let live_births = payload.births.iter().filter(|b| b.outcome == BirthOutcome::LiveBirth.as_str()).count();
+        if outcome.is_delivery() {
+            if payload.births.is_empty() {
+                errors.add("births", "must list each baby for a live birth or stillbirth");
+            } else if outcome == PregnancyOutcome::LiveBirth && live_births == 0 {
+                errors.add("births", "must include a live birth");
+            } else if outcome == PregnancyOutcome::Stillbirth && live_births > 0 {
+                errors.add("outcome", "must be live_birth when any baby was born alive");
+            }
+        } else {
+            if !payload.births.is_empty() {
+                errors.add("births", "are only recorded for a live birth or stillbirth");
+            }
+            if payload.delivery_mode.is_some() {
+                errors.add("delivery_mode", "is only recorded for a live birth or stillbirth");
+            }
+        }
+    }
+    errors.into_result()?;
+    let outcome = PregnancyOutcome::parse(&payload.outcome).ok_or(ServiceError::InternalServerError)?;
+    let status = if outcome.is_delivery() { PregnancyStatus::Delivered } else { PregnancyStatus::Ended };
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let current = lock_pregnancy(&mut tx, user.tenant_id, id).await?;
+    ensure_active(&current)?;
+    if outcome_date < gestation_start(current.edd) {
+        return Err(field_error("outcome_date", "is before the start of this pregnancy"));
+    }
+    let pregnancy = sqlx::query_as::<_, Pregnancy>(
+        "UPDATE pregnancies SET status = $2, outcome = $3, outcome_date = $4, delivery_mode = $5, delivery_place = $6,
+             maternal_death = $7, outcome_notes = $8, outcome_recorded_by = $9, updated_by = $9, updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(status.as_str())
+    .bind(&payload.outcome)
+    .bind(outcome_date)
+    .bind(&payload.delivery_mode)
+    .bind(&payload.delivery_place)
+    .bind(payload.maternal_death)
+    .bind(&payload.notes)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    let mut newborn_ids = Vec::new();
+    for (index, birth) in payload.births.iter().enumerate() {
+        let newborn_id = if payload.register_newborns && birth.outcome == BirthOutcome::LiveBirth.as_str() {
+            let newborn_id: i32 = sqlx::query_scalar(
+                "INSERT INTO patients (first_name, last_name, date_of_birth, gender, phone_number, address, village,
+                     village_id, household_id, status, additional_notes, tenant_id)
+                 SELECT 'Baby', last_name, $2, COALESCE($3, 'unknown'), phone_number, address, village, village_id,
+                     household_id, 'active', $4, tenant_id
+                 FROM patients WHERE id = $1
+                 RETURNING id",
+            )
+            .bind(pregnancy.patient_id)
+            .bind(outcome_date)
+            .bind(&birth.sex)
+            .bind(format!("Registered at birth from pregnancy #{}", id))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(db_error)?;
+            newborn_ids.push(newborn_id);
+            Some(newborn_id)
+        } else {
+            None
+        };
+        sqlx::query(
+            "INSERT INTO pregnancy_births (pregnancy_id, birth_order, outcome, sex, birth_weight_grams, newborn_patient_id)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(id)
+        .bind(index as i32 + 1)
+        .bind(&birth.outcome)
+        .bind(&birth.sex)
+        .bind(birth.birth_weight_grams)
+        .bind(newborn_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    }
+    if payload.maternal_death {
+        let previous: String = sqlx::query_scalar("SELECT status FROM patients WHERE id = $1 FOR UPDATE")
+            .bind(pregnancy.patient_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        if previous != "deceased" {
+            sqlx::query("UPDATE patients SET status = 'deceased', updated_at = now() WHERE id = $1")
+                .bind(pregnancy.patient_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+            audit::record(&mut *tx, AuditEntry {
+                user_id: Some(user.id),
+                action: "patient.status_changed",
+                entity_type: "patient",
+                entity_id: pregnancy.patient_id,
+                patient_id: Some(pregnancy.patient_id),
+                details: serde_json::json!({ "from": previous, "to": "deceased" }),
+            })
+            .await
+            .map_err(db_error)?;
+        }
+    }
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "pregnancy.outcome_recorded",
+        entity_type: "pregnancy",
+        entity_id: id,
+        patient_id: Some(pregnancy.patient_id),
+        details: serde_json::json!({
+            "outcome": pregnancy.outcome,
+            "outcome_date": pregnancy.outcome_date,
+            "delivery_mode": pregnancy.delivery_mode,
+            "delivery_place": pregnancy.delivery_place,
+            "maternal_death": pregnancy.maternal_death,
+            "births": payload.births.len(),
+            "newborn_patient_ids": newborn_ids,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    let detail = pregnancy_detail(&mut tx, pregnancy).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(detail))
+}
+
+/// Women with an ANC contact due or overdue, or past their EDD with no outcome recorded,
+/// by village and most overdue first, for outreach follow-up.
+pub async fn list_anc_due(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<AncDueQuery>,
+) -> Result<Json<Vec<AncDueEntry>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let schedule = load_anc_schedule(&mut *tx).await?;
+    let mothers = sqlx::query_as::<_, MotherRow>(
+        "SELECT pr.*, p.first_name || ' ' || p.last_name AS patient_name, p.village, p.household_id, p.phone_number
+         FROM pregnancies pr
+         JOIN patients p ON p.id = pr.patient_id
+         WHERE pr.status = 'active' AND p.status = 'active' AND p.tenant_id = $2
+           AND ($1::text IS NULL OR LOWER(p.village) = LOWER($1))
+         ORDER BY p.village, pr.edd, pr.id",
+    )
+    .bind(params.village.as_deref().map(str::trim).filter(|v| !v.is_empty()))
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    let ids: Vec<i32> = mothers.iter().map(|m| m.pregnancy.id).collect();
+    let visits = sqlx::query_as::<_, AncContact>("SELECT * FROM anc_contacts WHERE pregnancy_id = ANY($1)")
+        .bind(&ids)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    let today = Utc::now().date_naive();
+    let mut entries: Vec<AncDueEntry> = mothers
+        .into_iter()
+        .filter_map(|mother| {
+            let pregnancy = mother.pregnancy;
+            let held: Vec<AncContact> = visits.iter().filter(|v| v.pregnancy_id == pregnancy.id).cloned().collect();
+            let states = evaluate_contacts(pregnancy.edd, pregnancy.booked_on, None, today, &schedule, &held);
+            let missed_contacts = states.iter().filter(|s| s.status == AncContactStatus::Missed).count();
+            let (overdue, due): (Vec<AncContactState>, Vec<AncContactState>) = states
+                .into_iter()
+                .filter(|s| matches!(s.status, AncContactStatus::Overdue | AncContactStatus::Due))
+                .partition(|s| s.status == AncContactStatus::Overdue);
+            let past_edd = today > pregnancy.edd;
+            if overdue.is_empty() && due.is_empty() && !past_edd {
+                return None;
+            }
+            Some(AncDueEntry {
+                pregnancy_id: pregnancy.id,
+                patient_id: pregnancy.patient_id,
+                patient_name: mother.patient_name,
+                village: mother.village,
+                household_id: mother.household_id,
+                phone_number: mother.phone_number,
+                edd: pregnancy.edd,
+                gestational_age: gestational_age(pregnancy.edd, today),
+                high_risk: !pregnancy.risk_factors.is_empty(),
+                risk_factors: pregnancy.risk_factors,
+                last_contact_date: held.iter().map(|v| v.contact_date).max(),
+                oldest_overdue_date: overdue
+                    .iter()
+                    .map(|s| s.overdue_date)
+                    .chain(past_edd.then_some(pregnancy.edd + Duration::days(1)))
+                    .min(),
+                past_edd,
+                missed_contacts,
+                overdue,
+                due,
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        (&a.village, a.oldest_overdue_date.is_none(), a.oldest_overdue_date, !a.high_risk, a.pregnancy_id).cmp(&(
+            &b.village,
+            b.oldest_overdue_date.is_none(),
+            b.oldest_overdue_date,
+            !b.high_risk,
+            b.pregnancy_id,
+        ))
+    });
+    entries.truncate(params.limit.unwrap_or(DEFAULT_DUE_LIMIT).clamp(1, MAX_DUE_LIMIT));
+    Ok(Json(entries))
+}
@@ -0,0 +1,402 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::handlers::records_handler::authorize_record;
+use crate::models::error::ServiceError;
+use crate::models::prescription::{
+    AllergenClass, Drug, Frequency, MedicationWarning, Prescription, PrescriptionStatus, Route, WarningSeverity,
+};
+use crate::services::access::{check_clinical_role, RecordAction};
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::prescribing::{allergy_terms, check_allergies, find_drug};
+use crate::services::tenancy;
+use crate::services::validation::{
+    field_error, like_literal, normalize_enum, normalize_optional_enum, normalize_term, require_text, trim_optional,
+    TextEnum, ValidationErrors,
+};
+
+const MAX_DURATION_DAYS: i32 = 3650;
+const MAX_DRUG_MATCHES: i64 = 20;
+
+#[derive(Deserialize)]
+pub struct CreatePrescriptionRequest {
+    /// A name or synonym from the local drug table, or free text for unlisted drugs.
+    pub drug: String,
+    pub dose: String,
+    /// Defaults to the drug's usual route when it is in the local table.
+    pub route: Option<String>,
+    pub frequency: String,
+    /// Omit for long-term medication.
+    pub duration_days: Option<i32>,
+    /// Defaults to today.
+    pub start_date: Option<NaiveDate>,
+    pub instructions: Option<String>,
+    pub record_id: Option<i32>,
+    /// Required to proceed past overridable warnings.
+    pub override_reason: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct StopPrescriptionRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PrescriptionQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DrugSearchQuery {
+    pub q: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PrescriptionCheck {
+    pub drug: Option<String>,
+    pub warnings: Vec<MedicationWarning>,
+}
+
+#[derive(Serialize)]
+pub struct CurrentMedication {
+    #[serde(flatten)]
+    pub prescription: Prescription,
+    /// Days left in the course including today; `None` for long-term medication.
+    pub days_remaining: Option<i64>,
+}
+
+/// A validated prescription with the result of its safety checks.
+struct Evaluated {
+    drug: Option<Drug>,
+    drug_name: String,
+    route: String,
+    start_date: NaiveDate,
+    warnings: Vec<MedicationWarning>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[PRESCRIPTIONS ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+async fn ensure_patient(tx: &mut Transaction<'_, Postgres>, tenant_id: i32, id: i32) -> Result<(), ServiceError> {
+    let exists: Option<i32> = sqlx::query_scalar("SELECT id FROM patients WHERE id = $1 AND tenant_id = $2")
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    exists.map(|_| ()).ok_or(ServiceError::NotFound)
+}
+
+/// Validate a prescription and run the allergy and duplicate-therapy checks against the
+/// patient's current state.
+async fn evaluate(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: i32,
+    patient_id: i32,
+    payload: &mut CreatePrescriptionRequest,
+) -> Result<Evaluated, ServiceError> {
+    let mut errors = ValidationErrors::default();
+    require_text(&mut errors, "drug", &mut payload.drug);
+    require_text(&mut errors, "dose", &mut payload.dose);
+    normalize_optional_enum::<Route>(&mut errors, "route", &mut payload.route);
+    normalize_enum::<Frequency>(&mut errors, "frequency", &mut payload.frequency);
+    trim_optional(&mut payload.instructions);
+    trim_optional(&mut payload.override_reason);
+    if payload.duration_days.is_some_and(|days| !(1..=MAX_DURATION_DAYS).contains(&days)) {
+        errors.add("duration_days", format!("must be between 1 and {}", MAX_DURATION_DAYS));
+    }
+    let today = Utc::now().date_naive();
+    let start_date = payload.start_date.unwrap_or(today);
+    if start_date > today + Duration::days(365) {
+        errors.add("start_date", "cannot be more than a year ahead");
+    }
+    errors.into_result()?;
+
+    let allergies: Option<Vec<String>> =
+        sqlx::query_scalar("SELECT known_allergies FROM patients WHERE id = $1 AND tenant_id = $2")
+            .bind(patient_id)
+            .bind(tenant_id)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(db_error)?
+            .ok_or(ServiceError::NotFound)?;
+    let allergies = allergies.unwrap_or_default();
+
+    let mut terms = allergy_terms(&allergies);
+    terms.push(normalize_term(&payload.drug));
+    let drugs = sqlx::query_as::<_, Drug>("SELECT * FROM drugs WHERE name = ANY($1) OR synonyms && $1")
+        .bind(&terms)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    let classes = sqlx::query_as::<_, AllergenClass>("SELECT * FROM allergen_classes")
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(db_error)?;
+
+    let drug = find_drug(&drugs, &payload.drug).cloned();
+    let drug_name = drug.as_ref().map(|d| d.name.clone()).unwrap_or_else(|| payload.drug.clone());
+    let Some(route) = payload.route.clone().or_else(|| drug.as_ref().and_then(|d| d.default_route.clone())) else {
+        return Err(field_error("route", format!("is required; must be one of: {}", Route::allowed())));
+    };
+
+    let mut warnings = check_allergies(drug.as_ref(), &payload.drug, &allergies, &drugs, &classes);
+    let duplicate: Option<i32> = sqlx::query_scalar(
+        "SELECT id FROM prescriptions
+         WHERE patient_id = $1 AND status = 'active' AND (end_date IS NULL OR end_date >= $3)
+           AND lower(drug_name) = lower($2)
+         LIMIT 1",
+    )
+    .bind(patient_id)
+    .bind(&drug_name)
+    .bind(start_date)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    if let Some(existing) = duplicate {
+        warnings.push(MedicationWarning {
+            severity: WarningSeverity::Overridable,
+            code: "duplicate_therapy".to_string(),
+            allergy: None,
+            message: format!("Patient already has an active prescription for {} (#{})", drug_name, existing),
+        });
+    }
+    Ok(Evaluated { drug, drug_name, route, start_date, warnings })
+}
+
+/// Run the prescribing checks without issuing anything.
+pub async fn check_prescription(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<CreatePrescriptionRequest>,
+) -> Result<Json<PrescriptionCheck>, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let evaluated = evaluate(&mut tx, user.tenant_id, patient_id, &mut payload).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(PrescriptionCheck { drug: evaluated.drug.map(|d| d.name), warnings: evaluated.warnings }))
+}
+
+/// Issue a prescription. Blocking warnings always refuse it with `409`; overridable ones
+/// refuse it unless an `override_reason` is given, which is stored with the warnings.
+pub async fn create_prescription(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<CreatePrescriptionRequest>,
+) -> Result<(StatusCode, Json<Prescription>), ServiceError> {
+    check_clinical_role(&user)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let evaluated = evaluate(&mut tx, user.tenant_id, patient_id, &mut payload).await?;
+    if let Some(record_id) = payload.record_id {
+        let record = authorize_record(&mut *tx, record_id, &user, RecordAction::Read).await?;
+        if record.patient_id != patient_id {
+            return Err(field_error("record_id", "belongs to a different patient"));
+        }
+    }
+    let blocked = evaluated.warnings.iter().any(|w| w.severity == WarningSeverity::Blocking);
+    if blocked || (!evaluated.warnings.is_empty() && payload.override_reason.is_none()) {
+        return Err(ServiceError::MedicationWarnings(evaluated.warnings));
+    }
+    let (overridden, override_reason) = if evaluated.warnings.is_empty() {
+        (None, None)
+    } else {
+        (serde_json::to_value(&evaluated.warnings).ok(), payload.override_reason.clone())
+    };
+    let end_date = payload.duration_days.map(|days| evaluated.start_date + Duration::days(i64::from(days) - 1));
+
+    let prescription = sqlx::query_as::<_, Prescription>(
+        "INSERT INTO prescriptions (patient_id, record_id, drug, drug_name, dose, route, frequency, duration_days,
+             start_date, end_date, instructions, prescribed_by, overridden_warnings, override_reason)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+         RETURNING *",
+    )
+    .bind(patient_id)
+    .bind(payload.record_id)
+    .bind(evaluated.drug.as_ref().map(|d| d.name.as_str()))
+    .bind(&evaluated.drug_name)
+    .bind(&payload.dose)
+    .bind(&evaluated.route)
+    .bind(&payload.frequency)
+    .bind(payload.duration_days)
+    .bind(evaluated.start_date)
+    .bind(end_date)
+    .bind(&payload.instructions)
+    .bind(user.id)
+    .bind(&overridden)
+    .bind(&override_reason)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "prescription.created",
+        entity_type: "prescription",
+        entity_id: prescription.id,
+        patient_id: Some(patient_id),
+        details: serde_json::json!({
+            "drug": prescription.drug_name,
+            "dose": prescription.dose,
+            "route": prescription.route,
+            "frequency": prescription.frequency,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    if let Some(warnings) = overridden {
+        audit::record(&mut *tx, AuditEntry {
+            user_id: Some(user.id),
+            action: "prescription.warnings_overridden",
+            entity_type: "prescription",
+            entity_id: prescription.id,
+            patient_id: Some(patient_id),
+            details: serde_json::json!({ "warnings": warnings, "reason": override_reason }),
+        })
+        .await
+        .map_err(db_error)?;
+    }
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(prescription)))
+}
+
+/// Every prescription for the patient, newest first.
+pub async fn list_prescriptions(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Query(mut params): Query<PrescriptionQuery>,
+) -> Result<Json<Vec<Prescription>>, ServiceError> {
+    let mut errors = ValidationErrors::default();
+    normalize_optional_enum::<PrescriptionStatus>(&mut errors, "status", &mut params.status);
+    errors.into_result()?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    ensure_patient(&mut tx, user.tenant_id, patient_id).await?;
+    let prescriptions = sqlx::query_as::<_, Prescription>(
+        "SELECT * FROM prescriptions
+         WHERE patient_id = $1 AND ($2::text IS NULL OR status = $2)
+         ORDER BY prescribed_at DESC, id DESC",
+    )
+    .bind(patient_id)
+    .bind(&params.status)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(prescriptions))
+}
+
+/// What the patient should be taking today: active prescriptions that have started and whose
+/// course has not ended.
+pub async fn list_current_medications(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<CurrentMedication>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    ensure_patient(&mut tx, user.tenant_id, patient_id).await?;
+    let today = Utc::now().date_naive();
+    let prescriptions = sqlx::query_as::<_, Prescription>(
+        "SELECT * FROM prescriptions
+         WHERE patient_id = $1 AND status = 'active' AND start_date <= $2
+           AND (end_date IS NULL OR end_date >= $2)
+         ORDER BY drug_name, start_date",
+    )
+    .bind(patient_id)
+    .bind(today)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    let medications = prescriptions
+        .into_iter()
+        .map(|prescription| {
+            let days_remaining = prescription.end_date.map(|end| (end - today).num_days() + 1);
+            CurrentMedication { prescription, days_remaining }
+        })
+        .collect();
+    Ok(Json(medications))
+}
+
+/// Discontinue a prescription early; a `reason` is required.
+pub async fn stop_prescription(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<StopPrescriptionRequest>>,
+) -> Result<Json<Prescription>, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut payload = payload.map(|Json(p)| p).unwrap_or_default();
+    trim_optional(&mut payload.reason);
+    let Some(reason) = payload.reason else {
+        return Err(field_error("reason", "is required when stopping a prescription"));
+    };
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let current = sqlx::query_as::<_, Prescription>(
+        "SELECT o.* FROM prescriptions o JOIN patients p ON p.id = o.patient_id AND p.tenant_id = $2
+         WHERE o.id = $1 FOR UPDATE OF o",
+    )
+    .bind(id)
+    .bind(user.tenant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    if current.status != PrescriptionStatus::Active.as_str() {
+        return Err(ServiceError::Conflict("Prescription has already been stopped".to_string()));
+    }
+    let prescription = sqlx::query_as::<_, Prescription>(
+        "UPDATE prescriptions SET status = 'stopped', stopped_by = $2, stopped_at = now(), stop_reason = $3
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(user.id)
+    .bind(&reason)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "prescription.stopped",
+        entity_type: "prescription",
+        entity_id: id,
+        patient_id: Some(prescription.patient_id),
+        details: serde_json::json!({ "drug": prescription.drug_name, "reason": reason }),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(prescription))
+}
+
+/// Search the local drug table by name or synonym prefix, for autocomplete.
+pub async fn search_drugs(
+    _user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<DrugSearchQuery>,
+) -> Result<Json<Vec<Drug>>, ServiceError> {
+    let prefix = params.q.as_deref().map(normalize_term).unwrap_or_default();
+    let pattern = format!("{}%", like_literal(&prefix));
+    let drugs = sqlx::query_as::<_, Drug>(
+        "SELECT * FROM drugs
+         WHERE name LIKE $1 OR EXISTS (SELECT 1 FROM unnest(synonyms) s WHERE s LIKE $1)
+         ORDER BY name LIKE $1 DESC, name
+         LIMIT $2",
+    )
+    .bind(&pattern)
+    .bind(MAX_DRUG_MATCHES)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+    Ok(Json(drugs))
+}
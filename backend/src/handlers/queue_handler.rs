@@ -0,0 +1,577 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use crate::models::appointment::{Appointment, AppointmentStatus};
+use crate::models::error::ServiceError;
+use crate::models::queue::{QueueAction, QueueEntry, QueueStatus, TriagePriority, WaitTimeStats};
+use crate::services::access::check_clinical_role;
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::facilities::{facility_scope, require_facility};
+use crate::services::queue::wait_time_stats;
+use crate::services::scheduling::sync_next_visit;
+use crate::services::tenancy;
+use crate::services::validation::{field_error, normalize_enum, trim_optional, TextEnum, ValidationErrors};
+
+/// Wait-time reports cover the last week unless asked otherwise.
+const DEFAULT_REPORT_DAYS: i64 = 7;
+const MAX_REPORT_DAYS: i64 = 366;
+
+#[derive(Deserialize)]
+pub struct ArrivalRequest {
+    pub patient_id: i32,
+    /// Defaults to the appointment's facility, then the registering user's primary facility.
+    pub facility_id: Option<i32>,
+    /// The appointment the patient came for, which is checked in.
+    pub appointment_id: Option<i32>,
+    /// Defaults to now.
+    pub arrived_at: Option<NaiveDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct TriageRequest {
+    pub priority: String,
+    pub notes: Option<String>,
+    /// Defaults to now.
+    pub at: Option<NaiveDateTime>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct QueueStepRequest {
+    /// Defaults to now.
+    pub at: Option<NaiveDateTime>,
+    /// For leaving: why the patient left.
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct QueueQuery {
+    pub facility_id: Option<i32>,
+    /// Only the queues at the caller's facilities.
+    pub my_facilities: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct WaitTimeQuery {
+    pub facility_id: Option<i32>,
+    pub my_facilities: Option<bool>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Serialize, FromRow)]
+pub struct QueueEntrySummary {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub entry: QueueEntry,
+    pub patient_name: String,
+    pub facility_name: String,
+    /// Minutes since arrival, or until the consultation started.
+    pub waited_minutes: f64,
+}
+
+#[derive(Serialize)]
+pub struct FacilityQueue {
+    pub facility_id: i32,
+    pub facility_name: String,
+    /// Next to be called first: by triage priority, then arrival. Untriaged patients rank
+    /// as routine.
+    pub waiting: Vec<QueueEntrySummary>,
+    pub in_consultation: Vec<QueueEntrySummary>,
+    pub untriaged: usize,
+    pub completed_today: i64,
+    pub left_today: i64,
+    /// Today's figures so far.
+    pub wait_times: Option<WaitTimeStats>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[QUEUE ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+const SUMMARY_SELECT: &str = "SELECT q.*, p.first_name || ' ' || p.last_name AS patient_name, f.name AS facility_name,
+            round((EXTRACT(EPOCH FROM COALESCE(q.consultation_started_at, now()::timestamp) - q.arrived_at) / 60)::numeric, 1)::float8
+                AS waited_minutes
+     FROM queue_entries q
+     JOIN patients p ON p.id = q.patient_id
+     JOIN facilities f ON f.id = q.facility_id";
+
+async fn lock_entry(tx: &mut Transaction<'_, Postgres>, tenant_id: i32, id: i32) -> Result<QueueEntry, ServiceError> {
+    sqlx::query_as::<_, QueueEntry>(
+        "SELECT q.* FROM queue_entries q JOIN patients p ON p.id = q.patient_id AND p.tenant_id = $2
+         WHERE q.id = $1 FOR UPDATE OF q",
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)
+}
+
+async fn load_summary(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: i32,
+    id: i32,
+) -> Result<QueueEntrySummary, ServiceError> {
+    sqlx::query_as::<_, QueueEntrySummary>(&format!("{SUMMARY_SELECT} WHERE q.id = $1 AND p.tenant_id = $2"))
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?
+        .ok_or(ServiceError::NotFound)
+}
+
+fn transition_error(action: QueueAction, entry: &QueueEntry) -> ServiceError {
+    ServiceError::Conflict(format!(
+        "A patient who is {} cannot be {}",
+        entry.status.replace('_', " "),
+        action.past_tense()
+    ))
+}
+
+/// When a step happened: now unless given, never in the future and never before the step
+/// before it.
+fn step_time(at: Option<NaiveDateTime>, previous: NaiveDateTime, field: &str) -> Result<NaiveDateTime, ServiceError> {
+    let now = Utc::now().naive_utc();
+    let at = at.unwrap_or(now);
+    if at > now + Duration::minutes(5) {
+        return Err(field_error(field, "cannot be in the future"));
+    }
+    if at < previous {
+        return Err(field_error(field, "cannot be before the previous step"));
+    }
+    Ok(at)
+}
+
+/// Register a patient's arrival, as a walk-in or for an appointment, which is checked in.
+pub async fn register_arrival(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<ArrivalRequest>,
+) -> Result<(StatusCode, Json<QueueEntrySummary>), ServiceError> {
+    let now = Utc::now().naive_utc();
+    let arrived_at = payload.arrived_at.unwrap_or(now);
+    if arrived_at > now + Duration::minutes(5) {
+        return Err(field_error("arrived_at", "cannot be in the future"));
+    }
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let exists: Option<i32> = sqlx::query_scalar("SELECT id FROM patients WHERE id = $1 AND tenant_id = $2 FOR UPDATE")
+        .bind(payload.patient_id)
+        .bind(user.tenant_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    if exists.is_none() {
+        return Err(field_error("patient_id", "does not refer to an existing patient"));
+    }
+    let open: Option<(i32, String)> = sqlx::query_as(
+        "SELECT q.id, f.name FROM queue_entries q JOIN facilities f ON f.id = q.facility_id
+         WHERE q.patient_id = $1 AND q.status = ANY($2)",
+    )
+    .bind(payload.patient_id)
+    .bind(QueueStatus::OPEN)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    if let Some((id, facility)) = open {
+        return Err(ServiceError::Conflict(format!("The patient is already in the queue at {} (#{})", facility, id)));
+    }
+
+    let mut facility_id = payload.facility_id;
+    if let Some(appointment_id) = payload.appointment_id {
+        let appointment = sqlx::query_as::<_, Appointment>(
+            "SELECT a.* FROM appointments a JOIN patients p ON p.id = a.patient_id AND p.tenant_id = $2
+             WHERE a.id = $1 FOR UPDATE OF a",
+        )
+        .bind(appointment_id)
+        .bind(user.tenant_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| field_error("appointment_id", "does not refer to an existing appointment"))?;
+        if appointment.patient_id != payload.patient_id {
+            return Err(field_error("appointment_id", "belongs to a different patient"));
+        }
+        if appointment.starts_at.date() != arrived_at.date() {
+            return Err(field_error("appointment_id", "is not on the day of arrival"));
+        }
+        match AppointmentStatus::parse(&appointment.status) {
+            Some(AppointmentStatus::Booked) => {
+                sqlx::query(
+                    "UPDATE appointments SET status = 'checked_in', checked_in_at = $2, updated_at = now() WHERE id = $1",
+                )
+                .bind(appointment_id)
+                .bind(arrived_at)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+                sync_next_visit(&mut *tx, appointment.patient_id).await.map_err(db_error)?;
+            }
+            Some(AppointmentStatus::CheckedIn) => {}
+            _ => {
+                return Err(field_error(
+                    "appointment_id",
+                    format!("is {} and cannot be checked in", appointment.status.replace('_', " ")),
+                ))
+            }
+        }
+        if facility_id.is_none() {
+            facility_id = appointment.facility_id;
+        }
+    }
+    let facility = require_facility(&mut *tx, "facility_id", facility_id, user.id).await?;
+
+    let entry = sqlx::query_as::<_, QueueEntry>(
+        "INSERT INTO queue_entries (patient_id, appointment_id, facility_id, arrived_at, registered_by)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING *",
+    )
+    .bind(payload.patient_id)
+    .bind(payload.appointment_id)
+    .bind(facility.id)
+    .bind(arrived_at)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "queue.arrived",
+        entity_type: "queue_entry",
+        entity_id: entry.id,
+        patient_id: Some(entry.patient_id),
+        details: serde_json::json!({
+            "facility_id": entry.facility_id,
+            "appointment_id": entry.appointment_id,
+            "arrived_at": entry.arrived_at,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    let summary = load_summary(&mut tx, user.tenant_id, entry.id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(summary)))
+}
+
+/// Triage a waiting patient, setting their place in the queue. Clinicians and admins triage.
+pub async fn triage_patient(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<TriageRequest>,
+) -> Result<Json<QueueEntrySummary>, ServiceError> {
+    check_clinical_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    normalize_enum::<TriagePriority>(&mut errors, "priority", &mut payload.priority);
+    trim_optional(&mut payload.notes);
+    errors.into_result()?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let before = lock_entry(&mut tx, user.tenant_id, id).await?;
+    let to = apply(QueueAction::Triage, &before)?;
+    let at = step_time(payload.at, before.arrived_at, "at")?;
+    let entry = sqlx::query_as::<_, QueueEntry>(
+        "UPDATE queue_entries SET status = $2, priority = $3, triage_notes = COALESCE($4, triage_notes),
+             triaged_at = COALESCE(triaged_at, $5), triaged_by = $6, updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(to.as_str())
+    .bind(&payload.priority)
+    .bind(&payload.notes)
+    .bind(at)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "queue.triaged",
+        entity_type: "queue_entry",
+        entity_id: id,
+        patient_id: Some(entry.patient_id),
+        details: serde_json::json!({ "from": before.priority, "to": entry.priority }),
+    })
+    .await
+    .map_err(db_error)?;
+    let summary = load_summary(&mut tx, user.tenant_id, id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(summary))
+}
+
+fn apply(action: QueueAction, entry: &QueueEntry) -> Result<QueueStatus, ServiceError> {
+    let from = QueueStatus::parse(&entry.status).ok_or(ServiceError::InternalServerError)?;
+    action.apply(from).ok_or_else(|| transition_error(action, entry))
+}
+
+/// Call the patient in; the caller becomes the provider seeing them.
+pub async fn start_consultation(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<QueueStepRequest>>,
+) -> Result<Json<QueueEntrySummary>, ServiceError> {
+    check_clinical_role(&user)?;
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let before = lock_entry(&mut tx, user.tenant_id, id).await?;
+    let to = apply(QueueAction::Start, &before)?;
+    let at = step_time(payload.at, before.triaged_at.unwrap_or(before.arrived_at), "at")?;
+    let entry = sqlx::query_as::<_, QueueEntry>(
+        "UPDATE queue_entries SET status = $2, consultation_started_at = $3, provider_id = $4, updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(to.as_str())
+    .bind(at)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "queue.consultation_started",
+        entity_type: "queue_entry",
+        entity_id: id,
+        patient_id: Some(entry.patient_id),
+        details: serde_json::json!({ "started_at": at }),
+    })
+    .await
+    .map_err(db_error)?;
+    let summary = load_summary(&mut tx, user.tenant_id, id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(summary))
+}
+
+/// End the consultation; an appointment the patient came for is marked as seen.
+pub async fn finish_consultation(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<QueueStepRequest>>,
+) -> Result<Json<QueueEntrySummary>, ServiceError> {
+    check_clinical_role(&user)?;
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let before = lock_entry(&mut tx, user.tenant_id, id).await?;
+    let to = apply(QueueAction::Finish, &before)?;
+    let started_at = before.consultation_started_at.unwrap_or(before.arrived_at);
+    let at = step_time(payload.at, started_at, "at")?;
+    let entry = sqlx::query_as::<_, QueueEntry>(
+        "UPDATE queue_entries SET status = $2, consultation_ended_at = $3, updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(to.as_str())
+    .bind(at)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    if let Some(appointment_id) = entry.appointment_id {
+        sqlx::query(
+            "UPDATE appointments SET status = 'seen', seen_at = $2, updated_at = now()
+             WHERE id = $1 AND status IN ('booked', 'checked_in')",
+        )
+        .bind(appointment_id)
+        .bind(at)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        sync_next_visit(&mut *tx, entry.patient_id).await.map_err(db_error)?;
+    }
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "queue.consultation_finished",
+        entity_type: "queue_entry",
+        entity_id: id,
+        patient_id: Some(entry.patient_id),
+        details: serde_json::json!({ "ended_at": at }),
+    })
+    .await
+    .map_err(db_error)?;
+    let summary = load_summary(&mut tx, user.tenant_id, id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(summary))
+}
+
+/// Record that a patient left before being seen.
+pub async fn leave_queue(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<QueueStepRequest>>,
+) -> Result<Json<QueueEntrySummary>, ServiceError> {
+    let mut payload = payload.map(|Json(p)| p).unwrap_or_default();
+    trim_optional(&mut payload.reason);
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let before = lock_entry(&mut tx, user.tenant_id, id).await?;
+    let to = apply(QueueAction::Leave, &before)?;
+    let at = step_time(payload.at, before.triaged_at.unwrap_or(before.arrived_at), "at")?;
+    let entry = sqlx::query_as::<_, QueueEntry>(
+        "UPDATE queue_entries SET status = $2, left_at = $3, left_reason = $4, updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(to.as_str())
+    .bind(at)
+    .bind(&payload.reason)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "queue.left",
+        entity_type: "queue_entry",
+        entity_id: id,
+        patient_id: Some(entry.patient_id),
+        details: serde_json::json!({ "left_at": at, "reason": payload.reason }),
+    })
+    .await
+    .map_err(db_error)?;
+    let summary = load_summary(&mut tx, user.tenant_id, id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(summary))
+}
+
+/// Who is waiting and who is being seen, per facility, with today's wait times so far.
+pub async fn get_queue(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<QueueQuery>,
+) -> Result<Json<Vec<FacilityQueue>>, ServiceError> {
+    let facility_ids = facility_scope(&pool, user.id, params.facility_id, params.my_facilities)
+        .await
+        .map_err(db_error)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let entries = sqlx::query_as::<_, QueueEntrySummary>(&format!(
+        "{SUMMARY_SELECT}
+         WHERE q.status = ANY($1) AND ($2::int[] IS NULL OR q.facility_id = ANY($2)) AND p.tenant_id = $3
+         ORDER BY q.facility_id,
+                  CASE q.priority WHEN 'emergency' THEN 0 WHEN 'urgent' THEN 1 ELSE 2 END,
+                  q.arrived_at, q.id"
+    ))
+    .bind(QueueStatus::OPEN)
+    .bind(&facility_ids)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    let today = Utc::now().date_naive();
+    let closed: Vec<(i32, String, i64, i64)> = sqlx::query_as(
+        "SELECT q.facility_id, f.name,
+                COUNT(*) FILTER (WHERE q.status = 'completed'), COUNT(*) FILTER (WHERE q.status = 'left')
+         FROM queue_entries q
+         JOIN facilities f ON f.id = q.facility_id
+         JOIN patients p ON p.id = q.patient_id AND p.tenant_id = $3
+         WHERE q.arrived_at >= $1 AND q.arrived_at < $1 + 1 AND ($2::int[] IS NULL OR q.facility_id = ANY($2))
+         GROUP BY q.facility_id, f.name",
+    )
+    .bind(today)
+    .bind(&facility_ids)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    let stats = wait_time_stats(&mut *tx, facility_ids.as_deref(), user.tenant_id, today, today)
+        .await
+        .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    let mut facilities: Vec<FacilityQueue> = closed
+        .into_iter()
+        .map(|(facility_id, facility_name, completed_today, left_today)| FacilityQueue {
+            facility_id,
+            facility_name,
+            waiting: Vec::new(),
+            in_consultation: Vec::new(),
+            untriaged: 0,
+            completed_today,
+            left_today,
+            wait_times: None,
+        })
+        .collect();
+    for summary in entries {
+        let index = match facilities.iter().position(|f| f.facility_id == summary.entry.facility_id) {
+            Some(index) => index,
+            None => {
+                facilities.push(FacilityQueue {
+                    facility_id: summary.entry.facility_id,
+                    facility_name: summary.facility_name.clone(),
+                    waiting: Vec::new(),
+                    in_consultation: Vec::new(),
+                    untriaged: 0,
+                    completed_today: 0,
+                    left_today: 0,
+                    wait_times: None,
+                });
+                facilities.len() - 1
+            }
+        };
+        let queue = &mut facilities[index];
+        match QueueStatus::parse(&summary.entry.status) {
+            Some(QueueStatus::InConsultation) => queue.in_consultation.push(summary),
+            Some(QueueStatus::Waiting) => {
+                queue.untriaged += 1;
+                queue.waiting.push(summary);
+            }
+            _ => queue.waiting.push(summary),
+        }
+    }
+    for queue in &mut facilities {
+        queue.wait_times = stats.iter().find(|s| s.facility_id == queue.facility_id).cloned();
+    }
+    facilities.sort_by(|a, b| a.facility_name.cmp(&b.facility_name));
+    Ok(Json(facilities))
+}
+
+/// Average, median and 90th percentile waits per facility and day.
+pub async fn get_wait_times(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<WaitTimeQuery>,
+) -> Result<Json<Vec<WaitTimeStats>>, ServiceError> {
+    let to = params.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = params.from.unwrap_or(to - Duration::days(DEFAULT_REPORT_DAYS - 1));
+    if from > to {
+        return Err(field_error("from", "cannot be after to"));
+    }
+    if (to - from).num_days() >= MAX_REPORT_DAYS {
+        return Err(field_error("from", format!("the range cannot exceed {} days", MAX_REPORT_DAYS)));
+    }
+    let facility_ids = facility_scope(&pool, user.id, params.facility_id, params.my_facilities)
+        .await
+        .map_err(db_error)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let stats = wait_time_stats(&mut *tx, facility_ids.as_deref(), user.tenant_id, from, to)
+        .await
+        .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_happen_now_unless_given_and_in_order() {
+        let now = Utc::now().naive_utc();
+        let arrived = now - Duration::hours(2);
+        let taken = step_time(None, arrived, "triaged_at").unwrap();
+        assert!(taken >= now && taken - now < Duration::minutes(1));
+        assert_eq!(step_time(Some(arrived), arrived, "triaged_at").unwrap(), arrived);
+        assert!(step_time(Some(now + Duration::minutes(4)), arrived, "triaged_at").is_ok());
+        assert!(step_time(Some(now + Duration::minutes(10)), arrived, "triaged_at").is_err());
+        assert!(step_time(Some(arrived - Duration::minutes(1)), arrived, "triaged_at").is_err());
+    }
+}
@@ -0,0 +1,194 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::handlers::records_handler::{authorize_record, lock_record};
+use crate::models::error::ServiceError;
+use crate::models::record::{MedicalRecord, RecordStatus, WorkflowAction};
+use crate::services::access::{check_sign_off_role, RecordAction};
+use crate::services::auth::AuthUser;
+use crate::services::tenancy;
+use crate::services::validation::{field_error, normalize_optional_enum, trim_optional, TextEnum, ValidationErrors};
+use crate::services::versions::{record_version, VersionEntry, VersionKind};
+
+#[derive(Deserialize, Default)]
+pub struct TransitionRequest {
+    /// Comment kept in the record's history; required when rejecting.
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct WorklistQuery {
+    /// `submitted` (awaiting review, the default) or `reviewed` (awaiting signature).
+    pub status: Option<String>,
+}
+
+#[derive(Serialize, FromRow)]
+pub struct WorklistEntry {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub record: MedicalRecord,
+    pub patient_name: String,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[RECORD WORKFLOW ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+/// Move a record one step through draft -> submitted -> reviewed -> signed, or reject it
+/// back to draft. Authors submit; only clinicians and admins review, sign and reject, and
+/// nobody reviews a record they submitted themselves.
+async fn transition(
+    pool: &PgPool,
+    user: &AuthUser,
+    id: i32,
+    action: WorkflowAction,
+    mut payload: TransitionRequest,
+) -> Result<MedicalRecord, ServiceError> {
+    let required = match action {
+        WorkflowAction::Submit => RecordAction::Write,
+        _ => RecordAction::SignOff,
+    };
+    trim_optional(&mut payload.note);
+    if action == WorkflowAction::Reject && payload.note.is_none() {
+        return Err(field_error("note", "is required when rejecting a record"));
+    }
+
+    let mut tx = tenancy::begin(pool, user.tenant_id).await.map_err(db_error)?;
+    authorize_record(&mut *tx, id, user, required).await?;
+    let previous = lock_record(&mut tx, user.tenant_id, id).await?;
+    let from = RecordStatus::parse(&previous.status).ok_or(ServiceError::InternalServerError)?;
+    let to = action.apply(from).ok_or_else(|| {
+        ServiceError::Conflict(format!("A {} record cannot be {}", from.as_str(), version_kind(action).as_str()))
+    })?;
+    if action == WorkflowAction::Review && previous.submitted_by == Some(user.id) {
+        return Err(ServiceError::Forbidden);
+    }
+
+    let query = match action {
+        WorkflowAction::Submit => {
+            "UPDATE medical_records SET status = $1, submitted_by = $2, submitted_at = now(),
+                 secondary_status = NULL, reviewed_by = NULL, reviewed_at = NULL, updated_at = now()
+             WHERE id = $4 RETURNING *"
+        }
+        WorkflowAction::Review => {
+            "UPDATE medical_records SET status = $1, reviewed_by = $3, reviewed_at = now(), updated_at = now()
+             WHERE id = $4 RETURNING *"
+        }
+        WorkflowAction::Sign => {
+            "UPDATE medical_records SET status = $1, signed_by = $2, signed_at = now(), updated_at = now()
+             WHERE id = $4 RETURNING *"
+        }
+        WorkflowAction::Reject => {
+            "UPDATE medical_records SET status = $1, secondary_status = 'rejected',
+                 reviewed_by = NULL, reviewed_at = NULL, updated_at = now()
+             WHERE id = $4 RETURNING *"
+        }
+    };
+    let record = sqlx::query_as::<_, MedicalRecord>(query)
+        .bind(to.as_str())
+        .bind(user.id)
+        .bind(&user.email)
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    record_version(&mut *tx, VersionEntry {
+        previous: Some(&previous),
+        record: &record,
+        kind: version_kind(action),
+        reason: payload.note.as_deref(),
+        changed_by: Some(user.id),
+    })
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(record)
+}
+
+fn version_kind(action: WorkflowAction) -> VersionKind {
+    match action {
+        WorkflowAction::Submit => VersionKind::Submitted,
+        WorkflowAction::Review => VersionKind::Reviewed,
+        WorkflowAction::Sign => VersionKind::Signed,
+        WorkflowAction::Reject => VersionKind::Rejected,
+    }
+}
+
+pub async fn submit_record(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<TransitionRequest>>,
+) -> Result<Json<MedicalRecord>, ServiceError> {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    Ok(Json(transition(&pool, &user, id, WorkflowAction::Submit, payload).await?))
+}
+
+pub async fn review_record(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<TransitionRequest>>,
+) -> Result<Json<MedicalRecord>, ServiceError> {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    Ok(Json(transition(&pool, &user, id, WorkflowAction::Review, payload).await?))
+}
+
+pub async fn sign_record(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<TransitionRequest>>,
+) -> Result<Json<MedicalRecord>, ServiceError> {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    Ok(Json(transition(&pool, &user, id, WorkflowAction::Sign, payload).await?))
+}
+
+pub async fn reject_record(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(payload): Json<TransitionRequest>,
+) -> Result<Json<MedicalRecord>, ServiceError> {
+    Ok(Json(transition(&pool, &user, id, WorkflowAction::Reject, payload).await?))
+}
+
+/// Records of the caller's tenant waiting on a reviewer, oldest submission first. Records
+/// the caller submitted are left out of the review queue since they cannot review them.
+pub async fn get_review_worklist(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(mut params): Query<WorklistQuery>,
+) -> Result<Json<Vec<WorklistEntry>>, ServiceError> {
+    check_sign_off_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    normalize_optional_enum::<RecordStatus>(&mut errors, "status", &mut params.status);
+    errors.into_result()?;
+    let status = params.status.as_deref().and_then(RecordStatus::parse).unwrap_or(RecordStatus::Submitted);
+    if !matches!(status, RecordStatus::Submitted | RecordStatus::Reviewed) {
+        return Err(field_error("status", "must be submitted or reviewed"));
+    }
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let entries = sqlx::query_as::<_, WorklistEntry>(
+        r#"SELECT r.*, p.first_name || ' ' || p.last_name AS patient_name
+           FROM medical_records r
+           JOIN patients p ON p.id = r.patient_id
+           WHERE r.status = $1 AND r.tenant_id = $3
+             AND ($1 <> 'submitted' OR r.submitted_by IS DISTINCT FROM $2)
+           ORDER BY r.submitted_at NULLS FIRST, r.id"#
+    )
+    .bind(status.as_str())
+    .bind(user.id)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(entries))
+}
@@ -1,15 +1,29 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
 use chrono::{Utc, NaiveDate};
-use serde::Deserialize;
-use sqlx::PgPool;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{PgPool, Postgres, Transaction};
 
-use crate::models::record::MedicalRecord;
+use crate::models::record::{MedicalRecord, RecordStatus, RecordType};
+use crate::models::record_version::{RecordVersion, RecordVersionSummary};
 use crate::models::error::ServiceError;
+use crate::services::access::{check_record_access, RecordAction};
+use crate::services::audit::diff_fields;
+use crate::services::auth::AuthUser;
+use crate::services::facilities::{facility_scope, optional_facility};
+use crate::services::tenancy;
+use crate::services::versions::{record_version, VersionEntry, VersionKind, UNVERSIONED_FIELDS};
+use crate::services::validation::{
+    check_not_future, field_error, like_literal, normalize_enum, normalize_optional_enum, require_optional_text,
+    require_text, trim_optional, TextEnum, ValidationErrors,
+};
 
+/// A new record. Files are attached through the record's attachment uploads, which keep
+/// `attachments` in step.
 #[derive(Deserialize)]
 pub struct CreateRecordRequest {
     pub patient_id: i32,
@@ -17,12 +31,14 @@ pub struct CreateRecordRequest {
     pub title: String,
     pub provider: String,
     pub date: NaiveDate,
-    pub status: String,
+    /// Where the encounter took place; defaults to the author's primary facility.
+    pub facility_id: Option<i32>,
+    /// New records always start as `draft`; anything else is rejected.
+    pub status: Option<String>,
     pub record_category: Option<String>,
     pub description: Option<String>,
     pub secondary_status: Option<String>,
     pub reviewed_by: Option<String>,
-    pub attachments: Option<Vec<String>>,
     pub is_exported: Option<bool>,
 }
 
@@ -32,56 +48,386 @@ pub struct UpdateRecordRequest {
     pub title: Option<String>,
     pub provider: Option<String>,
     pub date: Option<NaiveDate>,
+    pub facility_id: Option<i32>,
     pub status: Option<String>,
     pub record_category: Option<String>,
     pub description: Option<String>,
     pub secondary_status: Option<String>,
     pub reviewed_by: Option<String>,
-    pub attachments: Option<Vec<String>>,
     pub is_exported: Option<bool>,
+    /// Why the record is being changed; required for amendments.
+    pub reason: Option<String>,
 }
 
-pub async fn list_records(
-    State(pool): State<PgPool>,
-) -> Result<Json<Vec<MedicalRecord>>, ServiceError> {
-    let records = sqlx::query_as!(MedicalRecord,
-        r#"SELECT * FROM medical_records"#
+impl CreateRecordRequest {
+    /// `date_of_birth` is the owning patient's, or `None` when the patient does not exist.
+    fn validate(&mut self, date_of_birth: Option<NaiveDate>) -> Result<(), ServiceError> {
+        let mut errors = ValidationErrors::default();
+        normalize_enum::<RecordType>(&mut errors, "record_type", &mut self.record_type);
+        require_text(&mut errors, "title", &mut self.title);
+        require_text(&mut errors, "provider", &mut self.provider);
+        if let Some(status) = &self.status {
+            if RecordStatus::parse(status) != Some(RecordStatus::Draft) {
+                errors.add("status", "new records start as draft; use the sign-off workflow to advance them");
+            }
+        }
+        check_workflow_fields(&mut errors, None, &self.secondary_status, &self.reviewed_by);
+        trim_optional(&mut self.record_category);
+        match date_of_birth {
+            Some(date_of_birth) => check_record_date(&mut errors, self.date, date_of_birth),
+            None => errors.add("patient_id", "does not refer to an existing patient"),
+        }
+        errors.into_result()
+    }
+}
+
+impl UpdateRecordRequest {
+    fn validate(&mut self, date_of_birth: NaiveDate) -> Result<(), ServiceError> {
+        let mut errors = ValidationErrors::default();
+        normalize_optional_enum::<RecordType>(&mut errors, "record_type", &mut self.record_type);
+        require_optional_text(&mut errors, "title", &mut self.title);
+        require_optional_text(&mut errors, "provider", &mut self.provider);
+        check_workflow_fields(&mut errors, self.status.as_ref(), &self.secondary_status, &self.reviewed_by);
+        trim_optional(&mut self.record_category);
+        trim_optional(&mut self.reason);
+        if let Some(date) = self.date {
+            check_record_date(&mut errors, date, date_of_birth);
+        }
+        errors.into_result()
+    }
+}
+
+/// `status`, `secondary_status` and `reviewed_by` are set by the sign-off workflow, never by clients.
+fn check_workflow_fields(
+    errors: &mut ValidationErrors,
+    status: Option<&String>,
+    secondary_status: &Option<String>,
+    reviewed_by: &Option<String>,
+) {
+    let fields = [("status", status), ("secondary_status", secondary_status.as_ref()), ("reviewed_by", reviewed_by.as_ref())];
+    for (field, value) in fields {
+        if value.is_some() {
+            errors.add(field, "is set by the sign-off workflow");
+        }
+    }
+}
+
+fn check_amendment(payload: &UpdateRecordRequest) -> Result<(), ServiceError> {
+    if payload.reason.is_none() {
+        return Err(field_error("reason", "is required for an amendment"));
+    }
+    Ok(())
+}
+
+fn check_record_date(errors: &mut ValidationErrors, date: NaiveDate, date_of_birth: NaiveDate) {
+    check_not_future(errors, "date", date);
+    if date < date_of_birth {
+        errors.add("date", "must not be before the patient's date of birth");
+    }
+}
+
+/// Load record `id`, failing unless `user` may perform `action` on it. Records of other
+/// tenants are not found.
+pub async fn authorize_record<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    id: i32,
+    user: &AuthUser,
+    action: RecordAction,
+) -> Result<MedicalRecord, ServiceError> {
+    let record = sqlx::query_as!(MedicalRecord,
+        r#"SELECT * FROM medical_records WHERE id = $1 AND tenant_id = $2"#,
+        id,
+        user.tenant_id
     )
-    .fetch_all(&pool)
+    .fetch_optional(executor)
     .await
-    .map_err(|_| ServiceError::Unauthorized)?;
-    Ok(Json(records))
+    .map_err(|_| ServiceError::InternalServerError)?
+    .ok_or(ServiceError::NotFound)?;
+    check_record_access(user, &record.record_type, action)?;
+    Ok(record)
 }
 
-pub async fn get_record(
-    Path(id): Path<i32>,
-    State(pool): State<PgPool>,
-) -> Result<Json<MedicalRecord>, ServiceError> {
-    let record = sqlx::query_as!(MedicalRecord,
-        r#"SELECT * FROM medical_records WHERE id = $1"#,
-        id
+/// Only drafts can be edited. Records under review must be rejected back to draft first,
+/// and signed records only change through `POST /records/:id/amendments`.
+pub fn ensure_editable(record: &MedicalRecord) -> Result<(), ServiceError> {
+    match RecordStatus::parse(&record.status) {
+        Some(RecordStatus::Draft) => Ok(()),
+        Some(RecordStatus::Signed) => Err(ServiceError::Conflict(
+            "Record is signed; changes must be filed as an amendment".to_string(),
+        )),
+        _ => Err(ServiceError::Conflict(
+            "Record is awaiting sign-off; reject it back to draft before editing".to_string(),
+        )),
+    }
+}
+
+/// Re-read record `id` of `tenant_id` inside `tx`, locking it until the transaction ends.
+pub async fn lock_record(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: i32,
+    id: i32,
+) -> Result<MedicalRecord, ServiceError> {
+    sqlx::query_as!(MedicalRecord,
+        r#"SELECT * FROM medical_records WHERE id = $1 AND tenant_id = $2 FOR UPDATE"#,
+        id,
+        tenant_id
     )
-    .fetch_optional(&pool)
+    .fetch_optional(&mut **tx)
     .await
-    .map_err(|_| ServiceError::Unauthorized)?;
-    if let Some(record) = record {
-        Ok(Json(record))
+    .map_err(|_| ServiceError::InternalServerError)?
+    .ok_or(ServiceError::NotFound)
+}
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+
+#[derive(Deserialize)]
+pub struct RecordListQuery {
+    pub patient_id: Option<i32>,
+    pub record_type: Option<String>,
+    pub record_category: Option<String>,
+    pub status: Option<String>,
+    pub facility_id: Option<i32>,
+    /// Only records from the caller's facilities.
+    pub my_facilities: Option<bool>,
+    /// Case-insensitive substring of the provider name.
+    pub provider: Option<String>,
+    /// Inclusive range on the record date.
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// `date` (default), `created_at` or `title`.
+    pub sort: Option<String>,
+    /// `desc` (default) or `asc`.
+    pub order: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct RecordPage {
+    pub records: Vec<MedicalRecord>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RecordSort {
+    Date,
+    CreatedAt,
+    Title,
+}
+
+impl TextEnum for RecordSort {
+    const ALL: &'static [Self] = &[RecordSort::Date, RecordSort::CreatedAt, RecordSort::Title];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            RecordSort::Date => "date",
+            RecordSort::CreatedAt => "created_at",
+            RecordSort::Title => "title",
+        }
+    }
+}
+
+impl RecordSort {
+    /// SQL sort key and the type its cursor value is cast to.
+    fn key(self) -> (&'static str, &'static str) {
+        match self {
+            RecordSort::Date => ("r.date", "date"),
+            RecordSort::CreatedAt => ("COALESCE(r.created_at, 'epoch'::timestamp)", "timestamp"),
+            RecordSort::Title => ("r.title", "text"),
+        }
+    }
+
+    fn cursor_value(self, record: &MedicalRecord) -> String {
+        match self {
+            RecordSort::Date => record.date.to_string(),
+            RecordSort::CreatedAt => record.created_at.unwrap_or_default().format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+            RecordSort::Title => record.title.clone(),
+        }
+    }
+
+    fn accepts(self, value: &str) -> bool {
+        match self {
+            RecordSort::Date => value.parse::<NaiveDate>().is_ok(),
+            RecordSort::CreatedAt => chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").is_ok(),
+            RecordSort::Title => true,
+        }
+    }
+}
+
+/// Position after the last record on a page, as `sort:id:value`. The value goes last so
+/// titles may contain any character.
+struct RecordCursor {
+    id: i32,
+    value: String,
+}
+
+impl RecordCursor {
+    fn encode(sort: RecordSort, record: &MedicalRecord) -> String {
+        format!("{}:{}:{}", sort.as_str(), record.id, sort.cursor_value(record))
+    }
+
+    fn decode(raw: &str, sort: RecordSort) -> Option<Self> {
+        let (cursor_sort, rest) = raw.split_once(':')?;
+        let (id, value) = rest.split_once(':')?;
+        if cursor_sort != sort.as_str() || !sort.accepts(value) {
+            return None;
+        }
+        Some(RecordCursor { id: id.parse().ok()?, value: value.to_string() })
+    }
+}
+
+async fn query_records(pool: &PgPool, user: &AuthUser, mut params: RecordListQuery) -> Result<RecordPage, ServiceError> {
+    let mut errors = ValidationErrors::default();
+    normalize_optional_enum::<RecordType>(&mut errors, "record_type", &mut params.record_type);
+    normalize_optional_enum::<RecordStatus>(&mut errors, "status", &mut params.status);
+    normalize_optional_enum::<RecordSort>(&mut errors, "sort", &mut params.sort);
+    trim_optional(&mut params.record_category);
+    trim_optional(&mut params.provider);
+    let descending = match params.order.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("desc") => true,
+        Some("asc") => false,
+        Some(_) => {
+            errors.add("order", "must be one of: asc, desc");
+            true
+        }
+    };
+    if let (Some(from), Some(to)) = (params.from, params.to) {
+        if from > to {
+            errors.add("from", "must not be after to");
+        }
+    }
+    errors.into_result()?;
+
+    let sort = params.sort.as_deref().and_then(RecordSort::parse).unwrap_or(RecordSort::Date);
+    let cursor = match params.cursor.as_deref() {
+        Some(raw) => Some(
+            RecordCursor::decode(raw, sort).ok_or_else(|| ServiceError::BadRequest("Invalid cursor".to_string()))?,
+        ),
+        None => None,
+    };
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let facility_ids = facility_scope(pool, user.id, params.facility_id, params.my_facilities)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+
+    let mut tx = tenancy::begin(pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    let (key, key_type) = sort.key();
+    let (direction, comparison) = if descending { ("DESC", "<") } else { ("ASC", ">") };
+    let sql = format!(
+        r#"SELECT * FROM medical_records r
+           WHERE ($1::int IS NULL OR r.patient_id = $1)
+             AND ($2::text IS NULL OR r.record_type = $2)
+             AND ($3::text IS NULL OR LOWER(r.record_category) = LOWER($3))
+             AND ($4::text IS NULL OR r.status = $4)
+             AND ($5::text IS NULL OR r.provider ILIKE '%' || $5 || '%')
+             AND ($6::date IS NULL OR r.date >= $6)
+             AND ($7::date IS NULL OR r.date <= $7)
+             AND ($8::text IS NULL OR ({key}, r.id) {comparison} ($8::{key_type}, $9))
+             AND ($11::int[] IS NULL OR r.facility_id = ANY($11))
+             AND r.tenant_id = $12
+           ORDER BY {key} {direction}, r.id {direction}
+           LIMIT $10"#
+    );
+    let mut records = sqlx::query_as::<_, MedicalRecord>(&sql)
+        .bind(params.patient_id)
+        .bind(params.record_type)
+        .bind(params.record_category)
+        .bind(params.status)
+        .bind(params.provider.as_deref().map(like_literal))
+        .bind(params.from)
+        .bind(params.to)
+        .bind(cursor.as_ref().map(|c| c.value.clone()))
+        .bind(cursor.as_ref().map(|c| c.id))
+        .bind(limit + 1)
+        .bind(&facility_ids)
+        .bind(user.tenant_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| {
+            eprintln!("[RECORD LIST ERROR] DB error: {:?}", e);
+            ServiceError::InternalServerError
+        })?;
+
+    let next_cursor = if records.len() as i64 > limit {
+        records.truncate(limit as usize);
+        records.last().map(|record| RecordCursor::encode(sort, record))
     } else {
-        Err(ServiceError::NotFound)
+        None
+    };
+    Ok(RecordPage { records, next_cursor })
+}
+
+/// Filtered, keyset-paginated record listing.
+pub async fn list_records(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<RecordListQuery>,
+) -> Result<Json<RecordPage>, ServiceError> {
+    Ok(Json(query_records(&pool, &user, params).await?))
+}
+
+/// The same listing scoped to one patient; a `patient_id` query parameter is ignored.
+pub async fn list_patient_records(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Query(mut params): Query<RecordListQuery>,
+) -> Result<Json<RecordPage>, ServiceError> {
+    let exists: Option<i32> = sqlx::query_scalar("SELECT id FROM patients WHERE id = $1 AND tenant_id = $2")
+        .bind(id)
+        .bind(user.tenant_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    if exists.is_none() {
+        return Err(ServiceError::NotFound);
     }
+    params.patient_id = Some(id);
+    Ok(Json(query_records(&pool, &user, params).await?))
+}
+
+pub async fn get_record(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<MedicalRecord>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    let record = authorize_record(&mut *tx, id, &user, RecordAction::Read).await?;
+    tx.commit().await.map_err(|_| ServiceError::InternalServerError)?;
+    Ok(Json(record))
 }
 
 pub async fn create_record(
+    user: AuthUser,
     State(pool): State<PgPool>,
-    Json(payload): Json<CreateRecordRequest>,
+    Json(mut payload): Json<CreateRecordRequest>,
 ) -> Result<(StatusCode, Json<MedicalRecord>), ServiceError> {
+    let date_of_birth: Option<NaiveDate> =
+        sqlx::query_scalar("SELECT date_of_birth FROM patients WHERE id = $1 AND tenant_id = $2")
+            .bind(payload.patient_id)
+            .bind(user.tenant_id)
+            .fetch_optional(&pool)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    payload.validate(date_of_birth)?;
+    check_record_access(&user, &payload.record_type, RecordAction::Write)?;
+    let facility = optional_facility(&pool, "facility_id", payload.facility_id, user.id).await?;
     let now = Utc::now().naive_utc();
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
     let rec = sqlx::query_as!(MedicalRecord,
         r#"
         INSERT INTO medical_records (
-            patient_id, record_type, record_category, title, provider, date, status, description, secondary_status, reviewed_by, attachments, is_exported, created_at, updated_at
+            patient_id, record_type, record_category, title, provider, date, status, description, is_exported, created_at, updated_at,
+            facility_id, tenant_id
         ) VALUES (
-            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13
         ) RETURNING *
         "#,
         payload.patient_id,
@@ -90,83 +436,338 @@ pub async fn create_record(
         payload.title,
         payload.provider,
         payload.date,
-        payload.status,
+        RecordStatus::Draft.as_str(),
         payload.description,
-        payload.secondary_status,
-        payload.reviewed_by,
-        payload.attachments.as_deref(),
         payload.is_exported,
         Some(now),
-        Some(now)
+        Some(now),
+        facility.map(|f| f.id),
+        user.tenant_id
     )
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|_| ServiceError::Unauthorized)?;
+    record_version(&mut *tx, VersionEntry {
+        previous: None,
+        record: &rec,
+        kind: VersionKind::Created,
+        reason: None,
+        changed_by: Some(user.id),
+    })
+    .await
+    .map_err(|_| ServiceError::InternalServerError)?;
+    tx.commit().await.map_err(|_| ServiceError::InternalServerError)?;
     Ok((StatusCode::CREATED, Json(rec)))
 }
 
-pub async fn update_record(
-    Path(id): Path<i32>,
-    State(pool): State<PgPool>,
-    Json(payload): Json<UpdateRecordRequest>,
-) -> Result<Json<MedicalRecord>, ServiceError> {
+/// Apply the non-null fields of `payload` to record `id` of `tenant_id`.
+async fn apply_update(
+    tx: &mut Transaction<'_, Postgres>,
+    id: i32,
+    tenant_id: i32,
+    payload: &UpdateRecordRequest,
+) -> Result<MedicalRecord, ServiceError> {
     let now = Utc::now().naive_utc();
-    let record = sqlx::query_as!(MedicalRecord,
+    sqlx::query_as!(MedicalRecord,
         r#"
         UPDATE medical_records SET
             record_type = COALESCE($1, record_type),
             title = COALESCE($2, title),
             provider = COALESCE($3, provider),
             date = COALESCE($4, date),
-            status = COALESCE($5, status),
-            record_category = COALESCE($6, record_category),
-            description = COALESCE($7, description),
-            secondary_status = COALESCE($8, secondary_status),
-            reviewed_by = COALESCE($9, reviewed_by),
-            attachments = COALESCE($10, attachments),
-            is_exported = COALESCE($11, is_exported),
-            updated_at = $12
-        WHERE id = $13
+            record_category = COALESCE($5, record_category),
+            description = COALESCE($6, description),
+            is_exported = COALESCE($7, is_exported),
+            updated_at = $8,
+            facility_id = COALESCE($10, facility_id)
+        WHERE id = $9 AND tenant_id = $11
         RETURNING *
         "#,
         payload.record_type,
         payload.title,
         payload.provider,
         payload.date,
-        payload.status,
         payload.record_category,
         payload.description,
-        payload.secondary_status,
-        payload.reviewed_by,
-        payload.attachments.as_deref(),
         payload.is_exported,
         Some(now),
-        id
+        id,
+        payload.facility_id,
+        tenant_id
     )
-    .fetch_optional(&pool)
+    .fetch_optional(&mut **tx)
     .await
-    .map_err(|_| ServiceError::Unauthorized)?;
-    if let Some(record) = record {
-        Ok(Json(record))
-    } else {
-        Err(ServiceError::NotFound)
+    .map_err(|_| ServiceError::Unauthorized)?
+    .ok_or(ServiceError::NotFound)
+}
+
+/// Look up the owning patient's date of birth, which bounds the record date.
+async fn record_patient_date_of_birth(pool: &PgPool, id: i32, tenant_id: i32) -> Result<NaiveDate, ServiceError> {
+    sqlx::query_scalar(
+        "SELECT p.date_of_birth FROM medical_records r JOIN patients p ON p.id = r.patient_id
+         WHERE r.id = $1 AND r.tenant_id = $2"
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| ServiceError::InternalServerError)?
+    .ok_or(ServiceError::NotFound)
+}
+
+/// Edit an unsigned record. The previous content is kept as a version.
+pub async fn update_record(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<UpdateRecordRequest>,
+) -> Result<Json<MedicalRecord>, ServiceError> {
+    ensure_editable(&authorize_record(&pool, id, &user, RecordAction::Write).await?)?;
+    let date_of_birth = record_patient_date_of_birth(&pool, id, user.tenant_id).await?;
+    payload.validate(date_of_birth)?;
+    if let Some(record_type) = &payload.record_type {
+        check_record_access(&user, record_type, RecordAction::Write)?;
+    }
+    if payload.facility_id.is_some() {
+        optional_facility(&pool, "facility_id", payload.facility_id, user.id).await?;
     }
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    let previous = lock_record(&mut tx, user.tenant_id, id).await?;
+    ensure_editable(&previous)?;
+    let record = apply_update(&mut tx, id, user.tenant_id, &payload).await?;
+    record_version(&mut *tx, VersionEntry {
+        previous: Some(&previous),
+        record: &record,
+        kind: VersionKind::Edited,
+        reason: payload.reason.as_deref(),
+        changed_by: Some(user.id),
+    })
+    .await
+    .map_err(|_| ServiceError::InternalServerError)?;
+    tx.commit().await.map_err(|_| ServiceError::InternalServerError)?;
+    Ok(Json(record))
+}
+
+/// Correct a signed record. The record stays signed and the amendment, with its reason,
+/// becomes a new version.
+pub async fn amend_record(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<UpdateRecordRequest>,
+) -> Result<Json<MedicalRecord>, ServiceError> {
+    authorize_record(&pool, id, &user, RecordAction::Write).await?;
+    let date_of_birth = record_patient_date_of_birth(&pool, id, user.tenant_id).await?;
+    payload.validate(date_of_birth)?;
+    check_amendment(&payload)?;
+    if let Some(record_type) = &payload.record_type {
+        check_record_access(&user, record_type, RecordAction::Write)?;
+    }
+    if payload.facility_id.is_some() {
+        optional_facility(&pool, "facility_id", payload.facility_id, user.id).await?;
+    }
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    let previous = lock_record(&mut tx, user.tenant_id, id).await?;
+    if !previous.is_signed() {
+        return Err(ServiceError::Conflict("Only signed records are amended; edit this record directly".to_string()));
+    }
+    let record = apply_update(&mut tx, id, user.tenant_id, &payload).await?;
+    let changed = record_version(&mut *tx, VersionEntry {
+        previous: Some(&previous),
+        record: &record,
+        kind: VersionKind::Amended,
+        reason: payload.reason.as_deref(),
+        changed_by: Some(user.id),
+    })
+    .await
+    .map_err(|_| ServiceError::InternalServerError)?;
+    if !changed {
+        return Err(ServiceError::BadRequest("Amendment does not change the record".to_string()));
+    }
+    tx.commit().await.map_err(|_| ServiceError::InternalServerError)?;
+    Ok(Json(record))
 }
 
 pub async fn delete_record(
+    user: AuthUser,
     Path(id): Path<i32>,
     State(pool): State<PgPool>,
 ) -> Result<StatusCode, ServiceError> {
+    ensure_editable(&authorize_record(&pool, id, &user, RecordAction::Write).await?)?;
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
     let result = sqlx::query!(
-        "DELETE FROM medical_records WHERE id = $1",
-        id
+        "DELETE FROM medical_records WHERE id = $1 AND tenant_id = $2",
+        id,
+        user.tenant_id
     )
-    .execute(&pool)
+    .execute(&mut *tx)
     .await
     .map_err(|_| ServiceError::Unauthorized)?;
     if result.rows_affected() > 0 {
+        tx.commit().await.map_err(|_| ServiceError::InternalServerError)?;
         Ok(StatusCode::NO_CONTENT)
     } else {
         Err(ServiceError::NotFound)
     }
 }
+
+#[derive(Deserialize)]
+pub struct VersionDiffQuery {
+    pub from: i32,
+    pub to: i32,
+}
+
+#[derive(Serialize)]
+pub struct VersionDiff {
+    pub record_id: i32,
+    pub from: i32,
+    pub to: i32,
+    pub changes: Map<String, Value>,
+}
+
+/// History of a record, oldest first.
+pub async fn list_record_versions(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<RecordVersionSummary>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    authorize_record(&mut *tx, id, &user, RecordAction::Read).await?;
+    let versions = sqlx::query_as::<_, RecordVersionSummary>(
+        r#"SELECT id, record_id, version, kind, reason, changes, changed_by, created_at
+           FROM record_versions WHERE record_id = $1 ORDER BY version"#
+    )
+    .bind(id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|_| ServiceError::InternalServerError)?;
+    tx.commit().await.map_err(|_| ServiceError::InternalServerError)?;
+    Ok(Json(versions))
+}
+
+async fn fetch_version(
+    tx: &mut Transaction<'_, Postgres>,
+    id: i32,
+    version: i32,
+) -> Result<RecordVersion, ServiceError> {
+    sqlx::query_as::<_, RecordVersion>("SELECT * FROM record_versions WHERE record_id = $1 AND version = $2")
+        .bind(id)
+        .bind(version)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?
+        .ok_or(ServiceError::NotFound)
+}
+
+/// A single version with the full record as it stood then.
+pub async fn get_record_version(
+    user: AuthUser,
+    Path((id, version)): Path<(i32, i32)>,
+    State(pool): State<PgPool>,
+) -> Result<Json<RecordVersion>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    authorize_record(&mut *tx, id, &user, RecordAction::Read).await?;
+    let version = fetch_version(&mut tx, id, version).await?;
+    tx.commit().await.map_err(|_| ServiceError::InternalServerError)?;
+    Ok(Json(version))
+}
+
+/// Field-level differences between two versions; `from` may be later than `to`.
+pub async fn diff_record_versions(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Query(params): Query<VersionDiffQuery>,
+) -> Result<Json<VersionDiff>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    authorize_record(&mut *tx, id, &user, RecordAction::Read).await?;
+    let from = fetch_version(&mut tx, id, params.from).await?;
+    let to = fetch_version(&mut tx, id, params.to).await?;
+    tx.commit().await.map_err(|_| ServiceError::InternalServerError)?;
+    Ok(Json(VersionDiff {
+        record_id: id,
+        from: params.from,
+        to: params.to,
+        changes: diff_fields(&from.snapshot, &to.snapshot, UNVERSIONED_FIELDS),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: i32, title: &str) -> MedicalRecord {
+        MedicalRecord {
+            id,
+            patient_id: 1,
+            record_type: "consultation".to_string(),
+            record_category: None,
+            title: title.to_string(),
+            provider: "Dr Test".to_string(),
+            date: NaiveDate::from_ymd_opt(2026, 3, 9).unwrap(),
+            status: "draft".to_string(),
+            description: None,
+            secondary_status: None,
+            reviewed_by: None,
+            attachments: None,
+            is_exported: None,
+            created_at: NaiveDate::from_ymd_opt(2026, 3, 9).unwrap().and_hms_micro_opt(8, 15, 0, 250),
+            updated_at: None,
+            submitted_by: None,
+            submitted_at: None,
+            reviewed_at: None,
+            signed_by: None,
+            signed_at: None,
+            facility_id: None,
+            tenant_id: 1,
+        }
+    }
+
+    #[test]
+    fn cursors_round_trip_for_every_sort() {
+        let record = record(42, "Follow-up: BP 150/95, recheck at 10:30");
+        for (sort, value) in [
+            (RecordSort::Date, "2026-03-09"),
+            (RecordSort::CreatedAt, "2026-03-09T08:15:00.000250"),
+            (RecordSort::Title, "Follow-up: BP 150/95, recheck at 10:30"),
+        ] {
+            let raw = RecordCursor::encode(sort, &record);
+            assert_eq!(raw, format!("{}:42:{}", sort.as_str(), value));
+            let cursor = RecordCursor::decode(&raw, sort).unwrap();
+            assert_eq!((cursor.id, cursor.value.as_str()), (42, value));
+        }
+    }
+
+    #[test]
+    fn records_without_a_creation_time_sort_first() {
+        let mut record = record(7, "Visit");
+        record.created_at = None;
+        let raw = RecordCursor::encode(RecordSort::CreatedAt, &record);
+        assert_eq!(raw, "created_at:7:1970-01-01T00:00:00");
+        assert!(RecordCursor::decode(&raw, RecordSort::CreatedAt).is_some());
+    }
+
+    #[test]
+    fn cursors_for_another_sort_or_with_bad_values_are_refused() {
+        let raw = RecordCursor::encode(RecordSort::Date, &record(42, "Visit"));
+        assert!(RecordCursor::decode(&raw, RecordSort::Title).is_none());
+        let refused = ["date:42:2026-02-30", "date:x:2026-03-09", "date:2026-03-09", "created_at:42:2026-03-09", ""];
+        for raw in refused {
+            assert!(RecordCursor::decode(raw, RecordSort::Date).is_none(), "{}", raw);
+            assert!(RecordCursor::decode(raw, RecordSort::CreatedAt).is_none(), "{}", raw);
+        }
+        assert!(RecordCursor::decode("title:42:", RecordSort::Title).is_some());
+    }
+}
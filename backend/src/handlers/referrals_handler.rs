@@ -0,0 +1,588 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use crate::handlers::records_handler::authorize_record;
+use crate::models::error::ServiceError;
+use crate::models::referral::{
+    Referral, ReferralAction, ReferralCompletion, ReferralNote, ReferralSide, ReferralStatus, ReferralUrgency,
+};
+use crate::services::access::{check_clinical_role, RecordAction};
+use crate::services::alerts::{notify_user, AlertEntry};
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::facilities::{facility_scope, require_facility};
+use crate::services::tenancy;
+use crate::services::validation::{
+    field_error, normalize_optional_enum, require_text, trim_optional, TextEnum, ValidationErrors,
+};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+/// Referral analytics cover the last 90 days unless asked otherwise.
+const DEFAULT_REPORT_DAYS: i64 = 90;
+
+#[derive(Deserialize)]
+pub struct CreateReferralRequest {
+    pub patient_id: i32,
+    /// Defaults to the referrer's primary facility.
+    pub from_facility_id: Option<i32>,
+    pub to_facility_id: i32,
+    pub reason: String,
+    /// Defaults to `routine`.
+    pub urgency: Option<String>,
+    pub clinical_summary: Option<String>,
+    /// A record holding the clinical summary.
+    pub record_id: Option<i32>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct ReferralStepRequest {
+    /// Required to decline or cancel.
+    pub reason: Option<String>,
+    /// Required to complete: what was done, for the referrer.
+    pub outcome: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ReferralNoteRequest {
+    pub note: String,
+    /// `referrer` or `receiver`; defaults to `referrer` for whoever made the referral and
+    /// `receiver` for everyone else.
+    pub side: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MailboxQuery {
+    /// Required unless `my_facilities` is set.
+    pub facility_id: Option<i32>,
+    /// The mailboxes of every facility the caller works at.
+    pub my_facilities: Option<bool>,
+    pub status: Option<String>,
+    /// Only referrals still open on the receiving side.
+    #[serde(default)]
+    pub open: bool,
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct ReferralAnalyticsQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub facility_id: Option<i32>,
+    pub my_facilities: Option<bool>,
+}
+
+#[derive(Serialize, FromRow)]
+pub struct ReferralSummary {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub referral: Referral,
+    pub patient_name: String,
+    pub from_facility_name: String,
+    pub to_facility_name: String,
+    pub note_count: i64,
+}
+
+#[derive(Serialize)]
+pub struct ReferralDetail {
+    #[serde(flatten)]
+    pub referral: Referral,
+    pub patient_name: String,
+    pub from_facility_name: String,
+    pub to_facility_name: String,
+    pub notes: Vec<ReferralNote>,
+}
+
+#[derive(Serialize)]
+pub struct ReferralAnalytics {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Referrals received by each facility.
+    pub by_destination: Vec<ReferralCompletion>,
+    /// Referrals sent by each facility.
+    pub by_source: Vec<ReferralCompletion>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[REFERRAL ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+const SUMMARY_SELECT: &str = "SELECT r.*, p.first_name || ' ' || p.last_name AS patient_name,
+            src.name AS from_facility_name, dst.name AS to_facility_name,
+            (SELECT COUNT(*) FROM referral_notes n WHERE n.referral_id = r.id) AS note_count
+     FROM referrals r
+     JOIN patients p ON p.id = r.patient_id
+     JOIN facilities src ON src.id = r.from_facility_id
+     JOIN facilities dst ON dst.id = r.to_facility_id";
+
+async fn lock_referral(tx: &mut Transaction<'_, Postgres>, tenant_id: i32, id: i32) -> Result<Referral, ServiceError> {
+    sqlx::query_as::<_, Referral>(
+        "SELECT r.* FROM referrals r JOIN patients p ON p.id = r.patient_id AND p.tenant_id = $2
+         WHERE r.id = $1 FOR UPDATE OF r",
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)
+}
+
+async fn referral_detail(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: i32,
+    id: i32,
+) -> Result<ReferralDetail, ServiceError> {
+    let summary = sqlx::query_as::<_, ReferralSummary>(&format!("{SUMMARY_SELECT} WHERE r.id = $1 AND p.tenant_id = $2"))
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?
+        .ok_or(ServiceError::NotFound)?;
+    let notes = sqlx::query_as::<_, ReferralNote>(
+        "SELECT * FROM referral_notes WHERE referral_id = $1 ORDER BY created_at, id",
+    )
+    .bind(id)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    Ok(ReferralDetail {
+        referral: summary.referral,
+        patient_name: summary.patient_name,
+        from_facility_name: summary.from_facility_name,
+        to_facility_name: summary.to_facility_name,
+        notes,
+    })
+}
+
+async fn facility_name(tx: &mut Transaction<'_, Postgres>, id: i32) -> Result<String, ServiceError> {
+    sqlx::query_scalar("SELECT name FROM facilities WHERE id = $1")
+        .bind(id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(db_error)
+}
+
+/// Let the clinician who made a referral know the receiving facility responded.
+async fn notify_referrer(
+    tx: &mut Transaction<'_, Postgres>,
+    referral: &Referral,
+    user: &AuthUser,
+    message: &str,
+) -> Result<(), ServiceError> {
+    let Some(referrer) = referral.referred_by.filter(|id| *id != user.id) else {
+        return Ok(());
+    };
+    notify_user(tx, referrer, AlertEntry {
+        patient_id: referral.patient_id,
+        vital_set_id: None,
+        kind: "referral_feedback",
+        severity: "low",
+        message,
+    })
+    .await
+    .map_err(db_error)?;
+    Ok(())
+}
+
+/// Refer a patient to another facility. Referring is for clinicians and admins.
+pub async fn create_referral(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<CreateReferralRequest>,
+) -> Result<(StatusCode, Json<ReferralDetail>), ServiceError> {
+    check_clinical_role(&user)?;
+    let mut errors = ValidationErrors::default();
+    require_text(&mut errors, "reason", &mut payload.reason);
+    trim_optional(&mut payload.clinical_summary);
+    normalize_optional_enum::<ReferralUrgency>(&mut errors, "urgency", &mut payload.urgency);
+    errors.into_result()?;
+    let from_facility = require_facility(&pool, "from_facility_id", payload.from_facility_id, user.id).await?;
+    let to_facility = require_facility(&pool, "to_facility_id", Some(payload.to_facility_id), user.id).await?;
+    if from_facility.id == to_facility.id {
+        return Err(field_error("to_facility_id", "must differ from from_facility_id"));
+    }
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let exists: Option<i32> = sqlx::query_scalar("SELECT id FROM patients WHERE id = $1 AND tenant_id = $2")
+        .bind(payload.patient_id)
+        .bind(user.tenant_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    if exists.is_none() {
+        return Err(field_error("patient_id", "does not refer to an existing patient"));
+    }
+    if let Some(record_id) = payload.record_id {
+        let record = authorize_record(&mut *tx, record_id, &user, RecordAction::Read).await?;
+        if record.patient_id != payload.patient_id {
+            return Err(field_error("record_id", "belongs to a different patient"));
+        }
+    }
+    let urgency = payload.urgency.unwrap_or_else(|| ReferralUrgency::Routine.as_str().to_string());
+
+    let referral = sqlx::query_as::<_, Referral>(
+        "INSERT INTO referrals (patient_id, from_facility_id, to_facility_id, reason, urgency, clinical_summary,
+             record_id, referred_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+         RETURNING *",
+    )
+    .bind(payload.patient_id)
+    .bind(from_facility.id)
+    .bind(to_facility.id)
+    .bind(&payload.reason)
+    .bind(&urgency)
+    .bind(&payload.clinical_summary)
+    .bind(payload.record_id)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "referral.sent",
+        entity_type: "referral",
+        entity_id: referral.id,
+        patient_id: Some(referral.patient_id),
+        details: serde_json::json!({
+            "from_facility_id": referral.from_facility_id,
+            "to_facility_id": referral.to_facility_id,
+            "urgency": referral.urgency,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    let detail = referral_detail(&mut tx, user.tenant_id, referral.id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(detail)))
+}
+
+pub async fn get_referral(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<ReferralDetail>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let detail = referral_detail(&mut tx, user.tenant_id, id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(detail))
+}
+
+/// A patient's referrals, latest first.
+pub async fn list_patient_referrals(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<ReferralSummary>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let referrals = sqlx::query_as::<_, ReferralSummary>(&format!(
+        "{SUMMARY_SELECT} WHERE r.patient_id = $1 AND p.tenant_id = $2 ORDER BY r.sent_at DESC, r.id DESC"
+    ))
+    .bind(patient_id)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(referrals))
+}
+
+async fn list_mailbox(
+    pool: &PgPool,
+    user: &AuthUser,
+    side: ReferralSide,
+    mut params: MailboxQuery,
+) -> Result<Vec<ReferralSummary>, ServiceError> {
+    let mut errors = ValidationErrors::default();
+    normalize_optional_enum::<ReferralStatus>(&mut errors, "status", &mut params.status);
+    errors.into_result()?;
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let facility_ids = facility_scope(pool, user.id, params.facility_id, params.my_facilities)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| field_error("facility_id", "is required unless my_facilities is set"))?;
+    let facility_column = match side {
+        ReferralSide::Receiver => "r.to_facility_id",
+        ReferralSide::Referrer => "r.from_facility_id",
+    };
+    let mut tx = tenancy::begin(pool, user.tenant_id).await.map_err(db_error)?;
+    let referrals = sqlx::query_as::<_, ReferralSummary>(&format!(
+        "{SUMMARY_SELECT}
+         WHERE {facility_column} = ANY($1)
+           AND ($2::text IS NULL OR r.status = $2)
+           AND (NOT $3 OR r.status = ANY($4))
+           AND p.tenant_id = $6
+         ORDER BY CASE r.urgency WHEN 'emergency' THEN 0 WHEN 'urgent' THEN 1 ELSE 2 END,
+                  r.sent_at DESC, r.id DESC
+         LIMIT $5"
+    ))
+    .bind(&facility_ids)
+    .bind(&params.status)
+    .bind(params.open)
+    .bind(ReferralStatus::OPEN)
+    .bind(limit)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(referrals)
+}
+
+/// Referrals sent to a facility, most urgent first.
+pub async fn list_referral_inbox(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<MailboxQuery>,
+) -> Result<Json<Vec<ReferralSummary>>, ServiceError> {
+    Ok(Json(list_mailbox(&pool, &user, ReferralSide::Receiver, params).await?))
+}
+
+/// Referrals a facility has sent, most urgent first.
+pub async fn list_referral_outbox(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<MailboxQuery>,
+) -> Result<Json<Vec<ReferralSummary>>, ServiceError> {
+    Ok(Json(list_mailbox(&pool, &user, ReferralSide::Referrer, params).await?))
+}
+
+/// Move a referral along its workflow, recording who did it, and tell the referrer.
+async fn apply_action(
+    pool: &PgPool,
+    user: &AuthUser,
+    id: i32,
+    action: ReferralAction,
+    payload: Option<Json<ReferralStepRequest>>,
+) -> Result<ReferralDetail, ServiceError> {
+    let mut payload = payload.map(|Json(p)| p).unwrap_or_default();
+    trim_optional(&mut payload.reason);
+    trim_optional(&mut payload.outcome);
+    match action {
+        ReferralAction::Decline | ReferralAction::Cancel if payload.reason.is_none() => {
+            return Err(field_error("reason", format!("is required when a referral is {}", action.past_tense())));
+        }
+        ReferralAction::Complete if payload.outcome.is_none() => {
+            return Err(field_error("outcome", "is required to complete a referral"));
+        }
+        _ => {}
+    }
+
+    let mut tx = tenancy::begin(pool, user.tenant_id).await.map_err(db_error)?;
+    let before = lock_referral(&mut tx, user.tenant_id, id).await?;
+    let from = ReferralStatus::parse(&before.status).ok_or(ServiceError::InternalServerError)?;
+    let to = action.apply(from).ok_or_else(|| {
+        ServiceError::Conflict(format!("A referral that is {} cannot be {}", before.status, action.past_tense()))
+    })?;
+    let referral = sqlx::query_as::<_, Referral>(
+        "UPDATE referrals SET status = $2,
+             received_at = CASE WHEN $2 IN ('received', 'accepted') THEN COALESCE(received_at, now()) ELSE received_at END,
+             received_by = CASE WHEN $2 IN ('received', 'accepted') THEN COALESCE(received_by, $3) ELSE received_by END,
+             accepted_at = CASE WHEN $2 = 'accepted' THEN now() ELSE accepted_at END,
+             accepted_by = CASE WHEN $2 = 'accepted' THEN $3 ELSE accepted_by END,
+             completed_at = CASE WHEN $2 = 'completed' THEN now() ELSE completed_at END,
+             completed_by = CASE WHEN $2 = 'completed' THEN $3 ELSE completed_by END,
+             outcome = COALESCE($4, outcome),
+             closed_reason = COALESCE($5, closed_reason),
+             updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(to.as_str())
+    .bind(user.id)
+    .bind(&payload.outcome)
+    .bind(&payload.reason)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    if action != ReferralAction::Cancel {
+        let destination = facility_name(&mut tx, referral.to_facility_id).await?;
+        let mut message = format!("Referral #{} to {} {}", referral.id, destination, action.past_tense());
+        if let Some(detail) = payload.outcome.as_deref().or(payload.reason.as_deref()) {
+            message = format!("{}: {}", message, detail);
+        }
+        notify_referrer(&mut tx, &referral, user, &message).await?;
+    }
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: &format!("referral.{}", to.as_str()),
+        entity_type: "referral",
+        entity_id: id,
+        patient_id: Some(referral.patient_id),
+        details: serde_json::json!({
+            "from": before.status,
+            "to": referral.status,
+            "reason": payload.reason,
+            "outcome": payload.outcome,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    let detail = referral_detail(&mut tx, user.tenant_id, id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(detail)
+}
+
+pub async fn receive_referral(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<ReferralDetail>, ServiceError> {
+    Ok(Json(apply_action(&pool, &user, id, ReferralAction::Receive, None).await?))
+}
+
+/// Accepting, declining and completing are for clinicians and admins.
+pub async fn accept_referral(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<ReferralDetail>, ServiceError> {
+    check_clinical_role(&user)?;
+    Ok(Json(apply_action(&pool, &user, id, ReferralAction::Accept, None).await?))
+}
+
+pub async fn decline_referral(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<ReferralStepRequest>>,
+) -> Result<Json<ReferralDetail>, ServiceError> {
+    check_clinical_role(&user)?;
+    Ok(Json(apply_action(&pool, &user, id, ReferralAction::Decline, payload).await?))
+}
+
+pub async fn complete_referral(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<ReferralStepRequest>>,
+) -> Result<Json<ReferralDetail>, ServiceError> {
+    check_clinical_role(&user)?;
+    Ok(Json(apply_action(&pool, &user, id, ReferralAction::Complete, payload).await?))
+}
+
+pub async fn cancel_referral(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    payload: Option<Json<ReferralStepRequest>>,
+) -> Result<Json<ReferralDetail>, ServiceError> {
+    check_clinical_role(&user)?;
+    Ok(Json(apply_action(&pool, &user, id, ReferralAction::Cancel, payload).await?))
+}
+
+/// Add a note to the referral's thread. Notes from the receiving side reach the referrer as
+/// an alert.
+pub async fn add_referral_note(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<ReferralNoteRequest>,
+) -> Result<(StatusCode, Json<ReferralDetail>), ServiceError> {
+    let mut errors = ValidationErrors::default();
+    require_text(&mut errors, "note", &mut payload.note);
+    normalize_optional_enum::<ReferralSide>(&mut errors, "side", &mut payload.side);
+    errors.into_result()?;
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let referral = lock_referral(&mut tx, user.tenant_id, id).await?;
+    let side = match payload.side.as_deref().and_then(ReferralSide::parse) {
+        Some(side) => side,
+        None if referral.referred_by == Some(user.id) => ReferralSide::Referrer,
+        None => ReferralSide::Receiver,
+    };
+    let note = sqlx::query_as::<_, ReferralNote>(
+        "INSERT INTO referral_notes (referral_id, side, note, author_id) VALUES ($1, $2, $3, $4) RETURNING *",
+    )
+    .bind(id)
+    .bind(side.as_str())
+    .bind(&payload.note)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    if side == ReferralSide::Receiver {
+        let destination = facility_name(&mut tx, referral.to_facility_id).await?;
+        let message = format!("Note on referral #{} from {}: {}", referral.id, destination, note.note);
+        notify_referrer(&mut tx, &referral, &user, &message).await?;
+    }
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "referral.note_added",
+        entity_type: "referral",
+        entity_id: id,
+        patient_id: Some(referral.patient_id),
+        details: serde_json::json!({ "note_id": note.id, "side": note.side }),
+    })
+    .await
+    .map_err(db_error)?;
+    let detail = referral_detail(&mut tx, user.tenant_id, id).await?;
+    tx.commit().await.map_err(db_error)?;
+    Ok((StatusCode::CREATED, Json(detail)))
+}
+
+/// Completion rates per receiving and per referring facility for referrals sent in a period.
+pub async fn get_referral_analytics(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<ReferralAnalyticsQuery>,
+) -> Result<Json<ReferralAnalytics>, ServiceError> {
+    let facility_ids = facility_scope(&pool, user.id, params.facility_id, params.my_facilities)
+        .await
+        .map_err(db_error)?;
+    let to = params.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = params.from.unwrap_or(to - Duration::days(DEFAULT_REPORT_DAYS - 1));
+    if from > to {
+        return Err(field_error("from", "cannot be after to"));
+    }
+    let completion = |facility_column: &str| {
+        format!(
+            "SELECT r.{facility_column} AS facility_id, f.name AS facility_name, COUNT(*) AS total,
+                    COUNT(*) FILTER (WHERE r.status = 'sent') AS sent,
+                    COUNT(*) FILTER (WHERE r.status = 'received') AS received,
+                    COUNT(*) FILTER (WHERE r.status = 'accepted') AS accepted,
+                    COUNT(*) FILTER (WHERE r.status = 'completed') AS completed,
+                    COUNT(*) FILTER (WHERE r.status = 'declined') AS declined,
+                    COUNT(*) FILTER (WHERE r.status = 'cancelled') AS cancelled,
+                    round(100.0 * COUNT(*) FILTER (WHERE r.status = 'completed')
+                          / NULLIF(COUNT(*) FILTER (WHERE r.status <> 'cancelled'), 0), 1)::float8 AS completion_rate,
+                    round((percentile_cont(0.5) WITHIN GROUP (
+                        ORDER BY EXTRACT(EPOCH FROM r.completed_at - r.sent_at) / 86400))::numeric, 1)::float8
+                        AS median_days_to_complete
+             FROM referrals r
+             JOIN patients p ON p.id = r.patient_id AND p.tenant_id = $4
+             JOIN facilities f ON f.id = r.{facility_column}
+             WHERE r.sent_at >= $1 AND r.sent_at < $2::date + 1
+               AND ($3::int[] IS NULL OR r.{facility_column} = ANY($3))
+             GROUP BY r.{facility_column}, f.name
+             ORDER BY f.name"
+        )
+    };
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let by_destination = sqlx::query_as::<_, ReferralCompletion>(&completion("to_facility_id"))
+        .bind(from)
+        .bind(to)
+        .bind(&facility_ids)
+        .bind(user.tenant_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    let by_source = sqlx::query_as::<_, ReferralCompletion>(&completion("from_facility_id"))
+        .bind(from)
+        .bind(to)
+        .bind(&facility_ids)
+        .bind(user.tenant_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(ReferralAnalytics { from, to, by_destination, by_source }))
+}
@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::models::error::ServiceError;
+use crate::models::terminology::{TerminologyCode, TerminologySystem};
+use crate::services::access::check_platform_admin_role;
+use crate::services::auth::AuthUser;
+use crate::services::terminology::{search_codes, LoadSummary, TerminologyError, TerminologyFiles};
+
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+const MAX_SEARCH_LIMIT: i64 = 50;
+
+#[derive(Deserialize)]
+pub struct CodeSearchQuery {
+    pub q: Option<String>,
+    pub system: Option<String>,
+    pub limit: Option<i64>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[TERMINOLOGY ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+pub async fn list_systems(
+    _user: AuthUser,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<TerminologySystem>>, ServiceError> {
+    let systems = sqlx::query_as::<_, TerminologySystem>("SELECT * FROM terminology_systems ORDER BY code")
+        .fetch_all(&pool)
+        .await
+        .map_err(db_error)?;
+    Ok(Json(systems))
+}
+
+/// Autocomplete over code, display and synonyms. An empty query returns nothing.
+pub async fn search_terminology(
+    _user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<CodeSearchQuery>,
+) -> Result<Json<Vec<TerminologyCode>>, ServiceError> {
+    let query = params.q.as_deref().map(str::trim).unwrap_or_default();
+    if query.is_empty() {
+        return Ok(Json(Vec::new()));
+    }
+    let system = params.system.as_deref().map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty());
+    let limit = params.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+    let codes = search_codes(&pool, query, system.as_deref(), limit).await.map_err(db_error)?;
+    Ok(Json(codes))
+}
+
+pub async fn get_code(
+    _user: AuthUser,
+    Path((system, code)): Path<(String, String)>,
+    State(pool): State<PgPool>,
+) -> Result<Json<TerminologyCode>, ServiceError> {
+    let code = sqlx::query_as::<_, TerminologyCode>(
+        "SELECT system, code, display, synonyms, active FROM terminology_codes
+         WHERE system = $1 AND upper(code) = upper($2)",
+    )
+    .bind(system.to_lowercase())
+    .bind(code)
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)?;
+    Ok(Json(code))
+}
+
+/// Re-read the code table files and load the ones that changed.
+pub async fn reload_terminology(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    State(files): State<TerminologyFiles>,
+) -> Result<Json<Vec<LoadSummary>>, ServiceError> {
+    check_platform_admin_role(&user)?;
+    match files.load_all(&pool).await {
+        Ok(summaries) => Ok(Json(summaries)),
+        Err(TerminologyError::Db(e)) => Err(db_error(e)),
+        Err(e @ TerminologyError::Parse { .. }) => {
+            Err(ServiceError::BadRequest(format!("Code tables were not reloaded: {}", e)))
+        }
+        Err(e) => {
+            eprintln!("[TERMINOLOGY ERROR] Reload failed: {}", e);
+            Err(ServiceError::InternalServerError)
+        }
+    }
+}
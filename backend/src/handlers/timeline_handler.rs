@@ -0,0 +1,214 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::models::error::ServiceError;
+use crate::services::auth::AuthUser;
+use crate::services::tenancy;
+
+/// Event types that can appear in a patient timeline.
+pub const TIMELINE_EVENT_TYPES: &[&str] = &[
+    "record",
+    "report",
+    "consent",
+    "profile_edit",
+    "status_change",
+    "appointment",
+];
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+
+#[derive(Deserialize)]
+pub struct TimelineQuery {
+    /// Comma-separated list of event types to include.
+    pub types: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, FromRow)]
+pub struct TimelineEntry {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub occurred_at: NaiveDateTime,
+    pub source_id: i32,
+    pub summary: String,
+    pub link: String,
+}
+
+#[derive(Serialize)]
+pub struct TimelineResponse {
+    pub entries: Vec<TimelineEntry>,
+    pub next_cursor: Option<String>,
+}
+
+/// Position of the last entry on a page: timestamp plus event type and source id as tie-breakers.
+struct TimelineCursor {
+    occurred_at: NaiveDateTime,
+    entry_key: String,
+}
+
+impl TimelineCursor {
+    const TIMESTAMP_FORMAT: &'static str = "%Y-%m-%dT%H:%M:%S%.f";
+
+    fn encode(entry: &TimelineEntry) -> String {
+        format!(
+            "{}~{}:{}",
+            entry.occurred_at.format(Self::TIMESTAMP_FORMAT),
+            entry.event_type,
+            entry.source_id
+        )
+    }
+
+    fn decode(raw: &str) -> Option<Self> {
+        let (timestamp, entry_key) = raw.split_once('~')?;
+        let occurred_at = NaiveDateTime::parse_from_str(timestamp, Self::TIMESTAMP_FORMAT).ok()?;
+        Some(TimelineCursor { occurred_at, entry_key: entry_key.to_string() })
+    }
+}
+
+/// Merged, time-ordered history of everything that happened to a patient of the caller's
+/// tenant.
+pub async fn get_patient_timeline(
+    user: AuthUser,
+    Path(id): Path<i32>,
+    State(pool): State<PgPool>,
+    Query(params): Query<TimelineQuery>,
+) -> Result<Json<TimelineResponse>, ServiceError> {
+    let types = match params.types.as_deref() {
+        Some(raw) => {
+            let types: Vec<String> = raw
+                .split(',')
+                .map(|t| t.trim().to_lowercase())
+                .filter(|t| !t.is_empty())
+                .collect();
+            if let Some(unknown) = types.iter().find(|t| !TIMELINE_EVENT_TYPES.contains(&t.as_str())) {
+                return Err(ServiceError::BadRequest(format!("Unknown timeline event type: {}", unknown)));
+            }
+            Some(types)
+        }
+        None => None,
+    };
+    let cursor = match params.cursor.as_deref() {
+        Some(raw) => Some(
+            TimelineCursor::decode(raw)
+                .ok_or_else(|| ServiceError::BadRequest("Invalid cursor".to_string()))?,
+        ),
+        None => None,
+    };
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    let exists: Option<(i32,)> = sqlx::query_as("SELECT id FROM patients WHERE id = $1 AND tenant_id = $2")
+        .bind(id)
+        .bind(user.tenant_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|_| ServiceError::InternalServerError)?;
+    if exists.is_none() {
+        return Err(ServiceError::NotFound);
+    }
+
+    let mut entries = sqlx::query_as::<_, TimelineEntry>(
+        r#"
+        WITH events AS (
+            SELECT 'record' AS event_type, r.date::timestamp AS occurred_at, r.id AS source_id,
+                   r.title || ' (' || r.record_type || ', ' || r.status || ')' AS summary,
+                   '/records/' || r.id AS link
+            FROM medical_records r
+            WHERE r.patient_id = $1
+            UNION ALL
+            SELECT 'report', COALESCE(mr.created_at, r.date::timestamp), mr.id,
+                   'Report added for ' || r.title,
+                   mr.report_url
+            FROM medical_reports mr
+            JOIN medical_records r ON r.id = mr.record_id
+            WHERE r.patient_id = $1
+            UNION ALL
+            SELECT 'consent', c.created_at, c.id,
+                   -- the row holds the current state; the first audited change tells us what it started as
+                   'Consent ' || CASE WHEN COALESCE(
+                       (SELECT a.action = 'consent.revoked' FROM audit_logs a
+                        WHERE a.entity_type = 'consent' AND a.entity_id = c.id
+                        ORDER BY a.created_at, a.id LIMIT 1),
+                       c.granted
+                   ) THEN 'granted' ELSE 'declined' END || ': ' || c.consent_type,
+                   '/consents/' || c.id
+            FROM consents c
+            WHERE c.patient_id = $1 AND c.created_at IS NOT NULL
+            UNION ALL
+            SELECT CASE a.action
+                       WHEN 'patient.updated' THEN 'profile_edit'
+                       WHEN 'patient.status_changed' THEN 'status_change'
+                       ELSE 'consent'
+                   END,
+                   a.created_at, a.id,
+                   CASE a.action
+                       WHEN 'patient.updated' THEN 'Profile updated: ' || COALESCE(
+                           (SELECT string_agg(k, ', ' ORDER BY k) FROM jsonb_object_keys(a.details->'changes') k), '')
+                       WHEN 'patient.status_changed' THEN 'Status changed from '
+                           || COALESCE(a.details->>'from', 'none') || ' to ' || COALESCE(a.details->>'to', 'none')
+                       WHEN 'consent.granted' THEN 'Consent granted: ' || COALESCE(a.details->>'consent_type', '')
+                       ELSE 'Consent revoked: ' || COALESCE(a.details->>'consent_type', '')
+                   END,
+                   CASE a.entity_type
+                       WHEN 'consent' THEN '/consents/' || a.entity_id
+                       ELSE '/patients/' || a.patient_id
+                   END
+            FROM audit_logs a
+            WHERE a.patient_id = $1
+              AND a.created_at IS NOT NULL
+              AND a.action IN ('patient.updated', 'patient.status_changed', 'consent.granted', 'consent.revoked')
+            UNION ALL
+            SELECT 'appointment', a.starts_at, a.id,
+                   initcap(replace(a.appointment_type, '_', ' ')) || ' appointment '
+                       || CASE a.status
+                              WHEN 'booked' THEN 'booked'
+                              WHEN 'checked_in' THEN '(checked in)'
+                              WHEN 'seen' THEN '(seen)'
+                              WHEN 'no_show' THEN '(missed)'
+                              ELSE '(cancelled)'
+                          END
+                       || COALESCE(' at ' || f.name, ''),
+                   '/appointments/' || a.id
+            FROM appointments a
+            LEFT JOIN facilities f ON f.id = a.facility_id
+            WHERE a.patient_id = $1
+        )
+        SELECT event_type, occurred_at, source_id, summary, link
+        FROM events
+        WHERE ($2::text[] IS NULL OR event_type = ANY($2))
+          AND ($3::timestamp IS NULL OR (occurred_at, event_type || ':' || source_id) < ($3, $4))
+        ORDER BY occurred_at DESC, event_type || ':' || source_id DESC
+        LIMIT $5
+        "#,
+    )
+    .bind(id)
+    .bind(types)
+    .bind(cursor.as_ref().map(|c| c.occurred_at))
+    .bind(cursor.as_ref().map(|c| c.entry_key.clone()))
+    .bind(limit + 1)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| {
+        eprintln!("[PATIENT TIMELINE ERROR] DB error: {:?}", e);
+        ServiceError::InternalServerError
+    })?;
+    tx.commit().await.map_err(|_| ServiceError::InternalServerError)?;
+
+    let next_cursor = if entries.len() as i64 > limit {
+        entries.truncate(limit as usize);
+        entries.last().map(TimelineCursor::encode)
+    } else {
+        None
+    };
+
+    Ok(Json(TimelineResponse { entries, next_cursor }))
+}
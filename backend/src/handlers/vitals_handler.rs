@@ -0,0 +1,611 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+use std::collections::HashMap;
+
+use crate::handlers::records_handler::authorize_record;
+use crate::models::error::ServiceError;
+use crate::models::patient::Gender;
+use crate::models::vital::{Consciousness, EarlyWarningRisk, VitalKind, VitalObservation, VitalReferenceRange, VitalSet};
+use crate::services::access::RecordAction;
+use crate::services::alerts::{notify_care_team, AlertEntry};
+use crate::services::audit::{self, AuditEntry};
+use crate::services::auth::AuthUser;
+use crate::services::early_warning::{self, News2Inputs, News2Score, ALERT_RISK, NEWS2_MIN_AGE_MONTHS};
+use crate::services::tenancy;
+use crate::services::validation::{field_error, normalize_optional_enum, TextEnum, ValidationErrors};
+use crate::services::vitals::{
+    age_in_months, applicable_range, bmi, canonical_unit, flag_value, plausible_range, to_canonical,
+};
+
+const DEFAULT_SET_LIMIT: i64 = 20;
+const MAX_SET_LIMIT: i64 = 100;
+const DEFAULT_DETERIORATION_HOURS: i64 = 24;
+const MAX_DETERIORATION_HOURS: i64 = 14 * 24;
+/// Adults' height is stable enough to reuse the last measurement when deriving BMI.
+const ADULT_AGE_MONTHS: i32 = 18 * 12;
+
+#[derive(Deserialize)]
+pub struct ObservationInput {
+    pub kind: String,
+    pub value: f64,
+    /// Defaults to the kind's canonical unit; common alternatives (°F, lb, in, mg/dL) are converted.
+    pub unit: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateVitalSetRequest {
+    /// When the measurements were taken; defaults to now.
+    pub observed_at: Option<NaiveDateTime>,
+    pub record_id: Option<i32>,
+    pub observations: Vec<ObservationInput>,
+    /// Whether the patient was on supplemental oxygen; scored by NEWS2.
+    pub supplemental_oxygen: Option<bool>,
+    /// ACVPU: `alert`, `new_confusion`, `voice`, `pain` or `unresponsive`.
+    pub consciousness: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VitalSetResponse {
+    #[serde(flatten)]
+    pub set: VitalSet,
+    pub observations: Vec<VitalObservation>,
+}
+
+#[derive(Deserialize)]
+pub struct VitalSetQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct VitalSeriesQuery {
+    /// Comma-separated kinds; all kinds with data when omitted.
+    pub kinds: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct DeterioratingQuery {
+    /// Only patients whose latest score is this recent (default 24, at most 336).
+    pub hours: Option<i64>,
+    /// Lowest risk band to include; defaults to `low_medium`, the band that raises alerts.
+    pub min_risk: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, FromRow)]
+pub struct DeterioratingPatient {
+    pub patient_id: i32,
+    pub patient_name: String,
+    pub village: Option<String>,
+    pub set_id: i32,
+    pub observed_at: NaiveDateTime,
+    pub news2_score: i32,
+    pub news2_risk: String,
+    pub news2_missing: Option<Vec<String>>,
+    /// The score before the latest one, to show the trend.
+    pub previous_score: Option<i32>,
+    pub critical_flag: Option<bool>,
+    pub critical_reason: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct VitalPoint {
+    pub observed_at: NaiveDateTime,
+    pub value: f64,
+    pub flag: Option<String>,
+    pub set_id: i32,
+}
+
+#[derive(Serialize)]
+pub struct VitalSeries {
+    pub kind: String,
+    pub unit: String,
+    pub points: Vec<VitalPoint>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[VITALS ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+async fn patient_demographics(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: i32,
+    id: i32,
+) -> Result<(NaiveDate, String), ServiceError> {
+    sqlx::query_as::<_, (NaiveDate, String)>(
+        "SELECT date_of_birth, gender FROM patients WHERE id = $1 AND tenant_id = $2",
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_error)?
+    .ok_or(ServiceError::NotFound)
+}
+
+/// Canonicalise and sanity-check the submitted measurements.
+fn validate_observations(inputs: &[ObservationInput]) -> Result<Vec<(VitalKind, f64)>, ServiceError> {
+    let mut errors = ValidationErrors::default();
+    let mut values: Vec<(VitalKind, f64)> = Vec::new();
+    if inputs.is_empty() {
+        errors.add("observations", "must contain at least one observation");
+    }
+    for (i, input) in inputs.iter().enumerate() {
+        let field = format!("observations[{}]", i);
+        let Some(kind) = VitalKind::parse(&input.kind) else {
+            errors.add(format!("{}.kind", field), format!("must be one of: {}", VitalKind::allowed()));
+            continue;
+        };
+        if values.iter().any(|(seen, _)| *seen == kind) {
+            errors.add(format!("{}.kind", field), format!("{} is given more than once", kind.as_str()));
+            continue;
+        }
+        let unit = input.unit.as_deref().unwrap_or(canonical_unit(kind));
+        let Some(value) = to_canonical(kind, input.value, unit) else {
+            errors.add(format!("{}.unit", field), format!("{} cannot be recorded in {}", kind.as_str(), unit));
+            continue;
+        };
+        let (min, max) = plausible_range(kind);
+        if !value.is_finite() || value < min || value > max {
+            errors.add(
+                format!("{}.value", field),
+                format!("must be between {} and {} {}", min, max, canonical_unit(kind)),
+            );
+            continue;
+        }
+        values.push((kind, value));
+    }
+    let find = |kind| values.iter().find(|(k, _)| *k == kind).map(|(_, v)| *v);
+    if let (Some(systolic), Some(diastolic)) = (find(VitalKind::SystolicBp), find(VitalKind::DiastolicBp)) {
+        if diastolic >= systolic {
+            errors.add("observations", "diastolic_bp must be lower than systolic_bp");
+        }
+    }
+    errors.into_result()?;
+    Ok(values)
+}
+
+async fn latest_height(tx: &mut Transaction<'_, Postgres>, patient_id: i32) -> Result<Option<f64>, ServiceError> {
+    sqlx::query_scalar(
+        "SELECT value FROM vital_observations WHERE patient_id = $1 AND kind = 'height'
+         ORDER BY observed_at DESC LIMIT 1",
+    )
+    .bind(patient_id)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_error)
+}
+
+async fn load_observations(
+    tx: &mut Transaction<'_, Postgres>,
+    set_ids: &[i32],
+) -> Result<HashMap<i32, Vec<VitalObservation>>, ServiceError> {
+    let rows = sqlx::query_as::<_, VitalObservation>(
+        "SELECT * FROM vital_observations WHERE set_id = ANY($1) ORDER BY set_id, id",
+    )
+    .bind(set_ids)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    let mut grouped: HashMap<i32, Vec<VitalObservation>> = HashMap::new();
+    for row in rows {
+        grouped.entry(row.set_id).or_default().push(row);
+    }
+    Ok(grouped)
+}
+
+async fn insert_observation(
+    tx: &mut Transaction<'_, Postgres>,
+    set: &VitalSet,
+    kind: VitalKind,
+    value: f64,
+    derived: bool,
+    range: Option<&VitalReferenceRange>,
+) -> Result<VitalObservation, ServiceError> {
+    sqlx::query_as::<_, VitalObservation>(
+        "INSERT INTO vital_observations
+             (set_id, patient_id, kind, value, unit, observed_at, derived, reference_low, reference_high, flag)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+         RETURNING *",
+    )
+    .bind(set.id)
+    .bind(set.patient_id)
+    .bind(kind.as_str())
+    .bind(value)
+    .bind(canonical_unit(kind))
+    .bind(set.observed_at)
+    .bind(derived)
+    .bind(range.and_then(|r| r.low))
+    .bind(range.and_then(|r| r.high))
+    .bind(range.map(|r| flag_value(r, value).as_str()))
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(db_error)
+}
+
+/// Keep the patient's critical flag in line with their latest score, and alert the care team
+/// when the risk band rises to `ALERT_RISK` or beyond. A flag set by staff is left alone, and
+/// back-dated sets older than the latest score change nothing.
+async fn apply_early_warning(
+    tx: &mut Transaction<'_, Postgres>,
+    user: &AuthUser,
+    set: &VitalSet,
+    score: &News2Score,
+) -> Result<(), ServiceError> {
+    let (flagged, source) = sqlx::query_as::<_, (Option<bool>, Option<String>)>(
+        "SELECT critical_flag, critical_flag_source FROM patients WHERE id = $1 FOR UPDATE",
+    )
+    .bind(set.patient_id)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    let earlier = sqlx::query_as::<_, (NaiveDateTime, Option<String>)>(
+        "SELECT observed_at, news2_risk FROM vital_sets
+         WHERE patient_id = $1 AND id <> $2 AND news2_score IS NOT NULL
+         ORDER BY observed_at DESC, id DESC LIMIT 1",
+    )
+    .bind(set.patient_id)
+    .bind(set.id)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    let previous_risk = match earlier {
+        Some((observed_at, _)) if observed_at > set.observed_at => return Ok(()),
+        Some((_, risk)) => risk.as_deref().and_then(EarlyWarningRisk::parse),
+        None => None,
+    };
+
+    let manual = source.as_deref() == Some("manual");
+    if score.risk >= ALERT_RISK {
+        let reason = format!("{} (observed {})", score.summary(), set.observed_at.format("%Y-%m-%d %H:%M"));
+        if !manual {
+            sqlx::query(
+                "UPDATE patients SET critical_flag = true, critical_reason = $2,
+                     critical_flag_source = 'early_warning', updated_at = now()
+                 WHERE id = $1",
+            )
+            .bind(set.patient_id)
+            .bind(&reason)
+            .execute(&mut **tx)
+            .await
+            .map_err(db_error)?;
+            if flagged != Some(true) {
+                audit::record(&mut **tx, AuditEntry {
+                    user_id: Some(user.id),
+                    action: "patient.critical_flag_set",
+                    entity_type: "patient",
+                    entity_id: set.patient_id,
+                    patient_id: Some(set.patient_id),
+                    details: serde_json::json!({ "vital_set_id": set.id, "reason": reason }),
+                })
+                .await
+                .map_err(db_error)?;
+            }
+        }
+        if previous_risk.is_none_or(|previous| score.risk > previous) {
+            notify_care_team(tx, AlertEntry {
+                patient_id: set.patient_id,
+                vital_set_id: Some(set.id),
+                kind: "deterioration",
+                severity: score.risk.as_str(),
+                message: &reason,
+            })
+            .await
+            .map_err(db_error)?;
+        }
+    } else if source.as_deref() == Some("early_warning") {
+        sqlx::query(
+            "UPDATE patients SET critical_flag = false, critical_reason = NULL,
+                 critical_flag_source = NULL, updated_at = now()
+             WHERE id = $1",
+        )
+        .bind(set.patient_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error)?;
+        audit::record(&mut **tx, AuditEntry {
+            user_id: Some(user.id),
+            action: "patient.critical_flag_cleared",
+            entity_type: "patient",
+            entity_id: set.patient_id,
+            patient_id: Some(set.patient_id),
+            details: serde_json::json!({ "vital_set_id": set.id, "reason": score.summary() }),
+        })
+        .await
+        .map_err(db_error)?;
+    }
+    Ok(())
+}
+
+/// Record a set of observations. Values are converted to canonical units and flagged
+/// against the reference range for the patient's age and sex at the time of measurement;
+/// BMI is derived when weight and height are known, and adults get a NEWS2 score.
+pub async fn create_vital_set(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Json(mut payload): Json<CreateVitalSetRequest>,
+) -> Result<(StatusCode, Json<VitalSetResponse>), ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let (date_of_birth, gender) = patient_demographics(&mut tx, user.tenant_id, patient_id).await?;
+    let observed_at = payload.observed_at.unwrap_or_else(|| Utc::now().naive_utc());
+    if observed_at > Utc::now().naive_utc() + chrono::Duration::minutes(5) {
+        return Err(field_error("observed_at", "cannot be in the future"));
+    }
+    if observed_at.date() < date_of_birth {
+        return Err(field_error("observed_at", "cannot be before the patient's date of birth"));
+    }
+    if let Some(record_id) = payload.record_id {
+        let record = authorize_record(&mut *tx, record_id, &user, RecordAction::Write).await?;
+        if record.patient_id != patient_id {
+            return Err(field_error("record_id", "belongs to a different patient"));
+        }
+    }
+    let mut values = validate_observations(&payload.observations)?;
+    let mut errors = ValidationErrors::default();
+    normalize_optional_enum::<Consciousness>(&mut errors, "consciousness", &mut payload.consciousness);
+    errors.into_result()?;
+
+    let age_months = age_in_months(date_of_birth, observed_at.date());
+    let has = |values: &[(VitalKind, f64)], kind| values.iter().find(|(k, _)| *k == kind).map(|(_, v)| *v);
+    let mut derived_bmi = None;
+    if let (Some(weight), None) = (has(&values, VitalKind::Weight), has(&values, VitalKind::Bmi)) {
+        let height = match has(&values, VitalKind::Height) {
+            Some(height) => Some(height),
+            None if age_months >= ADULT_AGE_MONTHS => latest_height(&mut tx, patient_id).await?,
+            None => None,
+        };
+        derived_bmi = height.map(|height| bmi(weight, height));
+    }
+    if let Some(value) = derived_bmi {
+        values.push((VitalKind::Bmi, value));
+    }
+
+    let news2 = if age_months >= NEWS2_MIN_AGE_MONTHS {
+        let consciousness = payload.consciousness.as_deref().and_then(Consciousness::parse);
+        early_warning::score(&News2Inputs::from_observations(&values, payload.supplemental_oxygen, consciousness))
+    } else {
+        None
+    };
+
+    let sex = Gender::parse(&gender).map(|g| g.as_str()).unwrap_or("unknown");
+    let ranges = sqlx::query_as::<_, VitalReferenceRange>("SELECT * FROM vital_reference_ranges")
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    let set = sqlx::query_as::<_, VitalSet>(
+        "INSERT INTO vital_sets (patient_id, record_id, observed_at, recorded_by, supplemental_oxygen, consciousness,
+             news2_score, news2_risk, news2_missing)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING *",
+    )
+    .bind(patient_id)
+    .bind(payload.record_id)
+    .bind(observed_at)
+    .bind(user.id)
+    .bind(payload.supplemental_oxygen)
+    .bind(&payload.consciousness)
+    .bind(news2.as_ref().map(|score| score.total))
+    .bind(news2.as_ref().map(|score| score.risk.as_str()))
+    .bind(news2.as_ref().map(|score| score.missing.clone()))
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    let mut observations = Vec::with_capacity(values.len());
+    for (kind, value) in values {
+        let derived = kind == VitalKind::Bmi && derived_bmi.is_some();
+        let range = applicable_range(&ranges, kind, sex, age_months);
+        observations.push(insert_observation(&mut tx, &set, kind, value, derived, range).await?);
+    }
+
+    let flagged: Vec<_> = observations
+        .iter()
+        .filter(|o| o.flag.as_deref().is_some_and(|f| f != "normal"))
+        .map(|o| serde_json::json!({ "kind": o.kind, "value": o.value, "flag": o.flag }))
+        .collect();
+    audit::record(&mut *tx, AuditEntry {
+        user_id: Some(user.id),
+        action: "vitals.recorded",
+        entity_type: "vital_set",
+        entity_id: set.id,
+        patient_id: Some(patient_id),
+        details: serde_json::json!({
+            "record_id": set.record_id,
+            "kinds": observations.iter().map(|o| o.kind.as_str()).collect::<Vec<_>>(),
+            "flagged": flagged,
+            "news2_score": set.news2_score,
+        }),
+    })
+    .await
+    .map_err(db_error)?;
+    if let Some(score) = &news2 {
+        apply_early_warning(&mut tx, &user, &set, score).await?;
+    }
+    tx.commit().await.map_err(db_error)?;
+
+    Ok((StatusCode::CREATED, Json(VitalSetResponse { set, observations })))
+}
+
+/// Observation sets for a patient, newest first.
+pub async fn list_vital_sets(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Query(params): Query<VitalSetQuery>,
+) -> Result<Json<Vec<VitalSetResponse>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    patient_demographics(&mut tx, user.tenant_id, patient_id).await?;
+    let limit = params.limit.unwrap_or(DEFAULT_SET_LIMIT).clamp(1, MAX_SET_LIMIT);
+    let sets = sqlx::query_as::<_, VitalSet>(
+        "SELECT * FROM vital_sets
+         WHERE patient_id = $1
+           AND ($2::date IS NULL OR observed_at >= $2::date)
+           AND ($3::date IS NULL OR observed_at < $3::date + 1)
+         ORDER BY observed_at DESC, id DESC
+         LIMIT $4",
+    )
+    .bind(patient_id)
+    .bind(params.from)
+    .bind(params.to)
+    .bind(limit)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    let ids: Vec<i32> = sets.iter().map(|s| s.id).collect();
+    let mut grouped = load_observations(&mut tx, &ids).await?;
+    tx.commit().await.map_err(db_error)?;
+    let response = sets
+        .into_iter()
+        .map(|set| {
+            let observations = grouped.remove(&set.id).unwrap_or_default();
+            VitalSetResponse { set, observations }
+        })
+        .collect();
+    Ok(Json(response))
+}
+
+/// The most recent value of each kind, whichever set it came from.
+pub async fn get_latest_vitals(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<VitalObservation>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    patient_demographics(&mut tx, user.tenant_id, patient_id).await?;
+    let latest = sqlx::query_as::<_, VitalObservation>(
+        "SELECT DISTINCT ON (kind) * FROM vital_observations
+         WHERE patient_id = $1
+         ORDER BY kind, observed_at DESC, id DESC",
+    )
+    .bind(patient_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(latest))
+}
+
+/// Chronological series per kind, for charting.
+pub async fn get_vital_series(
+    user: AuthUser,
+    Path(patient_id): Path<i32>,
+    State(pool): State<PgPool>,
+    Query(params): Query<VitalSeriesQuery>,
+) -> Result<Json<Vec<VitalSeries>>, ServiceError> {
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    patient_demographics(&mut tx, user.tenant_id, patient_id).await?;
+    let kinds: Option<Vec<String>> = match params.kinds.as_deref() {
+        Some(raw) => {
+            let mut kinds = Vec::new();
+            for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+                let kind = VitalKind::parse(part)
+                    .ok_or_else(|| field_error("kinds", format!("must be one of: {}", VitalKind::allowed())))?;
+                kinds.push(kind.as_str().to_string());
+            }
+            Some(kinds)
+        }
+        None => None,
+    };
+
+    let rows = sqlx::query_as::<_, VitalObservation>(
+        "SELECT * FROM vital_observations
+         WHERE patient_id = $1
+           AND ($2::text[] IS NULL OR kind = ANY($2))
+           AND ($3::date IS NULL OR observed_at >= $3::date)
+           AND ($4::date IS NULL OR observed_at < $4::date + 1)
+         ORDER BY kind, observed_at, id",
+    )
+    .bind(patient_id)
+    .bind(kinds)
+    .bind(params.from)
+    .bind(params.to)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    let mut series: Vec<VitalSeries> = Vec::new();
+    for row in rows {
+        let point = VitalPoint { observed_at: row.observed_at, value: row.value, flag: row.flag, set_id: row.set_id };
+        match series.last_mut() {
+            Some(current) if current.kind == row.kind => current.points.push(point),
+            _ => series.push(VitalSeries { kind: row.kind, unit: row.unit, points: vec![point] }),
+        }
+    }
+    Ok(Json(series))
+}
+
+/// Patients of the caller's tenant whose most recent NEWS2 score is recent and at or above
+/// `min_risk`, highest score first.
+pub async fn list_deteriorating_patients(
+    user: AuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<DeterioratingQuery>,
+) -> Result<Json<Vec<DeterioratingPatient>>, ServiceError> {
+    let hours = params.hours.unwrap_or(DEFAULT_DETERIORATION_HOURS).clamp(1, MAX_DETERIORATION_HOURS);
+    let min_risk = match params.min_risk.as_deref() {
+        Some(raw) => EarlyWarningRisk::parse(raw)
+            .ok_or_else(|| field_error("min_risk", format!("must be one of: {}", EarlyWarningRisk::allowed())))?,
+        None => ALERT_RISK,
+    };
+    let risks: Vec<&str> = EarlyWarningRisk::ALL.iter().filter(|r| **r >= min_risk).map(|r| r.as_str()).collect();
+    let since = Utc::now().naive_utc() - chrono::Duration::hours(hours);
+    let limit = params.limit.unwrap_or(DEFAULT_SET_LIMIT).clamp(1, MAX_SET_LIMIT);
+
+    let mut tx = tenancy::begin(&pool, user.tenant_id).await.map_err(db_error)?;
+    let patients = sqlx::query_as::<_, DeterioratingPatient>(
+        "WITH latest AS (
+             SELECT DISTINCT ON (patient_id) patient_id, id AS set_id, observed_at, news2_score, news2_risk, news2_missing
+             FROM vital_sets
+             WHERE news2_score IS NOT NULL
+             ORDER BY patient_id, observed_at DESC, id DESC
+         )
+         SELECT l.patient_id, p.first_name || ' ' || p.last_name AS patient_name, p.village,
+                l.set_id, l.observed_at, l.news2_score, l.news2_risk, l.news2_missing,
+                (SELECT prev.news2_score FROM vital_sets prev
+                  WHERE prev.patient_id = l.patient_id AND prev.news2_score IS NOT NULL
+                    AND (prev.observed_at, prev.id) < (l.observed_at, l.set_id)
+                  ORDER BY prev.observed_at DESC, prev.id DESC LIMIT 1) AS previous_score,
+                p.critical_flag, p.critical_reason
+         FROM latest l
+         JOIN patients p ON p.id = l.patient_id AND p.tenant_id = $4
+         WHERE l.observed_at >= $1 AND l.news2_risk = ANY($2) AND p.status <> 'deceased'
+         ORDER BY l.news2_score DESC, l.observed_at DESC
+         LIMIT $3",
+    )
+    .bind(since)
+    .bind(&risks)
+    .bind(limit)
+    .bind(user.tenant_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+    Ok(Json(patients))
+}
+
+/// The reference ranges observations are flagged against.
+pub async fn list_vital_reference_ranges(
+    _user: AuthUser,
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<VitalReferenceRange>>, ServiceError> {
+    let ranges = sqlx::query_as::<_, VitalReferenceRange>(
+        "SELECT * FROM vital_reference_ranges ORDER BY kind, sex NULLS FIRST, min_age_months",
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+    Ok(Json(ranges))
+}
@@ -1,16 +1,15 @@
-use axum::{routing::get, Router};
 use sqlx::PgPool;
 use std::net::SocketAddr;
 use tower_http::trace::TraceLayer;
 use tower_http::cors::{CorsLayer, Any};
-use tracing_subscriber;
 
 mod routes;
 mod config;
 mod handlers;
 mod models;
+mod services;
 
-use crate::routes::{patients, records, auth, analytics, administration, consents};
+use crate::config::state::AppState;
 
 #[tokio::main]
 async fn main() {
@@ -23,19 +22,17 @@ async fn main() {
     let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let pool = PgPool::connect(&db_url).await.expect("Failed to connect to DB");
 
+    let state = AppState::from_env(pool);
+    if let Err(e) = state.terminology.load_all(&state.pool).await {
+        tracing::error!("Failed to load terminology code tables: {}", e);
+    }
+
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
         .allow_headers(Any);
 
-    let app = Router::new()
-        .route("/health", get(health))
-        .merge(auth::routes().with_state(pool.clone()))
-        .merge(patients::routes().with_state(pool.clone()))
-        .merge(records::routes().with_state(pool.clone()))
-        .merge(analytics::routes().with_state(pool.clone()))
-        .merge(administration::routes().with_state(pool.clone()))
-        .merge(consents::routes().with_state(pool.clone()))
+    let app = routes::app(state)
         .layer(TraceLayer::new_for_http())
         .layer(cors);
 
@@ -45,7 +42,3 @@ async fn main() {
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
     axum::serve(listener, app).await.unwrap();
 }
-
-async fn health() -> &'static str {
-    "OK"
-}
\ No newline at end of file
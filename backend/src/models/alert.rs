@@ -0,0 +1,32 @@
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+
+/// A staff member responsible for a patient; care team members receive the patient's alerts.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct CareTeamMember {
+    pub patient_id: i32,
+    pub user_id: i32,
+    pub email: String,
+    pub staff_role: String,
+    /// Free-text role on this patient's team, e.g. "primary clinician" or "ASHA worker".
+    pub role: Option<String>,
+    pub assigned_by: Option<i32>,
+    pub assigned_at: NaiveDateTime,
+}
+
+/// An alert addressed to one care team member, or to nobody in particular when the patient
+/// has no care team.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct ClinicalAlert {
+    pub id: i32,
+    pub patient_id: i32,
+    pub vital_set_id: Option<i32>,
+    pub recipient_id: Option<i32>,
+    pub kind: String,
+    pub severity: String,
+    pub message: String,
+    pub created_at: NaiveDateTime,
+    pub acknowledged_at: Option<NaiveDateTime>,
+    pub acknowledged_by: Option<i32>,
+}
@@ -0,0 +1,178 @@
+use serde::{Serialize, Deserialize};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use sqlx::FromRow;
+
+use crate::services::validation::TextEnum;
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct Appointment {
+    pub id: i32,
+    pub patient_id: i32,
+    pub provider_id: Option<i32>,
+    pub facility_id: Option<i32>,
+    pub appointment_type: String,
+    pub starts_at: NaiveDateTime,
+    pub ends_at: NaiveDateTime,
+    pub status: String,
+    pub reason: Option<String>,
+    pub notes: Option<String>,
+    pub booked_by: Option<i32>,
+    pub booked_at: NaiveDateTime,
+    pub checked_in_at: Option<NaiveDateTime>,
+    pub seen_at: Option<NaiveDateTime>,
+    pub cancelled_reason: Option<String>,
+    pub reschedule_count: i32,
+    pub updated_at: NaiveDateTime,
+}
+
+/// A weekly session a provider works at a facility.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct ProviderAvailability {
+    pub id: i32,
+    pub provider_id: i32,
+    pub facility_id: i32,
+    /// ISO weekday, 1 = Monday.
+    pub weekday: i16,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub slot_minutes: i32,
+    pub valid_from: Option<NaiveDate>,
+    pub valid_until: Option<NaiveDate>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+/// An open slot in a provider's session.
+#[derive(Serialize, Debug, Clone)]
+pub struct Slot {
+    pub provider_id: i32,
+    pub facility_id: i32,
+    pub starts_at: NaiveDateTime,
+    pub ends_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppointmentStatus {
+    Booked,
+    CheckedIn,
+    Seen,
+    NoShow,
+    Cancelled,
+}
+
+impl TextEnum for AppointmentStatus {
+    const ALL: &'static [Self] = &[
+        AppointmentStatus::Booked,
+        AppointmentStatus::CheckedIn,
+        AppointmentStatus::Seen,
+        AppointmentStatus::NoShow,
+        AppointmentStatus::Cancelled,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            AppointmentStatus::Booked => "booked",
+            AppointmentStatus::CheckedIn => "checked_in",
+            AppointmentStatus::Seen => "seen",
+            AppointmentStatus::NoShow => "no_show",
+            AppointmentStatus::Cancelled => "cancelled",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            AppointmentStatus::Booked => &["scheduled"],
+            AppointmentStatus::CheckedIn => &["checkedin", "checked-in", "arrived"],
+            AppointmentStatus::Seen => &["completed", "attended"],
+            AppointmentStatus::NoShow => &["noshow", "no-show", "missed"],
+            AppointmentStatus::Cancelled => &["canceled"],
+        }
+    }
+}
+
+impl AppointmentStatus {
+    /// Statuses that hold the provider's time and count for conflict detection.
+    pub const ACTIVE: &'static [&'static str] = &["booked", "checked_in", "seen"];
+}
+
+/// A step in the booked -> checked-in -> seen workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppointmentAction {
+    CheckIn,
+    See,
+    MarkNoShow,
+    Cancel,
+    Reschedule,
+}
+
+impl AppointmentAction {
+    /// The status an appointment in `from` moves to, or `None` if the step is not allowed.
+    pub fn apply(self, from: AppointmentStatus) -> Option<AppointmentStatus> {
+        use AppointmentStatus::*;
+        match (self, from) {
+            (AppointmentAction::CheckIn, Booked) => Some(CheckedIn),
+            (AppointmentAction::See, Booked | CheckedIn) => Some(Seen),
+            (AppointmentAction::MarkNoShow, Booked) => Some(NoShow),
+            (AppointmentAction::Cancel, Booked | CheckedIn) => Some(Cancelled),
+            (AppointmentAction::Reschedule, Booked) => Some(Booked),
+            _ => None,
+        }
+    }
+
+    pub fn past_tense(self) -> &'static str {
+        match self {
+            AppointmentAction::CheckIn => "checked in",
+            AppointmentAction::See => "marked as seen",
+            AppointmentAction::MarkNoShow => "marked as a no-show",
+            AppointmentAction::Cancel => "cancelled",
+            AppointmentAction::Reschedule => "rescheduled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppointmentType {
+    Consultation,
+    FollowUp,
+    Antenatal,
+    Immunization,
+    GrowthMonitoring,
+    Procedure,
+    HomeVisit,
+}
+
+impl TextEnum for AppointmentType {
+    const ALL: &'static [Self] = &[
+        AppointmentType::Consultation,
+        AppointmentType::FollowUp,
+        AppointmentType::Antenatal,
+        AppointmentType::Immunization,
+        AppointmentType::GrowthMonitoring,
+        AppointmentType::Procedure,
+        AppointmentType::HomeVisit,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            AppointmentType::Consultation => "consultation",
+            AppointmentType::FollowUp => "follow_up",
+            AppointmentType::Antenatal => "antenatal",
+            AppointmentType::Immunization => "immunization",
+            AppointmentType::GrowthMonitoring => "growth_monitoring",
+            AppointmentType::Procedure => "procedure",
+            AppointmentType::HomeVisit => "home_visit",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            AppointmentType::Consultation => &["visit", "opd"],
+            AppointmentType::FollowUp => &["followup", "follow-up", "review"],
+            AppointmentType::Antenatal => &["anc"],
+            AppointmentType::Immunization => &["immunisation", "vaccination"],
+            AppointmentType::GrowthMonitoring => &["growthmonitoring", "growth-monitoring", "growth"],
+            AppointmentType::Procedure => &[],
+            AppointmentType::HomeVisit => &["homevisit", "home-visit"],
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A file attached to a medical record. The content is shared by digest in `attachment_blobs`.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct RecordAttachment {
+    pub id: i32,
+    pub record_id: i32,
+    pub sha256: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub uploaded_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+/// A resumable upload in progress; clients resume from `received_bytes`.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct AttachmentUpload {
+    pub id: Uuid,
+    pub record_id: i32,
+    pub filename: String,
+    pub total_bytes: i64,
+    pub received_bytes: i64,
+    pub uploaded_by: i32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
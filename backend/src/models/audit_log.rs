@@ -3,6 +3,7 @@
 use serde::{Serialize, Deserialize};
 
 use chrono::NaiveDateTime;
+use serde_json::Value;
 use sqlx::FromRow;
 
 #[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
@@ -11,4 +12,8 @@ pub struct AuditLog {
     pub user_id: Option<i32>,
     pub action: String,
     pub created_at: Option<NaiveDateTime>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<i32>,
+    pub patient_id: Option<i32>,
+    pub details: Option<Value>,
 }
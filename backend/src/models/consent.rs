@@ -12,4 +12,5 @@ pub struct Consent {
     pub consent_type: String,
     pub granted: bool,
     pub created_at: Option<NaiveDateTime>,
+    pub tenant_id: i32,
 }
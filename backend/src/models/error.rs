@@ -5,15 +5,38 @@ use axum::{
 };
 use serde::Serialize;
 
+use crate::models::prescription::{MedicationWarning, WarningSeverity};
+use crate::services::validation::FieldError;
+
 #[derive(Debug, Serialize)]
 pub struct ErrorResponse {
     pub error: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ValidationErrorResponse {
+    pub error: String,
+    pub details: Vec<FieldError>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MedicationWarningResponse {
+    pub error: String,
+    pub warnings: Vec<MedicationWarning>,
+}
+
 #[derive(Debug)]
 pub enum ServiceError {
     Unauthorized,
+    Forbidden,
     NotFound,
+    BadRequest(String),
+    Conflict(String),
+    PayloadTooLarge(String),
+    UnsupportedMediaType(String),
+    Validation(Vec<FieldError>),
+    /// Prescribing safety checks failed; blocking warnings can never be overridden.
+    MedicationWarnings(Vec<MedicationWarning>),
     InternalServerError,
 }
 
@@ -21,7 +44,25 @@ impl IntoResponse for ServiceError {
     fn into_response(self) -> Response {
         let (status, error_message) = match self {
             ServiceError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized".to_string()),
+            ServiceError::Forbidden => (StatusCode::FORBIDDEN, "Forbidden".to_string()),
             ServiceError::NotFound => (StatusCode::NOT_FOUND, "Not Found".to_string()),
+            ServiceError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ServiceError::Conflict(message) => (StatusCode::CONFLICT, message),
+            ServiceError::PayloadTooLarge(message) => (StatusCode::PAYLOAD_TOO_LARGE, message),
+            ServiceError::UnsupportedMediaType(message) => (StatusCode::UNSUPPORTED_MEDIA_TYPE, message),
+            ServiceError::Validation(details) => {
+                let body = Json(ValidationErrorResponse { error: "Validation failed".to_string(), details });
+                return (StatusCode::UNPROCESSABLE_ENTITY, body).into_response();
+            }
+            ServiceError::MedicationWarnings(warnings) => {
+                let error = if warnings.iter().any(|w| w.severity == WarningSeverity::Blocking) {
+                    "Prescription blocked by safety checks"
+                } else {
+                    "Prescription has warnings; resubmit with an override_reason to proceed"
+                };
+                let body = Json(MedicationWarningResponse { error: error.to_string(), warnings });
+                return (StatusCode::CONFLICT, body).into_response();
+            }
             ServiceError::InternalServerError => (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error".to_string()),
         };
         let body = Json(ErrorResponse { error: error_message });
@@ -0,0 +1,98 @@
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+
+use crate::services::validation::TextEnum;
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct Facility {
+    pub id: i32,
+    /// Short unique code, e.g. `PHC-RAMPUR`.
+    pub code: String,
+    pub name: String,
+    pub facility_type: String,
+    pub district: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct Department {
+    pub id: i32,
+    pub code: String,
+    pub name: String,
+    pub active: bool,
+}
+
+/// A facility a member of staff works at.
+#[derive(Serialize, Debug, Clone, FromRow)]
+pub struct FacilityAssignment {
+    pub facility_id: i32,
+    pub facility_name: String,
+    pub is_primary: bool,
+    pub assigned_by: Option<i32>,
+    pub assigned_at: NaiveDateTime,
+}
+
+/// A member of staff working at a facility.
+#[derive(Serialize, Debug, Clone, FromRow)]
+pub struct FacilityStaff {
+    pub user_id: i32,
+    pub email: String,
+    pub role: String,
+    pub department_id: Option<i32>,
+    pub department: Option<String>,
+    pub is_primary: bool,
+}
+
+/// Level of care, following the public health system's tiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FacilityType {
+    SubCentre,
+    Phc,
+    Chc,
+    SubDistrictHospital,
+    DistrictHospital,
+    /// Private or NGO-run clinic.
+    Clinic,
+    Other,
+}
+
+impl TextEnum for FacilityType {
+    const ALL: &'static [Self] = &[
+        FacilityType::SubCentre,
+        FacilityType::Phc,
+        FacilityType::Chc,
+        FacilityType::SubDistrictHospital,
+        FacilityType::DistrictHospital,
+        FacilityType::Clinic,
+        FacilityType::Other,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            FacilityType::SubCentre => "sub_centre",
+            FacilityType::Phc => "phc",
+            FacilityType::Chc => "chc",
+            FacilityType::SubDistrictHospital => "sub_district_hospital",
+            FacilityType::DistrictHospital => "district_hospital",
+            FacilityType::Clinic => "clinic",
+            FacilityType::Other => "other",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            FacilityType::SubCentre => &["sc", "subcentre", "subcenter", "hwc"],
+            FacilityType::Phc => &["primaryhealthcentre", "primaryhealthcenter"],
+            FacilityType::Chc => &["communityhealthcentre", "communityhealthcenter"],
+            FacilityType::SubDistrictHospital => &["sdh"],
+            FacilityType::DistrictHospital => &["dh", "hospital"],
+            FacilityType::Clinic => &["privateclinic", "ngoclinic"],
+            FacilityType::Other => &[],
+        }
+    }
+}
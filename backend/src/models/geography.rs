@@ -0,0 +1,73 @@
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDateTime;
+use serde_json::Value;
+use sqlx::FromRow;
+
+use crate::services::validation::TextEnum;
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct GeoArea {
+    pub id: i32,
+    pub level: String,
+    /// Unique code, by default the parent's code and the name, e.g. `UP-SITAPUR-MISRIKH-RAMPUR`.
+    pub code: String,
+    pub name: String,
+    pub parent_id: Option<i32>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// GeoJSON `Polygon` or `MultiPolygon` geometry.
+    pub boundary: Option<Value>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// An area on the way up from another one, e.g. a village's block.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct GeoAreaRef {
+    pub id: i32,
+    pub level: String,
+    pub code: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeoLevel {
+    State,
+    District,
+    Block,
+    Village,
+}
+
+impl TextEnum for GeoLevel {
+    const ALL: &'static [Self] = &[GeoLevel::State, GeoLevel::District, GeoLevel::Block, GeoLevel::Village];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            GeoLevel::State => "state",
+            GeoLevel::District => "district",
+            GeoLevel::Block => "block",
+            GeoLevel::Village => "village",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            GeoLevel::State => &["ut", "unionterritory"],
+            GeoLevel::District => &[],
+            GeoLevel::Block => &["tehsil", "taluk", "taluka", "mandal"],
+            GeoLevel::Village => &["gram"],
+        }
+    }
+}
+
+impl GeoLevel {
+    /// The level an area of this level sits in; states are the top.
+    pub fn parent(self) -> Option<GeoLevel> {
+        match self {
+            GeoLevel::State => None,
+            GeoLevel::District => Some(GeoLevel::State),
+            GeoLevel::Block => Some(GeoLevel::District),
+            GeoLevel::Village => Some(GeoLevel::Block),
+        }
+    }
+}
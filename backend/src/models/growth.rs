@@ -0,0 +1,130 @@
+use serde::{Serialize, Deserialize};
+use chrono::{NaiveDate, NaiveDateTime};
+use sqlx::FromRow;
+
+use crate::services::validation::TextEnum;
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct GrowthMeasurement {
+    pub id: i32,
+    pub patient_id: i32,
+    pub record_id: Option<i32>,
+    pub measured_on: NaiveDate,
+    pub age_days: i32,
+    pub weight_kg: Option<f64>,
+    /// Length when `measured_lying`, otherwise standing height.
+    pub height_cm: Option<f64>,
+    pub measured_lying: bool,
+    pub muac_cm: Option<f64>,
+    /// Bilateral pitting oedema.
+    pub oedema: bool,
+    pub weight_for_age_z: Option<f64>,
+    pub height_for_age_z: Option<f64>,
+    pub weight_for_height_z: Option<f64>,
+    pub acute_malnutrition: Option<String>,
+    pub stunting: Option<String>,
+    pub underweight: Option<String>,
+    pub notes: Option<String>,
+    pub recorded_by: Option<i32>,
+    pub recorded_at: NaiveDateTime,
+}
+
+/// Wasting, by weight-for-height, MUAC and oedema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AcuteMalnutrition {
+    Normal,
+    /// Moderate acute malnutrition.
+    Mam,
+    /// Severe acute malnutrition.
+    Sam,
+}
+
+impl TextEnum for AcuteMalnutrition {
+    const ALL: &'static [Self] = &[AcuteMalnutrition::Normal, AcuteMalnutrition::Mam, AcuteMalnutrition::Sam];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            AcuteMalnutrition::Normal => "normal",
+            AcuteMalnutrition::Mam => "mam",
+            AcuteMalnutrition::Sam => "sam",
+        }
+    }
+}
+
+/// Stunting (height-for-age) or underweight (weight-for-age): below -2 or -3 SD.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GrowthDeficit {
+    Normal,
+    Moderate,
+    Severe,
+}
+
+impl TextEnum for GrowthDeficit {
+    const ALL: &'static [Self] = &[GrowthDeficit::Normal, GrowthDeficit::Moderate, GrowthDeficit::Severe];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            GrowthDeficit::Normal => "normal",
+            GrowthDeficit::Moderate => "moderate",
+            GrowthDeficit::Severe => "severe",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrowthIndicator {
+    WeightForAge,
+    /// Length-for-age under two years, height-for-age after.
+    HeightForAge,
+    WeightForLength,
+    WeightForHeight,
+}
+
+impl TextEnum for GrowthIndicator {
+    const ALL: &'static [Self] = &[
+        GrowthIndicator::WeightForAge,
+        GrowthIndicator::HeightForAge,
+        GrowthIndicator::WeightForLength,
+        GrowthIndicator::WeightForHeight,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            GrowthIndicator::WeightForAge => "wfa",
+            GrowthIndicator::HeightForAge => "lhfa",
+            GrowthIndicator::WeightForLength => "wfl",
+            GrowthIndicator::WeightForHeight => "wfh",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            GrowthIndicator::WeightForAge => &["weightforage", "weight-for-age"],
+            GrowthIndicator::HeightForAge => &["heightforage", "height-for-age", "lengthforage", "hfa", "lfa"],
+            GrowthIndicator::WeightForLength => &["weightforlength", "weight-for-length"],
+            GrowthIndicator::WeightForHeight => &["weightforheight", "weight-for-height"],
+        }
+    }
+}
+
+/// One point of a child's growth curve.
+#[derive(Serialize, Debug, Clone)]
+pub struct GrowthPoint {
+    pub measurement_id: i32,
+    pub measured_on: NaiveDate,
+    /// Age in months, or length/height in cm for the weight-for-length/height charts.
+    pub x: f64,
+    pub y: f64,
+    pub z: Option<f64>,
+}
+
+/// The reference curves at one step of the table.
+#[derive(Serialize, Debug, Clone)]
+pub struct ReferencePoint {
+    pub x: f64,
+    pub sd3neg: f64,
+    pub sd2neg: f64,
+    pub median: f64,
+    pub sd2: f64,
+    pub sd3: f64,
+}
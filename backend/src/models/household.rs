@@ -0,0 +1,112 @@
+use serde::{Serialize, Deserialize};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde_json::Value;
+use sqlx::FromRow;
+
+use crate::services::validation::TextEnum;
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct Household {
+    pub id: i32,
+    pub name: String,
+    pub head_patient_id: Option<i32>,
+    pub village: Option<String>,
+    pub address: Option<Value>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+    pub tenant_id: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct PatientRelationship {
+    pub id: i32,
+    pub patient_id: i32,
+    pub related_patient_id: i32,
+    pub relationship_type: String,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// A relative as shown on a patient's profile.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FamilyMember {
+    pub patient_id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub gender: String,
+    pub date_of_birth: NaiveDate,
+    /// Relationship to the profile's patient, e.g. `mother`, `grandchild`.
+    pub relationship: String,
+    /// Generations above (positive) or below (negative) the profile's patient.
+    pub generation: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelationshipType {
+    Mother,
+    Father,
+    Child,
+    Spouse,
+    Sibling,
+    Guardian,
+    Ward,
+}
+
+impl TextEnum for RelationshipType {
+    const ALL: &'static [Self] = &[
+        RelationshipType::Mother,
+        RelationshipType::Father,
+        RelationshipType::Child,
+        RelationshipType::Spouse,
+        RelationshipType::Sibling,
+        RelationshipType::Guardian,
+        RelationshipType::Ward,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            RelationshipType::Mother => "mother",
+            RelationshipType::Father => "father",
+            RelationshipType::Child => "child",
+            RelationshipType::Spouse => "spouse",
+            RelationshipType::Sibling => "sibling",
+            RelationshipType::Guardian => "guardian",
+            RelationshipType::Ward => "ward",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            RelationshipType::Child => &["son", "daughter"],
+            RelationshipType::Spouse => &["husband", "wife"],
+            RelationshipType::Sibling => &["brother", "sister"],
+            _ => &[],
+        }
+    }
+}
+
+impl RelationshipType {
+    /// Types the reverse row can have.
+    pub fn inverse_types(self) -> &'static [RelationshipType] {
+        match self {
+            RelationshipType::Mother | RelationshipType::Father => &[RelationshipType::Child],
+            RelationshipType::Child => &[RelationshipType::Mother, RelationshipType::Father],
+            RelationshipType::Spouse => &[RelationshipType::Spouse],
+            RelationshipType::Sibling => &[RelationshipType::Sibling],
+            RelationshipType::Guardian => &[RelationshipType::Ward],
+            RelationshipType::Ward => &[RelationshipType::Guardian],
+        }
+    }
+
+    /// The relationship seen from the other side. A child's inverse depends on the parent's gender,
+    /// so `parent_gender` is the gender of the patient who is the parent.
+    pub fn inverse(self, parent_gender: &str) -> Option<RelationshipType> {
+        match self {
+            RelationshipType::Child => match parent_gender {
+                "female" => Some(RelationshipType::Mother),
+                "male" => Some(RelationshipType::Father),
+                _ => None,
+            },
+            other => other.inverse_types().first().copied(),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Serialize, Deserialize};
+use chrono::{NaiveDate, NaiveDateTime};
+use sqlx::FromRow;
+
+use crate::services::validation::TextEnum;
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct Vaccine {
+    pub code: String,
+    pub name: String,
+    pub default_route: Option<String>,
+    pub default_site: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct ImmunizationSchedule {
+    pub code: String,
+    pub name: String,
+    pub is_default: bool,
+    pub updated_by: Option<i32>,
+    pub updated_at: NaiveDateTime,
+}
+
+/// One dose of a schedule. Ages are in days from date of birth.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct ScheduleDose {
+    pub vaccine_code: String,
+    pub dose_number: i32,
+    pub label: String,
+    pub min_age_days: i32,
+    pub due_age_days: i32,
+    pub overdue_age_days: i32,
+    /// Past this age the dose is no longer given.
+    pub max_age_days: Option<i32>,
+    /// Minimum gap after the previous dose of the same vaccine.
+    pub min_interval_days: Option<i32>,
+    /// Only patients of this sex are eligible; `None` for everyone.
+    pub sex: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct Immunization {
+    pub id: i32,
+    pub patient_id: i32,
+    pub record_id: Option<i32>,
+    pub vaccine_code: String,
+    pub dose_number: i32,
+    pub administered_on: NaiveDate,
+    pub lot_number: Option<String>,
+    pub site: Option<String>,
+    pub route: Option<String>,
+    pub administered_by: Option<i32>,
+    /// Outreach workers without an account are recorded by name.
+    pub administered_by_name: Option<String>,
+    /// Copied from a vaccination card rather than given by our staff.
+    pub historical: bool,
+    pub notes: Option<String>,
+    pub recorded_by: Option<i32>,
+    pub recorded_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DoseStatus {
+    Given,
+    /// Not yet due.
+    Upcoming,
+    Due,
+    Overdue,
+    /// Past the last age at which the dose is given.
+    Missed,
+}
+
+/// Where a schedule dose stands for one patient.
+#[derive(Serialize, Debug, Clone)]
+pub struct DoseState {
+    pub vaccine_code: String,
+    pub dose_number: i32,
+    pub label: String,
+    pub status: DoseStatus,
+    pub earliest_date: NaiveDate,
+    pub due_date: NaiveDate,
+    pub overdue_date: NaiveDate,
+    pub last_date: Option<NaiveDate>,
+    pub given_on: Option<NaiveDate>,
+    pub immunization_id: Option<i32>,
+    /// Given before the minimum age or interval, so it may need repeating.
+    pub given_early: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaccinationSite {
+    LeftUpperArm,
+    RightUpperArm,
+    LeftThigh,
+    RightThigh,
+    Oral,
+    Intranasal,
+}
+
+impl TextEnum for VaccinationSite {
+    const ALL: &'static [Self] = &[
+        VaccinationSite::LeftUpperArm,
+        VaccinationSite::RightUpperArm,
+        VaccinationSite::LeftThigh,
+        VaccinationSite::RightThigh,
+        VaccinationSite::Oral,
+        VaccinationSite::Intranasal,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            VaccinationSite::LeftUpperArm => "left_upper_arm",
+            VaccinationSite::RightUpperArm => "right_upper_arm",
+            VaccinationSite::LeftThigh => "left_thigh",
+            VaccinationSite::RightThigh => "right_thigh",
+            VaccinationSite::Oral => "oral",
+            VaccinationSite::Intranasal => "intranasal",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            VaccinationSite::LeftUpperArm => &["leftupperarm", "leftarm", "leftdeltoid", "lua"],
+            VaccinationSite::RightUpperArm => &["rightupperarm", "rightarm", "rightdeltoid", "rua"],
+            VaccinationSite::LeftThigh => &["leftthigh", "leftanterolateralthigh"],
+            VaccinationSite::RightThigh => &["rightthigh", "rightanterolateralthigh"],
+            VaccinationSite::Oral => &["mouth", "po"],
+            VaccinationSite::Intranasal => &["nasal", "nose"],
+        }
+    }
+}
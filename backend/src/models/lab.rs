@@ -0,0 +1,247 @@
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+
+use crate::services::validation::TextEnum;
+
+/// A test that can be ordered, from the catalog in `lab_tests`.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct LabTest {
+    pub code: String,
+    pub name: String,
+    pub specimen_type: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct LabAnalyte {
+    pub test_code: String,
+    pub code: String,
+    pub name: String,
+    /// `numeric` or `text`.
+    pub result_type: String,
+    pub unit: Option<String>,
+    /// Text results that count as normal, in lowercase.
+    pub normal_values: Option<Vec<String>>,
+    pub position: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct LabReferenceRange {
+    pub analyte_code: String,
+    pub low: Option<f64>,
+    pub high: Option<f64>,
+    pub critical_low: Option<f64>,
+    pub critical_high: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct LabOrder {
+    pub id: i32,
+    pub patient_id: i32,
+    pub record_id: Option<i32>,
+    pub facility_id: i32,
+    pub priority: String,
+    pub specimen_type: String,
+    pub clinical_notes: Option<String>,
+    pub status: String,
+    pub ordered_by: Option<i32>,
+    pub ordered_at: NaiveDateTime,
+    pub collected_by: Option<i32>,
+    pub collected_at: Option<NaiveDateTime>,
+    pub resulted_by: Option<i32>,
+    pub resulted_at: Option<NaiveDateTime>,
+    pub reviewed_by: Option<i32>,
+    pub reviewed_at: Option<NaiveDateTime>,
+    pub review_note: Option<String>,
+    pub cancelled_reason: Option<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+/// One analyte's result, flagged against the range that applied when it was reported.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct LabResult {
+    pub id: i32,
+    pub order_id: i32,
+    pub test_code: String,
+    pub analyte_code: String,
+    pub analyte_name: String,
+    pub value_numeric: Option<f64>,
+    pub value_text: Option<String>,
+    pub unit: Option<String>,
+    pub reference_low: Option<f64>,
+    pub reference_high: Option<f64>,
+    pub flag: Option<String>,
+    pub resulted_by: Option<i32>,
+    pub resulted_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabOrderStatus {
+    Ordered,
+    Collected,
+    Resulted,
+    Reviewed,
+    Cancelled,
+}
+
+impl TextEnum for LabOrderStatus {
+    const ALL: &'static [Self] = &[
+        LabOrderStatus::Ordered,
+        LabOrderStatus::Collected,
+        LabOrderStatus::Resulted,
+        LabOrderStatus::Reviewed,
+        LabOrderStatus::Cancelled,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            LabOrderStatus::Ordered => "ordered",
+            LabOrderStatus::Collected => "collected",
+            LabOrderStatus::Resulted => "resulted",
+            LabOrderStatus::Reviewed => "reviewed",
+            LabOrderStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// A step in the ordered -> collected -> resulted -> reviewed workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabOrderAction {
+    Collect,
+    /// Report results; repeating it before review adds or corrects analytes.
+    Result,
+    Review,
+    Cancel,
+}
+
+impl LabOrderAction {
+    /// The status an order in `from` moves to, or `None` if the step is not allowed.
+    pub fn apply(self, from: LabOrderStatus) -> Option<LabOrderStatus> {
+        use LabOrderStatus::*;
+        match (self, from) {
+            (LabOrderAction::Collect, Ordered) => Some(Collected),
+            (LabOrderAction::Result, Collected | Resulted) => Some(Resulted),
+            (LabOrderAction::Review, Resulted) => Some(Reviewed),
+            (LabOrderAction::Cancel, Ordered | Collected) => Some(Cancelled),
+            _ => None,
+        }
+    }
+
+    pub fn past_tense(self) -> &'static str {
+        match self {
+            LabOrderAction::Collect => "collected",
+            LabOrderAction::Result => "resulted",
+            LabOrderAction::Review => "reviewed",
+            LabOrderAction::Cancel => "cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabPriority {
+    Routine,
+    Urgent,
+    Stat,
+}
+
+impl TextEnum for LabPriority {
+    const ALL: &'static [Self] = &[LabPriority::Routine, LabPriority::Urgent, LabPriority::Stat];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            LabPriority::Routine => "routine",
+            LabPriority::Urgent => "urgent",
+            LabPriority::Stat => "stat",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            LabPriority::Routine => &["normal"],
+            LabPriority::Urgent => &["asap"],
+            LabPriority::Stat => &["emergency"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecimenType {
+    Blood,
+    Urine,
+    Stool,
+    Sputum,
+    Swab,
+    Csf,
+    Other,
+}
+
+impl TextEnum for SpecimenType {
+    const ALL: &'static [Self] = &[
+        SpecimenType::Blood,
+        SpecimenType::Urine,
+        SpecimenType::Stool,
+        SpecimenType::Sputum,
+        SpecimenType::Swab,
+        SpecimenType::Csf,
+        SpecimenType::Other,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            SpecimenType::Blood => "blood",
+            SpecimenType::Urine => "urine",
+            SpecimenType::Stool => "stool",
+            SpecimenType::Sputum => "sputum",
+            SpecimenType::Swab => "swab",
+            SpecimenType::Csf => "csf",
+            SpecimenType::Other => "other",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            SpecimenType::Blood => &["serum", "plasma", "whole_blood"],
+            SpecimenType::Csf => &["cerebrospinal_fluid"],
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabFlag {
+    Normal,
+    Low,
+    High,
+    CriticalLow,
+    CriticalHigh,
+    /// A text result outside the analyte's normal values.
+    Abnormal,
+}
+
+impl LabFlag {
+    pub fn is_critical(self) -> bool {
+        matches!(self, LabFlag::CriticalLow | LabFlag::CriticalHigh)
+    }
+}
+
+impl TextEnum for LabFlag {
+    const ALL: &'static [Self] = &[
+        LabFlag::Normal,
+        LabFlag::Low,
+        LabFlag::High,
+        LabFlag::CriticalLow,
+        LabFlag::CriticalHigh,
+        LabFlag::Abnormal,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            LabFlag::Normal => "normal",
+            LabFlag::Low => "low",
+            LabFlag::High => "high",
+            LabFlag::CriticalLow => "critical_low",
+            LabFlag::CriticalHigh => "critical_high",
+            LabFlag::Abnormal => "abnormal",
+        }
+    }
+}
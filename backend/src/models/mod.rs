@@ -6,3 +6,21 @@ pub mod consent;
 pub mod report;
 pub mod audit_log;
 pub mod error;
+pub mod household;
+pub mod photo;
+pub mod attachment;
+pub mod record_version;
+pub mod vital;
+pub mod alert;
+pub mod lab;
+pub mod prescription;
+pub mod terminology;
+pub mod immunization;
+pub mod pregnancy;
+pub mod growth;
+pub mod appointment;
+pub mod queue;
+pub mod referral;
+pub mod facility;
+pub mod tenant;
+pub mod geography;
@@ -4,6 +4,8 @@ use chrono::{NaiveDate, NaiveDateTime};
 use serde_json::Value;
 use sqlx::FromRow;
 
+use crate::services::validation::TextEnum;
+
 
 #[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
 pub struct Patient {
@@ -28,4 +30,152 @@ pub struct Patient {
     pub next_visit: Option<NaiveDate>,
     pub created_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
+    pub household_id: Option<i32>,
+    pub critical_reason: Option<String>,
+    /// `manual` when staff set the flag, `early_warning` when a NEWS2 score did.
+    pub critical_flag_source: Option<String>,
+    /// The organisation the patient is registered with.
+    pub tenant_id: i32,
+    /// The village in the geography hierarchy; `village` holds its name.
+    pub village_id: Option<i32>,
+}
+
+impl Patient {
+    /// Age in completed years on the given date.
+    pub fn age_on(&self, date: NaiveDate) -> i32 {
+        date.years_since(self.date_of_birth).map(|y| y as i32).unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gender {
+    Female,
+    Male,
+    Other,
+    Unknown,
+}
+
+impl TextEnum for Gender {
+    const ALL: &'static [Self] = &[Gender::Female, Gender::Male, Gender::Other, Gender::Unknown];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Gender::Female => "female",
+            Gender::Male => "male",
+            Gender::Other => "other",
+            Gender::Unknown => "unknown",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            Gender::Female => &["f"],
+            Gender::Male => &["m"],
+            Gender::Other => &["o"],
+            Gender::Unknown => &["u"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BloodType {
+    APositive,
+    ANegative,
+    BPositive,
+    BNegative,
+    AbPositive,
+    AbNegative,
+    OPositive,
+    ONegative,
+}
+
+impl TextEnum for BloodType {
+    const ALL: &'static [Self] = &[
+        BloodType::APositive,
+        BloodType::ANegative,
+        BloodType::BPositive,
+        BloodType::BNegative,
+        BloodType::AbPositive,
+        BloodType::AbNegative,
+        BloodType::OPositive,
+        BloodType::ONegative,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BloodType::APositive => "A+",
+            BloodType::ANegative => "A-",
+            BloodType::BPositive => "B+",
+            BloodType::BNegative => "B-",
+            BloodType::AbPositive => "AB+",
+            BloodType::AbNegative => "AB-",
+            BloodType::OPositive => "O+",
+            BloodType::ONegative => "O-",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            BloodType::APositive => &["apos", "apositive"],
+            BloodType::ANegative => &["aneg", "anegative"],
+            BloodType::BPositive => &["bpos", "bpositive"],
+            BloodType::BNegative => &["bneg", "bnegative"],
+            BloodType::AbPositive => &["abpos", "abpositive"],
+            BloodType::AbNegative => &["abneg", "abnegative"],
+            BloodType::OPositive => &["opos", "opositive"],
+            BloodType::ONegative => &["oneg", "onegative"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatientStatus {
+    Active,
+    Inactive,
+    Transferred,
+    Deceased,
+}
+
+impl TextEnum for PatientStatus {
+    const ALL: &'static [Self] = &[
+        PatientStatus::Active,
+        PatientStatus::Inactive,
+        PatientStatus::Transferred,
+        PatientStatus::Deceased,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            PatientStatus::Active => "active",
+            PatientStatus::Inactive => "inactive",
+            PatientStatus::Transferred => "transferred",
+            PatientStatus::Deceased => "deceased",
+        }
+    }
+}
+
+/// Schema for `patients.address`. Rows written before validation existed may hold other shapes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Address {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub street: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postal_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+}
+
+/// Schema for `patients.emergency_contact`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct EmergencyContact {
+    pub name: String,
+    pub phone: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relationship: Option<String>,
 }
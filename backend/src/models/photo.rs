@@ -0,0 +1,21 @@
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct PatientPhoto {
+    pub id: i32,
+    pub patient_id: i32,
+    #[serde(skip_serializing)]
+    pub storage_key: String,
+    #[serde(skip_serializing)]
+    pub thumbnail_key: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub width: i32,
+    pub height: i32,
+    pub original_filename: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    /// Incremented on every replacement; signed URLs are only valid for the version they name.
+    pub version: i32,
+}
@@ -0,0 +1,552 @@
+use serde::{Serialize, Deserialize};
+use chrono::{NaiveDate, NaiveDateTime};
+use sqlx::FromRow;
+
+use crate::services::validation::TextEnum;
+
+/// One contact of the recommended ANC schedule.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct AncScheduleContact {
+    pub contact_number: i32,
+    /// Completed weeks of gestation at which the contact is due.
+    pub gestational_week: i32,
+    pub label: String,
+    pub checks: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct Pregnancy {
+    pub id: i32,
+    pub patient_id: i32,
+    pub lmp: Option<NaiveDate>,
+    pub edd: NaiveDate,
+    pub edd_method: String,
+    pub gravida: i32,
+    pub para: i32,
+    pub booked_on: NaiveDate,
+    pub status: String,
+    pub risk_factors: Vec<String>,
+    pub notes: Option<String>,
+    pub outcome: Option<String>,
+    pub outcome_date: Option<NaiveDate>,
+    pub delivery_mode: Option<String>,
+    pub delivery_place: Option<String>,
+    pub maternal_death: Option<bool>,
+    pub outcome_notes: Option<String>,
+    pub outcome_recorded_by: Option<i32>,
+    pub registered_by: Option<i32>,
+    pub registered_at: NaiveDateTime,
+    pub updated_by: Option<i32>,
+    pub updated_at: NaiveDateTime,
+}
+
+/// An antenatal contact that took place.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct AncContact {
+    pub id: i32,
+    pub pregnancy_id: i32,
+    /// The scheduled contact this visit counts as; `None` for an extra visit.
+    pub contact_number: Option<i32>,
+    pub contact_date: NaiveDate,
+    pub gestational_age_days: i32,
+    pub record_id: Option<i32>,
+    pub weight_kg: Option<f64>,
+    pub systolic_bp: Option<i32>,
+    pub diastolic_bp: Option<i32>,
+    /// g/dL.
+    pub haemoglobin: Option<f64>,
+    pub fundal_height_cm: Option<f64>,
+    pub fetal_heart_rate: Option<i32>,
+    pub urine_protein: Option<String>,
+    pub urine_glucose: Option<String>,
+    pub fetal_presentation: Option<String>,
+    pub checks_done: Vec<String>,
+    pub notes: Option<String>,
+    pub recorded_by: Option<i32>,
+    pub recorded_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct PregnancyBirth {
+    pub id: i32,
+    pub pregnancy_id: i32,
+    pub birth_order: i32,
+    pub outcome: String,
+    pub sex: Option<String>,
+    pub birth_weight_grams: Option<i32>,
+    pub newborn_patient_id: Option<i32>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GestationalAge {
+    pub weeks: i32,
+    pub days: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AncContactStatus {
+    Completed,
+    /// Not yet due.
+    Upcoming,
+    Due,
+    Overdue,
+    /// Its window closed without a visit.
+    Missed,
+    /// Its window closed before the pregnancy was booked.
+    Skipped,
+}
+
+/// Where a scheduled contact stands for one pregnancy.
+#[derive(Serialize, Debug, Clone)]
+pub struct AncContactState {
+    pub contact_number: i32,
+    pub label: String,
+    pub gestational_week: i32,
+    pub status: AncContactStatus,
+    pub due_date: NaiveDate,
+    pub overdue_date: NaiveDate,
+    /// Last day a visit still counts as this contact.
+    pub window_end: NaiveDate,
+    pub contact_id: Option<i32>,
+    pub contact_date: Option<NaiveDate>,
+    pub recommended_checks: Vec<String>,
+    /// Recommended checks not recorded at the visit; all of them until it takes place.
+    pub outstanding_checks: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PregnancyStatus {
+    Active,
+    /// Ended in a live birth or stillbirth.
+    Delivered,
+    /// Ended before a birth: miscarriage, abortion or ectopic pregnancy.
+    Ended,
+}
+
+impl TextEnum for PregnancyStatus {
+    const ALL: &'static [Self] = &[PregnancyStatus::Active, PregnancyStatus::Delivered, PregnancyStatus::Ended];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            PregnancyStatus::Active => "active",
+            PregnancyStatus::Delivered => "delivered",
+            PregnancyStatus::Ended => "ended",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EddMethod {
+    Lmp,
+    Ultrasound,
+    /// Estimated from fundal height or examination.
+    Clinical,
+}
+
+impl TextEnum for EddMethod {
+    const ALL: &'static [Self] = &[EddMethod::Lmp, EddMethod::Ultrasound, EddMethod::Clinical];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            EddMethod::Lmp => "lmp",
+            EddMethod::Ultrasound => "ultrasound",
+            EddMethod::Clinical => "clinical",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            EddMethod::Lmp => &["dates", "lastmenstrualperiod"],
+            EddMethod::Ultrasound => &["usg", "scan", "us"],
+            EddMethod::Clinical => &["examination", "fundalheight"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskFactor {
+    Adolescent,
+    AdvancedMaternalAge,
+    GrandMultipara,
+    PreviousCaesarean,
+    PreviousStillbirth,
+    PreviousPretermBirth,
+    PreviousPostpartumHaemorrhage,
+    Hypertension,
+    PreEclampsia,
+    Diabetes,
+    GestationalDiabetes,
+    Anaemia,
+    SevereAnaemia,
+    Hiv,
+    Syphilis,
+    MultiplePregnancy,
+    Malpresentation,
+    AntepartumHaemorrhage,
+    HeartDisease,
+    RhNegative,
+    ShortStature,
+    AbnormalFetalHeartRate,
+}
+
+impl TextEnum for RiskFactor {
+    const ALL: &'static [Self] = &[
+        RiskFactor::Adolescent,
+        RiskFactor::AdvancedMaternalAge,
+        RiskFactor::GrandMultipara,
+        RiskFactor::PreviousCaesarean,
+        RiskFactor::PreviousStillbirth,
+        RiskFactor::PreviousPretermBirth,
+        RiskFactor::PreviousPostpartumHaemorrhage,
+        RiskFactor::Hypertension,
+        RiskFactor::PreEclampsia,
+        RiskFactor::Diabetes,
+        RiskFactor::GestationalDiabetes,
+        RiskFactor::Anaemia,
+        RiskFactor::SevereAnaemia,
+        RiskFactor::Hiv,
+        RiskFactor::Syphilis,
+        RiskFactor::MultiplePregnancy,
+        RiskFactor::Malpresentation,
+        RiskFactor::AntepartumHaemorrhage,
+        RiskFactor::HeartDisease,
+        RiskFactor::RhNegative,
+        RiskFactor::ShortStature,
+        RiskFactor::AbnormalFetalHeartRate,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            RiskFactor::Adolescent => "adolescent",
+            RiskFactor::AdvancedMaternalAge => "advanced_maternal_age",
+            RiskFactor::GrandMultipara => "grand_multipara",
+            RiskFactor::PreviousCaesarean => "previous_caesarean",
+            RiskFactor::PreviousStillbirth => "previous_stillbirth",
+            RiskFactor::PreviousPretermBirth => "previous_preterm_birth",
+            RiskFactor::PreviousPostpartumHaemorrhage => "previous_postpartum_haemorrhage",
+            RiskFactor::Hypertension => "hypertension",
+            RiskFactor::PreEclampsia => "pre_eclampsia",
+            RiskFactor::Diabetes => "diabetes",
+            RiskFactor::GestationalDiabetes => "gestational_diabetes",
+            RiskFactor::Anaemia => "anaemia",
+            RiskFactor::SevereAnaemia => "severe_anaemia",
+            RiskFactor::Hiv => "hiv",
+            RiskFactor::Syphilis => "syphilis",
+            RiskFactor::MultiplePregnancy => "multiple_pregnancy",
+            RiskFactor::Malpresentation => "malpresentation",
+            RiskFactor::AntepartumHaemorrhage => "antepartum_haemorrhage",
+            RiskFactor::HeartDisease => "heart_disease",
+            RiskFactor::RhNegative => "rh_negative",
+            RiskFactor::ShortStature => "short_stature",
+            RiskFactor::AbnormalFetalHeartRate => "abnormal_fetal_heart_rate",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            RiskFactor::Adolescent => &["teenage", "teenagepregnancy", "under18"],
+            RiskFactor::AdvancedMaternalAge => &["advancedmaternalage", "elderlyprimi", "over35"],
+            RiskFactor::GrandMultipara => &["grandmultipara", "grandmultiparity"],
+            RiskFactor::PreviousCaesarean => &["previouscaesarean", "previouscesarean", "previouscs", "previouslscs"],
+            RiskFactor::PreviousStillbirth => &["previousstillbirth"],
+            RiskFactor::PreviousPretermBirth => &["previouspretermbirth", "previouspreterm"],
+            RiskFactor::PreviousPostpartumHaemorrhage => &["previouspostpartumhaemorrhage", "previouspph"],
+            RiskFactor::Hypertension => &["htn", "pih", "gestationalhypertension"],
+            RiskFactor::PreEclampsia => &["preeclampsia", "pe"],
+            RiskFactor::Diabetes => &["dm", "diabetesmellitus"],
+            RiskFactor::GestationalDiabetes => &["gestationaldiabetes", "gdm"],
+            RiskFactor::Anaemia => &["anemia"],
+            RiskFactor::SevereAnaemia => &["severeanaemia", "severeanemia"],
+            RiskFactor::Hiv => &["hivpositive"],
+            RiskFactor::Syphilis => &["vdrlpositive"],
+            RiskFactor::MultiplePregnancy => &["multiplepregnancy", "twins", "multiplegestation"],
+            RiskFactor::Malpresentation => &["breech", "transverselie"],
+            RiskFactor::AntepartumHaemorrhage => &["antepartumhaemorrhage", "aph"],
+            RiskFactor::HeartDisease => &["heartdisease", "cardiacdisease"],
+            RiskFactor::RhNegative => &["rhnegative", "rh-negative", "rh-ve"],
+            RiskFactor::ShortStature => &["shortstature"],
+            RiskFactor::AbnormalFetalHeartRate => &["abnormalfetalheartrate", "fetaldistress"],
+        }
+    }
+}
+
+/// Checks an ANC contact can include.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AncCheck {
+    Weight,
+    BloodPressure,
+    Haemoglobin,
+    BloodGroup,
+    UrineProtein,
+    UrineGlucose,
+    Hiv,
+    Syphilis,
+    HepatitisB,
+    Ultrasound,
+    FundalHeight,
+    FetalHeartRate,
+    FetalPresentation,
+    Td,
+    IronFolicAcid,
+    Calcium,
+    Deworming,
+    GlucoseTolerance,
+    BirthPreparedness,
+}
+
+impl TextEnum for AncCheck {
+    const ALL: &'static [Self] = &[
+        AncCheck::Weight,
+        AncCheck::BloodPressure,
+        AncCheck::Haemoglobin,
+        AncCheck::BloodGroup,
+        AncCheck::UrineProtein,
+        AncCheck::UrineGlucose,
+        AncCheck::Hiv,
+        AncCheck::Syphilis,
+        AncCheck::HepatitisB,
+        AncCheck::Ultrasound,
+        AncCheck::FundalHeight,
+        AncCheck::FetalHeartRate,
+        AncCheck::FetalPresentation,
+        AncCheck::Td,
+        AncCheck::IronFolicAcid,
+        AncCheck::Calcium,
+        AncCheck::Deworming,
+        AncCheck::GlucoseTolerance,
+        AncCheck::BirthPreparedness,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            AncCheck::Weight => "weight",
+            AncCheck::BloodPressure => "blood_pressure",
+            AncCheck::Haemoglobin => "haemoglobin",
+            AncCheck::BloodGroup => "blood_group",
+            AncCheck::UrineProtein => "urine_protein",
+            AncCheck::UrineGlucose => "urine_glucose",
+            AncCheck::Hiv => "hiv",
+            AncCheck::Syphilis => "syphilis",
+            AncCheck::HepatitisB => "hepatitis_b",
+            AncCheck::Ultrasound => "ultrasound",
+            AncCheck::FundalHeight => "fundal_height",
+            AncCheck::FetalHeartRate => "fetal_heart_rate",
+            AncCheck::FetalPresentation => "fetal_presentation",
+            AncCheck::Td => "td",
+            AncCheck::IronFolicAcid => "iron_folic_acid",
+            AncCheck::Calcium => "calcium",
+            AncCheck::Deworming => "deworming",
+            AncCheck::GlucoseTolerance => "glucose_tolerance",
+            AncCheck::BirthPreparedness => "birth_preparedness",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            AncCheck::BloodPressure => &["bloodpressure", "bp"],
+            AncCheck::Haemoglobin => &["hb", "hemoglobin"],
+            AncCheck::BloodGroup => &["bloodgroup", "bloodgrouping"],
+            AncCheck::UrineProtein => &["urineprotein", "urinealbumin"],
+            AncCheck::UrineGlucose => &["urineglucose", "urinesugar"],
+            AncCheck::Syphilis => &["vdrl", "rpr"],
+            AncCheck::HepatitisB => &["hepatitisb", "hbsag"],
+            AncCheck::Ultrasound => &["usg", "scan"],
+            AncCheck::FundalHeight => &["fundalheight", "sfh"],
+            AncCheck::FetalHeartRate => &["fetalheartrate", "fhr"],
+            AncCheck::FetalPresentation => &["fetalpresentation", "presentation"],
+            AncCheck::Td => &["tt", "tetanus"],
+            AncCheck::IronFolicAcid => &["ironfolicacid", "ifa"],
+            AncCheck::GlucoseTolerance => &["glucosetolerance", "ogtt", "gtt"],
+            AncCheck::BirthPreparedness => &["birthpreparedness", "birthplan"],
+            _ => &[],
+        }
+    }
+}
+
+/// Urine dipstick reading for protein or glucose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Dipstick {
+    Negative,
+    Trace,
+    OnePlus,
+    TwoPlus,
+    ThreePlus,
+}
+
+impl TextEnum for Dipstick {
+    const ALL: &'static [Self] =
+        &[Dipstick::Negative, Dipstick::Trace, Dipstick::OnePlus, Dipstick::TwoPlus, Dipstick::ThreePlus];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Dipstick::Negative => "negative",
+            Dipstick::Trace => "trace",
+            Dipstick::OnePlus => "1+",
+            Dipstick::TwoPlus => "2+",
+            Dipstick::ThreePlus => "3+",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            Dipstick::Negative => &["nil", "neg", "-"],
+            Dipstick::Trace => &["tr", "+/-"],
+            Dipstick::OnePlus => &["+"],
+            Dipstick::TwoPlus => &["++"],
+            Dipstick::ThreePlus => &["+++", "4+", "++++"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetalPresentation {
+    Cephalic,
+    Breech,
+    Transverse,
+}
+
+impl TextEnum for FetalPresentation {
+    const ALL: &'static [Self] = &[FetalPresentation::Cephalic, FetalPresentation::Breech, FetalPresentation::Transverse];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            FetalPresentation::Cephalic => "cephalic",
+            FetalPresentation::Breech => "breech",
+            FetalPresentation::Transverse => "transverse",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            FetalPresentation::Cephalic => &["vertex", "head"],
+            FetalPresentation::Breech => &[],
+            FetalPresentation::Transverse => &["oblique", "transverselie"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PregnancyOutcome {
+    LiveBirth,
+    Stillbirth,
+    Miscarriage,
+    Abortion,
+    Ectopic,
+}
+
+impl PregnancyOutcome {
+    /// Whether the pregnancy ended in a birth, live or still.
+    pub fn is_delivery(self) -> bool {
+        matches!(self, PregnancyOutcome::LiveBirth | PregnancyOutcome::Stillbirth)
+    }
+}
+
+impl TextEnum for PregnancyOutcome {
+    const ALL: &'static [Self] = &[
+        PregnancyOutcome::LiveBirth,
+        PregnancyOutcome::Stillbirth,
+        PregnancyOutcome::Miscarriage,
+        PregnancyOutcome::Abortion,
+        PregnancyOutcome::Ectopic,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            PregnancyOutcome::LiveBirth => "live_birth",
+            PregnancyOutcome::Stillbirth => "stillbirth",
+            PregnancyOutcome::Miscarriage => "miscarriage",
+            PregnancyOutcome::Abortion => "abortion",
+            PregnancyOutcome::Ectopic => "ectopic",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            PregnancyOutcome::LiveBirth => &["livebirth", "live", "alive"],
+            PregnancyOutcome::Stillbirth => &["stillborn", "iud", "iufd"],
+            PregnancyOutcome::Miscarriage => &["spontaneousabortion"],
+            PregnancyOutcome::Abortion => &["mtp", "termination", "inducedabortion"],
+            PregnancyOutcome::Ectopic => &["ectopicpregnancy"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryMode {
+    Vaginal,
+    /// Forceps or vacuum.
+    Assisted,
+    Caesarean,
+}
+
+impl TextEnum for DeliveryMode {
+    const ALL: &'static [Self] = &[DeliveryMode::Vaginal, DeliveryMode::Assisted, DeliveryMode::Caesarean];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            DeliveryMode::Vaginal => "vaginal",
+            DeliveryMode::Assisted => "assisted",
+            DeliveryMode::Caesarean => "caesarean",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            DeliveryMode::Vaginal => &["normal", "nvd", "svd"],
+            DeliveryMode::Assisted => &["forceps", "vacuum", "ventouse", "instrumental"],
+            DeliveryMode::Caesarean => &["cesarean", "cs", "lscs", "csection"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryPlace {
+    Facility,
+    Home,
+    InTransit,
+}
+
+impl TextEnum for DeliveryPlace {
+    const ALL: &'static [Self] = &[DeliveryPlace::Facility, DeliveryPlace::Home, DeliveryPlace::InTransit];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            DeliveryPlace::Facility => "facility",
+            DeliveryPlace::Home => "home",
+            DeliveryPlace::InTransit => "in_transit",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            DeliveryPlace::Facility => &["hospital", "institutional", "clinic"],
+            DeliveryPlace::Home => &[],
+            DeliveryPlace::InTransit => &["intransit", "transit", "ontheway"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BirthOutcome {
+    LiveBirth,
+    Stillbirth,
+}
+
+impl TextEnum for BirthOutcome {
+    const ALL: &'static [Self] = &[BirthOutcome::LiveBirth, BirthOutcome::Stillbirth];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BirthOutcome::LiveBirth => "live_birth",
+            BirthOutcome::Stillbirth => "stillbirth",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            BirthOutcome::LiveBirth => &["livebirth", "live", "alive"],
+            BirthOutcome::Stillbirth => &["stillborn"],
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use serde::{Serialize, Deserialize};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde_json::Value;
+use sqlx::FromRow;
+
+use crate::services::validation::TextEnum;
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct Prescription {
+    pub id: i32,
+    pub patient_id: i32,
+    pub record_id: Option<i32>,
+    /// The matching entry in the local drug table, if any.
+    pub drug: Option<String>,
+    pub drug_name: String,
+    pub dose: String,
+    pub route: String,
+    pub frequency: String,
+    pub duration_days: Option<i32>,
+    pub start_date: NaiveDate,
+    /// Last day of the course; `None` for long-term medication.
+    pub end_date: Option<NaiveDate>,
+    pub instructions: Option<String>,
+    pub status: String,
+    pub prescribed_by: Option<i32>,
+    pub prescribed_at: NaiveDateTime,
+    pub overridden_warnings: Option<Value>,
+    pub override_reason: Option<String>,
+    pub stopped_by: Option<i32>,
+    pub stopped_at: Option<NaiveDateTime>,
+    pub stop_reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct Drug {
+    pub name: String,
+    pub synonyms: Vec<String>,
+    pub allergen_class: Option<String>,
+    pub default_route: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct AllergenClass {
+    pub code: String,
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub cross_reactive_with: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningSeverity {
+    /// The prescription cannot be issued.
+    Blocking,
+    /// The prescription can be issued with an `override_reason`.
+    Overridable,
+}
+
+/// A safety problem found when checking a prescription.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MedicationWarning {
+    pub severity: WarningSeverity,
+    /// `allergy`, `class_allergy`, `cross_reactivity`, `unchecked_drug` or `duplicate_therapy`.
+    pub code: String,
+    /// The entry in the patient's allergy list that triggered the warning.
+    pub allergy: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrescriptionStatus {
+    Active,
+    Stopped,
+}
+
+impl TextEnum for PrescriptionStatus {
+    const ALL: &'static [Self] = &[PrescriptionStatus::Active, PrescriptionStatus::Stopped];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            PrescriptionStatus::Active => "active",
+            PrescriptionStatus::Stopped => "stopped",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    Oral,
+    Sublingual,
+    Iv,
+    Im,
+    Sc,
+    Intradermal,
+    Inhaled,
+    Topical,
+    Rectal,
+    Vaginal,
+    Ophthalmic,
+    Otic,
+    Nasal,
+    Transdermal,
+}
+
+impl TextEnum for Route {
+    const ALL: &'static [Self] = &[
+        Route::Oral,
+        Route::Sublingual,
+        Route::Iv,
+        Route::Im,
+        Route::Sc,
+        Route::Intradermal,
+        Route::Inhaled,
+        Route::Topical,
+        Route::Rectal,
+        Route::Vaginal,
+        Route::Ophthalmic,
+        Route::Otic,
+        Route::Nasal,
+        Route::Transdermal,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Route::Oral => "oral",
+            Route::Sublingual => "sublingual",
+            Route::Iv => "iv",
+            Route::Im => "im",
+            Route::Sc => "sc",
+            Route::Intradermal => "intradermal",
+            Route::Inhaled => "inhaled",
+            Route::Topical => "topical",
+            Route::Rectal => "rectal",
+            Route::Vaginal => "vaginal",
+            Route::Ophthalmic => "ophthalmic",
+            Route::Otic => "otic",
+            Route::Nasal => "nasal",
+            Route::Transdermal => "transdermal",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            Route::Oral => &["po", "bymouth"],
+            Route::Sublingual => &["sl"],
+            Route::Iv => &["intravenous"],
+            Route::Im => &["intramuscular"],
+            Route::Sc => &["subcutaneous", "subcut", "sq"],
+            Route::Intradermal => &["id"],
+            Route::Inhaled => &["inh", "inhalation", "nebulised", "nebulized"],
+            Route::Topical => &["cutaneous"],
+            Route::Rectal => &["pr"],
+            Route::Vaginal => &["pv"],
+            Route::Ophthalmic => &["eye"],
+            Route::Otic => &["ear"],
+            Route::Nasal => &["intranasal"],
+            Route::Transdermal => &["patch"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Once,
+    OnceDaily,
+    TwiceDaily,
+    ThreeTimesDaily,
+    FourTimesDaily,
+    AtNight,
+    Weekly,
+    AsNeeded,
+}
+
+impl TextEnum for Frequency {
+    const ALL: &'static [Self] = &[
+        Frequency::Once,
+        Frequency::OnceDaily,
+        Frequency::TwiceDaily,
+        Frequency::ThreeTimesDaily,
+        Frequency::FourTimesDaily,
+        Frequency::AtNight,
+        Frequency::Weekly,
+        Frequency::AsNeeded,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Frequency::Once => "once",
+            Frequency::OnceDaily => "once_daily",
+            Frequency::TwiceDaily => "twice_daily",
+            Frequency::ThreeTimesDaily => "three_times_daily",
+            Frequency::FourTimesDaily => "four_times_daily",
+            Frequency::AtNight => "at_night",
+            Frequency::Weekly => "weekly",
+            Frequency::AsNeeded => "as_needed",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            Frequency::Once => &["stat", "single_dose"],
+            Frequency::OnceDaily => &["od", "qd", "daily"],
+            Frequency::TwiceDaily => &["bd", "bid"],
+            Frequency::ThreeTimesDaily => &["tds", "tid"],
+            Frequency::FourTimesDaily => &["qid", "qds"],
+            Frequency::AtNight => &["hs", "qhs", "nightly"],
+            Frequency::Weekly => &["once_weekly"],
+            Frequency::AsNeeded => &["prn", "sos"],
+        }
+    }
+}
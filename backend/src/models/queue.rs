@@ -0,0 +1,163 @@
+use serde::{Serialize, Deserialize};
+use chrono::{NaiveDate, NaiveDateTime};
+use sqlx::FromRow;
+
+use crate::services::validation::TextEnum;
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct QueueEntry {
+    pub id: i32,
+    pub patient_id: i32,
+    pub appointment_id: Option<i32>,
+    pub facility_id: i32,
+    pub status: String,
+    pub priority: Option<String>,
+    pub arrived_at: NaiveDateTime,
+    pub triaged_at: Option<NaiveDateTime>,
+    pub triaged_by: Option<i32>,
+    pub triage_notes: Option<String>,
+    pub consultation_started_at: Option<NaiveDateTime>,
+    pub consultation_ended_at: Option<NaiveDateTime>,
+    pub provider_id: Option<i32>,
+    pub left_at: Option<NaiveDateTime>,
+    pub left_reason: Option<String>,
+    pub registered_by: Option<i32>,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Wait times at one facility on one day, in minutes. The wait is arrival to the start of
+/// the consultation.
+#[derive(Serialize, Debug, Clone, FromRow)]
+pub struct WaitTimeStats {
+    pub facility_id: i32,
+    pub facility_name: String,
+    pub date: NaiveDate,
+    pub arrivals: i64,
+    pub seen: i64,
+    pub left_without_being_seen: i64,
+    pub avg_wait_minutes: Option<f64>,
+    pub median_wait_minutes: Option<f64>,
+    pub p90_wait_minutes: Option<f64>,
+    pub max_wait_minutes: Option<f64>,
+    /// Arrival to triage.
+    pub avg_triage_wait_minutes: Option<f64>,
+    pub avg_consultation_minutes: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueStatus {
+    Waiting,
+    Triaged,
+    InConsultation,
+    Completed,
+    /// Left before being seen.
+    Left,
+}
+
+impl TextEnum for QueueStatus {
+    const ALL: &'static [Self] = &[
+        QueueStatus::Waiting,
+        QueueStatus::Triaged,
+        QueueStatus::InConsultation,
+        QueueStatus::Completed,
+        QueueStatus::Left,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            QueueStatus::Waiting => "waiting",
+            QueueStatus::Triaged => "triaged",
+            QueueStatus::InConsultation => "in_consultation",
+            QueueStatus::Completed => "completed",
+            QueueStatus::Left => "left",
+        }
+    }
+}
+
+impl QueueStatus {
+    /// Statuses of patients still in the clinic.
+    pub const OPEN: &'static [&'static str] = &["waiting", "triaged", "in_consultation"];
+}
+
+/// A step in the waiting -> triaged -> in consultation -> completed workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueAction {
+    /// Triage, or re-triage a patient whose condition changed while waiting.
+    Triage,
+    Start,
+    Finish,
+    Leave,
+}
+
+impl QueueAction {
+    /// The status an entry in `from` moves to, or `None` if the step is not allowed.
+    pub fn apply(self, from: QueueStatus) -> Option<QueueStatus> {
+        use QueueStatus::*;
+        match (self, from) {
+            (QueueAction::Triage, Waiting | Triaged) => Some(Triaged),
+            (QueueAction::Start, Waiting | Triaged) => Some(InConsultation),
+            (QueueAction::Finish, InConsultation) => Some(Completed),
+            (QueueAction::Leave, Waiting | Triaged) => Some(Left),
+            _ => None,
+        }
+    }
+
+    pub fn past_tense(self) -> &'static str {
+        match self {
+            QueueAction::Triage => "triaged",
+            QueueAction::Start => "called in",
+            QueueAction::Finish => "finished",
+            QueueAction::Leave => "marked as left",
+        }
+    }
+}
+
+/// Triage category; the queue is ordered by it, then by arrival.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TriagePriority {
+    Emergency,
+    Urgent,
+    Routine,
+}
+
+impl TextEnum for TriagePriority {
+    const ALL: &'static [Self] = &[TriagePriority::Emergency, TriagePriority::Urgent, TriagePriority::Routine];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            TriagePriority::Emergency => "emergency",
+            TriagePriority::Urgent => "urgent",
+            TriagePriority::Routine => "routine",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            TriagePriority::Emergency => &["red", "immediate"],
+            TriagePriority::Urgent => &["yellow", "orange", "priority"],
+            TriagePriority::Routine => &["green", "standard", "normal"],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patients_move_forward_through_the_queue() {
+        use QueueAction::{Finish, Leave, Start, Triage};
+        use QueueStatus::*;
+        for from in QueueStatus::ALL.iter().copied() {
+            let expected = match from {
+                Waiting => [Some(Triaged), Some(InConsultation), None, Some(Left)],
+                Triaged => [Some(Triaged), Some(InConsultation), None, Some(Left)],
+                InConsultation => [None, None, Some(Completed), None],
+                Completed | Left => [None, None, None, None],
+            };
+            for (action, to) in [Triage, Start, Finish, Leave].into_iter().zip(expected) {
+                assert_eq!(action.apply(from), to, "{:?} from {:?}", action, from);
+            }
+        }
+    }
+}
@@ -2,6 +2,8 @@ use serde::{Serialize, Deserialize};
 use chrono::{NaiveDate, NaiveDateTime};
 use sqlx::FromRow;
 
+use crate::services::validation::TextEnum;
+
 #[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
 pub struct MedicalRecord {
     pub id: i32,
@@ -19,6 +21,140 @@ pub struct MedicalRecord {
     pub is_exported: Option<bool>,
     pub created_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
+    pub submitted_by: Option<i32>,
+    pub submitted_at: Option<NaiveDateTime>,
+    pub reviewed_at: Option<NaiveDateTime>,
+    pub signed_by: Option<i32>,
+    pub signed_at: Option<NaiveDateTime>,
+    /// Where the encounter took place.
+    pub facility_id: Option<i32>,
+    /// Always the patient's tenant.
+    pub tenant_id: i32,
+}
+
+impl MedicalRecord {
+    /// Signed records are immutable; they change only through amendments.
+    pub fn is_signed(&self) -> bool {
+        self.status == RecordStatus::Signed.as_str()
+    }
+}
+
+/// Where a record is in the sign-off workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordStatus {
+    Draft,
+    Submitted,
+    Reviewed,
+    Signed,
+}
+
+impl TextEnum for RecordStatus {
+    const ALL: &'static [Self] = &[
+        RecordStatus::Draft,
+        RecordStatus::Submitted,
+        RecordStatus::Reviewed,
+        RecordStatus::Signed,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            RecordStatus::Draft => "draft",
+            RecordStatus::Submitted => "submitted",
+            RecordStatus::Reviewed => "reviewed",
+            RecordStatus::Signed => "signed",
+        }
+    }
+}
+
+/// A step in the sign-off workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkflowAction {
+    Submit,
+    Review,
+    Sign,
+    Reject,
 }
 
-// Additional context or functions can follow here
+impl WorkflowAction {
+    /// The status this action leads to from `from`, or `None` if it does not apply there.
+    pub fn apply(self, from: RecordStatus) -> Option<RecordStatus> {
+        match (self, from) {
+            (WorkflowAction::Submit, RecordStatus::Draft) => Some(RecordStatus::Submitted),
+            (WorkflowAction::Review, RecordStatus::Submitted) => Some(RecordStatus::Reviewed),
+            (WorkflowAction::Sign, RecordStatus::Reviewed) => Some(RecordStatus::Signed),
+            (WorkflowAction::Reject, RecordStatus::Submitted | RecordStatus::Reviewed) => Some(RecordStatus::Draft),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordType {
+    Consultation,
+    Lab,
+    Prescription,
+    Imaging,
+    Vitals,
+    Procedure,
+    Immunization,
+    Referral,
+    Note,
+}
+
+impl TextEnum for RecordType {
+    const ALL: &'static [Self] = &[
+        RecordType::Consultation,
+        RecordType::Lab,
+        RecordType::Prescription,
+        RecordType::Imaging,
+        RecordType::Vitals,
+        RecordType::Procedure,
+        RecordType::Immunization,
+        RecordType::Referral,
+        RecordType::Note,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            RecordType::Consultation => "consultation",
+            RecordType::Lab => "lab",
+            RecordType::Prescription => "prescription",
+            RecordType::Imaging => "imaging",
+            RecordType::Vitals => "vitals",
+            RecordType::Procedure => "procedure",
+            RecordType::Immunization => "immunization",
+            RecordType::Referral => "referral",
+            RecordType::Note => "note",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            RecordType::Lab => &["labresult", "laboratory"],
+            RecordType::Vitals => &["vital", "vitalsigns"],
+            _ => &[],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_action_applies_only_from_its_own_status() {
+        use RecordStatus::*;
+        use WorkflowAction::{Reject, Review, Sign, Submit};
+        for from in RecordStatus::ALL.iter().copied() {
+            let expected = match from {
+                Draft => [Some(Submitted), None, None, None],
+                Submitted => [None, Some(Reviewed), None, Some(Draft)],
+                Reviewed => [None, None, Some(Signed), Some(Draft)],
+                Signed => [None, None, None, None],
+            };
+            for (action, to) in [Submit, Review, Sign, Reject].into_iter().zip(expected) {
+                assert_eq!(action.apply(from), to, "{:?} from {:?}", action, from);
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDateTime;
+use serde_json::Value;
+use sqlx::FromRow;
+
+/// One entry in a record's history; `changes` holds `{field: {from, to}}` against the previous version.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct RecordVersionSummary {
+    pub id: i32,
+    pub record_id: i32,
+    pub version: i32,
+    pub kind: String,
+    pub reason: Option<String>,
+    pub changes: Value,
+    pub changed_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+/// A version together with the full record as it stood after that change.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct RecordVersion {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub summary: RecordVersionSummary,
+    pub snapshot: Value,
+}
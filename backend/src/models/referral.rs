@@ -0,0 +1,216 @@
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+
+use crate::services::validation::TextEnum;
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct Referral {
+    pub id: i32,
+    pub patient_id: i32,
+    pub from_facility_id: i32,
+    pub to_facility_id: i32,
+    pub reason: String,
+    pub urgency: String,
+    pub clinical_summary: Option<String>,
+    pub record_id: Option<i32>,
+    pub status: String,
+    pub referred_by: Option<i32>,
+    pub sent_at: NaiveDateTime,
+    pub received_by: Option<i32>,
+    pub received_at: Option<NaiveDateTime>,
+    pub accepted_by: Option<i32>,
+    pub accepted_at: Option<NaiveDateTime>,
+    pub completed_by: Option<i32>,
+    pub completed_at: Option<NaiveDateTime>,
+    /// What the receiving facility did, for the referrer.
+    pub outcome: Option<String>,
+    /// Why the referral was declined or cancelled.
+    pub closed_reason: Option<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct ReferralNote {
+    pub id: i32,
+    pub referral_id: i32,
+    /// `referrer` or `receiver`.
+    pub side: String,
+    pub note: String,
+    pub author_id: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Referral counts for one facility over a period.
+#[derive(Serialize, Debug, Clone, FromRow)]
+pub struct ReferralCompletion {
+    pub facility_id: i32,
+    pub facility_name: String,
+    pub total: i64,
+    /// Not yet received.
+    pub sent: i64,
+    pub received: i64,
+    pub accepted: i64,
+    pub completed: i64,
+    pub declined: i64,
+    pub cancelled: i64,
+    /// Completed as a percentage of referrals not cancelled.
+    pub completion_rate: Option<f64>,
+    /// Days from sending to completion, for completed referrals.
+    pub median_days_to_complete: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferralStatus {
+    Sent,
+    Received,
+    Accepted,
+    Completed,
+    Declined,
+    Cancelled,
+}
+
+impl TextEnum for ReferralStatus {
+    const ALL: &'static [Self] = &[
+        ReferralStatus::Sent,
+        ReferralStatus::Received,
+        ReferralStatus::Accepted,
+        ReferralStatus::Completed,
+        ReferralStatus::Declined,
+        ReferralStatus::Cancelled,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ReferralStatus::Sent => "sent",
+            ReferralStatus::Received => "received",
+            ReferralStatus::Accepted => "accepted",
+            ReferralStatus::Completed => "completed",
+            ReferralStatus::Declined => "declined",
+            ReferralStatus::Cancelled => "cancelled",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            ReferralStatus::Cancelled => &["canceled", "withdrawn"],
+            ReferralStatus::Declined => &["rejected"],
+            _ => &[],
+        }
+    }
+}
+
+impl ReferralStatus {
+    /// Referrals the receiving facility still has to act on.
+    pub const OPEN: &'static [&'static str] = &["sent", "received", "accepted"];
+}
+
+/// A step in the sent -> received -> accepted -> completed workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferralAction {
+    Receive,
+    /// Accepting a referral that was never marked received also receives it.
+    Accept,
+    Decline,
+    Complete,
+    /// Withdrawn by the referring facility.
+    Cancel,
+}
+
+impl ReferralAction {
+    /// The status a referral in `from` moves to, or `None` if the step is not allowed.
+    pub fn apply(self, from: ReferralStatus) -> Option<ReferralStatus> {
+        use ReferralStatus::*;
+        match (self, from) {
+            (ReferralAction::Receive, Sent) => Some(Received),
+            (ReferralAction::Accept, Sent | Received) => Some(Accepted),
+            (ReferralAction::Decline, Sent | Received) => Some(Declined),
+            (ReferralAction::Complete, Accepted) => Some(Completed),
+            (ReferralAction::Cancel, Sent | Received | Accepted) => Some(Cancelled),
+            _ => None,
+        }
+    }
+
+    pub fn past_tense(self) -> &'static str {
+        match self {
+            ReferralAction::Receive => "received",
+            ReferralAction::Accept => "accepted",
+            ReferralAction::Decline => "declined",
+            ReferralAction::Complete => "completed",
+            ReferralAction::Cancel => "cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferralUrgency {
+    Routine,
+    Urgent,
+    Emergency,
+}
+
+impl TextEnum for ReferralUrgency {
+    const ALL: &'static [Self] = &[ReferralUrgency::Routine, ReferralUrgency::Urgent, ReferralUrgency::Emergency];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ReferralUrgency::Routine => "routine",
+            ReferralUrgency::Urgent => "urgent",
+            ReferralUrgency::Emergency => "emergency",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            ReferralUrgency::Routine => &["normal", "elective"],
+            ReferralUrgency::Urgent => &["priority"],
+            ReferralUrgency::Emergency => &["immediate", "stat"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferralSide {
+    Referrer,
+    Receiver,
+}
+
+impl TextEnum for ReferralSide {
+    const ALL: &'static [Self] = &[ReferralSide::Referrer, ReferralSide::Receiver];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ReferralSide::Referrer => "referrer",
+            ReferralSide::Receiver => "receiver",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            ReferralSide::Referrer => &["from", "source", "sender"],
+            ReferralSide::Receiver => &["to", "destination", "recipient"],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn referrals_close_once_and_complete_only_when_accepted() {
+        use ReferralAction::{Accept, Cancel, Complete, Decline, Receive};
+        use ReferralStatus::*;
+        for from in ReferralStatus::ALL.iter().copied() {
+            let expected = match from {
+                Sent => [Some(Received), Some(Accepted), Some(Declined), None, Some(Cancelled)],
+                Received => [None, Some(Accepted), Some(Declined), None, Some(Cancelled)],
+                Accepted => [None, None, None, Some(Completed), Some(Cancelled)],
+                Completed | Declined | Cancelled => [None; 5],
+            };
+            for (action, to) in [Receive, Accept, Decline, Complete, Cancel].into_iter().zip(expected) {
+                assert_eq!(action.apply(from), to, "{:?} from {:?}", action, from);
+            }
+        }
+    }
+}
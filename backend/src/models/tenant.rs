@@ -0,0 +1,13 @@
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+
+/// A partner organisation. Its users only ever see its own patients, records and consents.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct Tenant {
+    pub id: i32,
+    pub code: String,
+    pub name: String,
+    pub active: bool,
+    pub created_at: NaiveDateTime,
+}
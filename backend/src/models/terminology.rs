@@ -0,0 +1,138 @@
+use serde::{Serialize, Deserialize};
+use chrono::{NaiveDate, NaiveDateTime};
+use sqlx::FromRow;
+
+use crate::services::validation::TextEnum;
+
+/// A code system loaded from a file in the terminology directory.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct TerminologySystem {
+    pub code: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub source_file: String,
+    pub checksum: String,
+    pub code_count: i32,
+    pub loaded_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct TerminologyCode {
+    pub system: String,
+    pub code: String,
+    pub display: String,
+    pub synonyms: Vec<String>,
+    /// `false` once the code has been dropped from its file; kept for existing diagnoses.
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct Diagnosis {
+    pub id: i32,
+    pub patient_id: i32,
+    pub record_id: Option<i32>,
+    pub system: String,
+    pub code: String,
+    pub display: String,
+    pub onset_date: Option<NaiveDate>,
+    /// Set once the condition has resolved; open diagnoses have none.
+    pub resolved_date: Option<NaiveDate>,
+    pub notes: Option<String>,
+    /// `clinician`, or `condition_mapping` when coded from a free-text condition.
+    pub source: String,
+    pub recorded_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_by: Option<i32>,
+    pub updated_at: NaiveDateTime,
+}
+
+/// A free-text entry of a patient's `active_conditions` and the code it maps to.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct ConditionMapping {
+    pub id: i32,
+    pub patient_id: i32,
+    pub source_text: String,
+    pub normalized_text: String,
+    pub status: String,
+    /// `exact`, `learned`, `suggested`, `reviewed` or `none`.
+    pub match_kind: String,
+    pub system: Option<String>,
+    pub code: Option<String>,
+    pub diagnosis_id: Option<i32>,
+    pub reviewed_by: Option<i32>,
+    pub reviewed_at: Option<NaiveDateTime>,
+    pub review_note: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosisStatus {
+    Active,
+    Resolved,
+}
+
+impl TextEnum for DiagnosisStatus {
+    const ALL: &'static [Self] = &[DiagnosisStatus::Active, DiagnosisStatus::Resolved];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            DiagnosisStatus::Active => "active",
+            DiagnosisStatus::Resolved => "resolved",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionMappingStatus {
+    /// Waiting for someone to confirm or pick a code.
+    Pending,
+    Mapped,
+    /// Reviewed and found not to be a codable condition.
+    Unmappable,
+}
+
+impl TextEnum for ConditionMappingStatus {
+    const ALL: &'static [Self] = &[
+        ConditionMappingStatus::Pending,
+        ConditionMappingStatus::Mapped,
+        ConditionMappingStatus::Unmappable,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ConditionMappingStatus::Pending => "pending",
+            ConditionMappingStatus::Mapped => "mapped",
+            ConditionMappingStatus::Unmappable => "unmappable",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// The text is the code, its display or one of its synonyms.
+    Exact,
+    /// A reviewer already mapped the same text for another patient.
+    Learned,
+    /// The best search hit, which a reviewer has to confirm.
+    Suggested,
+    /// Picked or confirmed by a reviewer.
+    Reviewed,
+    None,
+}
+
+impl MatchKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MatchKind::Exact => "exact",
+            MatchKind::Learned => "learned",
+            MatchKind::Suggested => "suggested",
+            MatchKind::Reviewed => "reviewed",
+            MatchKind::None => "none",
+        }
+    }
+
+    /// Whether a match is good enough to code the condition without review.
+    pub fn is_confident(self) -> bool {
+        matches!(self, MatchKind::Exact | MatchKind::Learned)
+    }
+}
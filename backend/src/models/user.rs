@@ -5,10 +5,55 @@ use serde::{Serialize, Deserialize};
 use chrono::NaiveDateTime;
 use sqlx::FromRow;
 
+use crate::services::validation::TextEnum;
+
 #[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
 pub struct User {
     pub id: i32,
     pub email: String,
     pub password_hash: String,
     pub created_at: Option<NaiveDateTime>,
+    pub role: String,
+    pub department_id: Option<i32>,
+    pub tenant_id: i32,
+}
+
+/// What a member of staff is allowed to do; stored in `users.role` and carried in access tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaffRole {
+    /// Administers their own tenant.
+    Admin,
+    /// Administers their own tenant and manages tenants; may create users in any tenant.
+    PlatformAdmin,
+    Clinician,
+    Technician,
+}
+
+impl TextEnum for StaffRole {
+    const ALL: &'static [Self] = &[StaffRole::Admin, StaffRole::PlatformAdmin, StaffRole::Clinician, StaffRole::Technician];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            StaffRole::Admin => "admin",
+            StaffRole::PlatformAdmin => "platform_admin",
+            StaffRole::Clinician => "clinician",
+            StaffRole::Technician => "technician",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            StaffRole::Admin => &["administrator", "tenantadmin"],
+            StaffRole::PlatformAdmin => &["platformadmin", "superadmin"],
+            StaffRole::Clinician => &["doctor", "nurse", "physician"],
+            StaffRole::Technician => &["labtechnician", "tech"],
+        }
+    }
+}
+
+impl StaffRole {
+    /// Tenant admins and platform admins alike.
+    pub fn is_admin(self) -> bool {
+        matches!(self, StaffRole::Admin | StaffRole::PlatformAdmin)
+    }
 }
@@ -0,0 +1,211 @@
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+
+use crate::services::validation::TextEnum;
+
+/// Observations taken together at one point in time, e.g. a triage or ward round.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct VitalSet {
+    pub id: i32,
+    pub patient_id: i32,
+    pub record_id: Option<i32>,
+    pub observed_at: NaiveDateTime,
+    pub recorded_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub supplemental_oxygen: Option<bool>,
+    pub consciousness: Option<String>,
+    /// NEWS2 total for adults, from the parameters present in the set.
+    pub news2_score: Option<i32>,
+    pub news2_risk: Option<String>,
+    /// NEWS2 parameters missing from the set; a partial score can only understate risk.
+    pub news2_missing: Option<Vec<String>>,
+}
+
+/// A single measurement in its kind's canonical unit, flagged against the reference range
+/// that applied to the patient when it was taken.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct VitalObservation {
+    pub id: i32,
+    pub set_id: i32,
+    pub patient_id: i32,
+    pub kind: String,
+    pub value: f64,
+    pub unit: String,
+    pub observed_at: NaiveDateTime,
+    /// Computed from other observations (BMI) rather than measured.
+    pub derived: bool,
+    pub reference_low: Option<f64>,
+    pub reference_high: Option<f64>,
+    pub flag: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VitalKind {
+    SystolicBp,
+    DiastolicBp,
+    HeartRate,
+    Temperature,
+    Spo2,
+    RespiratoryRate,
+    Weight,
+    Height,
+    Bmi,
+    BloodGlucose,
+}
+
+impl TextEnum for VitalKind {
+    const ALL: &'static [Self] = &[
+        VitalKind::SystolicBp,
+        VitalKind::DiastolicBp,
+        VitalKind::HeartRate,
+        VitalKind::Temperature,
+        VitalKind::Spo2,
+        VitalKind::RespiratoryRate,
+        VitalKind::Weight,
+        VitalKind::Height,
+        VitalKind::Bmi,
+        VitalKind::BloodGlucose,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            VitalKind::SystolicBp => "systolic_bp",
+            VitalKind::DiastolicBp => "diastolic_bp",
+            VitalKind::HeartRate => "heart_rate",
+            VitalKind::Temperature => "temperature",
+            VitalKind::Spo2 => "spo2",
+            VitalKind::RespiratoryRate => "respiratory_rate",
+            VitalKind::Weight => "weight",
+            VitalKind::Height => "height",
+            VitalKind::Bmi => "bmi",
+            VitalKind::BloodGlucose => "blood_glucose",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            VitalKind::SystolicBp => &["sbp", "systolic"],
+            VitalKind::DiastolicBp => &["dbp", "diastolic"],
+            VitalKind::HeartRate => &["hr", "pulse", "pulse_rate"],
+            VitalKind::Temperature => &["temp"],
+            VitalKind::Spo2 => &["oxygen_saturation", "o2_saturation", "sao2"],
+            VitalKind::RespiratoryRate => &["rr", "resp_rate"],
+            VitalKind::Weight => &[],
+            VitalKind::Height => &["length"],
+            VitalKind::Bmi => &[],
+            VitalKind::BloodGlucose => &["glucose", "blood_sugar"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VitalFlag {
+    Normal,
+    Low,
+    High,
+    CriticalLow,
+    CriticalHigh,
+}
+
+impl TextEnum for VitalFlag {
+    const ALL: &'static [Self] = &[
+        VitalFlag::Normal,
+        VitalFlag::Low,
+        VitalFlag::High,
+        VitalFlag::CriticalLow,
+        VitalFlag::CriticalHigh,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            VitalFlag::Normal => "normal",
+            VitalFlag::Low => "low",
+            VitalFlag::High => "high",
+            VitalFlag::CriticalLow => "critical_low",
+            VitalFlag::CriticalHigh => "critical_high",
+        }
+    }
+}
+
+/// Normal and critical limits for one kind, age band and (optionally) sex.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct VitalReferenceRange {
+    pub id: i32,
+    pub kind: String,
+    pub sex: Option<String>,
+    pub min_age_months: i32,
+    pub max_age_months: Option<i32>,
+    pub low: Option<f64>,
+    pub high: Option<f64>,
+    pub critical_low: Option<f64>,
+    pub critical_high: Option<f64>,
+}
+
+/// Level of consciousness on the ACVPU scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Consciousness {
+    Alert,
+    NewConfusion,
+    Voice,
+    Pain,
+    Unresponsive,
+}
+
+impl TextEnum for Consciousness {
+    const ALL: &'static [Self] = &[
+        Consciousness::Alert,
+        Consciousness::NewConfusion,
+        Consciousness::Voice,
+        Consciousness::Pain,
+        Consciousness::Unresponsive,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Consciousness::Alert => "alert",
+            Consciousness::NewConfusion => "new_confusion",
+            Consciousness::Voice => "voice",
+            Consciousness::Pain => "pain",
+            Consciousness::Unresponsive => "unresponsive",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            Consciousness::Alert => &["a"],
+            Consciousness::NewConfusion => &["c", "confusion", "confused"],
+            Consciousness::Voice => &["v"],
+            Consciousness::Pain => &["p"],
+            Consciousness::Unresponsive => &["u"],
+        }
+    }
+}
+
+/// NEWS2 clinical risk bands, in increasing order of urgency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EarlyWarningRisk {
+    Low,
+    /// A single parameter scored 3 although the total is below 5.
+    LowMedium,
+    Medium,
+    High,
+}
+
+impl TextEnum for EarlyWarningRisk {
+    const ALL: &'static [Self] = &[
+        EarlyWarningRisk::Low,
+        EarlyWarningRisk::LowMedium,
+        EarlyWarningRisk::Medium,
+        EarlyWarningRisk::High,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            EarlyWarningRisk::Low => "low",
+            EarlyWarningRisk::LowMedium => "low_medium",
+            EarlyWarningRisk::Medium => "medium",
+            EarlyWarningRisk::High => "high",
+        }
+    }
+}
@@ -1,10 +1,13 @@
-use axum::{routing::{get, post}, Router};
+use axum::{routing::{get, post, put}, Router};
 use crate::handlers::administration_handler::get_administration;
 use crate::handlers::administration_handler::create_user;
-use sqlx::PgPool;
+use crate::handlers::administration_handler::{list_tenants, create_tenant, update_tenant};
+use crate::config::state::AppState;
 
-pub fn routes() -> Router<PgPool> {
+pub fn routes() -> Router<AppState> {
     Router::new()
     .route("/administration", get(get_administration))
     .route("/api/users", post(create_user))
+    .route("/tenants", get(list_tenants).post(create_tenant))
+    .route("/tenants/:id", put(update_tenant))
 }
@@ -1,8 +1,9 @@
 use axum::{routing::get, Router};
-use crate::handlers::analytics_handler::get_analytics;
-use sqlx::PgPool;
+use crate::handlers::analytics_handler::{get_analytics, get_village_map};
+use crate::config::state::AppState;
 
-pub fn routes() -> Router<PgPool> {
+pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/analytics", get(get_analytics))
+        .route("/analytics/villages/geojson", get(get_village_map))
 }
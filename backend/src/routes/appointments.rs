@@ -0,0 +1,23 @@
+use axum::{routing::{delete, get, post}, Router};
+use crate::handlers::appointments_handler::{
+    list_appointments, book_appointment, get_appointment, list_patient_appointments, reschedule_appointment,
+    check_in_appointment, mark_appointment_seen, mark_appointment_no_show, cancel_appointment, list_availability,
+    add_availability, delete_availability, list_provider_slots, get_daily_schedule,
+};
+use crate::config::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/appointments", get(list_appointments).post(book_appointment))
+        .route("/appointments/schedule", get(get_daily_schedule))
+        .route("/appointments/:id", get(get_appointment))
+        .route("/appointments/:id/reschedule", post(reschedule_appointment))
+        .route("/appointments/:id/check-in", post(check_in_appointment))
+        .route("/appointments/:id/seen", post(mark_appointment_seen))
+        .route("/appointments/:id/no-show", post(mark_appointment_no_show))
+        .route("/appointments/:id/cancel", post(cancel_appointment))
+        .route("/patients/:id/appointments", get(list_patient_appointments))
+        .route("/providers/:id/availability", get(list_availability).post(add_availability))
+        .route("/providers/:id/slots", get(list_provider_slots))
+        .route("/availability/:id", delete(delete_availability))
+}
@@ -1,8 +1,8 @@
 use axum::{routing::post, Router};
 use crate::handlers::auth_handler::login;
-use sqlx::PgPool;
+use crate::config::state::AppState;
 
-pub fn routes() -> Router<PgPool> {
+pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/login", post(login))
 }
\ No newline at end of file
@@ -1,41 +1,8 @@
-use axum::{Json, extract::{State, Path}, routing::get, Router};
-use sqlx::PgPool;
-use crate::models::consent::Consent;
-use axum::http::StatusCode;
+use axum::{routing::get, Router};
+use crate::handlers::consents_handler::{list_consents, get_consent, update_consent};
+use crate::config::state::AppState;
 
-// List all consents
-pub async fn list_consents(State(pool): State<PgPool>) -> Result<Json<Vec<Consent>>, StatusCode> {
-    let consents = sqlx::query_as!(Consent, "SELECT * FROM consents")
-        .fetch_all(&pool)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    Ok(Json(consents))
-}
-
-// Get a single consent by id
-pub async fn get_consent(State(pool): State<PgPool>, Path(id): Path<i32>) -> Result<Json<Consent>, StatusCode> {
-    let consent = sqlx::query_as!(Consent, "SELECT * FROM consents WHERE id = $1", id)
-        .fetch_one(&pool)
-        .await
-        .map_err(|_| StatusCode::NOT_FOUND)?;
-    Ok(Json(consent))
-}
-
-// Update a consent (grant/revoke)
-#[derive(serde::Deserialize)]
-pub struct UpdateConsent {
-    pub granted: bool,
-}
-
-pub async fn update_consent(State(pool): State<PgPool>, Path(id): Path<i32>, Json(payload): Json<UpdateConsent>) -> Result<StatusCode, StatusCode> {
-    sqlx::query!("UPDATE consents SET granted = $1 WHERE id = $2", payload.granted, id)
-        .execute(&pool)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    Ok(StatusCode::NO_CONTENT)
-}
-
-pub fn routes() -> Router<PgPool> {
+pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/consents", get(list_consents))
         .route("/consents/:id", get(get_consent).put(update_consent))
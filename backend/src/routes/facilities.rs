@@ -0,0 +1,15 @@
+use axum::{routing::get, Router};
+use crate::handlers::facilities_handler::{
+    list_facilities, create_facility, get_facility, update_facility, list_facility_staff,
+    list_departments, create_department, get_user_assignment, set_user_assignment,
+};
+use crate::config::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/facilities", get(list_facilities).post(create_facility))
+        .route("/facilities/:id", get(get_facility).put(update_facility))
+        .route("/facilities/:id/staff", get(list_facility_staff))
+        .route("/departments", get(list_departments).post(create_department))
+        .route("/users/:id/assignment", get(get_user_assignment).put(set_user_assignment))
+}
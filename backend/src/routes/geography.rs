@@ -0,0 +1,10 @@
+use axum::{routing::get, Router};
+use crate::handlers::geography_handler::{list_areas, create_area, get_area, update_area, get_areas_geojson};
+use crate::config::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/geography", get(list_areas).post(create_area))
+        .route("/geography/geojson", get(get_areas_geojson))
+        .route("/geography/:id", get(get_area).put(update_area))
+}
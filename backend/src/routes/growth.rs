@@ -0,0 +1,12 @@
+use axum::{routing::{delete, get}, Router};
+use crate::handlers::growth_handler::{
+    list_growth_measurements, record_growth_measurement, delete_growth_measurement, get_growth_chart,
+};
+use crate::config::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/patients/:id/growth", get(list_growth_measurements).post(record_growth_measurement))
+        .route("/patients/:id/growth-chart", get(get_growth_chart))
+        .route("/growth-measurements/:id", delete(delete_growth_measurement))
+}
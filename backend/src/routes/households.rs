@@ -0,0 +1,15 @@
+use axum::{routing::{delete, get}, Router};
+use crate::handlers::households_handler::{
+    list_households, get_household, create_household, update_household, delete_household,
+    list_household_members, add_household_member, remove_household_member, get_household_outreach,
+};
+use crate::config::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/households", get(list_households).post(create_household))
+        .route("/households/outreach", get(get_household_outreach))
+        .route("/households/:id", get(get_household).put(update_household).delete(delete_household))
+        .route("/households/:id/members", get(list_household_members).post(add_household_member))
+        .route("/households/:id/members/:patient_id", delete(remove_household_member))
+}
@@ -0,0 +1,19 @@
+use axum::{routing::{delete, get, put}, Router};
+use crate::handlers::immunizations_handler::{
+    list_vaccines, put_vaccine, list_schedules, get_schedule, put_schedule, list_immunizations, record_immunization,
+    delete_immunization, get_immunization_card, get_immunization_coverage, list_immunization_defaulters,
+};
+use crate::config::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/immunization/vaccines", get(list_vaccines))
+        .route("/immunization/vaccines/:code", put(put_vaccine))
+        .route("/immunization/schedules", get(list_schedules))
+        .route("/immunization/schedules/:code", get(get_schedule).put(put_schedule))
+        .route("/immunization/coverage", get(get_immunization_coverage))
+        .route("/immunization/defaulters", get(list_immunization_defaulters))
+        .route("/patients/:id/immunizations", get(list_immunizations).post(record_immunization))
+        .route("/patients/:id/immunization-card", get(get_immunization_card))
+        .route("/immunizations/:id", delete(delete_immunization))
+}
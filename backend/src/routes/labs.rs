@@ -0,0 +1,18 @@
+use axum::{routing::{get, post}, Router};
+use crate::handlers::labs_handler::{
+    list_lab_tests, create_lab_order, list_lab_orders, list_outstanding_lab_orders, get_lab_order,
+    collect_lab_order, report_lab_results, review_lab_order, cancel_lab_order,
+};
+use crate::config::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/lab/tests", get(list_lab_tests))
+        .route("/lab/orders", get(list_lab_orders).post(create_lab_order))
+        .route("/lab/orders/outstanding", get(list_outstanding_lab_orders))
+        .route("/lab/orders/:id", get(get_lab_order))
+        .route("/lab/orders/:id/collect", post(collect_lab_order))
+        .route("/lab/orders/:id/results", post(report_lab_results))
+        .route("/lab/orders/:id/review", post(review_lab_order))
+        .route("/lab/orders/:id/cancel", post(cancel_lab_order))
+}
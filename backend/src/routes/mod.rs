@@ -4,3 +4,1252 @@ pub mod records;
 pub mod analytics;
 pub mod administration;
 pub mod consents;
+pub mod households;
+pub mod vitals;
+pub mod labs;
+pub mod prescriptions;
+pub mod terminology;
+pub mod immunizations;
+pub mod pregnancies;
+pub mod growth;
+pub mod appointments;
+pub mod queue;
+pub mod referrals;
+pub mod facilities;
+pub mod geography;
+
+use axum::{routing::get, Router};
+
+use crate::config::state::AppState;
+
+/// The whole API, before the tracing and CORS layers `main` adds.
+pub fn app(state: AppState) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .merge(auth::routes())
+        .merge(patients::routes())
+        .merge(records::routes())
+        .merge(analytics::routes())
+        .merge(administration::routes())
+        .merge(consents::routes())
+        .merge(households::routes())
+        .merge(vitals::routes())
+        .merge(labs::routes())
+        .merge(prescriptions::routes())
+        .merge(terminology::routes())
+        .merge(immunizations::routes())
+        .merge(pregnancies::routes())
+        .merge(growth::routes())
+        .merge(appointments::routes())
+        .merge(queue::routes())
+        .merge(referrals::routes())
+        .merge(facilities::routes())
+        .merge(geography::routes())
+        .with_state(state)
+}
+
+async fn health() -> &'static str {
+    "OK"
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{header, Method, Request, StatusCode};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use sqlx::{Executor, PgPool};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::models::error::ServiceError;
+    use crate::models::facility::Facility;
+    use crate::models::geography::GeoArea;
+    use crate::models::lab::LabReferenceRange;
+    use crate::models::terminology::MatchKind;
+    use crate::models::user::User;
+    use crate::services::facilities::{facility_scope, optional_facility, require_facility};
+    use crate::services::geography::resolve_village;
+    use crate::services::labs::applicable_ranges;
+    use crate::services::queue::wait_time_stats;
+    use crate::services::terminology::{match_condition, search_codes, LoadSummary, TerminologyError, TerminologyFiles};
+
+    struct SeededTenant {
+        patient_id: i32,
+        record_id: i32,
+        token: String,
+    }
+
+    /// Apply every migration in order. The oldest files keep their down steps uncommented
+    /// after `-- Down`, so only what comes before it is run.
+    async fn migrate(pool: &PgPool) {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/migrations");
+        let mut files: Vec<_> = std::fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        files.sort();
+        for file in files {
+            let sql = std::fs::read_to_string(&file).unwrap();
+            let up = sql.split("\n-- Down").next().unwrap_or_default();
+            pool.execute(up).await.unwrap_or_else(|e| panic!("{}: {}", file.display(), e));
+        }
+    }
+
+    /// A tenant with an admin and one patient who has a record awaiting review, a high NEWS2
+    /// score and an open alert, so every list below has something of theirs to leak.
+    async fn seed_tenant(pool: &PgPool, state: &AppState, code: &str) -> SeededTenant {
+        let tenant_id: i32 = sqlx::query_scalar("INSERT INTO tenants (code, name) VALUES ($1, $1) RETURNING id")
+            .bind(code)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        let admin = sqlx::query_as::<_, User>(
+            "INSERT INTO users (email, password_hash, role, tenant_id) VALUES ($1, '-', 'admin', $2) RETURNING *",
+        )
+        .bind(format!("admin@{}.test", code))
+        .bind(tenant_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        let patient_id: i32 = sqlx::query_scalar(
+            "INSERT INTO patients (first_name, last_name, date_of_birth, gender, phone_number, status, tenant_id)
+             VALUES ('Test', $1, '1980-01-01', 'female', '+15550100', 'active', $2) RETURNING id",
+        )
+        .bind(code)
+        .bind(tenant_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        let record_id: i32 = sqlx::query_scalar(
+            "INSERT INTO medical_records (patient_id, record_type, title, provider, date, status)
+             VALUES ($1, 'consultation', 'Visit', 'Dr Test', CURRENT_DATE, 'submitted') RETURNING id",
+        )
+        .bind(patient_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        let set_id: i32 = sqlx::query_scalar(
+            "INSERT INTO vital_sets (patient_id, observed_at, news2_score, news2_risk)
+             VALUES ($1, now()::timestamp, 7, 'high') RETURNING id",
+        )
+        .bind(patient_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO clinical_alerts (patient_id, vital_set_id, kind, severity, message)
+             VALUES ($1, $2, 'news2', 'high', 'NEWS2 score 7')",
+        )
+        .bind(patient_id)
+        .bind(set_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        let (token, _) = state.tokens.issue(&admin).unwrap();
+        SeededTenant { patient_id, record_id, token }
+    }
+
+    async fn get(app: &Router, token: &str, uri: &str) -> (StatusCode, Value) {
+        send(app, Method::GET, token, uri, None).await
+    }
+
+    async fn send(app: &Router, method: Method, token: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    /// The patients a listing mentions; `pointer` locates the array in the response.
+    fn listed_patients(body: &Value, pointer: &str) -> Vec<i64> {
+        let items = body.pointer(pointer).and_then(Value::as_array).cloned().unwrap_or_default();
+        items
+            .iter()
+            .filter_map(|item| item.get("patient_id").or_else(|| item.get("id")).and_then(Value::as_i64))
+            .collect()
+    }
+
+    async fn check_isolation(pool: PgPool) {
+        migrate(&pool).await;
+        let state = AppState::from_env(pool.clone());
+        let north = seed_tenant(&pool, &state, "north").await;
+        let south = seed_tenant(&pool, &state, "south").await;
+        let app = app(state);
+
+        let patient = north.patient_id;
+        for uri in [
+            format!("/patients/{}", patient),
+            format!("/patients/{}/profile", patient),
+            format!("/patients/{}/timeline", patient),
+            format!("/patients/{}/records", patient),
+            format!("/patients/{}/vitals", patient),
+            format!("/patients/{}/vitals/latest", patient),
+            format!("/patients/{}/care-team", patient),
+            format!("/patients/{}/prescriptions", patient),
+            format!("/patients/{}/diagnoses", patient),
+            format!("/patients/{}/immunizations", patient),
+            format!("/patients/{}/pregnancies", patient),
+            format!("/patients/{}/growth", patient),
+            format!("/records/{}", north.record_id),
+            format!("/records/{}/versions", north.record_id),
+        ] {
+            assert_eq!(get(&app, &north.token, &uri).await.0, StatusCode::OK, "{} for its own tenant", uri);
+            assert_eq!(get(&app, &south.token, &uri).await.0, StatusCode::NOT_FOUND, "{} for another tenant", uri);
+        }
+
+        for (uri, pointer) in [
+            ("/patients", ""),
+            ("/records", "/records"),
+            ("/records/worklist", ""),
+            ("/vitals/deteriorating", ""),
+            ("/alerts?status=all", ""),
+        ] {
+            let (status, body) = get(&app, &north.token, uri).await;
+            assert_eq!(status, StatusCode::OK, "{}", uri);
+            assert!(listed_patients(&body, pointer).contains(&i64::from(patient)), "{} for its own tenant", uri);
+            let (status, body) = get(&app, &south.token, uri).await;
+            assert_eq!(status, StatusCode::OK, "{}", uri);
+            let listed = listed_patients(&body, pointer);
+            assert!(!listed.contains(&i64::from(patient)), "{} for another tenant lists {:?}", uri, listed);
+            assert!(listed.contains(&i64::from(south.patient_id)), "{} misses the tenant's own patient", uri);
+        }
+
+        for (method, uri, body) in [
+            (Method::PUT, format!("/patients/{}", patient), Some(json!({ "first_name": "Changed" }))),
+            (Method::POST, format!("/records/{}/review", north.record_id), None),
+            (Method::DELETE, format!("/records/{}", north.record_id), None),
+            (Method::DELETE, format!("/patients/{}", patient), None),
+        ] {
+            let (status, _) = send(&app, method.clone(), &south.token, &uri, body).await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{} {} for another tenant", method, uri);
+        }
+        let (_, record) = get(&app, &north.token, &format!("/records/{}", north.record_id)).await;
+        assert_eq!(record["status"], "submitted");
+        let (_, body) = get(&app, &north.token, &format!("/patients/{}", patient)).await;
+        assert_eq!(body["first_name"], "Test");
+
+        // facilities, geography and code tables are shared, so a tenant admin may not change them
+        let facility_id: i32 =
+            sqlx::query_scalar("INSERT INTO facilities (code, name) VALUES ('HQ', 'Headquarters') RETURNING id")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        for (method, uri, body) in [
+            (Method::PUT, format!("/facilities/{}", facility_id), json!({ "name": "Renamed" })),
+            (Method::POST, "/facilities".to_string(), json!({ "code": "NEW", "name": "New" })),
+            (Method::POST, "/geography".to_string(), json!({ "level": "state", "name": "New" })),
+            (Method::POST, "/terminology/reload".to_string(), json!({})),
+        ] {
+            let (status, _) = send(&app, method.clone(), &north.token, &uri, Some(body)).await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{} {} for a tenant admin", method, uri);
+        }
+        let (_, facility) = get(&app, &north.token, &format!("/facilities/{}", facility_id)).await;
+        assert_eq!(facility["name"], "Headquarters");
+    }
+
+    async fn add_user(pool: &PgPool, state: &AppState, email: &str, role: &str) -> (i32, String) {
+        let user = sqlx::query_as::<_, User>(
+            "INSERT INTO users (email, password_hash, role, tenant_id)
+             SELECT $1, '-', $2, tenant_id FROM users WHERE email = 'admin@north.test' RETURNING *",
+        )
+        .bind(email)
+        .bind(role)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        (user.id, state.tokens.issue(&user).unwrap().0)
+    }
+
+    async fn act(app: &Router, token: &str, record: &str, action: &str, body: Option<Value>) -> (StatusCode, Value) {
+        send(app, Method::POST, token, &format!("{}/{}", record, action), body).await
+    }
+
+    async fn check_sign_off(pool: PgPool) {
+        migrate(&pool).await;
+        let state = AppState::from_env(pool.clone());
+        let north = seed_tenant(&pool, &state, "north").await;
+        let (clinician_id, clinician) = add_user(&pool, &state, "clinician@north.test", "clinician").await;
+        let (_, technician) = add_user(&pool, &state, "technician@north.test", "technician").await;
+        let app = app(state);
+        let record = format!("/records/{}", north.record_id);
+        let edit = json!({ "title": "Edited" });
+
+        let admin = north.token.as_str();
+        let status_of = |body: &Value, field: &str| (body["status"].clone(), body[field].clone());
+
+        // the seeded record is awaiting review
+        assert_eq!(act(&app, admin, &record, "sign", None).await.0, StatusCode::CONFLICT);
+        assert_eq!(act(&app, admin, &record, "submit", None).await.0, StatusCode::CONFLICT);
+        let blank_note = Some(json!({ "note": " " }));
+        assert_eq!(act(&app, admin, &record, "reject", blank_note).await.0, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(act(&app, &technician, &record, "review", None).await.0, StatusCode::FORBIDDEN);
+        let (status, body) = act(&app, admin, &record, "review", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(status_of(&body, "reviewed_by"), (json!("reviewed"), json!("admin@north.test")));
+        assert_eq!(send(&app, Method::PUT, admin, &record, Some(edit.clone())).await.0, StatusCode::CONFLICT);
+
+        // rejected back to draft, edited, and resubmitted by a clinician who may not review it
+        let note = Some(json!({ "note": "Missing findings" }));
+        let (status, body) = act(&app, admin, &record, "reject", note).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(status_of(&body, "secondary_status"), (json!("draft"), json!("rejected")));
+        assert_eq!(body["reviewed_by"], Value::Null);
+        assert_eq!(send(&app, Method::PUT, &clinician, &record, Some(edit.clone())).await.0, StatusCode::OK);
+        let (status, body) = act(&app, &clinician, &record, "submit", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(status_of(&body, "submitted_by"), (json!("submitted"), json!(clinician_id)));
+        assert_eq!(body["secondary_status"], Value::Null);
+        assert_eq!(act(&app, &clinician, &record, "review", None).await.0, StatusCode::FORBIDDEN);
+        let (_, worklist) = get(&app, &clinician, "/records/worklist").await;
+        assert!(!listed_patients(&worklist, "").contains(&i64::from(north.patient_id)));
+
+        assert_eq!(act(&app, admin, &record, "review", None).await.0, StatusCode::OK);
+        // whoever submitted may sign once someone else has reviewed
+        let (status, body) = act(&app, &clinician, &record, "sign", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(status_of(&body, "signed_by"), (json!("signed"), json!(clinician_id)));
+        let note = Some(json!({ "note": "Too late" }));
+        assert_eq!(act(&app, admin, &record, "reject", note).await.0, StatusCode::CONFLICT);
+        assert_eq!(send(&app, Method::PUT, admin, &record, Some(edit)).await.0, StatusCode::CONFLICT);
+        assert_eq!(send(&app, Method::DELETE, admin, &record, None).await.0, StatusCode::CONFLICT);
+
+        let (_, versions) = get(&app, admin, &format!("{}/versions", record)).await;
+        let kinds: Vec<&str> = versions.as_array().unwrap().iter().filter_map(|v| v["kind"].as_str()).collect();
+        assert_eq!(kinds, ["reviewed", "rejected", "edited", "submitted", "reviewed", "signed"]);
+    }
+
+    async fn check_record_versions(pool: PgPool) {
+        migrate(&pool).await;
+        let state = AppState::from_env(pool.clone());
+        let north = seed_tenant(&pool, &state, "north").await;
+        let app = app(state);
+        let admin = north.token.as_str();
+        let body = json!({
+            "patient_id": north.patient_id, "record_type": "note", "title": "Initial",
+            "provider": "Dr Test", "date": "2026-01-10",
+        });
+        let (status, created) = send(&app, Method::POST, admin, "/records", Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let record = format!("/records/{}", created["id"]);
+        let versions = format!("{}/versions", record);
+
+        // an edit that changes nothing leaves no version behind
+        let (status, _) = send(&app, Method::PUT, admin, &record, Some(json!({ "title": "Initial" }))).await;
+        assert_eq!(status, StatusCode::OK);
+        let edit = json!({ "title": "Revised", "description": "Details" });
+        assert_eq!(send(&app, Method::PUT, admin, &record, Some(edit)).await.0, StatusCode::OK);
+        let history = get(&app, admin, &versions).await.1;
+        assert_eq!(history.as_array().unwrap().len(), 2);
+        assert_eq!((&history[0]["kind"], &history[0]["changes"]), (&json!("created"), &json!({})));
+        assert_eq!(history[1]["kind"], "edited");
+        assert_eq!(
+            history[1]["changes"],
+            json!({
+                "title": { "from": "Initial", "to": "Revised" },
+                "description": { "from": null, "to": "Details" },
+            })
+        );
+
+        // signed directly; the sign-off workflow has its own test
+        sqlx::query("UPDATE medical_records SET status = 'signed' WHERE id = $1")
+            .bind(created["id"].as_i64())
+            .execute(&pool)
+            .await
+            .unwrap();
+        let amendments = format!("{}/amendments", record);
+        let amend = |body: Value| send(&app, Method::POST, admin, &amendments, Some(body));
+        let (status, _) = send(&app, Method::PUT, admin, &record, Some(json!({ "title": "Late" }))).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, body) = amend(json!({ "title": "Amended" })).await;
+        assert_eq!((status, invalid_fields(&body)), (StatusCode::UNPROCESSABLE_ENTITY, vec!["reason"]));
+        assert_eq!(amend(json!({ "title": "Revised", "reason": "No-op" })).await.0, StatusCode::BAD_REQUEST);
+        let (status, body) = amend(json!({ "title": "Amended", "reason": "Wrong title" })).await;
+        assert_eq!((status, &body["status"]), (StatusCode::OK, &json!("signed")));
+        let history = get(&app, admin, &versions).await.1;
+        assert_eq!((&history[2]["kind"], &history[2]["reason"]), (&json!("amended"), &json!("Wrong title")));
+        assert_eq!(history[2]["changes"], json!({ "title": { "from": "Revised", "to": "Amended" } }));
+
+        let (_, first) = get(&app, admin, &format!("{}/1", versions)).await;
+        assert_eq!((&first["snapshot"]["title"], &first["snapshot"]["status"]), (&json!("Initial"), &json!("draft")));
+        let (_, diff) = get(&app, admin, &format!("{}/diff?from=3&to=1", versions)).await;
+        let fields: Vec<&String> = diff["changes"].as_object().unwrap().keys().collect();
+        assert_eq!(fields, ["description", "status", "title"]);
+        assert_eq!(diff["changes"]["title"], json!({ "from": "Amended", "to": "Initial" }));
+        assert_eq!(get(&app, admin, &format!("{}/4", versions)).await.0, StatusCode::NOT_FOUND);
+    }
+
+    /// Every record id the listing returns for `query`, following `next_cursor` page by page.
+    async fn page_through(app: &Router, token: &str, query: &str) -> Vec<i64> {
+        let mut ids = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut uri = format!("/records?limit=2&{}", query);
+            if let Some(cursor) = &cursor {
+                let cursor: String = url::form_urlencoded::byte_serialize(cursor.as_bytes()).collect();
+                uri.push_str(&format!("&cursor={}", cursor));
+            }
+            let (status, body) = get(app, token, &uri).await;
+            assert_eq!(status, StatusCode::OK, "{}", uri);
+            let page = body["records"].as_array().unwrap();
+            assert!(page.len() <= 2, "{}", uri);
+            ids.extend(page.iter().map(|record| record["id"].as_i64().unwrap()));
+            match body["next_cursor"].as_str() {
+                Some(next) => cursor = Some(next.to_string()),
+                None => return ids,
+            }
+        }
+    }
+
+    async fn check_record_paging(pool: PgPool) {
+        migrate(&pool).await;
+        let state = AppState::from_env(pool.clone());
+        let north = seed_tenant(&pool, &state, "north").await;
+        let app = app(state);
+        let mut ids = vec![i64::from(north.record_id)];
+        // several records share a date and a title, so pages must break ties by id
+        for (title, date, status) in [
+            ("Visit: follow-up", "2026-01-10", "draft"),
+            ("Visit: follow-up", "2026-01-10", "draft"),
+            ("Antenatal", "2026-01-10", "submitted"),
+            ("Visit: follow-up", "2026-02-01", "draft"),
+            ("Lab", "2025-12-31", "draft"),
+        ] {
+            let id: i32 = sqlx::query_scalar(
+                "INSERT INTO medical_records (patient_id, record_type, title, provider, date, status)
+                 VALUES ($1, 'consultation', $2, 'Dr Test', $3::date, $4) RETURNING id",
+            )
+            .bind(north.patient_id)
+            .bind(title)
+            .bind(date)
+            .bind(status)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            ids.push(i64::from(id));
+        }
+
+        for (query, order) in [
+            ("sort=date&order=asc", "date, id"),
+            ("sort=date", "date DESC, id DESC"),
+            ("sort=title&order=asc", "title, id"),
+            ("sort=created_at&order=desc", "created_at DESC, id DESC"),
+            ("status=draft&from=2026-01-01&order=asc", "date, id"),
+        ] {
+            let filter =
+                if query.starts_with("status") { "status = 'draft' AND date >= '2026-01-01'" } else { "true" };
+            let expected: Vec<i32> =
+                sqlx::query_scalar(&format!("SELECT id FROM medical_records WHERE {} ORDER BY {}", filter, order))
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap();
+            let expected: Vec<i64> = expected.into_iter().map(i64::from).collect();
+            assert_eq!(page_through(&app, &north.token, query).await, expected, "{}", query);
+        }
+        let mut listed = page_through(&app, &north.token, "sort=title").await;
+        listed.sort();
+        assert_eq!(listed, ids);
+
+        for uri in ["/records?cursor=title:1:Visit", "/records?sort=date&cursor=date:1:yesterday"] {
+            assert_eq!(get(&app, &north.token, uri).await.0, StatusCode::BAD_REQUEST, "{}", uri);
+        }
+    }
+
+    fn timeline_entry(entry: &Value) -> (String, i64) {
+        (entry["type"].as_str().unwrap().to_string(), entry["source_id"].as_i64().unwrap())
+    }
+
+    /// Every timeline entry of `patient`, two per page, as (type, source id).
+    async fn timeline_pages(app: &Router, token: &str, patient: i32, query: &str) -> Vec<(String, i64)> {
+        let mut entries = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut uri = format!("/patients/{}/timeline?limit=2&{}", patient, query);
+            if let Some(cursor) = &cursor {
+                let cursor: String = url::form_urlencoded::byte_serialize(cursor.as_bytes()).collect();
+                uri.push_str(&format!("&cursor={}", cursor));
+            }
+            let (status, body) = get(app, token, &uri).await;
+            assert_eq!(status, StatusCode::OK, "{}", uri);
+            let page = body["entries"].as_array().unwrap();
+            assert!(page.len() <= 2, "{}", uri);
+            entries.extend(page.iter().map(timeline_entry));
+            match body["next_cursor"].as_str() {
+                Some(next) => cursor = Some(next.to_string()),
+                None => return entries,
+            }
+        }
+    }
+
+    async fn check_timeline(pool: PgPool) {
+        migrate(&pool).await;
+        let state = AppState::from_env(pool.clone());
+        let north = seed_tenant(&pool, &state, "north").await;
+        let app = app(state);
+        let patient = north.patient_id;
+        // records and consents at the same instant, so pages must break ties by type and id
+        let setup = [
+            "INSERT INTO medical_records (patient_id, record_type, title, provider, date, status)
+             SELECT $1, 'lab', 'CBC', 'Lab', DATE '2026-01-10', 'draft' FROM generate_series(1, 3)",
+            "INSERT INTO medical_records (patient_id, record_type, title, provider, date, status)
+             VALUES ($1, 'lab', 'CBC', 'Lab', '2026-02-01', 'draft')",
+            "INSERT INTO consents (patient_id, consent_type, granted, created_at)
+             VALUES ($1, 'treatment', true, '2026-01-10'), ($1, 'data_sharing', false, '2026-01-10')",
+        ];
+        for sql in setup {
+            sqlx::query(sql).bind(patient).execute(&pool).await.unwrap();
+        }
+
+        let (status, body) = get(&app, &north.token, &format!("/patients/{}/timeline?limit=200", patient)).await;
+        assert_eq!(status, StatusCode::OK);
+        let entries = body["entries"].as_array().unwrap();
+        let keys: Vec<(&str, String)> = entries
+            .iter()
+            .map(|e| (e["occurred_at"].as_str().unwrap(), format!("{:?}", timeline_entry(e))))
+            .collect();
+        assert!(keys.windows(2).all(|pair| pair[0] > pair[1]), "{:?}", keys);
+        let all: Vec<(String, i64)> = entries.iter().map(timeline_entry).collect();
+        assert_eq!(all.iter().filter(|(kind, _)| kind == "record").count(), 5);
+        assert_eq!(all.iter().filter(|(kind, _)| kind == "consent").count(), 2);
+        assert_eq!(timeline_pages(&app, &north.token, patient, "").await, all);
+
+        let consents = timeline_pages(&app, &north.token, patient, "types=consent").await;
+        assert_eq!(consents, all.iter().filter(|(kind, _)| kind == "consent").cloned().collect::<Vec<_>>());
+        let (_, body) = get(&app, &north.token, &format!("/patients/{}/timeline?types=consent", patient)).await;
+        let summaries: Vec<&str> =
+            body["entries"].as_array().unwrap().iter().filter_map(|e| e["summary"].as_str()).collect();
+        assert_eq!(summaries, ["Consent declined: data_sharing", "Consent granted: treatment"]);
+
+        for query in ["types=record,visits", "cursor=2026-01-10", "cursor=yesterday~record:1"] {
+            let uri = format!("/patients/{}/timeline?{}", patient, query);
+            assert_eq!(get(&app, &north.token, &uri).await.0, StatusCode::BAD_REQUEST, "{}", query);
+        }
+    }
+
+    /// The seeded lab reference ranges, picked the way results are flagged.
+    async fn check_lab_ranges(pool: PgPool) {
+        migrate(&pool).await;
+        let codes = ["hemoglobin".to_string(), "wbc".to_string(), "unknown".to_string()];
+        let range = |ranges: &HashMap<String, LabReferenceRange>, code: &str| ranges.get(code).map(|r| (r.low, r.high));
+        let adult_woman = applicable_ranges(&pool, &codes, "female", 30 * 12).await.unwrap();
+        assert_eq!(range(&adult_woman, "hemoglobin"), Some((Some(12.0), Some(15.5))));
+        assert_eq!(range(&adult_woman, "wbc"), Some((Some(4.0), Some(11.0))));
+        assert_eq!(range(&adult_woman, "unknown"), None);
+        let adult_man = applicable_ranges(&pool, &codes, "male", 30 * 12).await.unwrap();
+        assert_eq!(range(&adult_man, "hemoglobin"), Some((Some(13.0), Some(17.0))));
+        // age bands include their lower bound only
+        let child = applicable_ranges(&pool, &codes, "male", 60).await.unwrap();
+        assert_eq!(range(&child, "hemoglobin"), Some((Some(11.5), Some(15.5))));
+        let infant = applicable_ranges(&pool, &codes, "female", 3).await.unwrap();
+        assert_eq!(range(&infant, "hemoglobin"), None);
+        assert_eq!(range(&infant, "wbc"), Some((Some(5.0), Some(15.0))));
+    }
+
+    async fn check_wait_times(pool: PgPool) {
+        migrate(&pool).await;
+        let state = AppState::from_env(pool.clone());
+        let north = seed_tenant(&pool, &state, "north").await;
+        let south = seed_tenant(&pool, &state, "south").await;
+        let facility_id: i32 =
+            sqlx::query_scalar("INSERT INTO facilities (code, name) VALUES ('HQ', 'Headquarters') RETURNING id")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        // (patient, arrival, minutes to triage, minutes to consultation, minutes consulting)
+        for (patient, arrived, triage, wait, consultation) in [
+            (north.patient_id, "2026-03-02 08:00", Some(5), Some(10), Some(15)),
+            (north.patient_id, "2026-03-02 08:30", Some(15), Some(20), Some(5)),
+            (north.patient_id, "2026-03-02 09:00", None, Some(30), None),
+            (north.patient_id, "2026-03-02 09:30", None, Some(40), Some(10)),
+            (north.patient_id, "2026-03-02 10:00", None, Some(100), Some(10)),
+            (north.patient_id, "2026-03-02 11:00", Some(10), None, None),
+            (north.patient_id, "2026-03-03 08:00", None, Some(60), Some(10)),
+            (south.patient_id, "2026-03-02 08:00", None, Some(500), Some(10)),
+        ] {
+            let status = if wait.is_some() { "completed" } else { "left" };
+            sqlx::query(
+                "INSERT INTO queue_entries (patient_id, facility_id, status, arrived_at, triaged_at,
+                     consultation_started_at, consultation_ended_at)
+                 SELECT $1, $2, $3, a, a + make_interval(mins => $4), a + make_interval(mins => $5),
+                        a + make_interval(mins => $5 + $6)
+                 FROM (SELECT $7::timestamp AS a) arrival",
+            )
+            .bind(patient)
+            .bind(facility_id)
+            .bind(status)
+            .bind(triage)
+            .bind(wait)
+            .bind(consultation)
+            .bind(arrived)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let tenant_id: i32 = sqlx::query_scalar("SELECT tenant_id FROM patients WHERE id = $1")
+            .bind(north.patient_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let day = |d| chrono::NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let stats = wait_time_stats(&pool, None, tenant_id, day(1), day(3)).await.unwrap();
+        let days: Vec<_> = stats.iter().map(|s| (s.date, s.arrivals)).collect();
+        assert_eq!(days, [(day(3), 1), (day(2), 6)]);
+        let monday = &stats[1];
+        assert_eq!((monday.seen, monday.left_without_being_seen), (5, 1));
+        // waits of 10, 20, 30, 40 and 100 minutes; the 90th percentile interpolates 40 and 100
+        assert_eq!(
+            (monday.avg_wait_minutes, monday.median_wait_minutes, monday.p90_wait_minutes, monday.max_wait_minutes),
+            (Some(40.0), Some(30.0), Some(76.0), Some(100.0))
+        );
+        assert_eq!((monday.avg_triage_wait_minutes, monday.avg_consultation_minutes), (Some(10.0), Some(10.0)));
+        assert!(wait_time_stats(&pool, Some(&[facility_id + 1]), tenant_id, day(1), day(3)).await.unwrap().is_empty());
+        assert_eq!(wait_time_stats(&pool, None, tenant_id, day(3), day(3)).await.unwrap().len(), 1);
+    }
+
+    async fn check_referrals(pool: PgPool) {
+        migrate(&pool).await;
+        let state = AppState::from_env(pool.clone());
+        let north = seed_tenant(&pool, &state, "north").await;
+        let (clinician_id, clinician) = add_user(&pool, &state, "clinician@north.test", "clinician").await;
+        let app = app(state);
+        let admin = north.token.as_str();
+        let mut facilities = Vec::new();
+        for code in ["HC1", "DH1"] {
+            let id: i32 = sqlx::query_scalar("INSERT INTO facilities (code, name) VALUES ($1, $1) RETURNING id")
+                .bind(code)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            facilities.push(id);
+        }
+        let refer = || {
+            json!({
+                "patient_id": north.patient_id,
+                "from_facility_id": facilities[0],
+                "to_facility_id": facilities[1],
+                "reason": "Suspected appendicitis",
+            })
+        };
+        let step = |id: i64, action: &str| format!("/referrals/{}/{}", id, action);
+
+        let (status, body) = send(&app, Method::POST, &clinician, "/referrals", Some(refer())).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!((body["status"].as_str(), body["urgency"].as_str()), (Some("sent"), Some("routine")));
+        let id = body["id"].as_i64().unwrap();
+        let (status, _) = send(&app, Method::POST, admin, &step(id, "complete"), None).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let outcome = json!({ "outcome": "Appendicectomy" });
+        let (status, _) = send(&app, Method::POST, admin, &step(id, "complete"), Some(outcome.clone())).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, received) = send(&app, Method::POST, admin, &step(id, "receive"), None).await;
+        assert_eq!((status, received["status"].as_str()), (StatusCode::OK, Some("received")));
+        assert_eq!(send(&app, Method::POST, admin, &step(id, "receive"), None).await.0, StatusCode::CONFLICT);
+        let (_, accepted) = send(&app, Method::POST, admin, &step(id, "accept"), None).await;
+        assert_eq!(accepted["status"], "accepted");
+        // receiving is not stamped again when the referral is accepted
+        assert_eq!(accepted["received_at"], received["received_at"]);
+        let (status, completed) = send(&app, Method::POST, admin, &step(id, "complete"), Some(outcome)).await;
+        assert_eq!((status, completed["status"].as_str()), (StatusCode::OK, Some("completed")));
+        assert_eq!(completed["outcome"], "Appendicectomy");
+        let reason = json!({ "reason": "Changed our mind" });
+        let (status, _) = send(&app, Method::POST, &clinician, &step(id, "cancel"), Some(reason.clone())).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        // accepting straight from sent also receives it; cancelling needs a reason
+        let (_, body) = send(&app, Method::POST, &clinician, "/referrals", Some(refer())).await;
+        let second = body["id"].as_i64().unwrap();
+        let (_, accepted) = send(&app, Method::POST, admin, &step(second, "accept"), None).await;
+        assert!(accepted["received_at"].is_string() && accepted["received_by"].is_number());
+        let (status, _) = send(&app, Method::POST, &clinician, &step(second, "cancel"), None).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let (status, cancelled) = send(&app, Method::POST, &clinician, &step(second, "cancel"), Some(reason)).await;
+        assert_eq!((status, cancelled["closed_reason"].as_str()), (StatusCode::OK, Some("Changed our mind")));
+        let (status, _) = send(&app, Method::POST, admin, &step(second, "decline"), None).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let reason = Some(json!({ "reason": "No beds" }));
+        assert_eq!(send(&app, Method::POST, admin, &step(second, "decline"), reason).await.0, StatusCode::CONFLICT);
+
+        // the referrer hears about every step the receiving facility took, but not their own cancellation
+        let feedback: Vec<String> = sqlx::query_scalar(
+            "SELECT message FROM clinical_alerts WHERE recipient_id = $1 AND kind = 'referral_feedback' ORDER BY id",
+        )
+        .bind(clinician_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(feedback.len(), 4, "{:?}", feedback);
+        assert_eq!(feedback[2], format!("Referral #{} to DH1 completed: Appendicectomy", id));
+    }
+
+    async fn check_village_growth(pool: PgPool) {
+        migrate(&pool).await;
+        let state = AppState::from_env(pool.clone());
+        let north = seed_tenant(&pool, &state, "north").await;
+        let south = seed_tenant(&pool, &state, "south").await;
+        let app = app(state);
+        let facility_id: i32 =
+            sqlx::query_scalar("INSERT INTO facilities (code, name) VALUES ('HQ', 'Headquarters') RETURNING id")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let mut villages = Vec::new();
+        for name in ["Rampur", "Sitapur"] {
+            let id: i32 = sqlx::query_scalar(
+                "INSERT INTO geo_areas (level, code, name, parent_id)
+                 SELECT 'village', 'UNASSIGNED-B-' || upper($1), $1, id FROM geo_areas WHERE code = 'UNASSIGNED-B'
+                 RETURNING id",
+            )
+            .bind(name)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            villages.push(id);
+        }
+        // (tenant's seeded patient, village, registered, visit in the record list, visit in the queue)
+        for (tenant_patient, village, registered, record_on, arrived_at) in [
+            (north.patient_id, villages[0], "2026-03-05", Some("2026-03-06"), None),
+            (north.patient_id, villages[0], "2026-03-20", Some("2026-03-20"), None),
+            (north.patient_id, villages[0], "2026-03-21", None, None),
+            (north.patient_id, villages[0], "2026-02-10", None, Some("2026-02-11 09:00")),
+            (north.patient_id, villages[1], "2026-02-10", None, None),
+            (south.patient_id, villages[0], "2026-03-05", Some("2026-03-06"), None),
+        ] {
+            let patient_id: i32 = sqlx::query_scalar(
+                "INSERT INTO patients (first_name, last_name, date_of_birth, gender, phone_number, status,
+                     tenant_id, village_id, created_at)
+                 SELECT 'Village', 'Patient', '1990-01-01', 'male', '+15550101', 'active', tenant_id, $2, $3::timestamp
+                 FROM patients WHERE id = $1 RETURNING id",
+            )
+            .bind(tenant_patient)
+            .bind(village)
+            .bind(registered)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            if let Some(date) = record_on {
+                sqlx::query(
+                    "INSERT INTO medical_records (patient_id, record_type, title, provider, date, status)
+                     VALUES ($1, 'consultation', 'Visit', 'Dr Test', $2::date, 'draft')",
+                )
+                .bind(patient_id)
+                .bind(date)
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+            if let Some(arrived_at) = arrived_at {
+                sqlx::query(
+                    "INSERT INTO queue_entries (patient_id, facility_id, status, arrived_at)
+                     VALUES ($1, $2, 'left', $3::timestamp)",
+                )
+                .bind(patient_id)
+                .bind(facility_id)
+                .bind(arrived_at)
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+        }
+
+        // (name, patients, growth, new registrations now and before, active patients now and before)
+        let growth = |body: &Value| -> Vec<Value> {
+            let villages = body["villages"].as_array().unwrap();
+            let change = |v: &Value, field: &str| json!([v[field]["current"], v[field]["previous"]]);
+            let row = |v: &Value| {
+                let (registered, active) = (change(v, "new_registrations"), change(v, "active_patients"));
+                json!([v["name"], v["patients"], v["growth"], registered, active])
+            };
+            villages.iter().map(row).collect()
+        };
+        let march = "/analytics?from=2026-03-01&to=2026-03-31";
+        let (status, body) = get(&app, &north.token, &format!("{}&granularity=month", march)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["comparison"]["from"], "2026-01-29");
+        assert_eq!(body["comparison"]["to"], "2026-02-28");
+        assert_eq!(
+            growth(&body),
+            [json!(["Rampur", 4, "+200%", [3, 1], [2, 1]]), json!(["Sitapur", 1, "-100%", [0, 1], [0, 0]])]
+        );
+        assert_eq!(body["villages"][0]["new_registrations"]["change_pct"], 200.0);
+        assert_eq!(
+            body["villages"][0]["trend"],
+            json!([{ "period": "2026-03-01", "new_registrations": 3, "active_patients": 2 }])
+        );
+
+        // nothing was registered a year earlier, so growth is a count rather than a percentage
+        let (_, body) = get(&app, &north.token, &format!("{}&compare=previous_year", march)).await;
+        assert_eq!(body["villages"][0]["growth"], "+3");
+        assert_eq!(body["villages"][0]["new_registrations"]["change_pct"], Value::Null);
+    }
+
+    /// Another patient in the tenant of patient `of`.
+    async fn add_relative(pool: &PgPool, of: i32, first_name: &str, date_of_birth: &str) -> i32 {
+        sqlx::query_scalar(
+            "INSERT INTO patients (first_name, last_name, date_of_birth, gender, phone_number, status, tenant_id)
+             SELECT $2, last_name, $3::date, 'female', phone_number, 'active', tenant_id FROM patients WHERE id = $1
+             RETURNING id",
+        )
+        .bind(of)
+        .bind(first_name)
+        .bind(date_of_birth)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn check_profile(pool: PgPool) {
+        migrate(&pool).await;
+        let state = AppState::from_env(pool.clone());
+        let north = seed_tenant(&pool, &state, "north").await;
+        let app = app(state);
+        let patient = north.patient_id;
+        let mother = add_relative(&pool, patient, "Mother", "1955-01-01").await;
+        let grandmother = add_relative(&pool, patient, "Grandmother", "1930-01-01").await;
+        let child = add_relative(&pool, patient, "Child", "2010-01-01").await;
+        let setup = [
+            "UPDATE patients SET active_conditions = ARRAY['Type 2  Diabetes', ' ', 'Asthma'],
+                 known_allergies = ARRAY['Penicillin'], next_visit = CURRENT_DATE - 3 WHERE id = $1",
+            "INSERT INTO condition_mappings (patient_id, source_text, normalized_text, status, match_kind)
+             VALUES ($1, 'Type 2  Diabetes', 'type 2 diabetes', 'mapped', 'exact')",
+            "INSERT INTO consents (patient_id, consent_type, granted, created_at) VALUES
+                 ($1, 'data_sharing', true, '2026-01-01'), ($1, 'data_sharing', false, '2026-02-01'),
+                 ($1, 'treatment', true, '2026-01-01')",
+            "INSERT INTO medical_records (patient_id, record_type, title, provider, date, status)
+             SELECT $1, 'lab', 'CBC', 'Lab', DATE '2026-01-01' + n, 'signed' FROM generate_series(0, 5) n",
+        ];
+        for sql in setup {
+            sqlx::query(sql).bind(patient).execute(&pool).await.unwrap();
+        }
+        let relationships = [(patient, mother, "mother"), (mother, grandmother, "mother"), (patient, child, "child")];
+        for (from, to, relationship) in relationships {
+            sqlx::query(
+                "INSERT INTO patient_relationships (patient_id, related_patient_id, relationship_type)
+                 VALUES ($1, $2, $3)",
+            )
+            .bind(from)
+            .bind(to)
+            .bind(relationship)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let (status, profile) = get(&app, &north.token, &format!("/patients/{}/profile", patient)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(profile["active_conditions"], json!(["Type 2  Diabetes", " ", "Asthma"]));
+        assert_eq!(profile["uncoded_conditions"], json!(["Asthma"]));
+        assert_eq!(profile["allergies"], json!(["Penicillin"]));
+        let consents: Vec<&str> =
+            profile["active_consents"].as_array().unwrap().iter().filter_map(|c| c["consent_type"].as_str()).collect();
+        assert_eq!(consents, ["treatment"]);
+        let labs = profile["recent_records"]["lab"].as_array().unwrap();
+        assert_eq!((labs.len(), labs[0]["date"].as_str()), (5, Some("2026-01-06")));
+        assert_eq!(profile["recent_records"]["consultation"].as_array().unwrap().len(), 1);
+        assert_eq!(profile["latest_vitals"]["news2_score"], 7);
+        let tasks: Vec<&str> =
+            profile["open_tasks"].as_array().unwrap().iter().filter_map(|t| t["kind"].as_str()).collect();
+        assert_eq!(tasks, ["visit_overdue", "record_pending"]);
+        assert_eq!(profile["upcoming_visit"], Value::Null);
+        let family: Vec<(i64, &str)> = profile["family"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| (f["patient_id"].as_i64().unwrap(), f["relationship"].as_str().unwrap()))
+            .collect();
+        let expected = [(grandmother, "grandparent"), (mother, "mother"), (child, "child")];
+        assert_eq!(family, expected.map(|(id, relationship)| (i64::from(id), relationship)));
+    }
+
+    /// The fields a validation error names.
+    fn invalid_fields(body: &Value) -> Vec<&str> {
+        body["details"].as_array().into_iter().flatten().filter_map(|d| d["field"].as_str()).collect()
+    }
+
+    async fn check_households(pool: PgPool) {
+        migrate(&pool).await;
+        let state = AppState::from_env(pool.clone());
+        let north = seed_tenant(&pool, &state, "north").await;
+        let south = seed_tenant(&pool, &state, "south").await;
+        let app = app(state);
+        let patient = north.patient_id;
+        let child = add_relative(&pool, patient, "Child", "2024-01-01").await;
+
+        let body = json!({"name": " Banda ", "head_patient_id": patient});
+        let (status, first) = send(&app, Method::POST, &north.token, "/households", Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!((&first["name"], &first["head_patient_id"]), (&json!("Banda"), &json!(patient)));
+        let first = first["id"].as_i64().unwrap();
+        let body = json!({"name": "Phiri", "village": "Chileka", "head_patient_id": patient});
+        let (_, second) = send(&app, Method::POST, &north.token, "/households", Some(body)).await;
+        let second = second["id"].as_i64().unwrap();
+        // a head who moves out no longer heads their old household
+        let (_, old) = get(&app, &north.token, &format!("/households/{}", first)).await;
+        assert_eq!((old["household"]["head_patient_id"].clone(), old["members"].clone()), (Value::Null, json!([])));
+
+        let members = format!("/households/{}/members", second);
+        let uri = members.as_str();
+        let (status, _) = send(&app, Method::POST, &north.token, uri, Some(json!({"patient_id": child}))).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let stranger = json!({"patient_id": south.patient_id});
+        let (status, body) = send(&app, Method::POST, &north.token, uri, Some(stranger)).await;
+        assert_eq!((status, invalid_fields(&body)), (StatusCode::UNPROCESSABLE_ENTITY, vec!["patient_id"]));
+        let (_, listed) = get(&app, &north.token, uri).await;
+        assert_eq!(listed_patients(&listed, ""), [i64::from(patient), i64::from(child)]);
+        let (_, outreach) = get(&app, &north.token, "/households/outreach?village=chileka").await;
+        assert_eq!((&outreach[0]["member_count"], &outreach[0]["under_five_count"]), (&json!(2), &json!(1)));
+        assert_eq!(outreach.as_array().unwrap().len(), 1);
+
+        let head = format!("{}/{}", members, patient);
+        assert_eq!(send(&app, Method::DELETE, &north.token, &head, None).await.0, StatusCode::NO_CONTENT);
+        assert_eq!(send(&app, Method::DELETE, &north.token, &head, None).await.0, StatusCode::NOT_FOUND);
+        let (_, household) = get(&app, &north.token, &format!("/households/{}", second)).await;
+        assert_eq!(household["household"]["head_patient_id"], Value::Null);
+        assert_eq!(listed_patients(&household, "/members"), [i64::from(child)]);
+
+        let (status, body) = send(&app, Method::POST, &north.token, "/households", Some(json!({"name": " "}))).await;
+        assert_eq!((status, invalid_fields(&body)), (StatusCode::UNPROCESSABLE_ENTITY, vec!["name"]));
+        assert_eq!(get(&app, &south.token, &format!("/households/{}", second)).await.0, StatusCode::NOT_FOUND);
+    }
+
+    async fn check_relationships(pool: PgPool) {
+        migrate(&pool).await;
+        let state = AppState::from_env(pool.clone());
+        let north = seed_tenant(&pool, &state, "north").await;
+        let south = seed_tenant(&pool, &state, "south").await;
+        let app = app(state);
+        let patient = north.patient_id;
+        let child = add_relative(&pool, patient, "Child", "2010-01-01").await;
+        let uri = format!("/patients/{}/relationships", patient);
+        let link = |related: i32, relationship_type: &str| {
+            json!({"related_patient_id": related, "relationship_type": relationship_type})
+        };
+
+        // the patient is female, so the child's side of "daughter" reads mother
+        let (status, created) = send(&app, Method::POST, &north.token, &uri, Some(link(child, "Daughter"))).await;
+        assert_eq!((status, created["relationship_type"].as_str()), (StatusCode::CREATED, Some("child")));
+        let (_, inverse) = get(&app, &north.token, &format!("/patients/{}/relationships", child)).await;
+        let inverse = (inverse[0]["related_patient_id"].as_i64(), inverse[0]["relationship_type"].as_str());
+        assert_eq!(inverse, (Some(i64::from(patient)), Some("mother")));
+        let (status, _) = send(&app, Method::POST, &north.token, &uri, Some(link(child, "child"))).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        for (related, relationship_type, field) in [
+            (patient, "sibling", "related_patient_id"),
+            (south.patient_id, "sibling", "related_patient_id"),
+            (child, "cousin", "relationship_type"),
+        ] {
+            let body = link(related, relationship_type);
+            let (status, body) = send(&app, Method::POST, &north.token, &uri, Some(body)).await;
+            assert_eq!((status, invalid_fields(&body)), (StatusCode::UNPROCESSABLE_ENTITY, vec![field]), "{}", related);
+        }
+        sqlx::query("UPDATE patients SET gender = 'unknown' WHERE id = $1").bind(child).execute(&pool).await.unwrap();
+        let grandchild = add_relative(&pool, patient, "Grandchild", "2030-01-01").await;
+        let child_uri = format!("/patients/{}/relationships", child);
+        let (status, body) = send(&app, Method::POST, &north.token, &child_uri, Some(link(grandchild, "child"))).await;
+        assert_eq!((status, invalid_fields(&body)), (StatusCode::UNPROCESSABLE_ENTITY, vec!["relationship_type"]));
+
+        let id = created["id"].as_i64().unwrap();
+        let (status, _) = send(&app, Method::DELETE, &north.token, &format!("{}/{}", uri, id), None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert_eq!(get(&app, &north.token, &child_uri).await.1, json!([]));
+        assert_eq!(get(&app, &south.token, &uri).await.0, StatusCode::NOT_FOUND);
+    }
+
+    async fn check_terminology(pool: PgPool) {
+        migrate(&pool).await;
+        let state = AppState::from_env(pool.clone());
+        let north = seed_tenant(&pool, &state, "north").await;
+        let dir = std::env::temp_dir().join(format!("terminology-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let table = dir.join("demo.tsv");
+        let rows = "# name: Demo\nE11.9\tType 2 diabetes mellitus\tt2dm|sugar\n\
+                    E11.2\tType 2 diabetes with nephropathy\nE14.9\tDiabetes, unspecified\n\
+                    I10\tEssential hypertension\thtn|high blood pressure\n";
+        std::fs::write(&table, rows).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a code table").unwrap();
+        let files = TerminologyFiles::new(&dir);
+        let load = |summaries: Vec<LoadSummary>| {
+            summaries.into_iter().map(|s| (s.system, s.status, s.code_count, s.deactivated)).collect::<Vec<_>>()
+        };
+        assert_eq!(load(files.load_all(&pool).await.unwrap()), [("demo".to_string(), "loaded", 4, 0)]);
+        assert_eq!(load(files.load_all(&pool).await.unwrap()), [("demo".to_string(), "unchanged", 4, 0)]);
+
+        let search = |query: &'static str| {
+            let pool = pool.clone();
+            async move {
+                let codes = search_codes(&pool, query, Some("demo"), 10).await.unwrap();
+                codes.into_iter().map(|c| c.code).collect::<Vec<_>>()
+            }
+        };
+        // exact code, then code prefix with the dot optional, then display prefix, then any word
+        assert_eq!(search("e119").await, ["E11.9"]);
+        assert_eq!(search("E11").await, ["E11.9", "E11.2"]);
+        assert_eq!(search("diabetes").await, ["E14.9", "E11.9", "E11.2"]);
+        assert_eq!(search("high pressure").await, ["I10"]);
+        assert!(search("100%").await.is_empty());
+
+        let matched = |text: &'static str| {
+            let pool = pool.clone();
+            async move {
+                let (kind, code) = match_condition(&pool, "demo", text).await.unwrap();
+                (kind, code.map(|c| c.code))
+            }
+        };
+        assert_eq!(matched(" High  Blood Pressure ").await, (MatchKind::Exact, Some("I10".to_string())));
+        assert_eq!(matched("sugar problem").await, (MatchKind::Suggested, Some("E11.9".to_string())));
+        assert_eq!(matched("broken arm").await, (MatchKind::None, None));
+        sqlx::query(
+            "INSERT INTO condition_mappings (patient_id, source_text, normalized_text, status, match_kind, system, code,
+                 reviewed_at)
+             VALUES ($1, 'Sugar problem', 'sugar problem', 'mapped', 'reviewed', 'demo', 'E14.9', now())",
+        )
+        .bind(north.patient_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert_eq!(matched("Sugar  problem").await, (MatchKind::Learned, Some("E14.9".to_string())));
+
+        // codes dropped from the file stay for existing diagnoses but no longer match
+        std::fs::write(&table, rows.replace("E14.9\tDiabetes, unspecified\n", "")).unwrap();
+        assert_eq!(load(files.load_all(&pool).await.unwrap()), [("demo".to_string(), "loaded", 3, 1)]);
+        assert_eq!(search("diabetes").await, ["E11.9", "E11.2"]);
+        assert_eq!(matched("sugar problem").await, (MatchKind::Suggested, Some("E11.9".to_string())));
+
+        std::fs::write(&table, "E11.9\tDiabetes\nE11.9\tAgain\n").unwrap();
+        assert!(matches!(files.load_all(&pool).await, Err(TerminologyError::Parse { line: 2, .. })));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    async fn check_facility_scope(pool: PgPool) {
+        migrate(&pool).await;
+        let state = AppState::from_env(pool.clone());
+        let north = seed_tenant(&pool, &state, "north").await;
+        let (_, platform_admin) = add_user(&pool, &state, "platform@north.test", "platform_admin").await;
+        let (clinician, _) = add_user(&pool, &state, "clinician@north.test", "clinician").await;
+        let admin: i32 = sqlx::query_scalar("SELECT id FROM users WHERE email = 'admin@north.test'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let app = app(state);
+        let facilities: Vec<i32> = sqlx::query_scalar(
+            "INSERT INTO facilities (code, name, active)
+             VALUES ('A', 'Alpha', true), ('B', 'Beta', true), ('C', 'Closed', false) RETURNING id",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let [alpha, beta, closed] = facilities[..] else { unreachable!() };
+
+        let uri = format!("/users/{}/assignment", clinician);
+        let assign = |body: Value| send(&app, Method::PUT, &platform_admin, &uri, Some(body));
+        let body = json!({ "facility_ids": [beta, alpha, alpha], "primary_facility_id": beta });
+        assert_eq!(send(&app, Method::PUT, &north.token, &uri, Some(body.clone())).await.0, StatusCode::FORBIDDEN);
+        let (status, assignment) = assign(body).await;
+        assert_eq!(status, StatusCode::OK);
+        let assigned: Vec<(i64, bool)> = assignment["facilities"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| (f["facility_id"].as_i64().unwrap(), f["is_primary"].as_bool().unwrap()))
+            .collect();
+        assert_eq!(assigned, [(i64::from(beta), true), (i64::from(alpha), false)]);
+        for (body, field) in [
+            (json!({ "facility_ids": [alpha], "primary_facility_id": beta }), "primary_facility_id"),
+            (json!({ "facility_ids": [alpha, closed] }), "facility_ids"),
+        ] {
+            let (status, errors) = assign(body).await;
+            assert_eq!((status, invalid_fields(&errors)), (StatusCode::UNPROCESSABLE_ENTITY, vec![field]));
+        }
+
+        let scope = |facility_id: Option<i32>, mine: Option<bool>| {
+            let pool = pool.clone();
+            async move {
+                let ids = facility_scope(&pool, clinician, facility_id, mine).await.unwrap();
+                ids.map(|mut ids| {
+                    ids.sort();
+                    ids
+                })
+            }
+        };
+        assert_eq!(scope(None, None).await, None);
+        assert_eq!(scope(None, Some(false)).await, None);
+        assert_eq!(scope(Some(closed), None).await, Some(vec![closed]));
+        assert_eq!(scope(None, Some(true)).await, Some(vec![alpha, beta]));
+        assert_eq!(scope(Some(alpha), Some(true)).await, Some(vec![alpha]));
+        assert_eq!(scope(Some(closed), Some(true)).await, Some(vec![]));
+
+        let invalid = |result: Result<Option<Facility>, ServiceError>| match result {
+            Err(ServiceError::Validation(errors)) => errors.into_iter().map(|e| e.message).collect::<Vec<_>>(),
+            other => panic!("expected a validation error, got {:?}", other.map(|f| f.map(|f| f.id))),
+        };
+        let facility = require_facility(&pool, "facility_id", None, clinician).await.unwrap();
+        assert_eq!(facility.id, beta);
+        assert_eq!(require_facility(&pool, "facility_id", Some(alpha), admin).await.unwrap().id, alpha);
+        let required = require_facility(&pool, "facility_id", None, admin).await.map(Some);
+        assert_eq!(invalid(required), ["is required when you have no primary facility"]);
+        let inactive = optional_facility(&pool, "facility_id", Some(closed), clinician).await;
+        assert_eq!(invalid(inactive), ["is not an active facility"]);
+        assert!(optional_facility(&pool, "facility_id", None, admin).await.unwrap().is_none());
+
+        // the primary defaults to the first facility; dropped assignments go
+        let (_, assignment) = assign(json!({ "facility_ids": [alpha] })).await;
+        let assigned = assignment["facilities"].as_array().unwrap();
+        assert_eq!(assigned.len(), 1);
+        assert_eq!((&assigned[0]["facility_id"], &assigned[0]["is_primary"]), (&json!(alpha), &json!(true)));
+        assert_eq!(scope(None, Some(true)).await, Some(vec![alpha]));
+    }
+
+    async fn check_village_resolution(pool: PgPool) {
+        migrate(&pool).await;
+        let block: i32 = sqlx::query_scalar("SELECT id FROM geo_areas WHERE code = 'UNASSIGNED-B'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        // villages may share a name in different blocks
+        let areas: Vec<i32> = sqlx::query_scalar(
+            "WITH other AS (
+                 INSERT INTO geo_areas (level, code, name, parent_id)
+                 SELECT 'block', 'OTHER-B', 'Other', parent_id FROM geo_areas WHERE id = $1
+                 RETURNING id
+             )
+             INSERT INTO geo_areas (level, code, name, parent_id)
+             VALUES ('village', 'V-RAMPUR', 'Rampur', $1), ('village', 'V-SITA-1', 'Sitapur', $1),
+                    ('village', 'V-SITA-2', 'sitapur', (SELECT id FROM other))
+             RETURNING id",
+        )
+        .bind(block)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let resolve = |village_id: Option<i32>, village: Option<&'static str>| {
+            let pool = pool.clone();
+            async move { resolve_village(&pool, village_id, village).await }
+        };
+        let field = |result: Result<Option<GeoArea>, ServiceError>| match result {
+            Err(ServiceError::Validation(errors)) => errors.into_iter().map(|e| e.field).collect::<Vec<_>>(),
+            other => panic!("expected a validation error, got {:?}", other.map(|a| a.map(|a| a.code))),
+        };
+        let code = |result: Result<Option<GeoArea>, ServiceError>| result.unwrap().map(|a| a.code);
+
+        assert_eq!(code(resolve(None, None).await), None);
+        assert_eq!(code(resolve(Some(areas[0]), Some("ignored")).await).as_deref(), Some("V-RAMPUR"));
+        assert_eq!(code(resolve(None, Some("  RAMPUR ")).await).as_deref(), Some("V-RAMPUR"));
+        assert_eq!(field(resolve(None, Some("Sitapur")).await), ["village"]);
+        assert_eq!(code(resolve(Some(areas[2]), None).await).as_deref(), Some("V-SITA-2"));
+        assert_eq!(field(resolve(Some(block), None).await), ["village_id"]);
+        assert_eq!(field(resolve(None, Some(" -- ")).await), ["village"]);
+
+        // unknown names land under the Unassigned block, once per spelling up to case and punctuation
+        let added = resolve(None, Some("New  Colony")).await.unwrap().unwrap();
+        let added_as = (added.code.as_str(), added.name.as_str(), added.parent_id);
+        assert_eq!(added_as, ("UNASSIGNED-B-NEW-COLONY", "New Colony", Some(block)));
+        let again = resolve(None, Some("new-colony")).await.unwrap().unwrap();
+        assert_eq!(again.id, added.id);
+    }
+
+    /// Runs `check` against a scratch database on the server `DATABASE_URL` points at, and
+    /// skips it when that is not set.
+    async fn on_scratch_database<F, Fut>(label: &str, check: F)
+    where
+        F: FnOnce(PgPool) -> Fut,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            eprintln!("DATABASE_URL is not set; skipping the {} test", label.replace('_', " "));
+            return;
+        };
+        let server = PgPool::connect(&url).await.unwrap();
+        let name = format!("{}_{}", label, uuid::Uuid::new_v4().simple());
+        server.execute(format!("CREATE DATABASE {}", name).as_str()).await.unwrap();
+        let mut scratch = url::Url::parse(&url).unwrap();
+        scratch.set_path(&name);
+        let pool = PgPool::connect(scratch.as_str()).await.unwrap();
+
+        let outcome = tokio::spawn(check(pool.clone())).await;
+        pool.close().await;
+        server.execute(format!("DROP DATABASE {} WITH (FORCE)", name).as_str()).await.unwrap();
+        if let Err(e) = outcome {
+            std::panic::resume_unwind(e.into_panic());
+        }
+    }
+
+    #[tokio::test]
+    async fn tenants_only_reach_their_own_patients() {
+        on_scratch_database("tenant_isolation", check_isolation).await;
+    }
+
+    #[tokio::test]
+    async fn records_move_through_sign_off_in_order() {
+        on_scratch_database("sign_off", check_sign_off).await;
+    }
+
+    #[tokio::test]
+    async fn record_pages_follow_their_sort_and_filters() {
+        on_scratch_database("record_paging", check_record_paging).await;
+    }
+
+    #[tokio::test]
+    async fn lab_results_use_the_range_for_the_patient() {
+        on_scratch_database("lab_ranges", check_lab_ranges).await;
+    }
+
+    #[tokio::test]
+    async fn wait_times_summarise_each_facility_day() {
+        on_scratch_database("wait_times", check_wait_times).await;
+    }
+
+    #[tokio::test]
+    async fn referrals_follow_their_workflow() {
+        on_scratch_database("referrals", check_referrals).await;
+    }
+
+    #[tokio::test]
+    async fn village_growth_compares_with_the_previous_period() {
+        on_scratch_database("village_growth", check_village_growth).await;
+    }
+
+    #[tokio::test]
+    async fn profiles_summarise_the_patient() {
+        on_scratch_database("profile", check_profile).await;
+    }
+
+    #[tokio::test]
+    async fn timelines_page_through_ties() {
+        on_scratch_database("timeline", check_timeline).await;
+    }
+
+    #[tokio::test]
+    async fn households_keep_one_head_among_their_members() {
+        on_scratch_database("households", check_households).await;
+    }
+
+    #[tokio::test]
+    async fn relationships_are_stored_from_both_sides() {
+        on_scratch_database("relationships", check_relationships).await;
+    }
+
+    #[tokio::test]
+    async fn records_keep_a_version_per_change() {
+        on_scratch_database("versions", check_record_versions).await;
+    }
+
+    #[tokio::test]
+    async fn code_tables_load_and_match_conditions() {
+        on_scratch_database("terminology", check_terminology).await;
+    }
+
+    #[tokio::test]
+    async fn facility_filters_follow_assignments() {
+        on_scratch_database("facilities", check_facility_scope).await;
+    }
+
+    #[tokio::test]
+    async fn villages_resolve_by_id_or_name() {
+        on_scratch_database("villages", check_village_resolution).await;
+    }
+}
@@ -1,11 +1,32 @@
-use axum::{routing::get, Router};
+use axum::{extract::DefaultBodyLimit, routing::{delete, get}, Router};
 use crate::handlers::patients_handler::{
     list_patients, get_patient, create_patient, update_patient, delete_patient,
+    get_patient_profile,
 };
-use sqlx::PgPool;
+use crate::handlers::timeline_handler::get_patient_timeline;
+use crate::handlers::records_handler::list_patient_records;
+use crate::handlers::photos_handler::{
+    upload_patient_photo, get_patient_photo, get_patient_photo_url, delete_patient_photo,
+};
+use crate::handlers::households_handler::{list_relationships, create_relationship, delete_relationship};
+use crate::config::state::AppState;
 
-pub fn routes() -> Router<PgPool> {
+pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/patients", get(list_patients).post(create_patient))
         .route("/patients/:id", get(get_patient).put(update_patient).delete(delete_patient))
+        .route("/patients/:id/profile", get(get_patient_profile))
+        .route("/patients/:id/timeline", get(get_patient_timeline))
+        .route("/patients/:id/records", get(list_patient_records))
+        .route("/patients/:id/relationships", get(list_relationships).post(create_relationship))
+        .route("/patients/:id/relationships/:relationship_id", delete(delete_relationship))
+        // the upload handler enforces its own configurable size limit
+        .route(
+            "/patients/:id/photo",
+            get(get_patient_photo)
+                .put(upload_patient_photo)
+                .delete(delete_patient_photo)
+                .layer(DefaultBodyLimit::disable()),
+        )
+        .route("/patients/:id/photo/url", get(get_patient_photo_url))
 }
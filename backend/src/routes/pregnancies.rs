@@ -0,0 +1,17 @@
+use axum::{routing::{delete, get, post}, Router};
+use crate::handlers::pregnancies_handler::{
+    get_anc_schedule, list_anc_due, list_pregnancies, create_pregnancy, get_pregnancy, update_pregnancy,
+    record_anc_contact, delete_anc_contact, record_pregnancy_outcome,
+};
+use crate::config::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/anc/schedule", get(get_anc_schedule))
+        .route("/anc/due", get(list_anc_due))
+        .route("/patients/:id/pregnancies", get(list_pregnancies).post(create_pregnancy))
+        .route("/pregnancies/:id", get(get_pregnancy).put(update_pregnancy))
+        .route("/pregnancies/:id/contacts", post(record_anc_contact))
+        .route("/pregnancies/:id/outcome", post(record_pregnancy_outcome))
+        .route("/anc-contacts/:id", delete(delete_anc_contact))
+}
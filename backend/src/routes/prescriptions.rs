@@ -0,0 +1,15 @@
+use axum::{routing::{get, post}, Router};
+use crate::handlers::prescriptions_handler::{
+    create_prescription, check_prescription, list_prescriptions, list_current_medications, stop_prescription,
+    search_drugs,
+};
+use crate::config::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/patients/:id/prescriptions", get(list_prescriptions).post(create_prescription))
+        .route("/patients/:id/prescriptions/check", post(check_prescription))
+        .route("/patients/:id/medications", get(list_current_medications))
+        .route("/prescriptions/:id/stop", post(stop_prescription))
+        .route("/drugs", get(search_drugs))
+}
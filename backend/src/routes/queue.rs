@@ -0,0 +1,16 @@
+use axum::{routing::{get, post}, Router};
+use crate::handlers::queue_handler::{
+    get_queue, register_arrival, triage_patient, start_consultation, finish_consultation, leave_queue,
+    get_wait_times,
+};
+use crate::config::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/queue", get(get_queue).post(register_arrival))
+        .route("/queue/wait-times", get(get_wait_times))
+        .route("/queue/:id/triage", post(triage_patient))
+        .route("/queue/:id/start", post(start_consultation))
+        .route("/queue/:id/finish", post(finish_consultation))
+        .route("/queue/:id/leave", post(leave_queue))
+}
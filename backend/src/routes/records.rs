@@ -1,11 +1,40 @@
-use axum::{routing::get, Router};
+use axum::{extract::DefaultBodyLimit, routing::{get, post}, Router};
 use crate::handlers::records_handler::{
-    list_records, get_record, create_record, update_record, delete_record,
+    list_records, get_record, create_record, update_record, delete_record, amend_record,
+    list_record_versions, get_record_version, diff_record_versions,
 };
-use sqlx::PgPool;
+use crate::handlers::attachments_handler::{
+    list_record_attachments, get_record_attachment, download_record_attachment, delete_record_attachment,
+    create_attachment_upload, get_attachment_upload, upload_attachment_chunk, cancel_attachment_upload,
+};
+use crate::handlers::record_workflow_handler::{
+    submit_record, review_record, sign_record, reject_record, get_review_worklist,
+};
+use crate::config::state::AppState;
 
-pub fn routes() -> Router<PgPool> {
+pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/records", get(list_records).post(create_record))
+        .route("/records/worklist", get(get_review_worklist))
         .route("/records/:id", get(get_record).put(update_record).delete(delete_record))
+        .route("/records/:id/submit", post(submit_record))
+        .route("/records/:id/review", post(review_record))
+        .route("/records/:id/sign", post(sign_record))
+        .route("/records/:id/reject", post(reject_record))
+        .route("/records/:id/amendments", post(amend_record))
+        .route("/records/:id/versions", get(list_record_versions))
+        .route("/records/:id/versions/diff", get(diff_record_versions))
+        .route("/records/:id/versions/:version", get(get_record_version))
+        .route("/records/:id/attachments", get(list_record_attachments))
+        .route("/records/:id/attachments/uploads", post(create_attachment_upload))
+        // the chunk handler enforces its own configurable size limit
+        .route(
+            "/records/:id/attachments/uploads/:upload_id",
+            get(get_attachment_upload)
+                .put(upload_attachment_chunk)
+                .delete(cancel_attachment_upload)
+                .layer(DefaultBodyLimit::disable()),
+        )
+        .route("/records/:id/attachments/:attachment_id", get(get_record_attachment).delete(delete_record_attachment))
+        .route("/records/:id/attachments/:attachment_id/content", get(download_record_attachment))
 }
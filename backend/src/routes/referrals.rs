@@ -0,0 +1,23 @@
+use axum::{routing::{get, post}, Router};
+use crate::handlers::referrals_handler::{
+    create_referral, get_referral, list_patient_referrals, list_referral_inbox, list_referral_outbox,
+    receive_referral, accept_referral, decline_referral, complete_referral, cancel_referral, add_referral_note,
+    get_referral_analytics,
+};
+use crate::config::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/referrals", post(create_referral))
+        .route("/referrals/inbox", get(list_referral_inbox))
+        .route("/referrals/outbox", get(list_referral_outbox))
+        .route("/referrals/analytics", get(get_referral_analytics))
+        .route("/referrals/:id", get(get_referral))
+        .route("/referrals/:id/receive", post(receive_referral))
+        .route("/referrals/:id/accept", post(accept_referral))
+        .route("/referrals/:id/decline", post(decline_referral))
+        .route("/referrals/:id/complete", post(complete_referral))
+        .route("/referrals/:id/cancel", post(cancel_referral))
+        .route("/referrals/:id/notes", post(add_referral_note))
+        .route("/patients/:id/referrals", get(list_patient_referrals))
+}
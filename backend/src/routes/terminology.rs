@@ -0,0 +1,21 @@
+use axum::{routing::{get, post, put}, Router};
+use crate::handlers::terminology_handler::{list_systems, search_terminology, get_code, reload_terminology};
+use crate::handlers::diagnoses_handler::{
+    list_diagnoses, create_diagnosis, update_diagnosis, delete_diagnosis, run_condition_mapping,
+    list_condition_mappings, accept_condition_mapping, reject_condition_mapping,
+};
+use crate::config::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/terminology/systems", get(list_systems))
+        .route("/terminology/search", get(search_terminology))
+        .route("/terminology/reload", post(reload_terminology))
+        .route("/terminology/systems/:system/codes/:code", get(get_code))
+        .route("/terminology/condition-mappings", get(list_condition_mappings))
+        .route("/terminology/condition-mappings/run", post(run_condition_mapping))
+        .route("/terminology/condition-mappings/:id/accept", post(accept_condition_mapping))
+        .route("/terminology/condition-mappings/:id/reject", post(reject_condition_mapping))
+        .route("/patients/:id/diagnoses", get(list_diagnoses).post(create_diagnosis))
+        .route("/diagnoses/:id", put(update_diagnosis).delete(delete_diagnosis))
+}
@@ -0,0 +1,22 @@
+use axum::{routing::{delete, get, post}, Router};
+use crate::handlers::vitals_handler::{
+    create_vital_set, list_vital_sets, get_latest_vitals, get_vital_series, list_vital_reference_ranges,
+    list_deteriorating_patients,
+};
+use crate::handlers::alerts_handler::{
+    list_care_team, assign_care_team_member, remove_care_team_member, list_alerts, acknowledge_alert,
+};
+use crate::config::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/patients/:id/vitals", get(list_vital_sets).post(create_vital_set))
+        .route("/patients/:id/vitals/latest", get(get_latest_vitals))
+        .route("/patients/:id/vitals/series", get(get_vital_series))
+        .route("/vitals/reference-ranges", get(list_vital_reference_ranges))
+        .route("/vitals/deteriorating", get(list_deteriorating_patients))
+        .route("/patients/:id/care-team", get(list_care_team).post(assign_care_team_member))
+        .route("/patients/:id/care-team/:user_id", delete(remove_care_team_member))
+        .route("/alerts", get(list_alerts))
+        .route("/alerts/:id/acknowledge", post(acknowledge_alert))
+}
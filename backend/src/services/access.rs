@@ -0,0 +1,65 @@
+use crate::models::error::ServiceError;
+use crate::models::record::RecordType;
+use crate::models::user::StaffRole;
+use crate::services::auth::AuthUser;
+use crate::services::validation::TextEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordAction {
+    Read,
+    Write,
+    /// Review, reject or sign a record someone submitted.
+    SignOff,
+}
+
+/// Whether `user` may perform `action` on a record of `record_type`. Everything hanging off a
+/// record (attachments included) goes through this check with the owning record's type.
+///
+/// All staff can read records. Clinicians and admins can write any record; technicians only
+/// the results they produce (lab and imaging), and only clinicians and admins sign off.
+pub fn check_record_access(user: &AuthUser, record_type: &str, action: RecordAction) -> Result<(), ServiceError> {
+    let allowed = match (user.role, action) {
+        (_, RecordAction::Read) => true,
+        (StaffRole::Admin | StaffRole::PlatformAdmin | StaffRole::Clinician, RecordAction::Write) => true,
+        (StaffRole::Technician, RecordAction::Write) => {
+            matches!(RecordType::parse(record_type), Some(RecordType::Lab | RecordType::Imaging))
+        }
+        (_, RecordAction::SignOff) => return check_sign_off_role(user),
+    };
+    if allowed {
+        Ok(())
+    } else {
+        Err(ServiceError::Forbidden)
+    }
+}
+
+/// Reviewing and signing records is reserved for clinicians and admins.
+pub fn check_sign_off_role(user: &AuthUser) -> Result<(), ServiceError> {
+    match user.role {
+        StaffRole::Admin | StaffRole::PlatformAdmin | StaffRole::Clinician => Ok(()),
+        StaffRole::Technician => Err(ServiceError::Forbidden),
+    }
+}
+
+/// Managing care teams and acting on clinical alerts is for clinicians and admins.
+pub fn check_clinical_role(user: &AuthUser) -> Result<(), ServiceError> {
+    check_sign_off_role(user)
+}
+
+/// Managing a tenant's staff and running bulk migrations over its data is for admins.
+pub fn check_admin_role(user: &AuthUser) -> Result<(), ServiceError> {
+    if user.role.is_admin() {
+        Ok(())
+    } else {
+        Err(ServiceError::Forbidden)
+    }
+}
+
+/// Creating and suspending tenants, and changing the reference data every tenant shares, is for
+/// platform admins.
+pub fn check_platform_admin_role(user: &AuthUser) -> Result<(), ServiceError> {
+    match user.role {
+        StaffRole::PlatformAdmin => Ok(()),
+        _ => Err(ServiceError::Forbidden),
+    }
+}
@@ -0,0 +1,66 @@
+use sqlx::{Postgres, Transaction};
+
+use crate::models::alert::ClinicalAlert;
+
+pub struct AlertEntry<'a> {
+    pub patient_id: i32,
+    pub vital_set_id: Option<i32>,
+    pub kind: &'a str,
+    pub severity: &'a str,
+    pub message: &'a str,
+}
+
+/// Address an alert to every member of the patient's care team, or leave it unaddressed
+/// for any clinician to pick up when the patient has no care team.
+pub async fn notify_care_team(
+    tx: &mut Transaction<'_, Postgres>,
+    entry: AlertEntry<'_>,
+) -> Result<Vec<ClinicalAlert>, sqlx::Error> {
+    let mut alerts = sqlx::query_as::<_, ClinicalAlert>(
+        "INSERT INTO clinical_alerts (patient_id, vital_set_id, recipient_id, kind, severity, message)
+         SELECT $1, $2, user_id, $3, $4, $5 FROM patient_care_team WHERE patient_id = $1
+         RETURNING *",
+    )
+    .bind(entry.patient_id)
+    .bind(entry.vital_set_id)
+    .bind(entry.kind)
+    .bind(entry.severity)
+    .bind(entry.message)
+    .fetch_all(&mut **tx)
+    .await?;
+    if alerts.is_empty() {
+        let unassigned = sqlx::query_as::<_, ClinicalAlert>(
+            "INSERT INTO clinical_alerts (patient_id, vital_set_id, kind, severity, message)
+             VALUES ($1, $2, $3, $4, $5) RETURNING *",
+        )
+        .bind(entry.patient_id)
+        .bind(entry.vital_set_id)
+        .bind(entry.kind)
+        .bind(entry.severity)
+        .bind(entry.message)
+        .fetch_one(&mut **tx)
+        .await?;
+        alerts.push(unassigned);
+    }
+    Ok(alerts)
+}
+
+/// Address an alert to one member of staff, such as the clinician who made a referral.
+pub async fn notify_user(
+    tx: &mut Transaction<'_, Postgres>,
+    recipient_id: i32,
+    entry: AlertEntry<'_>,
+) -> Result<ClinicalAlert, sqlx::Error> {
+    sqlx::query_as::<_, ClinicalAlert>(
+        "INSERT INTO clinical_alerts (patient_id, vital_set_id, recipient_id, kind, severity, message)
+         VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+    )
+    .bind(entry.patient_id)
+    .bind(entry.vital_set_id)
+    .bind(recipient_id)
+    .bind(entry.kind)
+    .bind(entry.severity)
+    .bind(entry.message)
+    .fetch_one(&mut **tx)
+    .await
+}
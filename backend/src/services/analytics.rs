@@ -0,0 +1,321 @@
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use serde::Serialize;
+
+use crate::models::error::ServiceError;
+use crate::services::validation::{field_error, TextEnum};
+
+/// A report covers at most this many buckets.
+pub const MAX_BUCKETS: i64 = 366;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    Day,
+    Week,
+    Month,
+}
+
+impl TextEnum for Granularity {
+    const ALL: &'static [Self] = &[Granularity::Day, Granularity::Week, Granularity::Month];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Granularity::Day => "day",
+            Granularity::Week => "week",
+            Granularity::Month => "month",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            Granularity::Day => &["daily"],
+            Granularity::Week => &["weekly"],
+            Granularity::Month => &["monthly"],
+        }
+    }
+}
+
+impl Granularity {
+    /// First day of the bucket `date` falls in, as Postgres `date_trunc` has it: weeks start
+    /// on Monday.
+    pub fn bucket_start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Granularity::Day => date,
+            Granularity::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Granularity::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// How a bucket starting on `start` is shown, e.g. `2026-10-19`, `2026-W43` or `Oct 2026`.
+    pub fn label(self, start: NaiveDate) -> String {
+        match self {
+            Granularity::Day => start.format("%Y-%m-%d").to_string(),
+            Granularity::Week => start.format("%G-W%V").to_string(),
+            Granularity::Month => start.format("%b %Y").to_string(),
+        }
+    }
+}
+
+/// What a report's range is compared with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    /// As many days again, ending the day before the range starts.
+    PreviousPeriod,
+    /// The same dates a year earlier.
+    PreviousYear,
+}
+
+impl TextEnum for Comparison {
+    const ALL: &'static [Self] = &[Comparison::PreviousPeriod, Comparison::PreviousYear];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Comparison::PreviousPeriod => "previous_period",
+            Comparison::PreviousYear => "previous_year",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            Comparison::PreviousPeriod => &["previous", "period"],
+            Comparison::PreviousYear => &["year", "yoy", "year_over_year"],
+        }
+    }
+}
+
+/// The dates a report covers, inclusive, and the buckets it is split into.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ReportRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub granularity: Granularity,
+}
+
+impl ReportRange {
+    /// `granularity` defaults to month. Without `from` the range spans the last 30 days,
+    /// 12 weeks or 7 months up to `to`, which defaults to `today`.
+    pub fn resolve(
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        granularity: Option<&str>,
+        today: NaiveDate,
+    ) -> Result<Self, ServiceError> {
+        let granularity = match granularity.map(str::trim).filter(|g| !g.is_empty()) {
+            Some(g) => Granularity::parse(g)
+                .ok_or_else(|| field_error("granularity", format!("must be one of: {}", Granularity::allowed())))?,
+            None => Granularity::Month,
+        };
+        let to = to.unwrap_or(today);
+        let from = match from {
+            Some(from) => from,
+            None => {
+                let start = granularity.bucket_start(to);
+                match granularity {
+                    Granularity::Day => start - Duration::days(29),
+                    Granularity::Week => start - Duration::weeks(11),
+                    Granularity::Month => start.checked_sub_months(Months::new(6)).unwrap_or(start),
+                }
+            }
+        };
+        if from > to {
+            return Err(field_error("from", "cannot be after to"));
+        }
+        let range = ReportRange { from, to, granularity };
+        if range.bucket_count() > MAX_BUCKETS {
+            return Err(field_error(
+                "from",
+                format!("the range cannot span more than {} {}s", MAX_BUCKETS, granularity.as_str()),
+            ));
+        }
+        Ok(range)
+    }
+
+    pub fn bucket_count(&self) -> i64 {
+        let first = self.granularity.bucket_start(self.from);
+        let last = self.granularity.bucket_start(self.to);
+        match self.granularity {
+            Granularity::Day => (last - first).num_days() + 1,
+            Granularity::Week => (last - first).num_weeks() + 1,
+            Granularity::Month => {
+                (last.year() as i64 - first.year() as i64) * 12 + last.month() as i64 - first.month() as i64 + 1
+            }
+        }
+    }
+
+    /// `column`, a date or timestamp, truncated to the start of its bucket.
+    pub fn bucket_of(&self, column: &str) -> String {
+        format!("date_trunc('{}', {}::timestamp)::date", self.granularity.as_str(), column)
+    }
+
+    /// A `buckets (bucket)` CTE body with the start of every bucket from the one holding
+    /// `from` to the one holding `to`, whichever parameters those are bound to, in order.
+    pub fn buckets(&self, from: &str, to: &str) -> String {
+        let unit = self.granularity.as_str();
+        format!(
+            "SELECT generate_series(date_trunc('{unit}', {from}::date::timestamp), {to}::date::timestamp, interval '1 {unit}')::date AS bucket"
+        )
+    }
+}
+
+/// Dates a report's range is compared with.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ComparisonRange {
+    pub compare: Comparison,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl ComparisonRange {
+    /// `compare` defaults to the previous period.
+    pub fn resolve(range: &ReportRange, compare: Option<&str>) -> Result<Self, ServiceError> {
+        let compare = match compare.map(str::trim).filter(|c| !c.is_empty()) {
+            Some(c) => Comparison::parse(c)
+                .ok_or_else(|| field_error("compare", format!("must be one of: {}", Comparison::allowed())))?,
+            None => Comparison::PreviousPeriod,
+        };
+        let (from, to) = match compare {
+            Comparison::PreviousPeriod => {
+                let days = (range.to - range.from).num_days() + 1;
+                (range.from - Duration::days(days), range.from - Duration::days(1))
+            }
+            Comparison::PreviousYear => {
+                let year = Months::new(12);
+                (
+                    range.from.checked_sub_months(year).unwrap_or(range.from),
+                    range.to.checked_sub_months(year).unwrap_or(range.to),
+                )
+            }
+        };
+        Ok(ComparisonRange { compare, from, to })
+    }
+}
+
+/// A count over the report's range next to the same count over the comparison range.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PeriodChange {
+    pub current: i64,
+    pub previous: i64,
+    pub change: i64,
+    /// `None` when there is nothing to compare with.
+    pub change_pct: Option<f64>,
+}
+
+impl PeriodChange {
+    pub fn new(current: i64, previous: i64) -> Self {
+        let change_pct = (previous > 0).then(|| ((current - previous) as f64 / previous as f64 * 1000.0).round() / 10.0);
+        PeriodChange { current, previous, change: current - previous, change_pct }
+    }
+
+    /// e.g. `+25%`, `-10%`, or `+3` when the comparison period had none.
+    pub fn summary(&self) -> String {
+        match self.change_pct {
+            Some(pct) => format!("{:+.0}%", pct),
+            None if self.change == 0 => "+0%".to_string(),
+            None => format!("{:+}", self.change),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// The field a resolve was rejected on.
+    fn rejected(result: Result<ReportRange, ServiceError>) -> String {
+        match result {
+            Err(ServiceError::Validation(errors)) => errors[0].field.clone(),
+            other => panic!("expected a validation error, got {:?}", other.map(|r| (r.from, r.to))),
+        }
+    }
+
+    /// A Wednesday, so the default weekly range has to go back to a Monday.
+    fn today() -> NaiveDate {
+        date(2026, 10, 21)
+    }
+
+    #[test]
+    fn default_ranges_end_today_and_start_on_a_bucket_boundary() {
+        let day = ReportRange::resolve(None, None, Some("day"), today()).unwrap();
+        assert_eq!((day.from, day.to), (date(2026, 9, 22), today()));
+        assert_eq!(day.bucket_count(), 30);
+
+        let week = ReportRange::resolve(None, None, Some("week"), today()).unwrap();
+        assert_eq!((week.from, week.to), (date(2026, 8, 3), today()));
+        assert_eq!(week.from.weekday(), chrono::Weekday::Mon);
+        assert_eq!(week.bucket_count(), 12);
+
+        let month = ReportRange::resolve(None, None, None, today()).unwrap();
+        assert_eq!(month.granularity, Granularity::Month);
+        assert_eq!((month.from, month.to), (date(2026, 4, 1), today()));
+        assert_eq!(month.bucket_count(), 7);
+    }
+
+    #[test]
+    fn default_start_counts_back_from_to() {
+        let range = ReportRange::resolve(None, Some(date(2026, 2, 14)), Some("monthly"), today()).unwrap();
+        assert_eq!((range.from, range.to), (date(2025, 8, 1), date(2026, 2, 14)));
+        assert_eq!(range.bucket_count(), 7);
+    }
+
+    #[test]
+    fn explicit_ranges_are_kept_and_counted_in_partial_buckets() {
+        // Sunday to Monday touches two weeks
+        let range = ReportRange::resolve(Some(date(2026, 10, 18)), Some(date(2026, 10, 19)), Some("week"), today());
+        assert_eq!(range.unwrap().bucket_count(), 2);
+
+        let range = ReportRange::resolve(Some(date(2025, 12, 31)), Some(date(2026, 1, 1)), Some("month"), today());
+        let range = range.unwrap();
+        assert_eq!((range.from, range.to), (date(2025, 12, 31), date(2026, 1, 1)));
+        assert_eq!(range.bucket_count(), 2);
+
+        let single = ReportRange::resolve(Some(today()), Some(today()), Some("daily"), today()).unwrap();
+        assert_eq!(single.bucket_count(), 1);
+    }
+
+    #[test]
+    fn invalid_ranges_are_rejected() {
+        assert_eq!(rejected(ReportRange::resolve(None, None, Some("hourly"), today())), "granularity");
+        assert_eq!(rejected(ReportRange::resolve(Some(date(2026, 10, 22)), None, Some("day"), today())), "from");
+        // 366 days is the most a daily report covers
+        assert!(ReportRange::resolve(Some(date(2025, 1, 1)), Some(date(2026, 1, 1)), Some("day"), today()).is_ok());
+        assert_eq!(
+            rejected(ReportRange::resolve(Some(date(2025, 1, 1)), Some(date(2026, 1, 2)), Some("day"), today())),
+            "from"
+        );
+    }
+
+    #[test]
+    fn buckets_start_and_are_labelled_like_date_trunc() {
+        let sunday = date(2026, 1, 4);
+        assert_eq!(Granularity::Week.bucket_start(sunday), date(2025, 12, 29));
+        assert_eq!(Granularity::Week.label(date(2025, 12, 29)), "2026-W01");
+        assert_eq!(Granularity::Month.bucket_start(sunday), date(2026, 1, 1));
+        assert_eq!(Granularity::Month.label(date(2026, 1, 1)), "Jan 2026");
+        assert_eq!(Granularity::Day.label(sunday), "2026-01-04");
+    }
+
+    #[test]
+    fn comparison_ranges() {
+        let range = ReportRange::resolve(Some(date(2026, 3, 1)), Some(date(2026, 3, 10)), Some("day"), today()).unwrap();
+        let previous = ComparisonRange::resolve(&range, None).unwrap();
+        assert_eq!((previous.from, previous.to), (date(2026, 2, 19), date(2026, 2, 28)));
+
+        let leap = ReportRange::resolve(Some(date(2024, 2, 29)), Some(date(2024, 3, 31)), Some("month"), today());
+        let last_year = ComparisonRange::resolve(&leap.unwrap(), Some("yoy")).unwrap();
+        assert_eq!((last_year.from, last_year.to), (date(2023, 2, 28), date(2023, 3, 31)));
+    }
+
+    #[test]
+    fn period_change_summaries() {
+        assert_eq!(PeriodChange::new(5, 4).summary(), "+25%");
+        assert_eq!(PeriodChange::new(9, 10).summary(), "-10%");
+        assert_eq!(PeriodChange::new(3, 0).summary(), "+3");
+        assert_eq!(PeriodChange::new(0, 0).summary(), "+0%");
+        assert_eq!(PeriodChange::new(2, 3).change_pct, Some(-33.3));
+    }
+}
@@ -0,0 +1,364 @@
+use chrono::{Duration, NaiveDate};
+
+use crate::models::pregnancy::{
+    AncContact, AncContactState, AncContactStatus, AncScheduleContact, Dipstick, FetalPresentation, GestationalAge,
+    RiskFactor,
+};
+use crate::services::validation::TextEnum;
+use crate::services::vitals::age_in_months;
+
+/// Length of a pregnancy from the first day of the last menstrual period (Naegele's rule).
+pub const PREGNANCY_DAYS: i64 = 280;
+/// A contact is listed as due this many days before its due date.
+pub const DUE_LEAD_DAYS: i64 = 7;
+/// A contact becomes overdue this many days after its due date.
+pub const OVERDUE_AFTER_DAYS: i64 = 7;
+/// A visit up to this many days before a contact's due date still counts as that contact.
+const EARLY_VISIT_DAYS: i64 = 14;
+/// The last contact stays open until 42 weeks.
+const LAST_WINDOW_DAYS: i64 = 14;
+
+pub fn edd_from_lmp(lmp: NaiveDate) -> NaiveDate {
+    lmp + Duration::days(PREGNANCY_DAYS)
+}
+
+/// The day gestational age is counted from: the LMP, or the date implied by the EDD.
+pub fn gestation_start(edd: NaiveDate) -> NaiveDate {
+    edd - Duration::days(PREGNANCY_DAYS)
+}
+
+pub fn gestational_age_days(edd: NaiveDate, on: NaiveDate) -> i32 {
+    (on - gestation_start(edd)).num_days() as i32
+}
+
+pub fn gestational_age(edd: NaiveDate, on: NaiveDate) -> GestationalAge {
+    let days = gestational_age_days(edd, on).max(0);
+    GestationalAge { weeks: days / 7, days: days % 7 }
+}
+
+/// Due date and last counting day of each scheduled contact, in contact order.
+fn contact_windows(edd: NaiveDate, schedule: &[AncScheduleContact]) -> Vec<(&AncScheduleContact, NaiveDate, NaiveDate)> {
+    let start = gestation_start(edd);
+    let mut contacts: Vec<&AncScheduleContact> = schedule.iter().collect();
+    contacts.sort_by_key(|c| c.gestational_week);
+    let due: Vec<NaiveDate> = contacts.iter().map(|c| start + Duration::weeks(i64::from(c.gestational_week))).collect();
+    contacts
+        .into_iter()
+        .enumerate()
+        .map(|(i, contact)| {
+            let window_end = match due.get(i + 1) {
+                Some(next) => *next - Duration::days(1),
+                None => edd + Duration::days(LAST_WINDOW_DAYS),
+            };
+            (contact, due[i], window_end)
+        })
+        .collect()
+}
+
+/// The scheduled contact a visit on `date` counts as: the earliest one not yet held whose
+/// window the visit falls in, or is at most two weeks early for. `None` makes it an extra visit.
+pub fn contact_number_for_visit(
+    edd: NaiveDate,
+    schedule: &[AncScheduleContact],
+    held: &[i32],
+    date: NaiveDate,
+) -> Option<i32> {
+    contact_windows(edd, schedule)
+        .into_iter()
+        .enumerate()
+        .find(|(i, (contact, due, window_end))| {
+            !held.contains(&contact.contact_number)
+                && date <= *window_end
+                && (*i == 0 || date >= *due - Duration::days(EARLY_VISIT_DAYS))
+        })
+        .map(|(_, (contact, _, _))| contact.contact_number)
+}
+
+/// Where each scheduled contact stands on `today`.
+///
+/// Contacts whose window closed before the pregnancy was booked are skipped rather than
+/// missed. Once a pregnancy has ended (`ended_on`), contacts falling due after that are left
+/// out and any still open are counted as missed.
+pub fn evaluate_contacts(
+    edd: NaiveDate,
+    booked_on: NaiveDate,
+    ended_on: Option<NaiveDate>,
+    today: NaiveDate,
+    schedule: &[AncScheduleContact],
+    contacts: &[AncContact],
+) -> Vec<AncContactState> {
+    let today = ended_on.map_or(today, |ended| ended.min(today));
+    contact_windows(edd, schedule)
+        .into_iter()
+        .filter(|(_, due, _)| ended_on.is_none_or(|ended| *due <= ended))
+        .map(|(scheduled, due_date, window_end)| {
+            let overdue_date = due_date + Duration::days(OVERDUE_AFTER_DAYS);
+            let held = contacts.iter().find(|c| c.contact_number == Some(scheduled.contact_number));
+            let status = match held {
+                Some(_) => AncContactStatus::Completed,
+                None if window_end < booked_on => AncContactStatus::Skipped,
+                None if ended_on.is_some() || today > window_end => AncContactStatus::Missed,
+                None if today >= overdue_date => AncContactStatus::Overdue,
+                None if today >= due_date - Duration::days(DUE_LEAD_DAYS) => AncContactStatus::Due,
+                None => AncContactStatus::Upcoming,
+            };
+            let outstanding_checks = scheduled
+                .checks
+                .iter()
+                .filter(|check| held.is_none_or(|c| !c.checks_done.contains(check)))
+                .cloned()
+                .collect();
+            AncContactState {
+                contact_number: scheduled.contact_number,
+                label: scheduled.label.clone(),
+                gestational_week: scheduled.gestational_week,
+                status,
+                due_date,
+                overdue_date,
+                window_end,
+                contact_id: held.map(|c| c.id),
+                contact_date: held.map(|c| c.contact_date),
+                recommended_checks: scheduled.checks.clone(),
+                outstanding_checks,
+            }
+        })
+        .collect()
+}
+
+/// Risk factors that follow from the mother's age and obstetric history.
+pub fn baseline_risks(date_of_birth: NaiveDate, booked_on: NaiveDate, para: i32) -> Vec<RiskFactor> {
+    let age = age_in_months(date_of_birth, booked_on) / 12;
+    let mut risks = Vec::new();
+    if age < 18 {
+        risks.push(RiskFactor::Adolescent);
+    } else if age >= 35 {
+        risks.push(RiskFactor::AdvancedMaternalAge);
+    }
+    if para >= 5 {
+        risks.push(RiskFactor::GrandMultipara);
+    }
+    risks
+}
+
+/// Risk factors shown by a contact's measurements, with whether any of them needs urgent review.
+pub fn contact_risks(contact: &AncContact) -> (Vec<RiskFactor>, bool) {
+    let mut risks = Vec::new();
+    let mut urgent = false;
+    let systolic = contact.systolic_bp.unwrap_or(0);
+    let diastolic = contact.diastolic_bp.unwrap_or(0);
+    if systolic >= 140 || diastolic >= 90 {
+        risks.push(RiskFactor::Hypertension);
+        urgent |= systolic >= 160 || diastolic >= 110;
+        let proteinuria = contact
+            .urine_protein
+            .as_deref()
+            .and_then(Dipstick::parse)
+            .is_some_and(|reading| reading >= Dipstick::OnePlus);
+        // hypertension with proteinuria after 20 weeks
+        if proteinuria && contact.gestational_age_days >= 20 * 7 {
+            risks.push(RiskFactor::PreEclampsia);
+            urgent = true;
+        }
+    }
+    if let Some(hb) = contact.haemoglobin {
+        if hb < 7.0 {
+            risks.push(RiskFactor::SevereAnaemia);
+            urgent = true;
+        } else if hb < 11.0 {
+            risks.push(RiskFactor::Anaemia);
+        }
+    }
+    if contact.fetal_heart_rate.is_some_and(|rate| !(110..=160).contains(&rate)) {
+        risks.push(RiskFactor::AbnormalFetalHeartRate);
+        urgent = true;
+    }
+    let malpresented = contact
+        .fetal_presentation
+        .as_deref()
+        .and_then(FetalPresentation::parse)
+        .is_some_and(|p| p != FetalPresentation::Cephalic);
+    if malpresented && contact.gestational_age_days >= 36 * 7 {
+        risks.push(RiskFactor::Malpresentation);
+    }
+    (risks, urgent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn contact(contact_number: i32, gestational_week: i32) -> AncScheduleContact {
+        AncScheduleContact { contact_number, gestational_week, label: format!("Contact {}", contact_number), checks: Vec::new() }
+    }
+
+    /// The WHO 2016 eight-contact schedule.
+    fn who_schedule() -> Vec<AncScheduleContact> {
+        [12, 20, 26, 30, 34, 36, 38, 40].iter().enumerate().map(|(i, week)| contact(i as i32 + 1, *week)).collect()
+    }
+
+    /// LMP 2026-01-01.
+    fn edd() -> NaiveDate {
+        date(2026, 10, 8)
+    }
+
+    #[test]
+    fn windows_run_from_each_due_date_to_the_day_before_the_next() {
+        let schedule = who_schedule();
+        let windows = contact_windows(edd(), &schedule);
+        assert_eq!(windows.len(), 8);
+        let (first, due, end) = windows[0];
+        assert_eq!(first.contact_number, 1);
+        assert_eq!(due, date(2026, 3, 26));
+        assert_eq!(end, date(2026, 5, 20));
+        assert_eq!(windows[1].1, date(2026, 5, 21));
+        for pair in windows.windows(2) {
+            assert_eq!(pair[0].2 + Duration::days(1), pair[1].1);
+        }
+    }
+
+    #[test]
+    fn last_window_stays_open_until_42_weeks() {
+        let schedule = who_schedule();
+        let (last, due, end) = *contact_windows(edd(), &schedule).last().unwrap();
+        assert_eq!(last.contact_number, 8);
+        assert_eq!(due, edd());
+        assert_eq!(end, date(2026, 10, 22));
+    }
+
+    #[test]
+    fn schedule_order_does_not_matter() {
+        let mut schedule = who_schedule();
+        schedule.reverse();
+        let numbers: Vec<i32> = contact_windows(edd(), &schedule).iter().map(|(c, _, _)| c.contact_number).collect();
+        assert_eq!(numbers, (1..=8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn empty_schedule_has_no_windows() {
+        assert!(contact_windows(edd(), &[]).is_empty());
+        assert_eq!(contact_number_for_visit(edd(), &[], &[], date(2026, 4, 1)), None);
+    }
+
+    #[test]
+    fn visits_count_toward_the_open_window_or_up_to_two_weeks_early() {
+        let schedule = who_schedule();
+        let visit = |held: &[i32], on| contact_number_for_visit(edd(), &schedule, held, on);
+        // the first contact has no lower bound
+        assert_eq!(visit(&[], date(2026, 2, 1)), Some(1));
+        assert_eq!(visit(&[], date(2026, 5, 20)), Some(1));
+        assert_eq!(visit(&[1], date(2026, 5, 7)), Some(2));
+        assert_eq!(visit(&[1], date(2026, 5, 6)), None);
+        assert_eq!(visit(&[1, 2, 3, 4, 5, 6, 7, 8], date(2026, 9, 1)), None);
+        assert_eq!(visit(&[1, 2, 3, 4, 5, 6, 7], date(2026, 10, 22)), Some(8));
+        assert_eq!(visit(&[1, 2, 3, 4, 5, 6, 7], date(2026, 10, 23)), None);
+    }
+
+    fn held(contact_number: i32, on: NaiveDate) -> AncContact {
+        AncContact {
+            id: contact_number,
+            pregnancy_id: 1,
+            contact_number: Some(contact_number),
+            contact_date: on,
+            gestational_age_days: gestational_age_days(edd(), on),
+            record_id: None,
+            weight_kg: None,
+            systolic_bp: None,
+            diastolic_bp: None,
+            haemoglobin: None,
+            fundal_height_cm: None,
+            fetal_heart_rate: None,
+            urine_protein: None,
+            urine_glucose: None,
+            fetal_presentation: None,
+            checks_done: Vec::new(),
+            notes: None,
+            recorded_by: None,
+            recorded_at: on.and_hms_opt(9, 0, 0).unwrap(),
+        }
+    }
+
+    fn statuses(
+        booked_on: NaiveDate,
+        ended_on: Option<NaiveDate>,
+        today: NaiveDate,
+        contacts: &[AncContact],
+    ) -> Vec<AncContactStatus> {
+        evaluate_contacts(edd(), booked_on, ended_on, today, &who_schedule(), contacts)
+            .into_iter()
+            .map(|state| state.status)
+            .collect()
+    }
+
+    #[test]
+    fn contacts_are_due_a_week_ahead_and_overdue_a_week_after() {
+        use AncContactStatus::*;
+        let booked = date(2026, 6, 1);
+        // contact 1 closed before booking; contact 2 was due on 2026-05-21
+        assert_eq!(&statuses(booked, None, date(2026, 6, 10), &[])[..3], [Skipped, Overdue, Upcoming]);
+        let visited = [held(2, date(2026, 6, 12))];
+        assert_eq!(&statuses(booked, None, date(2026, 6, 26), &visited)[..3], [Skipped, Completed, Due]);
+        assert_eq!(&statuses(booked, None, date(2026, 7, 5), &[])[..3], [Skipped, Missed, Due]);
+    }
+
+    #[test]
+    fn an_ended_pregnancy_drops_later_contacts_and_misses_open_ones() {
+        use AncContactStatus::*;
+        let ended = statuses(date(2026, 6, 1), Some(date(2026, 7, 5)), date(2026, 8, 1), &[]);
+        assert_eq!(ended, [Skipped, Missed, Missed]);
+    }
+
+    #[test]
+    fn outstanding_checks_leave_out_those_done() {
+        let mut schedule = who_schedule();
+        schedule[0].checks = vec!["bp".to_string(), "hb".to_string()];
+        let mut visit = held(1, date(2026, 4, 1));
+        visit.checks_done = vec!["bp".to_string()];
+        let states =
+            evaluate_contacts(edd(), date(2026, 3, 1), None, date(2026, 4, 2), &schedule, &[visit]);
+        assert_eq!(states[0].outstanding_checks, ["hb"]);
+        assert_eq!(states[0].recommended_checks, ["bp", "hb"]);
+    }
+
+    #[test]
+    fn baseline_risks_follow_age_and_parity() {
+        let booked = date(2026, 1, 1);
+        assert_eq!(baseline_risks(date(2010, 6, 1), booked, 0), [RiskFactor::Adolescent]);
+        let older = baseline_risks(date(1990, 1, 1), booked, 5);
+        assert_eq!(older, [RiskFactor::AdvancedMaternalAge, RiskFactor::GrandMultipara]);
+        assert!(baseline_risks(date(2000, 1, 1), booked, 2).is_empty());
+    }
+
+    #[test]
+    fn contact_measurements_flag_risks_and_urgency() {
+        let at = |weeks: i64| held(1, gestation_start(edd()) + Duration::weeks(weeks));
+        let mut visit = at(30);
+        visit.systolic_bp = Some(150);
+        visit.diastolic_bp = Some(95);
+        visit.urine_protein = Some("2+".to_string());
+        assert_eq!(contact_risks(&visit), (vec![RiskFactor::Hypertension, RiskFactor::PreEclampsia], true));
+        // proteinuria before 20 weeks is not pre-eclampsia
+        let mut early = at(18);
+        early.systolic_bp = Some(150);
+        early.urine_protein = Some("2+".to_string());
+        assert_eq!(contact_risks(&early), (vec![RiskFactor::Hypertension], false));
+
+        let mut anaemic = at(30);
+        anaemic.haemoglobin = Some(10.0);
+        assert_eq!(contact_risks(&anaemic), (vec![RiskFactor::Anaemia], false));
+        anaemic.haemoglobin = Some(6.5);
+        assert_eq!(contact_risks(&anaemic), (vec![RiskFactor::SevereAnaemia], true));
+
+        let mut breech = at(30);
+        breech.fetal_presentation = Some("breech".to_string());
+        assert_eq!(contact_risks(&breech), (vec![], false));
+        let mut breech = at(37);
+        breech.fetal_presentation = Some("breech".to_string());
+        breech.fetal_heart_rate = Some(100);
+        let risks = vec![RiskFactor::AbnormalFetalHeartRate, RiskFactor::Malpresentation];
+        assert_eq!(contact_risks(&breech), (risks, true));
+    }
+}
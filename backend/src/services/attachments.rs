@@ -0,0 +1,262 @@
+use sha2::{Digest, Sha256};
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use uuid::Uuid;
+
+/// Bytes needed to recognise every supported type; DICOM puts its magic after a 128-byte preamble.
+pub const SNIFF_BYTES: usize = 132;
+
+/// Identify an attachment from its leading bytes, ignoring whatever the client claimed.
+/// Only document and imaging formats that belong in a medical record are accepted.
+pub fn sniff_attachment_type(head: &[u8]) -> Option<&'static str> {
+    if head.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if head.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("image/png")
+    } else if head.len() >= 12 && &head[0..4] == b"RIFF" && &head[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if head.starts_with(b"II*\0") || head.starts_with(b"MM\0*") {
+        Some("image/tiff")
+    } else if head.len() >= SNIFF_BYTES && &head[128..132] == b"DICM" {
+        Some("application/dicom")
+    } else {
+        None
+    }
+}
+
+/// Storage key for content with the given digest; identical files share one object.
+pub fn blob_key(sha256: &str) -> String {
+    format!("attachments/{}/{}", &sha256[..2], sha256)
+}
+
+/// How to answer a request given its `Range` header and the size of the content.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ByteRange {
+    Full,
+    /// Inclusive byte offsets.
+    Partial { start: u64, end: u64 },
+    Unsatisfiable,
+}
+
+/// Interpret a `Range` header. Only a single `bytes` range is honoured; anything
+/// else is ignored and the full content is served, as RFC 9110 allows.
+pub fn parse_range(header: Option<&str>, size: u64) -> ByteRange {
+    let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((first, last)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (first, last) = (first.trim(), last.trim());
+    let (start, end) = if first.is_empty() {
+        // suffix range: the last N bytes
+        match last.parse::<u64>() {
+            Ok(0) => return ByteRange::Unsatisfiable,
+            Ok(n) => (size.saturating_sub(n), size.saturating_sub(1)),
+            Err(_) => return ByteRange::Full,
+        }
+    } else {
+        let Ok(start) = first.parse::<u64>() else {
+            return ByteRange::Full;
+        };
+        let end = if last.is_empty() {
+            size.saturating_sub(1)
+        } else {
+            match last.parse::<u64>() {
+                Ok(end) if end >= start => end.min(size.saturating_sub(1)),
+                _ => return ByteRange::Full,
+            }
+        };
+        (start, end)
+    };
+    if size == 0 || start >= size {
+        ByteRange::Unsatisfiable
+    } else {
+        ByteRange::Partial { start, end }
+    }
+}
+
+/// Parse an upload chunk's `Content-Range: bytes <start>-<end>/<total>` header.
+pub fn parse_content_range(header: &str) -> Option<(u64, u64, u64)> {
+    let spec = header.trim().strip_prefix("bytes ")?;
+    let (range, total) = spec.split_once('/')?;
+    let (start, end) = range.split_once('-')?;
+    let (start, end, total) = (start.trim().parse().ok()?, end.trim().parse().ok()?, total.trim().parse().ok()?);
+    (start <= end && end < total).then_some((start, end, total))
+}
+
+/// `Content-Disposition` value that offers `filename` for download, with an ASCII fallback.
+pub fn content_disposition(filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' { c } else { '_' })
+        .collect();
+    let mut encoded = String::with_capacity(filename.len());
+    for byte in filename.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    format!("attachment; filename=\"{}\"; filename*=UTF-8''{}", fallback, encoded)
+}
+
+/// Local directory holding the bytes of unfinished chunked uploads, one file per upload.
+#[derive(Clone)]
+pub struct StagingArea {
+    dir: PathBuf,
+}
+
+impl StagingArea {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        StagingArea { dir: dir.into() }
+    }
+
+    pub fn path_for(&self, upload_id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.part", upload_id))
+    }
+
+    /// Write `bytes` at `offset`, discarding anything past it left by an interrupted chunk.
+    pub async fn write_at(&self, upload_id: Uuid, offset: u64, bytes: &[u8]) -> std::io::Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.path_for(upload_id))
+            .await?;
+        file.set_len(offset).await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+        file.write_all(bytes).await?;
+        file.sync_data().await
+    }
+
+    /// SHA-256 (hex) and leading bytes of a staged upload.
+    pub async fn digest(&self, upload_id: Uuid) -> std::io::Result<(String, Vec<u8>)> {
+        let mut file = tokio::fs::File::open(self.path_for(upload_id)).await?;
+        let mut hasher = Sha256::new();
+        let mut head = Vec::with_capacity(SNIFF_BYTES);
+        let mut buffer = vec![0u8; 1 << 20];
+        loop {
+            let read = file.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+            if head.len() < SNIFF_BYTES {
+                let take = (SNIFF_BYTES - head.len()).min(read);
+                head.extend_from_slice(&buffer[..take]);
+            }
+            hasher.update(&buffer[..read]);
+        }
+        Ok((hex::encode(hasher.finalize()), head))
+    }
+
+    /// Best-effort removal; a leftover file only wastes disk until the next cleanup.
+    pub async fn remove(&self, upload_id: Uuid) {
+        let path = self.path_for(upload_id);
+        if let Err(e) = tokio::fs::remove_file(&path).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("[ATTACHMENT ERROR] Failed to remove staged upload {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attachments_are_identified_by_their_leading_bytes() {
+        assert_eq!(sniff_attachment_type(b"%PDF-1.7\n"), Some("application/pdf"));
+        assert_eq!(sniff_attachment_type(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+        assert_eq!(sniff_attachment_type(b"\x89PNG\r\n\x1a\n...."), Some("image/png"));
+        assert_eq!(sniff_attachment_type(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_attachment_type(b"II*\0\x08\0\0\0"), Some("image/tiff"));
+        assert_eq!(sniff_attachment_type(b"MM\0*\0\0\0\x08"), Some("image/tiff"));
+        let mut dicom = vec![0u8; 128];
+        dicom.extend_from_slice(b"DICM");
+        assert_eq!(sniff_attachment_type(&dicom), Some("application/dicom"));
+        // the DICOM magic only counts after the preamble
+        assert_eq!(sniff_attachment_type(&dicom[..131]), None);
+        assert_eq!(sniff_attachment_type(b"<html><script>"), None);
+        assert_eq!(sniff_attachment_type(b"RIFF\0\0\0\0WAVE"), None);
+        assert_eq!(sniff_attachment_type(b""), None);
+    }
+
+    #[test]
+    fn single_ranges_are_clamped_to_the_content() {
+        let range = |start, end| ByteRange::Partial { start, end };
+        assert_eq!(parse_range(Some("bytes=0-99"), 1000), range(0, 99));
+        assert_eq!(parse_range(Some(" bytes= 500 - "), 1000), range(500, 999));
+        assert_eq!(parse_range(Some("bytes=900-5000"), 1000), range(900, 999));
+        assert_eq!(parse_range(Some("bytes=-100"), 1000), range(900, 999));
+        assert_eq!(parse_range(Some("bytes=-5000"), 1000), range(0, 999));
+    }
+
+    #[test]
+    fn ranges_past_the_end_are_unsatisfiable() {
+        assert_eq!(parse_range(Some("bytes=1000-"), 1000), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=-0"), 1000), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=0-"), 0), ByteRange::Unsatisfiable);
+    }
+
+    #[test]
+    fn other_ranges_serve_the_full_content() {
+        let headers = ["bytes=0-9,20-29", "items=0-9", "bytes=9-0", "bytes=a-b", "bytes=5"];
+        for header in headers.into_iter().map(Some).chain([None]) {
+            assert_eq!(parse_range(header, 1000), ByteRange::Full, "{:?}", header);
+        }
+    }
+
+    #[test]
+    fn upload_chunks_need_a_complete_content_range() {
+        assert_eq!(parse_content_range("bytes 0-1048575/3000000"), Some((0, 1048575, 3000000)));
+        assert_eq!(parse_content_range(" bytes 10-10/11 "), Some((10, 10, 11)));
+        assert_eq!(parse_content_range("bytes 10-20/20"), None);
+        assert_eq!(parse_content_range("bytes 20-10/100"), None);
+        assert_eq!(parse_content_range("bytes 0-9/*"), None);
+        assert_eq!(parse_content_range("bytes */100"), None);
+        assert_eq!(parse_content_range("0-9/100"), None);
+    }
+
+    #[test]
+    fn blobs_are_keyed_by_digest() {
+        assert_eq!(blob_key("ab12cd"), "attachments/ab/ab12cd");
+    }
+
+    #[test]
+    fn download_names_have_an_ascii_fallback_and_an_encoded_form() {
+        assert_eq!(
+            content_disposition("scan 1.pdf"),
+            "attachment; filename=\"scan 1.pdf\"; filename*=UTF-8''scan%201.pdf"
+        );
+        assert_eq!(
+            content_disposition("ré\"sumé.pdf"),
+            "attachment; filename=\"r__sum_.pdf\"; filename*=UTF-8''r%C3%A9%22sum%C3%A9.pdf"
+        );
+    }
+
+    #[tokio::test]
+    async fn staged_chunks_overwrite_from_their_offset() {
+        let staging = StagingArea::new(std::env::temp_dir().join(format!("staging-{}", Uuid::new_v4())));
+        let upload = Uuid::new_v4();
+        staging.write_at(upload, 0, b"%PDF-1.7 first chunk, interrupted").await.unwrap();
+        // a retried second chunk replaces whatever the interrupted one left past its offset
+        staging.write_at(upload, 9, b"retried").await.unwrap();
+        let (digest, head) = staging.digest(upload).await.unwrap();
+        assert_eq!(head, b"%PDF-1.7 retried");
+        assert_eq!(digest, hex::encode(Sha256::digest(b"%PDF-1.7 retried")));
+        staging.remove(upload).await;
+        assert!(!staging.path_for(upload).exists());
+        staging.remove(upload).await;
+        let _ = std::fs::remove_dir(&staging.dir);
+    }
+}
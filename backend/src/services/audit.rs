@@ -0,0 +1,67 @@
+use serde_json::{json, Map, Value};
+use sqlx::PgExecutor;
+
+/// A single entry to append to `audit_logs`.
+pub struct AuditEntry<'a> {
+    pub user_id: Option<i32>,
+    pub action: &'a str,
+    pub entity_type: &'a str,
+    pub entity_id: i32,
+    pub patient_id: Option<i32>,
+    pub details: Value,
+}
+
+pub async fn record<'e, E: PgExecutor<'e>>(executor: E, entry: AuditEntry<'_>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO audit_logs (user_id, action, entity_type, entity_id, patient_id, details, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, now())"#
+    )
+    .bind(entry.user_id)
+    .bind(entry.action)
+    .bind(entry.entity_type)
+    .bind(entry.entity_id)
+    .bind(entry.patient_id)
+    .bind(entry.details)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Field-level changes between two serialized rows, as `{field: {from, to}}`.
+pub fn diff_fields(before: &Value, after: &Value, ignore: &[&str]) -> Map<String, Value> {
+    let mut changes = Map::new();
+    let (Some(before), Some(after)) = (before.as_object(), after.as_object()) else {
+        return changes;
+    };
+    for (field, new_value) in after {
+        if ignore.contains(&field.as_str()) {
+            continue;
+        }
+        let old_value = before.get(field).unwrap_or(&Value::Null);
+        if old_value != new_value {
+            changes.insert(field.clone(), json!({ "from": old_value, "to": new_value }));
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_changed_fields_are_reported() {
+        let before = json!({ "title": "Visit", "status": "draft", "updated_at": "2026-01-01", "notes": "x" });
+        let after = json!({ "title": "Visit", "status": "signed", "updated_at": "2026-01-02", "provider": "Dr A" });
+        let changes = diff_fields(&before, &after, &["updated_at"]);
+        assert_eq!(
+            Value::Object(changes),
+            json!({
+                "status": { "from": "draft", "to": "signed" },
+                "provider": { "from": null, "to": "Dr A" },
+            })
+        );
+        assert!(diff_fields(&before, &before, &[]).is_empty());
+        assert!(diff_fields(&Value::Null, &after, &[]).is_empty());
+    }
+}
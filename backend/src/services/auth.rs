@@ -0,0 +1,89 @@
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts},
+    http::{header, request::Parts},
+};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::models::error::ServiceError;
+use crate::models::user::{StaffRole, User};
+use crate::services::validation::TextEnum;
+
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    sub: i32,
+    email: String,
+    role: String,
+    tenant: i32,
+    exp: i64,
+}
+
+/// Signs and verifies the HS256 access tokens handed out by `/login`.
+#[derive(Clone)]
+pub struct TokenKeys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    ttl: Duration,
+}
+
+impl TokenKeys {
+    pub fn new(secret: &[u8], ttl: Duration) -> Self {
+        TokenKeys {
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+            ttl,
+        }
+    }
+
+    pub fn issue(&self, user: &User) -> Result<(String, DateTime<Utc>), jsonwebtoken::errors::Error> {
+        let expires_at = Utc::now() + self.ttl;
+        let claims = Claims {
+            sub: user.id,
+            email: user.email.clone(),
+            role: user.role.clone(),
+            tenant: user.tenant_id,
+            exp: expires_at.timestamp(),
+        };
+        let token = encode(&Header::new(Algorithm::HS256), &claims, &self.encoding)?;
+        Ok((token, expires_at))
+    }
+
+    fn verify(&self, token: &str) -> Option<Claims> {
+        decode::<Claims>(token, &self.decoding, &Validation::new(Algorithm::HS256))
+            .ok()
+            .map(|data| data.claims)
+    }
+}
+
+/// The staff member behind a request, taken from its `Authorization: Bearer` token.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub id: i32,
+    pub email: String,
+    pub role: StaffRole,
+    /// Every tenant-scoped query is confined to this tenant.
+    pub tenant_id: i32,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    TokenKeys: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ServiceError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(ServiceError::Unauthorized)?;
+        let claims = TokenKeys::from_ref(state).verify(token.trim()).ok_or(ServiceError::Unauthorized)?;
+        let role = StaffRole::parse(&claims.role).ok_or(ServiceError::Unauthorized)?;
+        Ok(AuthUser { id: claims.sub, email: claims.email, role, tenant_id: claims.tenant })
+    }
+}
@@ -0,0 +1,217 @@
+use crate::models::vital::{Consciousness, EarlyWarningRisk, VitalKind};
+use crate::services::validation::TextEnum;
+
+/// NEWS2 is validated for patients aged 16 and over only.
+pub const NEWS2_MIN_AGE_MONTHS: i32 = 16 * 12;
+
+/// The first risk band at which a patient is flagged critical and the care team alerted.
+pub const ALERT_RISK: EarlyWarningRisk = EarlyWarningRisk::LowMedium;
+
+/// NEWS2 inputs for one observation set, in canonical units.
+#[derive(Default)]
+pub struct News2Inputs {
+    pub respiratory_rate: Option<f64>,
+    pub spo2: Option<f64>,
+    pub supplemental_oxygen: Option<bool>,
+    pub systolic_bp: Option<f64>,
+    pub heart_rate: Option<f64>,
+    pub consciousness: Option<Consciousness>,
+    pub temperature: Option<f64>,
+}
+
+impl News2Inputs {
+    pub fn from_observations(
+        values: &[(VitalKind, f64)],
+        supplemental_oxygen: Option<bool>,
+        consciousness: Option<Consciousness>,
+    ) -> Self {
+        let find = |kind| values.iter().find(|(k, _)| *k == kind).map(|(_, v)| *v);
+        News2Inputs {
+            respiratory_rate: find(VitalKind::RespiratoryRate),
+            spo2: find(VitalKind::Spo2),
+            supplemental_oxygen,
+            systolic_bp: find(VitalKind::SystolicBp),
+            heart_rate: find(VitalKind::HeartRate),
+            consciousness,
+            temperature: find(VitalKind::Temperature),
+        }
+    }
+}
+
+pub struct News2Score {
+    pub total: i32,
+    pub risk: EarlyWarningRisk,
+    /// Points per parameter that contributed, for explaining the score.
+    pub parameters: Vec<(&'static str, i32)>,
+    pub missing: Vec<String>,
+}
+
+impl News2Score {
+    /// A one-line explanation, e.g. "NEWS2 7 (high): respiratory_rate 3, spo2 2, heart_rate 2".
+    pub fn summary(&self) -> String {
+        let contributing: Vec<String> = self
+            .parameters
+            .iter()
+            .filter(|(_, points)| *points > 0)
+            .map(|(name, points)| format!("{} {}", name, points))
+            .collect();
+        let mut summary = format!("NEWS2 {} ({})", self.total, self.risk.as_str());
+        if !contributing.is_empty() {
+            summary.push_str(&format!(": {}", contributing.join(", ")));
+        }
+        if !self.missing.is_empty() {
+            summary.push_str(&format!("; not measured: {}", self.missing.join(", ")));
+        }
+        summary
+    }
+}
+
+/// Score an observation set on the NEWS2 chart (SpO2 scale 1). Returns `None` when none of
+/// the parameters were measured.
+pub fn score(inputs: &News2Inputs) -> Option<News2Score> {
+    let scored: [(&'static str, Option<i32>); 7] = [
+        ("respiratory_rate", inputs.respiratory_rate.map(|rr| banded(rr, &[(8.0, 3), (11.0, 1), (20.0, 0), (24.0, 2)], 3))),
+        ("spo2", inputs.spo2.map(|spo2| banded(spo2, &[(91.0, 3), (93.0, 2), (95.0, 1)], 0))),
+        ("supplemental_oxygen", inputs.supplemental_oxygen.map(|on_oxygen| if on_oxygen { 2 } else { 0 })),
+        ("systolic_bp", inputs.systolic_bp.map(|sbp| banded(sbp, &[(90.0, 3), (100.0, 2), (110.0, 1), (219.0, 0)], 3))),
+        ("heart_rate", inputs.heart_rate.map(|hr| banded(hr, &[(40.0, 3), (50.0, 1), (90.0, 0), (110.0, 1), (130.0, 2)], 3))),
+        ("consciousness", inputs.consciousness.map(|level| if level == Consciousness::Alert { 0 } else { 3 })),
+        ("temperature", inputs.temperature.map(|temp| banded(temp, &[(35.0, 3), (36.0, 1), (38.0, 0), (39.0, 1)], 2))),
+    ];
+
+    let parameters: Vec<(&'static str, i32)> =
+        scored.iter().filter_map(|(name, points)| points.map(|p| (*name, p))).collect();
+    if parameters.is_empty() {
+        return None;
+    }
+    let missing = scored
+        .iter()
+        .filter(|(_, points)| points.is_none())
+        .map(|(name, _)| name.to_string())
+        .collect();
+    let total = parameters.iter().map(|(_, points)| points).sum();
+    let risk = if total >= 7 {
+        EarlyWarningRisk::High
+    } else if total >= 5 {
+        EarlyWarningRisk::Medium
+    } else if parameters.iter().any(|(_, points)| *points == 3) {
+        EarlyWarningRisk::LowMedium
+    } else {
+        EarlyWarningRisk::Low
+    };
+    Some(News2Score { total, risk, parameters, missing })
+}
+
+/// Points for the first band whose upper bound (inclusive) holds the value.
+fn banded(value: f64, bands: &[(f64, i32)], above: i32) -> i32 {
+    bands.iter().find(|(upper, _)| value <= *upper).map(|(_, points)| *points).unwrap_or(above)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(inputs: News2Inputs, parameter: &str) -> i32 {
+        let score = score(&inputs).expect("one parameter was measured");
+        score.parameters.iter().find(|(name, _)| *name == parameter).map(|(_, p)| *p).unwrap()
+    }
+
+    fn assert_bands(parameter: &str, input: impl Fn(f64) -> News2Inputs, expected: &[(f64, i32)]) {
+        for (value, want) in expected {
+            assert_eq!(points(input(*value), parameter), *want, "{} {}", parameter, value);
+        }
+    }
+
+    #[test]
+    fn respiratory_rate_bands() {
+        assert_bands(
+            "respiratory_rate",
+            |v| News2Inputs { respiratory_rate: Some(v), ..Default::default() },
+            &[(8.0, 3), (9.0, 1), (11.0, 1), (12.0, 0), (20.0, 0), (21.0, 2), (24.0, 2), (25.0, 3)],
+        );
+    }
+
+    #[test]
+    fn spo2_bands() {
+        assert_bands(
+            "spo2",
+            |v| News2Inputs { spo2: Some(v), ..Default::default() },
+            &[(91.0, 3), (92.0, 2), (93.0, 2), (94.0, 1), (95.0, 1), (96.0, 0), (100.0, 0)],
+        );
+    }
+
+    #[test]
+    fn systolic_bp_bands() {
+        assert_bands(
+            "systolic_bp",
+            |v| News2Inputs { systolic_bp: Some(v), ..Default::default() },
+            &[(90.0, 3), (91.0, 2), (100.0, 2), (101.0, 1), (110.0, 1), (111.0, 0), (219.0, 0), (220.0, 3)],
+        );
+    }
+
+    #[test]
+    fn heart_rate_bands() {
+        assert_bands(
+            "heart_rate",
+            |v| News2Inputs { heart_rate: Some(v), ..Default::default() },
+            &[
+                (40.0, 3), (41.0, 1), (50.0, 1), (51.0, 0), (90.0, 0),
+                (91.0, 1), (110.0, 1), (111.0, 2), (130.0, 2), (131.0, 3),
+            ],
+        );
+    }
+
+    #[test]
+    fn temperature_bands() {
+        assert_bands(
+            "temperature",
+            |v| News2Inputs { temperature: Some(v), ..Default::default() },
+            &[(35.0, 3), (35.1, 1), (36.0, 1), (36.1, 0), (38.0, 0), (38.1, 1), (39.0, 1), (39.1, 2)],
+        );
+    }
+
+    #[test]
+    fn oxygen_and_consciousness() {
+        let oxygen = |on| News2Inputs { supplemental_oxygen: Some(on), ..Default::default() };
+        assert_eq!(points(oxygen(true), "supplemental_oxygen"), 2);
+        assert_eq!(points(oxygen(false), "supplemental_oxygen"), 0);
+        for (level, want) in [(Consciousness::Alert, 0), (Consciousness::NewConfusion, 3), (Consciousness::Unresponsive, 3)] {
+            let inputs = News2Inputs { consciousness: Some(level), ..Default::default() };
+            assert_eq!(points(inputs, "consciousness"), want);
+        }
+    }
+
+    #[test]
+    fn risk_bands() {
+        let risk = |inputs: News2Inputs| score(&inputs).map(|s| (s.total, s.risk));
+        // 4 without any single 3 stays low
+        let low = News2Inputs { respiratory_rate: Some(22.0), heart_rate: Some(115.0), ..Default::default() };
+        assert_eq!(risk(low), Some((4, EarlyWarningRisk::Low)));
+        let single_three = News2Inputs { respiratory_rate: Some(8.0), ..Default::default() };
+        assert_eq!(risk(single_three), Some((3, EarlyWarningRisk::LowMedium)));
+        let five = News2Inputs {
+            respiratory_rate: Some(22.0),
+            consciousness: Some(Consciousness::Voice),
+            ..Default::default()
+        };
+        assert_eq!(risk(five), Some((5, EarlyWarningRisk::Medium)));
+        let six = News2Inputs { respiratory_rate: Some(25.0), spo2: Some(94.0), supplemental_oxygen: Some(true), ..Default::default() };
+        assert_eq!(risk(six), Some((6, EarlyWarningRisk::Medium)));
+        let seven = News2Inputs { respiratory_rate: Some(25.0), heart_rate: Some(115.0), supplemental_oxygen: Some(true), ..Default::default() };
+        assert_eq!(risk(seven), Some((7, EarlyWarningRisk::High)));
+    }
+
+    #[test]
+    fn nothing_measured_is_not_scored() {
+        assert!(score(&News2Inputs::default()).is_none());
+    }
+
+    #[test]
+    fn partial_sets_list_what_is_missing() {
+        let score = score(&News2Inputs { heart_rate: Some(70.0), ..Default::default() }).unwrap();
+        assert_eq!(score.total, 0);
+        assert_eq!(score.missing.len(), 6);
+        assert!(!score.missing.contains(&"heart_rate".to_string()));
+        assert_eq!(score.summary(), format!("NEWS2 0 (low); not measured: {}", score.missing.join(", ")));
+    }
+}
@@ -0,0 +1,77 @@
+use sqlx::PgExecutor;
+
+use crate::models::error::ServiceError;
+use crate::models::facility::Facility;
+use crate::services::validation::field_error;
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[FACILITY ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+/// Facility filter shared by the list and analytics endpoints: `facility_id` picks one
+/// facility and `my_facilities` the ones `user_id` is assigned to; with both, the facility
+/// must be one of the user's. `None` means every facility.
+pub async fn facility_scope<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    facility_id: Option<i32>,
+    my_facilities: Option<bool>,
+) -> Result<Option<Vec<i32>>, sqlx::Error> {
+    if my_facilities != Some(true) {
+        return Ok(facility_id.map(|id| vec![id]));
+    }
+    let mut ids: Vec<i32> = sqlx::query_scalar("SELECT facility_id FROM user_facilities WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_all(executor)
+        .await?;
+    if let Some(id) = facility_id {
+        ids.retain(|assigned| *assigned == id);
+    }
+    Ok(Some(ids))
+}
+
+/// The active facility `requested`, or `user_id`'s primary facility when none was given.
+async fn facility_or_primary<'e, E: PgExecutor<'e>>(
+    executor: E,
+    requested: Option<i32>,
+    user_id: i32,
+) -> Result<Option<Facility>, sqlx::Error> {
+    sqlx::query_as::<_, Facility>(
+        "SELECT * FROM facilities
+         WHERE active AND id = COALESCE($1, (SELECT facility_id FROM user_facilities WHERE user_id = $2 AND is_primary))",
+    )
+    .bind(requested)
+    .bind(user_id)
+    .fetch_optional(executor)
+    .await
+}
+
+/// Where something the caller is recording happens: `requested`, defaulting to their primary
+/// facility. Fails validation on `field` when neither gives an active facility.
+pub async fn require_facility<'e, E: PgExecutor<'e>>(
+    executor: E,
+    field: &str,
+    requested: Option<i32>,
+    user_id: i32,
+) -> Result<Facility, ServiceError> {
+    match facility_or_primary(executor, requested, user_id).await.map_err(db_error)? {
+        Some(facility) => Ok(facility),
+        None if requested.is_some() => Err(field_error(field, "is not an active facility")),
+        None => Err(field_error(field, "is required when you have no primary facility")),
+    }
+}
+
+/// Like [`require_facility`], for records that may be left without a facility when the caller
+/// has no primary one.
+pub async fn optional_facility<'e, E: PgExecutor<'e>>(
+    executor: E,
+    field: &str,
+    requested: Option<i32>,
+    user_id: i32,
+) -> Result<Option<Facility>, ServiceError> {
+    match facility_or_primary(executor, requested, user_id).await.map_err(db_error)? {
+        None if requested.is_some() => Err(field_error(field, "is not an active facility")),
+        facility => Ok(facility),
+    }
+}
@@ -0,0 +1,160 @@
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+use crate::models::error::ServiceError;
+use crate::models::geography::GeoArea;
+use crate::services::validation::{code_from, field_error, ValidationErrors};
+
+/// Media type of the GeoJSON endpoints.
+pub const GEOJSON_CONTENT_TYPE: &str = "application/geo+json";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    eprintln!("[GEOGRAPHY ERROR] DB error: {:?}", e);
+    ServiceError::InternalServerError
+}
+
+/// The village a patient lives in, by `village_id` or else by name. Names are matched
+/// ignoring case and spacing, and must pick out at most one village; a name that matches
+/// none is added under the Unassigned block, as the migration did for the names typed before,
+/// for an admin to move under the right block later.
+pub async fn resolve_village(
+    pool: &PgPool,
+    village_id: Option<i32>,
+    village: Option<&str>,
+) -> Result<Option<GeoArea>, ServiceError> {
+    let name = village.map(|v| v.split_whitespace().collect::<Vec<_>>().join(" "));
+    if village_id.is_none() && name.is_none() {
+        return Ok(None);
+    }
+    let mut matches = sqlx::query_as::<_, GeoArea>(
+        "SELECT * FROM geo_areas
+         WHERE level = 'village' AND (id = $1 OR ($1 IS NULL AND LOWER(name) = LOWER($2)))
+         LIMIT 2",
+    )
+    .bind(village_id)
+    .bind(&name)
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?;
+    match (matches.len(), village_id, name) {
+        (1, _, _) => Ok(matches.pop()),
+        (0, Some(_), _) => Err(field_error("village_id", "is not a village")),
+        (0, None, Some(name)) => add_unassigned_village(pool, &name).await.map(Some),
+        _ => Err(field_error("village", "matches several villages; pass village_id instead")),
+    }
+}
+
+/// Spellings that differ only in case or punctuation share a code, and so the village.
+async fn add_unassigned_village(pool: &PgPool, name: &str) -> Result<GeoArea, ServiceError> {
+    let code = code_from(name).to_uppercase();
+    if code.is_empty() {
+        return Err(field_error("village", "must contain letters or digits"));
+    }
+    sqlx::query_as::<_, GeoArea>(
+        "INSERT INTO geo_areas (level, code, name, parent_id)
+         SELECT 'village', b.code || '-' || $1, $2, b.id FROM geo_areas b WHERE b.code = 'UNASSIGNED-B'
+         ON CONFLICT (code) DO UPDATE SET updated_at = geo_areas.updated_at
+         RETURNING *",
+    )
+    .bind(&code)
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| field_error("village", "is not a known village; add it under its block first"))
+}
+
+/// A boundary must be a GeoJSON `Polygon` or `MultiPolygon` with its coordinates.
+pub fn check_boundary(errors: &mut ValidationErrors, boundary: &Option<Value>) {
+    let Some(boundary) = boundary else {
+        return;
+    };
+    let polygon = matches!(boundary.get("type").and_then(Value::as_str), Some("Polygon" | "MultiPolygon"));
+    let coordinates = boundary.get("coordinates").and_then(Value::as_array).is_some_and(|c| !c.is_empty());
+    if !polygon || !coordinates {
+        errors.add("boundary", "must be a GeoJSON Polygon or MultiPolygon geometry");
+    }
+}
+
+/// `area` as a GeoJSON feature: its boundary, else its point, else no geometry.
+pub fn feature(area: &GeoArea, mut properties: Value) -> Value {
+    let geometry = match (&area.boundary, area.latitude, area.longitude) {
+        (Some(boundary), _, _) => boundary.clone(),
+        (None, Some(latitude), Some(longitude)) => json!({ "type": "Point", "coordinates": [longitude, latitude] }),
+        _ => Value::Null,
+    };
+    if let Value::Object(map) = &mut properties {
+        map.insert("code".to_string(), json!(area.code));
+        map.insert("name".to_string(), json!(area.name));
+        map.insert("level".to_string(), json!(area.level));
+        map.insert("parent_id".to_string(), json!(area.parent_id));
+    }
+    json!({ "type": "Feature", "id": area.id, "geometry": geometry, "properties": properties })
+}
+
+pub fn feature_collection(features: Vec<Value>) -> Value {
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(boundary: Option<Value>, point: Option<(f64, f64)>) -> GeoArea {
+        let now = chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        GeoArea {
+            id: 9,
+            level: "village".to_string(),
+            code: "UP-SITAPUR-MISRIKH-RAMPUR".to_string(),
+            name: "Rampur".to_string(),
+            parent_id: Some(3),
+            latitude: point.map(|p| p.0),
+            longitude: point.map(|p| p.1),
+            boundary,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn boundary_accepted(boundary: Option<Value>) -> bool {
+        let mut errors = ValidationErrors::default();
+        check_boundary(&mut errors, &boundary);
+        errors.into_result().is_ok()
+    }
+
+    #[test]
+    fn boundaries_must_be_polygons_with_coordinates() {
+        let square = json!([[[80.0, 27.0], [80.1, 27.0], [80.1, 27.1], [80.0, 27.0]]]);
+        assert!(boundary_accepted(None));
+        assert!(boundary_accepted(Some(json!({ "type": "Polygon", "coordinates": square }))));
+        assert!(boundary_accepted(Some(json!({ "type": "MultiPolygon", "coordinates": [square] }))));
+        assert!(!boundary_accepted(Some(json!({ "type": "Point", "coordinates": [80.0, 27.0] }))));
+        assert!(!boundary_accepted(Some(json!({ "type": "Polygon", "coordinates": [] }))));
+        assert!(!boundary_accepted(Some(json!({ "type": "Polygon" }))));
+        assert!(!boundary_accepted(Some(json!("Polygon"))));
+    }
+
+    #[test]
+    fn features_prefer_the_boundary_over_the_point() {
+        let polygon = json!({ "type": "Polygon", "coordinates": [[[80.0, 27.0], [80.1, 27.0], [80.0, 27.0]]] });
+        let with_boundary = feature(&area(Some(polygon.clone()), Some((27.05, 80.05))), json!({ "patients": 4 }));
+        assert_eq!(
+            with_boundary,
+            json!({
+                "type": "Feature",
+                "id": 9,
+                "geometry": polygon,
+                "properties": {
+                    "patients": 4, "code": "UP-SITAPUR-MISRIKH-RAMPUR", "name": "Rampur", "level": "village",
+                    "parent_id": 3,
+                },
+            })
+        );
+        // GeoJSON points are longitude first
+        let point = feature(&area(None, Some((27.05, 80.05))), json!({}));
+        assert_eq!(point["geometry"], json!({ "type": "Point", "coordinates": [80.05, 27.05] }));
+        assert_eq!(feature(&area(None, None), json!({}))["geometry"], Value::Null);
+        let collection = feature_collection(vec![point.clone()]);
+        assert_eq!(collection, json!({ "type": "FeatureCollection", "features": [point] }));
+    }
+}
@@ -0,0 +1,440 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::models::growth::{AcuteMalnutrition, GrowthDeficit, ReferencePoint};
+use crate::models::patient::Gender;
+use crate::services::validation::TextEnum;
+
+/// Average month length used by the WHO standards to convert between days and months.
+pub const DAYS_PER_MONTH: f64 = 30.4375;
+/// Length-for-age and weight-for-length apply before this age, height-based tables after.
+pub const LYING_UNTIL_DAYS: i32 = 731;
+/// Growth monitoring covers children under 60 months, where the tables end.
+pub const MAX_AGE_DAYS: i32 = 1826;
+/// Standing height is this much less than lying length.
+const LENGTH_HEIGHT_OFFSET_CM: f64 = 0.7;
+/// MUAC cut-offs apply from six months.
+const MUAC_MIN_AGE_DAYS: i32 = 183;
+const MUAC_SAM_CM: f64 = 11.5;
+const MUAC_MAM_CM: f64 = 12.5;
+
+/// The reference tables, one file per indicator and sex in the growth tables directory.
+const TABLES: &[&str] = &["wfa", "lfa", "hfa", "wfl", "wfh"];
+/// Tables that are not shipped with the code and may be absent; weight-for-height z-scores
+/// are left out until they are installed.
+const OPTIONAL_TABLES: &[&str] = &["wfl", "wfh"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GrowthSex {
+    Boys,
+    Girls,
+}
+
+impl GrowthSex {
+    pub fn from_gender(gender: &str) -> Option<Self> {
+        match Gender::parse(gender) {
+            Some(Gender::Male) => Some(GrowthSex::Boys),
+            Some(Gender::Female) => Some(GrowthSex::Girls),
+            _ => None,
+        }
+    }
+
+    fn file_suffix(self) -> &'static str {
+        match self {
+            GrowthSex::Boys => "boys",
+            GrowthSex::Girls => "girls",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GrowthTableError {
+    Io(PathBuf, std::io::Error),
+    Parse { file: String, line: usize, message: String },
+}
+
+impl fmt::Display for GrowthTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrowthTableError::Io(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            GrowthTableError::Parse { file, line, message } => write!(f, "{} line {}: {}", file, line, message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LmsRow {
+    /// Age in days, or length/height in cm.
+    x: f64,
+    l: f64,
+    m: f64,
+    s: f64,
+}
+
+impl LmsRow {
+    /// The measurement at `z` standard deviations.
+    fn value_at(&self, z: f64) -> f64 {
+        if self.l == 0.0 {
+            self.m * (self.s * z).exp()
+        } else {
+            self.m * (1.0 + self.l * self.s * z).powf(1.0 / self.l)
+        }
+    }
+
+    /// WHO z-score of `value`. Weight indicators are skewed, so beyond ±3 SD the distance is
+    /// measured in units of the 2-3 SD gap rather than from the LMS curve.
+    fn z_score(&self, value: f64, restrict_tails: bool) -> f64 {
+        let z = if self.l == 0.0 {
+            (value / self.m).ln() / self.s
+        } else {
+            ((value / self.m).powf(self.l) - 1.0) / (self.l * self.s)
+        };
+        if !restrict_tails || z.abs() <= 3.0 {
+            return z;
+        }
+        let sign = z.signum();
+        let sd3 = self.value_at(3.0 * sign);
+        let sd2 = self.value_at(2.0 * sign);
+        3.0 * sign + (value - sd3) / (sd3 - sd2).abs()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct LmsTable {
+    rows: Vec<LmsRow>,
+}
+
+impl LmsTable {
+    /// LMS parameters at `x`, interpolated between rows; `None` outside the table.
+    fn at(&self, x: f64) -> Option<LmsRow> {
+        let index = self.rows.partition_point(|row| row.x < x);
+        let upper = self.rows.get(index)?;
+        if upper.x == x {
+            return Some(*upper);
+        }
+        let lower = self.rows.get(index.checked_sub(1)?)?;
+        let t = (x - lower.x) / (upper.x - lower.x);
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        Some(LmsRow { x, l: lerp(lower.l, upper.l), m: lerp(lower.m, upper.m), s: lerp(lower.s, upper.s) })
+    }
+}
+
+/// WHO growth reference tables loaded from a directory.
+///
+/// Each file is tab-separated with a header row naming the first column (`Day`, `Month`,
+/// `Length` or `Height`) and `L`, `M` and `S` columns, so the WHO expanded tables can be
+/// dropped in as they are; other columns are ignored and `#` lines are comments. Files are
+/// named `<table>_<boys|girls>.tsv` for the tables `wfa`, `lfa` (0-24 months, lying),
+/// `hfa` (24-60 months, standing), `wfl` (45-110 cm) and `wfh` (65-120 cm). `wfl` and `wfh`
+/// are optional: without them weight-for-height is not scored and acute malnutrition is
+/// classified from MUAC and oedema alone.
+#[derive(Debug, Clone, Default)]
+pub struct GrowthReferences {
+    tables: HashMap<(&'static str, GrowthSex), LmsTable>,
+}
+
+fn parse_table(file: &str, contents: &str) -> Result<LmsTable, GrowthTableError> {
+    let error = |line: usize, message: String| GrowthTableError::Parse { file: file.to_string(), line, message };
+    let mut columns: Option<(f64, [usize; 4])> = None;
+    let mut rows: Vec<LmsRow> = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+        let Some((scale, [x, l, m, s])) = columns else {
+            let find = |name: &str| fields.iter().position(|f| f.eq_ignore_ascii_case(name));
+            let scale = match fields[0].to_lowercase().as_str() {
+                "day" | "days" => 1.0,
+                "month" | "months" => DAYS_PER_MONTH,
+                "length" | "height" => 1.0,
+                other => return Err(error(index + 1, format!("unknown first column '{}'", other))),
+            };
+            match (find("L"), find("M"), find("S")) {
+                (Some(l), Some(m), Some(s)) => columns = Some((scale, [0, l, m, s])),
+                _ => return Err(error(index + 1, "header must name L, M and S columns".to_string())),
+            }
+            continue;
+        };
+        let number = |column: usize| -> Result<f64, GrowthTableError> {
+            fields
+                .get(column)
+                .and_then(|f| f.parse::<f64>().ok())
+                .ok_or_else(|| error(index + 1, format!("column {} is not a number", column + 1)))
+        };
+        let row = LmsRow { x: number(x)? * scale, l: number(l)?, m: number(m)?, s: number(s)? };
+        if rows.last().is_some_and(|last| last.x >= row.x) {
+            return Err(error(index + 1, "rows must be in increasing order".to_string()));
+        }
+        if row.m <= 0.0 || row.s <= 0.0 {
+            return Err(error(index + 1, "M and S must be positive".to_string()));
+        }
+        rows.push(row);
+    }
+    if rows.len() < 2 {
+        return Err(error(0, "needs at least two rows".to_string()));
+    }
+    Ok(LmsTable { rows })
+}
+
+impl GrowthReferences {
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, GrowthTableError> {
+        let mut tables = HashMap::new();
+        for table in TABLES {
+            for sex in [GrowthSex::Boys, GrowthSex::Girls] {
+                let file = format!("{}_{}.tsv", table, sex.file_suffix());
+                let path = dir.as_ref().join(&file);
+                let contents = match std::fs::read_to_string(&path) {
+                    Ok(contents) => contents,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound && OPTIONAL_TABLES.contains(table) => {
+                        tracing::warn!("{} not found; weight-for-height z-scores will be missing", path.display());
+                        continue;
+                    }
+                    Err(e) => return Err(GrowthTableError::Io(path, e)),
+                };
+                tables.insert((*table, sex), parse_table(&file, &contents)?);
+            }
+        }
+        Ok(GrowthReferences { tables })
+    }
+
+    fn lms(&self, table: &'static str, sex: GrowthSex, x: f64) -> Option<LmsRow> {
+        self.tables.get(&(table, sex))?.at(x)
+    }
+
+    pub fn weight_for_age(&self, sex: GrowthSex, age_days: i32, weight_kg: f64) -> Option<f64> {
+        Some(round2(self.lms("wfa", sex, f64::from(age_days))?.z_score(weight_kg, true)))
+    }
+
+    /// Length-for-age before two years, height-for-age after, correcting for how the child
+    /// was measured.
+    pub fn height_for_age(&self, sex: GrowthSex, age_days: i32, height_cm: f64, measured_lying: bool) -> Option<f64> {
+        let (table, value) = age_based_measure(age_days, height_cm, measured_lying);
+        Some(round2(self.lms(table, sex, f64::from(age_days))?.z_score(value, false)))
+    }
+
+    /// Weight-for-length before two years, weight-for-height after.
+    pub fn weight_for_height(
+        &self,
+        sex: GrowthSex,
+        age_days: i32,
+        height_cm: f64,
+        measured_lying: bool,
+        weight_kg: f64,
+    ) -> Option<f64> {
+        let (table, value) = age_based_measure(age_days, height_cm, measured_lying);
+        let table = if table == "lfa" { "wfl" } else { "wfh" };
+        Some(round2(self.lms(table, sex, value)?.z_score(weight_kg, true)))
+    }
+
+    /// The -3, -2, 0, +2 and +3 SD curves of a table, with age in months for age-based tables.
+    pub fn reference_curve(&self, table: &'static str, sex: GrowthSex) -> Vec<ReferencePoint> {
+        let per_unit = if matches!(table, "wfa" | "lfa" | "hfa") { DAYS_PER_MONTH } else { 1.0 };
+        let Some(lms) = self.tables.get(&(table, sex)) else {
+            return Vec::new();
+        };
+        lms.rows
+            .iter()
+            .map(|row| ReferencePoint {
+                x: round2(row.x / per_unit),
+                sd3neg: round2(row.value_at(-3.0)),
+                sd2neg: round2(row.value_at(-2.0)),
+                median: round2(row.m),
+                sd2: round2(row.value_at(2.0)),
+                sd3: round2(row.value_at(3.0)),
+            })
+            .collect()
+    }
+}
+
+/// The length/height table that applies at an age, and the measurement converted to the
+/// position that table assumes.
+pub fn age_based_measure(age_days: i32, height_cm: f64, measured_lying: bool) -> (&'static str, f64) {
+    match (age_days < LYING_UNTIL_DAYS, measured_lying) {
+        (true, true) => ("lfa", height_cm),
+        (true, false) => ("lfa", height_cm + LENGTH_HEIGHT_OFFSET_CM),
+        (false, true) => ("hfa", height_cm - LENGTH_HEIGHT_OFFSET_CM),
+        (false, false) => ("hfa", height_cm),
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Whether a z-score is outside the WHO limits for biologically implausible values.
+pub fn implausible(indicator: &str, z: f64) -> bool {
+    match indicator {
+        "wfa" => !(-6.0..=5.0).contains(&z),
+        "lhfa" => !(-6.0..=6.0).contains(&z),
+        _ => !(-5.0..=5.0).contains(&z),
+    }
+}
+
+/// SAM or MAM from weight-for-height, MUAC (from six months) and oedema; `None` when none
+/// of them was measured.
+pub fn classify_acute(
+    age_days: i32,
+    weight_for_height_z: Option<f64>,
+    muac_cm: Option<f64>,
+    oedema: bool,
+) -> Option<AcuteMalnutrition> {
+    let muac = muac_cm.filter(|_| age_days >= MUAC_MIN_AGE_DAYS);
+    if oedema || weight_for_height_z.is_some_and(|z| z < -3.0) || muac.is_some_and(|m| m < MUAC_SAM_CM) {
+        Some(AcuteMalnutrition::Sam)
+    } else if weight_for_height_z.is_some_and(|z| z < -2.0) || muac.is_some_and(|m| m < MUAC_MAM_CM) {
+        Some(AcuteMalnutrition::Mam)
+    } else if weight_for_height_z.is_some() || muac.is_some() {
+        Some(AcuteMalnutrition::Normal)
+    } else {
+        None
+    }
+}
+
+/// Stunting from height-for-age or underweight from weight-for-age.
+pub fn classify_deficit(z: Option<f64>) -> Option<GrowthDeficit> {
+    z.map(|z| {
+        if z < -3.0 {
+            GrowthDeficit::Severe
+        } else if z < -2.0 {
+            GrowthDeficit::Moderate
+        } else {
+            GrowthDeficit::Normal
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Weight-for-age, boys, at birth (WHO expanded table).
+    const WFA_BOYS_BIRTH: LmsRow = LmsRow { x: 0.0, l: 0.3487, m: 3.3464, s: 0.14602 };
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() <= tolerance
+    }
+
+    #[test]
+    fn median_scores_zero() {
+        assert!(close(WFA_BOYS_BIRTH.z_score(3.3464, true), 0.0, 1e-9));
+    }
+
+    #[test]
+    fn sd_curves_match_the_published_chart() {
+        // WHO weight-for-age boys at birth: -3 SD 2.1, -2 SD 2.5, +2 SD 4.4, +3 SD 5.0 kg
+        for (z, kg) in [(-3.0, 2.1), (-2.0, 2.5), (2.0, 4.4), (3.0, 5.0)] {
+            assert!(close(WFA_BOYS_BIRTH.value_at(z), kg, 0.05), "{} SD", z);
+            assert!(close(WFA_BOYS_BIRTH.z_score(WFA_BOYS_BIRTH.value_at(z), true), z, 1e-9));
+        }
+    }
+
+    #[test]
+    fn tails_beyond_three_sd_are_measured_in_sd23_gaps() {
+        let row = WFA_BOYS_BIRTH;
+        let (sd2, sd3) = (row.value_at(2.0), row.value_at(3.0));
+        assert!(close(row.z_score(sd3 + (sd3 - sd2) / 2.0, true), 3.5, 1e-9));
+        let (sd2neg, sd3neg) = (row.value_at(-2.0), row.value_at(-3.0));
+        assert!(close(row.z_score(sd3neg - (sd2neg - sd3neg), true), -4.0, 1e-9));
+        // height indicators follow the LMS curve all the way
+        let far = row.value_at(4.0);
+        assert!(close(row.z_score(far, false), 4.0, 1e-9));
+        assert!(!close(row.z_score(far, true), 4.0, 1e-3));
+    }
+
+    #[test]
+    fn zero_box_cox_power_uses_the_log_form() {
+        let row = LmsRow { x: 0.0, l: 0.0, m: 10.0, s: 0.1 };
+        assert!(close(row.z_score(10.0 * 0.2f64.exp(), false), 2.0, 1e-9));
+        assert!(close(row.value_at(-1.0), 10.0 * (-0.1f64).exp(), 1e-9));
+    }
+
+    #[test]
+    fn lookups_interpolate_and_stop_at_the_table_edges() {
+        let table = parse_table("t.tsv", "Day\tL\tM\tS\n0\t1\t3\t0.1\n10\t1\t4\t0.2\n").unwrap();
+        let mid = table.at(5.0).unwrap();
+        assert!(close(mid.m, 3.5, 1e-9) && close(mid.s, 0.15, 1e-9));
+        assert!(table.at(-1.0).is_none());
+        assert!(table.at(10.5).is_none());
+    }
+
+    #[test]
+    fn malformed_tables_are_rejected() {
+        for contents in [
+            "Day\tL\tM\n0\t1\t3\n1\t1\t3\n",
+            "Week\tL\tM\tS\n0\t1\t3\t0.1\n1\t1\t3\t0.1\n",
+            "Day\tL\tM\tS\n1\t1\t3\t0.1\n0\t1\t3\t0.1\n",
+            "Day\tL\tM\tS\n0\t1\t0\t0.1\n1\t1\t3\t0.1\n",
+            "Day\tL\tM\tS\n0\t1\tx\t0.1\n1\t1\t3\t0.1\n",
+            "Day\tL\tM\tS\n0\t1\t3\t0.1\n",
+        ] {
+            assert!(parse_table("t.tsv", contents).is_err(), "{:?}", contents);
+        }
+    }
+
+    #[test]
+    fn weight_for_height_tables_are_optional() {
+        let dir = std::env::temp_dir().join(format!("growth-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for table in ["wfa", "lfa", "hfa"] {
+            for sex in ["boys", "girls"] {
+                std::fs::write(dir.join(format!("{}_{}.tsv", table, sex)), "Day\tL\tM\tS\n0\t1\t3\t0.1\n10\t1\t4\t0.1\n")
+                    .unwrap();
+            }
+        }
+        let references = GrowthReferences::load(&dir).unwrap();
+        assert!(references.weight_for_age(GrowthSex::Boys, 5, 3.5).is_some());
+        assert_eq!(references.weight_for_height(GrowthSex::Girls, 5, 50.0, true, 3.5), None);
+        assert!(references.reference_curve("wfl", GrowthSex::Girls).is_empty());
+
+        std::fs::remove_file(dir.join("hfa_girls.tsv")).unwrap();
+        assert!(matches!(GrowthReferences::load(&dir), Err(GrowthTableError::Io(..))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// The shipped tables against WHO's published SD charts, which give the -2 SD, median and
+    /// +2 SD curves in kilograms and centimetres to one decimal.
+    #[test]
+    fn shipped_tables_reproduce_who_reference_points() {
+        use GrowthSex::{Boys, Girls};
+        let references = GrowthReferences::load(concat!(env!("CARGO_MANIFEST_DIR"), "/growth")).unwrap();
+        // (table, sex, age in months, -2 SD, median, +2 SD)
+        for (table, sex, months, published) in [
+            ("wfa", Boys, 0.0, [2.5, 3.3, 4.4]),
+            ("wfa", Girls, 0.0, [2.4, 3.2, 4.2]),
+            ("wfa", Boys, 12.0, [7.7, 9.6, 12.0]),
+            ("wfa", Girls, 12.0, [7.0, 8.9, 11.5]),
+            ("lfa", Boys, 0.0, [46.1, 49.9, 53.7]),
+            ("lfa", Girls, 0.0, [45.4, 49.1, 52.9]),
+            ("hfa", Boys, 24.0, [81.0, 87.1, 93.2]),
+            ("hfa", Girls, 24.0, [79.3, 85.7, 92.2]),
+        ] {
+            let curve = references.reference_curve(table, sex);
+            let point = curve.iter().find(|point| point.x == months).unwrap();
+            for (value, expected) in [point.sd2neg, point.median, point.sd2].into_iter().zip(published) {
+                assert!(close(value, expected, 0.05 + 1e-9), "{} {:?} {} months: {} vs {}", table, sex, months, value, expected);
+            }
+        }
+
+        // and every row's median and ±2 SD values score 0 and ±2 back through the table
+        for sex in [Boys, Girls] {
+            for table in ["wfa", "lfa", "hfa"] {
+                for row in &references.tables[&(table, sex)].rows {
+                    let lms = references.lms(table, sex, row.x).unwrap();
+                    for z in [-2.0, 0.0, 2.0] {
+                        let score = lms.z_score(row.value_at(z), table == "wfa");
+                        assert!(close(score, z, 1e-9), "{} {:?} {} days: {} for {}", table, sex, row.x, score, z);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn month_tables_are_converted_to_days() {
+        let table = parse_table("t.tsv", "# comment\nMonth\tL\tM\tS\n0\t1\t3\t0.1\n1\t1\t4\t0.1\n").unwrap();
+        assert!(close(table.rows[1].x, DAYS_PER_MONTH, 1e-9));
+    }
+}
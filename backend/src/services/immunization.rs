@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate};
+
+use crate::models::immunization::{DoseState, DoseStatus, Immunization, ScheduleDose};
+
+/// Where each dose of a schedule stands for a patient on `today`.
+///
+/// Doses restricted to the other sex are left out. A dose falls due at its due age or
+/// `min_interval_days` after the previous dose of the same vaccine (given, or else due),
+/// whichever is later, so a late first dose pushes the rest of the series back.
+pub fn evaluate_schedule(
+    date_of_birth: NaiveDate,
+    sex: &str,
+    today: NaiveDate,
+    doses: &[ScheduleDose],
+    given: &[Immunization],
+) -> Vec<DoseState> {
+    let mut doses: Vec<&ScheduleDose> = doses.iter().filter(|d| d.sex.as_deref().is_none_or(|s| s == sex)).collect();
+    doses.sort_by(|a, b| (&a.vaccine_code, a.dose_number).cmp(&(&b.vaccine_code, b.dose_number)));
+
+    let at_age = |days: i32| date_of_birth + Duration::days(i64::from(days));
+    let mut previous: HashMap<&str, NaiveDate> = HashMap::new();
+    let mut states = Vec::with_capacity(doses.len());
+    for dose in doses {
+        let mut earliest_date = at_age(dose.min_age_days);
+        let mut due_date = at_age(dose.due_age_days);
+        if let (Some(interval), Some(previous_date)) = (dose.min_interval_days, previous.get(dose.vaccine_code.as_str())) {
+            let after = *previous_date + Duration::days(i64::from(interval));
+            earliest_date = earliest_date.max(after);
+            due_date = due_date.max(after);
+        }
+        let overdue_date = due_date + Duration::days(i64::from(dose.overdue_age_days - dose.due_age_days));
+        let last_date = dose.max_age_days.map(at_age);
+        let record = given.iter().find(|i| i.vaccine_code == dose.vaccine_code && i.dose_number == dose.dose_number);
+        let status = match record {
+            Some(_) => DoseStatus::Given,
+            None if last_date.is_some_and(|last| today > last) => DoseStatus::Missed,
+            None if today >= overdue_date => DoseStatus::Overdue,
+            None if today >= due_date => DoseStatus::Due,
+            None => DoseStatus::Upcoming,
+        };
+        previous.insert(&dose.vaccine_code, record.map(|r| r.administered_on).unwrap_or(due_date));
+        states.push(DoseState {
+            vaccine_code: dose.vaccine_code.clone(),
+            dose_number: dose.dose_number,
+            label: dose.label.clone(),
+            status,
+            earliest_date,
+            due_date,
+            overdue_date,
+            last_date,
+            given_on: record.map(|r| r.administered_on),
+            immunization_id: record.map(|r| r.id),
+            given_early: record.is_some_and(|r| r.administered_on < earliest_date),
+        });
+    }
+    states.sort_by(|a, b| (a.due_date, &a.vaccine_code, a.dose_number).cmp(&(b.due_date, &b.vaccine_code, b.dose_number)));
+    states
+}
+
+/// Whether a dose should have been given by now: coverage counts it in the denominator.
+pub fn counts_for_coverage(state: &DoseState) -> bool {
+    matches!(state.status, DoseStatus::Given | DoseStatus::Overdue | DoseStatus::Missed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn birth() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()
+    }
+
+    /// The date `days` after birth.
+    fn day(days: i64) -> NaiveDate {
+        birth() + Duration::days(days)
+    }
+
+    fn dose(vaccine_code: &str, dose_number: i32, due_age_days: i32, interval: Option<i32>) -> ScheduleDose {
+        ScheduleDose {
+            vaccine_code: vaccine_code.to_string(),
+            dose_number,
+            label: format!("{} {}", vaccine_code, dose_number),
+            min_age_days: due_age_days,
+            due_age_days,
+            overdue_age_days: due_age_days + 28,
+            max_age_days: None,
+            min_interval_days: interval,
+            sex: None,
+        }
+    }
+
+    fn schedule() -> Vec<ScheduleDose> {
+        vec![
+            dose("penta", 3, 98, Some(28)),
+            dose("penta", 1, 42, None),
+            dose("penta", 2, 70, Some(28)),
+            ScheduleDose { max_age_days: Some(365), ..dose("bcg", 1, 0, None) },
+            ScheduleDose { sex: Some("female".to_string()), ..dose("hpv", 1, 3285, None) },
+        ]
+    }
+
+    fn given(vaccine_code: &str, dose_number: i32, on: NaiveDate) -> Immunization {
+        Immunization {
+            id: dose_number,
+            patient_id: 1,
+            record_id: None,
+            vaccine_code: vaccine_code.to_string(),
+            dose_number,
+            administered_on: on,
+            lot_number: None,
+            site: None,
+            route: None,
+            administered_by: None,
+            administered_by_name: None,
+            historical: false,
+            notes: None,
+            recorded_by: None,
+            recorded_at: on.and_hms_opt(10, 0, 0).unwrap(),
+        }
+    }
+
+    fn statuses(sex: &str, today: NaiveDate, given: &[Immunization]) -> Vec<(String, DoseStatus)> {
+        evaluate_schedule(birth(), sex, today, &schedule(), given)
+            .into_iter()
+            .map(|state| (state.label, state.status))
+            .collect()
+    }
+
+    fn expected(statuses: &[(&str, DoseStatus)]) -> Vec<(String, DoseStatus)> {
+        statuses.iter().map(|(label, status)| (label.to_string(), *status)).collect()
+    }
+
+    #[test]
+    fn doses_are_due_at_their_age_and_overdue_after() {
+        use DoseStatus::*;
+        assert_eq!(
+            statuses("male", day(59), &[]),
+            expected(&[("bcg 1", Overdue), ("penta 1", Due), ("penta 2", Upcoming), ("penta 3", Upcoming)])
+        );
+        assert_eq!(statuses("male", day(41), &[])[1].1, Upcoming);
+        assert_eq!(statuses("male", day(70), &[])[1].1, Overdue);
+    }
+
+    #[test]
+    fn doses_for_the_other_sex_are_left_out() {
+        assert_eq!(statuses("male", day(0), &[]).len(), 4);
+        let states = statuses("female", day(0), &[]);
+        assert_eq!(states.last(), Some(&("hpv 1".to_string(), DoseStatus::Upcoming)));
+    }
+
+    #[test]
+    fn doses_past_their_last_age_are_missed() {
+        assert_eq!(statuses("male", day(365), &[])[0].1, DoseStatus::Overdue);
+        assert_eq!(statuses("male", day(366), &[])[0].1, DoseStatus::Missed);
+        assert_eq!(statuses("male", day(366), &[given("bcg", 1, day(300))])[0].1, DoseStatus::Given);
+    }
+
+    #[test]
+    fn a_late_dose_pushes_the_rest_of_the_series_back() {
+        let states = evaluate_schedule(birth(), "male", day(100), &schedule(), &[given("penta", 1, day(80))]);
+        let penta: Vec<_> = states.iter().filter(|s| s.vaccine_code == "penta").collect();
+        assert_eq!((penta[0].status, penta[0].given_on), (DoseStatus::Given, Some(day(80))));
+        assert!(!penta[0].given_early);
+        assert_eq!((penta[1].earliest_date, penta[1].due_date, penta[1].overdue_date), (day(108), day(108), day(136)));
+        assert_eq!(penta[1].status, DoseStatus::Upcoming);
+        // the next interval counts from when dose 2 falls due, or from when it was given
+        assert_eq!(penta[2].due_date, day(136));
+        let given = [given("penta", 1, day(80)), given("penta", 2, day(120))];
+        let states = evaluate_schedule(birth(), "male", day(130), &schedule(), &given);
+        assert_eq!(states.iter().find(|s| s.label == "penta 3").unwrap().due_date, day(148));
+    }
+
+    #[test]
+    fn doses_given_before_the_interval_are_marked_early() {
+        let given = [given("penta", 1, day(42)), given("penta", 2, day(60))];
+        let states = evaluate_schedule(birth(), "male", day(61), &schedule(), &given);
+        let second = states.iter().find(|s| s.label == "penta 2").unwrap();
+        assert_eq!((second.status, second.given_early, second.earliest_date), (DoseStatus::Given, true, day(70)));
+    }
+
+    #[test]
+    fn only_doses_that_should_have_been_given_count_for_coverage() {
+        let states = evaluate_schedule(birth(), "male", day(400), &schedule(), &[given("penta", 1, day(42))]);
+        let counted: Vec<_> = states.iter().filter(|s| counts_for_coverage(s)).map(|s| s.label.as_str()).collect();
+        assert_eq!(counted, ["bcg 1", "penta 1", "penta 2", "penta 3"]);
+        let states = evaluate_schedule(birth(), "male", day(50), &schedule(), &[]);
+        let counted: Vec<_> = states.iter().filter(|s| counts_for_coverage(s)).map(|s| s.label.as_str()).collect();
+        assert_eq!(counted, ["bcg 1"]);
+    }
+}
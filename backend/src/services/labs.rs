@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use sqlx::PgExecutor;
+
+use crate::models::lab::{LabAnalyte, LabFlag, LabReferenceRange};
+
+/// The range for each analyte that fits the patient: sex-specific rows win over generic ones,
+/// then the narrowest age band.
+pub async fn applicable_ranges<'e, E: PgExecutor<'e>>(
+    executor: E,
+    analyte_codes: &[String],
+    sex: &str,
+    age_months: i32,
+) -> Result<HashMap<String, LabReferenceRange>, sqlx::Error> {
+    let ranges = sqlx::query_as::<_, LabReferenceRange>(
+        "SELECT DISTINCT ON (analyte_code) analyte_code, low, high, critical_low, critical_high
+         FROM lab_reference_ranges
+         WHERE analyte_code = ANY($1)
+           AND (sex IS NULL OR sex = $2)
+           AND min_age_months <= $3 AND (max_age_months IS NULL OR $3 < max_age_months)
+         ORDER BY analyte_code, sex IS NULL, COALESCE(max_age_months, 2147483647) - min_age_months",
+    )
+    .bind(analyte_codes)
+    .bind(sex)
+    .bind(age_months)
+    .fetch_all(executor)
+    .await?;
+    Ok(ranges.into_iter().map(|range| (range.analyte_code.clone(), range)).collect())
+}
+
+pub fn flag_numeric(range: &LabReferenceRange, value: f64) -> LabFlag {
+    if range.critical_low.is_some_and(|limit| value < limit) {
+        LabFlag::CriticalLow
+    } else if range.critical_high.is_some_and(|limit| value > limit) {
+        LabFlag::CriticalHigh
+    } else if range.low.is_some_and(|limit| value < limit) {
+        LabFlag::Low
+    } else if range.high.is_some_and(|limit| value > limit) {
+        LabFlag::High
+    } else {
+        LabFlag::Normal
+    }
+}
+
+/// Text results are normal when they match one of the analyte's normal values, ignoring case.
+/// Returns `None` for analytes without a list of normal values.
+pub fn flag_text(analyte: &LabAnalyte, value: &str) -> Option<LabFlag> {
+    let normal = analyte.normal_values.as_ref()?;
+    let value = value.trim().to_lowercase();
+    Some(if normal.contains(&value) { LabFlag::Normal } else { LabFlag::Abnormal })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(
+        low: Option<f64>,
+        high: Option<f64>,
+        critical_low: Option<f64>,
+        critical_high: Option<f64>,
+    ) -> LabReferenceRange {
+        LabReferenceRange { analyte_code: "hemoglobin".to_string(), low, high, critical_low, critical_high }
+    }
+
+    fn analyte(normal_values: Option<&[&str]>) -> LabAnalyte {
+        LabAnalyte {
+            test_code: "malaria_rdt".to_string(),
+            code: "malaria_rdt".to_string(),
+            name: "Malaria RDT".to_string(),
+            result_type: "text".to_string(),
+            unit: None,
+            normal_values: normal_values.map(|values| values.iter().map(|v| v.to_string()).collect()),
+            position: 1,
+        }
+    }
+
+    #[test]
+    fn numeric_results_are_flagged_against_the_range_limits() {
+        let range = range(Some(12.0), Some(15.5), Some(7.0), Some(20.0));
+        assert_eq!(flag_numeric(&range, 6.9), LabFlag::CriticalLow);
+        assert_eq!(flag_numeric(&range, 7.0), LabFlag::Low);
+        assert_eq!(flag_numeric(&range, 12.0), LabFlag::Normal);
+        assert_eq!(flag_numeric(&range, 15.5), LabFlag::Normal);
+        assert_eq!(flag_numeric(&range, 15.6), LabFlag::High);
+        assert_eq!(flag_numeric(&range, 20.1), LabFlag::CriticalHigh);
+        assert!(flag_numeric(&range, 20.1).is_critical());
+        assert!(!flag_numeric(&range, 15.6).is_critical());
+    }
+
+    #[test]
+    fn missing_limits_are_not_checked() {
+        // HbA1c has no critical low
+        let hba1c = range(Some(4.0), Some(5.6), None, Some(14.0));
+        assert_eq!(flag_numeric(&hba1c, 1.0), LabFlag::Low);
+        assert_eq!(flag_numeric(&range(None, None, None, None), 1000.0), LabFlag::Normal);
+    }
+
+    #[test]
+    fn text_results_match_the_normal_values_in_any_case() {
+        let rdt = analyte(Some(&["negative", "not seen"]));
+        assert_eq!(flag_text(&rdt, " Negative "), Some(LabFlag::Normal));
+        assert_eq!(flag_text(&rdt, "NOT SEEN"), Some(LabFlag::Normal));
+        assert_eq!(flag_text(&rdt, "P. falciparum"), Some(LabFlag::Abnormal));
+        assert_eq!(flag_text(&analyte(None), "anything"), None);
+    }
+}
@@ -0,0 +1,199 @@
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use serde::Serialize;
+use sha2::Sha256;
+use std::io::Cursor;
+
+/// Longest edge of the stored full-size photo.
+const MAX_PHOTO_DIMENSION: u32 = 2048;
+/// Longest edge of the generated thumbnail.
+const THUMBNAIL_DIMENSION: u32 = 256;
+const JPEG_QUALITY: u8 = 85;
+
+/// Identify an image from its leading bytes, ignoring whatever the client claimed.
+pub fn sniff_image_format(bytes: &[u8]) -> Option<ImageFormat> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(ImageFormat::Jpeg)
+    } else if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some(ImageFormat::Png)
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some(ImageFormat::WebP)
+    } else {
+        None
+    }
+}
+
+pub struct ProcessedPhoto {
+    pub photo: Vec<u8>,
+    pub thumbnail: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Decode, apply EXIF orientation, and re-encode as JPEG. Re-encoding drops all
+/// embedded metadata (EXIF, GPS, XMP), so nothing from the original file survives.
+pub fn process_photo(bytes: &[u8], format: ImageFormat) -> Result<ProcessedPhoto, image::ImageError> {
+    let mut reader = ImageReader::new(Cursor::new(bytes));
+    reader.set_format(format);
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+
+    if image.width() > MAX_PHOTO_DIMENSION || image.height() > MAX_PHOTO_DIMENSION {
+        image = image.resize(MAX_PHOTO_DIMENSION, MAX_PHOTO_DIMENSION, image::imageops::FilterType::Lanczos3);
+    }
+    let image = DynamicImage::ImageRgb8(image.to_rgb8());
+    let thumbnail = image.thumbnail(THUMBNAIL_DIMENSION, THUMBNAIL_DIMENSION);
+
+    Ok(ProcessedPhoto {
+        width: image.width(),
+        height: image.height(),
+        photo: encode_jpeg(&image)?,
+        thumbnail: encode_jpeg(&thumbnail)?,
+    })
+}
+
+fn encode_jpeg(image: &DynamicImage) -> Result<Vec<u8>, image::ImageError> {
+    let mut out = Vec::new();
+    image.write_with_encoder(JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY))?;
+    Ok(out)
+}
+
+#[derive(Serialize)]
+pub struct SignedUrl {
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Issues and checks time-limited, HMAC-signed URLs for media served by the API.
+#[derive(Clone)]
+pub struct UrlSigner {
+    secret: Vec<u8>,
+    ttl: Duration,
+}
+
+impl UrlSigner {
+    pub fn new(secret: Vec<u8>, ttl: Duration) -> Self {
+        UrlSigner { secret, ttl }
+    }
+
+    fn mac(&self, path: &str, expires: i64) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(path.as_bytes());
+        mac.update(b"\n");
+        mac.update(expires.to_string().as_bytes());
+        mac
+    }
+
+    /// Sign `path`, which may already carry a query string.
+    pub fn sign(&self, path: &str) -> SignedUrl {
+        let expires_at = Utc::now() + self.ttl;
+        let expires = expires_at.timestamp();
+        let signature = hex::encode(self.mac(path, expires).finalize().into_bytes());
+        let separator = if path.contains('?') { '&' } else { '?' };
+        SignedUrl {
+            url: format!("{}{}expires={}&signature={}", path, separator, expires, signature),
+            expires_at,
+        }
+    }
+
+    pub fn verify(&self, path: &str, expires: i64, signature: &str) -> bool {
+        if expires < Utc::now().timestamp() {
+            return false;
+        }
+        match hex::decode(signature) {
+            Ok(raw) => self.mac(path, expires).verify_slice(&raw).is_ok(),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn encode(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let image = RgbImage::from_pixel(width, height, Rgb([200, 40, 40]));
+        let mut out = Cursor::new(Vec::new());
+        image.write_to(&mut out, format).unwrap();
+        out.into_inner()
+    }
+
+    /// A JPEG whose EXIF block asks for a quarter turn clockwise and names a camera.
+    fn rotated_jpeg(width: u32, height: u32) -> Vec<u8> {
+        let jpeg = encode(width, height, ImageFormat::Jpeg);
+        let mut exif = b"Exif\0\0II*\0\x08\0\0\0\x01\0".to_vec();
+        // orientation (0x0112), SHORT, one value: 6
+        exif.extend_from_slice(&[0x12, 0x01, 0x03, 0x00, 0x01, 0, 0, 0, 0x06, 0, 0, 0, 0, 0, 0, 0]);
+        exif.extend_from_slice(b"CameraMaker");
+        let length = u16::try_from(exif.len() + 2).unwrap().to_be_bytes();
+        [&jpeg[..2], &[0xFF, 0xE1], &length, &exif, &jpeg[2..]].concat()
+    }
+
+    #[test]
+    fn images_are_identified_by_their_leading_bytes() {
+        assert_eq!(sniff_image_format(&encode(2, 2, ImageFormat::Jpeg)), Some(ImageFormat::Jpeg));
+        assert_eq!(sniff_image_format(&encode(2, 2, ImageFormat::Png)), Some(ImageFormat::Png));
+        assert_eq!(sniff_image_format(b"RIFF\0\0\0\0WEBPVP8 "), Some(ImageFormat::WebP));
+        assert_eq!(sniff_image_format(b"GIF89a"), None);
+        assert_eq!(sniff_image_format(b"RIFF\0\0\0\0WAVE"), None);
+        assert_eq!(sniff_image_format(b""), None);
+    }
+
+    #[test]
+    fn large_photos_are_scaled_down_and_thumbnailed() {
+        let processed = process_photo(&encode(2560, 1280, ImageFormat::Png), ImageFormat::Png).unwrap();
+        assert_eq!((processed.width, processed.height), (2048, 1024));
+        assert_eq!(sniff_image_format(&processed.photo), Some(ImageFormat::Jpeg));
+        let thumbnail = image::load_from_memory(&processed.thumbnail).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (256, 128));
+
+        let small = process_photo(&encode(640, 480, ImageFormat::Png), ImageFormat::Png).unwrap();
+        assert_eq!((small.width, small.height), (640, 480));
+    }
+
+    #[test]
+    fn photos_are_turned_upright_and_lose_their_metadata() {
+        let original = rotated_jpeg(40, 20);
+        assert!(original.windows(11).any(|w| w == b"CameraMaker"));
+        let processed = process_photo(&original, ImageFormat::Jpeg).unwrap();
+        assert_eq!((processed.width, processed.height), (20, 40));
+        for bytes in [&processed.photo, &processed.thumbnail] {
+            assert!(!bytes.windows(4).any(|w| w == b"Exif"));
+            assert!(!bytes.windows(11).any(|w| w == b"CameraMaker"));
+        }
+    }
+
+    #[test]
+    fn photos_that_do_not_decode_are_rejected() {
+        assert!(process_photo(b"\xFF\xD8\xFF\xE0 not really a jpeg", ImageFormat::Jpeg).is_err());
+    }
+
+    fn query(url: &str, key: &str) -> String {
+        let (_, query) = url.split_once('?').unwrap();
+        query.split('&').find_map(|pair| pair.strip_prefix(&format!("{}=", key))).unwrap().to_string()
+    }
+
+    #[test]
+    fn signed_urls_verify_only_for_their_path_and_until_they_expire() {
+        let signer = UrlSigner::new(b"secret".to_vec(), Duration::minutes(5));
+        let signed = signer.sign("/patients/7/photo?size=thumbnail");
+        assert!(signed.url.starts_with("/patients/7/photo?size=thumbnail&expires="));
+        let (expires, signature) = (query(&signed.url, "expires").parse().unwrap(), query(&signed.url, "signature"));
+        assert!(signer.verify("/patients/7/photo?size=thumbnail", expires, &signature));
+        assert!(!signer.verify("/patients/8/photo?size=thumbnail", expires, &signature));
+        assert!(!signer.verify("/patients/7/photo?size=thumbnail", expires + 60, &signature));
+        assert!(!signer.verify("/patients/7/photo?size=thumbnail", expires, "not hex"));
+        let other = UrlSigner::new(b"other".to_vec(), Duration::minutes(5));
+        assert!(!other.verify("/patients/7/photo?size=thumbnail", expires, &signature));
+
+        let expired = UrlSigner::new(b"secret".to_vec(), Duration::minutes(-1));
+        let signed = expired.sign("/patients/7/photo");
+        let (expires, signature) = (query(&signed.url, "expires").parse().unwrap(), query(&signed.url, "signature"));
+        assert!(!expired.verify("/patients/7/photo", expires, &signature));
+    }
+}
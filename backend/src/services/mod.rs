@@ -0,0 +1,23 @@
+pub mod audit;
+pub mod validation;
+pub mod media;
+pub mod storage;
+pub mod auth;
+pub mod access;
+pub mod attachments;
+pub mod versions;
+pub mod vitals;
+pub mod early_warning;
+pub mod alerts;
+pub mod labs;
+pub mod prescribing;
+pub mod terminology;
+pub mod immunization;
+pub mod antenatal;
+pub mod growth;
+pub mod scheduling;
+pub mod queue;
+pub mod facilities;
+pub mod tenancy;
+pub mod geography;
+pub mod analytics;
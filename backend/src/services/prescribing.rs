@@ -0,0 +1,221 @@
+use crate::models::prescription::{AllergenClass, Drug, MedicationWarning, WarningSeverity};
+use crate::services::validation::normalize_term;
+
+/// Allergy list entries that record the absence of allergies.
+const NO_ALLERGY_MARKERS: &[&str] = &["none", "nil", "nka", "nkda", "no known allergies", "no known drug allergies"];
+
+/// The drug table entry known by `term`, by name or synonym.
+pub fn find_drug<'a>(drugs: &'a [Drug], term: &str) -> Option<&'a Drug> {
+    let term = normalize_term(term);
+    drugs.iter().find(|d| d.name == term || d.synonyms.contains(&term))
+}
+
+fn find_class<'a>(classes: &'a [AllergenClass], code: &str) -> Option<&'a AllergenClass> {
+    classes.iter().find(|c| c.code == code)
+}
+
+/// Patient allergy terms worth checking, normalized, without "none"-style markers.
+pub fn allergy_terms(allergies: &[String]) -> Vec<String> {
+    allergies
+        .iter()
+        .map(|a| normalize_term(a))
+        .filter(|a| !a.is_empty() && !NO_ALLERGY_MARKERS.contains(&a.as_str()))
+        .collect()
+}
+
+/// Check a prescription against the patient's allergy list.
+///
+/// An allergy to the drug itself or to its class (named directly or through another drug
+/// in it) blocks the prescription. Cross-reactive classes and drugs missing from the local
+/// table produce warnings that can be overridden. `drugs` must contain the prescribed drug
+/// and any drugs named in the allergy list.
+pub fn check_allergies(
+    drug: Option<&Drug>,
+    drug_name: &str,
+    allergies: &[String],
+    drugs: &[Drug],
+    classes: &[AllergenClass],
+) -> Vec<MedicationWarning> {
+    let mut warnings = Vec::new();
+    let terms = allergy_terms(allergies);
+    let Some(drug) = drug else {
+        let name = normalize_term(drug_name);
+        for term in &terms {
+            if name.contains(term.as_str()) || term.contains(name.as_str()) {
+                warnings.push(MedicationWarning {
+                    severity: WarningSeverity::Blocking,
+                    code: "allergy".to_string(),
+                    allergy: Some(term.clone()),
+                    message: format!("Patient is allergic to {}", term),
+                });
+            }
+        }
+        if warnings.is_empty() && !terms.is_empty() {
+            warnings.push(MedicationWarning {
+                severity: WarningSeverity::Overridable,
+                code: "unchecked_drug".to_string(),
+                allergy: None,
+                message: format!(
+                    "{} is not in the local drug table, so it could not be checked against the patient's allergies",
+                    drug_name
+                ),
+            });
+        }
+        return warnings;
+    };
+
+    let drug_class = drug.allergen_class.as_deref().and_then(|code| find_class(classes, code));
+    for term in &terms {
+        if drug.name == *term || drug.synonyms.contains(term) {
+            warnings.push(MedicationWarning {
+                severity: WarningSeverity::Blocking,
+                code: "allergy".to_string(),
+                allergy: Some(term.clone()),
+                message: format!("Patient is allergic to {}", drug.name),
+            });
+            continue;
+        }
+        // the allergy names a class, or a drug that belongs to one
+        let allergic_class = classes
+            .iter()
+            .find(|c| c.code == *term || normalize_term(&c.name) == *term || c.aliases.contains(term))
+            .or_else(|| {
+                find_drug(drugs, term)
+                    .and_then(|d| d.allergen_class.as_deref())
+                    .and_then(|code| find_class(classes, code))
+            });
+        let (Some(allergic_class), Some(drug_class)) = (allergic_class, drug_class) else {
+            continue;
+        };
+        if allergic_class.code == drug_class.code {
+            warnings.push(MedicationWarning {
+                severity: WarningSeverity::Blocking,
+                code: "class_allergy".to_string(),
+                allergy: Some(term.clone()),
+                message: format!(
+                    "{} belongs to {}; patient is allergic to {}",
+                    drug.name, drug_class.name, term
+                ),
+            });
+        } else if allergic_class.cross_reactive_with.contains(&drug_class.code) {
+            warnings.push(MedicationWarning {
+                severity: WarningSeverity::Overridable,
+                code: "cross_reactivity".to_string(),
+                allergy: Some(term.clone()),
+                message: format!(
+                    "{} ({}) may cross-react with the patient's allergy to {} ({})",
+                    drug.name, drug_class.name, term, allergic_class.name
+                ),
+            });
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drug(name: &str, synonyms: &[&str], class: Option<&str>) -> Drug {
+        Drug {
+            name: name.to_string(),
+            synonyms: synonyms.iter().map(|s| s.to_string()).collect(),
+            allergen_class: class.map(str::to_string),
+            default_route: None,
+        }
+    }
+
+    fn class(code: &str, name: &str, aliases: &[&str], cross_reactive_with: &[&str]) -> AllergenClass {
+        AllergenClass {
+            code: code.to_string(),
+            name: name.to_string(),
+            aliases: aliases.iter().map(|s| s.to_string()).collect(),
+            cross_reactive_with: cross_reactive_with.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn fixtures() -> (Vec<Drug>, Vec<AllergenClass>) {
+        let drugs = vec![
+            drug("amoxicillin", &["amoxil"], Some("penicillin")),
+            drug("ampicillin", &[], Some("penicillin")),
+            drug("cefalexin", &["cephalexin"], Some("cephalosporin")),
+            drug("paracetamol", &["acetaminophen"], None),
+        ];
+        let classes = vec![
+            class("penicillin", "Penicillins", &["penicillins", "pcn"], &["cephalosporin"]),
+            class("cephalosporin", "Cephalosporins", &["cephalosporins"], &["penicillin"]),
+        ];
+        (drugs, classes)
+    }
+
+    /// (code, severity) of each warning for prescribing `name` to a patient with `allergies`.
+    fn check(name: &str, allergies: &[&str]) -> Vec<(String, WarningSeverity)> {
+        let (drugs, classes) = fixtures();
+        let allergies: Vec<String> = allergies.iter().map(|a| a.to_string()).collect();
+        check_allergies(find_drug(&drugs, name), name, &allergies, &drugs, &classes)
+            .into_iter()
+            .map(|w| (w.code, w.severity))
+            .collect()
+    }
+
+    fn warning(code: &str, severity: WarningSeverity) -> Vec<(String, WarningSeverity)> {
+        vec![(code.to_string(), severity)]
+    }
+
+    #[test]
+    fn allergy_to_the_drug_blocks_by_name_or_synonym() {
+        assert_eq!(check("amoxicillin", &["Amoxicillin"]), warning("allergy", WarningSeverity::Blocking));
+        assert_eq!(check("Amoxil", &[" amoxicillin "]), warning("allergy", WarningSeverity::Blocking));
+        assert_eq!(check("amoxicillin", &["amoxil"]), warning("allergy", WarningSeverity::Blocking));
+    }
+
+    #[test]
+    fn allergy_to_the_class_blocks() {
+        assert_eq!(check("amoxicillin", &["PCN"]), warning("class_allergy", WarningSeverity::Blocking));
+        assert_eq!(check("amoxicillin", &["Penicillins"]), warning("class_allergy", WarningSeverity::Blocking));
+        // another drug of the same class
+        assert_eq!(check("amoxicillin", &["ampicillin"]), warning("class_allergy", WarningSeverity::Blocking));
+    }
+
+    #[test]
+    fn cross_reactive_classes_warn() {
+        assert_eq!(check("cefalexin", &["penicillin"]), warning("cross_reactivity", WarningSeverity::Overridable));
+        assert_eq!(check("amoxicillin", &["cephalexin"]), warning("cross_reactivity", WarningSeverity::Overridable));
+    }
+
+    #[test]
+    fn unrelated_or_absent_allergies_pass() {
+        assert!(check("amoxicillin", &["latex", "peanuts"]).is_empty());
+        assert!(check("paracetamol", &["penicillin"]).is_empty());
+        assert!(check("amoxicillin", &[]).is_empty());
+        assert!(check("amoxicillin", &["None", " NKDA ", "no known allergies", ""]).is_empty());
+    }
+
+    #[test]
+    fn drugs_missing_from_the_table_are_matched_by_name_or_flagged_unchecked() {
+        assert_eq!(check("Augmentin 625", &["augmentin"]), warning("allergy", WarningSeverity::Blocking));
+        assert_eq!(check("Augmentin 625", &["sulfa"]), warning("unchecked_drug", WarningSeverity::Overridable));
+        assert!(check("Augmentin 625", &["nil"]).is_empty());
+    }
+
+    #[test]
+    fn every_matching_allergy_is_reported() {
+        let warnings = check("amoxicillin", &["amoxicillin", "pcn", "cefalexin"]);
+        let codes: Vec<&str> = warnings.iter().map(|(code, _)| code.as_str()).collect();
+        assert_eq!(codes, ["allergy", "class_allergy", "cross_reactivity"]);
+    }
+
+    #[test]
+    fn drugs_are_found_by_name_or_synonym_in_any_case_and_spacing() {
+        let (drugs, _) = fixtures();
+        assert_eq!(find_drug(&drugs, "  AMOXIL ").map(|d| d.name.as_str()), Some("amoxicillin"));
+        assert_eq!(find_drug(&drugs, "Acetaminophen").map(|d| d.name.as_str()), Some("paracetamol"));
+        assert!(find_drug(&drugs, "amoxicillin clavulanate").is_none());
+    }
+
+    #[test]
+    fn allergy_terms_are_normalized_without_markers() {
+        let allergies: Vec<String> = ["  Penicillin  G ", "NKDA", "", "Sulfa"].iter().map(|a| a.to_string()).collect();
+        assert_eq!(allergy_terms(&allergies), ["penicillin g", "sulfa"]);
+    }
+}
@@ -0,0 +1,67 @@
+use chrono::NaiveDate;
+use sqlx::PgExecutor;
+
+use crate::models::queue::WaitTimeStats;
+
+/// Wait times per facility and day for `tenant_id`'s patients who arrived between `from` and
+/// `to` (inclusive), latest day first. Patients who left before being seen count as arrivals only.
+pub async fn wait_time_stats<'e, E: PgExecutor<'e>>(
+    executor: E,
+    facility_ids: Option<&[i32]>,
+    tenant_id: i32,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<WaitTimeStats>, sqlx::Error> {
+    sqlx::query_as::<_, WaitTimeStats>(
+        "SELECT facility_id, f.name AS facility_name, date, COUNT(*) AS arrivals,
+                COUNT(wait) AS seen,
+                COUNT(*) FILTER (WHERE status = 'left') AS left_without_being_seen,
+                round(AVG(wait)::numeric, 1)::float8 AS avg_wait_minutes,
+                round((percentile_cont(0.5) WITHIN GROUP (ORDER BY wait))::numeric, 1)::float8 AS median_wait_minutes,
+                round((percentile_cont(0.9) WITHIN GROUP (ORDER BY wait))::numeric, 1)::float8 AS p90_wait_minutes,
+                round(MAX(wait)::numeric, 1)::float8 AS max_wait_minutes,
+                round(AVG(triage_wait)::numeric, 1)::float8 AS avg_triage_wait_minutes,
+                round(AVG(consultation)::numeric, 1)::float8 AS avg_consultation_minutes
+         FROM (
+             SELECT e.facility_id, e.arrived_at::date AS date, e.status,
+                    EXTRACT(EPOCH FROM e.consultation_started_at - e.arrived_at) / 60 AS wait,
+                    EXTRACT(EPOCH FROM e.triaged_at - e.arrived_at) / 60 AS triage_wait,
+                    EXTRACT(EPOCH FROM e.consultation_ended_at - e.consultation_started_at) / 60 AS consultation
+             FROM queue_entries e
+             JOIN patients p ON p.id = e.patient_id AND p.tenant_id = $4
+             WHERE e.arrived_at >= $1 AND e.arrived_at < $2::date + 1
+               AND ($3::int[] IS NULL OR e.facility_id = ANY($3))
+         ) q
+         JOIN facilities f ON f.id = q.facility_id
+         GROUP BY facility_id, f.name, date
+         ORDER BY date DESC, f.name",
+    )
+    .bind(from)
+    .bind(to)
+    .bind(facility_ids)
+    .bind(tenant_id)
+    .fetch_all(executor)
+    .await
+}
+
+/// Average wait in minutes over every consultation of `tenant_id`'s patients started since
+/// `since`, across `facility_ids` or every facility.
+pub async fn average_wait_minutes<'e, E: PgExecutor<'e>>(
+    executor: E,
+    facility_ids: Option<&[i32]>,
+    tenant_id: i32,
+    since: NaiveDate,
+) -> Result<Option<f64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT AVG(EXTRACT(EPOCH FROM q.consultation_started_at - q.arrived_at) / 60)::float8
+         FROM queue_entries q JOIN patients p ON p.id = q.patient_id
+         WHERE q.arrived_at >= $1 AND q.consultation_started_at IS NOT NULL
+           AND ($2::int[] IS NULL OR q.facility_id = ANY($2))
+           AND p.tenant_id = $3",
+    )
+    .bind(since)
+    .bind(facility_ids)
+    .bind(tenant_id)
+    .fetch_one(executor)
+    .await
+}
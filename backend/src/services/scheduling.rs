@@ -0,0 +1,225 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use sqlx::{PgExecutor, Postgres, Transaction};
+
+use crate::models::appointment::{Appointment, ProviderAvailability, Slot};
+
+/// Length of an appointment booked outside any availability session.
+pub const DEFAULT_APPOINTMENT_MINUTES: i64 = 15;
+
+/// Sessions from `templates` that apply on `date`.
+pub fn sessions_on(templates: &[ProviderAvailability], date: NaiveDate) -> Vec<&ProviderAvailability> {
+    let weekday = date.weekday().number_from_monday() as i16;
+    templates
+        .iter()
+        .filter(|t| {
+            t.weekday == weekday
+                && t.valid_from.is_none_or(|from| from <= date)
+                && t.valid_until.is_none_or(|until| date <= until)
+        })
+        .collect()
+}
+
+/// The session an appointment from `starts_at` to `ends_at` falls in, if any; a session at
+/// `facility_id` wins when one is given.
+pub fn session_for(
+    templates: &[ProviderAvailability],
+    starts_at: NaiveDateTime,
+    ends_at: NaiveDateTime,
+    facility_id: Option<i32>,
+) -> Option<&ProviderAvailability> {
+    if ends_at.date() != starts_at.date() {
+        return None;
+    }
+    sessions_on(templates, starts_at.date()).into_iter().find(|s| {
+        s.start_time <= starts_at.time()
+            && ends_at.time() <= s.end_time
+            && facility_id.is_none_or(|f| s.facility_id == f)
+    })
+}
+
+/// Slots in the sessions on `date` not overlapped by any of `appointments`, skipping those
+/// that start before `not_before`.
+pub fn free_slots(
+    templates: &[ProviderAvailability],
+    appointments: &[Appointment],
+    date: NaiveDate,
+    not_before: Option<NaiveDateTime>,
+) -> Vec<Slot> {
+    let mut slots = Vec::new();
+    for session in sessions_on(templates, date) {
+        let step = Duration::minutes(i64::from(session.slot_minutes));
+        let session_end = date.and_time(session.end_time);
+        let mut starts_at = date.and_time(session.start_time);
+        while starts_at + step <= session_end {
+            let ends_at = starts_at + step;
+            let taken = appointments.iter().any(|a| a.starts_at < ends_at && starts_at < a.ends_at);
+            if !taken && not_before.is_none_or(|t| starts_at >= t) {
+                slots.push(Slot {
+                    provider_id: session.provider_id,
+                    facility_id: session.facility_id,
+                    starts_at,
+                    ends_at,
+                });
+            }
+            starts_at = ends_at;
+        }
+    }
+    slots.sort_by_key(|s| s.starts_at);
+    slots
+}
+
+/// Recompute `patients.next_visit` from the patient's appointments: the date of the earliest
+/// one still booked, which is in the past while it waits to be checked in or marked missed.
+pub async fn sync_next_visit<'e, E: PgExecutor<'e>>(
+    executor: E,
+    patient_id: i32,
+) -> Result<Option<NaiveDate>, sqlx::Error> {
+    sqlx::query_scalar(
+        "UPDATE patients SET next_visit = (
+             SELECT MIN(starts_at)::date FROM appointments WHERE patient_id = $1 AND status = 'booked'
+         )
+         WHERE id = $1
+         RETURNING next_visit",
+    )
+    .bind(patient_id)
+    .fetch_optional(executor)
+    .await
+    .map(Option::flatten)
+}
+
+/// Book an unassigned follow-up on `date` unless the patient already has an appointment booked
+/// that day, for clients that still set `next_visit` on the patient; returns the re-derived
+/// next visit. Follow-ups booked from a bare date start with the morning clinic at 09:00.
+pub async fn book_follow_up(
+    tx: &mut Transaction<'_, Postgres>,
+    patient_id: i32,
+    date: NaiveDate,
+) -> Result<Option<NaiveDate>, sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO appointments (patient_id, appointment_type, starts_at, ends_at, reason)
+         SELECT $1, 'follow_up', $2 + TIME '09:00', $2 + TIME '09:00' + make_interval(mins => $3), 'Follow-up visit'
+         WHERE NOT EXISTS (
+             SELECT 1 FROM appointments
+             WHERE patient_id = $1 AND status = 'booked' AND starts_at >= $2 AND starts_at < $2 + 1
+         )",
+    )
+    .bind(patient_id)
+    .bind(date)
+    .bind(DEFAULT_APPOINTMENT_MINUTES as i32)
+    .execute(&mut **tx)
+    .await?;
+    sync_next_visit(&mut **tx, patient_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+
+    /// Monday 2 March 2026.
+    fn monday() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 2).unwrap()
+    }
+
+    fn at(date: NaiveDate, time: &str) -> NaiveDateTime {
+        date.and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+    }
+
+    fn session(
+        id: i32,
+        facility_id: i32,
+        weekday: i16,
+        hours: (&str, &str),
+        slot_minutes: i32,
+    ) -> ProviderAvailability {
+        let time = |t| NaiveTime::parse_from_str(t, "%H:%M").unwrap();
+        ProviderAvailability {
+            id,
+            provider_id: 7,
+            facility_id,
+            weekday,
+            start_time: time(hours.0),
+            end_time: time(hours.1),
+            slot_minutes,
+            valid_from: None,
+            valid_until: None,
+            created_by: None,
+            created_at: at(monday(), "00:00"),
+        }
+    }
+
+    fn appointment(starts_at: NaiveDateTime, minutes: i64) -> Appointment {
+        Appointment {
+            id: 1,
+            patient_id: 1,
+            provider_id: Some(7),
+            facility_id: Some(1),
+            appointment_type: "follow_up".to_string(),
+            starts_at,
+            ends_at: starts_at + Duration::minutes(minutes),
+            status: "booked".to_string(),
+            reason: None,
+            notes: None,
+            booked_by: None,
+            booked_at: starts_at,
+            checked_in_at: None,
+            seen_at: None,
+            cancelled_reason: None,
+            reschedule_count: 0,
+            updated_at: starts_at,
+        }
+    }
+
+    fn ids(sessions: Vec<&ProviderAvailability>) -> Vec<i32> {
+        sessions.into_iter().map(|s| s.id).collect()
+    }
+
+    #[test]
+    fn sessions_apply_on_their_weekday_within_their_validity() {
+        let next_week = monday() + Duration::days(7);
+        let templates = [
+            session(1, 1, 1, ("09:00", "12:00"), 15),
+            ProviderAvailability { valid_from: Some(next_week), ..session(2, 1, 1, ("13:00", "16:00"), 15) },
+            ProviderAvailability { valid_until: Some(monday()), ..session(3, 2, 1, ("17:00", "18:00"), 15) },
+            session(4, 1, 2, ("09:00", "12:00"), 15),
+        ];
+        assert_eq!(ids(sessions_on(&templates, monday())), [1, 3]);
+        assert_eq!(ids(sessions_on(&templates, monday() + Duration::days(1))), [4]);
+        assert_eq!(ids(sessions_on(&templates, next_week)), [1, 2]);
+    }
+
+    #[test]
+    fn appointments_fall_in_the_session_that_contains_them() {
+        let templates = [session(1, 1, 1, ("09:00", "12:00"), 15), session(2, 2, 1, ("09:00", "17:00"), 15)];
+        let found = |starts_at, ends_at, facility_id| {
+            session_for(&templates, starts_at, ends_at, facility_id).map(|s| s.id)
+        };
+        let day = monday();
+        assert_eq!(found(at(day, "09:00"), at(day, "12:00"), None), Some(1));
+        assert_eq!(found(at(day, "11:50"), at(day, "12:05"), None), Some(2));
+        assert_eq!(found(at(day, "10:00"), at(day, "10:15"), Some(2)), Some(2));
+        assert_eq!(found(at(day, "12:00"), at(day, "12:15"), Some(1)), None);
+        assert_eq!(found(at(day, "08:45"), at(day, "09:00"), None), None);
+        assert_eq!(found(at(day, "16:50"), at(day + Duration::days(1), "09:05"), None), None);
+    }
+
+    #[test]
+    fn free_slots_skip_booked_and_past_times() {
+        let templates = [session(1, 1, 1, ("10:00", "10:50"), 20), session(2, 2, 1, ("09:00", "10:00"), 30)];
+        let day = monday();
+        let starts = |slots: Vec<Slot>| slots.into_iter().map(|s| (s.facility_id, s.starts_at)).collect::<Vec<_>>();
+        // the last 10 minutes of the first session do not fit a slot
+        assert_eq!(
+            starts(free_slots(&templates, &[], day, None)),
+            [(2, at(day, "09:00")), (2, at(day, "09:30")), (1, at(day, "10:00")), (1, at(day, "10:20"))]
+        );
+        let booked = [appointment(at(day, "09:10"), 10), appointment(at(day, "10:20"), 5)];
+        assert_eq!(
+            starts(free_slots(&templates, &booked, day, None)),
+            [(2, at(day, "09:30")), (1, at(day, "10:00"))]
+        );
+        let later = free_slots(&templates, &[], day, Some(at(day, "09:45")));
+        assert_eq!(starts(later), [(1, at(day, "10:00")), (1, at(day, "10:20"))]);
+        assert!(free_slots(&templates, &[], day + Duration::days(1), None).is_empty());
+    }
+}
@@ -0,0 +1,14 @@
+use sqlx::{PgPool, Postgres, Transaction};
+
+/// Begin a transaction confined to `tenant_id`. Besides the `tenant_id` filters in the queries
+/// themselves, the transaction runs as `tenant_scoped`, whose row-level security policies hide
+/// other tenants' users, patients, records and consents.
+pub async fn begin(pool: &PgPool, tenant_id: i32) -> Result<Transaction<'static, Postgres>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("SELECT set_config('app.tenant_id', $1::text, true)")
+        .bind(tenant_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("SET LOCAL ROLE tenant_scoped").execute(&mut *tx).await?;
+    Ok(tx)
+}
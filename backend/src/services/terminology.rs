@@ -0,0 +1,358 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::{PgExecutor, PgPool};
+
+use crate::models::terminology::{MatchKind, TerminologyCode};
+use crate::services::validation::{like_literal, normalize_term};
+
+/// Extension of the code table files read from the terminology directory.
+const FILE_EXTENSION: &str = "tsv";
+/// Shorter words of an unmatched condition are not searched on their own.
+const MIN_SUGGESTION_WORD: usize = 4;
+
+/// Local directory of code table files, one code system per file.
+///
+/// Each file is tab-separated: `code`, `display` and optionally synonyms separated by `|`.
+/// Lines starting with `#` are comments, except `# system:`, `# name:` and `# version:`
+/// headers describing the system; the system code defaults to the file name.
+#[derive(Clone)]
+pub struct TerminologyFiles {
+    dir: PathBuf,
+}
+
+#[derive(Debug)]
+pub enum TerminologyError {
+    Io(PathBuf, std::io::Error),
+    Parse { file: String, line: usize, message: String },
+    Db(sqlx::Error),
+}
+
+impl fmt::Display for TerminologyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TerminologyError::Io(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            TerminologyError::Parse { file, line, message } => write!(f, "{} line {}: {}", file, line, message),
+            TerminologyError::Db(e) => write!(f, "database error: {}", e),
+        }
+    }
+}
+
+impl From<sqlx::Error> for TerminologyError {
+    fn from(e: sqlx::Error) -> Self {
+        TerminologyError::Db(e)
+    }
+}
+
+/// What happened to one file during a load.
+#[derive(Serialize, Debug)]
+pub struct LoadSummary {
+    pub system: String,
+    pub source_file: String,
+    /// `loaded`, or `unchanged` when the file matches the last load.
+    pub status: &'static str,
+    pub code_count: usize,
+    /// Codes from the previous load that are no longer in the file.
+    pub deactivated: u64,
+}
+
+struct ParsedCode {
+    code: String,
+    display: String,
+    synonyms: Vec<String>,
+}
+
+struct ParsedSystem {
+    code: String,
+    name: String,
+    version: Option<String>,
+    codes: Vec<ParsedCode>,
+}
+
+fn parse_file(file: &str, stem: &str, contents: &str) -> Result<ParsedSystem, TerminologyError> {
+    let mut system = ParsedSystem { code: stem.to_lowercase(), name: String::new(), version: None, codes: Vec::new() };
+    let mut seen = HashSet::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            if let Some((key, value)) = comment.split_once(':') {
+                let value = value.trim();
+                match key.trim().to_lowercase().as_str() {
+                    "system" if !value.is_empty() => system.code = value.to_lowercase(),
+                    "name" => system.name = value.to_string(),
+                    "version" if !value.is_empty() => system.version = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+            continue;
+        }
+        let error = |message: &str| TerminologyError::Parse {
+            file: file.to_string(),
+            line: index + 1,
+            message: message.to_string(),
+        };
+        let mut columns = line.split('\t');
+        let code = columns.next().unwrap_or_default().trim();
+        let display = columns.next().unwrap_or_default().trim();
+        if code.is_empty() || display.is_empty() {
+            return Err(error("expected a code and a display separated by a tab"));
+        }
+        if !seen.insert(code.to_string()) {
+            return Err(error("duplicate code"));
+        }
+        let mut synonyms: Vec<String> = Vec::new();
+        for synonym in columns.next().unwrap_or_default().split('|').map(normalize_term) {
+            if !synonym.is_empty() && !synonyms.contains(&synonym) {
+                synonyms.push(synonym);
+            }
+        }
+        system.codes.push(ParsedCode { code: code.to_string(), display: display.to_string(), synonyms });
+    }
+    if system.name.is_empty() {
+        system.name = system.code.to_uppercase();
+    }
+    Ok(system)
+}
+
+impl TerminologyFiles {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        TerminologyFiles { dir: dir.into() }
+    }
+
+    /// Load every code table file whose contents changed since it was last loaded.
+    /// A missing directory loads nothing.
+    pub async fn load_all(&self, pool: &PgPool) -> Result<Vec<LoadSummary>, TerminologyError> {
+        let io_error = |e| TerminologyError::Io(self.dir.clone(), e);
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                tracing::warn!("Terminology directory {} does not exist; no code tables loaded", self.dir.display());
+                return Ok(Vec::new());
+            }
+            Err(e) => return Err(io_error(e)),
+        };
+        let mut paths = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(io_error)? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == FILE_EXTENSION) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut summaries = Vec::new();
+        for path in paths {
+            summaries.push(load_file(pool, &path).await?);
+        }
+        Ok(summaries)
+    }
+}
+
+async fn load_file(pool: &PgPool, path: &Path) -> Result<LoadSummary, TerminologyError> {
+    let bytes = tokio::fs::read(path).await.map_err(|e| TerminologyError::Io(path.to_path_buf(), e))?;
+    let file = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let checksum = hex::encode(Sha256::digest(&bytes));
+    let parsed = parse_file(&file, &stem, &String::from_utf8_lossy(&bytes))?;
+
+    let loaded: Option<(String, i32)> =
+        sqlx::query_as("SELECT checksum, code_count FROM terminology_systems WHERE code = $1")
+            .bind(&parsed.code)
+            .fetch_optional(pool)
+            .await?;
+    if let Some((_, code_count)) = loaded.filter(|(previous, _)| *previous == checksum) {
+        return Ok(LoadSummary {
+            system: parsed.code,
+            source_file: file,
+            status: "unchanged",
+            code_count: code_count as usize,
+            deactivated: 0,
+        });
+    }
+
+    let codes: Vec<&str> = parsed.codes.iter().map(|c| c.code.as_str()).collect();
+    let displays: Vec<&str> = parsed.codes.iter().map(|c| c.display.as_str()).collect();
+    let synonyms: Vec<String> = parsed.codes.iter().map(|c| c.synonyms.join("|")).collect();
+    let search_texts: Vec<String> = parsed
+        .codes
+        .iter()
+        .map(|c| {
+            let mut terms = vec![normalize_term(&c.code), normalize_term(&c.display)];
+            terms.extend(c.synonyms.iter().cloned());
+            terms.join(" ")
+        })
+        .collect();
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "INSERT INTO terminology_systems (code, name, version, source_file, checksum, code_count)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         ON CONFLICT (code) DO UPDATE SET name = $2, version = $3, source_file = $4, checksum = $5,
+             code_count = $6, loaded_at = now()",
+    )
+    .bind(&parsed.code)
+    .bind(&parsed.name)
+    .bind(&parsed.version)
+    .bind(&file)
+    .bind(&checksum)
+    .bind(parsed.codes.len() as i32)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        "INSERT INTO terminology_codes (system, code, display, synonyms, search_text, active)
+         SELECT $1, code, display, string_to_array(synonyms, '|'), search_text, TRUE
+         FROM UNNEST($2::text[], $3::text[], $4::text[], $5::text[]) AS t(code, display, synonyms, search_text)
+         ON CONFLICT (system, code) DO UPDATE SET display = EXCLUDED.display, synonyms = EXCLUDED.synonyms,
+             search_text = EXCLUDED.search_text, active = TRUE",
+    )
+    .bind(&parsed.code)
+    .bind(&codes)
+    .bind(&displays)
+    .bind(&synonyms)
+    .bind(&search_texts)
+    .execute(&mut *tx)
+    .await?;
+    let deactivated = sqlx::query(
+        "UPDATE terminology_codes SET active = FALSE WHERE system = $1 AND active AND NOT (code = ANY($2))",
+    )
+    .bind(&parsed.code)
+    .bind(&codes)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    tx.commit().await?;
+
+    tracing::info!("Loaded {} {} codes from {}", parsed.codes.len(), parsed.name, file);
+    Ok(LoadSummary { system: parsed.code, source_file: file, status: "loaded", code_count: parsed.codes.len(), deactivated })
+}
+
+/// Active codes matching `query`, best first: exact code, code prefix (dots optional), display
+/// or synonym prefix, then codes whose text contains every word of the query.
+pub async fn search_codes<'e, E: PgExecutor<'e>>(
+    executor: E,
+    query: &str,
+    system: Option<&str>,
+    limit: i64,
+) -> Result<Vec<TerminologyCode>, sqlx::Error> {
+    let query = normalize_term(query);
+    let compact_code = query.replace(['.', ' '], "");
+    let words: Vec<String> = query.split(' ').map(|word| format!("%{}%", like_literal(word))).collect();
+    sqlx::query_as::<_, TerminologyCode>(
+        "SELECT system, code, display, synonyms, active FROM terminology_codes
+         WHERE active AND ($1::text IS NULL OR system = $1)
+           AND (replace(lower(code), '.', '') LIKE $3 OR search_text LIKE ALL ($4))
+         ORDER BY
+           CASE WHEN replace(lower(code), '.', '') = $2 THEN 0
+                WHEN replace(lower(code), '.', '') LIKE $3 THEN 1
+                WHEN lower(display) LIKE $5 OR EXISTS (SELECT 1 FROM unnest(synonyms) s WHERE s LIKE $5) THEN 2
+                ELSE 3 END,
+           length(display), code
+         LIMIT $6",
+    )
+    .bind(system)
+    .bind(&compact_code)
+    .bind(format!("{}%", like_literal(&compact_code)))
+    .bind(&words)
+    .bind(format!("{}%", like_literal(&query)))
+    .bind(limit)
+    .fetch_all(executor)
+    .await
+}
+
+/// Find the code for a free-text condition in `system`.
+///
+/// A reviewer's earlier mapping of the same text wins, then a single code whose code, display
+/// or synonym is exactly the text. Otherwise the best search hit for the whole text, or failing
+/// that for one of its words, is returned as a suggestion.
+pub async fn match_condition(
+    pool: &PgPool,
+    system: &str,
+    text: &str,
+) -> Result<(MatchKind, Option<TerminologyCode>), sqlx::Error> {
+    let text = normalize_term(text);
+    let learned = sqlx::query_as::<_, TerminologyCode>(
+        "SELECT c.system, c.code, c.display, c.synonyms, c.active
+         FROM condition_mappings m
+         JOIN terminology_codes c ON c.system = m.system AND c.code = m.code
+         WHERE m.normalized_text = $1 AND m.system = $2 AND m.status = 'mapped' AND m.match_kind = 'reviewed'
+           AND c.active
+         ORDER BY m.reviewed_at DESC
+         LIMIT 1",
+    )
+    .bind(&text)
+    .bind(system)
+    .fetch_optional(pool)
+    .await?;
+    if let Some(code) = learned {
+        return Ok((MatchKind::Learned, Some(code)));
+    }
+
+    let mut exact = sqlx::query_as::<_, TerminologyCode>(
+        "SELECT system, code, display, synonyms, active FROM terminology_codes
+         WHERE active AND system = $1 AND (lower(code) = $2 OR lower(display) = $2 OR $2 = ANY(synonyms))
+         LIMIT 2",
+    )
+    .bind(system)
+    .bind(&text)
+    .fetch_all(pool)
+    .await?;
+    if exact.len() == 1 {
+        return Ok((MatchKind::Exact, exact.pop()));
+    }
+
+    let mut suggestion = search_codes(pool, &text, Some(system), 1).await?.pop();
+    // "sugar problem" has no code containing both words; suggest on the first word that hits
+    let mut words = text.split(' ').filter(|word| word.len() >= MIN_SUGGESTION_WORD);
+    while suggestion.is_none() {
+        let Some(word) = words.next() else { break };
+        suggestion = search_codes(pool, word, Some(system), 1).await?.pop();
+    }
+    let kind = if suggestion.is_some() { MatchKind::Suggested } else { MatchKind::None };
+    Ok((kind, suggestion))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers_describe_the_system_and_rows_list_codes() {
+        let contents = "# system: ICD10\r\n# name: ICD-10\n# version: 2019\n# Columns: code, display\n\n\
+                        E11.9\tType 2 diabetes\tT2DM | dm2|t2dm||\r\nI10\t Hypertension \n";
+        let system = parse_file("codes.tsv", "codes", contents).unwrap();
+        assert_eq!((system.code.as_str(), system.name.as_str()), ("icd10", "ICD-10"));
+        assert_eq!(system.version.as_deref(), Some("2019"));
+        let codes: Vec<_> =
+            system.codes.iter().map(|c| (c.code.as_str(), c.display.as_str(), c.synonyms.clone())).collect();
+        assert_eq!(
+            codes,
+            [
+                ("E11.9", "Type 2 diabetes", vec!["t2dm".to_string(), "dm2".to_string()]),
+                ("I10", "Hypertension", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn files_without_headers_are_named_after_the_file() {
+        let system = parse_file("Local.tsv", "Local", "# system:\nX1\tThing\n").unwrap();
+        assert_eq!((system.code.as_str(), system.name.as_str(), system.version), ("local", "LOCAL", None));
+    }
+
+    #[test]
+    fn bad_rows_are_reported_with_their_line() {
+        let error = |contents: &str| match parse_file("codes.tsv", "codes", contents) {
+            Err(TerminologyError::Parse { line, message, .. }) => (line, message),
+            other => panic!("expected a parse error, got {:?}", other.map(|s| s.code)),
+        };
+        assert_eq!(error("# name: X\nA1\tFirst\nA2\n").0, 3);
+        assert_eq!(error("A1\t \n").0, 1);
+        assert_eq!(error("A1\tFirst\n\nA1\tAgain\n"), (3, "duplicate code".to_string()));
+    }
+}
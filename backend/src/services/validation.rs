@@ -0,0 +1,262 @@
+use chrono::{Months, NaiveDate, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::sync::OnceLock;
+
+use crate::models::error::ServiceError;
+use crate::models::patient::Address;
+
+/// Oldest plausible age for a date of birth.
+const MAX_AGE_YEARS: u32 = 130;
+
+#[derive(Debug, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Collects every problem with a payload so the client sees them all at once.
+#[derive(Default)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.0.push(FieldError { field: field.into(), message: message.into() });
+    }
+
+    pub fn into_result(self) -> Result<(), ServiceError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ServiceError::Validation(self.0))
+        }
+    }
+}
+
+/// A 422 for a single offending field.
+pub fn field_error(field: impl Into<String>, message: impl Into<String>) -> ServiceError {
+    ServiceError::Validation(vec![FieldError { field: field.into(), message: message.into() }])
+}
+
+/// A closed set of values stored in a text column.
+pub trait TextEnum: Sized + Copy + 'static {
+    const ALL: &'static [Self];
+
+    fn as_str(self) -> &'static str;
+
+    /// Extra spellings accepted on input, in lowercase.
+    fn aliases(self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn parse(raw: &str) -> Option<Self> {
+        let needle: String = raw.split_whitespace().collect::<String>().to_lowercase();
+        Self::ALL.iter().copied().find(|value| {
+            value.as_str().eq_ignore_ascii_case(&needle) || value.aliases().contains(&needle.as_str())
+        })
+    }
+
+    fn allowed() -> String {
+        Self::ALL.iter().map(|v| v.as_str()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Replace `value` with its canonical spelling, or record an error.
+pub fn normalize_enum<T: TextEnum>(errors: &mut ValidationErrors, field: &str, value: &mut String) {
+    match T::parse(value) {
+        Some(parsed) => *value = parsed.as_str().to_string(),
+        None => errors.add(field, format!("must be one of: {}", T::allowed())),
+    }
+}
+
+pub fn normalize_optional_enum<T: TextEnum>(errors: &mut ValidationErrors, field: &str, value: &mut Option<String>) {
+    if let Some(inner) = value.as_mut() {
+        normalize_enum::<T>(errors, field, inner);
+    }
+}
+
+/// Trim an optional string, treating blank input as absent.
+pub fn trim_optional(value: &mut Option<String>) {
+    if let Some(inner) = value.take() {
+        let trimmed = inner.trim();
+        if !trimmed.is_empty() {
+            *value = Some(trimmed.to_string());
+        }
+    }
+}
+
+pub fn require_text(errors: &mut ValidationErrors, field: &str, value: &mut String) {
+    *value = value.trim().to_string();
+    if value.is_empty() {
+        errors.add(field, "must not be empty");
+    }
+}
+
+pub fn require_optional_text(errors: &mut ValidationErrors, field: &str, value: &mut Option<String>) {
+    if let Some(inner) = value.as_mut() {
+        require_text(errors, field, inner);
+    }
+}
+
+pub fn check_email(errors: &mut ValidationErrors, field: &str, value: &mut Option<String>) {
+    if let Some(email) = value.as_mut() {
+        *email = email.trim().to_string();
+        let valid = match email.split_once('@') {
+            Some((local, domain)) => !local.is_empty() && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.'),
+            None => false,
+        };
+        if !valid {
+            errors.add(field, "must be a valid email address");
+        }
+    }
+}
+
+fn default_country_code() -> Option<&'static str> {
+    static CODE: OnceLock<Option<String>> = OnceLock::new();
+    CODE.get_or_init(|| {
+        std::env::var("DEFAULT_PHONE_COUNTRY_CODE")
+            .ok()
+            .map(|c| c.trim().trim_start_matches('+').to_string())
+            .filter(|c| !c.is_empty())
+    })
+    .as_deref()
+}
+
+/// Normalise a phone number to E.164 (`+` followed by 8 to 15 digits).
+///
+/// Numbers without an international prefix are accepted when
+/// `DEFAULT_PHONE_COUNTRY_CODE` is set; a leading trunk `0` is dropped.
+pub fn normalize_phone(raw: &str) -> Result<String, &'static str> {
+    let trimmed = raw.trim();
+    if trimmed.chars().any(|c| !(c.is_ascii_digit() || " -().+".contains(c)))
+        || trimmed.rfind('+').is_some_and(|i| i > 0)
+    {
+        return Err("may only contain digits, spaces, dashes, dots, parentheses and a leading +");
+    }
+    let digits: String = trimmed.chars().filter(|c| c.is_ascii_digit()).collect();
+    let international = if trimmed.starts_with('+') {
+        digits
+    } else if let Some(rest) = digits.strip_prefix("00") {
+        rest.to_string()
+    } else {
+        match default_country_code() {
+            Some(code) => format!("{}{}", code, digits.trim_start_matches('0')),
+            None => return Err("must include the country code, e.g. +234 800 000 0000"),
+        }
+    };
+    if !(8..=15).contains(&international.len()) || international.starts_with('0') {
+        return Err("must be a valid international number (E.164)");
+    }
+    Ok(format!("+{}", international))
+}
+
+pub fn check_phone(errors: &mut ValidationErrors, field: &str, value: &mut String) {
+    match normalize_phone(value) {
+        Ok(normalized) => *value = normalized,
+        Err(message) => errors.add(field, message),
+    }
+}
+
+pub fn check_date_of_birth(errors: &mut ValidationErrors, field: &str, date: NaiveDate) {
+    let today = Utc::now().date_naive();
+    if date > today {
+        errors.add(field, "must not be in the future");
+    } else if today.checked_sub_months(Months::new(MAX_AGE_YEARS * 12)).is_some_and(|oldest| date < oldest) {
+        errors.add(field, format!("must be within the last {} years", MAX_AGE_YEARS));
+    }
+}
+
+pub fn check_not_future(errors: &mut ValidationErrors, field: &str, date: NaiveDate) {
+    if date > Utc::now().date_naive() {
+        errors.add(field, "must not be in the future");
+    }
+}
+
+pub fn check_not_past(errors: &mut ValidationErrors, field: &str, date: NaiveDate) {
+    if date < Utc::now().date_naive() {
+        errors.add(field, "must not be in the past");
+    }
+}
+
+/// Parse a JSON object against a typed schema, then re-serialize it so only known fields are stored.
+pub fn check_schema<T: DeserializeOwned + Serialize>(
+    errors: &mut ValidationErrors,
+    field: &str,
+    value: &mut Option<Value>,
+) -> Option<T> {
+    let raw = value.take()?;
+    match serde_json::from_value::<T>(raw) {
+        Ok(parsed) => {
+            *value = serde_json::to_value(&parsed).ok();
+            Some(parsed)
+        }
+        Err(e) => {
+            errors.add(field, e.to_string());
+            None
+        }
+    }
+}
+
+/// Validate `address` against the [`Address`] schema.
+pub fn check_address(errors: &mut ValidationErrors, value: &mut Option<Value>) {
+    if let Some(address) = check_schema::<Address>(errors, "address", value) {
+        let fields = [&address.street, &address.city, &address.state, &address.postal_code, &address.country];
+        if fields.iter().all(|f| f.as_deref().is_none_or(|v| v.trim().is_empty())) {
+            errors.add("address", "must contain at least one non-empty field");
+        }
+    }
+}
+
+/// Escape `%`, `_` and `\` so user input matches literally inside a LIKE/ILIKE pattern.
+pub fn like_literal(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// Lowercase with single spaces, the form drug, allergy and condition names are compared in.
+pub fn normalize_term(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// `name` as a code: runs of anything but letters and digits become one `-`.
+pub fn code_from(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // numbers without an international prefix depend on DEFAULT_PHONE_COUNTRY_CODE and are
+    // left out here
+
+    #[test]
+    fn international_numbers_are_normalized_to_e164() {
+        assert_eq!(normalize_phone("+234 800 000 0000"), Ok("+2348000000000".to_string()));
+        assert_eq!(normalize_phone("  +1 (415) 555-0100 "), Ok("+14155550100".to_string()));
+        assert_eq!(normalize_phone("+44.20.7946.0958"), Ok("+442079460958".to_string()));
+        assert_eq!(normalize_phone("0044 20 7946 0958"), Ok("+442079460958".to_string()));
+    }
+
+    #[test]
+    fn length_limits_are_inclusive() {
+        assert!(normalize_phone("+1234567").is_err());
+        assert_eq!(normalize_phone("+12345678"), Ok("+12345678".to_string()));
+        assert_eq!(normalize_phone("+123456789012345"), Ok("+123456789012345".to_string()));
+        assert!(normalize_phone("+1234567890123456").is_err());
+    }
+
+    #[test]
+    fn invalid_numbers_are_rejected() {
+        assert!(normalize_phone("+234 800 CALL NOW").is_err());
+        assert!(normalize_phone("+234/800/000/0000").is_err());
+        assert!(normalize_phone("+").is_err());
+        assert!(normalize_phone("12+34 5678 9012").is_err());
+        assert!(normalize_phone("++234 800 000 0000").is_err());
+        // country codes never start with 0
+        assert!(normalize_phone("+0123456789").is_err());
+        assert!(normalize_phone("000123456789").is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use serde_json::Value;
+use sqlx::PgExecutor;
+
+use crate::models::record::MedicalRecord;
+use crate::services::audit::diff_fields;
+
+/// Bookkeeping columns that change on every write and say nothing about clinical content.
+pub const UNVERSIONED_FIELDS: &[&str] = &["created_at", "updated_at"];
+
+/// How a record reached a version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionKind {
+    Created,
+    Edited,
+    Amended,
+    Submitted,
+    Reviewed,
+    Signed,
+    Rejected,
+}
+
+impl VersionKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VersionKind::Created => "created",
+            VersionKind::Edited => "edited",
+            VersionKind::Amended => "amended",
+            VersionKind::Submitted => "submitted",
+            VersionKind::Reviewed => "reviewed",
+            VersionKind::Signed => "signed",
+            VersionKind::Rejected => "rejected",
+        }
+    }
+}
+
+pub struct VersionEntry<'a> {
+    pub previous: Option<&'a MedicalRecord>,
+    pub record: &'a MedicalRecord,
+    pub kind: VersionKind,
+    pub reason: Option<&'a str>,
+    pub changed_by: Option<i32>,
+}
+
+/// Append the next version of a record. Edits that leave the content unchanged are not
+/// recorded; returns whether a version was written.
+///
+/// Callers must hold a lock on the record row so version numbers are assigned in order.
+pub async fn record_version<'e, E: PgExecutor<'e>>(executor: E, entry: VersionEntry<'_>) -> Result<bool, sqlx::Error> {
+    let snapshot = serde_json::to_value(entry.record).unwrap_or(Value::Null);
+    let changes = match entry.previous {
+        Some(previous) => {
+            let before = serde_json::to_value(previous).unwrap_or(Value::Null);
+            let changes = diff_fields(&before, &snapshot, UNVERSIONED_FIELDS);
+            if changes.is_empty() {
+                return Ok(false);
+            }
+            changes
+        }
+        None => Default::default(),
+    };
+    sqlx::query(
+        r#"INSERT INTO record_versions (record_id, version, kind, reason, changes, snapshot, changed_by, created_at)
+           SELECT $1, COALESCE(MAX(version), 0) + 1, $2, $3, $4, $5, $6, now()
+           FROM record_versions WHERE record_id = $1"#
+    )
+    .bind(entry.record.id)
+    .bind(entry.kind.as_str())
+    .bind(entry.reason)
+    .bind(Value::Object(changes))
+    .bind(snapshot)
+    .bind(entry.changed_by)
+    .execute(executor)
+    .await?;
+    Ok(true)
+}
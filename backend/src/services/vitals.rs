@@ -0,0 +1,199 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::models::vital::{VitalFlag, VitalKind, VitalReferenceRange};
+use crate::services::validation::TextEnum;
+
+/// Age in completed months on the given date, used to pick an age band.
+pub fn age_in_months(date_of_birth: NaiveDate, on: NaiveDate) -> i32 {
+    if on < date_of_birth {
+        return 0;
+    }
+    let mut months = (on.year() - date_of_birth.year()) * 12 + on.month() as i32 - date_of_birth.month() as i32;
+    if on.day() < date_of_birth.day() {
+        months -= 1;
+    }
+    months
+}
+
+/// UCUM code values of a kind are stored in.
+pub fn canonical_unit(kind: VitalKind) -> &'static str {
+    match kind {
+        VitalKind::SystolicBp | VitalKind::DiastolicBp => "mm[Hg]",
+        VitalKind::HeartRate | VitalKind::RespiratoryRate => "/min",
+        VitalKind::Temperature => "Cel",
+        VitalKind::Spo2 => "%",
+        VitalKind::Weight => "kg",
+        VitalKind::Height => "cm",
+        VitalKind::Bmi => "kg/m2",
+        VitalKind::BloodGlucose => "mmol/L",
+    }
+}
+
+/// Convert a value given in `unit` to the kind's canonical unit. Returns `None` for a unit
+/// the kind cannot be expressed in.
+pub fn to_canonical(kind: VitalKind, value: f64, unit: &str) -> Option<f64> {
+    let unit: String = unit.split_whitespace().collect::<String>().to_lowercase();
+    if unit == canonical_unit(kind).to_lowercase() {
+        return Some(value);
+    }
+    let converted = match (kind, unit.as_str()) {
+        (VitalKind::SystolicBp | VitalKind::DiastolicBp, "mmhg") => value,
+        (VitalKind::HeartRate, "bpm" | "beats/min") => value,
+        (VitalKind::RespiratoryRate, "breaths/min" | "bpm") => value,
+        (VitalKind::Temperature, "c" | "°c" | "degc" | "celsius") => value,
+        (VitalKind::Temperature, "f" | "°f" | "degf" | "[degf]" | "fahrenheit") => (value - 32.0) * 5.0 / 9.0,
+        (VitalKind::Weight, "g") => value / 1000.0,
+        (VitalKind::Weight, "lb" | "lbs" | "[lb_av]") => value * 0.453_592_37,
+        (VitalKind::Height, "m") => value * 100.0,
+        (VitalKind::Height, "mm") => value / 10.0,
+        (VitalKind::Height, "in" | "[in_i]") => value * 2.54,
+        (VitalKind::Bmi, "kg/m²") => value,
+        (VitalKind::BloodGlucose, "mmol/l") => value,
+        (VitalKind::BloodGlucose, "mg/dl") => value / 18.016,
+        _ => return None,
+    };
+    Some(round2(converted))
+}
+
+/// Bounds outside which a value is a typing or unit mistake rather than a measurement.
+pub fn plausible_range(kind: VitalKind) -> (f64, f64) {
+    match kind {
+        VitalKind::SystolicBp => (30.0, 300.0),
+        VitalKind::DiastolicBp => (10.0, 200.0),
+        VitalKind::HeartRate => (10.0, 300.0),
+        VitalKind::Temperature => (25.0, 45.0),
+        VitalKind::Spo2 => (30.0, 100.0),
+        VitalKind::RespiratoryRate => (2.0, 120.0),
+        VitalKind::Weight => (0.3, 500.0),
+        VitalKind::Height => (20.0, 272.0),
+        VitalKind::Bmi => (5.0, 150.0),
+        VitalKind::BloodGlucose => (0.5, 60.0),
+    }
+}
+
+pub fn bmi(weight_kg: f64, height_cm: f64) -> f64 {
+    let metres = height_cm / 100.0;
+    round2(weight_kg / (metres * metres))
+}
+
+/// Pick the range for a kind that fits the patient, preferring sex-specific rows and then
+/// the narrowest age band.
+pub fn applicable_range<'a>(
+    ranges: &'a [VitalReferenceRange],
+    kind: VitalKind,
+    sex: &str,
+    age_months: i32,
+) -> Option<&'a VitalReferenceRange> {
+    ranges
+        .iter()
+        .filter(|r| r.kind == kind.as_str())
+        .filter(|r| r.sex.as_deref().is_none_or(|s| s == sex))
+        .filter(|r| r.min_age_months <= age_months && r.max_age_months.is_none_or(|max| age_months < max))
+        .min_by_key(|r| (r.sex.is_none(), r.max_age_months.unwrap_or(i32::MAX) - r.min_age_months))
+}
+
+pub fn flag_value(range: &VitalReferenceRange, value: f64) -> VitalFlag {
+    if range.critical_low.is_some_and(|limit| value < limit) {
+        VitalFlag::CriticalLow
+    } else if range.critical_high.is_some_and(|limit| value > limit) {
+        VitalFlag::CriticalHigh
+    } else if range.low.is_some_and(|limit| value < limit) {
+        VitalFlag::Low
+    } else if range.high.is_some_and(|limit| value > limit) {
+        VitalFlag::High
+    } else {
+        VitalFlag::Normal
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn range(id: i32, sex: Option<&str>, min_age_months: i32, max_age_months: Option<i32>) -> VitalReferenceRange {
+        VitalReferenceRange {
+            id,
+            kind: "heart_rate".to_string(),
+            sex: sex.map(str::to_string),
+            min_age_months,
+            max_age_months,
+            low: Some(60.0),
+            high: Some(100.0),
+            critical_low: Some(40.0),
+            critical_high: Some(130.0),
+        }
+    }
+
+    #[test]
+    fn ages_count_completed_months() {
+        assert_eq!(age_in_months(date(2020, 1, 31), date(2020, 2, 29)), 0);
+        assert_eq!(age_in_months(date(2020, 1, 31), date(2020, 3, 1)), 1);
+        assert_eq!(age_in_months(date(2020, 6, 15), date(2026, 6, 15)), 72);
+        assert_eq!(age_in_months(date(2020, 6, 15), date(2026, 6, 14)), 71);
+        assert_eq!(age_in_months(date(2026, 6, 15), date(2026, 6, 1)), 0);
+    }
+
+    #[test]
+    fn values_are_converted_to_the_canonical_unit() {
+        assert_eq!(to_canonical(VitalKind::Temperature, 98.6, "°F"), Some(37.0));
+        assert_eq!(to_canonical(VitalKind::Temperature, 37.2, " CEL "), Some(37.2));
+        assert_eq!(to_canonical(VitalKind::Weight, 3250.0, "g"), Some(3.25));
+        assert_eq!(to_canonical(VitalKind::Weight, 150.0, "lbs"), Some(68.04));
+        assert_eq!(to_canonical(VitalKind::Height, 1.72, "m"), Some(172.0));
+        assert_eq!(to_canonical(VitalKind::Height, 60.0, "[in_i]"), Some(152.4));
+        assert_eq!(to_canonical(VitalKind::BloodGlucose, 180.0, "mg/dL"), Some(9.99));
+        assert_eq!(to_canonical(VitalKind::SystolicBp, 120.0, "mm Hg"), Some(120.0));
+        assert_eq!(to_canonical(VitalKind::RespiratoryRate, 18.0, "bpm"), Some(18.0));
+    }
+
+    #[test]
+    fn units_of_another_quantity_are_refused() {
+        assert_eq!(to_canonical(VitalKind::Weight, 70.0, "cm"), None);
+        assert_eq!(to_canonical(VitalKind::Spo2, 0.97, "ratio"), None);
+        assert_eq!(to_canonical(VitalKind::Temperature, 310.0, "K"), None);
+    }
+
+    #[test]
+    fn bmi_is_rounded_to_two_places() {
+        assert_eq!(bmi(70.0, 175.0), 22.86);
+    }
+
+    #[test]
+    fn sex_specific_and_narrower_ranges_win() {
+        let ranges = [
+            range(1, None, 0, None),
+            range(2, None, 144, None),
+            range(3, Some("female"), 144, None),
+            range(4, None, 0, Some(12)),
+            VitalReferenceRange { kind: "spo2".to_string(), ..range(5, None, 0, Some(12)) },
+        ];
+        let pick = |sex, age| applicable_range(&ranges, VitalKind::HeartRate, sex, age).map(|r| r.id);
+        assert_eq!(pick("female", 240), Some(3));
+        assert_eq!(pick("male", 240), Some(2));
+        assert_eq!(pick("male", 6), Some(4));
+        // the upper bound is exclusive
+        assert_eq!(pick("male", 12), Some(1));
+        assert_eq!(applicable_range(&ranges[..1], VitalKind::Temperature, "male", 12).map(|r| r.id), None);
+    }
+
+    #[test]
+    fn critical_limits_are_checked_before_normal_ones() {
+        let range = range(1, None, 0, None);
+        assert_eq!(flag_value(&range, 35.0), VitalFlag::CriticalLow);
+        assert_eq!(flag_value(&range, 50.0), VitalFlag::Low);
+        assert_eq!(flag_value(&range, 60.0), VitalFlag::Normal);
+        assert_eq!(flag_value(&range, 100.0), VitalFlag::Normal);
+        assert_eq!(flag_value(&range, 120.0), VitalFlag::High);
+        assert_eq!(flag_value(&range, 131.0), VitalFlag::CriticalHigh);
+        let open = VitalReferenceRange { low: None, critical_low: None, ..range };
+        assert_eq!(flag_value(&open, 0.0), VitalFlag::Normal);
+    }
+}
@@ -89,7 +89,7 @@ export class DashboardComponent implements OnInit, OnDestroy {
     });
 
     this.apiService.getRecords().subscribe({
-      next: (records) => {
+      next: ({ records }) => {
         this.stats.recordsToday = records.length;
         this.recentRecords = records.slice(0, 3);
         this.loading = false;
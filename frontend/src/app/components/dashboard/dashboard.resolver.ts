@@ -1,7 +1,7 @@
 import { Injectable } from '@angular/core';
 import { Resolve } from '@angular/router';
 import { ApiService } from '../../services/api.service';
-import { Observable, forkJoin } from 'rxjs';
+import { Observable, forkJoin, map } from 'rxjs';
 
 @Injectable({ providedIn: 'root' })
 export class DashboardResolver implements Resolve<any> {
@@ -11,7 +11,7 @@ export class DashboardResolver implements Resolve<any> {
     // Fetch both patients and records before activating dashboard
     return forkJoin({
       patients: this.api.getPatients(),
-      records: this.api.getRecords()
+      records: this.api.getRecords().pipe(map(page => page.records))
     });
   }
 }
@@ -51,11 +51,7 @@ export class LoginComponent {
           // Save token to localStorage
           if (response && response.token) {
             localStorage.setItem('token', response.token);
-            console.log('Token saved:', response.token);
-          } else {
-            console.warn('No token found in response:', response);
           }
-          console.log('Token in localStorage:', this.api.getToken());
           this.loginSuccess = true;
           this.errorMessage = null;
           this.router.navigate(['/dashboard']);
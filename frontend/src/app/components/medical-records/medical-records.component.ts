@@ -3,7 +3,7 @@ import { CommonModule } from '@angular/common';
 import { FormsModule } from '@angular/forms';
 import { RouterModule, ActivatedRoute } from '@angular/router';
 import { Sidebar } from '../shared/sidebar/sidebar';
-import { ApiService, MedicalRecord, Patient, AnalyticsResponse, StatsResponse } from '../../services/api.service';
+import { ApiService, MedicalRecord, Patient, AnalyticsResponse, RecordPage, RecordQuery, StatsResponse } from '../../services/api.service';
 
 @Component({
   selector: 'app-medical-records',
@@ -25,14 +25,15 @@ export class MedicalRecordsComponent implements OnInit {
   filteredRecords: MedicalRecord[] = [];
   patients: Patient[] = [];
   loading: boolean = true;
+  loadingMore = false;
   error: string = '';
+  // Cursor of the page after the loaded ones; null once the last page is in
+  nextCursor: string | null = null;
 
-  selectedStatuses: { [key: string]: boolean } = {
-    'completed': true,
-    'pending': true,
-    'active': true,
-    'pending review': true
-  };
+  readonly statuses = ['draft', 'submitted', 'reviewed', 'signed'];
+  selectedStatus = '';
+  sort: 'date' | 'created_at' | 'title' = 'date';
+  order: 'asc' | 'desc' = 'desc';
 
   stats: StatsResponse | null = null;
   statsLoading = true;
@@ -55,7 +56,7 @@ export class MedicalRecordsComponent implements OnInit {
     // Use resolver data for initial load
     this.route.data.subscribe((data: any) => {
       if (data.medicalRecordsData) {
-        this.records = data.medicalRecordsData.records;
+        this.showPage(data.medicalRecordsData.records);
         this.patients = data.medicalRecordsData.patients;
         this.loading = false;
         
@@ -108,12 +109,35 @@ export class MedicalRecordsComponent implements OnInit {
     });
   }
 
+  // Tab, status, date range and sort are applied by the server, a page at a time
+  recordQuery(): RecordQuery {
+    const tabTypeMap: { [key: string]: string } = {
+      'consultations': 'consultation',
+      'lab-results': 'lab',
+      'prescriptions': 'prescription',
+      'imaging': 'imaging'
+    };
+    return {
+      record_type: tabTypeMap[this.activeTab],
+      status: this.selectedStatus,
+      from: this.dateRangeStart,
+      to: this.dateRangeEnd,
+      sort: this.sort,
+      order: this.order
+    };
+  }
+
+  showPage(page: RecordPage, append = false) {
+    this.records = append ? this.records.concat(page.records) : page.records;
+    this.nextCursor = page.next_cursor ?? null;
+  }
+
   loadRecords() {
     this.loading = true;
     this.error = '';
-    this.apiService.getRecords().subscribe({
-      next: (data) => {
-        this.records = data;
+    this.apiService.getRecords(this.recordQuery()).subscribe({
+      next: (page) => {
+        this.showPage(page);
         this.filterRecords();
         this.calculateStats();
         this.loading = false;
@@ -126,6 +150,25 @@ export class MedicalRecordsComponent implements OnInit {
     });
   }
 
+  loadMore() {
+    if (!this.nextCursor || this.loadingMore) {
+      return;
+    }
+    this.loadingMore = true;
+    this.apiService.getRecords({ ...this.recordQuery(), cursor: this.nextCursor }).subscribe({
+      next: (page) => {
+        this.showPage(page, true);
+        this.filterRecords();
+        this.loadingMore = false;
+      },
+      error: (err: any) => {
+        this.error = 'Failed to load more medical records';
+        this.loadingMore = false;
+        console.error('Error loading records:', err);
+      }
+    });
+  }
+
   loadPatients() {
     this.apiService.getPatients().subscribe({
       next: (data) => {
@@ -138,24 +181,10 @@ export class MedicalRecordsComponent implements OnInit {
     });
   }
 
+  // Narrows the loaded records to the search query
   filterRecords() {
     let filtered = [...this.records];
 
-    // Filter by active tab
-    if (this.activeTab !== 'all') {
-      const tabTypeMap: { [key: string]: string } = {
-        'consultations': 'consultation',
-        'lab-results': 'lab',
-        'prescriptions': 'prescription',
-        'imaging': 'imaging'
-      };
-      
-      const recordType = tabTypeMap[this.activeTab];
-      if (recordType) {
-        filtered = filtered.filter(r => r.record_type === recordType);
-      }
-    }
-
     // Filter by search query
     if (this.searchQuery.trim()) {
       const term = this.searchQuery.toLowerCase();
@@ -167,28 +196,12 @@ export class MedicalRecordsComponent implements OnInit {
       );
     }
 
-    // Filter by status
-    const activeStatuses = Object.keys(this.selectedStatuses).filter(s => this.selectedStatuses[s]);
-    if (activeStatuses.length > 0 && activeStatuses.length < Object.keys(this.selectedStatuses).length) {
-      filtered = filtered.filter(r => activeStatuses.includes(r.status.toLowerCase()));
-    }
-
-    // Filter by date range
-    if (this.dateRangeStart) {
-      const startDate = new Date(this.dateRangeStart);
-      filtered = filtered.filter(r => new Date(r.date) >= startDate);
-    }
-    if (this.dateRangeEnd) {
-      const endDate = new Date(this.dateRangeEnd);
-      filtered = filtered.filter(r => new Date(r.date) <= endDate);
-    }
-
     this.filteredRecords = filtered;
   }
 
   switchTab(tab: string): void {
     this.activeTab = tab;
-    this.filterRecords();
+    this.loadRecords();
   }
 
   onSearch(event: any): void {
@@ -200,19 +213,19 @@ export class MedicalRecordsComponent implements OnInit {
     this.showFilters = !this.showFilters;
   }
 
-  toggleStatusFilter(status: string): void {
-    this.selectedStatuses[status] = !this.selectedStatuses[status];
-    this.filterRecords();
+  selectStatus(status: string): void {
+    this.selectedStatus = status;
+    this.loadRecords();
   }
 
   clearFilters(): void {
     this.searchQuery = '';
     this.dateRangeStart = '';
     this.dateRangeEnd = '';
-    Object.keys(this.selectedStatuses).forEach(status => {
-      this.selectedStatuses[status] = true;
-    });
-    this.filterRecords();
+    this.selectedStatus = '';
+    this.sort = 'date';
+    this.order = 'desc';
+    this.loadRecords();
   }
 
   openNewRecordModal() {
@@ -102,7 +102,7 @@ export class ReportsComponent {
 
   loadRecords() {
     this.api.getRecords().subscribe({
-      next: (records) => {
+      next: ({ records }) => {
         this.medicalReports = records;
       },
       error: (err) => {
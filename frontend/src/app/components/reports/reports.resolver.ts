@@ -1,7 +1,7 @@
 import { Injectable } from '@angular/core';
 import { Resolve } from '@angular/router';
 import { ApiService } from '../../services/api.service';
-import { Observable, forkJoin } from 'rxjs';
+import { Observable, forkJoin, map } from 'rxjs';
 
 @Injectable({ providedIn: 'root' })
 export class ReportsResolver implements Resolve<any> {
@@ -11,7 +11,7 @@ export class ReportsResolver implements Resolve<any> {
     // Fetch analytics, records, and patients before activating the reports route
     return forkJoin({
       analytics: this.api.getAnalytics(),
-      records: this.api.getRecords(),
+      records: this.api.getRecords().pipe(map(page => page.records)),
       patients: this.api.getPatients()
     });
   }
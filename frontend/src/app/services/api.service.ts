@@ -1,4 +1,3 @@
-import { HttpHeaders } from '@angular/common/http';
 // Administration API types
 export interface AdministrationStats {
   total_users: number;
@@ -66,6 +65,7 @@ export interface StatsResponse {
   total_patients: number;
   total_records: number;
   consultations_mtd: number;
+  consultations_in_range: number;
   completed: number;
   pending: number;
   lab_results: number;
@@ -75,10 +75,27 @@ export interface StatsResponse {
   data_completeness?: string;
 }
 
+export interface PeriodChange {
+  current: number;
+  previous: number;
+  change: number;
+  change_pct: number | null;
+}
+
+export interface VillageTrend {
+  period: string;
+  new_registrations: number;
+  active_patients: number;
+}
+
 export interface VillageDistribution {
+  village_id: number;
   name: string;
   patients: number;
   growth: string;
+  new_registrations: PeriodChange;
+  active_patients: PeriodChange;
+  trend: VillageTrend[];
 }
 
 export interface ConditionDistribution {
@@ -87,11 +104,26 @@ export interface ConditionDistribution {
 }
 
 export interface DiseaseTrend {
-  month: string;
+  period: string;
+  label: string;
   value: number;
 }
 
+export interface ReportRange {
+  from: string;
+  to: string;
+  granularity: 'day' | 'week' | 'month';
+}
+
+export interface ComparisonRange {
+  compare: 'previous_period' | 'previous_year';
+  from: string;
+  to: string;
+}
+
 export interface AnalyticsResponse {
+  range: ReportRange;
+  comparison: ComparisonRange;
   stats: StatsResponse;
   villages: VillageDistribution[];
   conditions: ConditionDistribution[];
@@ -133,6 +165,7 @@ export interface Patient {
   status: string;
   sync_status?: string;
   critical_flag?: boolean;
+  critical_reason?: string;
   profile_picture_url?: string;
   next_visit?: string;
   created_at: string;
@@ -157,6 +190,24 @@ export interface MedicalRecord {
   updated_at: string;
 }
 
+export interface RecordPage {
+  records: MedicalRecord[];
+  next_cursor?: string | null;
+}
+
+export interface RecordQuery {
+  patient_id?: string;
+  record_type?: string;
+  status?: string;
+  provider?: string;
+  from?: string;
+  to?: string;
+  sort?: 'date' | 'created_at' | 'title';
+  order?: 'asc' | 'desc';
+  cursor?: string | null;
+  limit?: number;
+}
+
 @Injectable({
   providedIn: 'root'
 })
@@ -188,14 +239,19 @@ export class ApiService {
   }
 
   deletePatient(id: string): Observable<void> {
-    const token = this.getToken();
-    const headers = token ? new HttpHeaders({ Authorization: `Bearer ${token}` }) : undefined;
-    return this.http.delete<void>(`${this.apiUrl}/patients/${id}`, { headers });
+    return this.http.delete<void>(`${this.apiUrl}/patients/${id}`);
   }
 
   // Medical Records API
-  getRecords(): Observable<MedicalRecord[]> {
-    return this.http.get<MedicalRecord[]>(`${this.apiUrl}/records`);
+  // One page of records; pass its next_cursor back with the same query for the next page
+  getRecords(query: RecordQuery = {}): Observable<RecordPage> {
+    const params: Record<string, string | number> = {};
+    for (const [key, value] of Object.entries(query)) {
+      if (value !== undefined && value !== null && value !== '') {
+        params[key] = value;
+      }
+    }
+    return this.http.get<RecordPage>(`${this.apiUrl}/records`, { params });
   }
 
   getRecord(id: string): Observable<MedicalRecord> {
@@ -214,7 +270,6 @@ export class ApiService {
       description: record.description || null,
       secondary_status: record.secondary_status || null,
       reviewed_by: record.reviewed_by || null,
-      attachments: record.attachments?.length ? record.attachments : null,
       is_exported: record.is_exported ?? false
     };
     console.log('FINAL Payload:', payload);
@@ -0,0 +1,13 @@
+import { HttpInterceptorFn } from '@angular/common/http';
+
+// Attach the signed-in user's token to every API request
+export const authInterceptor: HttpInterceptorFn = (req, next) => {
+  if (typeof window === 'undefined' || !window.localStorage) {
+    return next(req);
+  }
+  const token = localStorage.getItem('token');
+  if (!token || req.headers.has('Authorization')) {
+    return next(req);
+  }
+  return next(req.clone({ setHeaders: { Authorization: `Bearer ${token}` } }));
+};